@@ -1,7 +1,12 @@
 import { useEffect } from "react"
 import { Routes, Route, Navigate } from "react-router-dom"
+import { Onboarding } from "@/pages/Onboarding"
 import { OpenProject } from "@/pages/OpenProject"
 import { ProjectEditor } from "@/pages/ProjectEditor"
+import { Settings } from "@/pages/Settings"
+import { Toaster } from "@/components/ui/toast"
+import { isOnboardingComplete } from "@/lib/onboarding"
+import { syncThemeWithSettings } from "@/lib/settings"
 import { checkForUpdates } from "@/lib/updater"
 
 function App() {
@@ -12,12 +17,23 @@ function App() {
     }
   }, [])
 
+  // Keep the theme in sync with the persisted settings
+  useEffect(() => syncThemeWithSettings(), [])
+
   return (
-    <Routes>
-      <Route path="/open" element={<OpenProject />} />
-      <Route path="/project/:id" element={<ProjectEditor />} />
-      <Route path="/" element={<Navigate to="/open" replace />} />
-    </Routes>
+    <>
+      <Routes>
+        <Route path="/onboarding" element={<Onboarding />} />
+        <Route path="/open" element={<OpenProject />} />
+        <Route path="/project/:id" element={<ProjectEditor />} />
+        <Route path="/settings" element={<Settings />} />
+        <Route
+          path="/"
+          element={<Navigate to={isOnboardingComplete() ? "/open" : "/onboarding"} replace />}
+        />
+      </Routes>
+      <Toaster />
+    </>
   )
 }
 
@@ -57,9 +57,10 @@ export function BuildAndRun() {
       .catch((err) => console.error("Failed to fetch simulators:", err))
   }, [])
 
-  // Subscribe to simulator logs when streaming starts
+  // Subscribe to the streamed simulator's logs when streaming starts
+  const streamingUdid = buildState.status === "streaming" ? buildState.udid : ""
   useEffect(() => {
-    if (buildState.status !== "streaming") {
+    if (!streamingUdid) {
       return
     }
 
@@ -83,12 +84,12 @@ export function BuildAndRun() {
 
     const unsubscribe = api.simulator.onLog((event) => {
       setStreamLogs((prev) => [...prev.slice(-100), formatLogEvent(event)])
-    })
+    }, streamingUdid)
 
     return () => {
       unsubscribe()
     }
-  }, [buildState.status])
+  }, [streamingUdid])
 
   const handlePathChange = (e: React.ChangeEvent<HTMLInputElement>) => {
     const path = e.target.value
@@ -0,0 +1,80 @@
+import { useEffect, useState } from "react"
+import { ChevronDown, ChevronUp } from "lucide-react"
+import { Label } from "@/components/ui/label"
+import { Textarea } from "@/components/ui/textarea"
+import { api, type ProjectSettings } from "@/lib/api"
+import { showErrorToast } from "@/lib/toast"
+
+type HookKey = "preBuildHook" | "postBuildHook" | "postInstallHook"
+
+const HOOKS: { key: HookKey; label: string }[] = [
+  { key: "preBuildHook", label: "Before build" },
+  { key: "postBuildHook", label: "After a successful build" },
+  { key: "postInstallHook", label: "After install" },
+]
+
+interface BuildHooksProps {
+  projectId: number
+}
+
+/**
+ * Collapsible editor for the shell commands run around a build, one per line.
+ * Empty stages fall back to the [hooks] table in the repository's plasma.toml.
+ */
+export function BuildHooks({ projectId }: BuildHooksProps) {
+  const [isOpen, setIsOpen] = useState(false)
+  const [settings, setSettings] = useState<ProjectSettings>({})
+  const [drafts, setDrafts] = useState<Partial<Record<HookKey, string>>>({})
+
+  useEffect(() => {
+    api.projects
+      .getSettings(projectId)
+      .then((loaded) => {
+        setSettings(loaded)
+        setDrafts({})
+      })
+      .catch((err) => console.error("Failed to load hooks:", err))
+  }, [projectId])
+
+  const configuredCount = HOOKS.filter(({ key }) => settings[key]?.trim()).length
+
+  const save = (key: HookKey) => {
+    const value = drafts[key]
+    if (value === undefined || value === (settings[key] ?? "")) return
+
+    api.projects
+      .updateSettings(projectId, { [key]: value.trim() || null })
+      .then(setSettings)
+      .catch((err) => showErrorToast("Couldn't save build hooks", err))
+  }
+
+  return (
+    <div className="flex flex-col gap-2">
+      <button
+        className="text-xs text-muted-foreground hover:text-foreground flex items-center gap-1 self-start"
+        onClick={() => setIsOpen(!isOpen)}
+      >
+        {isOpen ? <ChevronUp className="w-3 h-3" /> : <ChevronDown className="w-3 h-3" />}
+        Build hooks
+        {configuredCount > 0 && ` (${configuredCount})`}
+      </button>
+      {isOpen && (
+        <div className="flex flex-col gap-3">
+          {HOOKS.map(({ key, label }) => (
+            <div key={key} className="flex flex-col gap-1">
+              <Label htmlFor={key} className="text-xs text-muted-foreground">{label}</Label>
+              <Textarea
+                id={key}
+                placeholder="From plasma.toml"
+                className="font-mono text-xs min-h-12"
+                value={drafts[key] ?? settings[key] ?? ""}
+                onChange={(e) => setDrafts((current) => ({ ...current, [key]: e.target.value }))}
+                onBlur={() => save(key)}
+              />
+            </div>
+          ))}
+        </div>
+      )}
+    </div>
+  )
+}
@@ -0,0 +1,240 @@
+import { useEffect, useMemo, useRef, useState } from "react"
+import { Copy, Check, Search } from "lucide-react"
+import { Button } from "@/components/ui/button"
+import { Input } from "@/components/ui/input"
+import { api, type FormattedBuildLine, type SourceLocation } from "@/lib/api"
+import { showErrorToast } from "@/lib/toast"
+import { cn } from "@/lib/utils"
+import {
+  countByLevel,
+  filterBuildLog,
+  toBuildLogLines,
+  toFormattedLogLines,
+  type BuildLogLevel,
+} from "@/lib/build-log"
+
+interface BuildLogPanelProps {
+  lines: string[]
+  height?: number
+  /** Earlier lines left out of lines */
+  trimmedLines?: number
+  /** Where the full log can be opened */
+  fullLogUrl?: string
+  /** The condensed form of each line, shown unless the raw output is asked for */
+  formattedLines?: (FormattedBuildLine | null)[]
+}
+
+const ROW_HEIGHT = 18
+const OVERSCAN = 20
+
+const LEVELS: { level: BuildLogLevel; label: string; className: string }[] = [
+  { level: "error", label: "Errors", className: "text-red-400" },
+  { level: "warning", label: "Warnings", className: "text-yellow-400" },
+  { level: "info", label: "Output", className: "text-muted-foreground" },
+]
+
+function HighlightedText({ text, query }: { text: string; query: string }) {
+  const trimmed = query.trim()
+  if (!trimmed) return <>{text}</>
+
+  const lower = text.toLowerCase()
+  const needle = trimmed.toLowerCase()
+  const parts: React.ReactNode[] = []
+  let start = 0
+  let match = lower.indexOf(needle)
+
+  while (match !== -1) {
+    parts.push(text.slice(start, match))
+    parts.push(
+      <mark key={match} className="bg-yellow-500/40 text-inherit rounded-sm">
+        {text.slice(match, match + needle.length)}
+      </mark>
+    )
+    start = match + needle.length
+    match = lower.indexOf(needle, start)
+  }
+  parts.push(text.slice(start))
+
+  return <>{parts}</>
+}
+
+/**
+ * Virtualized build log with severity highlighting, level filters, and search.
+ * Only the rows in the viewport are rendered so large builds stay responsive.
+ * When formatted lines are given, they're shown instead of the raw output
+ * until the Raw toggle is on.
+ */
+export function BuildLogPanel({
+  lines,
+  height = 240,
+  trimmedLines = 0,
+  fullLogUrl,
+  formattedLines,
+}: BuildLogPanelProps) {
+  const [levels, setLevels] = useState<Set<BuildLogLevel>>(
+    () => new Set(["error", "warning", "info"])
+  )
+  const [query, setQuery] = useState("")
+  const [scrollTop, setScrollTop] = useState(0)
+  const [copied, setCopied] = useState(false)
+  const [raw, setRaw] = useState(false)
+  const containerRef = useRef<HTMLDivElement>(null)
+  const stickToBottomRef = useRef(true)
+
+  const logLines = useMemo(
+    () => (formattedLines && !raw ? toFormattedLogLines(formattedLines) : toBuildLogLines(lines)),
+    [lines, formattedLines, raw]
+  )
+  const counts = useMemo(() => countByLevel(logLines), [logLines])
+  const visibleLines = useMemo(
+    () => filterBuildLog(logLines, { levels, query }),
+    [logLines, levels, query]
+  )
+
+  // Follow the tail of the log unless the user scrolled up
+  useEffect(() => {
+    const container = containerRef.current
+    if (container && stickToBottomRef.current) {
+      container.scrollTop = container.scrollHeight
+    }
+  }, [visibleLines.length])
+
+  const handleScroll = () => {
+    const container = containerRef.current
+    if (!container) return
+    setScrollTop(container.scrollTop)
+    stickToBottomRef.current =
+      container.scrollHeight - container.scrollTop - container.clientHeight < ROW_HEIGHT * 2
+  }
+
+  const toggleLevel = (level: BuildLogLevel) => {
+    setLevels((prev) => {
+      const next = new Set(prev)
+      if (next.has(level)) {
+        next.delete(level)
+      } else {
+        next.add(level)
+      }
+      return next
+    })
+  }
+
+  const handleCopy = async () => {
+    try {
+      await navigator.clipboard.writeText(visibleLines.map((line) => line.text).join("\n"))
+      setCopied(true)
+      setTimeout(() => setCopied(false), 1500)
+    } catch (err) {
+      console.error("Failed to copy build log:", err)
+    }
+  }
+
+  const handleOpenLocation = (location: SourceLocation) => {
+    api.editor.open(location).catch((err) => showErrorToast("Couldn't open the file in your editor", err))
+  }
+
+  const firstRow = Math.max(0, Math.floor(scrollTop / ROW_HEIGHT) - OVERSCAN)
+  const lastRow = Math.min(
+    visibleLines.length,
+    Math.ceil((scrollTop + height) / ROW_HEIGHT) + OVERSCAN
+  )
+
+  return (
+    <div className="flex flex-col gap-2">
+      <div className="flex items-center gap-2">
+        <div className="relative flex-1">
+          <Search className="absolute left-2 top-1/2 -translate-y-1/2 w-3.5 h-3.5 text-muted-foreground" />
+          <Input
+            aria-label="Search build log"
+            placeholder="Search output"
+            value={query}
+            onChange={(e) => setQuery(e.target.value)}
+            className="h-8 pl-7 text-xs"
+          />
+        </div>
+        <Button
+          variant="outline"
+          size="icon-sm"
+          onClick={handleCopy}
+          aria-label="Copy build log"
+          disabled={visibleLines.length === 0}
+        >
+          {copied ? <Check className="w-4 h-4" /> : <Copy className="w-4 h-4" />}
+        </Button>
+      </div>
+
+      <div className="flex gap-1">
+        {LEVELS.map(({ level, label, className }) => (
+          <button
+            key={level}
+            onClick={() => toggleLevel(level)}
+            aria-pressed={levels.has(level)}
+            className={cn(
+              "px-2 py-0.5 rounded text-xs border transition-colors",
+              levels.has(level) ? "bg-secondary" : "opacity-50",
+              className
+            )}
+          >
+            {label} ({counts[level]})
+          </button>
+        ))}
+        {formattedLines && (
+          <button
+            onClick={() => setRaw((prev) => !prev)}
+            aria-pressed={raw}
+            title="Show xcodebuild's output as it was printed"
+            className={cn(
+              "ml-auto px-2 py-0.5 rounded text-xs border transition-colors text-muted-foreground",
+              raw ? "bg-secondary" : "opacity-50"
+            )}
+          >
+            Raw
+          </button>
+        )}
+      </div>
+
+      {trimmedLines > 0 && (
+        <p className="text-xs text-muted-foreground">
+          {trimmedLines.toLocaleString()} earlier lines aren't shown.
+          {fullLogUrl && (
+            <>
+              {" "}
+              <a href={fullLogUrl} target="_blank" rel="noreferrer" className="underline hover:text-foreground">
+                Open the full log
+              </a>
+            </>
+          )}
+        </p>
+      )}
+
+      <div
+        ref={containerRef}
+        onScroll={handleScroll}
+        className="rounded-md border bg-black/20 overflow-auto font-mono text-xs"
+        style={{ height }}
+      >
+        <div style={{ height: visibleLines.length * ROW_HEIGHT, position: "relative" }}>
+          {visibleLines.slice(firstRow, lastRow).map((line, i) => (
+            <div
+              key={line.index}
+              className={cn(
+                "absolute left-0 right-0 px-2 whitespace-pre truncate",
+                line.level === "error"
+                  ? "text-red-400"
+                  : line.level === "warning"
+                  ? "text-yellow-400"
+                  : "text-muted-foreground",
+                line.location && "cursor-pointer hover:underline"
+              )}
+              style={{ top: (firstRow + i) * ROW_HEIGHT, height: ROW_HEIGHT, lineHeight: `${ROW_HEIGHT}px` }}
+              title={line.location ? `Open ${line.location.file}:${line.location.line} in your editor` : line.text}
+              onClick={line.location ? () => handleOpenLocation(line.location!) : undefined}
+            >
+              <HighlightedText text={line.text} query={query} />
+            </div>
+          ))}
+        </div>
+      </div>
+    </div>
+  )
+}
@@ -0,0 +1,106 @@
+import { useEffect, useState } from "react"
+import { ChevronDown, ChevronUp, Loader2 } from "lucide-react"
+import { api, type BundleSizeReport, type SizeEntry } from "@/lib/api"
+import { categoryLabel, formatSize, sizeShare } from "@/lib/bundle-size"
+import { showErrorToast } from "@/lib/toast"
+
+interface BundleSizeProps {
+  /** Built .app to analyze */
+  productPath: string
+}
+
+function SizeRows({ title, entries, total }: { title: string; entries: SizeEntry[]; total: number }) {
+  if (entries.length === 0) return null
+  return (
+    <div className="flex flex-col gap-1">
+      <span className="text-xs text-muted-foreground">{title}</span>
+      {entries.map((entry) => (
+        <div key={entry.name} className="flex flex-col gap-0.5">
+          <div className="flex justify-between gap-2 text-xs">
+            <span className="truncate" title={entry.name}>{entry.name}</span>
+            <span className="shrink-0 text-muted-foreground">{formatSize(entry.bytes)}</span>
+          </div>
+          <div className="h-1 rounded-full bg-secondary">
+            <div
+              className="h-1 rounded-full bg-primary"
+              style={{ width: `${sizeShare(entry.bytes, total)}%` }}
+            />
+          </div>
+        </div>
+      ))}
+    </div>
+  )
+}
+
+/**
+ * Collapsible breakdown of where the size of the last built product goes,
+ * loaded when first opened
+ */
+export function BundleSize({ productPath }: BundleSizeProps) {
+  const [isOpen, setIsOpen] = useState(false)
+  const [report, setReport] = useState<BundleSizeReport | null>(null)
+
+  useEffect(() => {
+    if (!isOpen || report) return
+    let cancelled = false
+    api.xcode
+      .bundleSize(productPath)
+      .then((loaded) => {
+        if (!cancelled) setReport(loaded)
+      })
+      .catch((err) => {
+        if (cancelled) return
+        showErrorToast("Couldn't analyze the app size", err)
+        setIsOpen(false)
+      })
+    return () => {
+      cancelled = true
+    }
+  }, [isOpen, report, productPath])
+
+  return (
+    <div className="flex flex-col gap-2">
+      <button
+        className="text-xs text-muted-foreground hover:text-foreground flex items-center gap-1 self-start"
+        onClick={() => setIsOpen(!isOpen)}
+      >
+        {isOpen ? <ChevronUp className="w-3 h-3" /> : <ChevronDown className="w-3 h-3" />}
+        App size
+        {report && ` (${formatSize(report.totalBytes)})`}
+      </button>
+      {isOpen && (
+        report === null ? (
+          <div className="flex items-center gap-2 text-xs text-muted-foreground">
+            <Loader2 className="w-3 h-3 animate-spin" />
+            Analyzing...
+          </div>
+        ) : (
+          <div className="flex flex-col gap-3">
+            <SizeRows
+              title="By kind"
+              entries={report.categories.map((category) => ({
+                name: `${categoryLabel(category.name)} (${category.files})`,
+                bytes: category.bytes,
+              }))}
+              total={report.totalBytes}
+            />
+            <SizeRows title="Frameworks" entries={report.frameworks} total={report.totalBytes} />
+            {report.binary && (
+              <SizeRows
+                title={`${report.binary.path} segments`}
+                entries={report.binary.segments}
+                total={report.binary.bytes}
+              />
+            )}
+            <SizeRows title="Localizations" entries={report.localizations} total={report.totalBytes} />
+            <SizeRows
+              title="Largest files"
+              entries={report.largestFiles.slice(0, 10).map((file) => ({ name: file.path, bytes: file.bytes }))}
+              total={report.totalBytes}
+            />
+          </div>
+        )
+      )}
+    </div>
+  )
+}
@@ -0,0 +1,85 @@
+import { useEffect, useState } from "react"
+import { Bug, Check } from "lucide-react"
+import { Button } from "@/components/ui/button"
+import { api, type DebugOption, type DebugOptionsState } from "@/lib/api"
+import { showErrorToast, showToast } from "@/lib/toast"
+import { cn } from "@/lib/utils"
+
+interface DebugOptionsProps {
+  udid: string
+}
+
+/**
+ * Toolbar menu for slow animations and rendering overlays. They're passed
+ * to apps at launch, so the app has to be relaunched to pick them up.
+ */
+export function DebugOptions({ udid }: DebugOptionsProps) {
+  const [isOpen, setIsOpen] = useState(false)
+  const [state, setState] = useState<DebugOptionsState | null>(null)
+
+  useEffect(() => {
+    let cancelled = false
+    api.simulator
+      .debugOptions(udid)
+      .then((loaded) => {
+        if (!cancelled) setState(loaded)
+      })
+      .catch((err) => console.error("[DebugOptions] Failed to load debug options:", err))
+    return () => {
+      cancelled = true
+    }
+  }, [udid])
+
+  const toggle = async (option: DebugOption) => {
+    if (!state) return
+    const enabled = state.enabled.includes(option)
+      ? state.enabled.filter((id) => id !== option)
+      : [...state.enabled, option]
+    try {
+      setState(await api.simulator.setDebugOptions(udid, enabled))
+      showToast({
+        title: "Relaunch the app to apply debug options",
+        description: "Apps launched from Plasma from now on get them.",
+        severity: "info",
+      })
+    } catch (err) {
+      showErrorToast("Couldn't change debug options", err)
+    }
+  }
+
+  const hasEnabled = (state?.enabled.length ?? 0) > 0
+
+  return (
+    <div className="relative">
+      <Button
+        variant="ghost"
+        size="icon-sm"
+        aria-label="Debug options"
+        aria-expanded={isOpen}
+        title="Debug options"
+        onClick={() => setIsOpen(!isOpen)}
+        className={cn(hasEnabled && "bg-secondary text-amber-500")}
+      >
+        <Bug className="w-4 h-4" />
+      </Button>
+      {isOpen && state && (
+        <div className="absolute right-0 top-full mt-1 z-10 w-64 rounded-lg border bg-background p-1 shadow-md">
+          {state.available.map(({ id, title, description }) => (
+            <button
+              key={id}
+              className="w-full flex items-center justify-between gap-2 rounded-md px-2 py-1.5 text-left hover:bg-secondary"
+              aria-pressed={state.enabled.includes(id)}
+              onClick={() => toggle(id)}
+            >
+              <span className="flex flex-col">
+                <span className="text-sm">{title}</span>
+                <span className="text-xs text-muted-foreground">{description}</span>
+              </span>
+              {state.enabled.includes(id) && <Check className="w-3 h-3 shrink-0" />}
+            </button>
+          ))}
+        </div>
+      )}
+    </div>
+  )
+}
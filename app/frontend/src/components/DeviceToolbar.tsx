@@ -0,0 +1,167 @@
+import { useState } from "react"
+import { Camera, Circle, FileCode, Gauge, Home, Lock, MemoryStick, RotateCw, Share2, Square, Vibrate } from "lucide-react"
+import { Button } from "@/components/ui/button"
+import { InstalledApps } from "@/components/InstalledApps"
+import { NetworkConditioner } from "@/components/NetworkConditioner"
+import { DebugOptions } from "@/components/DebugOptions"
+import { api } from "@/lib/api"
+import { showErrorToast, showToast } from "@/lib/toast"
+import { cn } from "@/lib/utils"
+
+interface DeviceToolbarProps {
+  udid: string
+  showStats: boolean
+  onToggleStats: () => void
+}
+
+function downloadBlob(blob: Blob, filename: string) {
+  const url = URL.createObjectURL(blob)
+  const link = document.createElement("a")
+  link.href = url
+  link.download = filename
+  link.click()
+  URL.revokeObjectURL(url)
+}
+
+/**
+ * Hardware controls for the streamed simulator
+ */
+export function DeviceToolbar({ udid, showStats, onToggleStats }: DeviceToolbarProps) {
+  const [rotation, setRotation] = useState(0)
+  const [isRecording, setIsRecording] = useState(false)
+  const [isRecordingInteractions, setIsRecordingInteractions] = useState(false)
+
+  const run = async (label: string, action: () => Promise<void>) => {
+    try {
+      await action()
+    } catch (err) {
+      console.error(`[DeviceToolbar] ${label} failed:`, err)
+      showErrorToast(`${label} failed`, err)
+    }
+  }
+
+  const handleRotate = () =>
+    run("Rotate", async () => {
+      const next = (rotation + 90) % 360
+      await api.simulator.rotate(udid, next)
+      setRotation(next)
+    })
+
+  const handleScreenshot = () =>
+    run("Screenshot", async () => {
+      const blob = await api.simulator.screenshot(udid)
+      const timestamp = new Date().toISOString().replace(/[:.]/g, "-")
+      downloadBlob(blob, `Simulator-${timestamp}.png`)
+    })
+
+  const handleRecord = () =>
+    run("Recording", async () => {
+      if (isRecording) {
+        const { path } = await api.simulator.stopRecording(udid)
+        setIsRecording(false)
+        showToast({ title: "Recording saved", description: path, severity: "success" })
+      } else {
+        await api.simulator.startRecording(udid)
+        setIsRecording(true)
+      }
+    })
+
+  // Record taps, swipes, and button presses and copy them as an XCUITest method
+  const handleRecordInteractions = () =>
+    run("Interaction recording", async () => {
+      if (isRecordingInteractions) {
+        const { steps, xcuitest } = await api.simulator.stopInteractionRecording(udid)
+        setIsRecordingInteractions(false)
+        await navigator.clipboard.writeText(xcuitest)
+        showToast({
+          title: "XCUITest copied to clipboard",
+          description: `${steps.length} recorded ${steps.length === 1 ? "step" : "steps"}`,
+          severity: "success",
+        })
+      } else {
+        await api.simulator.startInteractionRecording(udid)
+        setIsRecordingInteractions(true)
+      }
+    })
+
+  // Copy a view-only link teammates on the same network can watch the stream at
+  const handleShare = () =>
+    run("Sharing", async () => {
+      const share = await api.simulator.createShare(udid)
+      await navigator.clipboard.writeText(share.url)
+      showToast({
+        title: "Share link copied",
+        description: `View only, expires at ${new Date(share.expiresAt).toLocaleTimeString()}`,
+        severity: "success",
+      })
+    })
+
+  return (
+    <div className="flex items-center gap-1 rounded-lg border bg-background/80 p-1">
+      <Button variant="ghost" size="icon-sm" aria-label="Home" title="Home"
+        onClick={() => run("Home", () => api.simulator.pressButton(udid, "home"))}>
+        <Home className="w-4 h-4" />
+      </Button>
+      <Button variant="ghost" size="icon-sm" aria-label="Lock" title="Lock"
+        onClick={() => run("Lock", () => api.simulator.pressButton(udid, "lock"))}>
+        <Lock className="w-4 h-4" />
+      </Button>
+      <Button variant="ghost" size="icon-sm" aria-label="Rotate" title="Rotate" onClick={handleRotate}>
+        <RotateCw className="w-4 h-4" />
+      </Button>
+      <Button variant="ghost" size="icon-sm" aria-label="Screenshot" title="Screenshot" onClick={handleScreenshot}>
+        <Camera className="w-4 h-4" />
+      </Button>
+      <Button
+        variant="ghost"
+        size="icon-sm"
+        aria-label={isRecording ? "Stop recording" : "Record"}
+        title={isRecording ? "Stop recording" : "Record"}
+        onClick={handleRecord}
+      >
+        {isRecording ? (
+          <Square className="w-4 h-4 text-red-500 fill-red-500" />
+        ) : (
+          <Circle className="w-4 h-4" />
+        )}
+      </Button>
+      <Button
+        variant="ghost"
+        size="icon-sm"
+        aria-label={isRecordingInteractions ? "Stop recording interactions" : "Record interactions as a UI test"}
+        aria-pressed={isRecordingInteractions}
+        title={isRecordingInteractions ? "Stop recording interactions" : "Record interactions as a UI test"}
+        onClick={handleRecordInteractions}
+        className={cn(isRecordingInteractions && "bg-secondary text-red-500")}
+      >
+        <FileCode className="w-4 h-4" />
+      </Button>
+      <Button variant="ghost" size="icon-sm" aria-label="Share a view-only link" title="Share a view-only link"
+        onClick={handleShare}>
+        <Share2 className="w-4 h-4" />
+      </Button>
+      <Button variant="ghost" size="icon-sm" aria-label="Shake" title="Shake"
+        onClick={() => run("Shake", () => api.simulator.shake(udid))}>
+        <Vibrate className="w-4 h-4" />
+      </Button>
+      <Button variant="ghost" size="icon-sm" aria-label="Simulate memory warning" title="Simulate memory warning"
+        onClick={() => run("Memory warning", () => api.simulator.simulateMemoryWarning(udid))}>
+        <MemoryStick className="w-4 h-4" />
+      </Button>
+      <InstalledApps udid={udid} />
+      <NetworkConditioner udid={udid} />
+      <DebugOptions udid={udid} />
+      <Button
+        variant="ghost"
+        size="icon-sm"
+        aria-label="Performance overlay"
+        aria-pressed={showStats}
+        title="Performance overlay"
+        onClick={onToggleStats}
+        className={cn(showStats && "bg-secondary")}
+      >
+        <Gauge className="w-4 h-4" />
+      </Button>
+    </div>
+  )
+}
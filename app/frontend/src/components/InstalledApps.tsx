@@ -0,0 +1,175 @@
+import { useEffect, useState } from "react"
+import { Eraser, KeyRound, LayoutGrid, Loader2, SlidersHorizontal } from "lucide-react"
+import { Button } from "@/components/ui/button"
+import { api, type InstalledApp } from "@/lib/api"
+import { showErrorToast, showToast } from "@/lib/toast"
+
+interface InstalledAppsProps {
+  udid: string
+}
+
+type ResetKind = "data" | "defaults"
+
+/**
+ * Toolbar menu listing the apps installed on the simulator so one can be
+ * relaunched without rebuilding it, or reset to replay its first launch
+ */
+export function InstalledApps({ udid }: InstalledAppsProps) {
+  const [isOpen, setIsOpen] = useState(false)
+  const [apps, setApps] = useState<InstalledApp[] | null>(null)
+  const [launching, setLaunching] = useState<string | null>(null)
+  // "<kind>:<bundleId>", or "keychain"
+  const [resetting, setResetting] = useState<string | null>(null)
+  const busy = launching !== null || resetting !== null
+
+  useEffect(() => {
+    if (!isOpen) return
+    let cancelled = false
+    setApps(null)
+    api.simulator
+      .apps(udid)
+      .then((loaded) => {
+        if (!cancelled) setApps(loaded.filter((app) => app.applicationType === "User"))
+      })
+      .catch((err) => {
+        if (cancelled) return
+        showErrorToast("Couldn't list installed apps", err)
+        setIsOpen(false)
+      })
+    return () => {
+      cancelled = true
+    }
+  }, [isOpen, udid])
+
+  const launch = async (app: InstalledApp) => {
+    setLaunching(app.bundleId)
+    try {
+      await api.simulator.launchApp(udid, app.bundleId)
+      setIsOpen(false)
+    } catch (err) {
+      showErrorToast(`Couldn't launch ${app.name}`, err)
+    } finally {
+      setLaunching(null)
+    }
+  }
+
+  const resetApp = async (app: InstalledApp, kind: ResetKind) => {
+    setResetting(`${kind}:${app.bundleId}`)
+    try {
+      if (kind === "data") {
+        await api.simulator.resetAppData(udid, app.bundleId)
+      } else {
+        await api.simulator.resetUserDefaults(udid, app.bundleId)
+      }
+      showToast({
+        title: kind === "data" ? `${app.name} data erased` : `${app.name} defaults cleared`,
+        severity: "success",
+      })
+    } catch (err) {
+      showErrorToast(`Couldn't reset ${app.name}`, err)
+    } finally {
+      setResetting(null)
+    }
+  }
+
+  const resetKeychain = async () => {
+    setResetting("keychain")
+    try {
+      await api.simulator.resetKeychain(udid)
+      showToast({ title: "Keychain reset", severity: "success" })
+    } catch (err) {
+      showErrorToast("Couldn't reset the keychain", err)
+    } finally {
+      setResetting(null)
+    }
+  }
+
+  return (
+    <div className="relative">
+      <Button
+        variant="ghost"
+        size="icon-sm"
+        aria-label="Installed apps"
+        aria-expanded={isOpen}
+        title="Installed apps"
+        onClick={() => setIsOpen(!isOpen)}
+      >
+        <LayoutGrid className="w-4 h-4" />
+      </Button>
+      {isOpen && (
+        <div className="absolute right-0 top-full mt-1 z-10 w-64 max-h-72 overflow-y-auto rounded-lg border bg-background p-1 shadow-md">
+          {apps === null ? (
+            <div className="flex items-center gap-2 px-2 py-1.5 text-xs text-muted-foreground">
+              <Loader2 className="w-3 h-3 animate-spin" />
+              Loading apps...
+            </div>
+          ) : apps.length === 0 ? (
+            <div className="px-2 py-1.5 text-xs text-muted-foreground">No apps installed</div>
+          ) : (
+            apps.map((app) => (
+              <div key={app.bundleId} className="flex items-center rounded-md hover:bg-secondary">
+                <button
+                  className="flex-1 min-w-0 flex flex-col items-start px-2 py-1.5 text-left disabled:opacity-50"
+                  disabled={busy}
+                  onClick={() => launch(app)}
+                >
+                  <span className="text-sm flex items-center gap-2">
+                    {app.name}
+                    {launching === app.bundleId && <Loader2 className="w-3 h-3 animate-spin" />}
+                  </span>
+                  <span className="text-xs text-muted-foreground truncate w-full">
+                    {app.bundleId}
+                    {app.version && ` · ${app.version}`}
+                    {app.buildNumber && ` (${app.buildNumber})`}
+                  </span>
+                </button>
+                <Button
+                  variant="ghost"
+                  size="icon-sm"
+                  aria-label={`Clear ${app.name} defaults`}
+                  title="Clear NSUserDefaults"
+                  disabled={busy}
+                  onClick={() => resetApp(app, "defaults")}
+                >
+                  {resetting === `defaults:${app.bundleId}` ? (
+                    <Loader2 className="w-3 h-3 animate-spin" />
+                  ) : (
+                    <SlidersHorizontal className="w-3 h-3" />
+                  )}
+                </Button>
+                <Button
+                  variant="ghost"
+                  size="icon-sm"
+                  aria-label={`Erase ${app.name} data`}
+                  title="Erase app data"
+                  disabled={busy}
+                  onClick={() => resetApp(app, "data")}
+                >
+                  {resetting === `data:${app.bundleId}` ? (
+                    <Loader2 className="w-3 h-3 animate-spin" />
+                  ) : (
+                    <Eraser className="w-3 h-3" />
+                  )}
+                </Button>
+              </div>
+            ))
+          )}
+          <div className="mt-1 border-t pt-1">
+            <button
+              className="w-full flex items-center gap-2 rounded-md px-2 py-1.5 text-left text-sm hover:bg-secondary disabled:opacity-50"
+              disabled={busy}
+              onClick={resetKeychain}
+            >
+              {resetting === "keychain" ? (
+                <Loader2 className="w-3 h-3 animate-spin" />
+              ) : (
+                <KeyRound className="w-3 h-3" />
+              )}
+              Reset keychain
+            </button>
+          </div>
+        </div>
+      )}
+    </div>
+  )
+}
@@ -0,0 +1,98 @@
+import { useEffect, useState } from "react"
+import { Check, Wifi, WifiOff } from "lucide-react"
+import { Button } from "@/components/ui/button"
+import { api, type NetworkConditionState, type NetworkPreset } from "@/lib/api"
+import { showErrorToast, showToast } from "@/lib/toast"
+import { cn } from "@/lib/utils"
+
+interface NetworkConditionerProps {
+  udid: string
+}
+
+const PRESETS: Array<{ preset: NetworkPreset; label: string; description: string }> = [
+  { preset: "none", label: "No conditioning", description: "Full speed" },
+  { preset: "3g", label: "3G", description: "780/330 kbps, 100 ms" },
+  { preset: "lossy-wifi", label: "Lossy Wi-Fi", description: "10/5 Mbps, 40 ms, 10% loss" },
+  { preset: "offline", label: "Offline", description: "Every connection fails" },
+]
+
+/**
+ * Toolbar menu for simulating poor networks. Conditions apply to apps
+ * launched after the first preset is picked, since they are pointed at the
+ * simulator's throttling proxy on launch.
+ */
+export function NetworkConditioner({ udid }: NetworkConditionerProps) {
+  const [isOpen, setIsOpen] = useState(false)
+  const [state, setState] = useState<NetworkConditionState | null>(null)
+
+  useEffect(() => {
+    let cancelled = false
+    api.simulator
+      .network(udid)
+      .then((loaded) => {
+        if (!cancelled) setState(loaded)
+      })
+      .catch((err) => console.error("[NetworkConditioner] Failed to load conditions:", err))
+    return () => {
+      cancelled = true
+    }
+  }, [udid])
+
+  const select = async (preset: NetworkPreset) => {
+    const wasProxied = state?.proxyUrl != null
+    try {
+      const next = await api.simulator.setNetwork(udid, { preset })
+      setState(next)
+      setIsOpen(false)
+      if (!wasProxied) {
+        showToast({
+          title: "Relaunch the app to apply network conditions",
+          description: `Apps launched from now on use the proxy at ${next.proxyUrl}`,
+          severity: "info",
+        })
+      }
+    } catch (err) {
+      showErrorToast("Couldn't change network conditions", err)
+    }
+  }
+
+  // Null when custom conditions were set through the API
+  const active = state ? state.preset : "none"
+  const isConditioned = active !== "none"
+
+  return (
+    <div className="relative">
+      <Button
+        variant="ghost"
+        size="icon-sm"
+        aria-label="Network conditions"
+        aria-expanded={isOpen}
+        title="Network conditions"
+        onClick={() => setIsOpen(!isOpen)}
+        className={cn(isConditioned && "bg-secondary text-amber-500")}
+      >
+        {active === "offline" ? <WifiOff className="w-4 h-4" /> : <Wifi className="w-4 h-4" />}
+      </Button>
+      {isOpen && (
+        <div className="absolute right-0 top-full mt-1 z-10 w-56 rounded-lg border bg-background p-1 shadow-md">
+          {PRESETS.map(({ preset, label, description }) => (
+            <button
+              key={preset}
+              className="w-full flex items-center justify-between rounded-md px-2 py-1.5 text-left hover:bg-secondary"
+              onClick={() => select(preset)}
+            >
+              <span className="flex flex-col">
+                <span className="text-sm">{label}</span>
+                <span className="text-xs text-muted-foreground">{description}</span>
+              </span>
+              {active === preset && <Check className="w-3 h-3" />}
+            </button>
+          ))}
+          {active === null && (
+            <div className="px-2 py-1.5 text-xs text-muted-foreground">Custom conditions set through the API</div>
+          )}
+        </div>
+      )}
+    </div>
+  )
+}
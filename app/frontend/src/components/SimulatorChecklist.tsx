@@ -0,0 +1,51 @@
+import { useMemo } from "react"
+import { SimulatorThumbnail } from "@/components/SimulatorThumbnail"
+import { groupSimulatorsByRuntime } from "@/lib/simulators"
+import type { Simulator } from "@/lib/api"
+
+interface SimulatorChecklistProps {
+  simulators: Simulator[]
+  excludeUdid: string
+  selected: string[]
+  onChange: (udids: string[]) => void
+}
+
+/**
+ * Checklist of extra simulators to run the same build on, with a preview
+ * of the ones that are booted
+ */
+export function SimulatorChecklist({ simulators, excludeUdid, selected, onChange }: SimulatorChecklistProps) {
+  const groups = useMemo(
+    () => groupSimulatorsByRuntime(simulators.filter((s) => s.udid !== excludeUdid)),
+    [simulators, excludeUdid]
+  )
+
+  const toggle = (udid: string) => {
+    onChange(selected.includes(udid) ? selected.filter((u) => u !== udid) : [...selected, udid])
+  }
+
+  if (groups.length === 0) {
+    return <p className="text-xs text-muted-foreground">No other simulators available</p>
+  }
+
+  return (
+    <div className="max-h-[160px] overflow-y-auto rounded-md border p-2 flex flex-col gap-2">
+      {groups.map((group) => (
+        <div key={group.runtime} className="flex flex-col gap-1">
+          <span className="text-xs font-medium text-muted-foreground">{group.label}</span>
+          {group.simulators.map((sim) => (
+            <label key={sim.udid} className="flex items-center gap-2 text-sm cursor-pointer">
+              <input
+                type="checkbox"
+                checked={selected.includes(sim.udid)}
+                onChange={() => toggle(sim.udid)}
+              />
+              <span className="flex-1 truncate">{sim.name}</span>
+              {sim.state === "Booted" && <SimulatorThumbnail udid={sim.udid} width={48} className="h-10" />}
+            </label>
+          ))}
+        </div>
+      ))}
+    </div>
+  )
+}
@@ -0,0 +1,53 @@
+import { StreamViewer } from "@/components/StreamViewer"
+import { cn } from "@/lib/utils"
+import type { Simulator } from "@/lib/api"
+
+interface SimulatorGridProps {
+  udids: string[]
+  simulators: Simulator[]
+  focusedUdid: string
+  onFocus: (udid: string) => void
+  showStats?: boolean
+}
+
+/**
+ * Renders one stream per simulator side by side. Only the focused device
+ * receives input; pressing on another tile moves focus to it.
+ */
+export function SimulatorGrid({ udids, simulators, focusedUdid, onFocus, showStats = false }: SimulatorGridProps) {
+  if (udids.length === 1) {
+    return <StreamViewer udid={udids[0]} showStats={showStats} />
+  }
+
+  return (
+    <div
+      className="h-full w-full grid gap-3"
+      style={{ gridTemplateColumns: `repeat(auto-fit, minmax(${udids.length > 2 ? 220 : 280}px, 1fr))` }}
+    >
+      {udids.map((udid) => {
+        const simulator = simulators.find((s) => s.udid === udid)
+        const isFocused = udid === focusedUdid
+
+        return (
+          <div
+            key={udid}
+            onPointerDownCapture={() => {
+              if (!isFocused) onFocus(udid)
+            }}
+            className={cn(
+              "flex flex-col min-h-0 rounded-xl border-2 p-2 transition-colors",
+              isFocused ? "border-primary/60" : "border-transparent opacity-80 hover:opacity-100"
+            )}
+          >
+            <span className="text-xs text-muted-foreground text-center truncate pb-1">
+              {simulator?.name ?? udid}
+            </span>
+            <div className="flex-1 min-h-0">
+              <StreamViewer udid={udid} interactive={isFocused} showStats={showStats && isFocused} />
+            </div>
+          </div>
+        )
+      })}
+    </div>
+  )
+}
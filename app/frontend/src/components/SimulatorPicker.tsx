@@ -0,0 +1,68 @@
+import { useMemo } from "react"
+import { RefreshCw } from "lucide-react"
+import { Button } from "@/components/ui/button"
+import { groupSimulatorsByRuntime } from "@/lib/simulators"
+import type { Simulator } from "@/lib/api"
+
+interface SimulatorPickerProps {
+  simulators: Simulator[]
+  value: string
+  onChange: (udid: string) => void
+  isLoading?: boolean
+  onRefresh?: () => void
+  /** Label for an empty choice; omit to require a simulator */
+  noneLabel?: string
+}
+
+export function SimulatorPicker({
+  simulators,
+  value,
+  onChange,
+  isLoading = false,
+  onRefresh,
+  noneLabel,
+}: SimulatorPickerProps) {
+  const groups = useMemo(() => groupSimulatorsByRuntime(simulators), [simulators])
+
+  return (
+    <div className="flex gap-2">
+      <select
+        aria-label="Simulator"
+        className="w-full h-9 px-3 rounded-md border border-input bg-background text-sm"
+        value={value}
+        onChange={(e) => onChange(e.target.value)}
+        disabled={isLoading || simulators.length === 0}
+      >
+        {isLoading ? (
+          <option value="">Loading simulators...</option>
+        ) : simulators.length === 0 ? (
+          <option value="">No simulators found</option>
+        ) : (
+          <>
+            {noneLabel !== undefined && <option value="">{noneLabel}</option>}
+            {groups.map((group) => (
+              <optgroup key={group.runtime} label={group.label}>
+                {group.simulators.map((sim) => (
+                  <option key={sim.udid} value={sim.udid}>
+                    {sim.name} {sim.state === "Booted" ? "(Booted)" : ""}
+                  </option>
+                ))}
+              </optgroup>
+            ))}
+          </>
+        )}
+      </select>
+      {onRefresh && (
+        <Button
+          variant="outline"
+          size="icon"
+          onClick={onRefresh}
+          disabled={isLoading}
+          aria-label="Refresh simulators"
+        >
+          <RefreshCw className={`w-4 h-4 ${isLoading ? "animate-spin" : ""}`} />
+        </Button>
+      )}
+    </div>
+  )
+}
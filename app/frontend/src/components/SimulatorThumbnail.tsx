@@ -0,0 +1,41 @@
+import { useEffect, useState } from "react"
+import { api } from "@/lib/api"
+import { cn } from "@/lib/utils"
+
+// The server captures a simulator's screen at most this often
+const REFRESH_INTERVAL_MS = 2000
+
+interface SimulatorThumbnailProps {
+  udid: string
+  /** Width the server scales the screen down to, in pixels */
+  width?: number
+  className?: string
+}
+
+/**
+ * Live preview of a booted simulator's screen that refreshes every couple
+ * of seconds, light enough to show one per device in a list
+ */
+export function SimulatorThumbnail({ udid, width = 160, className }: SimulatorThumbnailProps) {
+  const [tick, setTick] = useState(0)
+  const [failed, setFailed] = useState(false)
+
+  useEffect(() => {
+    setFailed(false)
+    const interval = setInterval(() => {
+      if (!document.hidden) setTick((t) => t + 1)
+    }, REFRESH_INTERVAL_MS)
+    return () => clearInterval(interval)
+  }, [udid])
+
+  if (failed) return null
+
+  return (
+    <img
+      src={`${api.simulator.thumbnailUrl(udid, width)}&t=${tick}`}
+      alt="Simulator screen"
+      onError={() => setFailed(true)}
+      className={cn("rounded-md border bg-black/20 object-contain", className)}
+    />
+  )
+}
@@ -0,0 +1,58 @@
+import { captureFps, formatBandwidth, formatMemory } from "@/lib/stream-stats"
+import type { AppMetricsSample, StreamStats } from "@/lib/api"
+
+export interface StreamHudStats extends StreamStats {
+  /** Average time between the server receiving a frame and it being drawn */
+  latencyMs: number | null
+  /** Frames superseded before the previous one finished decoding */
+  clientDroppedFrames: number
+}
+
+interface StreamStatsOverlayProps {
+  stats: StreamHudStats | null
+  /** Latest sample of the app running on the simulator, if it is being sampled */
+  appMetrics?: AppMetricsSample | null
+}
+
+/**
+ * Performance HUD for the active stream
+ */
+export function StreamStatsOverlay({ stats, appMetrics }: StreamStatsOverlayProps) {
+  const capture = stats ? captureFps(stats.captureReport) : null
+
+  const rows: [string, string][] = stats
+    ? [
+        ["Backend", stats.backend],
+        ["FPS", capture !== null ? `${stats.fps} (capture ${Math.round(capture)})` : `${stats.fps}`],
+        ["Latency", stats.latencyMs !== null ? `${Math.round(stats.latencyMs)} ms` : "–"],
+        ["Dropped", `${stats.droppedFrames + stats.clientDroppedFrames}`],
+        ["Bandwidth", formatBandwidth(stats.bytesPerSecond)],
+      ]
+    : []
+  if (stats && appMetrics) {
+    rows.push(
+      ["App CPU", `${appMetrics.cpuPercent.toFixed(1)}%`],
+      ["App memory", formatMemory(appMetrics.rssKb)],
+      ["Threads", `${appMetrics.threads}`]
+    )
+  }
+
+  return (
+    <div className="absolute top-2 left-2 z-20 pointer-events-none rounded bg-black/70 px-2 py-1.5 font-mono text-[11px] text-white/90">
+      {stats ? (
+        <table>
+          <tbody>
+            {rows.map(([label, value]) => (
+              <tr key={label}>
+                <td className="pr-3 text-white/60">{label}</td>
+                <td>{value}</td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      ) : (
+        <span className="text-white/60">Waiting for stats…</span>
+      )}
+    </div>
+  )
+}
@@ -1,9 +1,14 @@
-import { useEffect, useRef, useState, useCallback, type MouseEvent } from "react";
-import { Loader2 } from "lucide-react";
-import { api } from "@/lib/api";
+import { useEffect, useRef, useState, useCallback, type DragEvent, type PointerEvent } from "react";
+import { ImagePlus, Loader2 } from "lucide-react";
+import { api, type AppMetricsSample } from "@/lib/api";
+import { StreamStatsOverlay, type StreamHudStats } from "@/components/StreamStatsOverlay";
 
 interface StreamViewerProps {
   udid: string;
+  /** When false, frames are shown but no input is forwarded */
+  interactive?: boolean;
+  /** Show the performance HUD over the stream */
+  showStats?: boolean;
 }
 
 interface TouchPoint {
@@ -12,12 +17,14 @@ interface TouchPoint {
 }
 
 /**
- * StreamViewer displays frames from the simulator via IPC.
+ * StreamViewer displays frames from the simulator and mirrors input back to it.
  *
- * Frames are received as base64-encoded JPEGs via IPC events.
- * Touch events are sent via IPC to the main process.
+ * Frames are received as base64-encoded JPEGs over the WebSocket.
+ * Pointer presses, drags, and releases inside the frame are translated into
+ * normalized touch coordinates and forwarded to the simulator session.
+ * Photos and videos dropped on the stream are added to the simulator's library.
  */
-export function StreamViewer({ udid }: StreamViewerProps) {
+export function StreamViewer({ udid, interactive = true, showStats = false }: StreamViewerProps) {
   const [isLoading, setIsLoading] = useState(true);
   const [dimensions, setDimensions] = useState("");
   const [isPressing, setIsPressing] = useState(false);
@@ -26,37 +33,83 @@ export function StreamViewer({ udid }: StreamViewerProps) {
   const canvasRef = useRef<HTMLCanvasElement>(null);
   const wrapperRef = useRef<HTMLDivElement>(null);
   const frameCountRef = useRef(0);
+  const [hudStats, setHudStats] = useState<StreamHudStats | null>(null);
+  const [appMetrics, setAppMetrics] = useState<AppMetricsSample | null>(null);
+  // Client-side frame metrics accumulated between server stats reports
+  const clientMetricsRef = useRef({ latencyTotal: 0, latencySamples: 0, dropped: 0 });
   const dragStartRef = useRef<TouchPoint | null>(null);
+  const [isDropTarget, setIsDropTarget] = useState(false);
+  const [mediaStatus, setMediaStatus] = useState<string | null>(null);
+
+  const pendingMoveRef = useRef<TouchPoint | null>(null);
+  const moveFrameRef = useRef<number | null>(null);
 
   // Send touch event to backend (began, moved, ended)
   const sendTouch = useCallback(
-    async (point: TouchPoint, touchType: "began" | "moved" | "ended") => {
-      try {
-        // Fire-and-forget for move events to avoid blocking
-        api.simulator.touch({
-          udid,
-          type: touchType,
-          touches: [{ x: point.x, y: point.y }],
-        }).catch((err) => {
-          console.error("[StreamViewer] Failed to send touch:", err);
-        });
-      } catch (err) {
+    (point: TouchPoint, touchType: "began" | "moved" | "ended") => {
+      // Fire-and-forget to avoid blocking pointer handling
+      api.simulator.touch({
+        udid,
+        type: touchType,
+        touches: [{ x: point.x, y: point.y }],
+      }).catch((err) => {
         console.error("[StreamViewer] Failed to send touch:", err);
-      }
+      });
     },
     [udid]
   );
 
-  // Get normalized touch coordinates (0-1 range) from mouse event
+  // Coalesce move events to one per animation frame
+  const flushPendingMove = useCallback(() => {
+    if (moveFrameRef.current !== null) {
+      cancelAnimationFrame(moveFrameRef.current);
+      moveFrameRef.current = null;
+    }
+    if (pendingMoveRef.current) {
+      sendTouch(pendingMoveRef.current, "moved");
+      pendingMoveRef.current = null;
+    }
+  }, [sendTouch]);
+
+  const queueMove = useCallback(
+    (point: TouchPoint) => {
+      pendingMoveRef.current = point;
+      if (moveFrameRef.current === null) {
+        moveFrameRef.current = requestAnimationFrame(() => {
+          moveFrameRef.current = null;
+          flushPendingMove();
+        });
+      }
+    },
+    [flushPendingMove]
+  );
+
+  useEffect(() => {
+    return () => {
+      if (moveFrameRef.current !== null) {
+        cancelAnimationFrame(moveFrameRef.current);
+      }
+    };
+  }, []);
+
+  // Get normalized touch coordinates (0-1 range) within the rendered frame.
+  // Returns null when the pointer is outside the frame, unless clamping is requested.
   const getNormalizedCoordinates = useCallback(
-    (e: MouseEvent<HTMLDivElement>): TouchPoint => {
+    (e: PointerEvent<HTMLDivElement>, clamp: boolean): TouchPoint | null => {
       const canvas = canvasRef.current;
-      if (!canvas) return { x: 0.5, y: 0.5 };
+      if (!canvas) return null;
 
       const rect = canvas.getBoundingClientRect();
-      const x = Math.max(0, Math.min(1, (e.clientX - rect.left) / rect.width));
-      const y = Math.max(0, Math.min(1, (e.clientY - rect.top) / rect.height));
-      return { x, y };
+      if (rect.width === 0 || rect.height === 0) return null;
+
+      const x = (e.clientX - rect.left) / rect.width;
+      const y = (e.clientY - rect.top) / rect.height;
+      if (!clamp && (x < 0 || x > 1 || y < 0 || y > 1)) return null;
+
+      return {
+        x: Math.max(0, Math.min(1, x)),
+        y: Math.max(0, Math.min(1, y)),
+      };
     },
     []
   );
@@ -74,77 +127,104 @@ export function StreamViewer({ udid }: StreamViewerProps) {
     []
   );
 
-  // Mouse event handlers - send continuous touch events for real-time feedback
-  const handleMouseDown = useCallback(
-    (e: MouseEvent<HTMLDivElement>) => {
+  // Pointer handlers - the pointer is captured on press so drags that leave
+  // the frame keep tracking until release
+  const handlePointerDown = useCallback(
+    (e: PointerEvent<HTMLDivElement>) => {
+      if (e.button !== 0 || isLoading || !interactive) return;
+      const point = getNormalizedCoordinates(e, false);
+      if (!point) return;
+
       e.preventDefault();
-      const point = getNormalizedCoordinates(e);
+      e.currentTarget.setPointerCapture(e.pointerId);
       setIsPressing(true);
       setTouchPoint(point);
       setIsDragging(false);
       dragStartRef.current = point;
-      // Send touch began immediately
       sendTouch(point, "began");
     },
-    [getNormalizedCoordinates, sendTouch]
+    [isLoading, interactive, getNormalizedCoordinates, sendTouch]
   );
 
-  const handleMouseMove = useCallback(
-    (e: MouseEvent<HTMLDivElement>) => {
+  const handlePointerMove = useCallback(
+    (e: PointerEvent<HTMLDivElement>) => {
       if (!isPressing) return;
       e.preventDefault();
-      const point = getNormalizedCoordinates(e);
+      const point = getNormalizedCoordinates(e, true);
+      if (!point) return;
       setTouchPoint(point);
 
-      // Check if we've started dragging
-      if (!isDragging && dragStartRef.current) {
-        if (isDragMovement(dragStartRef.current, point)) {
-          setIsDragging(true);
-        }
-      }
-
-      // Send move event for continuous feedback
+      // Only forward moves once the drag threshold is crossed so taps stay taps
       if (isDragging || (dragStartRef.current && isDragMovement(dragStartRef.current, point))) {
-        sendTouch(point, "moved");
+        if (!isDragging) setIsDragging(true);
+        queueMove(point);
       }
     },
-    [isPressing, isDragging, getNormalizedCoordinates, isDragMovement, sendTouch]
+    [isPressing, isDragging, getNormalizedCoordinates, isDragMovement, queueMove]
   );
 
-  const handleMouseUp = useCallback(
-    (e: MouseEvent<HTMLDivElement>) => {
+  const handlePointerUp = useCallback(
+    (e: PointerEvent<HTMLDivElement>) => {
       if (!isPressing) return;
       e.preventDefault();
-      const point = getNormalizedCoordinates(e);
+      const point = getNormalizedCoordinates(e, true) ?? dragStartRef.current;
 
-      // Send touch ended - HID touch Up completes the tap/drag
-      sendTouch(point, "ended");
+      if (e.currentTarget.hasPointerCapture(e.pointerId)) {
+        e.currentTarget.releasePointerCapture(e.pointerId);
+      }
+
+      // Flush any queued move so the ended event arrives last
+      flushPendingMove();
+      if (point) {
+        sendTouch(point, "ended");
+      }
 
       setIsPressing(false);
       setTouchPoint(null);
       setIsDragging(false);
       dragStartRef.current = null;
     },
-    [isPressing, getNormalizedCoordinates, sendTouch]
+    [isPressing, getNormalizedCoordinates, flushPendingMove, sendTouch]
   );
 
-  const handleMouseLeave = useCallback(
-    (e: MouseEvent<HTMLDivElement>) => {
-      if (!isPressing) return;
+  // Files dragged over the stream are uploaded one by one into the simulator's library
+  const handleDragOver = useCallback(
+    (e: DragEvent<HTMLDivElement>) => {
+      if (!interactive || !e.dataTransfer.types.includes("Files")) return;
       e.preventDefault();
-      const point = getNormalizedCoordinates(e);
+      e.dataTransfer.dropEffect = "copy";
+      setIsDropTarget(true);
+    },
+    [interactive]
+  );
 
-      // Send touch ended when leaving the area
-      sendTouch(point, "ended");
+  const handleDrop = useCallback(
+    async (e: DragEvent<HTMLDivElement>) => {
+      if (!interactive) return;
+      e.preventDefault();
+      setIsDropTarget(false);
+      const files = Array.from(e.dataTransfer.files);
+      if (files.length === 0) return;
 
-      setIsPressing(false);
-      setTouchPoint(null);
-      setIsDragging(false);
-      dragStartRef.current = null;
+      setMediaStatus(`Adding ${files.length === 1 ? files[0].name : `${files.length} files`}...`);
+      try {
+        for (const file of files) {
+          await api.simulator.uploadMedia(udid, file);
+        }
+        setMediaStatus(`Added ${files.length === 1 ? files[0].name : `${files.length} files`} to the library`);
+      } catch (err) {
+        setMediaStatus(err instanceof Error ? err.message : "Could not add the files");
+      }
     },
-    [isPressing, getNormalizedCoordinates, sendTouch]
+    [interactive, udid]
   );
 
+  useEffect(() => {
+    if (!mediaStatus || mediaStatus.endsWith("...")) return;
+    const timer = setTimeout(() => setMediaStatus(null), 3000);
+    return () => clearTimeout(timer);
+  }, [mediaStatus]);
+
   // Subscribe to frame events from IPC
   useEffect(() => {
     const canvas = canvasRef.current;
@@ -163,13 +243,24 @@ export function StreamViewer({ udid }: StreamViewerProps) {
 
     // Create an image element to decode base64 frames
     const img = new Image();
+    let decoding = false;
+    img.onerror = () => {
+      decoding = false;
+    };
 
     const unsubscribe = api.simulator.onStreamFrame((frame) => {
       // Only process frames for this UDID
       if (frame.udid !== udid) return;
 
+      // A new frame replaces one that has not finished decoding yet
+      if (decoding) {
+        clientMetricsRef.current.dropped++;
+      }
+      decoding = true;
+
       // Decode base64 frame
       img.onload = () => {
+        decoding = false;
         const w = img.naturalWidth;
         const h = img.naturalHeight;
 
@@ -185,13 +276,18 @@ export function StreamViewer({ udid }: StreamViewerProps) {
         ctx.drawImage(img, 0, 0);
         setIsLoading(false);
 
+        if (frame.timestamp) {
+          clientMetricsRef.current.latencyTotal += Date.now() - frame.timestamp;
+          clientMetricsRef.current.latencySamples++;
+        }
+
         frameCountRef.current++;
         if (frameCountRef.current % 60 === 0) {
           console.log("[StreamViewer] Drew frame", frameCountRef.current);
         }
       };
 
-      img.src = `data:image/jpeg;base64,${frame.frame}`;
+      img.src = `data:${frame.mimeType ?? "image/jpeg"};base64,${frame.frame}`;
     });
 
     return () => {
@@ -200,6 +296,55 @@ export function StreamViewer({ udid }: StreamViewerProps) {
     };
   }, [udid]);
 
+  // Nobody sees the frames of a hidden or minimized window, so don't send them
+  useEffect(() => {
+    const handleVisibilityChange = () => {
+      const request = document.hidden ? api.simulator.pauseSession(udid) : api.simulator.resumeSession(udid);
+      request.catch((err) => {
+        console.error("[StreamViewer] Failed to pause or resume the session:", err);
+      });
+    };
+    document.addEventListener("visibilitychange", handleVisibilityChange);
+    return () => document.removeEventListener("visibilitychange", handleVisibilityChange);
+  }, [udid]);
+
+  // Combine server stream stats with client-side latency and drops
+  useEffect(() => {
+    if (!showStats) return;
+
+    clientMetricsRef.current = { latencyTotal: 0, latencySamples: 0, dropped: 0 };
+    const unsubscribe = api.simulator.onStreamStats((stats) => {
+      if (stats.udid !== udid) return;
+
+      const metrics = clientMetricsRef.current;
+      setHudStats({
+        ...stats,
+        latencyMs: metrics.latencySamples > 0 ? metrics.latencyTotal / metrics.latencySamples : null,
+        clientDroppedFrames: metrics.dropped,
+      });
+      clientMetricsRef.current = { latencyTotal: 0, latencySamples: 0, dropped: 0 };
+    });
+
+    return () => {
+      unsubscribe();
+      setHudStats(null);
+    };
+  }, [udid, showStats]);
+
+  // CPU/memory samples of the app launched on the simulator
+  useEffect(() => {
+    if (!showStats) return;
+
+    const unsubscribe = api.metrics.onEvent(udid, (event) => {
+      setAppMetrics(event.type === "sample" ? event.sample : null);
+    });
+
+    return () => {
+      unsubscribe();
+      setAppMetrics(null);
+    };
+  }, [udid, showStats]);
+
   // Calculate touch indicator position relative to canvas
   const getTouchIndicatorStyle = useCallback(() => {
     if (!touchPoint || !canvasRef.current || !wrapperRef.current) return null;
@@ -238,11 +383,14 @@ export function StreamViewer({ udid }: StreamViewerProps) {
     <div
       ref={wrapperRef}
       className="relative h-full w-full flex items-center justify-center overflow-hidden"
-      onMouseDown={handleMouseDown}
-      onMouseMove={handleMouseMove}
-      onMouseUp={handleMouseUp}
-      onMouseLeave={handleMouseLeave}
-      style={{ cursor: "pointer" }}
+      onPointerDown={handlePointerDown}
+      onPointerMove={handlePointerMove}
+      onPointerUp={handlePointerUp}
+      onPointerCancel={handlePointerUp}
+      onDragOver={handleDragOver}
+      onDragLeave={() => setIsDropTarget(false)}
+      onDrop={handleDrop}
+      style={{ cursor: interactive ? "pointer" : "default", touchAction: "none" }}
     >
       {isLoading && (
         <div className="absolute inset-0 flex items-center justify-center bg-black/30 rounded-xl z-10">
@@ -269,6 +417,23 @@ export function StreamViewer({ udid }: StreamViewerProps) {
         <div style={getTouchIndicatorStyle()!} />
       )}
 
+      {isDropTarget && (
+        <div className="absolute inset-0 flex items-center justify-center bg-black/40 rounded-xl z-20 pointer-events-none">
+          <div className="flex flex-col items-center gap-2 text-white text-sm">
+            <ImagePlus className="w-8 h-8" />
+            Drop photos or videos to add them to the library
+          </div>
+        </div>
+      )}
+
+      {mediaStatus && (
+        <div className="absolute top-2 left-1/2 -translate-x-1/2 text-xs text-white bg-black/70 px-2 py-1 rounded z-20">
+          {mediaStatus}
+        </div>
+      )}
+
+      {showStats && !isLoading && <StreamStatsOverlay stats={hudStats} appMetrics={appMetrics} />}
+
       {dimensions && (
         <div className="absolute bottom-2 left-2 text-xs text-white/70 bg-black/50 px-2 py-1 rounded">
           {dimensions}
@@ -0,0 +1,142 @@
+import { useEffect, useState } from "react"
+import { ChevronDown, ChevronUp, Loader2, Paperclip } from "lucide-react"
+import { api, type TestAttachment, type TestCaseResult, type TestRun } from "@/lib/api"
+import { formatSize } from "@/lib/bundle-size"
+import { showErrorToast } from "@/lib/toast"
+
+interface TestFailuresProps {
+  projectPath: string
+}
+
+interface LoadedFailures {
+  run: TestRun | null
+  failed: TestCaseResult[]
+  attachments: Map<string, TestAttachment[]>
+}
+
+function AttachmentLink({ runId, attachment }: { runId: string; attachment: TestAttachment }) {
+  const url = api.tests.attachmentUrl(runId, attachment.fileName)
+  if (attachment.contentType.startsWith("image/") && attachment.contentType !== "image/heic") {
+    return (
+      <a href={url} target="_blank" rel="noreferrer" title={attachment.name}>
+        <img
+          src={url}
+          alt={attachment.name}
+          loading="lazy"
+          className="h-32 rounded border object-contain bg-black/20 hover:opacity-80"
+        />
+      </a>
+    )
+  }
+  return (
+    <a
+      href={url}
+      target="_blank"
+      rel="noreferrer"
+      className="text-xs flex items-center gap-1 underline text-muted-foreground hover:text-foreground"
+    >
+      <Paperclip className="w-3 h-3 shrink-0" />
+      <span className="truncate">{attachment.name}</span>
+      <span className="shrink-0">({formatSize(attachment.bytes)})</span>
+    </a>
+  )
+}
+
+/**
+ * Collapsible list of the tests that failed in the project's last test run,
+ * with their failure messages and attachments like failure screenshots,
+ * loaded when first opened
+ */
+export function TestFailures({ projectPath }: TestFailuresProps) {
+  const [isOpen, setIsOpen] = useState(false)
+  const [loaded, setLoaded] = useState<LoadedFailures | null>(null)
+
+  useEffect(() => {
+    setLoaded(null)
+  }, [projectPath])
+
+  useEffect(() => {
+    if (!isOpen || loaded) return
+    let cancelled = false
+    const load = async (): Promise<LoadedFailures> => {
+      const [run] = await api.tests.runs(projectPath, 1)
+      const failed = run?.tests.filter((test) => test.status === "failed" || test.flaky) ?? []
+      const attachments = new Map<string, TestAttachment[]>()
+      if (run && failed.length > 0 && run.resultBundlePath) {
+        const { tests } = await api.tests.attachments(run.id)
+        for (const test of tests) {
+          attachments.set(test.identifier, test.attachments)
+        }
+      }
+      return { run: run ?? null, failed, attachments }
+    }
+    load()
+      .then((result) => {
+        if (!cancelled) setLoaded(result)
+      })
+      .catch((err) => {
+        if (cancelled) return
+        showErrorToast("Couldn't load the last test run", err)
+        setIsOpen(false)
+      })
+    return () => {
+      cancelled = true
+    }
+  }, [isOpen, loaded, projectPath])
+
+  return (
+    <div className="flex flex-col gap-2">
+      <button
+        className="text-xs text-muted-foreground hover:text-foreground flex items-center gap-1 self-start"
+        onClick={() => setIsOpen(!isOpen)}
+      >
+        {isOpen ? <ChevronUp className="w-3 h-3" /> : <ChevronDown className="w-3 h-3" />}
+        Test failures
+        {loaded?.run && ` (${loaded.failed.length})`}
+      </button>
+      {isOpen && (
+        loaded === null ? (
+          <div className="flex items-center gap-2 text-xs text-muted-foreground">
+            <Loader2 className="w-3 h-3 animate-spin" />
+            Loading the last test run...
+          </div>
+        ) : !loaded.run ? (
+          <p className="text-xs text-muted-foreground">This project hasn't been tested yet.</p>
+        ) : loaded.failed.length === 0 ? (
+          <p className="text-xs text-muted-foreground">
+            No tests failed in the last run ({new Date(loaded.run.startedAt).toLocaleString()}).
+          </p>
+        ) : (
+          <div className="flex flex-col gap-3">
+            {loaded.failed.map((test) => (
+              <div key={test.identifier} className="flex flex-col gap-1">
+                <span className="text-xs font-mono truncate" title={test.identifier}>
+                  {test.identifier}
+                  {test.flaky && <span className="text-yellow-500"> (flaky)</span>}
+                </span>
+                {test.failures.map((failure, index) => (
+                  <span key={index} className="text-xs text-red-400">
+                    {failure.message}
+                    {failure.file && (
+                      <span className="text-muted-foreground">
+                        {" "}
+                        {failure.file.split("/").pop()}:{failure.line}
+                      </span>
+                    )}
+                  </span>
+                ))}
+                {(loaded.attachments.get(test.identifier) ?? []).length > 0 && (
+                  <div className="flex flex-wrap gap-2">
+                    {loaded.attachments.get(test.identifier)!.map((attachment) => (
+                      <AttachmentLink key={attachment.fileName} runId={loaded.run!.id} attachment={attachment} />
+                    ))}
+                  </div>
+                )}
+              </div>
+            ))}
+          </div>
+        )
+      )}
+    </div>
+  )
+}
@@ -0,0 +1,36 @@
+import { Monitor, Moon, Sun } from "lucide-react"
+import { Button } from "@/components/ui/button"
+import { setThemePreference, useThemePreference, type ThemePreference } from "@/lib/theme"
+
+const NEXT: Record<ThemePreference, ThemePreference> = {
+  system: "light",
+  light: "dark",
+  dark: "system",
+}
+
+const LABELS: Record<ThemePreference, string> = {
+  system: "Theme: System",
+  light: "Theme: Light",
+  dark: "Theme: Dark",
+}
+
+/**
+ * Cycles between following the system appearance and the manual overrides
+ */
+export function ThemeToggle() {
+  const preference = useThemePreference()
+  const Icon = preference === "light" ? Sun : preference === "dark" ? Moon : Monitor
+
+  return (
+    <Button
+      variant="ghost"
+      size="icon-sm"
+      onClick={() => setThemePreference(NEXT[preference])}
+      aria-label={LABELS[preference]}
+      title={LABELS[preference]}
+      style={{ WebkitAppRegion: "no-drag" } as React.CSSProperties}
+    >
+      <Icon className="w-4 h-4" />
+    </Button>
+  )
+}
@@ -0,0 +1,91 @@
+import * as React from "react"
+import { cva } from "class-variance-authority"
+import { AlertTriangle, CheckCircle2, Info, X, XCircle } from "lucide-react"
+
+import { Button } from "@/components/ui/button"
+import { dismissToast, useToasts, type Toast, type ToastSeverity } from "@/lib/toast"
+import { cn } from "@/lib/utils"
+
+const toastVariants = cva(
+  "pointer-events-auto flex w-[360px] items-start gap-3 rounded-lg border bg-popover p-3 text-popover-foreground shadow-lg",
+  {
+    variants: {
+      severity: {
+        info: "",
+        success: "border-green-500/40",
+        warning: "border-yellow-500/40",
+        error: "border-destructive/50",
+      },
+    },
+    defaultVariants: {
+      severity: "info",
+    },
+  }
+)
+
+const ICONS: Record<ToastSeverity, { icon: React.ElementType; className: string }> = {
+  info: { icon: Info, className: "text-muted-foreground" },
+  success: { icon: CheckCircle2, className: "text-green-500" },
+  warning: { icon: AlertTriangle, className: "text-yellow-500" },
+  error: { icon: XCircle, className: "text-destructive" },
+}
+
+function ToastItem({ toast }: { toast: Toast }) {
+  const { icon: Icon, className } = ICONS[toast.severity]
+
+  return (
+    <div
+      data-slot="toast"
+      role={toast.severity === "error" ? "alert" : "status"}
+      className={cn(toastVariants({ severity: toast.severity }))}
+    >
+      <Icon className={cn("mt-0.5 size-4 shrink-0", className)} />
+      <div className="flex min-w-0 flex-1 flex-col gap-1">
+        <span className="text-sm font-medium">{toast.title}</span>
+        {toast.description && (
+          <span className="text-xs text-muted-foreground break-words">{toast.description}</span>
+        )}
+        {toast.action && (
+          <Button
+            variant="outline"
+            size="sm"
+            className="mt-1 h-7 self-start text-xs"
+            onClick={() => {
+              toast.action?.onClick()
+              dismissToast(toast.id)
+            }}
+          >
+            {toast.action.label}
+          </Button>
+        )}
+      </div>
+      <button
+        aria-label="Dismiss"
+        onClick={() => dismissToast(toast.id)}
+        className="rounded p-0.5 text-muted-foreground hover:bg-secondary/50 hover:text-foreground"
+      >
+        <X className="size-3.5" />
+      </button>
+    </div>
+  )
+}
+
+/**
+ * Renders queued toasts in the bottom-right corner. Mount once near the app root.
+ */
+function Toaster() {
+  const toasts = useToasts()
+
+  return (
+    <div
+      data-slot="toaster"
+      className="pointer-events-none fixed bottom-4 right-4 z-50 flex flex-col gap-2"
+    >
+      {toasts.map((toast) => (
+        <ToastItem key={toast.id} toast={toast} />
+      ))}
+    </div>
+  )
+}
+
+export { Toaster, toastVariants }
@@ -2,6 +2,124 @@
  * API client - uses HTTP/WebSocket to communicate with the backend server
  */
 
+import type {
+  AccessibilityElement,
+  AgentAction,
+  AgentActResponse,
+  AndroidDevice,
+  AndroidLaunchRequest,
+  AndroidValidationResult,
+  AppLogEntry,
+  AppMetricsEvent,
+  AppMetricsSummary,
+  AppSettings,
+  AppTemplateInfo,
+  AutomationEvent,
+  AutomationScript,
+  BatchBuild,
+  BatchBuildEvent,
+  BuildEnvironmentDiff,
+  BuildEvent,
+  BuildProduct,
+  BuildQueueStatus,
+  BuildStreamRequest,
+  BuildSummary,
+  BuildTrigger,
+  BundleSizeReport,
+  CleanupRequest,
+  CoverageReport,
+  CrashReport,
+  CrashReportSummary,
+  CreateBuildTriggerRequest,
+  CreateProjectRequest,
+  CreateProjectResponse,
+  CreateSimulatorPairRequest,
+  DebugOption,
+  DebugOptionsState,
+  DefaultsValue,
+  DeviceLogEntry,
+  DeviceRunStatus,
+  DeviceTypeCatalog,
+  DiscoverProjectRequest,
+  DiscoverProjectResponse,
+  DiskUsageReport,
+  EnvironmentCheck,
+  EnvironmentCheckId,
+  ExportFormat,
+  FileLineCoverage,
+  FixtureSummary,
+  FlakyTestSummary,
+  GetLaunchableProductsRequest,
+  GetLaunchableProductsResponse,
+  GetRecentProjectsRequest,
+  GetRecentProjectsResponse,
+  HardwareButton,
+  InstalledApp,
+  InstallOnPairRequest,
+  InstallOnPairResponse,
+  LaunchAppRequest,
+  LaunchAppResponse,
+  LaunchMultipleRequest,
+  ListSimulatorsResponse,
+  LogFilterProfile,
+  MacLaunchRequest,
+  MacLaunchResponse,
+  MultiDeviceEvent,
+  NetworkConditions,
+  NetworkConditionState,
+  NetworkPreset,
+  NewAppRequest,
+  NewAppResponse,
+  PrewarmStatus,
+  ProductDetails,
+  ProjectSettings,
+  ProvisioningProfile,
+  RecordingResponse,
+  ScreenshotMatrixEvent,
+  SeedFixturesRequest,
+  SeedFixturesResponse,
+  SigningIdentity,
+  Simulator,
+  SimulatorPair,
+  SourceLocation,
+  StartStreamRequest,
+  StartWatchRequest,
+  StopInteractionRecordingResponse,
+  StreamFrame,
+  StreamLogEvent,
+  StreamSession,
+  StreamShare,
+  StreamStats,
+  SwipeRequest,
+  TapElementRequest,
+  TapElementResponse,
+  TapRequest,
+  TestFlightEvent,
+  TestFlightUploadRequest,
+  TestRun,
+  TestRunAttachments,
+  TestRunEvent,
+  TestRunRequest,
+  TouchEvent,
+  TriggerBuildEvent,
+  UnifiedProject,
+  UserDefaults,
+  ValidateProjectRequest,
+  ValidateProjectResponse,
+  VisualCheckRequest,
+  VisualCheckResult,
+  WarningsReport,
+  WatchStatus,
+  Workspace,
+  WorkspaceProjectSelection,
+  XcodeProject,
+  XcodeValidationResult,
+} from '@shared/ipc-types';
+import { showToast } from './toast';
+
+// Request, response, and event types are shared with the server
+export type * from '@shared/ipc-types';
+
 const API_BASE = 'http://localhost:3001';
 const WS_BASE = 'ws://localhost:3001';
 
@@ -9,6 +127,15 @@ const WS_BASE = 'ws://localhost:3001';
 let ws: WebSocket | null = null;
 let wsReconnectTimer: ReturnType<typeof setTimeout> | null = null;
 const wsListeners = new Map<string, Set<(payload: unknown) => void>>();
+// Whether the server has gone away since we last had a connection
+let wsConnectionLost = false;
+let wsEverConnected = false;
+// Whether the server said it was shutting down before the connection dropped
+let wsServerStopped = false;
+// Log channels this page follows, keyed by UDID ('*' for all), with listener counts
+const logSubscriptions = new Map<string, number>();
+// Tokens of the stream sessions this page started, by UDID
+const streamTokens = new Map<string, string>();
 
 function connectWebSocket(): WebSocket {
   if (ws && ws.readyState === WebSocket.OPEN) {
@@ -19,6 +146,15 @@ function connectWebSocket(): WebSocket {
 
   ws.onopen = () => {
     console.log('[api] WebSocket connected');
+    if (wsConnectionLost) {
+      wsConnectionLost = false;
+      showToast({ title: 'Reconnected to the Plasma server', severity: 'success' });
+    }
+    wsEverConnected = true;
+    // Subscriptions live on the connection, so restore them after a reconnect
+    for (const key of logSubscriptions.keys()) {
+      ws?.send(JSON.stringify({ type: 'simulator:logs:subscribe', payload: { udid: key === '*' ? undefined : key } }));
+    }
     if (wsReconnectTimer) {
       clearTimeout(wsReconnectTimer);
       wsReconnectTimer = null;
@@ -28,6 +164,16 @@ function connectWebSocket(): WebSocket {
   ws.onmessage = (event) => {
     try {
       const { type, payload } = JSON.parse(event.data);
+      if (type === 'server:shutdown') {
+        wsServerStopped = true;
+      }
+      if (type === 'error') {
+        showToast({
+          title: 'Server error',
+          description: (payload as { message?: string } | undefined)?.message,
+          severity: 'error',
+        });
+      }
       const listeners = wsListeners.get(type);
       if (listeners) {
         for (const listener of listeners) {
@@ -42,6 +188,16 @@ function connectWebSocket(): WebSocket {
   ws.onclose = () => {
     console.log('[api] WebSocket disconnected');
     ws = null;
+    // Only report the first drop, not every failed reconnect attempt
+    if (wsEverConnected && !wsConnectionLost) {
+      wsConnectionLost = true;
+      showToast({
+        title: wsServerStopped ? 'The Plasma server shut down' : 'Lost connection to the Plasma server',
+        description: 'Reconnecting…',
+        severity: 'warning',
+      });
+    }
+    wsServerStopped = false;
     // Reconnect after 2 seconds
     if (!wsReconnectTimer) {
       wsReconnectTimer = setTimeout(() => {
@@ -83,6 +239,26 @@ function sendWsMessage(type: string, payload: unknown): void {
   }
 }
 
+/**
+ * Error returned by the server, carrying its machine-readable code
+ */
+export class ApiError extends Error {
+  readonly code: string;
+  readonly status: number;
+
+  constructor(message: string, code: string, status: number) {
+    super(message);
+    this.name = 'ApiError';
+    this.code = code;
+    this.status = status;
+  }
+}
+
+async function toApiError(response: Response): Promise<ApiError> {
+  const body = await response.json().catch(() => ({ error: response.statusText }));
+  return new ApiError(body.error || 'Request failed', body.code || 'internal', response.status);
+}
+
 async function httpPost<T>(path: string, body: unknown): Promise<T> {
   const response = await fetch(`${API_BASE}${path}`, {
     method: 'POST',
@@ -90,8 +266,7 @@ async function httpPost<T>(path: string, body: unknown): Promise<T> {
     body: JSON.stringify(body),
   });
   if (!response.ok) {
-    const error = await response.json().catch(() => ({ error: response.statusText }));
-    throw new Error(error.error || 'Request failed');
+    throw await toApiError(response);
   }
   return response.json();
 }
@@ -99,177 +274,25 @@ async function httpPost<T>(path: string, body: unknown): Promise<T> {
 async function httpGet<T>(path: string): Promise<T> {
   const response = await fetch(`${API_BASE}${path}`);
   if (!response.ok) {
-    const error = await response.json().catch(() => ({ error: response.statusText }));
-    throw new Error(error.error || 'Request failed');
+    throw await toApiError(response);
   }
   return response.json();
 }
 
-// Types
-export type Platform = 'ios' | 'android';
-
-interface ValidateProjectRequest {
-  path: string;
-}
-
-interface Project {
-  path: string;
-  name: string;
-  type: 'xcode' | 'android';
-  platforms: Platform[];
-  valid: boolean;
-}
-
-interface ValidateProjectResponse {
-  project: Project | null;
-  error?: string;
-}
-
-interface GetRecentProjectsRequest {
-  query?: string;
-  limit?: number;
-}
-
-export interface ProjectRecord {
-  id: number;
-  path: string;
-  name: string;
-  platforms: Platform[];
-  last_opened_at: string | null;
-  created_at: string | null;
-}
-
-type GetRecentProjectsResponse = ProjectRecord[];
-
-// New unified project types
-export interface UnifiedProject {
-  id: number;
-  name: string;
-  xcode_path: string | null;
-  android_path: string | null;
-  last_opened_at: string | null;
-  created_at: string | null;
-}
-
-interface CreateProjectRequest {
-  name: string;
-  xcodePath?: string;
-  androidPath?: string;
-  directory?: string;
-}
-
-interface CreateProjectResponse {
-  project?: UnifiedProject;
-  error?: string;
-}
-
-interface XcodeValidationResult {
-  valid: boolean;
-  path: string | null;
-  type: 'workspace' | 'project' | null;
-  error?: string;
-}
-
-interface AndroidValidationResult {
-  valid: boolean;
-  path: string | null;
-  error?: string;
-}
-
-interface DiscoverProjectRequest {
-  path: string;
-}
-
-interface XcodeProject {
-  path: string;
-  projectType: 'project' | 'workspace';
-  schemes: string[];
-  targets: string[];
-  configurations: string[];
-}
-
-type DiscoverProjectResponse = XcodeProject;
-
-interface BuildStreamRequest {
-  path: string;
-  scheme: string;
-}
-
-interface BuildProduct {
-  name: string;
-  path: string;
-}
-
-interface BuildEvent {
-  type: 'started' | 'output' | 'completed' | 'error';
-  scheme?: string;
-  projectPath?: string;
-  line?: string;
-  success?: boolean;
-  buildDir?: string;
-  products?: BuildProduct[];
-  message?: string;
-}
-
-interface GetLaunchableProductsRequest {
-  buildDir: string;
-}
-
-type GetLaunchableProductsResponse = BuildProduct[];
-
-interface Simulator {
-  udid: string;
-  name: string;
-  state: string;
-  runtime: string;
-}
-
-type ListSimulatorsResponse = Simulator[];
-
-interface LaunchAppRequest {
-  udid: string;
-  appPath: string;
-  bundleId?: string;
-}
-
-interface LaunchAppResponse {
-  message: string;
-}
-
-interface TapRequest {
-  udid: string;
-  x: number;
-  y: number;
-  screenWidth: number;
-  screenHeight: number;
-}
-
-interface SwipeRequest {
-  udid: string;
-  startX: number;
-  startY: number;
-  endX: number;
-  endY: number;
-  screenWidth: number;
-  screenHeight: number;
-  duration?: number;
-}
-
-interface StartStreamRequest {
-  udid: string;
-  fps?: number;
-  quality?: number;
-}
-
-interface StreamFrame {
-  udid: string;
-  frame: string;
+async function httpGetText(path: string): Promise<string> {
+  const response = await fetch(`${API_BASE}${path}`);
+  if (!response.ok) {
+    throw await toApiError(response);
+  }
+  return response.text();
 }
 
-interface StreamLogEvent {
-  type: 'info' | 'error' | 'debug' | 'frame';
-  message?: string;
-  frameNumber?: number;
+async function httpDelete<T>(path: string): Promise<T> {
+  const response = await fetch(`${API_BASE}${path}`, { method: 'DELETE' });
+  if (!response.ok) {
+    throw await toApiError(response);
+  }
+  return response.json();
 }
 
 /**
@@ -329,6 +352,15 @@ export const api = {
       return httpPost('/api/projects/create', request);
     },
 
+    // SwiftUI apps scaffolded from a template
+    templates: async (): Promise<AppTemplateInfo[]> => {
+      return httpGet('/api/projects/templates');
+    },
+
+    newApp: async (request: NewAppRequest): Promise<NewAppResponse> => {
+      return httpPost('/api/projects/new', request);
+    },
+
     getRecentUnified: async (limit?: number): Promise<UnifiedProject[]> => {
       const params = limit ? `?limit=${limit}` : '';
       return httpGet(`/api/projects/unified/recent${params}`);
@@ -338,6 +370,14 @@ export const api = {
       return httpGet(`/api/projects/unified/${id}`);
     },
 
+    getSettings: async (id: number): Promise<ProjectSettings> => {
+      return httpGet(`/api/projects/${id}/settings`);
+    },
+
+    updateSettings: async (id: number, settings: Partial<Record<keyof ProjectSettings, string | null>>): Promise<ProjectSettings> => {
+      return httpPost(`/api/projects/${id}/settings`, settings);
+    },
+
     validateXcode: async (path: string): Promise<XcodeValidationResult> => {
       return httpPost('/api/validate/xcode', { path });
     },
@@ -347,6 +387,41 @@ export const api = {
     },
   },
 
+  // Workspaces API (projects open side by side, each with its own scheme and device)
+  workspaces: {
+    list: async (): Promise<Workspace[]> => {
+      return httpGet('/api/workspaces');
+    },
+
+    get: async (id: string): Promise<Workspace> => {
+      return httpGet(`/api/workspaces/${encodeURIComponent(id)}`);
+    },
+
+    create: async (name: string, projectIds: number[] = []): Promise<Workspace> => {
+      return httpPost('/api/workspaces', { name, projectIds });
+    },
+
+    update: async (id: string, changes: { name?: string; activeProjectId?: number | null }): Promise<Workspace> => {
+      return httpPost(`/api/workspaces/${encodeURIComponent(id)}`, changes);
+    },
+
+    delete: async (id: string): Promise<void> => {
+      await httpDelete(`/api/workspaces/${encodeURIComponent(id)}`);
+    },
+
+    openProject: async (id: string, projectId: number, selection: WorkspaceProjectSelection = {}): Promise<Workspace> => {
+      return httpPost(`/api/workspaces/${encodeURIComponent(id)}/projects`, { projectId, ...selection });
+    },
+
+    updateProject: async (id: string, projectId: number, selection: WorkspaceProjectSelection): Promise<Workspace> => {
+      return httpPost(`/api/workspaces/${encodeURIComponent(id)}/projects/${projectId}`, selection);
+    },
+
+    closeProject: async (id: string, projectId: number): Promise<Workspace> => {
+      return httpDelete(`/api/workspaces/${encodeURIComponent(id)}/projects/${projectId}`);
+    },
+  },
+
   // Xcode API
   xcode: {
     discover: async (request: DiscoverProjectRequest): Promise<DiscoverProjectResponse> => {
@@ -357,6 +432,10 @@ export const api = {
       sendWsMessage('xcode:build:start', request);
     },
 
+    purgePackageCache: async (): Promise<{ removed: string[]; bytes: number }> => {
+      return httpPost('/api/xcode/packages/purge-cache', {});
+    },
+
     onBuildEvent: (callback: (event: BuildEvent) => void): (() => void) => {
       connectWebSocket();
       return addWsListener('xcode:build:event', callback as (payload: unknown) => void);
@@ -365,6 +444,101 @@ export const api = {
     getLaunchableProducts: async (request: GetLaunchableProductsRequest): Promise<GetLaunchableProductsResponse> => {
       return httpPost('/api/xcode/launchable-products', request);
     },
+
+    productDetails: async (productPath: string): Promise<ProductDetails> => {
+      return httpGet(`/api/xcode/product-details?path=${encodeURIComponent(productPath)}`);
+    },
+
+    bundleSize: async (productPath: string, linkMapPath?: string): Promise<BundleSizeReport> => {
+      const linkMap = linkMapPath ? `&linkMap=${encodeURIComponent(linkMapPath)}` : '';
+      return httpGet(`/api/xcode/bundle-size?path=${encodeURIComponent(productPath)}${linkMap}`);
+    },
+  },
+
+  // Code signing identities and provisioning profiles installed on this Mac
+  signing: {
+    identities: async (): Promise<SigningIdentity[]> => {
+      const { identities } = await httpGet<{ identities: SigningIdentity[] }>('/api/signing/identities');
+      return identities;
+    },
+
+    profiles: async (): Promise<ProvisioningProfile[]> => {
+      const { profiles } = await httpGet<{ profiles: ProvisioningProfile[] }>('/api/signing/profiles');
+      return profiles;
+    },
+  },
+
+  testflight: {
+    // Resolves with the completed or error event; uploads take minutes and processing longer
+    upload: async (
+      request: TestFlightUploadRequest,
+      onEvent: (event: TestFlightEvent) => void,
+      signal?: AbortSignal
+    ): Promise<TestFlightEvent | null> => {
+      const response = await fetch(`${API_BASE}/api/testflight/upload`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(request),
+        signal,
+      });
+      if (!response.ok || !response.body) {
+        throw await toApiError(response);
+      }
+
+      const reader = response.body.getReader();
+      const decoder = new TextDecoder();
+      let buffered = '';
+      let result: TestFlightEvent | null = null;
+      for (;;) {
+        const { done, value } = await reader.read();
+        buffered += decoder.decode(value, { stream: !done });
+        const lines = buffered.split('\n');
+        buffered = lines.pop() ?? '';
+        for (const line of lines) {
+          if (!line.trim()) continue;
+          const event = JSON.parse(line) as TestFlightEvent;
+          if (event.type === 'completed' || event.type === 'error') {
+            result = event;
+          }
+          onEvent(event);
+        }
+        if (done) return result;
+      }
+    },
+  },
+
+  // Mac API, for apps built with the macos or mac-catalyst platform
+  mac: {
+    launch: async (request: MacLaunchRequest): Promise<MacLaunchResponse> => {
+      return httpPost('/api/mac/launch', request);
+    },
+
+    terminate: async (bundleId: string): Promise<{ terminated: boolean }> => {
+      return httpPost('/api/mac/terminate', { bundleId });
+    },
+
+    // Needs the Screen Recording permission
+    screenshot: async (pid: number): Promise<Blob> => {
+      const response = await fetch(`${API_BASE}/api/mac/screenshot?pid=${pid}`);
+      if (!response.ok) {
+        throw await toApiError(response);
+      }
+      return response.blob();
+    },
+  },
+
+  // Fixture sets from plasma.toml seeded into a simulator
+  fixtures: {
+    list: async (projectPath: string): Promise<FixtureSummary[]> => {
+      const { fixtures } = await httpGet<{ fixtures: FixtureSummary[] }>(
+        `/api/fixtures?projectPath=${encodeURIComponent(projectPath)}`
+      );
+      return fixtures;
+    },
+
+    seed: async (request: SeedFixturesRequest): Promise<SeedFixturesResponse> => {
+      return httpPost('/api/fixtures/seed', request);
+    },
   },
 
   // Simulator API
@@ -373,34 +547,779 @@ export const api = {
       return httpGet('/api/simulator/list');
     },
 
+    // Small preview of the screen, at most a couple of seconds old
+    thumbnailUrl: (udid: string, width?: number): string => {
+      const params = new URLSearchParams({ udid });
+      if (width) params.set('width', String(width));
+      return `${API_BASE}/api/simulator/thumbnail?${params}`;
+    },
+
+    // Device types and runtimes a simulator can be created from
+    deviceTypes: async (): Promise<DeviceTypeCatalog> => {
+      return httpGet('/api/simulators/devicetypes');
+    },
+
     launch: async (request: LaunchAppRequest): Promise<LaunchAppResponse> => {
       return httpPost('/api/simulator/launch', request);
     },
 
+    prewarmStatus: async (): Promise<PrewarmStatus> => {
+      return httpGet('/api/simulator/prewarm');
+    },
+
+    // Boot a project's preferred simulator now, even with the setting off
+    prewarm: async (projectId: number): Promise<PrewarmStatus> => {
+      return httpPost('/api/simulator/prewarm', { projectId });
+    },
+
+    apps: async (udid: string): Promise<InstalledApp[]> => {
+      return httpGet(`/api/simulator/apps?udid=${encodeURIComponent(udid)}`);
+    },
+
+    appInfo: async (udid: string, bundleId: string): Promise<InstalledApp> => {
+      return httpGet(
+        `/api/simulator/apps?udid=${encodeURIComponent(udid)}&bundleId=${encodeURIComponent(bundleId)}`
+      );
+    },
+
+    launchApp: async (udid: string, bundleId: string): Promise<LaunchAppResponse> => {
+      return httpPost('/api/simulator/apps/launch', { udid, bundleId });
+    },
+
+    userDefaults: async (udid: string, bundleId: string): Promise<UserDefaults> => {
+      return httpGet(
+        `/api/simulator/apps/defaults?udid=${encodeURIComponent(udid)}&bundleId=${encodeURIComponent(bundleId)}`
+      );
+    },
+
+    // Keys set to null are removed; the app is relaunched unless relaunch is false
+    writeUserDefaults: async (
+      udid: string,
+      bundleId: string,
+      values: Record<string, DefaultsValue | null>,
+      relaunch = true
+    ): Promise<UserDefaults> => {
+      return httpPost('/api/simulator/apps/defaults', { udid, bundleId, values, relaunch });
+    },
+
+    // Erases the app's data container, NSUserDefaults included
+    resetAppData: async (udid: string, bundleId: string): Promise<void> => {
+      await httpPost('/api/simulator/apps/reset-data', { udid, bundleId });
+    },
+
+    resetUserDefaults: async (udid: string, bundleId: string): Promise<void> => {
+      await httpPost('/api/simulator/apps/reset-defaults', { udid, bundleId });
+    },
+
+    resetKeychain: async (udid: string): Promise<void> => {
+      await httpPost('/api/simulator/keychain/reset', { udid });
+    },
+
+    // Resolves with every device's final status; events arrive as each device progresses.
+    // Start a stream per device with startStream once it is streaming.
+    launchMultiple: async (
+      request: LaunchMultipleRequest,
+      onEvent: (event: MultiDeviceEvent) => void,
+      signal?: AbortSignal
+    ): Promise<DeviceRunStatus[]> => {
+      const response = await fetch(`${API_BASE}/api/simulator/launch-multiple`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(request),
+        signal,
+      });
+      if (!response.ok || !response.body) {
+        throw await toApiError(response);
+      }
+
+      const reader = response.body.getReader();
+      const decoder = new TextDecoder();
+      let buffered = '';
+      let devices: DeviceRunStatus[] = [];
+      for (;;) {
+        const { done, value } = await reader.read();
+        buffered += decoder.decode(value, { stream: !done });
+        const lines = buffered.split('\n');
+        buffered = lines.pop() ?? '';
+        for (const line of lines) {
+          if (!line.trim()) continue;
+          const event = JSON.parse(line) as MultiDeviceEvent;
+          if (event.type === 'completed') {
+            devices = event.devices;
+          }
+          onEvent(event);
+        }
+        if (done) return devices;
+      }
+    },
+
+    pairs: async (): Promise<SimulatorPair[]> => {
+      return httpGet('/api/simulator/pairs');
+    },
+
+    createPair: async (request: CreateSimulatorPairRequest): Promise<SimulatorPair> => {
+      return httpPost('/api/simulator/pairs', request);
+    },
+
+    deletePair: async (pairId: string): Promise<void> => {
+      await httpDelete(`/api/simulator/pairs?pairId=${encodeURIComponent(pairId)}`);
+    },
+
+    bootPair: async (pairId: string): Promise<SimulatorPair> => {
+      return httpPost('/api/simulator/pairs/boot', { pairId });
+    },
+
+    installOnPair: async (request: InstallOnPairRequest): Promise<InstallOnPairResponse> => {
+      return httpPost('/api/simulator/pairs/install', request);
+    },
+
+    touch: async (request: TouchEvent): Promise<void> => {
+      await httpPost('/api/simulator/touch', { ...request, token: streamTokens.get(request.udid) });
+    },
+
     tap: async (request: TapRequest): Promise<void> => {
-      await httpPost('/api/simulator/tap', request);
+      await httpPost('/api/simulator/tap', { ...request, token: streamTokens.get(request.udid) });
     },
 
     swipe: async (request: SwipeRequest): Promise<void> => {
-      await httpPost('/api/simulator/swipe', request);
+      await httpPost('/api/simulator/swipe', { ...request, token: streamTokens.get(request.udid) });
+    },
+
+    pressButton: async (udid: string, button: HardwareButton): Promise<void> => {
+      await httpPost('/api/simulator/button', { udid, button, token: streamTokens.get(udid) });
+    },
+
+    rotate: async (udid: string, rotation: number): Promise<void> => {
+      await httpPost('/api/simulator/rotate', { udid, rotation, token: streamTokens.get(udid) });
+    },
+
+    // Accessibility hierarchy of the foreground app; pass a point (in points) for the element under it
+    accessibility: async (udid: string, point?: { x: number; y: number }): Promise<AccessibilityElement[]> => {
+      const query = point ? `&x=${point.x}&y=${point.y}` : '';
+      const { elements } = await httpGet<{ elements: AccessibilityElement[] }>(
+        `/api/simulator/accessibility?udid=${encodeURIComponent(udid)}${query}`
+      );
+      return elements;
+    },
+
+    tapElement: async (request: TapElementRequest): Promise<TapElementResponse> => {
+      return httpPost('/api/simulator/actions/tap-element', { ...request, token: streamTokens.get(request.udid) });
+    },
+
+    startInteractionRecording: async (udid: string): Promise<void> => {
+      await httpPost('/api/simulator/interactions/start', { udid });
+    },
+
+    stopInteractionRecording: async (udid: string, testName?: string): Promise<StopInteractionRecordingResponse> => {
+      return httpPost('/api/simulator/interactions/stop', { udid, testName });
+    },
+
+    shake: async (udid: string): Promise<void> => {
+      await httpPost('/api/simulator/shake', { udid, token: streamTokens.get(udid) });
+    },
+
+    simulateMemoryWarning: async (udid: string): Promise<void> => {
+      await httpPost('/api/simulator/action', { udid, action: 'memory-warning', token: streamTokens.get(udid) });
     },
 
+    screenshot: async (udid: string): Promise<Blob> => {
+      const response = await fetch(`${API_BASE}/api/simulator/screenshot?udid=${encodeURIComponent(udid)}`);
+      if (!response.ok) {
+        throw await toApiError(response);
+      }
+      return response.blob();
+    },
+
+    // Photos, videos, and vCards at paths on the machine running Plasma
+    addMedia: async (udid: string, paths: string[]): Promise<void> => {
+      await httpPost('/api/simulator/media', { udid, paths });
+    },
+
+    uploadMedia: async (udid: string, file: File): Promise<void> => {
+      const params = new URLSearchParams({ udid, filename: file.name });
+      const response = await fetch(`${API_BASE}/api/simulator/media/upload?${params}`, {
+        method: 'POST',
+        headers: { 'Content-Type': file.type || 'application/octet-stream' },
+        body: file,
+      });
+      if (!response.ok) {
+        throw await toApiError(response);
+      }
+    },
+
+    startRecording: async (udid: string): Promise<RecordingResponse> => {
+      return httpPost('/api/simulator/recording/start', { udid });
+    },
+
+    stopRecording: async (udid: string): Promise<RecordingResponse> => {
+      return httpPost('/api/simulator/recording/stop', { udid });
+    },
+
+    // Creates the session (or joins the running one) for its token, then streams it
     startStream: async (request: StartStreamRequest): Promise<void> => {
-      sendWsMessage('simulator:stream:start', request);
+      const { token } = await httpPost<StreamSession>('/api/simulator/session', request);
+      streamTokens.set(request.udid, token);
+      sendWsMessage('simulator:stream:start', { ...request, token });
     },
 
     stopStream: async (udid: string): Promise<void> => {
       sendWsMessage('simulator:stream:stop', { udid });
     },
 
+    // Stop the session's frames while nobody can see them; simulator-server keeps running
+    pauseSession: async (udid: string): Promise<void> => {
+      await httpPost('/api/simulator/session/pause', { udid });
+    },
+
+    // Stream the paused session again; starting a stream of it does the same
+    resumeSession: async (udid: string): Promise<void> => {
+      await httpPost('/api/simulator/session/resume', { udid });
+    },
+
+    // Shut down the simulator-server session instead of leaving it for idle eviction
+    stopSession: async (udid: string): Promise<void> => {
+      await httpDelete(`/api/simulator/session?udid=${encodeURIComponent(udid)}`);
+      streamTokens.delete(udid);
+    },
+
+    network: async (udid: string): Promise<NetworkConditionState> => {
+      return httpGet<NetworkConditionState>(`/api/simulator/network?udid=${encodeURIComponent(udid)}`);
+    },
+
+    // Apps launched afterwards are pointed at the simulator's throttling proxy
+    setNetwork: async (
+      udid: string,
+      setting: { preset: NetworkPreset } | { conditions: Partial<NetworkConditions> }
+    ): Promise<NetworkConditionState> => {
+      return httpPost<NetworkConditionState>('/api/simulator/network', { udid, ...setting });
+    },
+
+    clearNetwork: async (udid: string): Promise<void> => {
+      await httpDelete(`/api/simulator/network?udid=${encodeURIComponent(udid)}`);
+    },
+
+    debugOptions: async (udid: string): Promise<DebugOptionsState> => {
+      return httpGet<DebugOptionsState>(`/api/simulator/debug-options?udid=${encodeURIComponent(udid)}`);
+    },
+
+    // Options apply to later launches, or right away when an app to relaunch is given
+    setDebugOptions: async (
+      udid: string,
+      options: DebugOption[],
+      relaunch?: { bundleId: string; projectPath?: string }
+    ): Promise<DebugOptionsState> => {
+      return httpPost<DebugOptionsState>('/api/simulator/debug-options', { udid, options, ...relaunch });
+    },
+
+    // View-only links to the stream, expiring after an hour unless told otherwise
+    createShare: async (udid: string, expiresInMinutes?: number): Promise<StreamShare> => {
+      return httpPost<StreamShare>('/api/simulator/shares', { udid, expiresInMinutes });
+    },
+
+    shares: async (udid?: string): Promise<StreamShare[]> => {
+      return httpGet<StreamShare[]>(`/api/simulator/shares${udid ? `?udid=${encodeURIComponent(udid)}` : ''}`);
+    },
+
+    revokeShare: async (token: string): Promise<void> => {
+      await httpDelete(`/api/simulator/shares/${encodeURIComponent(token)}`);
+    },
+
     onStreamFrame: (callback: (frame: StreamFrame) => void): (() => void) => {
       connectWebSocket();
       return addWsListener('simulator:stream:frame', callback as (payload: unknown) => void);
     },
 
-    onLog: (callback: (log: StreamLogEvent) => void): (() => void) => {
+    onStreamStats: (callback: (stats: StreamStats) => void): (() => void) => {
+      connectWebSocket();
+      return addWsListener('simulator:stream:stats', callback as (payload: unknown) => void);
+    },
+
+    // Logs for one simulator, or for every simulator when udid is omitted
+    onLog: (callback: (log: StreamLogEvent) => void, udid?: string): (() => void) => {
+      const key = udid ?? '*';
+      const count = logSubscriptions.get(key) ?? 0;
+      logSubscriptions.set(key, count + 1);
+      if (count === 0) {
+        sendWsMessage('simulator:logs:subscribe', { udid });
+      }
+
+      const removeListener = addWsListener('simulator:log', (payload) => {
+        const log = payload as StreamLogEvent;
+        if (!udid || log.udid === udid) {
+          callback(log);
+        }
+      });
+
+      return () => {
+        removeListener();
+        const remaining = (logSubscriptions.get(key) ?? 1) - 1;
+        if (remaining > 0) {
+          logSubscriptions.set(key, remaining);
+        } else {
+          logSubscriptions.delete(key);
+          sendWsMessage('simulator:logs:unsubscribe', { udid });
+        }
+      };
+    },
+  },
+
+  // App metrics API (CPU/memory sampling of launched apps)
+  metrics: {
+    // Apps are sampled automatically after a launch; this restarts sampling by hand
+    start: async (udid: string, bundleId: string, intervalMs?: number): Promise<AppMetricsSummary> => {
+      return httpPost<AppMetricsSummary>('/api/metrics/start', { udid, bundleId, intervalMs });
+    },
+
+    stop: async (udid: string, bundleId: string): Promise<AppMetricsSummary> => {
+      return httpPost<AppMetricsSummary>('/api/metrics/stop', { udid, bundleId });
+    },
+
+    active: async (udid: string): Promise<AppMetricsSummary[]> => {
+      const { sessions } = await httpGet<{ sessions: AppMetricsSummary[] }>(
+        `/api/metrics?udid=${encodeURIComponent(udid)}`
+      );
+      return sessions;
+    },
+
+    history: async (bundleId?: string, limit?: number): Promise<AppMetricsSummary[]> => {
+      const params = new URLSearchParams();
+      if (bundleId) params.set('bundleId', bundleId);
+      if (limit) params.set('limit', String(limit));
+      const { sessions } = await httpGet<{ sessions: AppMetricsSummary[] }>(`/api/metrics/history?${params}`);
+      return sessions;
+    },
+
+    onEvent: (udid: string, callback: (event: AppMetricsEvent) => void): (() => void) => {
+      const source = new EventSource(`${API_BASE}/api/metrics/stream?udid=${encodeURIComponent(udid)}`);
+      source.onmessage = (message) => callback(JSON.parse(message.data) as AppMetricsEvent);
+      return () => source.close();
+    },
+  },
+
+  // Crash reports API (simulator app crashes, symbolicated against their build)
+  crashes: {
+    list: async (filter: { bundleId?: string; udid?: string } = {}): Promise<CrashReportSummary[]> => {
+      const params = new URLSearchParams();
+      if (filter.bundleId) params.set('bundleId', filter.bundleId);
+      if (filter.udid) params.set('udid', filter.udid);
+      const { crashes } = await httpGet<{ crashes: CrashReportSummary[] }>(`/api/crashes?${params}`);
+      return crashes;
+    },
+
+    get: async (id: string): Promise<CrashReport> => {
+      return httpGet<CrashReport>(`/api/crashes/${encodeURIComponent(id)}`);
+    },
+  },
+
+  // Device logs API (a simulator's unified log, filtered on the server)
+  deviceLogs: {
+    profiles: async (): Promise<LogFilterProfile[]> => {
+      const { profiles } = await httpGet<{ profiles: LogFilterProfile[] }>('/api/logs/profiles');
+      return profiles;
+    },
+
+    saveProfile: async (profile: Omit<LogFilterProfile, 'builtIn'>): Promise<LogFilterProfile> => {
+      return httpPost('/api/logs/profiles', profile);
+    },
+
+    deleteProfile: async (name: string): Promise<void> => {
+      await httpDelete(`/api/logs/profiles/${encodeURIComponent(name)}`);
+    },
+
+    // The app's bundleId or process is needed by profiles that keep only the app's entries
+    onEntry: (
+      udid: string,
+      options: { profile?: string; bundleId?: string; process?: string },
+      callback: (entry: DeviceLogEntry) => void
+    ): (() => void) => {
+      const params = new URLSearchParams({ udid });
+      if (options.profile) params.set('profile', options.profile);
+      if (options.bundleId) params.set('bundleId', options.bundleId);
+      if (options.process) params.set('process', options.process);
+      const source = new EventSource(`${API_BASE}/api/simulator/device-logs?${params}`);
+      source.onmessage = (message) => callback(JSON.parse(message.data) as DeviceLogEntry);
+      return () => source.close();
+    },
+  },
+
+  // Automation API (replayable scripts run against a simulator)
+  automation: {
+    // Resolves with whether every step passed; events arrive as each step starts and finishes
+    run: async (
+      udid: string,
+      script: AutomationScript | string,
+      onEvent: (event: AutomationEvent) => void,
+      signal?: AbortSignal
+    ): Promise<boolean> => {
+      const response = await fetch(`${API_BASE}/api/automation/run`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ udid, script, token: streamTokens.get(udid) }),
+        signal,
+      });
+      if (!response.ok || !response.body) {
+        throw await toApiError(response);
+      }
+
+      const reader = response.body.getReader();
+      const decoder = new TextDecoder();
+      let buffered = '';
+      let success = false;
+      for (;;) {
+        const { done, value } = await reader.read();
+        buffered += decoder.decode(value, { stream: !done });
+        const lines = buffered.split('\n');
+        buffered = lines.pop() ?? '';
+        for (const line of lines) {
+          if (!line.trim()) continue;
+          const event = JSON.parse(line) as AutomationEvent;
+          if (event.type === 'completed') {
+            success = event.success;
+          }
+          onEvent(event);
+        }
+        if (done) return success;
+      }
+    },
+  },
+
+  // Agent API (batches of UI actions answered in one response, for AI agents)
+  agent: {
+    act: async (
+      udid: string,
+      actions: AgentAction[],
+      options: { stopOnError?: boolean; screenshots?: 'none' | 'final' | 'each' } = {}
+    ): Promise<AgentActResponse> => {
+      return httpPost<AgentActResponse>('/api/agent/act', {
+        udid,
+        actions,
+        ...options,
+        token: streamTokens.get(udid),
+      });
+    },
+  },
+
+  // Localized screenshot API (a script run for every device and locale)
+  screenshots: {
+    // Config is TOML or JSON text with snake_case keys, as in a screenshots file
+    run: async (
+      config: string,
+      onEvent: (event: ScreenshotMatrixEvent) => void,
+      signal?: AbortSignal
+    ): Promise<boolean> => {
+      const response = await fetch(`${API_BASE}/api/screenshots/run`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ config }),
+        signal,
+      });
+      if (!response.ok || !response.body) {
+        throw await toApiError(response);
+      }
+
+      const reader = response.body.getReader();
+      const decoder = new TextDecoder();
+      let buffered = '';
+      let success = false;
+      for (;;) {
+        const { done, value } = await reader.read();
+        buffered += decoder.decode(value, { stream: !done });
+        const lines = buffered.split('\n');
+        buffered = lines.pop() ?? '';
+        for (const line of lines) {
+          if (!line.trim()) continue;
+          const event = JSON.parse(line) as ScreenshotMatrixEvent;
+          if (event.type === 'completed') {
+            success = event.success;
+          }
+          onEvent(event);
+        }
+        if (done) return success;
+      }
+    },
+  },
+
+  // Builds API (stored builds and their diagnostics)
+  builds: {
+    list: async (projectPath?: string, limit?: number): Promise<BuildSummary[]> => {
+      const params = new URLSearchParams();
+      if (projectPath) params.set('projectPath', projectPath);
+      if (limit) params.set('limit', String(limit));
+      const { builds } = await httpGet<{ builds: BuildSummary[] }>(`/api/builds?${params}`);
+      return builds;
+    },
+
+    get: async (id: string): Promise<BuildSummary> => {
+      return httpGet<BuildSummary>(`/api/builds/${encodeURIComponent(id)}`);
+    },
+
+    export: async (id: string, format: ExportFormat): Promise<string> => {
+      return httpGetText(`/api/builds/${encodeURIComponent(id)}/export?format=${format}`);
+    },
+
+    warnings: async (projectPath?: string, limit?: number): Promise<WarningsReport> => {
+      const params = new URLSearchParams();
+      if (projectPath) params.set('projectPath', projectPath);
+      if (limit) params.set('limit', String(limit));
+      return httpGet<WarningsReport>(`/api/builds/warnings?${params}`);
+    },
+
+    logTail: async (id: string, lines?: number): Promise<{ lines: string[]; complete: boolean }> => {
+      const params = lines ? `?lines=${lines}` : '';
+      return httpGet(`/api/builds/${encodeURIComponent(id)}/log/tail${params}`);
+    },
+
+    /** URL of the full log as plain text, which also takes Range requests */
+    logUrl: (id: string): string => {
+      return `${API_BASE}/api/builds/${encodeURIComponent(id)}/log`;
+    },
+
+    environmentDiff: async (from: string, to: string): Promise<BuildEnvironmentDiff> => {
+      const params = new URLSearchParams({ from, to });
+      return httpGet<BuildEnvironmentDiff>(`/api/builds/environment-diff?${params}`);
+    },
+
+    queue: async (): Promise<BuildQueueStatus> => {
+      return httpGet<BuildQueueStatus>('/api/builds/queue');
+    },
+
+    // Resolves with whether every build succeeded; events of all builds arrive interleaved
+    batch: async (
+      builds: BatchBuild[],
+      onEvent: (event: BatchBuildEvent) => void,
+      signal?: AbortSignal
+    ): Promise<boolean> => {
+      const response = await fetch(`${API_BASE}/api/builds/batch`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ builds }),
+        signal,
+      });
+      if (!response.ok || !response.body) {
+        throw await toApiError(response);
+      }
+
+      const reader = response.body.getReader();
+      const decoder = new TextDecoder();
+      let buffered = '';
+      let success = false;
+      for (;;) {
+        const { done, value } = await reader.read();
+        buffered += decoder.decode(value, { stream: !done });
+        const lines = buffered.split('\n');
+        buffered = lines.pop() ?? '';
+        for (const line of lines) {
+          if (!line.trim()) continue;
+          const event = JSON.parse(line) as BatchBuildEvent;
+          if (event.type === 'completed') {
+            success = event.success;
+          }
+          onEvent(event);
+        }
+        if (done) return success;
+      }
+    },
+  },
+
+  // Tests API (xcodebuild test runs on a simulator)
+  tests: {
+    // Resolves with the finished run; events arrive as tests finish
+    run: async (
+      request: TestRunRequest,
+      onEvent: (event: TestRunEvent) => void,
+      signal?: AbortSignal
+    ): Promise<TestRun | null> => {
+      const response = await fetch(`${API_BASE}/api/tests/run`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(request),
+        signal,
+      });
+      if (!response.ok || !response.body) {
+        throw await toApiError(response);
+      }
+
+      const reader = response.body.getReader();
+      const decoder = new TextDecoder();
+      let buffered = '';
+      let run: TestRun | null = null;
+      for (;;) {
+        const { done, value } = await reader.read();
+        buffered += decoder.decode(value, { stream: !done });
+        const lines = buffered.split('\n');
+        buffered = lines.pop() ?? '';
+        for (const line of lines) {
+          if (!line.trim()) continue;
+          const event = JSON.parse(line) as TestRunEvent;
+          if (event.type === 'completed') {
+            run = event.run;
+          }
+          onEvent(event);
+        }
+        if (done) return run;
+      }
+    },
+
+    runs: async (projectPath?: string, limit?: number): Promise<TestRun[]> => {
+      const params = new URLSearchParams();
+      if (projectPath) params.set('projectPath', projectPath);
+      if (limit) params.set('limit', String(limit));
+      const { runs } = await httpGet<{ runs: TestRun[] }>(`/api/tests/runs?${params}`);
+      return runs;
+    },
+
+    get: async (id: string): Promise<TestRun> => {
+      return httpGet<TestRun>(`/api/tests/runs/${encodeURIComponent(id)}`);
+    },
+
+    export: async (id: string, format: ExportFormat): Promise<string> => {
+      return httpGetText(`/api/tests/runs/${encodeURIComponent(id)}/export?format=${format}`);
+    },
+
+    // Attachments of failed tests, like failure screenshots, from the run's result bundle
+    attachments: async (id: string): Promise<TestRunAttachments> => {
+      return httpGet<TestRunAttachments>(`/api/tests/runs/${encodeURIComponent(id)}/attachments`);
+    },
+
+    attachmentUrl: (id: string, fileName: string): string => {
+      return `${API_BASE}/api/tests/runs/${encodeURIComponent(id)}/attachments/${encodeURIComponent(fileName)}`;
+    },
+
+    // Tests that passed only on a retry, most often flaky first
+    flaky: async (projectPath?: string): Promise<FlakyTestSummary[]> => {
+      const params = new URLSearchParams();
+      if (projectPath) params.set('projectPath', projectPath);
+      const { tests } = await httpGet<{ tests: FlakyTestSummary[] }>(`/api/tests/flaky?${params}`);
+      return tests;
+    },
+  },
+
+  // Coverage API (code coverage of test runs)
+  coverage: {
+    get: async (testRunId: string): Promise<CoverageReport> => {
+      return httpGet<CoverageReport>(`/api/coverage/${encodeURIComponent(testRunId)}`);
+    },
+
+    // Per-line execution counts of a file, for overlaying on source
+    lines: async (testRunId: string, file: string): Promise<FileLineCoverage> => {
+      const params = new URLSearchParams({ file });
+      return httpGet<FileLineCoverage>(`/api/coverage/${encodeURIComponent(testRunId)}/lines?${params}`);
+    },
+  },
+
+  // Visual regression API (screenshots compared against stored baselines)
+  visual: {
+    check: async (request: VisualCheckRequest): Promise<VisualCheckResult> => {
+      return httpPost<VisualCheckResult>('/api/visual/check', request);
+    },
+  },
+
+  // Watch mode API (rebuild and relaunch on source changes)
+  watch: {
+    start: async (request: StartWatchRequest): Promise<void> => {
+      await httpPost('/api/watch/start', request);
+    },
+
+    stop: async (path: string): Promise<void> => {
+      await httpPost('/api/watch/stop', { path });
+    },
+
+    onStatus: (callback: (status: WatchStatus) => void): (() => void) => {
+      connectWebSocket();
+      return addWsListener('watch:status', callback as (payload: unknown) => void);
+    },
+  },
+
+  // Build triggers API (builds on a cron schedule or on git pushes)
+  triggers: {
+    list: async (): Promise<BuildTrigger[]> => {
+      return httpGet('/api/triggers');
+    },
+
+    create: async (request: CreateBuildTriggerRequest): Promise<BuildTrigger> => {
+      return httpPost('/api/triggers', request);
+    },
+
+    remove: async (id: string): Promise<void> => {
+      await httpDelete(`/api/triggers/${encodeURIComponent(id)}`);
+    },
+
+    run: async (id: string): Promise<void> => {
+      await httpPost(`/api/triggers/${encodeURIComponent(id)}/run`, {});
+    },
+
+    // Sets the secret webhooks are signed with, generating one when none is given
+    setSecret: async (secret?: string): Promise<string> => {
+      const response = await httpPost<{ secret: string }>('/api/triggers/secret', { secret });
+      return response.secret;
+    },
+
+    onBuild: (callback: (event: TriggerBuildEvent) => void): (() => void) => {
       connectWebSocket();
-      return addWsListener('simulator:log', callback as (payload: unknown) => void);
+      return addWsListener('trigger:build', callback as (payload: unknown) => void);
+    },
+  },
+
+  // Android API
+  android: {
+    listDevices: async (): Promise<AndroidDevice[]> => {
+      return httpGet('/api/android/devices');
+    },
+
+    launch: async (request: AndroidLaunchRequest): Promise<{ message: string }> => {
+      return httpPost('/api/android/launch', request);
+    },
+
+    terminate: async (serial: string, applicationId: string): Promise<void> => {
+      await httpPost('/api/android/terminate', { serial, applicationId });
+    },
+  },
+
+  // App settings API
+  settings: {
+    get: async (): Promise<AppSettings> => {
+      return httpGet('/api/settings');
+    },
+
+    update: async (settings: Partial<Record<keyof AppSettings, string | null>>): Promise<AppSettings> => {
+      return httpPost('/api/settings', settings);
+    },
+  },
+
+  // Application logs API
+  logs: {
+    app: async (since?: string): Promise<AppLogEntry[]> => {
+      const params = since ? `?since=${encodeURIComponent(since)}` : '';
+      return httpGet(`/api/logs/app${params}`);
+    },
+  },
+
+  // Disk used by DerivedData, simulators, and Plasma's artifacts
+  maintenance: {
+    diskUsage: async (): Promise<DiskUsageReport> => {
+      return httpGet('/api/maintenance/disk-usage');
+    },
+
+    cleanUp: async (request: CleanupRequest): Promise<{ freedBytes: number }> => {
+      return httpPost('/api/maintenance/cleanup', request);
+    },
+  },
+
+  // Jump to source in the user's editor
+  editor: {
+    open: async (location: SourceLocation): Promise<void> => {
+      await httpPost('/api/editor/open', location);
+    },
+  },
+
+  // Environment API
+  environment: {
+    checks: async (): Promise<EnvironmentCheck[]> => {
+      return httpGet('/api/environment/checks');
+    },
+
+    fix: async (id: EnvironmentCheckId): Promise<void> => {
+      await httpPost('/api/environment/fix', { id });
     },
   },
 };
@@ -0,0 +1,124 @@
+import { describe, it, expect } from "vitest"
+import {
+  classifyBuildLine,
+  countByLevel,
+  filterBuildLog,
+  formatGitInfo,
+  parseDiagnosticLocation,
+  toBuildLogLines,
+  toFormattedLogLines,
+  trimToTail,
+} from "./build-log"
+
+describe("build-log", () => {
+  describe("classifyBuildLine", () => {
+    it("detects compiler errors", () => {
+      expect(classifyBuildLine("/src/App.swift:10:5: error: cannot find 'foo' in scope")).toBe("error")
+      expect(classifyBuildLine("** BUILD FAILED **")).toBe("error")
+    })
+
+    it("detects warnings", () => {
+      expect(classifyBuildLine("/src/App.swift:3:1: warning: unused variable 'x'")).toBe("warning")
+    })
+
+    it("treats everything else as info", () => {
+      expect(classifyBuildLine("CompileSwift normal arm64 /src/App.swift")).toBe("info")
+    })
+  })
+
+  describe("filterBuildLog", () => {
+    const lines = toBuildLogLines([
+      "CompileSwift normal arm64 App.swift",
+      "App.swift:1:1: warning: deprecated",
+      "App.swift:2:1: error: missing return",
+    ])
+
+    it("filters by level", () => {
+      const result = filterBuildLog(lines, { levels: new Set(["error"]), query: "" })
+      expect(result.map((l) => l.index)).toEqual([2])
+    })
+
+    it("searches case-insensitively", () => {
+      const result = filterBuildLog(lines, {
+        levels: new Set(["error", "warning", "info"]),
+        query: "COMPILESWIFT",
+      })
+      expect(result.map((l) => l.index)).toEqual([0])
+    })
+
+    it("counts lines per level", () => {
+      expect(countByLevel(lines)).toEqual({ error: 1, warning: 1, info: 1 })
+    })
+  })
+
+  describe("parseDiagnosticLocation", () => {
+    it("reads the file, line, and column of a diagnostic", () => {
+      expect(parseDiagnosticLocation("/src/App.swift:10:5: error: cannot find 'foo' in scope")).toEqual({
+        file: "/src/App.swift",
+        line: 10,
+        column: 5,
+      })
+      expect(parseDiagnosticLocation("/src/My App/View.m:3: warning: unused")).toEqual({
+        file: "/src/My App/View.m",
+        line: 3,
+      })
+    })
+
+    it("ignores lines that aren't diagnostics", () => {
+      expect(parseDiagnosticLocation("** BUILD FAILED **")).toBeNull()
+      expect(parseDiagnosticLocation("App.swift:2:1: error: relative paths can't be opened")).toBeNull()
+    })
+
+    it("attaches locations to error and warning lines", () => {
+      const [info, error] = toBuildLogLines(["CompileSwift /src/App.swift", "/src/App.swift:2:1: error: missing return"])
+      expect(info.location).toBeUndefined()
+      expect(error.location).toEqual({ file: "/src/App.swift", line: 2, column: 1 })
+    })
+  })
+
+  describe("toFormattedLogLines", () => {
+    it("leaves out dropped lines but keeps raw indexes", () => {
+      const rows = toFormattedLogLines([null, { kind: "compile", text: "Compiling App.swift", target: "App" }, null])
+      expect(rows).toEqual([{ index: 1, text: "[App] Compiling App.swift", level: "info" }])
+    })
+
+    it("gives source excerpts the level of their diagnostic", () => {
+      const location = { file: "/src/App.swift", line: 3, column: 5 }
+      const rows = toFormattedLogLines([
+        { kind: "error", text: "App.swift:3:5: cannot find 'x' in scope", location },
+        { kind: "context", text: "    print(x)" },
+        { kind: "context", text: "          ^" },
+        { kind: "warning", text: "ld: warning: directory not found" },
+        { kind: "test-failed", text: "testLaunch (0.12s)" },
+      ])
+      expect(rows.map((row) => row.level)).toEqual(["error", "error", "error", "warning", "error"])
+      expect(rows[0].location).toEqual(location)
+    })
+  })
+
+  describe("trimToTail", () => {
+    it("keeps the newest lines", () => {
+      const lines = ["a", "b", "c", "d"]
+      expect(trimToTail(lines, 2)).toBe(2)
+      expect(lines).toEqual(["c", "d"])
+    })
+
+    it("leaves short logs alone", () => {
+      const lines = ["a"]
+      expect(trimToTail(lines, 2)).toBe(0)
+      expect(lines).toEqual(["a"])
+    })
+  })
+
+  describe("formatGitInfo", () => {
+    const commit = "1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b"
+
+    it("shows the branch and short commit", () => {
+      expect(formatGitInfo({ commit, branch: "main", dirty: false })).toBe("main @ 1a2b3c4")
+    })
+
+    it("calls out detached heads and uncommitted changes", () => {
+      expect(formatGitInfo({ commit, branch: null, dirty: true })).toBe("detached @ 1a2b3c4 + uncommitted changes")
+    })
+  })
+})
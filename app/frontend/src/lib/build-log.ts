@@ -0,0 +1,118 @@
+import type { BuildLineKind, FormattedBuildLine, GitInfo, SourceLocation } from "@/lib/api"
+
+export type BuildLogLevel = "error" | "warning" | "info"
+
+export interface BuildLogLine {
+  index: number
+  text: string
+  level: BuildLogLevel
+  /** Source of a compiler diagnostic, which can be opened in the editor */
+  location?: SourceLocation
+}
+
+export interface BuildLogFilter {
+  levels: Set<BuildLogLevel>
+  query: string
+}
+
+/** Lines of a build kept in the page; the rest is in the build's log file */
+export const MAX_BUILD_LOG_LINES = 5000
+
+const ERROR_PATTERN = /(\berror:|\*\* BUILD FAILED \*\*|^\s*fatal error|The following build commands failed)/i
+const WARNING_PATTERN = /\bwarning:/i
+// /path/File.swift:12:5: error: ..., with the column left out by some tools
+const DIAGNOSTIC_PATTERN = /^(\/[^:]+):(\d+):(?:(\d+):)?\s*(?:fatal error|error|warning|note):/
+
+/**
+ * Classify an xcodebuild output line by severity
+ */
+export function classifyBuildLine(text: string): BuildLogLevel {
+  if (ERROR_PATTERN.test(text)) return "error"
+  if (WARNING_PATTERN.test(text)) return "warning"
+  return "info"
+}
+
+/**
+ * Where a compiler diagnostic points to, when the line is one
+ */
+export function parseDiagnosticLocation(text: string): SourceLocation | null {
+  const match = text.trim().match(DIAGNOSTIC_PATTERN)
+  if (!match) return null
+  return {
+    file: match[1],
+    line: parseInt(match[2], 10),
+    ...(match[3] ? { column: parseInt(match[3], 10) } : {}),
+  }
+}
+
+export function toBuildLogLines(lines: string[]): BuildLogLine[] {
+  return lines.map((text, index) => {
+    const level = classifyBuildLine(text)
+    const location = level === "info" ? null : parseDiagnosticLocation(text)
+    return location ? { index, text, level, location } : { index, text, level }
+  })
+}
+
+const FORMATTED_LEVELS: Partial<Record<BuildLineKind, BuildLogLevel>> = {
+  error: "error",
+  failed: "error",
+  "test-failed": "error",
+  warning: "warning",
+}
+
+/**
+ * Rows of the formatted log: the lines the server condensed, without the
+ * ones it left out. A diagnostic's source excerpt takes its level, so it
+ * isn't filtered away from it. Indexes still point at the raw lines.
+ */
+export function toFormattedLogLines(formatted: (FormattedBuildLine | null)[]): BuildLogLine[] {
+  const rows: BuildLogLine[] = []
+  let diagnosticLevel: BuildLogLevel = "info"
+  formatted.forEach((line, index) => {
+    if (!line) return
+    const level = line.kind === "context" ? diagnosticLevel : FORMATTED_LEVELS[line.kind] ?? "info"
+    if (line.kind !== "context") diagnosticLevel = level
+    const text = line.target ? `[${line.target}] ${line.text}` : line.text
+    rows.push(line.location ? { index, text, level, location: line.location } : { index, text, level })
+  })
+  return rows
+}
+
+/**
+ * Apply level filters and a case-insensitive search query
+ */
+export function filterBuildLog(lines: BuildLogLine[], filter: BuildLogFilter): BuildLogLine[] {
+  const query = filter.query.trim().toLowerCase()
+  return lines.filter(
+    (line) =>
+      filter.levels.has(line.level) &&
+      (query === "" || line.text.toLowerCase().includes(query))
+  )
+}
+
+export function countByLevel(lines: BuildLogLine[]): Record<BuildLogLevel, number> {
+  const counts: Record<BuildLogLevel, number> = { error: 0, warning: 0, info: 0 }
+  for (const line of lines) {
+    counts[line.level]++
+  }
+  return counts
+}
+
+/**
+ * Drop the oldest lines beyond max, returning how many were dropped
+ */
+export function trimToTail(lines: string[], max: number = MAX_BUILD_LOG_LINES): number {
+  const excess = lines.length - max
+  if (excess <= 0) return 0
+  lines.splice(0, excess)
+  return excess
+}
+
+/**
+ * The commit a build came from, like main @ 1a2b3c4, with uncommitted
+ * changes called out since the commit alone doesn't describe the build
+ */
+export function formatGitInfo(git: GitInfo): string {
+  const revision = `${git.branch ?? "detached"} @ ${git.commit.slice(0, 7)}`
+  return git.dirty ? `${revision} + uncommitted changes` : revision
+}
@@ -0,0 +1,33 @@
+import { describe, it, expect } from "vitest"
+import { categoryLabel, formatSize, sizeShare } from "./bundle-size"
+
+describe("bundle-size", () => {
+  describe("formatSize", () => {
+    it("picks a unit based on magnitude", () => {
+      expect(formatSize(512)).toBe("512 B")
+      expect(formatSize(20 * 1024)).toBe("20 KB")
+      expect(formatSize(1.5 * 1024 * 1024)).toBe("1.5 MB")
+      expect(formatSize(2 * 1024 * 1024 * 1024)).toBe("2.0 GB")
+    })
+  })
+
+  describe("sizeShare", () => {
+    it("rounds to a whole percentage", () => {
+      expect(sizeShare(1, 3)).toBe(33)
+      expect(sizeShare(3, 3)).toBe(100)
+    })
+
+    it("keeps small entries visible", () => {
+      expect(sizeShare(1, 10_000)).toBe(1)
+    })
+
+    it("is zero for empty entries or totals", () => {
+      expect(sizeShare(0, 100)).toBe(0)
+      expect(sizeShare(10, 0)).toBe(0)
+    })
+  })
+
+  it("labels app extensions", () => {
+    expect(categoryLabel("plugins")).toBe("Extensions")
+  })
+})
@@ -0,0 +1,39 @@
+import type { SizeCategory } from "@/lib/api"
+
+const CATEGORY_LABELS: Record<SizeCategory, string> = {
+  binaries: "Binaries",
+  frameworks: "Frameworks",
+  plugins: "Extensions",
+  assets: "Assets",
+  localizations: "Localizations",
+  other: "Other",
+}
+
+export function categoryLabel(category: SizeCategory): string {
+  return CATEGORY_LABELS[category] ?? category
+}
+
+/**
+ * Format a file size for display, e.g. 1572864 -> "1.5 MB"
+ */
+export function formatSize(bytes: number): string {
+  if (bytes >= 1024 * 1024 * 1024) {
+    return `${(bytes / (1024 * 1024 * 1024)).toFixed(1)} GB`
+  }
+  if (bytes >= 1024 * 1024) {
+    return `${(bytes / (1024 * 1024)).toFixed(1)} MB`
+  }
+  if (bytes >= 1024) {
+    return `${Math.round(bytes / 1024)} KB`
+  }
+  return `${bytes} B`
+}
+
+/**
+ * Share of a total as a whole percentage, at least 1 for anything non-empty
+ * so small entries still show up in a bar
+ */
+export function sizeShare(bytes: number, total: number): number {
+  if (total <= 0 || bytes <= 0) return 0
+  return Math.max(1, Math.round((bytes / total) * 100))
+}
@@ -0,0 +1,24 @@
+import { describe, it, expect, beforeEach } from "vitest"
+import { isOnboardingComplete, markOnboardingComplete, requiredChecksPass } from "./onboarding"
+import type { EnvironmentCheck } from "./api"
+
+function check(overrides: Partial<EnvironmentCheck>): EnvironmentCheck {
+  return { id: "command-line-tools", title: "Check", ok: true, required: true, ...overrides }
+}
+
+describe("onboarding", () => {
+  beforeEach(() => {
+    localStorage.clear()
+  })
+
+  it("remembers completion", () => {
+    expect(isOnboardingComplete()).toBe(false)
+    markOnboardingComplete()
+    expect(isOnboardingComplete()).toBe(true)
+  })
+
+  it("only blocks on failing required checks", () => {
+    expect(requiredChecksPass([check({}), check({ id: "axe", ok: false, required: false })])).toBe(true)
+    expect(requiredChecksPass([check({ id: "xcode-license", ok: false })])).toBe(false)
+  })
+})
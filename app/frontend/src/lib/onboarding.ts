@@ -0,0 +1,26 @@
+import type { EnvironmentCheck } from "@/lib/api"
+
+const STORAGE_KEY = "plasma:onboarding-complete"
+
+export function isOnboardingComplete(): boolean {
+  try {
+    return localStorage.getItem(STORAGE_KEY) === "true"
+  } catch {
+    return false
+  }
+}
+
+export function markOnboardingComplete() {
+  try {
+    localStorage.setItem(STORAGE_KEY, "true")
+  } catch {
+    // Storage unavailable (e.g. private mode)
+  }
+}
+
+/**
+ * Whether every required environment check passes
+ */
+export function requiredChecksPass(checks: EnvironmentCheck[]): boolean {
+  return checks.every((check) => check.ok || !check.required)
+}
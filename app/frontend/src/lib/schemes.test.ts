@@ -0,0 +1,38 @@
+import { describe, it, expect } from "vitest"
+import type { WorkspaceMember } from "./api"
+import { groupSchemesByProject } from "./schemes"
+
+function member(name: string, schemes: string[]): WorkspaceMember {
+  return { name, path: `/repo/${name}.xcodeproj`, targets: schemes, schemes }
+}
+
+describe("groupSchemesByProject", () => {
+  it("groups schemes under the project that builds them", () => {
+    const groups = groupSchemesByProject(
+      ["App", "Core", "AppTests"],
+      [member("App", ["App", "AppTests"]), member("Core", ["Core"])]
+    )
+    expect(groups).toEqual([
+      { project: "App", schemes: ["App", "AppTests"] },
+      { project: "Core", schemes: ["Core"] },
+    ])
+  })
+
+  it("puts schemes of no project last", () => {
+    const groups = groupSchemesByProject(["App", "Alamofire"], [member("App", ["App"])])
+    expect(groups).toEqual([
+      { project: "App", schemes: ["App"] },
+      { project: null, schemes: ["Alamofire"] },
+    ])
+  })
+
+  it("leaves out projects without schemes", () => {
+    expect(groupSchemesByProject(["App"], [member("App", ["App"]), member("Pods", [])])).toEqual([
+      { project: "App", schemes: ["App"] },
+    ])
+  })
+
+  it("keeps everything in one group without projects", () => {
+    expect(groupSchemesByProject(["App", "Widget"])).toEqual([{ project: null, schemes: ["App", "Widget"] }])
+  })
+})
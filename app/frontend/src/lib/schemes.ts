@@ -0,0 +1,23 @@
+import type { WorkspaceMember } from "@/lib/api"
+
+export interface SchemeGroup {
+  /** Project the schemes build, or null for schemes of no single project */
+  project: string | null
+  schemes: string[]
+}
+
+/**
+ * Group a workspace's schemes by the project they build, keeping the order
+ * xcodebuild listed them in. Projects without schemes are left out.
+ */
+export function groupSchemesByProject(schemes: string[], projects: WorkspaceMember[] = []): SchemeGroup[] {
+  const groups: SchemeGroup[] = projects
+    .map((project) => ({
+      project: project.name,
+      schemes: schemes.filter((scheme) => project.schemes.includes(scheme)),
+    }))
+    .filter((group) => group.schemes.length > 0)
+  const grouped = new Set(groups.flatMap((group) => group.schemes))
+  const other = schemes.filter((scheme) => !grouped.has(scheme))
+  return other.length > 0 ? [...groups, { project: null, schemes: other }] : groups
+}
@@ -0,0 +1,35 @@
+import { describe, it, expect, beforeEach } from "vitest"
+import { clearSession, loadSession, resumableUdids, saveSession } from "./session"
+import type { Simulator } from "./api"
+
+function simulator(udid: string, state: string): Simulator {
+  return { udid, name: udid, state, runtime: "com.apple.CoreSimulator.SimRuntime.iOS-18-2" }
+}
+
+describe("session", () => {
+  beforeEach(() => {
+    localStorage.clear()
+  })
+
+  it("round-trips the previous session", () => {
+    expect(loadSession()).toBeNull()
+    saveSession({ projectId: 3, projectName: "App", simulatorUdid: "A", streamingUdids: ["A", "B"] })
+    expect(loadSession()).toMatchObject({ projectId: 3, projectName: "App", simulatorUdid: "A", streamingUdids: ["A", "B"] })
+    clearSession()
+    expect(loadSession()).toBeNull()
+  })
+
+  it("ignores malformed sessions", () => {
+    localStorage.setItem("plasma:previous-session", "{not json")
+    expect(loadSession()).toBeNull()
+    localStorage.setItem("plasma:previous-session", JSON.stringify({ simulatorUdid: "A" }))
+    expect(loadSession()).toBeNull()
+  })
+
+  it("resumes only simulators that are still booted, selected one first", () => {
+    const session = { projectId: 1, projectName: "App", simulatorUdid: "B", streamingUdids: ["A", "B", "C"], savedAt: "" }
+    const simulators = [simulator("A", "Booted"), simulator("B", "Booted"), simulator("C", "Shutdown")]
+    expect(resumableUdids(session, simulators)).toEqual(["B", "A"])
+    expect(resumableUdids({ ...session, streamingUdids: [] }, simulators)).toEqual([])
+  })
+})
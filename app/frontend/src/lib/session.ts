@@ -0,0 +1,64 @@
+import type { Simulator } from "@/lib/api"
+
+const STORAGE_KEY = "plasma:previous-session"
+
+/**
+ * What was open when Plasma last ran, so a crash or restart can pick up
+ * where it left off
+ */
+export interface PreviousSession {
+  projectId: number
+  projectName: string
+  simulatorUdid: string
+  // Simulators that were streaming; empty when nothing was running
+  streamingUdids: string[]
+  savedAt: string
+}
+
+export function saveSession(session: Omit<PreviousSession, "savedAt">) {
+  try {
+    localStorage.setItem(STORAGE_KEY, JSON.stringify({ ...session, savedAt: new Date().toISOString() }))
+  } catch {
+    // Storage unavailable (e.g. private mode)
+  }
+}
+
+export function loadSession(): PreviousSession | null {
+  try {
+    const stored = localStorage.getItem(STORAGE_KEY)
+    if (!stored) return null
+    const session = JSON.parse(stored) as Partial<PreviousSession>
+    if (typeof session.projectId !== "number" || typeof session.simulatorUdid !== "string") {
+      return null
+    }
+    return {
+      projectId: session.projectId,
+      projectName: session.projectName ?? "",
+      simulatorUdid: session.simulatorUdid,
+      streamingUdids: Array.isArray(session.streamingUdids) ? session.streamingUdids : [],
+      savedAt: session.savedAt ?? "",
+    }
+  } catch {
+    return null
+  }
+}
+
+export function clearSession() {
+  try {
+    localStorage.removeItem(STORAGE_KEY)
+  } catch {
+    // Storage unavailable (e.g. private mode)
+  }
+}
+
+/**
+ * The previously streaming simulators that are still booted and can be
+ * streamed again without rebuilding, the selected one first
+ */
+export function resumableUdids(session: PreviousSession, simulators: Simulator[]): string[] {
+  const booted = new Set(simulators.filter((simulator) => simulator.state === "Booted").map((simulator) => simulator.udid))
+  const udids = session.streamingUdids.includes(session.simulatorUdid)
+    ? [session.simulatorUdid, ...session.streamingUdids.filter((udid) => udid !== session.simulatorUdid)]
+    : session.streamingUdids
+  return udids.filter((udid) => booted.has(udid))
+}
@@ -0,0 +1,32 @@
+import { describe, it, expect } from "vitest"
+import { DEFAULT_STREAM_FPS, DEFAULT_STREAM_QUALITY, editorOption, parseStreamSettings } from "./settings"
+
+describe("settings", () => {
+  describe("parseStreamSettings", () => {
+    it("reads stored values", () => {
+      expect(parseStreamSettings({ streamFps: "30", streamQuality: "0.5" })).toEqual({
+        fps: 30,
+        quality: 0.5,
+      })
+    })
+
+    it("falls back to defaults for missing or invalid values", () => {
+      expect(parseStreamSettings({ streamFps: "fast", streamQuality: "-1" })).toEqual({
+        fps: DEFAULT_STREAM_FPS,
+        quality: DEFAULT_STREAM_QUALITY,
+      })
+    })
+
+    it("clamps quality to 1", () => {
+      expect(parseStreamSettings({ streamQuality: "4" }).quality).toBe(1)
+    })
+  })
+
+  describe("editorOption", () => {
+    it("maps presets and custom commands", () => {
+      expect(editorOption(undefined)).toBe("xcode")
+      expect(editorOption("cursor")).toBe("cursor")
+      expect(editorOption("zed {file}:{line}")).toBe("custom")
+    })
+  })
+})
@@ -0,0 +1,92 @@
+import { api, type AppSettings } from "@/lib/api"
+import {
+  getThemePreference,
+  setThemePreference,
+  subscribeTheme,
+  type ThemePreference,
+} from "@/lib/theme"
+
+export const DEFAULT_STREAM_FPS = 60
+export const DEFAULT_STREAM_QUALITY = 0.7
+
+export const STREAM_FPS_OPTIONS = [15, 30, 60]
+
+export const EDITORS: { value: string; label: string }[] = [
+  { value: "xcode", label: "Xcode" },
+  { value: "vscode", label: "Visual Studio Code" },
+  { value: "cursor", label: "Cursor" },
+  { value: "custom", label: "Custom command" },
+]
+
+/** The editor option a saved editorCommand setting corresponds to */
+export function editorOption(editorCommand: string | undefined): string {
+  if (!editorCommand) return "xcode"
+  return EDITORS.some((editor) => editor.value === editorCommand) ? editorCommand : "custom"
+}
+
+export interface TimeoutField {
+  key: "buildTimeout" | "xcodebuildTimeout" | "simctlTimeout" | "toolTimeout" | "uploadTimeout" | "sessionIdleTimeout"
+  label: string
+  defaultSeconds: number
+}
+
+/** Timeouts for external tools and idle sessions in seconds; 0 disables the limit */
+export const TIMEOUT_FIELDS: TimeoutField[] = [
+  { key: "buildTimeout", label: "Build", defaultSeconds: 30 * 60 },
+  { key: "xcodebuildTimeout", label: "Project inspection", defaultSeconds: 2 * 60 },
+  { key: "simctlTimeout", label: "Simulator commands", defaultSeconds: 60 },
+  { key: "toolTimeout", label: "Other tools", defaultSeconds: 2 * 60 },
+  { key: "uploadTimeout", label: "TestFlight uploads", defaultSeconds: 60 * 60 },
+  { key: "sessionIdleTimeout", label: "Idle stream sessions", defaultSeconds: 5 * 60 },
+]
+
+export interface StreamSettings {
+  fps: number
+  quality: number
+}
+
+/**
+ * Read stream settings, falling back to defaults for missing or invalid values
+ */
+export function parseStreamSettings(settings: AppSettings): StreamSettings {
+  const fps = parseInt(settings.streamFps ?? "", 10)
+  const quality = parseFloat(settings.streamQuality ?? "")
+
+  return {
+    fps: Number.isFinite(fps) && fps > 0 ? Math.min(fps, 120) : DEFAULT_STREAM_FPS,
+    quality:
+      Number.isFinite(quality) && quality > 0 ? Math.min(quality, 1) : DEFAULT_STREAM_QUALITY,
+  }
+}
+
+function isThemePreference(value: string | undefined): value is ThemePreference {
+  return value === "system" || value === "light" || value === "dark"
+}
+
+/**
+ * Restore the theme saved in settings and persist later changes to it
+ */
+export function syncThemeWithSettings(): () => void {
+  let persisted: ThemePreference | null = null
+
+  api.settings
+    .get()
+    .then((settings) => {
+      if (isThemePreference(settings.theme)) {
+        persisted = settings.theme
+        if (settings.theme !== getThemePreference()) {
+          setThemePreference(settings.theme)
+        }
+      }
+    })
+    .catch((err) => console.error("Failed to load settings:", err))
+
+  return subscribeTheme(() => {
+    const preference = getThemePreference()
+    if (preference === persisted) return
+    persisted = preference
+    api.settings
+      .update({ theme: preference })
+      .catch((err) => console.error("Failed to save theme:", err))
+  })
+}
@@ -0,0 +1,64 @@
+import { describe, it, expect } from "vitest"
+import { formatRuntime, groupSimulatorsByRuntime, pickDefaultSimulator } from "./simulators"
+import type { Simulator } from "./api"
+
+function simulator(overrides: Partial<Simulator>): Simulator {
+  return {
+    udid: "UDID",
+    name: "iPhone 16",
+    state: "Shutdown",
+    runtime: "com.apple.CoreSimulator.SimRuntime.iOS-18-2",
+    ...overrides,
+  }
+}
+
+describe("simulators", () => {
+  describe("formatRuntime", () => {
+    it("formats CoreSimulator runtime identifiers", () => {
+      expect(formatRuntime("com.apple.CoreSimulator.SimRuntime.iOS-18-2")).toBe("iOS 18.2")
+      expect(formatRuntime("com.apple.CoreSimulator.SimRuntime.watchOS-11-0")).toBe("watchOS 11.0")
+    })
+
+    it("returns unknown identifiers unchanged", () => {
+      expect(formatRuntime("custom")).toBe("custom")
+    })
+  })
+
+  describe("groupSimulatorsByRuntime", () => {
+    it("groups by runtime with newest iOS first", () => {
+      const groups = groupSimulatorsByRuntime([
+        simulator({ udid: "a", runtime: "com.apple.CoreSimulator.SimRuntime.iOS-17-5" }),
+        simulator({ udid: "b", runtime: "com.apple.CoreSimulator.SimRuntime.watchOS-11-0" }),
+        simulator({ udid: "c", runtime: "com.apple.CoreSimulator.SimRuntime.iOS-18-2" }),
+        simulator({ udid: "d", runtime: "com.apple.CoreSimulator.SimRuntime.iOS-17-5" }),
+      ])
+
+      expect(groups.map((g) => g.label)).toEqual(["iOS 18.2", "iOS 17.5", "watchOS 11.0"])
+      expect(groups[1].simulators.map((s) => s.udid)).toEqual(["a", "d"])
+    })
+  })
+
+  describe("pickDefaultSimulator", () => {
+    const simulators = [
+      simulator({ udid: "a" }),
+      simulator({ udid: "b", state: "Booted" }),
+    ]
+
+    it("prefers the persisted simulator", () => {
+      expect(pickDefaultSimulator(simulators, "a")).toBe("a")
+    })
+
+    it("falls back to a booted simulator when the persisted one is gone", () => {
+      expect(pickDefaultSimulator(simulators, "missing")).toBe("b")
+    })
+
+    it("matches the plasma.toml device by name when nothing is persisted", () => {
+      const named = [...simulators, simulator({ udid: "c", name: "iPad Pro" })]
+      expect(pickDefaultSimulator(named, undefined, "ipad pro")).toBe("c")
+    })
+
+    it("returns an empty string when there are no simulators", () => {
+      expect(pickDefaultSimulator([])).toBe("")
+    })
+  })
+})
@@ -0,0 +1,88 @@
+import type { Simulator } from "@/lib/api"
+
+export interface RuntimeGroup {
+  runtime: string
+  label: string
+  simulators: Simulator[]
+}
+
+const PLATFORM_ORDER = ["iOS", "iPadOS", "watchOS", "tvOS", "visionOS", "xrOS"]
+
+/**
+ * Turn a CoreSimulator runtime identifier into a readable label,
+ * e.g. "com.apple.CoreSimulator.SimRuntime.iOS-18-2" -> "iOS 18.2"
+ */
+export function formatRuntime(runtime: string): string {
+  const identifier = runtime.split(".").pop() || runtime
+  const match = identifier.match(/^([A-Za-z]+)-(.+)$/)
+  if (!match) return identifier
+
+  const [, platform, version] = match
+  return `${platform} ${version.replace(/-/g, ".")}`
+}
+
+function runtimeSortKey(label: string): [number, number[]] {
+  const [platform, version = ""] = label.split(" ")
+  const platformIndex = PLATFORM_ORDER.indexOf(platform)
+  return [
+    platformIndex === -1 ? PLATFORM_ORDER.length : platformIndex,
+    version.split(".").map((part) => parseInt(part) || 0),
+  ]
+}
+
+/**
+ * Group simulators by runtime, newest runtime first within each platform
+ */
+export function groupSimulatorsByRuntime(simulators: Simulator[]): RuntimeGroup[] {
+  const groups = new Map<string, RuntimeGroup>()
+
+  for (const simulator of simulators) {
+    let group = groups.get(simulator.runtime)
+    if (!group) {
+      group = {
+        runtime: simulator.runtime,
+        label: formatRuntime(simulator.runtime),
+        simulators: [],
+      }
+      groups.set(simulator.runtime, group)
+    }
+    group.simulators.push(simulator)
+  }
+
+  return [...groups.values()].sort((a, b) => {
+    const [aPlatform, aVersion] = runtimeSortKey(a.label)
+    const [bPlatform, bVersion] = runtimeSortKey(b.label)
+    if (aPlatform !== bPlatform) return aPlatform - bPlatform
+
+    for (let i = 0; i < Math.max(aVersion.length, bVersion.length); i++) {
+      const diff = (bVersion[i] ?? 0) - (aVersion[i] ?? 0)
+      if (diff !== 0) return diff
+    }
+    return a.label.localeCompare(b.label)
+  })
+}
+
+/**
+ * Pick the simulator to preselect: the persisted one if it still exists,
+ * then the plasma.toml device, otherwise a booted device, otherwise the first one available
+ */
+export function pickDefaultSimulator(
+  simulators: Simulator[],
+  preferredUdid?: string | null,
+  preferredDevice?: string | null
+): string {
+  if (preferredUdid && simulators.some((s) => s.udid === preferredUdid)) {
+    return preferredUdid
+  }
+  // plasma.toml names a device by UDID or name
+  if (preferredDevice) {
+    const matches = simulators.filter(
+      (s) => s.udid === preferredDevice || s.name.toLowerCase() === preferredDevice.toLowerCase()
+    )
+    const match = matches.find((s) => s.state === "Booted") ?? matches[0]
+    if (match) return match.udid
+  }
+  const booted = simulators.find((s) => s.state === "Booted")
+  if (booted) return booted.udid
+  return simulators[0]?.udid ?? ""
+}
@@ -0,0 +1,32 @@
+import { describe, it, expect } from "vitest"
+import { captureFps, formatBandwidth, formatMemory } from "./stream-stats"
+
+describe("stream-stats", () => {
+  describe("formatBandwidth", () => {
+    it("picks a unit based on magnitude", () => {
+      expect(formatBandwidth(512)).toBe("512 B/s")
+      expect(formatBandwidth(20 * 1024)).toBe("20 KB/s")
+      expect(formatBandwidth(1.5 * 1024 * 1024)).toBe("1.5 MB/s")
+    })
+  })
+
+  describe("formatMemory", () => {
+    it("picks a unit based on magnitude", () => {
+      expect(formatMemory(512)).toBe("512 KB")
+      expect(formatMemory(210 * 1024)).toBe("210 MB")
+      expect(formatMemory(1.5 * 1024 * 1024)).toBe("1.5 GB")
+    })
+  })
+
+  describe("captureFps", () => {
+    it("reads the frame rate from a capture report", () => {
+      expect(captureFps({ fps: 59.8 })).toBe(59.8)
+      expect(captureFps({ capture_fps: 30 })).toBe(30)
+    })
+
+    it("returns null when the report has no frame rate", () => {
+      expect(captureFps(null)).toBeNull()
+      expect(captureFps({ fps: "fast" })).toBeNull()
+    })
+  })
+})
@@ -0,0 +1,39 @@
+/**
+ * Format a byte rate for display, e.g. 1536000 -> "1.5 MB/s"
+ */
+export function formatBandwidth(bytesPerSecond: number): string {
+  if (bytesPerSecond >= 1024 * 1024) {
+    return `${(bytesPerSecond / (1024 * 1024)).toFixed(1)} MB/s`
+  }
+  if (bytesPerSecond >= 1024) {
+    return `${Math.round(bytesPerSecond / 1024)} KB/s`
+  }
+  return `${bytesPerSecond} B/s`
+}
+
+/**
+ * Format a resident memory size given in kilobytes, e.g. 215040 -> "210 MB"
+ */
+export function formatMemory(kilobytes: number): string {
+  if (kilobytes >= 1024 * 1024) {
+    return `${(kilobytes / (1024 * 1024)).toFixed(1)} GB`
+  }
+  if (kilobytes >= 1024) {
+    return `${Math.round(kilobytes / 1024)} MB`
+  }
+  return `${kilobytes} KB`
+}
+
+/**
+ * Extract the capture frame rate from a simulator-server fps_report, if present
+ */
+export function captureFps(report: Record<string, unknown> | null): number | null {
+  if (!report) return null
+  for (const key of ["fps", "captureFps", "capture_fps"]) {
+    const value = report[key]
+    if (typeof value === "number" && Number.isFinite(value)) {
+      return value
+    }
+  }
+  return null
+}
@@ -0,0 +1,16 @@
+import { describe, it, expect } from "vitest"
+import { resolveTheme } from "./theme"
+
+describe("theme", () => {
+  describe("resolveTheme", () => {
+    it("follows the system appearance when set to system", () => {
+      expect(resolveTheme("system", true)).toBe("dark")
+      expect(resolveTheme("system", false)).toBe("light")
+    })
+
+    it("uses the manual override regardless of system appearance", () => {
+      expect(resolveTheme("light", true)).toBe("light")
+      expect(resolveTheme("dark", false)).toBe("dark")
+    })
+  })
+})
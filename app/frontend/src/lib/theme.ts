@@ -0,0 +1,110 @@
+import { useSyncExternalStore } from "react"
+
+export type ThemePreference = "system" | "light" | "dark"
+export type ResolvedTheme = "light" | "dark"
+
+const STORAGE_KEY = "plasma:theme"
+const DARK_QUERY = "(prefers-color-scheme: dark)"
+
+const listeners = new Set<() => void>()
+let preference: ThemePreference = readStoredPreference()
+
+function readStoredPreference(): ThemePreference {
+  try {
+    const stored = localStorage.getItem(STORAGE_KEY)
+    if (stored === "light" || stored === "dark" || stored === "system") {
+      return stored
+    }
+  } catch {
+    // Storage unavailable (e.g. private mode)
+  }
+  return "system"
+}
+
+function systemPrefersDark(): boolean {
+  if (typeof window === "undefined" || typeof window.matchMedia !== "function") {
+    return true
+  }
+  return window.matchMedia(DARK_QUERY).matches
+}
+
+/**
+ * Resolve a preference against the current system appearance
+ */
+export function resolveTheme(pref: ThemePreference, prefersDark: boolean): ResolvedTheme {
+  if (pref === "system") {
+    return prefersDark ? "dark" : "light"
+  }
+  return pref
+}
+
+function notify() {
+  for (const listener of listeners) {
+    listener()
+  }
+}
+
+/**
+ * Apply the resolved theme to the document root
+ */
+export function applyTheme() {
+  const resolved = resolveTheme(preference, systemPrefersDark())
+  const root = document.documentElement
+  root.classList.toggle("dark", resolved === "dark")
+  root.classList.toggle("light", resolved === "light")
+  root.style.colorScheme = resolved
+}
+
+export function getThemePreference(): ThemePreference {
+  return preference
+}
+
+export function getResolvedTheme(): ResolvedTheme {
+  return resolveTheme(preference, systemPrefersDark())
+}
+
+export function setThemePreference(next: ThemePreference) {
+  preference = next
+  try {
+    localStorage.setItem(STORAGE_KEY, next)
+  } catch {
+    // Storage unavailable (e.g. private mode)
+  }
+  // Keep native window chrome in sync when running inside Electron
+  window.electron?.setThemeSource?.(next)
+  applyTheme()
+  notify()
+}
+
+export function subscribeTheme(listener: () => void): () => void {
+  listeners.add(listener)
+  return () => listeners.delete(listener)
+}
+
+/**
+ * Apply the stored theme and follow system appearance changes
+ */
+export function initTheme(): () => void {
+  applyTheme()
+  window.electron?.setThemeSource?.(preference)
+
+  if (typeof window.matchMedia !== "function") {
+    return () => {}
+  }
+
+  const media = window.matchMedia(DARK_QUERY)
+  const handleChange = () => {
+    applyTheme()
+    notify()
+  }
+  media.addEventListener("change", handleChange)
+  return () => media.removeEventListener("change", handleChange)
+}
+
+export function useThemePreference(): ThemePreference {
+  return useSyncExternalStore(subscribeTheme, getThemePreference)
+}
+
+export function useResolvedTheme(): ResolvedTheme {
+  return useSyncExternalStore(subscribeTheme, getResolvedTheme)
+}
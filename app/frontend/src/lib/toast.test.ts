@@ -0,0 +1,48 @@
+import { describe, it, expect, beforeEach, afterEach, vi } from "vitest"
+import { MAX_TOASTS, dismissToast, getToasts, showErrorToast, showToast } from "./toast"
+
+describe("toast", () => {
+  beforeEach(() => {
+    vi.useFakeTimers()
+    for (const toast of getToasts()) {
+      dismissToast(toast.id)
+    }
+  })
+
+  afterEach(() => {
+    vi.useRealTimers()
+  })
+
+  it("auto-dismisses after the toast duration", () => {
+    showToast({ title: "Saved", duration: 1000 })
+    expect(getToasts()).toHaveLength(1)
+
+    vi.advanceTimersByTime(1000)
+    expect(getToasts()).toHaveLength(0)
+  })
+
+  it("keeps toasts with a zero duration until dismissed", () => {
+    const id = showToast({ title: "Sticky", duration: 0 })
+    vi.advanceTimersByTime(60_000)
+    expect(getToasts()).toHaveLength(1)
+
+    dismissToast(id)
+    expect(getToasts()).toHaveLength(0)
+  })
+
+  it("drops the oldest toasts when the queue is full", () => {
+    for (let i = 0; i < MAX_TOASTS + 2; i++) {
+      showToast({ title: `Toast ${i}` })
+    }
+    const titles = getToasts().map((toast) => toast.title)
+    expect(titles).toHaveLength(MAX_TOASTS)
+    expect(titles[0]).toBe("Toast 2")
+  })
+
+  it("uses the error message as the description", () => {
+    showErrorToast("Build failed", new Error("exit code 65"))
+    const [toast] = getToasts()
+    expect(toast.severity).toBe("error")
+    expect(toast.description).toBe("exit code 65")
+  })
+})
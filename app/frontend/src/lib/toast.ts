@@ -0,0 +1,115 @@
+import { useSyncExternalStore } from "react"
+
+export type ToastSeverity = "info" | "success" | "warning" | "error"
+
+export interface ToastAction {
+  label: string
+  onClick: () => void
+}
+
+export interface ToastOptions {
+  title: string
+  description?: string
+  severity?: ToastSeverity
+  action?: ToastAction
+  /** Milliseconds before auto-dismissal; 0 keeps the toast until dismissed */
+  duration?: number
+}
+
+export interface Toast {
+  id: number
+  title: string
+  description?: string
+  severity: ToastSeverity
+  action?: ToastAction
+  duration: number
+}
+
+// Errors stay up longer so there is time to read them
+const DEFAULT_DURATION: Record<ToastSeverity, number> = {
+  info: 4000,
+  success: 4000,
+  warning: 6000,
+  error: 8000,
+}
+
+/** Older toasts are dropped once the queue grows past this */
+export const MAX_TOASTS = 5
+
+const listeners = new Set<() => void>()
+const timers = new Map<number, ReturnType<typeof setTimeout>>()
+let toasts: Toast[] = []
+let nextId = 1
+
+function notify() {
+  for (const listener of listeners) {
+    listener()
+  }
+}
+
+function clearTimer(id: number) {
+  const timer = timers.get(id)
+  if (timer) {
+    clearTimeout(timer)
+    timers.delete(id)
+  }
+}
+
+/**
+ * Queue a toast and return its id
+ */
+export function showToast(options: ToastOptions): number {
+  const severity = options.severity ?? "info"
+  const toast: Toast = {
+    id: nextId++,
+    title: options.title,
+    description: options.description,
+    severity,
+    action: options.action,
+    duration: options.duration ?? DEFAULT_DURATION[severity],
+  }
+
+  const next = [...toasts, toast]
+  for (const dropped of next.slice(0, Math.max(0, next.length - MAX_TOASTS))) {
+    clearTimer(dropped.id)
+  }
+  toasts = next.slice(-MAX_TOASTS)
+
+  if (toast.duration > 0) {
+    timers.set(toast.id, setTimeout(() => dismissToast(toast.id), toast.duration))
+  }
+
+  notify()
+  return toast.id
+}
+
+/**
+ * Show an error toast, using the message of an Error when available
+ */
+export function showErrorToast(title: string, error?: unknown, action?: ToastAction): number {
+  const description =
+    error instanceof Error ? error.message : typeof error === "string" ? error : undefined
+  return showToast({ title, description, severity: "error", action })
+}
+
+export function dismissToast(id: number) {
+  clearTimer(id)
+  const next = toasts.filter((toast) => toast.id !== id)
+  if (next.length !== toasts.length) {
+    toasts = next
+    notify()
+  }
+}
+
+export function getToasts(): Toast[] {
+  return toasts
+}
+
+export function subscribeToasts(listener: () => void): () => void {
+  listeners.add(listener)
+  return () => listeners.delete(listener)
+}
+
+export function useToasts(): Toast[] {
+  return useSyncExternalStore(subscribeToasts, getToasts)
+}
@@ -0,0 +1,177 @@
+import { useCallback, useEffect, useState } from "react"
+import { useNavigate } from "react-router-dom"
+import { AlertTriangle, CheckCircle2, Copy, Loader2, RefreshCw, XCircle } from "lucide-react"
+import { Button } from "@/components/ui/button"
+import {
+  Card,
+  CardContent,
+  CardDescription,
+  CardFooter,
+  CardHeader,
+  CardTitle,
+} from "@/components/ui/card"
+import { ThemeToggle } from "@/components/ThemeToggle"
+import { api, type EnvironmentCheck, type EnvironmentCheckId } from "@/lib/api"
+import { markOnboardingComplete, requiredChecksPass } from "@/lib/onboarding"
+import { showErrorToast, showToast } from "@/lib/toast"
+
+function CheckIcon({ check }: { check: EnvironmentCheck }) {
+  if (check.ok) {
+    return <CheckCircle2 className="w-5 h-5 shrink-0 text-green-500" />
+  }
+  if (check.required) {
+    return <XCircle className="w-5 h-5 shrink-0 text-red-500" />
+  }
+  return <AlertTriangle className="w-5 h-5 shrink-0 text-yellow-500" />
+}
+
+/**
+ * First-run view that verifies the development environment before a project is picked
+ */
+export function Onboarding() {
+  const navigate = useNavigate()
+  const [checks, setChecks] = useState<EnvironmentCheck[]>([])
+  const [isChecking, setIsChecking] = useState(true)
+  const [fixing, setFixing] = useState<EnvironmentCheckId | null>(null)
+
+  const runChecks = useCallback(async () => {
+    setIsChecking(true)
+    try {
+      setChecks(await api.environment.checks())
+    } catch (err) {
+      console.error("Failed to check environment:", err)
+      showErrorToast("Couldn't check the environment", err)
+    } finally {
+      setIsChecking(false)
+    }
+  }, [])
+
+  useEffect(() => {
+    runChecks()
+  }, [runChecks])
+
+  const handleFix = async (check: EnvironmentCheck) => {
+    setFixing(check.id)
+    try {
+      await api.environment.fix(check.id)
+      showToast({
+        title: check.fixLabel ?? "Fix started",
+        description: "Finish the steps in the window that opened, then check again.",
+        action: { label: "Check again", onClick: () => runChecks() },
+      })
+    } catch (err) {
+      showErrorToast(`Couldn't fix "${check.title}"`, err)
+    } finally {
+      setFixing(null)
+    }
+  }
+
+  const handleCopyInstallCommand = async (command: string) => {
+    try {
+      await navigator.clipboard.writeText(command)
+      showToast({ title: "Install command copied", description: command, severity: "success" })
+    } catch (err) {
+      showErrorToast("Couldn't copy the install command", err)
+    }
+  }
+
+  const handleContinue = () => {
+    markOnboardingComplete()
+    navigate("/open", { replace: true })
+  }
+
+  const canContinue = checks.length > 0 && requiredChecksPass(checks)
+
+  return (
+    <div className="h-screen w-screen flex flex-col bg-background text-foreground overflow-hidden">
+      {/* Title Bar */}
+      <header
+        className="h-12 shrink-0 flex items-center justify-between pl-20 pr-4 border-b"
+        style={{ WebkitAppRegion: 'drag' } as React.CSSProperties}
+      >
+        <div className="flex items-center gap-3">
+          <img
+            src="/plasma-icon.png"
+            alt="Plasma"
+            className="w-6 h-6 rounded"
+          />
+          <span className="text-sm font-medium text-muted-foreground">Plasma</span>
+        </div>
+        <ThemeToggle />
+      </header>
+
+      {/* Main content */}
+      <div
+        className="flex-1 flex flex-col items-center p-6 pt-12 overflow-auto"
+        style={{ WebkitAppRegion: 'no-drag' } as React.CSSProperties}
+      >
+        <Card className="w-full max-w-lg">
+          <CardHeader>
+            <CardTitle className="text-2xl text-center">Welcome to Plasma</CardTitle>
+            <CardDescription className="text-center">
+              Let's make sure your Mac is ready to build and run apps.
+            </CardDescription>
+          </CardHeader>
+          <CardContent className="flex flex-col gap-3">
+            {isChecking && checks.length === 0 ? (
+              <div className="flex items-center justify-center gap-2 py-6 text-sm text-muted-foreground">
+                <Loader2 className="w-4 h-4 animate-spin" />
+                Checking your environment...
+              </div>
+            ) : (
+              checks.map((check) => (
+                <div key={check.id} className="flex items-start gap-3 p-3 rounded-md bg-secondary/30">
+                  <CheckIcon check={check} />
+                  <div className="flex-1 min-w-0 flex flex-col gap-0.5">
+                    <span className="text-sm font-medium">
+                      {check.title}
+                      {!check.required && (
+                        <span className="ml-1 text-xs font-normal text-muted-foreground">(optional)</span>
+                      )}
+                    </span>
+                    {check.detail && (
+                      <span className="text-xs text-muted-foreground break-words">{check.detail}</span>
+                    )}
+                    {check.ok && check.version && (
+                      <span className="text-xs text-muted-foreground">{check.version}</span>
+                    )}
+                    {!check.ok && check.installCommand && (
+                      <button
+                        className="text-xs font-mono text-muted-foreground hover:text-foreground flex items-center gap-1 self-start"
+                        title="Copy the install command"
+                        onClick={() => handleCopyInstallCommand(check.installCommand!)}
+                      >
+                        {check.installCommand}
+                        <Copy className="w-3 h-3 shrink-0" />
+                      </button>
+                    )}
+                  </div>
+                  {!check.ok && check.fixLabel && (
+                    <Button
+                      variant="outline"
+                      size="sm"
+                      onClick={() => handleFix(check)}
+                      disabled={fixing !== null}
+                    >
+                      {fixing === check.id && <Loader2 className="w-3 h-3 animate-spin" />}
+                      {check.fixLabel}
+                    </Button>
+                  )}
+                </div>
+              ))
+            )}
+          </CardContent>
+          <CardFooter className="flex justify-between">
+            <Button variant="ghost" onClick={runChecks} disabled={isChecking}>
+              <RefreshCw className={isChecking ? "w-4 h-4 animate-spin" : "w-4 h-4"} />
+              Check again
+            </Button>
+            <Button onClick={handleContinue} disabled={!canContinue}>
+              Continue
+            </Button>
+          </CardFooter>
+        </Card>
+      </div>
+    </div>
+  )
+}
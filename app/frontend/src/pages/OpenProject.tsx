@@ -12,9 +12,12 @@ import {
 import { Input } from "@/components/ui/input"
 import { Label } from "@/components/ui/label"
 import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/tabs"
-import { Apple, Smartphone, FolderOpen, Clock, Plus, FolderSearch, CheckCircle2, XCircle } from "lucide-react"
+import { Apple, Smartphone, FolderOpen, Clock, Plus, FolderSearch, CheckCircle2, XCircle, Settings, RotateCcw, X } from "lucide-react"
+import { ThemeToggle } from "@/components/ThemeToggle"
 import { api } from "@/lib/api"
-import type { UnifiedProject } from "@/lib/api"
+import type { AppTemplateInfo, UnifiedProject } from "@/lib/api"
+import { clearSession, loadSession } from "@/lib/session"
+import { showErrorToast } from "@/lib/toast"
 
 export function OpenProject() {
   const navigate = useNavigate()
@@ -27,6 +30,9 @@ export function OpenProject() {
   // Form state for new project
   const [newProjectName, setNewProjectName] = useState("")
   const [newProjectDirectory, setNewProjectDirectory] = useState("")
+  // A SwiftUI app template, or "" for the iOS and Android project
+  const [newProjectTemplate, setNewProjectTemplate] = useState("")
+  const [appTemplates, setAppTemplates] = useState<AppTemplateInfo[]>([])
 
   // Validation state
   const [xcodeValidation, setXcodeValidation] = useState<{ valid: boolean; error?: string } | null>(null)
@@ -37,9 +43,13 @@ export function OpenProject() {
   // Recent projects
   const [recentProjects, setRecentProjects] = useState<UnifiedProject[]>([])
 
-  // Load recent projects on mount
+  // What was open when Plasma last quit or crashed
+  const [previousSession, setPreviousSession] = useState(loadSession)
+
+  // Load recent projects and app templates on mount
   useEffect(() => {
     api.projects.getRecentUnified(5).then(setRecentProjects).catch(console.error)
+    api.projects.templates().then(setAppTemplates).catch(console.error)
   }, [])
 
   // Validate Xcode path when it changes
@@ -101,6 +111,23 @@ export function OpenProject() {
 
     setIsCreating(true)
 
+    if (newProjectTemplate) {
+      try {
+        const result = await api.projects.newApp({
+          name: newProjectName.trim(),
+          directory: newProjectDirectory.trim(),
+          template: newProjectTemplate as AppTemplateInfo["id"],
+        })
+        // Build and stream the new app as soon as the editor opens
+        navigate(`/project/${result.project.id}?run=1`)
+      } catch (err) {
+        showErrorToast("Couldn't create the app", err)
+      } finally {
+        setIsCreating(false)
+      }
+      return
+    }
+
     try {
       const result = await api.projects.create({
         name: newProjectName.trim(),
@@ -148,6 +175,11 @@ export function OpenProject() {
     navigate(`/project/${project.id}`)
   }
 
+  const handleDismissPreviousSession = () => {
+    clearSession()
+    setPreviousSession(null)
+  }
+
   const handleOpenExistingProject = async () => {
     // Must have at least one valid project
     const hasValidXcode = xcodeProjectPath.trim() && xcodeValidation?.valid
@@ -205,7 +237,7 @@ export function OpenProject() {
     <div className="h-screen w-screen flex flex-col bg-background text-foreground overflow-hidden">
       {/* Title Bar */}
       <header
-        className="h-12 shrink-0 flex items-center pl-20 pr-4 border-b"
+        className="h-12 shrink-0 flex items-center justify-between pl-20 pr-4 border-b"
         style={{ WebkitAppRegion: 'drag' } as React.CSSProperties}
       >
         <div className="flex items-center gap-3">
@@ -216,6 +248,19 @@ export function OpenProject() {
           />
           <span className="text-sm font-medium text-muted-foreground">Plasma</span>
         </div>
+        <div className="flex items-center gap-1">
+          <ThemeToggle />
+          <Button
+            variant="ghost"
+            size="icon-sm"
+            onClick={() => navigate("/settings")}
+            aria-label="Settings"
+            title="Settings"
+            style={{ WebkitAppRegion: 'no-drag' } as React.CSSProperties}
+          >
+            <Settings className="w-4 h-4" />
+          </Button>
+        </div>
       </header>
 
       {/* Main content */}
@@ -223,6 +268,31 @@ export function OpenProject() {
         className="flex-1 flex flex-col items-center p-6 pt-12 overflow-auto"
         style={{ WebkitAppRegion: 'no-drag' } as React.CSSProperties}
       >
+        {previousSession && (
+          <div className="w-full max-w-md mb-4 flex items-center gap-3 p-3 rounded-lg border bg-card">
+            <RotateCcw className="h-4 w-4 text-muted-foreground shrink-0" />
+            <div className="flex-1 min-w-0">
+              <div className="text-sm font-medium">Resume previous session</div>
+              <div className="text-xs text-muted-foreground truncate">
+                {previousSession.projectName || "Last project"}
+                {previousSession.streamingUdids.length > 0 && " · streaming"}
+              </div>
+            </div>
+            <Button size="sm" onClick={() => navigate(`/project/${previousSession.projectId}?resume=1`)}>
+              Resume
+            </Button>
+            <Button
+              variant="ghost"
+              size="icon-sm"
+              onClick={handleDismissPreviousSession}
+              aria-label="Dismiss"
+              title="Dismiss"
+            >
+              <X className="w-4 h-4" />
+            </Button>
+          </div>
+        )}
+
         <Card className="w-full max-w-md">
           <Tabs defaultValue={recentProjects.length > 0 ? "recent" : "new"}>
             <CardHeader className="pb-4">
@@ -352,7 +422,7 @@ export function OpenProject() {
                   )}
                   {!androidValidation && (
                     <p className="text-sm text-muted-foreground mt-1">
-                      Select a directory containing build.gradle or build.gradle.kts
+                      Select a directory containing settings.gradle or build.gradle (or their .kts variants)
                     </p>
                   )}
                 </div>
@@ -387,6 +457,28 @@ export function OpenProject() {
                   />
                 </div>
 
+                {/* Template */}
+                <div className="grid gap-2">
+                  <Label htmlFor="new-project-template">Template</Label>
+                  <select
+                    id="new-project-template"
+                    className="w-full h-9 px-3 rounded-md border border-input bg-background text-sm"
+                    value={newProjectTemplate}
+                    onChange={(e) => setNewProjectTemplate(e.target.value)}
+                  >
+                    <option value="">iOS and Android projects</option>
+                    {appTemplates.map((template) => (
+                      <option key={template.id} value={template.id}>
+                        SwiftUI app: {template.title}
+                      </option>
+                    ))}
+                  </select>
+                  <p className="text-sm text-muted-foreground mt-1">
+                    {appTemplates.find((template) => template.id === newProjectTemplate)?.description ??
+                      "An apple/ and android/ project side by side"}
+                  </p>
+                </div>
+
                 {/* Directory */}
                 <div className="grid gap-2">
                   <Label htmlFor="new-project-directory" className="flex items-center gap-2">
@@ -1,32 +1,79 @@
 import { useState, useEffect, useCallback } from "react"
-import { useParams, useNavigate } from "react-router-dom"
+import { useParams, useNavigate, useSearchParams } from "react-router-dom"
 import { Button } from "@/components/ui/button"
 import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/card"
 import { ScrollArea } from "@/components/ui/scroll-area"
-import { Play, FolderOpen, Loader2, CheckCircle, XCircle, ChevronDown, ChevronUp, Terminal, ArrowLeft } from "lucide-react"
-import { StreamViewer } from "@/components/StreamViewer"
+import { Play, FolderOpen, Loader2, CheckCircle, XCircle, ChevronDown, ChevronUp, Terminal, ArrowLeft, Settings, AlertTriangle, Trash2, GitBranch } from "lucide-react"
+import { SimulatorGrid } from "@/components/SimulatorGrid"
+import { SimulatorChecklist } from "@/components/SimulatorChecklist"
+import { BuildHooks } from "@/components/BuildHooks"
 import { ProjectSelector } from "@/components/ProjectSelector"
-import { api, type BuildEvent, type BuildProduct, type Simulator, type StreamLogEvent, type ProjectRecord } from "@/lib/api"
+import { SimulatorPicker } from "@/components/SimulatorPicker"
+import { BuildLogPanel } from "@/components/BuildLogPanel"
+import { BundleSize } from "@/components/BundleSize"
+import { SimulatorThumbnail } from "@/components/SimulatorThumbnail"
+import { TestFailures } from "@/components/TestFailures"
+import { DeviceToolbar } from "@/components/DeviceToolbar"
+import { ThemeToggle } from "@/components/ThemeToggle"
+import { formatGitInfo, trimToTail } from "@/lib/build-log"
+import { formatSize } from "@/lib/bundle-size"
+import { groupSchemesByProject, type SchemeGroup } from "@/lib/schemes"
+import { loadSession, resumableUdids, saveSession } from "@/lib/session"
+import { pickDefaultSimulator } from "@/lib/simulators"
+import { showErrorToast, showToast } from "@/lib/toast"
+import { api, type BuildArch, type BuildEvent, type BuildProduct, type FormattedBuildLine, type GitInfo, type Simulator, type StreamLogEvent, type ProjectRecord, type PackageResolution, type ProjectSettings, type SchemeSharing, type WatchStatus } from "@/lib/api"
+
+const DEFAULT_CONFIGURATIONS = ["Debug", "Release"]
 
 type BuildState =
   | { status: "idle" }
-  | { status: "building"; lines: string[] }
+  // packages is the package being resolved, while xcodebuild resolves them
+  | { status: "building"; packages?: string; queued?: number }
   | { status: "installing" }
-  | { status: "streaming"; udid: string }
+  | { status: "streaming"; udids: string[] }
   | { status: "error"; message: string }
   | { status: "success"; products: BuildProduct[] }
 
 export function ProjectEditor() {
   const { id } = useParams<{ id: string }>()
   const navigate = useNavigate()
+  const [searchParams, setSearchParams] = useSearchParams()
+  // Opened from "Resume previous session" on the open page
+  const isResuming = searchParams.get("resume") === "1"
+  // Opened right after creating an app, to build and stream it
+  const isNewApp = searchParams.get("run") === "1"
 
   const [project, setProject] = useState<ProjectRecord | null>(null)
   const [isLoadingProject, setIsLoadingProject] = useState(true)
   const [simulators, setSimulators] = useState<Simulator[]>([])
+  const [isLoadingSimulators, setIsLoadingSimulators] = useState(true)
   const [selectedSimulator, setSelectedSimulator] = useState("")
+  const [additionalSimulators, setAdditionalSimulators] = useState<string[]>([])
+  const [showAdditionalSimulators, setShowAdditionalSimulators] = useState(false)
+  const [focusedSimulator, setFocusedSimulator] = useState("")
+  const [showStreamStats, setShowStreamStats] = useState(false)
+  const [isWatching, setIsWatching] = useState(false)
+  const [watchStatus, setWatchStatus] = useState<WatchStatus | null>(null)
   const [schemes, setSchemes] = useState<string[]>([])
   const [selectedScheme, setSelectedScheme] = useState("")
+  const [schemeSharing, setSchemeSharing] = useState<Record<string, SchemeSharing>>({})
+  const [schemeGroups, setSchemeGroups] = useState<SchemeGroup[]>([])
+  const [configurations, setConfigurations] = useState<string[]>([])
+  const [selectedConfiguration, setSelectedConfiguration] = useState("")
+  const [packageResolution, setPackageResolution] = useState<PackageResolution>("automatic")
+  // Empty builds the architectures the project builds for by default
+  const [buildArch, setBuildArch] = useState<BuildArch | "">("")
   const [buildState, setBuildState] = useState<BuildState>({ status: "idle" })
+  const [buildLog, setBuildLog] = useState<string[]>([])
+  // Condensed form of each line of buildLog
+  const [formattedLog, setFormattedLog] = useState<(FormattedBuildLine | null)[]>([])
+  // Lines dropped from buildLog, and the build whose log file has them all
+  const [trimmedLines, setTrimmedLines] = useState(0)
+  const [buildId, setBuildId] = useState<string | null>(null)
+  // Commit the running or last build came from
+  const [buildGit, setBuildGit] = useState<GitInfo | null>(null)
+  // Product of the last successful build, for the size breakdown
+  const [builtProduct, setBuiltProduct] = useState<string | null>(null)
   const [streamLogs, setStreamLogs] = useState<string[]>([])
   const [showLogs, setShowLogs] = useState(true)
 
@@ -51,20 +98,89 @@ export function ProjectEditor() {
       .finally(() => setIsLoadingProject(false))
   }, [id, navigate])
 
-  // Discover schemes for a given path
+  // Discover schemes and configurations, restoring the ones remembered for this project
   const discoverSchemes = useCallback(async (path: string) => {
-    if (!path) return
+    if (!path || !id) return
 
     try {
-      const data = await api.xcode.discover({ path })
-      setSchemes(data.schemes || [])
-      if (data.schemes?.length > 0) {
-        setSelectedScheme(data.schemes[0])
-      }
+      const [data, settings] = await Promise.all([
+        api.xcode.discover({ path }),
+        api.projects.getSettings(parseInt(id)).catch((): ProjectSettings => ({})),
+      ])
+      const discoveredSchemes = data.schemes || []
+      // Workspaces don't report configurations, so fall back to Xcode's defaults
+      const discoveredConfigurations = data.configurations?.length > 0
+        ? data.configurations
+        : DEFAULT_CONFIGURATIONS
+
+      setSchemes(discoveredSchemes)
+      setSchemeSharing(data.schemeSharing ?? {})
+      setSchemeGroups(groupSchemesByProject(discoveredSchemes, data.projects))
+      setConfigurations(discoveredConfigurations)
+      setSelectedScheme(
+        settings.scheme && discoveredSchemes.includes(settings.scheme)
+          ? settings.scheme
+          : discoveredSchemes[0] ?? ""
+      )
+      setPackageResolution(settings.packageResolution ?? "automatic")
+      setBuildArch(settings.arch ?? "")
+      setSelectedConfiguration(
+        settings.configuration && discoveredConfigurations.includes(settings.configuration)
+          ? settings.configuration
+          : discoveredConfigurations.includes("Debug")
+          ? "Debug"
+          : discoveredConfigurations[0]
+      )
     } catch (err) {
       console.error("Failed to discover schemes:", err)
     }
-  }, [])
+  }, [id])
+
+  const persistSetting = (settings: Partial<Record<keyof ProjectSettings, string | null>>) => {
+    if (!id) return
+    api.projects.updateSettings(parseInt(id), settings)
+      .catch((err) => {
+        console.error("Failed to persist project settings:", err)
+        showToast({
+          title: "Couldn't save project settings",
+          description: err instanceof Error ? err.message : undefined,
+          severity: "warning",
+        })
+      })
+  }
+
+  const handleSelectScheme = (scheme: string) => {
+    setSelectedScheme(scheme)
+    persistSetting({ scheme })
+  }
+
+  const handleSelectConfiguration = (configuration: string) => {
+    setSelectedConfiguration(configuration)
+    persistSetting({ configuration })
+  }
+
+  const handleSelectPackageResolution = (resolution: PackageResolution) => {
+    setPackageResolution(resolution)
+    persistSetting({ packageResolution: resolution === "automatic" ? null : resolution })
+  }
+
+  const handleSelectBuildArch = (arch: BuildArch | "") => {
+    setBuildArch(arch)
+    persistSetting({ arch: arch || null })
+  }
+
+  const handlePurgePackageCache = async () => {
+    try {
+      const { bytes } = await api.xcode.purgePackageCache()
+      showToast({
+        title: "Package cache purged",
+        description: `Freed ${formatSize(bytes)}. Packages are fetched again on the next build.`,
+        severity: "success",
+      })
+    } catch (err) {
+      showErrorToast("Couldn't purge the package cache", err)
+    }
+  }
 
   // Load schemes when project loads
   useEffect(() => {
@@ -73,31 +189,91 @@ export function ProjectEditor() {
     }
   }, [project?.path, discoverSchemes])
 
-  // Fetch simulators on mount
+  // Fetch simulators and restore the simulator persisted for this project
+  const loadSimulators = useCallback(async () => {
+    if (!id) return
+
+    setIsLoadingSimulators(true)
+    try {
+      const [simulatorList, settings, appSettings] = await Promise.all([
+        api.simulator.list(),
+        api.projects.getSettings(parseInt(id)).catch((): ProjectSettings => ({})),
+        api.settings.get().catch(() => ({ defaultSimulatorUdid: undefined })),
+      ])
+      setSimulators(simulatorList)
+      setSelectedSimulator((current) =>
+        pickDefaultSimulator(
+          simulatorList,
+          settings.simulatorUdid || current || appSettings.defaultSimulatorUdid,
+          settings.preferredDevice
+        )
+      )
+    } catch (err) {
+      console.error("Failed to fetch simulators:", err)
+      showErrorToast("Couldn't load simulators", err, { label: "Retry", onClick: () => loadSimulators() })
+    } finally {
+      setIsLoadingSimulators(false)
+    }
+  }, [id])
+
   useEffect(() => {
-    api.simulator.list()
-      .then((simulatorList) => {
-        setSimulators(simulatorList)
-        const booted = simulatorList.find((s) => s.state === "Booted")
-        if (booted) {
-          setSelectedSimulator(booted.udid)
-        } else if (simulatorList.length > 0) {
-          setSelectedSimulator(simulatorList[0].udid)
-        }
+    loadSimulators()
+  }, [loadSimulators])
+
+  // Rebuild and relaunch on source changes while streaming
+  const projectPath = project?.path
+  const streamingUdids = buildState.status === "streaming" ? buildState.udids.join(",") : ""
+  useEffect(() => {
+    if (!isWatching || !streamingUdids || !projectPath || !selectedScheme) {
+      return
+    }
+
+    const unsubscribe = api.watch.onStatus((status) => {
+      if (status.projectPath !== projectPath) return
+      setWatchStatus(status)
+      if (status.state === "error") {
+        showErrorToast("Rebuild failed", status.message)
+      }
+    })
+
+    api.watch
+      .start({
+        path: projectPath,
+        scheme: selectedScheme,
+        configuration: selectedConfiguration || undefined,
+        udids: streamingUdids.split(","),
       })
-      .catch((err) => console.error("Failed to fetch simulators:", err))
-  }, [])
+      .catch((err) => {
+        showErrorToast("Couldn't start watching for changes", err)
+        setIsWatching(false)
+      })
+
+    return () => {
+      unsubscribe()
+      setWatchStatus(null)
+      api.watch.stop(projectPath).catch((err) => console.error("Failed to stop watching:", err))
+    }
+  }, [isWatching, streamingUdids, projectPath, selectedScheme, selectedConfiguration])
+
+  const handleSelectSimulator = (udid: string) => {
+    setSelectedSimulator(udid)
+    persistSetting({ simulatorUdid: udid })
+  }
 
-  // Subscribe to simulator logs when streaming starts
+  // Subscribe to the streamed simulators' logs when streaming starts
   useEffect(() => {
-    if (buildState.status !== "streaming") {
+    if (!streamingUdids) {
       return
     }
+    const udids = streamingUdids.split(",")
 
     setStreamLogs([])
 
     const formatLogEvent = (event: StreamLogEvent): string => {
-      const timestamp = new Date().toLocaleTimeString()
+      // Tag lines with the device when more than one is streaming
+      const timestamp = udids.length > 1
+        ? `${new Date().toLocaleTimeString()} ${event.udid.slice(0, 8)}`
+        : new Date().toLocaleTimeString()
       switch (event.type) {
         case "info":
           return `[${timestamp}] INFO: ${event.message}`
@@ -112,16 +288,64 @@ export function ProjectEditor() {
       }
     }
 
-    const unsubscribe = api.simulator.onLog((event) => {
-      setStreamLogs((prev) => [...prev.slice(-100), formatLogEvent(event)])
-    })
+    const unsubscribes = udids.map((udid) =>
+      api.simulator.onLog((event) => {
+        setStreamLogs((prev) => [...prev.slice(-100), formatLogEvent(event)])
+      }, udid)
+    )
 
     return () => {
-      unsubscribe()
+      unsubscribes.forEach((unsubscribe) => unsubscribe())
+    }
+  }, [streamingUdids])
+
+  // Remember what's open and streaming so a crash or restart can resume it
+  useEffect(() => {
+    if (!project || !selectedSimulator || isResuming) return
+    saveSession({
+      projectId: project.id,
+      projectName: project.name,
+      simulatorUdid: selectedSimulator,
+      streamingUdids: streamingUdids ? streamingUdids.split(",") : [],
+    })
+  }, [project, selectedSimulator, streamingUdids, isResuming])
+
+  // Stream the previous session's simulators again if they're still booted,
+  // without rebuilding; the app is still installed and usually still running
+  useEffect(() => {
+    if (!isResuming || !project || isLoadingSimulators) return
+    setSearchParams({}, { replace: true })
+    const session = loadSession()
+    if (!session || session.projectId !== project.id) return
+
+    const udids = resumableUdids(session, simulators)
+    if (udids.length === 0) {
+      showToast({
+        title: "Nothing to resume",
+        description: "The simulators from the previous session are no longer booted. Build & Run to start again.",
+        severity: "warning",
+      })
+      return
     }
-  }, [buildState.status])
+    setSelectedSimulator(udids[0])
+    setAdditionalSimulators(udids.slice(1))
+    setShowAdditionalSimulators(udids.length > 1)
+    Promise.all(udids.map((udid) => api.simulator.startStream({ udid })))
+      .then(() => {
+        setFocusedSimulator(udids[0])
+        setBuildState({ status: "streaming", udids })
+      })
+      .catch((err) => showErrorToast("Couldn't resume the previous session", err))
+  }, [isResuming, project, isLoadingSimulators, simulators, setSearchParams])
+
+  // Build and stream a new app as soon as its scheme and a simulator are picked
+  useEffect(() => {
+    if (!isNewApp || !project || !selectedScheme || !selectedSimulator) return
+    setSearchParams({}, { replace: true })
+    handleBuildAndRun()
+  }, [isNewApp, project, selectedScheme, selectedSimulator, setSearchParams])
 
-  const handleBuildAndRun = async () => {
+  const handleBuildAndRun = async (arch: BuildArch | "" = buildArch) => {
     if (!project?.path || !selectedScheme || !selectedSimulator) {
       setBuildState({
         status: "error",
@@ -130,29 +354,77 @@ export function ProjectEditor() {
       return
     }
 
-    setBuildState({ status: "building", lines: [] })
+    setBuildState({ status: "building" })
+    setBuildLog([])
+    setFormattedLog([])
+    setTrimmedLines(0)
+    setBuildId(null)
+    setBuildGit(null)
+    setBuiltProduct(null)
+
+    const targets = [
+      selectedSimulator,
+      ...additionalSimulators.filter((udid) => udid !== selectedSimulator),
+    ]
 
     try {
       const lines: string[] = []
+      const formatted: (FormattedBuildLine | null)[] = []
+      let trimmed = 0
+      // Batch output lines so large builds don't re-render on every line
+      let flushTimer: ReturnType<typeof setTimeout> | null = null
+      const flushLines = () => {
+        if (flushTimer) {
+          clearTimeout(flushTimer)
+          flushTimer = null
+        }
+        const excess = trimToTail(lines)
+        formatted.splice(0, excess)
+        trimmed += excess
+        setBuildLog([...lines])
+        setFormattedLog([...formatted])
+        setTrimmedLines(trimmed)
+      }
 
-      const buildResult = await new Promise<{ success: boolean; products: BuildProduct[]; buildDir?: string; error?: string }>((resolve) => {
+      const buildResult = await new Promise<{
+        success: boolean
+        products: BuildProduct[]
+        buildDir?: string
+        error?: string
+        suggestedArch?: BuildArch
+      }>((resolve) => {
         const unsubscribeBuild = api.xcode.onBuildEvent((event: BuildEvent) => {
           if (event.type === "output" && event.line) {
-            console.log("[BUILD]", event.line)
             lines.push(event.line)
-            setBuildState({ status: "building", lines: [...lines] })
+            formatted.push(event.formatted === undefined ? { kind: "info", text: event.line } : event.formatted)
+            if (!flushTimer) {
+              flushTimer = setTimeout(flushLines, 100)
+            }
+          } else if (event.type === "queued") {
+            setBuildState({ status: "building", queued: event.position })
+          } else if (event.type === "packages") {
+            setBuildState({
+              status: "building",
+              packages: event.phase === "resolved" ? undefined : event.package ?? "",
+            })
           } else if (event.type === "started") {
             console.log("[BUILD] Started:", event.scheme)
+            setBuildState({ status: "building" })
+            setBuildId(event.buildId ?? null)
+            setBuildGit(event.git ?? null)
           } else if (event.type === "completed") {
             console.log("[BUILD] Completed:", event.success ? "SUCCESS" : "FAILED")
+            flushLines()
             unsubscribeBuild()
             resolve({
               success: event.success ?? false,
               products: event.products || [],
               buildDir: event.buildDir,
+              suggestedArch: event.suggestedArch,
             })
           } else if (event.type === "error") {
             console.error("[BUILD] Error:", event.message)
+            flushLines()
             unsubscribeBuild()
             resolve({
               success: false,
@@ -165,6 +437,11 @@ export function ProjectEditor() {
         api.xcode.startBuild({
           path: project.path,
           scheme: selectedScheme,
+          configuration: selectedConfiguration || undefined,
+          packageResolution,
+          arch: arch || undefined,
+          // Booted while the app builds, so installing can start right after
+          udids: targets,
         })
       })
 
@@ -173,6 +450,20 @@ export function ProjectEditor() {
           status: "error",
           message: buildResult.error || "Build failed",
         })
+        const suggestedArch = buildResult.suggestedArch
+        showErrorToast(
+          `Building ${selectedScheme} failed`,
+          suggestedArch ? `Some libraries are only built for ${suggestedArch} simulators` : buildResult.error,
+          suggestedArch
+            ? {
+                label: `Build for ${suggestedArch}`,
+                onClick: () => {
+                  handleSelectBuildArch(suggestedArch)
+                  handleBuildAndRun(suggestedArch)
+                },
+              }
+            : { label: "Retry", onClick: () => handleBuildAndRun() }
+        )
         return
       }
 
@@ -190,25 +481,39 @@ export function ProjectEditor() {
         return
       }
 
+      setBuiltProduct(buildProducts[0].path)
       setBuildState({ status: "installing" })
 
-      await api.simulator.launch({
-        udid: selectedSimulator,
-        appPath: buildProducts[0].path,
-      })
+      const launches = await Promise.all(
+        targets.map((udid) =>
+          api.simulator.launch({
+            udid,
+            appPath: buildProducts[0].path,
+            projectPath: project.path,
+          })
+        )
+      )
+      const hookOutput = launches.flatMap((launch) => launch.hookOutput ?? [])
+      if (hookOutput.length > 0) {
+        setBuildLog((current) => [...current, ...hookOutput])
+        setFormattedLog((current) => [...current, ...hookOutput.map((text) => ({ kind: "info" as const, text }))])
+      }
 
-      await api.simulator.startStream({
-        udid: selectedSimulator,
-        fps: 60,
-        quality: 0.7,
-      })
+      await Promise.all(
+        targets.map((udid) =>
+          // Frame rate and quality come from the app settings on the server
+          api.simulator.startStream({ udid })
+        )
+      )
 
-      setBuildState({ status: "streaming", udid: selectedSimulator })
+      setFocusedSimulator(selectedSimulator)
+      setBuildState({ status: "streaming", udids: targets })
     } catch (err) {
       setBuildState({
         status: "error",
         message: err instanceof Error ? err.message : "Unknown error",
       })
+      showErrorToast("Couldn't run on the simulator", err)
     }
   }
 
@@ -230,6 +535,12 @@ export function ProjectEditor() {
   const getStatusText = () => {
     switch (buildState.status) {
       case "building":
+        if (buildState.queued !== undefined) {
+          return `Waiting for a build slot (#${buildState.queued})...`
+        }
+        if (buildState.packages !== undefined) {
+          return buildState.packages ? `Resolving ${buildState.packages}...` : "Resolving packages..."
+        }
         return "Building..."
       case "installing":
         return "Installing..."
@@ -286,6 +597,19 @@ export function ProjectEditor() {
             }}
           />
         </div>
+        <div className="flex items-center gap-1">
+          <ThemeToggle />
+          <Button
+            variant="ghost"
+            size="icon-sm"
+            onClick={() => navigate("/settings")}
+            aria-label="Settings"
+            title="Settings"
+            style={{ WebkitAppRegion: 'no-drag' } as React.CSSProperties}
+          >
+            <Settings className="w-4 h-4" />
+          </Button>
+        </div>
       </header>
 
       {/* Main content */}
@@ -311,11 +635,22 @@ export function ProjectEditor() {
               <select
                 className="w-full h-9 px-3 rounded-md border border-input bg-background text-sm"
                 value={selectedScheme}
-                onChange={(e) => setSelectedScheme(e.target.value)}
+                onChange={(e) => handleSelectScheme(e.target.value)}
                 disabled={schemes.length === 0}
               >
                 {schemes.length === 0 ? (
                   <option value="">No schemes found</option>
+                ) : schemeGroups.length > 1 ? (
+                  // Large workspaces: show which project each scheme builds
+                  schemeGroups.map((group) => (
+                    <optgroup key={group.project ?? ""} label={group.project ?? "Other"}>
+                      {group.schemes.map((scheme) => (
+                        <option key={scheme} value={scheme}>
+                          {scheme}
+                        </option>
+                      ))}
+                    </optgroup>
+                  ))
                 ) : (
                   schemes.map((scheme) => (
                     <option key={scheme} value={scheme}>
@@ -324,53 +659,171 @@ export function ProjectEditor() {
                   ))
                 )}
               </select>
+              {schemeSharing[selectedScheme] === "user" && (
+                <p className="flex items-start gap-1.5 text-xs text-yellow-500">
+                  <AlertTriangle className="w-3 h-3 mt-0.5 shrink-0" />
+                  This scheme isn't shared, so CI and teammates can't build it. Check Shared in Xcode's Manage Schemes to commit it.
+                </p>
+              )}
             </div>
 
-            {/* Simulator Selector */}
+            {/* Configuration Selector */}
             <div className="flex flex-col gap-2">
-              <label className="text-sm text-muted-foreground">Simulator</label>
+              <label className="text-sm text-muted-foreground">Configuration</label>
               <select
                 className="w-full h-9 px-3 rounded-md border border-input bg-background text-sm"
-                value={selectedSimulator}
-                onChange={(e) => setSelectedSimulator(e.target.value)}
-                disabled={simulators.length === 0}
+                value={selectedConfiguration}
+                onChange={(e) => handleSelectConfiguration(e.target.value)}
+                disabled={configurations.length === 0}
               >
-                {simulators.length === 0 ? (
-                  <option value="">No simulators found</option>
-                ) : (
-                  simulators.map((sim) => (
-                    <option key={sim.udid} value={sim.udid}>
-                      {sim.name} {sim.state === "Booted" ? "(Booted)" : ""}
-                    </option>
-                  ))
-                )}
+                {configurations.map((configuration) => (
+                  <option key={configuration} value={configuration}>
+                    {configuration}
+                  </option>
+                ))}
               </select>
             </div>
 
+            {/* Swift package resolution */}
+            <div className="flex flex-col gap-2">
+              <label className="text-sm text-muted-foreground">Packages</label>
+              <div className="flex items-center gap-1">
+                <select
+                  className="flex-1 h-9 px-3 rounded-md border border-input bg-background text-sm"
+                  value={packageResolution}
+                  onChange={(e) => handleSelectPackageResolution(e.target.value as PackageResolution)}
+                >
+                  <option value="automatic">Resolve automatically</option>
+                  <option value="resolved-file">Only use Package.resolved</option>
+                  <option value="disabled">Don't resolve</option>
+                </select>
+                <Button
+                  variant="ghost"
+                  size="icon-sm"
+                  title="Purge the Swift package cache"
+                  onClick={handlePurgePackageCache}
+                >
+                  <Trash2 className="w-4 h-4" />
+                </Button>
+              </div>
+            </div>
+
+            {/* Architecture */}
+            <div className="flex flex-col gap-2">
+              <label className="text-sm text-muted-foreground">Architecture</label>
+              <select
+                className="w-full h-9 px-3 rounded-md border border-input bg-background text-sm"
+                value={buildArch}
+                onChange={(e) => handleSelectBuildArch(e.target.value as BuildArch | "")}
+              >
+                <option value="">Project default</option>
+                <option value="arm64">arm64</option>
+                <option value="x86_64">x86_64 (Rosetta)</option>
+              </select>
+            </div>
+
+            {/* Simulator Selector */}
+            <div className="flex flex-col gap-2">
+              <label className="text-sm text-muted-foreground">Simulator</label>
+              <SimulatorPicker
+                simulators={simulators}
+                value={selectedSimulator}
+                onChange={handleSelectSimulator}
+                isLoading={isLoadingSimulators}
+                onRefresh={loadSimulators}
+              />
+              {buildState.status !== "streaming" &&
+                simulators.some((s) => s.udid === selectedSimulator && s.state === "Booted") && (
+                  <SimulatorThumbnail udid={selectedSimulator} className="h-40 self-start" />
+                )}
+              <button
+                className="text-xs text-muted-foreground hover:text-foreground flex items-center gap-1 self-start"
+                onClick={() => setShowAdditionalSimulators(!showAdditionalSimulators)}
+              >
+                {showAdditionalSimulators ? <ChevronUp className="w-3 h-3" /> : <ChevronDown className="w-3 h-3" />}
+                Also run on
+                {additionalSimulators.length > 0 && ` (${additionalSimulators.length})`}
+              </button>
+              {showAdditionalSimulators && (
+                <SimulatorChecklist
+                  simulators={simulators}
+                  excludeUdid={selectedSimulator}
+                  selected={additionalSimulators}
+                  onChange={setAdditionalSimulators}
+                />
+              )}
+            </div>
+
+            {id && <BuildHooks projectId={parseInt(id)} />}
+
             {/* Build & Run Button */}
             <Button
               className="w-full mt-2"
-              onClick={handleBuildAndRun}
+              onClick={() => handleBuildAndRun()}
               disabled={isLoading || !selectedScheme}
             >
               {getStatusIcon()}
               <span className="ml-2">{getStatusText()}</span>
             </Button>
 
+            {/* Watch Mode */}
+            {buildState.status === "streaming" && (
+              <div className="flex flex-col gap-1">
+                <label className="flex items-center gap-2 text-sm cursor-pointer">
+                  <input
+                    type="checkbox"
+                    checked={isWatching}
+                    onChange={(e) => setIsWatching(e.target.checked)}
+                  />
+                  Rebuild on save
+                </label>
+                {isWatching && watchStatus && (
+                  <span className="text-xs text-muted-foreground flex items-center gap-1 truncate" title={watchStatus.message}>
+                    {(watchStatus.state === "building" || watchStatus.state === "launching") && (
+                      <Loader2 className="w-3 h-3 animate-spin" />
+                    )}
+                    {watchStatus.state === "building"
+                      ? `Rebuilding (${watchStatus.changedFiles?.length ?? 0} changed)`
+                      : watchStatus.state === "launching"
+                      ? "Relaunching..."
+                      : watchStatus.state === "error"
+                      ? "Last rebuild failed"
+                      : watchStatus.message ?? "Watching for changes"}
+                  </span>
+                )}
+              </div>
+            )}
+
             {/* Build Output */}
-            {buildState.status === "building" && buildState.lines.length > 0 && (
+            {buildLog.length > 0 && (
               <div className="flex flex-col gap-2">
-                <label className="text-sm text-muted-foreground">
-                  Build Output
-                </label>
-                <ScrollArea className="h-[200px] rounded-md border p-2 bg-black/20">
-                  <pre className="text-xs font-mono text-muted-foreground whitespace-pre-wrap">
-                    {buildState.lines.slice(-50).join("\n")}
-                  </pre>
-                </ScrollArea>
+                <div className="flex items-center justify-between gap-2">
+                  <label className="text-sm text-muted-foreground">
+                    Build Output
+                  </label>
+                  {buildGit && (
+                    <span
+                      className={`text-xs flex items-center gap-1 truncate ${buildGit.dirty ? "text-yellow-500" : "text-muted-foreground"}`}
+                      title={buildGit.commit}
+                    >
+                      <GitBranch className="w-3 h-3 shrink-0" />
+                      {formatGitInfo(buildGit)}
+                    </span>
+                  )}
+                </div>
+                <BuildLogPanel
+                  lines={buildLog}
+                  formattedLines={formattedLog}
+                  trimmedLines={trimmedLines}
+                  fullLogUrl={buildId ? api.builds.logUrl(buildId) : undefined}
+                />
               </div>
             )}
 
+            {builtProduct && <BundleSize productPath={builtProduct} />}
+
+            <TestFailures projectPath={project.path} />
+
             {/* Stream Logs */}
             {buildState.status === "streaming" && (
               <div className="flex flex-col gap-2">
@@ -427,7 +880,23 @@ export function ProjectEditor() {
         {/* Right side - Simulator Stream */}
         <div className="flex-1 flex items-center justify-center min-w-0 min-h-0 overflow-hidden bg-black/20 rounded-xl">
           {buildState.status === "streaming" ? (
-            <StreamViewer udid={(buildState as { udid: string }).udid} />
+            <div className="h-full w-full flex flex-col items-center gap-3 p-3">
+              <DeviceToolbar
+                key={focusedSimulator}
+                udid={focusedSimulator}
+                showStats={showStreamStats}
+                onToggleStats={() => setShowStreamStats((show) => !show)}
+              />
+              <div className="flex-1 w-full min-h-0">
+                <SimulatorGrid
+                  udids={buildState.udids}
+                  simulators={simulators}
+                  focusedUdid={focusedSimulator}
+                  onFocus={setFocusedSimulator}
+                  showStats={showStreamStats}
+                />
+              </div>
+            </div>
           ) : (
             <div className="flex flex-col items-center justify-center gap-4 text-muted-foreground">
               <div className="w-[200px] h-[400px] border-2 border-dashed border-border rounded-3xl flex items-center justify-center">
@@ -0,0 +1,309 @@
+import { useCallback, useEffect, useState } from "react"
+import { useNavigate } from "react-router-dom"
+import { ArrowLeft, FolderOpen } from "lucide-react"
+import { Button } from "@/components/ui/button"
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card"
+import { Input } from "@/components/ui/input"
+import { Label } from "@/components/ui/label"
+import { SimulatorPicker } from "@/components/SimulatorPicker"
+import { api, type AppSettings, type Simulator } from "@/lib/api"
+import {
+  EDITORS,
+  STREAM_FPS_OPTIONS,
+  TIMEOUT_FIELDS,
+  editorOption,
+  parseStreamSettings,
+} from "@/lib/settings"
+import { setThemePreference, useThemePreference, type ThemePreference } from "@/lib/theme"
+import { showErrorToast } from "@/lib/toast"
+
+const QUALITY_OPTIONS = [
+  { value: "0.5", label: "Low" },
+  { value: "0.7", label: "Medium" },
+  { value: "0.9", label: "High" },
+]
+
+const BUILD_CONCURRENCY_OPTIONS = [1, 2, 3, 4]
+
+const selectClassName = "w-full h-9 px-3 rounded-md border border-input bg-background text-sm"
+
+export function Settings() {
+  const navigate = useNavigate()
+  const theme = useThemePreference()
+  const [settings, setSettings] = useState<AppSettings>({})
+  const [derivedDataPath, setDerivedDataPath] = useState("")
+  // Chosen editor, kept apart from the setting while a custom command is typed
+  const [editor, setEditor] = useState("xcode")
+  const [editorCommand, setEditorCommand] = useState("")
+  const [simulators, setSimulators] = useState<Simulator[]>([])
+  const [isLoadingSimulators, setIsLoadingSimulators] = useState(true)
+
+  useEffect(() => {
+    api.settings
+      .get()
+      .then((loaded) => {
+        setSettings(loaded)
+        setDerivedDataPath(loaded.derivedDataPath ?? "")
+        setEditor(editorOption(loaded.editorCommand))
+        setEditorCommand(editorOption(loaded.editorCommand) === "custom" ? loaded.editorCommand ?? "" : "")
+      })
+      .catch((err) => showErrorToast("Couldn't load settings", err))
+  }, [])
+
+  const loadSimulators = useCallback(async () => {
+    setIsLoadingSimulators(true)
+    try {
+      setSimulators(await api.simulator.list())
+    } catch (err) {
+      console.error("Failed to fetch simulators:", err)
+    } finally {
+      setIsLoadingSimulators(false)
+    }
+  }, [])
+
+  useEffect(() => {
+    loadSimulators()
+  }, [loadSimulators])
+
+  const save = (updates: Partial<Record<keyof AppSettings, string | null>>) => {
+    setSettings((current) => ({ ...current, ...updates } as AppSettings))
+    api.settings
+      .update(updates)
+      .then(setSettings)
+      .catch((err) => showErrorToast("Couldn't save settings", err))
+  }
+
+  const handleBrowseDerivedData = async () => {
+    const result = await api.showOpenDialog({
+      properties: ['openDirectory', 'createDirectory'],
+    })
+    if (!result.canceled && result.filePaths.length > 0) {
+      setDerivedDataPath(result.filePaths[0])
+      save({ derivedDataPath: result.filePaths[0] })
+    }
+  }
+
+  const stream = parseStreamSettings(settings)
+
+  return (
+    <div className="h-screen w-screen flex flex-col bg-background text-foreground overflow-hidden">
+      {/* Title Bar */}
+      <header
+        className="h-12 shrink-0 flex items-center pl-20 pr-4 border-b"
+        style={{ WebkitAppRegion: 'drag' } as React.CSSProperties}
+      >
+        <div className="flex items-center gap-3">
+          <button
+            onClick={() => navigate(-1)}
+            className="p-1 rounded hover:bg-secondary/50 transition-colors"
+            style={{ WebkitAppRegion: 'no-drag' } as React.CSSProperties}
+            aria-label="Back"
+          >
+            <ArrowLeft className="w-4 h-4 text-muted-foreground" />
+          </button>
+          <span className="text-sm font-medium text-muted-foreground">Settings</span>
+        </div>
+      </header>
+
+      {/* Main content */}
+      <div
+        className="flex-1 flex flex-col items-center gap-6 p-6 overflow-auto"
+        style={{ WebkitAppRegion: 'no-drag' } as React.CSSProperties}
+      >
+        <Card className="w-full max-w-lg">
+          <CardHeader>
+            <CardTitle>Streaming</CardTitle>
+            <CardDescription>Applies to streams started after the change.</CardDescription>
+          </CardHeader>
+          <CardContent className="flex flex-col gap-4">
+            <div className="flex flex-col gap-2">
+              <Label htmlFor="stream-fps">Frame rate</Label>
+              <select
+                id="stream-fps"
+                className={selectClassName}
+                value={String(stream.fps)}
+                onChange={(e) => save({ streamFps: e.target.value })}
+              >
+                {STREAM_FPS_OPTIONS.map((fps) => (
+                  <option key={fps} value={String(fps)}>{fps} FPS</option>
+                ))}
+              </select>
+            </div>
+            <div className="flex flex-col gap-2">
+              <Label htmlFor="stream-quality">Quality</Label>
+              <select
+                id="stream-quality"
+                className={selectClassName}
+                value={String(stream.quality)}
+                onChange={(e) => save({ streamQuality: e.target.value })}
+              >
+                {QUALITY_OPTIONS.map((option) => (
+                  <option key={option.value} value={option.value}>{option.label}</option>
+                ))}
+              </select>
+            </div>
+          </CardContent>
+        </Card>
+
+        <Card className="w-full max-w-lg">
+          <CardHeader>
+            <CardTitle>Building</CardTitle>
+          </CardHeader>
+          <CardContent className="flex flex-col gap-4">
+            <div className="flex flex-col gap-2">
+              <Label htmlFor="derived-data">Derived data path</Label>
+              <div className="flex gap-2">
+                <Input
+                  id="derived-data"
+                  placeholder="Xcode default"
+                  value={derivedDataPath}
+                  onChange={(e) => setDerivedDataPath(e.target.value)}
+                  onBlur={() => {
+                    if (derivedDataPath !== (settings.derivedDataPath ?? "")) {
+                      save({ derivedDataPath: derivedDataPath.trim() || null })
+                    }
+                  }}
+                />
+                <Button variant="outline" size="icon" onClick={handleBrowseDerivedData} aria-label="Browse">
+                  <FolderOpen className="w-4 h-4" />
+                </Button>
+              </div>
+            </div>
+            <div className="flex flex-col gap-2">
+              <Label>Default simulator</Label>
+              <SimulatorPicker
+                simulators={simulators}
+                value={settings.defaultSimulatorUdid ?? ""}
+                onChange={(udid) => save({ defaultSimulatorUdid: udid || null })}
+                isLoading={isLoadingSimulators}
+                onRefresh={loadSimulators}
+                noneLabel="Use the booted simulator"
+              />
+            </div>
+            <div className="flex flex-col gap-2">
+              <Label htmlFor="prewarm-simulator">Simulator pre-warming</Label>
+              <select
+                id="prewarm-simulator"
+                className={selectClassName}
+                value={settings.prewarmSimulator === "true" ? "true" : "false"}
+                onChange={(e) => save({ prewarmSimulator: e.target.value === "true" ? "true" : null })}
+              >
+                <option value="false">Boot when running</option>
+                <option value="true">Boot when a project opens</option>
+              </select>
+            </div>
+            <div className="flex flex-col gap-2">
+              <Label htmlFor="build-concurrency">Parallel builds</Label>
+              <select
+                id="build-concurrency"
+                className={selectClassName}
+                value={settings.buildConcurrency ?? "1"}
+                onChange={(e) => save({ buildConcurrency: e.target.value })}
+              >
+                {BUILD_CONCURRENCY_OPTIONS.map((count) => (
+                  <option key={count} value={String(count)}>
+                    {count === 1 ? "One at a time" : `Up to ${count}, sharing the cores`}
+                  </option>
+                ))}
+              </select>
+            </div>
+          </CardContent>
+        </Card>
+
+        <Card className="w-full max-w-lg">
+          <CardHeader>
+            <CardTitle>Editor</CardTitle>
+            <CardDescription>Clicking an error or warning in the build output opens it here.</CardDescription>
+          </CardHeader>
+          <CardContent className="flex flex-col gap-4">
+            <div className="flex flex-col gap-2">
+              <Label htmlFor="editor">Open files in</Label>
+              <select
+                id="editor"
+                className={selectClassName}
+                value={editor}
+                onChange={(e) => {
+                  setEditor(e.target.value)
+                  if (e.target.value !== "custom") {
+                    save({ editorCommand: e.target.value === "xcode" ? null : e.target.value })
+                  }
+                }}
+              >
+                {EDITORS.map((option) => (
+                  <option key={option.value} value={option.value}>{option.label}</option>
+                ))}
+              </select>
+            </div>
+            {editor === "custom" && (
+              <div className="flex flex-col gap-2">
+                <Label htmlFor="editor-command">Command</Label>
+                <Input
+                  id="editor-command"
+                  placeholder="zed {file}:{line}:{column}"
+                  value={editorCommand}
+                  onChange={(e) => setEditorCommand(e.target.value)}
+                  onBlur={() => {
+                    if (editorCommand.trim() && editorCommand.trim() !== settings.editorCommand) {
+                      save({ editorCommand: editorCommand.trim() })
+                    }
+                  }}
+                />
+              </div>
+            )}
+          </CardContent>
+        </Card>
+
+        <Card className="w-full max-w-lg">
+          <CardHeader>
+            <CardTitle>Timeouts</CardTitle>
+            <CardDescription>
+              Seconds before a hung xcodebuild, simctl, or other tool is terminated, or an unwatched simulator stream is shut down. Use 0 to wait indefinitely.
+            </CardDescription>
+          </CardHeader>
+          <CardContent className="flex flex-col gap-4">
+            {TIMEOUT_FIELDS.map((field) => (
+              <div key={field.key} className="flex flex-col gap-2">
+                <Label htmlFor={field.key}>{field.label}</Label>
+                <Input
+                  key={settings[field.key] ?? ""}
+                  id={field.key}
+                  type="number"
+                  min={0}
+                  placeholder={String(field.defaultSeconds)}
+                  defaultValue={settings[field.key] ?? ""}
+                  onBlur={(e) => {
+                    const value = e.target.value.trim()
+                    if (value !== (settings[field.key] ?? "")) {
+                      save({ [field.key]: value || null })
+                    }
+                  }}
+                />
+              </div>
+            ))}
+          </CardContent>
+        </Card>
+
+        <Card className="w-full max-w-lg">
+          <CardHeader>
+            <CardTitle>Appearance</CardTitle>
+          </CardHeader>
+          <CardContent>
+            <div className="flex flex-col gap-2">
+              <Label htmlFor="theme">Theme</Label>
+              <select
+                id="theme"
+                className={selectClassName}
+                value={theme}
+                onChange={(e) => setThemePreference(e.target.value as ThemePreference)}
+              >
+                <option value="system">System</option>
+                <option value="light">Light</option>
+                <option value="dark">Dark</option>
+              </select>
+            </div>
+          </CardContent>
+        </Card>
+      </div>
+    </div>
+  )
+}
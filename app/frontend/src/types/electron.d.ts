@@ -0,0 +1,27 @@
+// Electron APIs exposed by the preload script (see app/src/preload/index.ts).
+// Undefined when the frontend runs in a regular browser.
+
+declare namespace Electron {
+  interface OpenDialogOptions {
+    title?: string
+    defaultPath?: string
+    properties?: string[]
+  }
+
+  interface OpenDialogReturnValue {
+    canceled: boolean
+    filePaths: string[]
+  }
+}
+
+interface Window {
+  electron?: {
+    getVersion: () => Promise<string>
+    showOpenDialog: (options: Electron.OpenDialogOptions) => Promise<Electron.OpenDialogReturnValue>
+    quit: () => void
+    minimize: () => void
+    maximize: () => void
+    setThemeSource?: (source: "system" | "light" | "dark") => void
+    platform: string
+  }
+}
@@ -10,6 +10,8 @@ export default defineConfig({
   resolve: {
     alias: {
       '@': path.resolve(__dirname, './src'),
+      // Types shared with the Electron main process and server
+      '@shared': path.resolve(__dirname, '../src/shared'),
     },
   },
   server: {
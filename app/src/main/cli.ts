@@ -0,0 +1,1210 @@
+#!/usr/bin/env node
+/**
+ * Headless command line interface for scripts and CI
+ * Run with: node dist/main/cli.js <command>
+ *
+ * With --json, each command prints a single JSON document to stdout and
+ * progress output (e.g. xcodebuild logs) goes to stderr. Exit codes are
+ * 0 on success, 1 on failure, and 2 on invalid usage.
+ */
+
+import * as fs from 'fs';
+import * as path from 'path';
+import { parseArgs } from 'util';
+import {
+  buildScheme,
+  getLaunchableProducts,
+  BuildEvent,
+  BuildResult,
+  BuildProduct,
+  BuildPlatform,
+  BUILD_PLATFORMS,
+  PackageResolution,
+  PACKAGE_RESOLUTIONS,
+} from './services/xcode';
+import { BuildArch, BUILD_ARCHS } from './services/architectures';
+import { captureMacAppWindow, launchMacApp } from './services/mac-app';
+import { createSimulatorPair, listSimulatorPairs } from './services/watch-pairs';
+import { listFixtureSets, seedFixtures } from './services/fixtures';
+import { TestFlightEvent, uploadToTestFlight } from './services/asc';
+import {
+  bootSimulator,
+  findSimulator,
+  installAndLaunch,
+  listSimulators,
+  LaunchAttempts,
+  Simulator,
+} from './services/simulator';
+import { getRecentUnifiedProjects, getSettings, saveProject, saveUnifiedProject } from './services/database';
+import { AppTemplate, NewAppResult, scaffoldSwiftUIApp } from './services/project-scaffold';
+import { checkEnvironment } from './services/environment';
+import { watchProject } from './services/watcher';
+import { runHooks } from './services/hooks';
+import { getLaunchOptions, readManifest } from './services/manifest';
+import { killAllProcesses, reapOrphanedProcesses } from './services/process-manager';
+import { ErrorCode, PlasmaError, toErrorBody, toPlasmaError } from './services/errors';
+import { AutomationEvent, parseAutomationScript, runAutomation } from './services/automation';
+import {
+  parseScreenshotMatrix,
+  resolveMatrixDevices,
+  runScreenshotMatrix,
+  ScreenshotMatrixEvent,
+} from './services/screenshot-matrix';
+import { runTests, TestRun, TestRunEvent, TestRunOptions } from './services/test-runner';
+import { getBuildById } from './services/build-records';
+import { renderBuildLine } from './services/build-format';
+import fetch from 'node-fetch';
+import type { GitInfo } from './services/git';
+import { ExportedReport, ExportFormat, exportBuild, exportTestRun } from './services/report-export';
+
+const USAGE = `Usage: plasma <command> [options]
+
+Commands:
+  build <path> --scheme <name> [--configuration <name>] [--platform <platform>]
+      Build an Xcode scheme for the simulator, or for this Mac with --platform
+  run <path> --scheme <name> --simulator <name|udid> [--configuration <name>] [--watch]
+      Build, install, and launch an app on a simulator.
+      With --watch, rebuild and relaunch whenever a source file changes
+  run <path> --scheme <name> --platform macos|mac-catalyst [--screenshot <path>]
+      Build and launch a Mac app on this Mac, optionally saving a PNG of its window
+  test <path> --scheme <name> --simulator <name|udid> [--configuration <name>] [--coverage] [--shards <n>] [--max-attempts <n>]
+      Run a scheme's tests on a simulator, optionally collecting code coverage.
+      With --shards, split the tests across n clones of the simulator.
+      With --max-attempts, retry failed tests up to n runs in total
+  automate <script> --simulator <name|udid>
+      Run an automation script (JSON or TOML) against a simulator
+  ci <path> --scheme <name> --destination <name|udid> [--output <dir>] [--coverage] [--shards <n>] [--max-attempts <n>]
+      Build and test for CI, writing logs, JUnit reports, and summary.json to --output
+      (default plasma-ci). Exits 0 when tests pass, 1 when tests fail, 3 when the
+      build fails, and 4 when the tests couldn't run
+  screenshots <config>
+      Capture localized screenshots for every device and locale in a config
+  testflight <ipa> [<project path>] [--no-wait]
+      Upload an exported IPA to App Store Connect and wait until TestFlight has
+      processed it. The API key comes from APP_STORE_CONNECT_* variables or the
+      [app_store_connect] table of the project's plasma.toml (default: current directory)
+  fixtures list <path>
+      List the fixture sets declared in the project's plasma.toml
+  fixtures seed <path> <name> --simulator <name|udid>
+      Seed a simulator with a fixture set's photos, contacts, and calendar events
+  simulators list
+      List available simulators
+  simulators pairs
+      List phone and watch simulator pairs
+  simulators pair <watch udid> <phone udid>
+      Pair a watch simulator with a phone simulator
+  projects list
+      List recently opened projects
+  builds tail [--id <id>] [--server <url>]
+      Follow a build running in the Plasma app or server, the latest one without --id,
+      printing its output until it finishes
+  new <directory> --name <name> [--template <template>] [--simulator <name|udid>]
+      Create a SwiftUI app in <directory>/<name> from a template (hello-world, list,
+      or tabs) and register it. With --simulator, build and launch it right away
+  doctor
+      Check Xcode, simulator runtimes, and helper binaries
+
+Options:
+  --platform <platform>   ios-simulator (default), macos, or mac-catalyst (build, run)
+  --package-resolution <mode>
+                          automatic (default), resolved-file to only use the versions in
+                          Package.resolved, or disabled to skip resolution (build, run, ci)
+  --arch <arch>           arm64 or x86_64 to build for one architecture only; x86_64 simulator
+                          apps run through Rosetta on Apple silicon (build, run)
+  --json                  Print machine-readable JSON to stdout
+  --junit <path>          Write build or test results as JUnit XML (build, run, test)
+  --github-annotations    Print errors and failures as GitHub Actions annotations (build, run, test)
+  --raw                   Print xcodebuild's output as it is instead of one line per step (build, run, builds tail)
+  --server <url>          Plasma server to connect to (builds tail, default http://localhost:3001)
+  -h, --help              Show this help
+
+--scheme, --configuration, and --simulator (or --destination) default to the
+scheme, configuration, and device declared in the project's plasma.toml.
+`;
+
+const EXIT_SUCCESS = 0;
+const EXIT_FAILURE = 1;
+const EXIT_USAGE = 2;
+// Only used by ci, which tells a broken build apart from failing tests
+const EXIT_BUILD_FAILED = 3;
+const EXIT_TESTS_NOT_RUN = 4;
+
+const DEFAULT_SERVER_URL = 'http://localhost:3001';
+
+const DEFAULT_CI_OUTPUT = 'plasma-ci';
+
+interface CliOptions {
+  scheme?: string;
+  configuration?: string;
+  simulator?: string;
+  destination?: string;
+  output?: string;
+  platform?: string;
+  'package-resolution'?: string;
+  arch?: string;
+  id?: string;
+  server?: string;
+  screenshot?: string;
+  name?: string;
+  template?: string;
+  watch?: boolean;
+  'no-wait'?: boolean;
+  coverage?: boolean;
+  shards?: string;
+  'max-attempts'?: string;
+  json?: boolean;
+  junit?: string;
+  'github-annotations'?: boolean;
+  raw?: boolean;
+  help?: boolean;
+}
+
+// JSON output schemas
+
+interface BuildOutput {
+  success: boolean;
+  products: BuildProduct[];
+  buildDir: string | null;
+  /** Whether the products came from the build cache */
+  cached: boolean;
+  error: string | null;
+  code: ErrorCode | null;
+}
+
+interface RunOutput extends BuildOutput {
+  simulator: { udid: string; name: string } | null;
+  attempts?: LaunchAttempts;
+}
+
+interface MacRunOutput extends BuildOutput {
+  platform: BuildPlatform;
+  pid: number | null;
+  logPath: string | null;
+  screenshot: string | null;
+}
+
+interface CiSummary {
+  success: boolean;
+  exitCode: number;
+  projectPath: string;
+  scheme: string;
+  configuration: string;
+  simulator: { udid: string; name: string };
+  startedAt: string;
+  durationMs: number;
+  build: {
+    success: boolean;
+    buildId: string | null;
+    errors: number;
+    warnings: number;
+    error: string | null;
+    code: ErrorCode | null;
+    /** Commit, branch, and dirty state the build came from */
+    git: GitInfo | null;
+  };
+  tests: {
+    testRunId: string;
+    status: TestRun['status'];
+    passed: number;
+    failed: number;
+    skipped: number;
+    flaky: number;
+    lineCoverage: number | null;
+    resultBundlePath: string | null;
+    error: string | null;
+  } | null;
+  /** Paths of the files written to the output directory */
+  artifacts: Record<string, string>;
+}
+
+interface ErrorOutput {
+  success: false;
+  error: string;
+  code: ErrorCode;
+}
+
+function printJson(value: unknown): void {
+  process.stdout.write(`${JSON.stringify(value, null, 2)}\n`);
+}
+
+/**
+ * Report an error in the active output format and return the exit code
+ * Plain messages are reported as invalid_request for usage errors and internal otherwise.
+ */
+function fail(options: CliOptions, error: unknown, exitCode: number = EXIT_FAILURE): number {
+  const { error: message, code } = toErrorBody(
+    toPlasmaError(error, exitCode === EXIT_USAGE ? 'invalid_request' : 'internal')
+  );
+  if (options.json) {
+    printJson({ success: false, error: message, code } satisfies ErrorOutput);
+  } else {
+    console.error(message);
+  }
+  return exitCode;
+}
+
+/**
+ * Build a scheme, echoing xcodebuild output as it arrives.
+ * Output goes to stderr in JSON mode so stdout stays parseable.
+ */
+async function buildWithOutput(projectPath: string, options: CliOptions): Promise<BuildResult> {
+  const log = options.json ? console.error : console.log;
+  const stream = options.json ? process.stderr : process.stdout;
+  const color = Boolean(stream.isTTY) && !process.env.NO_COLOR;
+
+  // Packages resolve before the build starts, without other output
+  let started = false;
+  const result = await buildScheme(
+    projectPath,
+    options.scheme!,
+    options.configuration,
+    getSettings().derivedDataPath || undefined,
+    (event) => {
+      if (event.type === 'started') {
+        started = true;
+      } else if (event.type === 'packages' && !started && event.message) {
+        log(event.message);
+      } else if (event.type === 'output' && event.line) {
+        if (options.raw || event.formatted === undefined) {
+          log(event.line);
+        } else if (event.formatted) {
+          log(renderBuildLine(event.formatted, color));
+        }
+      }
+    },
+    options.platform as BuildPlatform | undefined,
+    options['package-resolution'] as PackageResolution | undefined,
+    options.arch as BuildArch | undefined
+  );
+
+  if (!result.success && !options.json) {
+    console.error(`Build failed${result.error ? `: ${result.error}` : ''}`);
+    if (result.suggestedArch) {
+      console.error(`Try again with --arch ${result.suggestedArch}`);
+    }
+  }
+  return result;
+}
+
+function toBuildOutput(result: BuildResult): BuildOutput {
+  return {
+    success: result.success,
+    products: result.products,
+    buildDir: result.buildDir ?? null,
+    cached: result.cached ?? false,
+    error: result.success ? null : result.error ?? 'Build failed',
+    code: result.success ? null : result.code ?? 'build_failed',
+  };
+}
+
+/**
+ * Write the exports requested with --junit and --github-annotations.
+ * Annotations go to stderr in JSON mode so stdout stays parseable; the
+ * Actions runner reads workflow commands from both.
+ */
+function writeExports(options: CliOptions, exportAs: (format: ExportFormat) => ExportedReport): void {
+  if (options.junit) {
+    fs.writeFileSync(options.junit, exportAs('junit').body);
+  }
+  if (options['github-annotations']) {
+    (options.json ? process.stderr : process.stdout).write(exportAs('github').body);
+  }
+}
+
+/**
+ * Export a build's diagnostics, when the build got far enough to be stored
+ */
+function writeBuildExports(result: BuildResult, options: CliOptions): void {
+  if (!result.buildId || (!options.junit && !options['github-annotations'])) {
+    return;
+  }
+  const build = getBuildById(result.buildId);
+  const root = process.env.GITHUB_WORKSPACE ?? process.cwd();
+  writeExports(options, (format) => exportBuild(build, format, { root }));
+}
+
+/**
+ * Fill options the user didn't pass from the project's plasma.toml
+ */
+function withManifestDefaults(projectPath: string | undefined, options: CliOptions): CliOptions {
+  const manifest = projectPath ? readManifest(projectPath) : null;
+  return {
+    ...options,
+    scheme: options.scheme ?? manifest?.scheme,
+    configuration: options.configuration ?? manifest?.configuration,
+    simulator: options.simulator ?? manifest?.device,
+  };
+}
+
+function isBuildPlatform(value: string): value is BuildPlatform {
+  return BUILD_PLATFORMS.includes(value as BuildPlatform);
+}
+
+async function buildCommand(projectPath: string | undefined, cliOptions: CliOptions): Promise<number> {
+  const options = withManifestDefaults(projectPath, cliOptions);
+  if (!projectPath || !options.scheme) {
+    return fail(options, 'build requires a project path and --scheme', EXIT_USAGE);
+  }
+  if (options.platform && !isBuildPlatform(options.platform)) {
+    return fail(options, `--platform must be one of ${BUILD_PLATFORMS.join(', ')}`, EXIT_USAGE);
+  }
+
+  const result = await buildWithOutput(projectPath, options);
+  writeBuildExports(result, options);
+
+  if (options.json) {
+    printJson(toBuildOutput(result));
+  } else if (result.success) {
+    console.log(result.cached ? 'Build restored from the build cache' : 'Build succeeded');
+    for (const product of result.products) {
+      console.log(`  ${product.name}: ${product.path}`);
+    }
+  }
+  return result.success ? EXIT_SUCCESS : EXIT_FAILURE;
+}
+
+async function runCommand(projectPath: string | undefined, cliOptions: CliOptions): Promise<number> {
+  const options = withManifestDefaults(projectPath, cliOptions);
+  if (options.platform && !isBuildPlatform(options.platform)) {
+    return fail(options, `--platform must be one of ${BUILD_PLATFORMS.join(', ')}`, EXIT_USAGE);
+  }
+  if (options.platform && options.platform !== 'ios-simulator') {
+    return runOnMac(projectPath, options.platform, options);
+  }
+  if (!projectPath || !options.scheme || !options.simulator) {
+    return fail(options, 'run requires a project path, --scheme, and --simulator', EXIT_USAGE);
+  }
+
+  const simulator = findSimulator(await listSimulators(), options.simulator);
+  if (!simulator) {
+    return fail(options, new PlasmaError('simulator_not_found', `No simulator matches "${options.simulator}"`));
+  }
+
+  // Boot while the app builds; installing waits on this boot and reports its failure
+  bootSimulator(simulator.udid).catch(() => {});
+  const result = await buildWithOutput(projectPath, options);
+  writeBuildExports(result, options);
+  const output: RunOutput = {
+    ...toBuildOutput(result),
+    simulator: { udid: simulator.udid, name: simulator.name },
+  };
+
+  if (result.success) {
+    if (output.products.length === 0 && result.buildDir) {
+      output.products = await getLaunchableProducts(result.buildDir);
+    }
+
+    if (output.products.length === 0) {
+      output.success = false;
+      output.error = 'No build products found';
+      output.code = 'build_failed';
+    } else {
+      const launched = await installAndLaunch(
+        simulator.udid,
+        output.products[0].path,
+        undefined,
+        getLaunchOptions(projectPath)
+      );
+      output.attempts = launched.attempts;
+      if (!options.json) {
+        console.log(`${launched.message} on ${simulator.name} (${simulator.udid})`);
+      }
+      await runHooks(
+        'postInstall',
+        {
+          projectPath,
+          scheme: options.scheme,
+          configuration: options.configuration,
+          udid: simulator.udid,
+          appPath: output.products[0].path,
+        },
+        (line) => (options.json ? process.stderr : process.stdout).write(`${line}\n`)
+      );
+    }
+  }
+
+  if (options.json) {
+    printJson(output);
+  } else if (output.error && result.success) {
+    console.error(output.error);
+  }
+
+  if (options.watch) {
+    return watchAndRelaunch(projectPath, simulator, options);
+  }
+  return output.success ? EXIT_SUCCESS : EXIT_FAILURE;
+}
+
+/**
+ * Build and launch a macOS or Mac Catalyst app on this Mac
+ */
+async function runOnMac(projectPath: string | undefined, platform: BuildPlatform, options: CliOptions): Promise<number> {
+  if (!projectPath || !options.scheme) {
+    return fail(options, 'run requires a project path and --scheme', EXIT_USAGE);
+  }
+  if (options.watch) {
+    return fail(options, '--watch is only supported on simulators', EXIT_USAGE);
+  }
+
+  const result = await buildWithOutput(projectPath, options);
+  writeBuildExports(result, options);
+  const output: MacRunOutput = { ...toBuildOutput(result), platform, pid: null, logPath: null, screenshot: null };
+
+  if (result.success) {
+    if (output.products.length === 0) {
+      output.success = false;
+      output.error = 'No build products found';
+      output.code = 'build_failed';
+    } else {
+      try {
+        const appPath = output.products[0].path;
+        const launched = await launchMacApp(appPath, getLaunchOptions(projectPath));
+        output.pid = launched.pid;
+        output.logPath = launched.logPath;
+        if (!options.json) {
+          console.log(`${launched.message} (pid ${launched.pid}, output in ${launched.logPath})`);
+        }
+        await runHooks(
+          'postInstall',
+          { projectPath, scheme: options.scheme, configuration: options.configuration, appPath },
+          (line) => (options.json ? process.stderr : process.stdout).write(`${line}\n`)
+        );
+        if (options.screenshot) {
+          fs.writeFileSync(options.screenshot, await captureMacAppWindow(launched.pid));
+          output.screenshot = path.resolve(options.screenshot);
+          if (!options.json) {
+            console.log(`Saved a screenshot of the window to ${output.screenshot}`);
+          }
+        }
+      } catch (err) {
+        const error = toPlasmaError(err);
+        output.success = false;
+        output.error = error.message;
+        output.code = error.code;
+      }
+    }
+  }
+
+  if (options.json) {
+    printJson(output);
+  } else if (output.error && result.success) {
+    console.error(output.error);
+  }
+  return output.success ? EXIT_SUCCESS : EXIT_FAILURE;
+}
+
+/**
+ * Keep rebuilding on change until interrupted. In JSON mode each status is
+ * printed as one JSON object per line.
+ */
+function watchAndRelaunch(projectPath: string, simulator: Simulator, options: CliOptions): Promise<number> {
+  return new Promise((resolve) => {
+    const stop = watchProject(
+      {
+        projectPath,
+        scheme: options.scheme!,
+        configuration: options.configuration,
+        udids: [simulator.udid],
+        derivedDataPath: getSettings().derivedDataPath || undefined,
+      },
+      (status) => {
+        if (options.json) {
+          process.stdout.write(`${JSON.stringify(status)}\n`);
+        } else {
+          const files = status.changedFiles?.length ? ` (${status.changedFiles.join(', ')})` : '';
+          console.log(`[watch] ${status.state}${status.message ? `: ${status.message}` : ''}${files}`);
+        }
+      }
+    );
+
+    process.once('SIGINT', () => {
+      stop();
+      resolve(EXIT_SUCCESS);
+    });
+  });
+}
+
+function testRunOptions(projectPath: string, udid: string, options: CliOptions): TestRunOptions {
+  return {
+    projectPath,
+    scheme: options.scheme!,
+    udid,
+    configuration: options.configuration,
+    coverage: options.coverage,
+    shards: options.shards === undefined ? undefined : Number(options.shards),
+    retry: options['max-attempts'] === undefined ? undefined : { maxAttempts: Number(options['max-attempts']) },
+  };
+}
+
+/**
+ * Print a test run event as a line of progress
+ */
+function printTestEvent(event: TestRunEvent): void {
+  if (event.type === 'shard' && event.status === 'running') {
+    console.log(`Shard ${event.index + 1}: ${event.tests} tests on ${event.udid}`);
+  } else if (event.type === 'retry') {
+    console.log(`Retrying ${event.tests.length} failed tests (attempt ${event.attempt})`);
+  } else if (event.type === 'test') {
+    const mark = event.test.status === 'passed' ? '✓' : event.test.status === 'failed' ? '✗' : '-';
+    const flaky = event.test.flaky ? ' [flaky]' : '';
+    console.log(`${mark} ${event.test.identifier} (${(event.test.durationMs / 1000).toFixed(3)}s)${flaky}`);
+    for (const failure of event.test.failures) {
+      console.log(`    ${failure.file ? `${failure.file}:${failure.line}: ` : ''}${failure.message}`);
+    }
+  } else if (event.type === 'completed') {
+    const { run } = event;
+    if (run.error) {
+      console.error(run.error);
+    }
+    console.log(`${run.passed} passed (${run.flaky} flaky), ${run.failed} failed, ${run.skipped} skipped`);
+    if (run.lineCoverage !== null) {
+      console.log(`Line coverage: ${(run.lineCoverage * 100).toFixed(1)}% (test run ${run.id})`);
+    }
+  }
+}
+
+/**
+ * Run a scheme's tests. In JSON mode each event is printed as one JSON object per line.
+ */
+async function testCommand(projectPath: string | undefined, cliOptions: CliOptions): Promise<number> {
+  const options = withManifestDefaults(projectPath, cliOptions);
+  if (!projectPath || !options.scheme || !options.simulator) {
+    return fail(options, 'test requires a project path, --scheme, and --simulator', EXIT_USAGE);
+  }
+
+  const simulator = findSimulator(await listSimulators(), options.simulator);
+  if (!simulator) {
+    return fail(options, new PlasmaError('simulator_not_found', `No simulator matches "${options.simulator}"`));
+  }
+
+  const report = (event: TestRunEvent) => {
+    if (options.json) {
+      process.stdout.write(`${JSON.stringify(event)}\n`);
+    } else {
+      printTestEvent(event);
+    }
+  };
+
+  const run = await runTests(testRunOptions(projectPath, simulator.udid, options), report);
+  const root = process.env.GITHUB_WORKSPACE ?? process.cwd();
+  writeExports(options, (format) => exportTestRun(run, format, { root }));
+  return run.status === 'passed' ? EXIT_SUCCESS : EXIT_FAILURE;
+}
+
+/**
+ * Build and test for CI. Progress goes to stdout while xcodebuild output
+ * goes to log files, and everything a pipeline needs (logs, JUnit reports,
+ * summary.json) is written to the output directory. Annotations are
+ * printed automatically on GitHub Actions.
+ */
+async function ciCommand(projectPath: string | undefined, cliOptions: CliOptions): Promise<number> {
+  const options = withManifestDefaults(projectPath, {
+    ...cliOptions,
+    simulator: cliOptions.destination ?? cliOptions.simulator,
+  });
+  if (!projectPath || !options.scheme || !options.simulator) {
+    return fail(options, 'ci requires a project path, --scheme, and --destination', EXIT_USAGE);
+  }
+
+  const simulator = findSimulator(await listSimulators(), options.simulator);
+  if (!simulator) {
+    return fail(
+      options,
+      new PlasmaError('simulator_not_found', `No simulator matches "${options.simulator}"`),
+      EXIT_TESTS_NOT_RUN
+    );
+  }
+
+  const startedAt = new Date();
+  const outputDir = path.resolve(options.output ?? DEFAULT_CI_OUTPUT);
+  fs.mkdirSync(outputDir, { recursive: true });
+  const artifacts: Record<string, string> = {};
+  const artifact = (name: string, file: string): string => (artifacts[name] = path.join(outputDir, file));
+  const root = process.env.GITHUB_WORKSPACE ?? process.cwd();
+  const annotate = options['github-annotations'] || process.env.GITHUB_ACTIONS === 'true';
+  // Keep stdout to progress lines (and the summary in JSON mode)
+  const progress = options.json ? console.error : console.log;
+
+  progress(`==> Building ${options.scheme} (${options.configuration ?? 'Debug'})`);
+  const buildLog = fs.createWriteStream(artifact('buildLog', 'build.log'));
+  const build = await buildScheme(
+    projectPath,
+    options.scheme,
+    options.configuration,
+    getSettings().derivedDataPath || undefined,
+    (event) => {
+      if (event.type === 'packages' && event.phase === 'resolving' && event.package) {
+        progress(`    Resolving ${event.package}`);
+      }
+      if (event.type === 'output' && event.line) {
+        buildLog.write(`${event.line}\n`);
+      }
+    },
+    undefined,
+    options['package-resolution'] as PackageResolution | undefined
+  );
+  buildLog.end();
+
+  const buildSummary = build.buildId ? getBuildById(build.buildId) : null;
+  if (buildSummary) {
+    fs.writeFileSync(artifact('buildJUnit', 'build.junit.xml'), exportBuild(buildSummary, 'junit').body);
+    if (annotate) {
+      process.stderr.write(exportBuild(buildSummary, 'github', { root }).body);
+    }
+  }
+  progress(
+    build.success
+      ? `Build succeeded${buildSummary ? ` with ${buildSummary.warnings} warnings` : ''}`
+      : `Build failed: ${build.error ?? `${buildSummary?.errors ?? 0} errors`} (see ${artifacts.buildLog})`
+  );
+
+  let run: TestRun | null = null;
+  if (build.success) {
+    progress(`==> Testing ${options.scheme} on ${simulator.name} (${simulator.udid})`);
+    const testLog = fs.createWriteStream(artifact('testLog', 'test.log'));
+    try {
+      run = await runTests(testRunOptions(projectPath, simulator.udid, options), (event) => {
+        if (event.type === 'output') {
+          testLog.write(`${event.line}\n`);
+        } else if (options.json) {
+          // Progress stays off stdout in JSON mode
+          if (event.type === 'completed') {
+            console.error(`${event.run.passed} passed, ${event.run.failed} failed, ${event.run.skipped} skipped`);
+          }
+        } else {
+          printTestEvent(event);
+        }
+      });
+    } catch (err) {
+      progress(`Tests could not run: ${toPlasmaError(err).message}`);
+    }
+    testLog.end();
+
+    if (run) {
+      fs.writeFileSync(artifact('testJUnit', 'tests.junit.xml'), exportTestRun(run, 'junit').body);
+      if (annotate) {
+        process.stderr.write(exportTestRun(run, 'github', { root }).body);
+      }
+    }
+  }
+
+  const exitCode = !build.success
+    ? EXIT_BUILD_FAILED
+    : !run || run.status === 'error' || run.status === 'cancelled'
+      ? EXIT_TESTS_NOT_RUN
+      : run.status === 'passed'
+        ? EXIT_SUCCESS
+        : EXIT_FAILURE;
+
+  artifact('summary', 'summary.json');
+  const summary: CiSummary = {
+    success: exitCode === EXIT_SUCCESS,
+    exitCode,
+    projectPath,
+    scheme: options.scheme,
+    configuration: options.configuration ?? 'Debug',
+    simulator: { udid: simulator.udid, name: simulator.name },
+    startedAt: startedAt.toISOString(),
+    durationMs: Date.now() - startedAt.getTime(),
+    build: {
+      success: build.success,
+      buildId: build.buildId ?? null,
+      errors: buildSummary?.errors ?? 0,
+      warnings: buildSummary?.warnings ?? 0,
+      error: build.success ? null : build.error ?? null,
+      code: build.success ? null : build.code ?? 'build_failed',
+      git: buildSummary?.git ?? null,
+    },
+    tests: run && {
+      testRunId: run.id,
+      status: run.status,
+      passed: run.passed,
+      failed: run.failed,
+      skipped: run.skipped,
+      flaky: run.flaky,
+      lineCoverage: run.lineCoverage,
+      resultBundlePath: run.resultBundlePath,
+      error: run.error,
+    },
+    artifacts,
+  };
+  fs.writeFileSync(artifacts.summary, `${JSON.stringify(summary, null, 2)}\n`);
+
+  if (options.json) {
+    printJson(summary);
+  } else {
+    console.log(`==> ${summary.success ? 'Passed' : 'Failed'} in ${(summary.durationMs / 1000).toFixed(1)}s, summary at ${artifacts.summary}`);
+  }
+  return exitCode;
+}
+
+/**
+ * Run an automation script. In JSON mode each event is printed as one JSON object per line.
+ */
+async function automateCommand(scriptPath: string | undefined, options: CliOptions): Promise<number> {
+  if (!scriptPath || !options.simulator) {
+    return fail(options, 'automate requires a script path and --simulator', EXIT_USAGE);
+  }
+  if (!fs.existsSync(scriptPath)) {
+    return fail(options, new PlasmaError('not_found', `Script not found: ${scriptPath}`));
+  }
+
+  const script = parseAutomationScript(fs.readFileSync(scriptPath, 'utf-8'));
+  const simulator = findSimulator(await listSimulators(), options.simulator);
+  if (!simulator) {
+    return fail(options, new PlasmaError('simulator_not_found', `No simulator matches "${options.simulator}"`));
+  }
+
+  const report = (event: AutomationEvent) => {
+    if (options.json) {
+      process.stdout.write(`${JSON.stringify(event)}\n`);
+    } else if (event.type === 'step' && event.status !== 'running') {
+      const mark = event.status === 'passed' ? '✓' : '✗';
+      const detail = event.message ?? event.screenshotPath ?? '';
+      console.log(`${mark} ${event.index + 1}. ${JSON.stringify(event.step)}${detail ? ` — ${detail}` : ''}`);
+    } else if (event.type === 'completed') {
+      console.log(event.success ? `Passed in ${(event.durationMs / 1000).toFixed(1)}s` : `Failed: ${event.error}`);
+    }
+  };
+
+  const success = await runAutomation(simulator.udid, script, report);
+  return success ? EXIT_SUCCESS : EXIT_FAILURE;
+}
+
+async function screenshotsCommand(configPath: string | undefined, options: CliOptions): Promise<number> {
+  if (!configPath) {
+    return fail(options, 'screenshots requires a config path', EXIT_USAGE);
+  }
+  if (!fs.existsSync(configPath)) {
+    return fail(options, new PlasmaError('not_found', `Config not found: ${configPath}`));
+  }
+
+  const matrix = parseScreenshotMatrix(fs.readFileSync(configPath, 'utf-8'));
+  const devices = await resolveMatrixDevices(matrix);
+
+  const report = (event: ScreenshotMatrixEvent) => {
+    if (options.json) {
+      process.stdout.write(`${JSON.stringify(event)}\n`);
+    } else if (event.type === 'started') {
+      console.log(`Capturing ${event.runs} device/locale combinations into ${event.outputDir}`);
+    } else if (event.type === 'run' && event.status !== 'running') {
+      const mark = event.status === 'passed' ? '✓' : '✗';
+      console.log(`${mark} ${event.device} (${event.locale})${event.error ? ` — ${event.error}` : ''}`);
+    } else if (event.type === 'completed') {
+      console.log(`${event.passed} passed, ${event.failed} failed in ${(event.durationMs / 1000).toFixed(1)}s`);
+    }
+  };
+
+  const success = await runScreenshotMatrix(matrix, devices, report);
+  return success ? EXIT_SUCCESS : EXIT_FAILURE;
+}
+
+async function testflightCommand(
+  ipaPath: string | undefined,
+  projectPath: string | undefined,
+  options: CliOptions
+): Promise<number> {
+  if (!ipaPath) {
+    return fail(options, 'Usage: plasma testflight <ipa> [<project path>] [--no-wait]', EXIT_USAGE);
+  }
+  const log = options.json ? console.error : console.log;
+  let result: Extract<TestFlightEvent, { type: 'completed' | 'error' }> | null = null;
+
+  await uploadToTestFlight(
+    path.resolve(ipaPath),
+    { projectPath: path.resolve(projectPath ?? '.'), waitForProcessing: !options['no-wait'] },
+    (event) => {
+      switch (event.type) {
+        case 'uploading':
+          log(`Uploading ${event.bundleId} ${event.version} (${event.buildNumber})`);
+          break;
+        case 'output':
+          log(event.line);
+          break;
+        case 'processing':
+          log(event.state === 'WAITING' ? 'Waiting for App Store Connect to list the build' : `Processing: ${event.state}`);
+          break;
+        case 'completed':
+        case 'error':
+          result = event;
+          break;
+      }
+    }
+  );
+
+  const outcome = result as Extract<TestFlightEvent, { type: 'completed' | 'error' }> | null;
+  if (!outcome || outcome.type === 'error') {
+    return fail(options, new PlasmaError(outcome?.code ?? 'internal', outcome?.message ?? 'Upload failed'));
+  }
+  // Processing can end with the build rejected
+  const success = outcome.state === null || outcome.state === 'VALID';
+  if (options.json) {
+    printJson({ success, ...outcome });
+  } else {
+    console.log(success ? `Uploaded ${outcome.version} (${outcome.buildNumber})` : `Processing ended as ${outcome.state}`);
+  }
+  return success ? EXIT_SUCCESS : EXIT_FAILURE;
+}
+
+async function fixturesCommand(subcommand: string | undefined, args: string[], options: CliOptions): Promise<number> {
+  const [projectPath, name] = args;
+  if (subcommand === 'list' && projectPath) {
+    const fixtures = listFixtureSets(projectPath);
+    if (options.json) {
+      printJson(fixtures);
+      return EXIT_SUCCESS;
+    }
+    for (const set of fixtures) {
+      console.log(
+        `${set.name.padEnd(20)} ${set.photos} photos, ${set.contacts + set.vcards} contacts, ${set.events} events${
+          set.erase ? ' (erases the simulator)' : ''
+        }`
+      );
+    }
+    return EXIT_SUCCESS;
+  }
+
+  if (subcommand !== 'seed' || !projectPath || !name) {
+    return fail(options, 'Usage: plasma fixtures list <path> | seed <path> <name> --simulator <name|udid>', EXIT_USAGE);
+  }
+  const query = options.simulator ?? readManifest(projectPath)?.device;
+  if (!query) {
+    return fail(options, 'fixtures seed requires --simulator', EXIT_USAGE);
+  }
+  const simulator = findSimulator(await listSimulators(), query);
+  if (!simulator) {
+    return fail(options, new PlasmaError('simulator_not_found', `No simulator matches "${query}"`));
+  }
+
+  const result = await seedFixtures(simulator.udid, projectPath, name, (message) =>
+    (options.json ? console.error : console.log)(message)
+  );
+  if (options.json) {
+    printJson(result);
+  } else {
+    console.log(`Seeded ${simulator.name} with ${name}`);
+  }
+  return EXIT_SUCCESS;
+}
+
+async function simulatorsCommand(
+  subcommand: string | undefined,
+  args: string[],
+  options: CliOptions
+): Promise<number> {
+  if (subcommand === 'pairs') {
+    const pairs = await listSimulatorPairs();
+    if (options.json) {
+      printJson(pairs);
+      return EXIT_SUCCESS;
+    }
+    for (const pair of pairs) {
+      const state = [pair.active && 'active', pair.connected && 'connected'].filter(Boolean).join(', ');
+      console.log(`${pair.id}  ${pair.watch.name} ⇄ ${pair.phone.name}${state ? ` (${state})` : ''}`);
+    }
+    return EXIT_SUCCESS;
+  }
+
+  if (subcommand === 'pair') {
+    const [watchUdid, phoneUdid] = args;
+    if (!watchUdid || !phoneUdid) {
+      return fail(options, 'Usage: plasma simulators pair <watch udid> <phone udid>', EXIT_USAGE);
+    }
+    const pair = await createSimulatorPair(watchUdid, phoneUdid);
+    if (options.json) {
+      printJson(pair);
+    } else {
+      console.log(`Paired ${pair.watch.name} with ${pair.phone.name} (${pair.id})`);
+    }
+    return EXIT_SUCCESS;
+  }
+
+  if (subcommand !== 'list') {
+    return fail(options, 'Usage: plasma simulators list|pairs|pair', EXIT_USAGE);
+  }
+
+  const simulators = await listSimulators();
+  if (options.json) {
+    printJson(simulators);
+    return EXIT_SUCCESS;
+  }
+
+  for (const simulator of simulators) {
+    console.log(`${simulator.name.padEnd(32)} ${simulator.state.padEnd(10)} ${simulator.udid}`);
+  }
+  return EXIT_SUCCESS;
+}
+
+/**
+ * Print a followed build's event the way build prints its own
+ */
+function printBuildEvent(event: BuildEvent, options: CliOptions, color: boolean): void {
+  switch (event.type) {
+    case 'started':
+      console.log(`==> Building ${event.scheme} (${event.configuration}) in ${event.projectPath} [${event.buildId}]`);
+      break;
+    case 'packages':
+      if (event.message) console.log(event.message);
+      break;
+    case 'output':
+      if (!event.line) break;
+      if (options.raw || event.formatted === undefined) {
+        console.log(event.line);
+      } else if (event.formatted) {
+        console.log(renderBuildLine(event.formatted, color));
+      }
+      break;
+    case 'completed':
+      if (event.success) {
+        console.log(event.cached ? 'Build restored from the build cache' : 'Build succeeded');
+        for (const product of event.products ?? []) {
+          console.log(`  ${product.name}: ${product.path}`);
+        }
+      } else {
+        console.error('Build failed');
+        if (event.suggestedArch) {
+          console.error(`Try again with the ${event.suggestedArch} architecture`);
+        }
+      }
+      break;
+    case 'error':
+      console.error(`Build failed${event.message ? `: ${event.message}` : ''}`);
+      break;
+  }
+}
+
+/**
+ * Follow a build running in a Plasma server until it finishes. In JSON mode
+ * each event is printed as one JSON object per line.
+ */
+async function buildsCommand(subcommand: string | undefined, options: CliOptions): Promise<number> {
+  if (subcommand !== 'tail') {
+    return fail(options, 'Usage: plasma builds tail [--id <id>] [--server <url>]', EXIT_USAGE);
+  }
+
+  const server = (options.server ?? DEFAULT_SERVER_URL).replace(/\/+$/, '');
+  const query = options.id ? `?id=${encodeURIComponent(options.id)}` : '';
+  let response;
+  try {
+    response = await fetch(`${server}/api/builds/active/events${query}`);
+  } catch (err) {
+    return fail(options, `Couldn't reach Plasma at ${server}: ${err instanceof Error ? err.message : err}`);
+  }
+  if (!response.ok || !response.body) {
+    const body = (await response.json().catch(() => null)) as { error?: string; code?: ErrorCode } | null;
+    return fail(options, new PlasmaError(body?.code ?? 'internal', body?.error ?? `${server} responded ${response.status}`));
+  }
+
+  const color = Boolean(process.stdout.isTTY) && !process.env.NO_COLOR;
+  let success: boolean | null = null;
+  let buffered = '';
+  for await (const chunk of response.body as unknown as AsyncIterable<Buffer>) {
+    const lines = (buffered + chunk.toString()).split('\n');
+    buffered = lines.pop() ?? '';
+    for (const line of lines.filter((candidate) => candidate.trim())) {
+      const event = JSON.parse(line) as BuildEvent;
+      if (options.json) {
+        process.stdout.write(`${line}\n`);
+      } else {
+        printBuildEvent(event, options, color);
+      }
+      if (event.type === 'completed' || event.type === 'error') {
+        success = event.type === 'completed' && event.success === true;
+      }
+    }
+  }
+
+  if (success === null) {
+    return fail(options, 'The server stopped sending events before the build finished');
+  }
+  return success ? EXIT_SUCCESS : EXIT_FAILURE;
+}
+
+async function projectsCommand(subcommand: string | undefined, options: CliOptions): Promise<number> {
+  if (subcommand !== 'list') {
+    return fail(options, 'Usage: plasma projects list', EXIT_USAGE);
+  }
+
+  const projects = getRecentUnifiedProjects(50);
+  if (options.json) {
+    printJson(
+      projects.map((project) => ({
+        id: project.id,
+        name: project.name,
+        xcodePath: project.xcode_path,
+        androidPath: project.android_path,
+        lastOpenedAt: project.last_opened_at,
+      }))
+    );
+    return EXIT_SUCCESS;
+  }
+
+  for (const project of projects) {
+    const paths = [project.xcode_path, project.android_path].filter(Boolean).join(', ');
+    console.log(`${String(project.id).padStart(4)}  ${project.name.padEnd(24)} ${paths}`);
+  }
+  return EXIT_SUCCESS;
+}
+
+/**
+ * Create a SwiftUI app from a template and register it, then build and
+ * launch it when a simulator is given
+ */
+async function newCommand(directory: string | undefined, options: CliOptions): Promise<number> {
+  if (!directory || !options.name) {
+    return fail(options, 'new requires a directory and --name', EXIT_USAGE);
+  }
+
+  let created: NewAppResult;
+  try {
+    created = await scaffoldSwiftUIApp(path.resolve(directory), options.name, options.template as AppTemplate | undefined);
+  } catch (err) {
+    return fail(options, err);
+  }
+  saveUnifiedProject(options.name, created.xcodePath, null);
+  saveProject(created.xcodePath, options.name, ['ios']);
+
+  if (options.simulator) {
+    (options.json ? console.error : console.log)(`Created ${created.xcodePath} from the ${created.template} template`);
+    return runCommand(created.xcodePath, { ...options, scheme: created.scheme });
+  }
+  if (options.json) {
+    printJson({ success: true, ...created });
+  } else {
+    console.log(`Created ${created.xcodePath} from the ${created.template} template`);
+    console.log(`Run it with: plasma run ${created.xcodePath} --scheme ${created.scheme} --simulator <name|udid>`);
+  }
+  return EXIT_SUCCESS;
+}
+
+async function doctorCommand(options: CliOptions): Promise<number> {
+  const checks = await checkEnvironment();
+  const success = checks.every((check) => check.ok || !check.required);
+
+  if (options.json) {
+    printJson({ success, checks });
+  } else {
+    for (const check of checks) {
+      const mark = check.ok ? '✓' : check.required ? '✗' : '!';
+      console.log(`${mark} ${check.title}${check.detail ? ` — ${check.detail}` : ''}`);
+      if (check.version) {
+        console.log(`    ${check.version}`);
+      }
+      if (!check.ok && check.installCommand) {
+        console.log(`    Install with: ${check.installCommand}`);
+      }
+    }
+  }
+  return success ? EXIT_SUCCESS : EXIT_FAILURE;
+}
+
+/**
+ * Parse arguments and run the matching command, returning the exit code
+ */
+export async function runCli(argv: string[]): Promise<number> {
+  let parsed;
+  try {
+    parsed = parseArgs({
+      args: argv,
+      allowPositionals: true,
+      options: {
+        scheme: { type: 'string', short: 's' },
+        configuration: { type: 'string', short: 'c' },
+        simulator: { type: 'string' },
+        watch: { type: 'boolean', short: 'w' },
+        'no-wait': { type: 'boolean' },
+        coverage: { type: 'boolean' },
+        shards: { type: 'string' },
+        'max-attempts': { type: 'string' },
+        json: { type: 'boolean' },
+        destination: { type: 'string' },
+        output: { type: 'string', short: 'o' },
+        platform: { type: 'string' },
+        'package-resolution': { type: 'string' },
+        arch: { type: 'string' },
+        id: { type: 'string' },
+        server: { type: 'string' },
+        screenshot: { type: 'string' },
+        name: { type: 'string' },
+        template: { type: 'string' },
+        junit: { type: 'string' },
+        'github-annotations': { type: 'boolean' },
+        raw: { type: 'boolean' },
+        help: { type: 'boolean', short: 'h' },
+      },
+    });
+  } catch (err) {
+    return fail({ json: argv.includes('--json') }, err, EXIT_USAGE);
+  }
+
+  const options = parsed.values as CliOptions;
+  const [command, ...args] = parsed.positionals;
+
+  if (options.help || !command) {
+    console.log(USAGE);
+    return options.help ? EXIT_SUCCESS : EXIT_USAGE;
+  }
+  const packageResolution = options['package-resolution'];
+  if (packageResolution && !PACKAGE_RESOLUTIONS.includes(packageResolution as PackageResolution)) {
+    return fail(options, `--package-resolution must be one of ${PACKAGE_RESOLUTIONS.join(', ')}`, EXIT_USAGE);
+  }
+  if (options.arch && !BUILD_ARCHS.includes(options.arch as BuildArch)) {
+    return fail(options, `--arch must be one of ${BUILD_ARCHS.join(', ')}`, EXIT_USAGE);
+  }
+
+  switch (command) {
+    case 'build':
+      return buildCommand(args[0], options);
+    case 'run':
+      return runCommand(args[0], options);
+    case 'test':
+      return testCommand(args[0], options);
+    case 'ci':
+      return ciCommand(args[0], options);
+    case 'automate':
+      return automateCommand(args[0], options);
+    case 'screenshots':
+      return screenshotsCommand(args[0], options);
+    case 'testflight':
+      return testflightCommand(args[0], args[1], options);
+    case 'fixtures':
+      return fixturesCommand(args[0], args.slice(1), options);
+    case 'simulators':
+      return simulatorsCommand(args[0], args.slice(1), options);
+    case 'projects':
+      return projectsCommand(args[0], options);
+    case 'builds':
+      return buildsCommand(args[0], options);
+    case 'new':
+      return newCommand(args[0], options);
+    case 'doctor':
+      return doctorCommand(options);
+    default:
+      return fail(options, `Unknown command: ${command}\n\n${USAGE}`, EXIT_USAGE);
+  }
+}
+
+if (require.main === module) {
+  reapOrphanedProcesses();
+
+  process.on('SIGTERM', () => {
+    killAllProcesses().finally(() => process.exit(143));
+  });
+
+  // Set on SIGINT, so watch mode only exits once its children have
+  let killing: Promise<void> = Promise.resolve();
+  process.on('SIGINT', () => {
+    killing = killAllProcesses();
+    // Watch mode resolves on SIGINT itself and exits cleanly
+    if (!process.argv.includes('--watch') && !process.argv.includes('-w')) {
+      killing.finally(() => process.exit(130));
+    }
+  });
+
+  const json = process.argv.includes('--json');
+  runCli(process.argv.slice(2))
+    .then((code) => killing.then(() => process.exit(code)))
+    .catch((err) => {
+      process.exit(fail({ json }, err));
+    });
+}
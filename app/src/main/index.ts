@@ -1,12 +1,36 @@
-import { app, BrowserWindow, ipcMain, dialog, nativeImage } from 'electron';
+import { app, BrowserWindow, ipcMain, dialog, nativeImage, nativeTheme, Menu, shell, Tray } from 'electron';
 import * as path from 'path';
 import { startServer } from './server';
-import { killAllProcesses } from './services/process-manager';
+import { reapOrphanedProcesses } from './services/process-manager';
+import { openInEditor, parseEditorUrl } from './services/editor';
+import { getLogsDir, initLogging } from './services/logger';
+import { shutdown } from './shutdown';
+
+initLogging();
 
 let mainWindow: BrowserWindow | null = null;
+let tray: Tray | null = null;
+// Set once shutdown has finished and the app can really quit
+let readyToQuit = false;
 
 const isDev = process.env.NODE_ENV === 'development' || !app.isPackaged;
 
+function createTray() {
+  const icon = nativeImage.createFromPath(path.join(__dirname, '../../icons/32x32.png'));
+  tray = new Tray(icon.isEmpty() ? icon : icon.resize({ width: 16, height: 16 }));
+  if (icon.isEmpty()) {
+    tray.setTitle('Plasma');
+  }
+  tray.setToolTip('Plasma');
+  tray.setContextMenu(
+    Menu.buildFromTemplate([
+      { label: 'Reveal Logs', click: () => shell.openPath(getLogsDir()) },
+      { type: 'separator' },
+      { label: 'Quit Plasma', click: () => app.quit() },
+    ])
+  );
+}
+
 async function createWindow() {
   // Set dock icon in development mode on macOS
   if (isDev && process.platform === 'darwin') {
@@ -21,6 +45,8 @@ async function createWindow() {
     }
   }
 
+  reapOrphanedProcesses();
+
   // Start HTTP server (used by both Electron and browser modes)
   await startServer();
   console.log('HTTP server started');
@@ -55,7 +81,20 @@ async function createWindow() {
   });
 }
 
-app.whenReady().then(createWindow);
+// plasma://open?file=...&line=... links jump to source in the user's editor
+app.setAsDefaultProtocolClient('plasma');
+
+app.on('open-url', (event, url) => {
+  event.preventDefault();
+  Promise.resolve()
+    .then(() => openInEditor(parseEditorUrl(url)))
+    .catch((err) => console.error(`Failed to open ${url}:`, err));
+});
+
+app.whenReady().then(() => {
+  createTray();
+  return createWindow();
+});
 
 app.on('window-all-closed', () => {
   // On macOS, apps typically stay open until explicitly quit
@@ -71,9 +110,25 @@ app.on('activate', () => {
   }
 });
 
-app.on('before-quit', () => {
+app.on('before-quit', (event) => {
+  if (readyToQuit) {
+    return;
+  }
+  // Hold the quit until streams are closed and children are gone
+  event.preventDefault();
   console.log('Application quitting...');
-  killAllProcesses();
+  shutdown().finally(() => {
+    readyToQuit = true;
+    app.quit();
+  });
+});
+
+process.on('SIGTERM', () => {
+  app.quit();
+});
+
+process.on('SIGINT', () => {
+  app.quit();
 });
 
 // Core IPC handlers (app control)
@@ -103,3 +158,9 @@ ipcMain.on('app-maximize', () => {
     mainWindow?.maximize();
   }
 });
+
+ipcMain.on('set-theme-source', (_event, source: 'system' | 'light' | 'dark') => {
+  if (source === 'system' || source === 'light' || source === 'dark') {
+    nativeTheme.themeSource = source;
+  }
+});
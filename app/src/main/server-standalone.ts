@@ -1,29 +1,62 @@
 /**
  * Standalone server entry point for browser mode
  * Run with: pnpm dev:browser
+ *
+ * Pass --frontend-dev-url <url> (or set PLASMA_FRONTEND_DEV_URL) to serve
+ * the frontend from a Vite dev server on the server's own port.
  */
 
 import { startServer } from './server';
+import { parseFrontendDevUrl } from './services/frontend';
+import { reapOrphanedProcesses } from './services/process-manager';
+import { initLogging } from './services/logger';
+import { shutdown } from './shutdown';
+
+initLogging();
 
 console.log('[standalone] Starting Plasma server for browser mode...');
 
-startServer()
+reapOrphanedProcesses();
+
+function frontendDevUrlArg(argv: string[]): string | undefined {
+  const index = argv.findIndex((arg) => arg === '--frontend-dev-url' || arg.startsWith('--frontend-dev-url='));
+  if (index === -1) {
+    return process.env.PLASMA_FRONTEND_DEV_URL || undefined;
+  }
+  const arg = argv[index];
+  return arg.includes('=') ? arg.slice(arg.indexOf('=') + 1) : argv[index + 1] ?? '';
+}
+
+let frontendDevUrl: string | undefined;
+try {
+  const arg = frontendDevUrlArg(process.argv.slice(2));
+  frontendDevUrl = arg !== undefined ? parseFrontendDevUrl(arg) : undefined;
+} catch (err) {
+  console.error(`[standalone] ${err instanceof Error ? err.message : err}`);
+  process.exit(2);
+}
+
+startServer({ devUrl: frontendDevUrl })
   .then(() => {
     console.log('[standalone] Server started successfully');
-    console.log('[standalone] Open http://localhost:5173 in your browser');
+    console.log(`[standalone] Open ${frontendDevUrl ? 'http://localhost:3001' : 'http://localhost:5173'} in your browser`);
   })
   .catch((err) => {
     console.error('[standalone] Failed to start server:', err);
     process.exit(1);
   });
 
-// Handle shutdown gracefully
-process.on('SIGINT', () => {
-  console.log('\n[standalone] Shutting down...');
-  process.exit(0);
-});
+// Handle shutdown gracefully; a second Ctrl+C exits right away
+let shuttingDown = false;
 
-process.on('SIGTERM', () => {
+function handleSignal(): void {
+  if (shuttingDown) {
+    process.exit(1);
+  }
+  shuttingDown = true;
   console.log('\n[standalone] Shutting down...');
-  process.exit(0);
-});
+  shutdown().finally(() => process.exit(0));
+}
+
+process.on('SIGINT', handleSignal);
+process.on('SIGTERM', handleSignal);
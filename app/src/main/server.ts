@@ -9,19 +9,181 @@ import {
   getRecentUnifiedProjects,
   getUnifiedProjectById,
   updateProjectLastOpened,
+  getProjectSettings,
+  updateProjectSettings,
+  getSettings,
+  updateSettings,
 } from './services/database';
 import { validateXcodePath, validateAndroidPath } from './services/project-validation';
-import { scaffoldNewProject } from './services/project-scaffold';
-import { discoverProject, buildSchemeStream, getLaunchableProducts } from './services/xcode';
+import { listAppTemplates, scaffoldNewProject, scaffoldSwiftUIApp } from './services/project-scaffold';
+import {
+  discoverProject,
+  buildSchemeStream,
+  buildProjects,
+  getLaunchableProducts,
+  parseBatchBuilds,
+  purgePackageCache,
+  BUILD_PLATFORMS,
+  PACKAGE_RESOLUTIONS,
+} from './services/xcode';
+import { captureMacAppWindow, launchMacApp, terminateMacApp } from './services/mac-app';
+import { runOnDevices } from './services/multi-device';
+import { listFixtureSets, seedFixtures } from './services/fixtures';
+import { readUserDefaults, writeUserDefaults } from './services/user-defaults';
+import { getProductDetails } from './services/product-details';
+import { analyzeBundleSize } from './services/bundle-size';
+import { listProvisioningProfiles, listSigningIdentities } from './services/signing';
+import { uploadToTestFlight } from './services/asc';
+import {
+  bootSimulatorPair,
+  createSimulatorPair,
+  deleteSimulatorPair,
+  installOnPair,
+  listSimulatorPairs,
+} from './services/watch-pairs';
 import {
   listSimulators,
   installAndLaunch,
   sendSessionCommand,
   sendTap,
   sendSwipe,
+  bootSimulator,
   getOrCreateSession,
-  logEmitter,
+  subscribeLogs,
+  retainSession,
+  releaseSession,
+  pauseSession,
+  resumeSession,
+  hasSession,
+  stopSession,
+  stopAllSessions,
+  getRecentLogs,
+  pressButton,
+  rotate,
+  shake,
+  performDeviceAction,
+  resetAppData,
+  resetUserDefaults,
+  resetKeychain,
+  takeScreenshot,
+  startRecording,
+  stopRecording,
+  getFpsReport,
+  listInstalledApps,
+  getAppInfo,
+  launchInstalledApp,
+  addMedia,
+  addMediaUpload,
+  proxyStream,
 } from './services/simulator';
+import { issueStreamToken, requestStreamToken, verifyStreamToken } from './services/stream-auth';
+import { checkEnvironment, fixEnvironmentCheck } from './services/environment';
+import { detectHelperTools } from './services/tooling';
+import { listAndroidDevices, installAndLaunchApk, terminateApp } from './services/android';
+import { getOrCreateAndroidSession, stopAllAndroidSessions, stopAndroidSession } from './services/android-stream';
+import { watchProject } from './services/watcher';
+import {
+  createBuildTrigger,
+  handleGitPush,
+  listBuildTriggers,
+  parseGitPush,
+  removeBuildTrigger,
+  runBuildTrigger,
+  setTriggerSecret,
+  startBuildScheduler,
+  stopBuildScheduler,
+  TRIGGER_SECRET_SETTING,
+  TriggerBuildEvent,
+  verifyWebhook,
+} from './services/build-triggers';
+import { PlasmaError, toErrorBody, toPlasmaError } from './services/errors';
+import { readLogs } from './services/logger';
+import { cleanUp, getDiskUsage } from './services/disk-usage';
+import { openInEditor, validateEditorCommand, validateSourceLocation } from './services/editor';
+import { isAppRequest, requireAppRequest } from './services/app-requests';
+import { getBuildQueueStatus } from './services/build-queue';
+import { getPrewarmStatus, prewarmForProject, stopPrewarm } from './services/prewarm';
+import {
+  closeWorkspaceProject,
+  createWorkspace,
+  getWorkspaceById,
+  listWorkspaces,
+  openWorkspaceProject,
+  removeWorkspace,
+  updateWorkspace,
+  updateWorkspaceProject,
+} from './services/workspaces';
+import { getBuildLogSize, parseByteRange, readBuildLogRange, tailBuildLog } from './services/build-logs';
+import { followBuild, listActiveBuilds } from './services/active-builds';
+import {
+  FrontendOptions,
+  isHmrUpgrade,
+  proxyToDevServer,
+  proxyUpgradeToDevServer,
+  serveFrontendFile,
+} from './services/frontend';
+import { encodeMjpegPart, MJPEG_BOUNDARY, MjpegParser } from './services/mjpeg';
+import { parseReencodeOptions, reencodeMjpeg } from './services/mjpeg-reencode';
+import { describePoint, describeScreen, tapElement } from './services/accessibility';
+import {
+  isRecordingInteractions,
+  recordButton,
+  recordSwipe,
+  recordTap,
+  recordTouch,
+  startInteractionRecording,
+  stopInteractionRecording,
+  toAutomationScript,
+  toXCUITest,
+} from './services/interaction-recorder';
+import {
+  addShareViewer,
+  createStreamShare,
+  getStreamShare,
+  lanAddress,
+  listStreamShares,
+  renderSharePage,
+  revokeAllStreamShares,
+  revokeStreamShare,
+  StreamShare,
+} from './services/stream-shares';
+import { parseAgentActions, parseScreenshotMode, runAgentActions } from './services/agent';
+import { parseAutomationScript, runAutomation } from './services/automation';
+import { checkScreenshot, parseMasks } from './services/visual-diff';
+import { parseScreenshotMatrix, resolveMatrixDevices, runScreenshotMatrix } from './services/screenshot-matrix';
+import { HOOK_SETTING_KEYS, runHooks } from './services/hooks';
+import { TIMEOUT_SETTING_KEYS } from './services/timeouts';
+import {
+  getActiveAppMetrics,
+  startAppMetrics,
+  stopAppMetrics,
+  stopAllAppMetrics,
+  subscribeAppMetrics,
+  toAppMetricsSummary,
+} from './services/app-metrics';
+import { getAppMetricsSessions } from './services/database';
+import { getCrashReportById, listCrashReports } from './services/crash-reports';
+import {
+  appPredicate,
+  createLogFilter,
+  deleteLogFilterProfile,
+  getLogFilterProfile,
+  listLogFilterProfiles,
+  saveLogFilterProfile,
+  streamDeviceLogs,
+} from './services/device-logs';
+import { listDeviceTypes } from './services/device-types';
+import { getThumbnail, parseThumbnailWidth, THUMBNAIL_INTERVAL_MS } from './services/thumbnails';
+import { contentTypeOf, getTestAttachmentPath, getTestRunAttachments } from './services/test-attachments';
+import { getTestRunById, listFlakyTests, listTestRuns, runTests, TestRunOptions, validateTestRun } from './services/test-runner';
+import { getCoverageReport, getFileLineCoverage } from './services/coverage';
+import { getBuildById, getBuildEnvironmentDiff, getWarningsReport, listBuilds } from './services/build-records';
+import { ExportedReport, exportBuild, exportTestRun, parseExportFormat } from './services/report-export';
+import { clearNetworkConditions, getNetworkConditions, setNetworkConditions } from './services/network-conditioner';
+import { getDebugOptions, setDebugOptions } from './services/debug-options';
+import { getLaunchOptions, manifestSettings, readManifest } from './services/manifest';
+import { BUILD_ARCHS } from './services/architectures';
+import type { Platform } from '../shared/ipc-types';
 import fetch from 'node-fetch';
 
 const PORT = 3001;
@@ -33,16 +195,70 @@ interface WebSocketClient {
 
 const clients = new Map<WebSocket, WebSocketClient>();
 
+// Skip frames for a client whose socket is this far behind
+const MAX_BUFFERED_BYTES = 4 * 1024 * 1024;
+
+// How often per-stream metrics are reported to subscribers
+const STATS_INTERVAL_MS = 1000;
+
+// Active rebuild-on-change watchers per project path
+const activeWatchers = new Map<string, () => void>();
+
 // Active MJPEG streams per UDID
-const activeStreams = new Map<string, { abort: AbortController }>();
+const activeStreams = new Map<string, { abort: AbortController; platform: Platform; ws: WebSocket }>();
+
+// Clients whose stream stopped when its session was paused, to stream to again on resume
+const pausedStreams = new Map<string, WebSocket>();
+
+// Open server-sent event responses, ended when the server stops
+const eventStreams = new Set<http.ServerResponse>();
+
+// How long clients get to finish closing before their connections are dropped
+const SHUTDOWN_GRACE_MS = 2000;
+
+// Settings POST /api/settings may change. Others, like the trigger secret and
+// log filter profiles, are stored by their own endpoints.
+const APP_SETTING_KEYS = new Set<string>([
+  'streamFps',
+  'streamQuality',
+  'derivedDataPath',
+  'theme',
+  'defaultSimulatorUdid',
+  'editorCommand',
+  'buildConcurrency',
+  'buildJobs',
+  'prewarmSimulator',
+  ...Object.values(TIMEOUT_SETTING_KEYS),
+]);
+
+let httpServer: http.Server | null = null;
+let webSocketServer: WebSocketServer | null = null;
+let frontendOptions: FrontendOptions = {};
 
 /**
  * Start the HTTP + WebSocket server for browser mode
  */
-export function startServer(): Promise<void> {
+export function startServer(options: FrontendOptions = {}): Promise<void> {
+  frontendOptions = options;
   return new Promise((resolve) => {
     const server = http.createServer(handleRequest);
-    const wss = new WebSocketServer({ server });
+    const wss = new WebSocketServer({ noServer: true });
+    httpServer = server;
+    webSocketServer = wss;
+
+    server.on('upgrade', (req, socket, head) => {
+      // Vite's HMR socket shares the port with Plasma's when proxying the frontend
+      if (options.devUrl && isHmrUpgrade(req)) {
+        proxyUpgradeToDevServer(req, socket, head, options.devUrl);
+        return;
+      }
+      // The socket starts builds, which run the project's hooks, so other pages and machines can't open one
+      if (!isAppRequest(req, { port: PORT, devUrl: options.devUrl })) {
+        socket.end('HTTP/1.1 403 Forbidden\r\nConnection: close\r\n\r\n');
+        return;
+      }
+      wss.handleUpgrade(req, socket, head, (ws) => wss.emit('connection', ws, req));
+    });
 
     wss.on('connection', (ws) => {
       console.log('[server] WebSocket client connected');
@@ -68,17 +284,89 @@ export function startServer(): Promise<void> {
       });
     });
 
-    // Forward simulator logs to WebSocket clients
-    logEmitter.on('log', (logEvent) => {
-      broadcast('simulator:log', logEvent);
+    // Forward simulator logs to the clients following that simulator
+    subscribeLogs(null, (logEvent) => {
+      publish([`stream:${logEvent.udid}`, `logs:${logEvent.udid}`, 'logs:*'], 'simulator:log', logEvent);
     });
 
+    startBuildScheduler(broadcastTriggerEvent);
+
     server.listen(PORT, () => {
       console.log(`[server] HTTP server running at http://localhost:${PORT}`);
       console.log(`[server] WebSocket server ready`);
+      if (options.devUrl) {
+        console.log(`[server] Proxying the frontend to ${options.devUrl}`);
+      }
+      resolve();
+    });
+  });
+}
+
+/**
+ * Stop the server without cutting clients off mid-frame: tell them it's
+ * shutting down, end log and metrics streams, stop watchers and capture
+ * sessions, then close the sockets once they've drained
+ */
+export async function stopServer(): Promise<void> {
+  const server = httpServer;
+  if (!server) {
+    return;
+  }
+  httpServer = null;
+  console.log('[server] Shutting down...');
+
+  broadcast('server:shutdown', {});
+  for (const stream of activeStreams.values()) {
+    stream.abort.abort();
+  }
+  activeStreams.clear();
+  pausedStreams.clear();
+  revokeAllStreamShares();
+  for (const stop of activeWatchers.values()) {
+    stop();
+  }
+  activeWatchers.clear();
+  stopBuildScheduler();
+  stopPrewarm();
+  stopAllAppMetrics();
+  stopAllAndroidSessions();
+  await stopAllSessions();
+
+  // Ended after capture stops so the last log lines and metrics summaries get through
+  for (const res of eventStreams) {
+    res.end('event: shutdown\ndata: {}\n\n');
+  }
+  eventStreams.clear();
+
+  // Close handshakes let the last frames reach the browser before the socket goes away
+  await Promise.all(
+    [...clients.keys()].map(
+      (ws) =>
+        new Promise<void>((resolve) => {
+          const timer = setTimeout(() => {
+            ws.terminate();
+            resolve();
+          }, SHUTDOWN_GRACE_MS);
+          ws.once('close', () => {
+            clearTimeout(timer);
+            resolve();
+          });
+          ws.close(1001, 'Server shutting down');
+        })
+    )
+  );
+  webSocketServer?.close();
+  webSocketServer = null;
+
+  await new Promise<void>((resolve) => {
+    const timer = setTimeout(() => server.closeAllConnections(), SHUTDOWN_GRACE_MS);
+    server.close(() => {
+      clearTimeout(timer);
       resolve();
     });
+    server.closeIdleConnections();
   });
+  console.log('[server] Server stopped');
 }
 
 /**
@@ -87,7 +375,7 @@ export function startServer(): Promise<void> {
 async function handleRequest(req: http.IncomingMessage, res: http.ServerResponse): Promise<void> {
   // CORS headers
   res.setHeader('Access-Control-Allow-Origin', '*');
-  res.setHeader('Access-Control-Allow-Methods', 'GET, POST, OPTIONS');
+  res.setHeader('Access-Control-Allow-Methods', 'GET, POST, DELETE, OPTIONS');
   res.setHeader('Access-Control-Allow-Headers', 'Content-Type');
 
   if (req.method === 'OPTIONS') {
@@ -132,6 +420,26 @@ async function handleRequest(req: http.IncomingMessage, res: http.ServerResponse
       return;
     }
 
+    // Templates for new SwiftUI apps
+    if (path === '/api/projects/templates' && req.method === 'GET') {
+      sendJson(res, listAppTemplates());
+      return;
+    }
+
+    // Scaffold a SwiftUI app from a template and register it, ready to build and stream
+    if (path === '/api/projects/new' && req.method === 'POST') {
+      const { directory, name, template } = await readBody(req);
+      if (typeof directory !== 'string' || typeof name !== 'string' || !name.trim()) {
+        sendError(res, new PlasmaError('invalid_request', 'directory and name are required'));
+        return;
+      }
+      const result = await scaffoldSwiftUIApp(directory, name.trim(), template);
+      saveUnifiedProject(name.trim(), result.xcodePath, null);
+      const project = saveProject(result.xcodePath, name.trim(), ['ios']);
+      sendJson(res, { project, ...result });
+      return;
+    }
+
     // New unified projects API
     if (path === '/api/projects/create' && req.method === 'POST') {
       const body = await readBody(req);
@@ -140,13 +448,13 @@ async function handleRequest(req: http.IncomingMessage, res: http.ServerResponse
       // If directory is provided, scaffold a new project
       if (directory) {
         if (!name) {
-          sendJson(res, { error: 'Name is required for new project' }, 400);
+          sendError(res, new PlasmaError('invalid_request', 'Name is required for new project'));
           return;
         }
 
         const result = await scaffoldNewProject(directory, name);
         if (!result.success) {
-          sendJson(res, { error: result.error }, 400);
+          sendError(res, new PlasmaError('invalid_request', result.error));
           return;
         }
 
@@ -158,7 +466,7 @@ async function handleRequest(req: http.IncomingMessage, res: http.ServerResponse
 
       // Otherwise, open an existing project
       if (!name || (!xcodePath && !androidPath)) {
-        sendJson(res, { error: 'Name and at least one project path are required' }, 400);
+        sendError(res, new PlasmaError('invalid_request', 'Name and at least one project path are required'));
         return;
       }
 
@@ -167,7 +475,7 @@ async function handleRequest(req: http.IncomingMessage, res: http.ServerResponse
       if (xcodePath) {
         const xcodeResult = validateXcodePath(xcodePath);
         if (!xcodeResult.valid) {
-          sendJson(res, { error: `Xcode: ${xcodeResult.error}` }, 400);
+          sendError(res, new PlasmaError('invalid_request', `Xcode: ${xcodeResult.error}`));
           return;
         }
         validatedXcodePath = xcodeResult.path;
@@ -178,7 +486,7 @@ async function handleRequest(req: http.IncomingMessage, res: http.ServerResponse
       if (androidPath) {
         const androidResult = validateAndroidPath(androidPath);
         if (!androidResult.valid) {
-          sendJson(res, { error: `Android: ${androidResult.error}` }, 400);
+          sendError(res, new PlasmaError('invalid_request', `Android: ${androidResult.error}`));
           return;
         }
         validatedAndroidPath = androidResult.path;
@@ -200,223 +508,1897 @@ async function handleRequest(req: http.IncomingMessage, res: http.ServerResponse
     if (path.startsWith('/api/projects/unified/') && req.method === 'GET') {
       const id = parseInt(path.split('/').pop() || '');
       if (isNaN(id)) {
-        sendJson(res, { error: 'Invalid project ID' }, 400);
+        sendError(res, new PlasmaError('invalid_request', 'Invalid project ID'));
         return;
       }
       const project = getUnifiedProjectById(id);
       if (!project) {
-        sendJson(res, { error: 'Project not found' }, 404);
+        sendError(res, new PlasmaError('project_not_found', 'Project not found'));
         return;
       }
       // Update last opened
       updateProjectLastOpened(id);
+      prewarmForProject(id).catch((err) => {
+        console.warn('[prewarm] Could not pre-warm a simulator:', err);
+      });
       sendJson(res, project);
       return;
     }
 
-    if (path === '/api/validate/xcode' && req.method === 'POST') {
-      const body = await readBody(req);
-      const result = validateXcodePath(body.path || '');
-      sendJson(res, result);
+    // Workspaces: projects open side by side, each with its own scheme and device
+    if (path === '/api/workspaces' && req.method === 'GET') {
+      sendJson(res, listWorkspaces());
       return;
     }
 
-    if (path === '/api/validate/android' && req.method === 'POST') {
-      const body = await readBody(req);
-      const result = validateAndroidPath(body.path || '');
-      sendJson(res, result);
+    if (path === '/api/workspaces' && req.method === 'POST') {
+      sendJson(res, createWorkspace(await readBody(req)));
       return;
     }
 
-    // Xcode API
-    if (path === '/api/xcode/discover' && req.method === 'POST') {
+    const workspaceProjectMatch = path.match(/^\/api\/workspaces\/([^/]+)\/projects(?:\/(\d+))?$/);
+    if (workspaceProjectMatch) {
+      const workspaceId = decodeURIComponent(workspaceProjectMatch[1]);
+      const projectId = workspaceProjectMatch[2] ? parseInt(workspaceProjectMatch[2]) : null;
+
+      if (projectId === null && req.method === 'POST') {
+        const { projectId: openedId, ...selection } = await readBody(req);
+        if (typeof openedId !== 'number') {
+          sendError(res, new PlasmaError('invalid_request', 'projectId is required'));
+          return;
+        }
+        sendJson(res, openWorkspaceProject(workspaceId, openedId, selection));
+        return;
+      }
+      if (projectId !== null && req.method === 'POST') {
+        sendJson(res, updateWorkspaceProject(workspaceId, projectId, await readBody(req)));
+        return;
+      }
+      if (projectId !== null && req.method === 'DELETE') {
+        sendJson(res, closeWorkspaceProject(workspaceId, projectId));
+        return;
+      }
+    }
+
+    const workspaceMatch = path.match(/^\/api\/workspaces\/([^/]+)$/);
+    if (workspaceMatch) {
+      const workspaceId = decodeURIComponent(workspaceMatch[1]);
+      if (req.method === 'GET') {
+        sendJson(res, getWorkspaceById(workspaceId));
+        return;
+      }
+      if (req.method === 'POST') {
+        sendJson(res, updateWorkspace(workspaceId, await readBody(req)));
+        return;
+      }
+      if (req.method === 'DELETE') {
+        removeWorkspace(workspaceId);
+        sendJson(res, { success: true });
+        return;
+      }
+    }
+
+    // Watch mode API. Rebuilds run the project's hooks, so only Plasma itself can start it.
+    if (path === '/api/watch/start' && req.method === 'POST') {
+      requireApp(req);
       const body = await readBody(req);
-      const result = await discoverProject(body.path);
-      sendJson(res, result);
+      const { path: projectPath, scheme, configuration, udids } = body;
+      if (!projectPath || !scheme || !Array.isArray(udids) || udids.length === 0) {
+        sendError(res, new PlasmaError('invalid_request', 'path, scheme, and udids are required'));
+        return;
+      }
+
+      activeWatchers.get(projectPath)?.();
+      const stop = watchProject(
+        {
+          projectPath,
+          scheme,
+          configuration,
+          udids,
+          derivedDataPath: getSettings().derivedDataPath || undefined,
+        },
+        (status) => broadcast('watch:status', status)
+      );
+      activeWatchers.set(projectPath, stop);
+      sendJson(res, { watching: true });
       return;
     }
 
-    if (path === '/api/xcode/launchable-products' && req.method === 'POST') {
+    if (path === '/api/watch/stop' && req.method === 'POST') {
       const body = await readBody(req);
-      const result = await getLaunchableProducts(body.buildDir);
-      sendJson(res, result);
+      activeWatchers.get(body.path)?.();
+      activeWatchers.delete(body.path);
+      sendJson(res, { watching: false });
       return;
     }
 
-    // Simulator API
-    if (path === '/api/simulator/list' && req.method === 'GET') {
-      const result = await listSimulators();
-      sendJson(res, result);
+    // Scheduled and push-triggered builds
+    if (path === '/api/triggers' && req.method === 'GET') {
+      sendJson(res, listBuildTriggers());
       return;
     }
 
-    if (path === '/api/simulator/launch' && req.method === 'POST') {
-      const body = await readBody(req);
-      const result = await installAndLaunch(body.udid, body.appPath, body.bundleId);
-      sendJson(res, { message: result });
+    // Triggers build, and run the hooks of, the project they name, so only Plasma itself can add them
+    if (path === '/api/triggers' && req.method === 'POST') {
+      requireApp(req);
+      sendJson(res, createBuildTrigger(await readBody(req)));
       return;
     }
 
-    if (path === '/api/simulator/tap' && req.method === 'POST') {
-      const body = await readBody(req);
-      await sendTap(body.udid, body.x, body.y, body.screenWidth, body.screenHeight);
+    // The secret webhooks are signed with, returned only here, to Plasma itself
+    if (path === '/api/triggers/secret' && req.method === 'POST') {
+      requireApp(req);
+      const { secret } = await readBody(req);
+      sendJson(res, { secret: setTriggerSecret(secret) });
+      return;
+    }
+
+    // Webhook for git hosts; builds every trigger matching the pushed repository and branch
+    if (path === '/api/triggers/git' && req.method === 'POST') {
+      const rawBody = await readRawBody(req);
+      verifyWebhook(req.headers, rawBody);
+      let body: unknown;
+      try {
+        body = rawBody.length > 0 ? JSON.parse(rawBody.toString('utf-8')) : {};
+      } catch {
+        sendError(res, new PlasmaError('invalid_request', 'Invalid JSON body'));
+        return;
+      }
+      const push = parseGitPush(body);
+      sendJson(res, { push, triggered: handleGitPush(push, broadcastTriggerEvent) }, 202);
+      return;
+    }
+
+    const triggerRunMatch = path.match(/^\/api\/triggers\/([^/]+)\/run$/);
+    if (triggerRunMatch && req.method === 'POST') {
+      const id = decodeURIComponent(triggerRunMatch[1]);
+      if (!listBuildTriggers().some((trigger) => trigger.id === id)) {
+        sendError(res, new PlasmaError('not_found', `Build trigger ${id} not found`));
+        return;
+      }
+      runBuildTrigger(id, 'manual', broadcastTriggerEvent).catch((err) => {
+        console.error(`[triggers] Build of trigger ${id} failed:`, err);
+      });
+      sendJson(res, { triggered: [id] }, 202);
+      return;
+    }
+
+    if (path.startsWith('/api/triggers/') && req.method === 'DELETE') {
+      removeBuildTrigger(decodeURIComponent(path.slice('/api/triggers/'.length)));
       sendJson(res, { success: true });
       return;
     }
 
-    if (path === '/api/simulator/swipe' && req.method === 'POST') {
+    // Android API
+    if (path === '/api/android/devices' && req.method === 'GET') {
+      sendJson(res, await listAndroidDevices());
+      return;
+    }
+
+    if (path === '/api/android/launch' && req.method === 'POST') {
       const body = await readBody(req);
-      await sendSwipe(
-        body.udid,
-        body.startX,
-        body.startY,
-        body.endX,
-        body.endY,
-        body.screenWidth,
-        body.screenHeight,
-        body.duration
-      );
-      sendJson(res, { success: true });
+      if (!body.serial || !body.apkPath) {
+        sendError(res, new PlasmaError('invalid_request', 'serial and apkPath are required'));
+        return;
+      }
+      const result = await installAndLaunchApk(body.serial, body.apkPath, body.applicationId);
+      sendJson(res, { message: result });
       return;
     }
 
-    if (path === '/api/simulator/touch' && req.method === 'POST') {
+    if (path === '/api/android/terminate' && req.method === 'POST') {
       const body = await readBody(req);
-      await sendSessionCommand(body.udid, `touch ${body.type} ${body.touches.map((t: any) => `${t.x},${t.y}`).join(' ')}`);
+      if (!body.serial || !body.applicationId) {
+        sendError(res, new PlasmaError('invalid_request', 'serial and applicationId are required'));
+        return;
+      }
+      await terminateApp(body.serial, body.applicationId);
       sendJson(res, { success: true });
       return;
     }
 
-    // Health check
-    if (path === '/api/health') {
-      sendJson(res, { status: 'ok' });
+    // Environment API
+    if (path === '/api/environment/checks' && req.method === 'GET') {
+      sendJson(res, await checkEnvironment());
       return;
     }
 
-    // 404
-    res.writeHead(404, { 'Content-Type': 'application/json' });
-    res.end(JSON.stringify({ error: 'Not found' }));
-  } catch (error) {
-    console.error('[server] Request error:', error);
-    res.writeHead(500, { 'Content-Type': 'application/json' });
-    res.end(JSON.stringify({ error: error instanceof Error ? error.message : 'Internal error' }));
-  }
-}
-
-/**
- * Handle WebSocket messages for streaming APIs
- */
-async function handleWebSocketMessage(ws: WebSocket, message: string): Promise<void> {
-  try {
-    const data = JSON.parse(message);
-    const { type, payload } = data;
+    if (path === '/api/environment/fix' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.id) {
+        sendError(res, new PlasmaError('invalid_request', 'id is required'));
+        return;
+      }
+      await fixEnvironmentCheck(body.id);
+      sendJson(res, { success: true });
+      return;
+    }
 
-    switch (type) {
-      case 'xcode:build:start': {
-        const { path, scheme } = payload;
-        const emitter = buildSchemeStream(path, scheme);
+    // Application logs API
+    if (path === '/api/logs/app' && req.method === 'GET') {
+      const since = url.searchParams.get('since');
+      const limit = url.searchParams.get('limit');
+      const sinceDate = since ? new Date(since) : undefined;
+      if (sinceDate && isNaN(sinceDate.getTime())) {
+        sendError(res, new PlasmaError('invalid_request', 'Invalid since timestamp'));
+        return;
+      }
+      sendJson(res, readLogs(sinceDate, limit ? parseInt(limit) : undefined));
+      return;
+    }
 
-        emitter.on('event', (buildEvent) => {
-          sendToClient(ws, 'xcode:build:event', buildEvent);
-        });
+    // Disk usage of DerivedData, simulators, and stored artifacts, and removing them
+    if (path === '/api/maintenance/disk-usage' && req.method === 'GET') {
+      sendJson(res, await getDiskUsage());
+      return;
+    }
 
-        emitter.on('end', () => {
-          sendToClient(ws, 'xcode:build:end', {});
-        });
-        break;
+    // Deletes files, so only Plasma itself can ask for it
+    if (path === '/api/maintenance/cleanup' && req.method === 'POST') {
+      requireApp(req);
+      const body = await readBody(req);
+      if (
+        (body.target === 'derived-data' && typeof body.name !== 'string') ||
+        (body.target === 'simulator' && typeof body.udid !== 'string')
+      ) {
+        sendError(res, new PlasmaError('invalid_request', 'name is required for derived-data and udid for simulator'));
+        return;
       }
+      sendJson(res, await cleanUp(body));
+      return;
+    }
 
-      case 'simulator:stream:start': {
-        const { udid, fps = 60, quality = 0.7 } = payload;
-        const client = clients.get(ws);
-        if (client) {
-          client.subscriptions.add(`stream:${udid}`);
-        }
+    // App settings API
+    if (path === '/api/settings' && req.method === 'GET') {
+      sendJson(res, withoutSecrets(getSettings()));
+      return;
+    }
 
-        // Stop existing stream if any
-        const existing = activeStreams.get(udid);
-        if (existing) {
-          existing.abort.abort();
-        }
+    // Settings decide which commands run and which folders builds write to,
+    // so only Plasma itself can change them
+    if (path === '/api/settings' && req.method === 'POST') {
+      requireApp(req);
+      const body = await readBody(req);
+      if (TRIGGER_SECRET_SETTING in body) {
+        sendError(res, new PlasmaError('invalid_request', 'Set the trigger secret with POST /api/triggers/secret'));
+        return;
+      }
+      const unknown = Object.keys(body).filter((key) => !APP_SETTING_KEYS.has(key));
+      if (unknown.length > 0) {
+        sendError(res, new PlasmaError('invalid_request', `Unknown settings: ${unknown.join(', ')}`));
+        return;
+      }
+      if (typeof body.editorCommand === 'string' && body.editorCommand) {
+        validateEditorCommand(body.editorCommand);
+      }
+      // An empty value clears a setting back to its default
+      const updates = settingUpdates(body);
+      for (const [key, value] of Object.entries(updates)) {
+        if (value === '') updates[key] = null;
+      }
+      sendJson(res, withoutSecrets(updateSettings(updates)));
+      return;
+    }
 
-        // Start new stream
-        const session = await getOrCreateSession(udid, fps, quality);
-        const abortController = new AbortController();
-        activeStreams.set(udid, { abort: abortController });
+    const settingsMatch = path.match(/^\/api\/projects\/(\d+)\/settings$/);
+    if (settingsMatch) {
+      const projectId = parseInt(settingsMatch[1]);
 
-        streamFramesToWebSocket(session.streamUrl, udid, ws, abortController.signal);
-        break;
+      if (req.method === 'GET') {
+        // Values committed in plasma.toml are defaults; settings saved in Plasma win
+        const record = getUnifiedProjectById(projectId);
+        const projectPath = record?.xcode_path ?? record?.android_path;
+        const manifest = projectPath ? readManifest(projectPath) : null;
+        sendJson(res, { ...manifestSettings(manifest), ...getProjectSettings(projectId) });
+        return;
       }
 
-      case 'simulator:stream:stop': {
-        const { udid } = payload;
-        stopStreamForClient(udid, ws);
-        break;
+      if (req.method === 'POST') {
+        const body = await readBody(req);
+        // Hooks are run by the shell on the next build, so only Plasma itself can change them
+        if (Object.values(HOOK_SETTING_KEYS).some((key) => key in body)) {
+          requireApp(req);
+        }
+        sendJson(res, updateProjectSettings(projectId, settingUpdates(body)));
+        return;
       }
     }
-  } catch (error) {
-    console.error('[server] WebSocket message error:', error);
-    sendToClient(ws, 'error', { message: error instanceof Error ? error.message : 'Unknown error' });
-  }
-}
 
-/**
- * Stream MJPEG frames to WebSocket client
- */
-async function streamFramesToWebSocket(
-  streamUrl: string,
-  udid: string,
-  ws: WebSocket,
-  signal: AbortSignal
-): Promise<void> {
-  try {
-    const response = await fetch(streamUrl, { signal: signal as any });
+    if (path === '/api/validate/xcode' && req.method === 'POST') {
+      const body = await readBody(req);
+      const result = validateXcodePath(body.path || '');
+      sendJson(res, result);
+      return;
+    }
 
-    if (!response.ok) {
-      throw new Error(`Failed to connect to simulator stream: ${response.statusText}`);
+    if (path === '/api/validate/android' && req.method === 'POST') {
+      const body = await readBody(req);
+      const result = validateAndroidPath(body.path || '');
+      sendJson(res, result);
+      return;
     }
 
-    const body = response.body;
-    if (!body) {
-      throw new Error('No response body from simulator stream');
+    // Xcode API
+    // Clears fetched packages so the next build resolves them from scratch
+    if (path === '/api/xcode/packages/purge-cache' && req.method === 'POST') {
+      sendJson(res, purgePackageCache());
+      return;
     }
 
-    let buffer = Buffer.alloc(0);
-    const boundary = '--mjpegstream';
+    if (path === '/api/xcode/discover' && req.method === 'POST') {
+      const body = await readBody(req);
+      const result = await discoverProject(body.path, { forceRefresh: body.forceRefresh === true });
+      sendJson(res, result);
+      return;
+    }
 
-    const nodeStream = body as unknown as NodeJS.ReadableStream & { destroy?: () => void };
+    if (path === '/api/xcode/launchable-products' && req.method === 'POST') {
+      const body = await readBody(req);
+      const result = await getLaunchableProducts(body.buildDir);
+      sendJson(res, result);
+      return;
+    }
 
-    nodeStream.on('data', (chunk: Buffer) => {
-      if (signal.aborted || ws.readyState !== WebSocket.OPEN) {
-        nodeStream.destroy?.();
+    // Info.plist, entitlements, and linked libraries of a built .app
+    if (path === '/api/xcode/product-details' && req.method === 'GET') {
+      const productPath = url.searchParams.get('path');
+      if (!productPath) {
+        sendError(res, new PlasmaError('invalid_request', 'path is required'));
         return;
       }
+      sendJson(res, await getProductDetails(productPath));
+      return;
+    }
 
-      buffer = Buffer.concat([buffer, chunk]);
+    // Where a built .app's size goes, with per-library code size when a link map is given
+    if (path === '/api/xcode/bundle-size' && req.method === 'GET') {
+      const productPath = url.searchParams.get('path');
+      if (!productPath) {
+        sendError(res, new PlasmaError('invalid_request', 'path is required'));
+        return;
+      }
+      sendJson(res, await analyzeBundleSize(productPath, url.searchParams.get('linkMap') ?? undefined));
+      return;
+    }
 
-      let boundaryIndex: number;
-      while ((boundaryIndex = buffer.indexOf(boundary)) !== -1) {
-        const headerEnd = buffer.indexOf('\r\n\r\n', boundaryIndex);
-        if (headerEnd === -1) break;
+    // Simulator API
+    if (path === '/api/simulator/list' && req.method === 'GET') {
+      const result = await listSimulators();
+      sendJson(res, result);
+      return;
+    }
 
-        const dataStart = headerEnd + 4;
-        const nextBoundary = buffer.indexOf(boundary, dataStart);
-        if (nextBoundary === -1) break;
+    // Device types and runtimes simulators can be created from
+    if (path === '/api/simulators/devicetypes' && req.method === 'GET') {
+      sendJson(res, await listDeviceTypes());
+      return;
+    }
 
-        const jpegData = buffer.slice(dataStart, nextBoundary - 2);
+    // The simulator booted ahead of the first run of the open project
+    if (path === '/api/simulator/prewarm' && req.method === 'GET') {
+      sendJson(res, getPrewarmStatus());
+      return;
+    }
 
-        if (jpegData.length > 0) {
-          sendToClient(ws, 'simulator:stream:frame', {
-            udid,
-            frame: jpegData.toString('base64'),
-          });
+    if (path === '/api/simulator/prewarm' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (typeof body.projectId !== 'number') {
+        sendError(res, new PlasmaError('invalid_request', 'projectId is required'));
+        return;
+      }
+      sendJson(res, await prewarmForProject(body.projectId, true));
+      return;
+    }
+
+    if (path === '/api/simulator/prewarm' && req.method === 'DELETE') {
+      stopPrewarm();
+      sendJson(res, { success: true });
+      return;
+    }
+
+    // Runs the project's postInstall hooks, so only Plasma itself can use it
+    if (path === '/api/simulator/launch' && req.method === 'POST') {
+      requireApp(req);
+      const body = await readBody(req);
+      const launchOptions = body.projectPath ? getLaunchOptions(body.projectPath) : undefined;
+      const result = await installAndLaunch(body.udid, body.appPath, body.bundleId, launchOptions);
+      const hookOutput: string[] = [];
+      if (body.projectPath) {
+        await runHooks(
+          'postInstall',
+          { projectPath: body.projectPath, udid: body.udid, appPath: body.appPath },
+          (line) => hookOutput.push(line)
+        );
+      }
+      sampleLaunchedApp(body.udid, result.bundleId);
+      sendJson(res, { ...result, hookOutput });
+      return;
+    }
+
+    // Signing identities in the keychain and installed provisioning profiles
+    if (path === '/api/signing/identities' && req.method === 'GET') {
+      sendJson(res, { identities: await listSigningIdentities() });
+      return;
+    }
+
+    if (path === '/api/signing/profiles' && req.method === 'GET') {
+      sendJson(res, { profiles: listProvisioningProfiles() });
+      return;
+    }
+
+    // Upload an exported IPA to TestFlight, one JSON event per line until it's processed
+    if (path === '/api/testflight/upload' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.ipaPath) {
+        sendError(res, new PlasmaError('invalid_request', 'ipaPath is required'));
+        return;
+      }
+
+      // Headers go out with the first event so validation errors are still plain errors
+      let streaming = false;
+      await uploadToTestFlight(
+        body.ipaPath,
+        { projectPath: body.projectPath, waitForProcessing: body.waitForProcessing !== false },
+        (event) => {
+          if (!streaming) {
+            res.writeHead(200, { 'Content-Type': 'application/x-ndjson', 'Cache-Control': 'no-cache' });
+            streaming = true;
+          }
+          res.write(`${JSON.stringify(event)}\n`);
+        }
+      );
+      res.end();
+      return;
+    }
+
+    // Mac API, for macOS and Mac Catalyst builds. Launching runs the app and
+    // its hooks on this Mac and screenshots show its windows, so only Plasma
+    // itself can use it.
+    if (path === '/api/mac/launch' && req.method === 'POST') {
+      requireApp(req);
+      const body = await readBody(req);
+      if (!body.appPath) {
+        sendError(res, new PlasmaError('invalid_request', 'appPath is required'));
+        return;
+      }
+      const launchOptions = body.projectPath ? getLaunchOptions(body.projectPath) : undefined;
+      const result = await launchMacApp(body.appPath, launchOptions);
+      const hookOutput: string[] = [];
+      if (body.projectPath) {
+        await runHooks('postInstall', { projectPath: body.projectPath, appPath: body.appPath }, (line) =>
+          hookOutput.push(line)
+        );
+      }
+      sendJson(res, { ...result, hookOutput });
+      return;
+    }
+
+    if (path === '/api/mac/terminate' && req.method === 'POST') {
+      requireApp(req);
+      const body = await readBody(req);
+      if (!body.bundleId) {
+        sendError(res, new PlasmaError('invalid_request', 'bundleId is required'));
+        return;
+      }
+      sendJson(res, { terminated: await terminateMacApp(body.bundleId) });
+      return;
+    }
+
+    if (path === '/api/mac/screenshot' && req.method === 'GET') {
+      requireApp(req);
+      const pid = Number(url.searchParams.get('pid'));
+      if (!Number.isInteger(pid) || pid <= 0) {
+        sendError(res, new PlasmaError('invalid_request', 'pid is required'));
+        return;
+      }
+      const png = await captureMacAppWindow(pid);
+      res.writeHead(200, { 'Content-Type': 'image/png', 'Content-Length': png.length });
+      res.end(png);
+      return;
+    }
+
+    // Photos and videos for the simulator's library, from local paths or uploaded one per request
+    if (path === '/api/simulator/media' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.udid || !Array.isArray(body.paths)) {
+        sendError(res, new PlasmaError('invalid_request', 'udid and paths are required'));
+        return;
+      }
+      await addMedia(body.udid, body.paths);
+      sendJson(res, { success: true, added: body.paths.length });
+      return;
+    }
+
+    if (path === '/api/simulator/media/upload' && req.method === 'POST') {
+      const udid = url.searchParams.get('udid');
+      const filename = url.searchParams.get('filename');
+      if (!udid || !filename) {
+        sendError(res, new PlasmaError('invalid_request', 'udid and filename are required'));
+        return;
+      }
+      await addMediaUpload(udid, filename, req);
+      sendJson(res, { success: true, added: 1 });
+      return;
+    }
+
+    // Fixture sets declared in the project's plasma.toml
+    if (path === '/api/fixtures' && req.method === 'GET') {
+      const projectPath = url.searchParams.get('projectPath');
+      if (!projectPath) {
+        sendError(res, new PlasmaError('invalid_request', 'projectPath is required'));
+        return;
+      }
+      sendJson(res, { fixtures: listFixtureSets(projectPath) });
+      return;
+    }
+
+    if (path === '/api/fixtures/seed' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.udid || !body.projectPath || !body.name) {
+        sendError(res, new PlasmaError('invalid_request', 'udid, projectPath, and name are required'));
+        return;
+      }
+      sendJson(res, await seedFixtures(body.udid, body.projectPath, body.name));
+      return;
+    }
+
+    // Install and launch one build on several simulators, one JSON status per line
+    if (path === '/api/simulator/launch-multiple' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.appPath || !Array.isArray(body.udids)) {
+        sendError(res, new PlasmaError('invalid_request', 'appPath and udids are required'));
+        return;
+      }
+
+      // Headers go out with the first event so validation errors are still plain errors
+      let streaming = false;
+      await runOnDevices(
+        body.appPath,
+        body.udids,
+        {
+          launchOptions: body.projectPath ? getLaunchOptions(body.projectPath) : undefined,
+          fps: body.fps,
+          quality: body.quality,
+        },
+        (event) => {
+          if (!streaming) {
+            res.writeHead(200, { 'Content-Type': 'application/x-ndjson', 'Cache-Control': 'no-cache' });
+            streaming = true;
+          }
+          if (event.type === 'status' && event.device.state === 'streaming' && event.device.bundleId) {
+            sampleLaunchedApp(event.device.udid, event.device.bundleId);
+          }
+          res.write(`${JSON.stringify(event)}\n`);
+        }
+      );
+      res.end();
+      return;
+    }
+
+    // Phone and watch simulator pairs
+    if (path === '/api/simulator/pairs' && req.method === 'GET') {
+      sendJson(res, await listSimulatorPairs());
+      return;
+    }
+
+    if (path === '/api/simulator/pairs' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.watchUdid || !body.phoneUdid) {
+        sendError(res, new PlasmaError('invalid_request', 'watchUdid and phoneUdid are required'));
+        return;
+      }
+      sendJson(res, await createSimulatorPair(body.watchUdid, body.phoneUdid));
+      return;
+    }
+
+    if (path === '/api/simulator/pairs' && req.method === 'DELETE') {
+      const pairId = url.searchParams.get('pairId');
+      if (!pairId) {
+        sendError(res, new PlasmaError('invalid_request', 'pairId is required'));
+        return;
+      }
+      await deleteSimulatorPair(pairId);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/simulator/pairs/boot' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.pairId) {
+        sendError(res, new PlasmaError('invalid_request', 'pairId is required'));
+        return;
+      }
+      sendJson(res, await bootSimulatorPair(body.pairId));
+      return;
+    }
+
+    if (path === '/api/simulator/pairs/install' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.pairId || !body.appPath) {
+        sendError(res, new PlasmaError('invalid_request', 'pairId and appPath are required'));
+        return;
+      }
+      const result = await installOnPair(body.pairId, body.appPath, {
+        launchWatchApp: body.launchWatchApp === true,
+        launchOptions: body.projectPath ? getLaunchOptions(body.projectPath) : undefined,
+      });
+      sendJson(res, result);
+      return;
+    }
+
+    if (path === '/api/simulator/tap' && req.method === 'POST') {
+      const body = await readBody(req);
+      verifyInputToken(req, url, body);
+      recordTap(body.udid, { x: body.x, y: body.y });
+      await sendTap(body.udid, body.x, body.y, body.screenWidth, body.screenHeight);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    // Accessibility hierarchy of the foreground app, or the element at x,y (in points).
+    // Like screenshots, it shows what's on screen, so only Plasma itself can read it.
+    if (path === '/api/simulator/accessibility' && req.method === 'GET') {
+      requireApp(req);
+      const udid = url.searchParams.get('udid');
+      if (!udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      const x = url.searchParams.get('x');
+      const y = url.searchParams.get('y');
+      if ((x === null) !== (y === null) || (x !== null && (isNaN(Number(x)) || isNaN(Number(y))))) {
+        sendError(res, new PlasmaError('invalid_request', 'x and y must both be numbers'));
+        return;
+      }
+      const elements = x !== null && y !== null
+        ? await describePoint(udid, Number(x), Number(y))
+        : await describeScreen(udid);
+      sendJson(res, { elements });
+      return;
+    }
+
+    // Tap an element by accessibility identifier or label instead of coordinates
+    if (path === '/api/simulator/actions/tap-element' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.udid || (!body.identifier && !body.label)) {
+        sendError(res, new PlasmaError('invalid_request', 'udid and an identifier or label are required'));
+        return;
+      }
+      verifyInputToken(req, url, body);
+      const result = await tapElement(body.udid, {
+        identifier: body.identifier,
+        label: body.label,
+        index: body.index,
+      });
+      recordTap(body.udid, result.normalized, result.element);
+      sendJson(res, result);
+      return;
+    }
+
+    if (path === '/api/simulator/swipe' && req.method === 'POST') {
+      const body = await readBody(req);
+      verifyInputToken(req, url, body);
+      recordSwipe(
+        body.udid,
+        { x: body.startX, y: body.startY },
+        { x: body.endX, y: body.endY },
+        (body.duration ?? 0.3) * 1000
+      );
+      await sendSwipe(
+        body.udid,
+        body.startX,
+        body.startY,
+        body.endX,
+        body.endY,
+        body.screenWidth,
+        body.screenHeight,
+        body.duration
+      );
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/simulator/touch' && req.method === 'POST') {
+      const body = await readBody(req);
+      verifyInputToken(req, url, body);
+      if (body.touches.length > 0) {
+        recordTouch(body.udid, body.type, body.touches[0]);
+      }
+      await sendSessionCommand(body.udid, `touch ${body.type} ${body.touches.map((t: any) => `${t.x},${t.y}`).join(' ')}`);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/simulator/button' && req.method === 'POST') {
+      const body = await readBody(req);
+      verifyInputToken(req, url, body);
+      if (!['home', 'lock', 'sideButton'].includes(body.button)) {
+        sendError(res, new PlasmaError('invalid_request', `Unknown button: ${body.button}`));
+        return;
+      }
+      recordButton(body.udid, body.button);
+      await pressButton(body.udid, body.button);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    // Record forwarded input so it can be exported as an XCUITest
+    if (path === '/api/simulator/interactions' && req.method === 'GET') {
+      const udid = url.searchParams.get('udid');
+      if (!udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      sendJson(res, { recording: isRecordingInteractions(udid) });
+      return;
+    }
+
+    if (path === '/api/simulator/interactions/start' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      startInteractionRecording(body.udid);
+      sendJson(res, { recording: true });
+      return;
+    }
+
+    if (path === '/api/simulator/interactions/stop' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      const steps = await stopInteractionRecording(body.udid);
+      sendJson(res, {
+        steps,
+        xcuitest: toXCUITest(steps, body.testName || undefined),
+        script: toAutomationScript(steps, body.testName || undefined),
+      });
+      return;
+    }
+
+    // Run an automation script, streaming one JSON event per line as steps finish
+    if (path === '/api/automation/run' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.udid || !body.script) {
+        sendError(res, new PlasmaError('invalid_request', 'udid and script are required'));
+        return;
+      }
+      // Scripts tap, type, and swipe, so they need the session's token like single inputs do
+      verifyInputToken(req, url, body);
+      const script = parseAutomationScript(body.script);
+
+      res.writeHead(200, { 'Content-Type': 'application/x-ndjson', 'Cache-Control': 'no-cache' });
+      const abortController = new AbortController();
+      res.on('close', () => abortController.abort());
+      await runAutomation(
+        body.udid,
+        script,
+        (event) => res.write(`${JSON.stringify(event)}\n`),
+        abortController.signal
+      );
+      res.end();
+      return;
+    }
+
+    // Run a batch of agent actions and answer with every action's result at once
+    if (path === '/api/agent/act' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      // Actions send input and return screenshots, so they need the session's token like other input
+      verifyInputToken(req, url, body);
+      const actions = parseAgentActions(body.actions);
+      const screenshots = parseScreenshotMode(body.screenshots);
+
+      const abortController = new AbortController();
+      res.on('close', () => abortController.abort());
+      const result = await runAgentActions(
+        body.udid,
+        actions,
+        { stopOnError: body.stopOnError !== false, screenshots },
+        abortController.signal
+      );
+      sendJson(res, result);
+      return;
+    }
+
+    // Capture localized screenshots for every device and locale, streamed like automation runs
+    if (path === '/api/screenshots/run' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.config) {
+        sendError(res, new PlasmaError('invalid_request', 'config is required'));
+        return;
+      }
+      const matrix = parseScreenshotMatrix(body.config);
+      const devices = await resolveMatrixDevices(matrix);
+
+      res.writeHead(200, { 'Content-Type': 'application/x-ndjson', 'Cache-Control': 'no-cache' });
+      const abortController = new AbortController();
+      res.on('close', () => abortController.abort());
+      await runScreenshotMatrix(
+        matrix,
+        devices,
+        (event) => res.write(`${JSON.stringify(event)}\n`),
+        abortController.signal
+      );
+      res.end();
+      return;
+    }
+
+    // Compare the current screen against its stored baseline
+    if (path === '/api/visual/check' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.udid || !body.name) {
+        sendError(res, new PlasmaError('invalid_request', 'udid and name are required'));
+        return;
+      }
+      sendJson(res, await checkScreenshot({
+        udid: body.udid,
+        name: body.name,
+        project: body.projectPath || undefined,
+        threshold: body.threshold,
+        tolerance: body.tolerance,
+        masks: parseMasks(body.masks),
+        updateBaseline: body.updateBaseline === true,
+      }));
+      return;
+    }
+
+    if (path === '/api/simulator/rotate' && req.method === 'POST') {
+      const body = await readBody(req);
+      verifyInputToken(req, url, body);
+      await rotate(body.udid, Number(body.rotation) || 0);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/simulator/shake' && req.method === 'POST') {
+      const body = await readBody(req);
+      verifyInputToken(req, url, body);
+      await shake(body.udid);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    // Shake or memory warning, for testing debug menus and low-memory handling
+    if (path === '/api/simulator/action' && req.method === 'POST') {
+      const body = await readBody(req);
+      verifyInputToken(req, url, body);
+      await performDeviceAction(body.udid, body.action);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/simulator/apps' && req.method === 'GET') {
+      const udid = url.searchParams.get('udid');
+      const bundleId = url.searchParams.get('bundleId');
+      if (!udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      sendJson(res, bundleId ? await getAppInfo(udid, bundleId) : await listInstalledApps(udid));
+      return;
+    }
+
+    if (path === '/api/simulator/apps/launch' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.udid || !body.bundleId) {
+        sendError(res, new PlasmaError('invalid_request', 'udid and bundleId are required'));
+        return;
+      }
+      const result = await launchInstalledApp(body.udid, body.bundleId);
+      sampleLaunchedApp(body.udid, result.bundleId);
+      sendJson(res, result);
+      return;
+    }
+
+    // An app's NSUserDefaults as JSON; writes relaunch the app unless relaunch is false
+    if (path === '/api/simulator/apps/defaults' && req.method === 'GET') {
+      const udid = url.searchParams.get('udid');
+      const bundleId = url.searchParams.get('bundleId');
+      if (!udid || !bundleId) {
+        sendError(res, new PlasmaError('invalid_request', 'udid and bundleId are required'));
+        return;
+      }
+      sendJson(res, await readUserDefaults(udid, bundleId));
+      return;
+    }
+
+    if (path === '/api/simulator/apps/defaults' && req.method === 'POST') {
+      const body = await readBody(req);
+      const values = body.values;
+      if (!body.udid || !body.bundleId || !values || typeof values !== 'object' || Array.isArray(values)) {
+        sendError(res, new PlasmaError('invalid_request', 'udid, bundleId, and values are required'));
+        return;
+      }
+      const relaunch = body.relaunch !== false;
+      const defaults = await writeUserDefaults(body.udid, body.bundleId, values, relaunch);
+      if (relaunch) {
+        sampleLaunchedApp(body.udid, body.bundleId);
+      }
+      sendJson(res, defaults);
+      return;
+    }
+
+    // Targeted resets for reproducing first-launch flows without erasing the simulator
+    if (path === '/api/simulator/apps/reset-data' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.udid || !body.bundleId) {
+        sendError(res, new PlasmaError('invalid_request', 'udid and bundleId are required'));
+        return;
+      }
+      await resetAppData(body.udid, body.bundleId);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/simulator/apps/reset-defaults' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.udid || !body.bundleId) {
+        sendError(res, new PlasmaError('invalid_request', 'udid and bundleId are required'));
+        return;
+      }
+      await resetUserDefaults(body.udid, body.bundleId);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/simulator/keychain/reset' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      await resetKeychain(body.udid);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    // Start a stream session (or reuse the running one) and get the token to stream it with.
+    // The token lets its holder watch and drive the simulator, so only Plasma itself gets it.
+    if (path === '/api/simulator/session' && req.method === 'POST') {
+      requireApp(req);
+      const body = await readBody(req);
+      if (!body.udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      const settings = getSettings();
+      const platform: Platform = body.platform ?? 'ios';
+      const fps = body.fps ?? parseInt(settings.streamFps || '60');
+      const quality = body.quality ?? parseFloat(settings.streamQuality || '0.7');
+      if (platform === 'android') {
+        await getOrCreateAndroidSession(body.udid, fps, quality);
+      } else {
+        await getOrCreateSession(body.udid, fps, quality);
+      }
+      sendJson(res, { udid: body.udid, platform, token: issueStreamToken(body.udid) });
+      return;
+    }
+
+    // A session's frames as multipart MJPEG, for consumers other than the WebSocket.
+    // maxWidth and quality re-encode frames for clients on slow links.
+    if (path === '/api/simulator/stream' && req.method === 'GET') {
+      const udid = url.searchParams.get('udid');
+      if (!udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      verifyStreamToken(udid, requestStreamToken(req.headers.authorization, url.searchParams));
+      const reencode = parseReencodeOptions(url.searchParams);
+      const platform = url.searchParams.get('platform') === 'android' ? 'android' : 'ios';
+      const session = platform === 'android'
+        ? await getOrCreateAndroidSession(udid)
+        : await getOrCreateSession(udid);
+      // Someone is watching again
+      if (platform === 'ios') {
+        await resumeStreaming(udid);
+      }
+      const upstream = (await proxyStream(session.streamUrl)) as NodeJS.ReadableStream & { destroy: () => void };
+      // Started before the headers go out so a missing ffmpeg is still a plain error;
+      // frames only arrive on later ticks
+      let stopReencoding: (() => void) | null = null;
+      try {
+        stopReencoding = reencode
+          ? reencodeMjpeg(upstream, reencode, (frame) => {
+              // Skip frames for a reader that hasn't drained the previous ones
+              if (res.writableLength <= MAX_BUFFERED_BYTES) {
+                res.write(encodeMjpegPart(frame));
+              }
+            })
+          : null;
+      } catch (err) {
+        upstream.destroy();
+        throw err;
+      }
+
+      res.writeHead(200, {
+        'Content-Type': `multipart/x-mixed-replace; boundary=${MJPEG_BOUNDARY.slice(2)}`,
+        'Cache-Control': 'no-cache',
+        Connection: 'close',
+      });
+      if (platform === 'ios') {
+        retainSession(udid);
+      }
+      res.on('close', () => {
+        stopReencoding?.();
+        upstream.destroy();
+        if (platform === 'ios') {
+          releaseSession(udid);
+        }
+      });
+      upstream.on('error', () => res.end());
+      if (stopReencoding) {
+        upstream.on('end', () => res.end());
+      } else {
+        upstream.pipe(res);
+      }
+      return;
+    }
+
+    // Stop the app's own stream of a session nobody can see in it, like a minimized viewer's, and start again
+    const sessionPauseMatch = path.match(/^\/api\/simulator\/session\/(pause|resume)$/);
+    if (sessionPauseMatch && req.method === 'POST') {
+      const { udid } = await readBody(req);
+      if (typeof udid !== 'string' || !udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      if (sessionPauseMatch[1] === 'pause') {
+        pauseStreaming(udid);
+      } else if (!(await resumeStreaming(udid)) && !hasSession(udid)) {
+        sendError(res, new PlasmaError('session_not_found', `No active session for simulator ${udid}`));
+        return;
+      }
+      sendJson(res, { udid, paused: sessionPauseMatch[1] === 'pause' });
+      return;
+    }
+
+    // Tear down a simulator's stream session and its simulator-server process
+    if (path === '/api/simulator/session' && req.method === 'DELETE') {
+      const udid = url.searchParams.get('udid');
+      if (!udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      const stream = activeStreams.get(udid);
+      if (stream) {
+        stream.abort.abort();
+        activeStreams.delete(udid);
+      }
+      pausedStreams.delete(udid);
+      if (!stopSession(udid)) {
+        sendError(res, new PlasmaError('session_not_found', `No active session for simulator ${udid}`));
+        return;
+      }
+      sendJson(res, { success: true });
+      return;
+    }
+
+    // View-only links to a simulator's stream for teammates on the LAN. Only
+    // Plasma itself manages them, since a link's token is all it takes to watch.
+    if (path.startsWith('/api/simulator/shares')) {
+      requireApp(req);
+    }
+
+    if (path === '/api/simulator/shares' && req.method === 'GET') {
+      sendJson(res, listStreamShares(url.searchParams.get('udid') || undefined).map(withShareUrl));
+      return;
+    }
+
+    if (path === '/api/simulator/shares' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      sendJson(res, withShareUrl(createStreamShare(body.udid, body.expiresInMinutes ?? undefined)));
+      return;
+    }
+
+    if (path.startsWith('/api/simulator/shares/') && req.method === 'DELETE') {
+      revokeStreamShare(decodeURIComponent(path.slice('/api/simulator/shares/'.length)));
+      sendJson(res, { success: true });
+      return;
+    }
+
+    // Network link conditioning through a per-simulator throttling proxy
+    if (path === '/api/simulator/network' && req.method === 'GET') {
+      const udid = url.searchParams.get('udid');
+      if (!udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      sendJson(res, getNetworkConditions(udid));
+      return;
+    }
+
+    if (path === '/api/simulator/network' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.udid || (!body.preset && !body.conditions)) {
+        sendError(res, new PlasmaError('invalid_request', 'udid and preset or conditions are required'));
+        return;
+      }
+      sendJson(
+        res,
+        await setNetworkConditions(body.udid, body.preset ? { preset: body.preset } : { conditions: body.conditions })
+      );
+      return;
+    }
+
+    if (path === '/api/simulator/network' && req.method === 'DELETE') {
+      const udid = url.searchParams.get('udid');
+      if (!udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      sendJson(res, { success: clearNetworkConditions(udid) });
+      return;
+    }
+
+    // Slow animations and debug overlays, applied when apps are next launched
+    if (path === '/api/simulator/debug-options' && req.method === 'GET') {
+      const udid = url.searchParams.get('udid');
+      if (!udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      sendJson(res, getDebugOptions(udid));
+      return;
+    }
+
+    if (path === '/api/simulator/debug-options' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      const state = setDebugOptions(body.udid, body.options);
+      // Relaunch right away when the client says which app is running
+      if (body.bundleId) {
+        await launchInstalledApp(
+          body.udid,
+          body.bundleId,
+          body.projectPath ? getLaunchOptions(body.projectPath) : undefined
+        );
+      }
+      sendJson(res, state);
+      return;
+    }
+
+    // Crash reports of simulator apps, symbolicated against the build that crashed
+    // Stored builds and their diagnostics
+    if (path === '/api/builds' && req.method === 'GET') {
+      const limit = url.searchParams.get('limit');
+      sendJson(res, {
+        builds: listBuilds(url.searchParams.get('projectPath') ?? undefined, limit ? parseInt(limit) : undefined),
+      });
+      return;
+    }
+
+    // Warnings of the latest builds by file and rule, and per build to show the trend
+    if (path === '/api/builds/warnings' && req.method === 'GET') {
+      const limit = url.searchParams.get('limit');
+      sendJson(res, getWarningsReport(url.searchParams.get('projectPath') ?? undefined, limit ? parseInt(limit) : undefined));
+      return;
+    }
+
+    // What changed in Xcode, SDKs, tools, variables, and packages between two builds
+    if (path === '/api/builds/environment-diff' && req.method === 'GET') {
+      const from = url.searchParams.get('from');
+      const to = url.searchParams.get('to');
+      if (!from || !to) {
+        sendError(res, new PlasmaError('invalid_request', 'from and to build ids are required'));
+        return;
+      }
+      sendJson(res, getBuildEnvironmentDiff(from, to));
+      return;
+    }
+
+    // Builds running and waiting for a slot
+    if (path === '/api/builds/queue' && req.method === 'GET') {
+      sendJson(res, getBuildQueueStatus());
+      return;
+    }
+
+    // Builds running now, whether started from the app, the API, or a trigger
+    if (path === '/api/builds/active' && req.method === 'GET') {
+      sendJson(res, { builds: listActiveBuilds() });
+      return;
+    }
+
+    // Follow a running build, or the latest one, until it ends. Headers go out with
+    // the replayed started event, so a build that isn't running is still a 404.
+    if (path === '/api/builds/active/events' && req.method === 'GET') {
+      const stop = followBuild(
+        url.searchParams.get('id') ?? undefined,
+        (event) => {
+          if (!res.headersSent) {
+            res.writeHead(200, { 'Content-Type': 'application/x-ndjson', 'Cache-Control': 'no-cache' });
+          }
+          res.write(`${JSON.stringify(event)}\n`);
+        },
+        () => res.end()
+      );
+      res.on('close', stop);
+      return;
+    }
+
+    // Build several projects at once, streaming every build's events tagged with its index
+    if (path === '/api/builds/batch' && req.method === 'POST') {
+      // Builds run the projects' hooks
+      requireApp(req);
+      const body = await readBody(req);
+      const builds = parseBatchBuilds(body.builds);
+
+      // Builds still queued or running are cancelled when the client goes away
+      const abortController = new AbortController();
+      res.on('close', () => abortController.abort());
+
+      res.writeHead(200, { 'Content-Type': 'application/x-ndjson', 'Cache-Control': 'no-cache' });
+      await buildProjects(
+        builds,
+        (event) => res.write(`${JSON.stringify(event)}\n`),
+        getSettings().derivedDataPath || undefined,
+        abortController.signal
+      );
+      res.end();
+      return;
+    }
+
+    const buildExportMatch = path.match(/^\/api\/builds\/([\w-]+)\/export$/);
+    if (buildExportMatch && req.method === 'GET') {
+      const format = parseExportFormat(url.searchParams.get('format'));
+      const root = url.searchParams.get('root') ?? undefined;
+      sendReport(res, exportBuild(getBuildById(buildExportMatch[1]), format, { root }));
+      return;
+    }
+
+    // Full output of a build as text; Range requests read it in parts
+    const buildLogMatch = path.match(/^\/api\/builds\/([\w-]+)\/log$/);
+    if (buildLogMatch && req.method === 'GET') {
+      const size = getBuildLogSize(buildLogMatch[1]);
+      const range = parseByteRange(req.headers.range, size);
+      const { start, end } = range ?? { start: 0, end: size - 1 };
+      res.writeHead(range ? 206 : 200, {
+        'Content-Type': 'text/plain; charset=utf-8',
+        'Accept-Ranges': 'bytes',
+        ...(range ? { 'Content-Range': `bytes ${start}-${end}/${size}` } : {}),
+      });
+      res.end(readBuildLogRange(buildLogMatch[1], start, end));
+      return;
+    }
+
+    const buildLogTailMatch = path.match(/^\/api\/builds\/([\w-]+)\/log\/tail$/);
+    if (buildLogTailMatch && req.method === 'GET') {
+      const lines = url.searchParams.get('lines');
+      sendJson(res, tailBuildLog(buildLogTailMatch[1], lines ? parseInt(lines) : undefined));
+      return;
+    }
+
+    const buildMatch = path.match(/^\/api\/builds\/([\w-]+)$/);
+    if (buildMatch && req.method === 'GET') {
+      sendJson(res, getBuildById(buildMatch[1]));
+      return;
+    }
+
+    // Test runs and their code coverage
+    if (path === '/api/tests/run' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.projectPath || !body.scheme || !body.udid) {
+        sendError(res, new PlasmaError('invalid_request', 'projectPath, scheme, and udid are required'));
+        return;
+      }
+
+      const options: TestRunOptions = {
+        projectPath: body.projectPath,
+        scheme: body.scheme,
+        udid: body.udid,
+        configuration: body.configuration || undefined,
+        coverage: body.coverage === true,
+        onlyTesting: Array.isArray(body.onlyTesting) ? body.onlyTesting : undefined,
+        shards: body.shards === undefined ? undefined : Number(body.shards),
+        retry: body.retry && typeof body.retry === 'object' ? body.retry : undefined,
+      };
+      validateTestRun(options);
+
+      res.writeHead(200, { 'Content-Type': 'application/x-ndjson', 'Cache-Control': 'no-cache' });
+      const abortController = new AbortController();
+      res.on('close', () => abortController.abort());
+      await runTests(
+        options,
+        (event) => res.write(`${JSON.stringify(event)}\n`),
+        abortController.signal
+      );
+      res.end();
+      return;
+    }
+
+    if (path === '/api/tests/runs' && req.method === 'GET') {
+      const limit = url.searchParams.get('limit');
+      sendJson(res, {
+        runs: listTestRuns(url.searchParams.get('projectPath') ?? undefined, limit ? parseInt(limit) : undefined),
+      });
+      return;
+    }
+
+    if (path === '/api/tests/flaky' && req.method === 'GET') {
+      const limit = url.searchParams.get('limit');
+      sendJson(res, {
+        tests: listFlakyTests(url.searchParams.get('projectPath') ?? undefined, limit ? parseInt(limit) : undefined),
+      });
+      return;
+    }
+
+    const testRunExportMatch = path.match(/^\/api\/tests\/runs\/([\w-]+)\/export$/);
+    if (testRunExportMatch && req.method === 'GET') {
+      const format = parseExportFormat(url.searchParams.get('format'));
+      const root = url.searchParams.get('root') ?? undefined;
+      sendReport(res, exportTestRun(getTestRunById(testRunExportMatch[1]), format, { root }));
+      return;
+    }
+
+    // Attachments of failed tests, like failure screenshots, from the run's result bundle
+    const testAttachmentMatch = path.match(/^\/api\/tests\/runs\/([\w-]+)\/attachments\/([^/]+)$/);
+    if (testAttachmentMatch && req.method === 'GET') {
+      const fileName = decodeURIComponent(testAttachmentMatch[2]);
+      const file = await getTestAttachmentPath(testAttachmentMatch[1], fileName);
+      res.writeHead(200, {
+        'Content-Type': contentTypeOf(fileName),
+        'Content-Length': fs.statSync(file).size,
+        'Cache-Control': 'max-age=86400',
+      });
+      fs.createReadStream(file).pipe(res);
+      return;
+    }
+
+    const testAttachmentsMatch = path.match(/^\/api\/tests\/runs\/([\w-]+)\/attachments$/);
+    if (testAttachmentsMatch && req.method === 'GET') {
+      sendJson(res, await getTestRunAttachments(testAttachmentsMatch[1]));
+      return;
+    }
+
+    const testRunMatch = path.match(/^\/api\/tests\/runs\/([\w-]+)$/);
+    if (testRunMatch && req.method === 'GET') {
+      sendJson(res, getTestRunById(testRunMatch[1]));
+      return;
+    }
+
+    const coverageLinesMatch = path.match(/^\/api\/coverage\/([\w-]+)\/lines$/);
+    if (coverageLinesMatch && req.method === 'GET') {
+      const file = url.searchParams.get('file');
+      if (!file) {
+        sendError(res, new PlasmaError('invalid_request', 'file is required'));
+        return;
+      }
+      sendJson(res, await getFileLineCoverage(coverageLinesMatch[1], file));
+      return;
+    }
+
+    const coverageMatch = path.match(/^\/api\/coverage\/([\w-]+)$/);
+    if (coverageMatch && req.method === 'GET') {
+      sendJson(res, getCoverageReport(coverageMatch[1]));
+      return;
+    }
+
+    if (path === '/api/crashes' && req.method === 'GET') {
+      sendJson(res, {
+        crashes: await listCrashReports({
+          bundleId: url.searchParams.get('bundleId') ?? undefined,
+          udid: url.searchParams.get('udid') ?? undefined,
+        }),
+      });
+      return;
+    }
+
+    const crashMatch = path.match(/^\/api\/crashes\/([\w-]+)$/);
+    if (crashMatch && req.method === 'GET') {
+      sendJson(res, getCrashReportById(crashMatch[1]));
+      return;
+    }
+
+    // CPU/memory sampling of apps running on a simulator
+    if (path === '/api/metrics' && req.method === 'GET') {
+      const udid = url.searchParams.get('udid');
+      if (!udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      sendJson(res, { sessions: getActiveAppMetrics(udid) });
+      return;
+    }
+
+    if (path === '/api/metrics/start' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.udid || !body.bundleId) {
+        sendError(res, new PlasmaError('invalid_request', 'udid and bundleId are required'));
+        return;
+      }
+      sendJson(res, await startAppMetrics(body.udid, body.bundleId, body.intervalMs));
+      return;
+    }
+
+    if (path === '/api/metrics/stop' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!body.udid || !body.bundleId) {
+        sendError(res, new PlasmaError('invalid_request', 'udid and bundleId are required'));
+        return;
+      }
+      sendJson(res, stopAppMetrics(body.udid, body.bundleId));
+      return;
+    }
+
+    // Stored summaries of finished sessions, newest first
+    if (path === '/api/metrics/history' && req.method === 'GET') {
+      const bundleId = url.searchParams.get('bundleId') ?? undefined;
+      const limit = Number(url.searchParams.get('limit')) || 20;
+      sendJson(res, { sessions: getAppMetricsSessions(bundleId, limit).map(toAppMetricsSummary) });
+      return;
+    }
+
+    // Server-sent events with samples for the apps sampled on a simulator
+    if (path === '/api/metrics/stream' && req.method === 'GET') {
+      const udid = url.searchParams.get('udid');
+      if (!udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      res.writeHead(200, {
+        'Content-Type': 'text/event-stream',
+        'Cache-Control': 'no-cache',
+        Connection: 'keep-alive',
+      });
+      const unsubscribe = subscribeAppMetrics(udid, (event) => res.write(`data: ${JSON.stringify(event)}\n\n`));
+      eventStreams.add(res);
+      req.on('close', () => {
+        unsubscribe();
+        eventStreams.delete(res);
+      });
+      return;
+    }
+
+    // Server-sent events for one simulator's logs, or all simulators without udid
+    if (path === '/api/simulator/logs' && req.method === 'GET') {
+      const udid = url.searchParams.get('udid');
+      res.writeHead(200, {
+        'Content-Type': 'text/event-stream',
+        'Cache-Control': 'no-cache',
+        Connection: 'keep-alive',
+      });
+
+      const send = (event: unknown) => res.write(`data: ${JSON.stringify(event)}\n\n`);
+      if (udid) {
+        getRecentLogs(udid).forEach(send);
+      }
+      const unsubscribe = subscribeLogs(udid, send);
+      eventStreams.add(res);
+      req.on('close', () => {
+        unsubscribe();
+        eventStreams.delete(res);
+      });
+      return;
+    }
+
+    // A simulator's unified log as it's written, filtered by a profile
+    if (path === '/api/simulator/device-logs' && req.method === 'GET') {
+      const udid = url.searchParams.get('udid');
+      if (!udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      const app = {
+        bundleId: url.searchParams.get('bundleId') ?? undefined,
+        process: url.searchParams.get('process') ?? undefined,
+      };
+      const profileName = url.searchParams.get('profile');
+      const profile = profileName ? getLogFilterProfile(profileName) : null;
+      const filter = profile ? createLogFilter(profile, app) : undefined;
+
+      res.writeHead(200, {
+        'Content-Type': 'text/event-stream',
+        'Cache-Control': 'no-cache',
+        Connection: 'keep-alive',
+      });
+      const abortController = new AbortController();
+      eventStreams.add(res);
+      req.on('close', () => {
+        abortController.abort();
+        eventStreams.delete(res);
+      });
+      try {
+        await streamDeviceLogs(udid, (entry) => res.write(`data: ${JSON.stringify(entry)}\n\n`), {
+          filter,
+          predicate: profile?.appOnly ? appPredicate(app) : null,
+          signal: abortController.signal,
+        });
+      } catch (err) {
+        res.write(`event: error\ndata: ${JSON.stringify(toErrorBody(err))}\n\n`);
+      }
+      res.end();
+      return;
+    }
+
+    // Named filters for the device log stream
+    if (path === '/api/logs/profiles' && req.method === 'GET') {
+      sendJson(res, { profiles: listLogFilterProfiles() });
+      return;
+    }
+
+    if (path === '/api/logs/profiles' && req.method === 'POST') {
+      sendJson(res, saveLogFilterProfile(await readBody(req)));
+      return;
+    }
+
+    if (path.startsWith('/api/logs/profiles/') && req.method === 'DELETE') {
+      deleteLogFilterProfile(decodeURIComponent(path.slice('/api/logs/profiles/'.length)));
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/simulator/screenshot' && req.method === 'GET') {
+      requireApp(req);
+      const udid = url.searchParams.get('udid');
+      if (!udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      const png = await takeScreenshot(udid);
+      res.writeHead(200, {
+        'Content-Type': 'image/png',
+        'Content-Length': png.length,
+        'Content-Disposition': `attachment; filename="Simulator-${new Date().toISOString().replace(/[:.]/g, '-')}.png"`,
+      });
+      res.end(png);
+      return;
+    }
+
+    // Small preview of the screen for device lists, refreshed every couple of seconds
+    if (path === '/api/simulator/thumbnail' && req.method === 'GET') {
+      requireApp(req);
+      const udid = url.searchParams.get('udid');
+      if (!udid) {
+        sendError(res, new PlasmaError('invalid_request', 'udid is required'));
+        return;
+      }
+      const thumbnail = await getThumbnail(udid, parseThumbnailWidth(url.searchParams.get('width')));
+      res.writeHead(200, {
+        'Content-Type': 'image/jpeg',
+        'Content-Length': thumbnail.jpeg.length,
+        'Cache-Control': `max-age=${THUMBNAIL_INTERVAL_MS / 1000}`,
+        'Last-Modified': new Date(thumbnail.capturedAt).toUTCString(),
+      });
+      res.end(thumbnail.jpeg);
+      return;
+    }
+
+    if (path === '/api/simulator/recording/start' && req.method === 'POST') {
+      const body = await readBody(req);
+      const recordingPath = startRecording(body.udid);
+      sendJson(res, { path: recordingPath });
+      return;
+    }
+
+    if (path === '/api/simulator/recording/stop' && req.method === 'POST') {
+      const body = await readBody(req);
+      const recordingPath = await stopRecording(body.udid);
+      sendJson(res, { path: recordingPath });
+      return;
+    }
+
+    // Open a file at a line in the user's editor
+    if (path === '/api/editor/open' && req.method === 'POST') {
+      requireApp(req);
+      await openInEditor(validateSourceLocation(await readBody(req)));
+      sendJson(res, { success: true });
+      return;
+    }
+
+    // Health check, with the helper tools found
+    if (path === '/api/health') {
+      sendJson(res, { status: 'ok', tools: await detectHelperTools() });
+      return;
+    }
+
+    // Shared streams: a page with the stream, and the stream itself as MJPEG
+    const shareMatch = path.match(/^\/share\/([\w-]+)(\/stream)?$/);
+    if (shareMatch && req.method === 'GET') {
+      const [, token, isStream] = shareMatch;
+      if (isStream) {
+        addShareViewer(token, res);
+        return;
+      }
+      res.writeHead(200, { 'Content-Type': 'text/html; charset=utf-8', 'Cache-Control': 'no-cache' });
+      res.end(renderSharePage(getStreamShare(token)));
+      return;
+    }
+
+    // Everything outside the API is the web frontend
+    if (!path.startsWith('/api/')) {
+      if (frontendOptions.devUrl) {
+        proxyToDevServer(req, res, frontendOptions.devUrl);
+        return;
+      }
+      if (req.method === 'GET' || req.method === 'HEAD') {
+        serveFrontendFile(req, res, path);
+        return;
+      }
+    }
+
+    // 404
+    sendError(res, new PlasmaError('not_found', 'Not found'));
+  } catch (error) {
+    console.error('[server] Request error:', error);
+    sendError(res, error);
+  }
+}
+
+/**
+ * A share link with the URL teammates on the LAN open it at
+ */
+function withShareUrl(share: StreamShare): StreamShare & { url: string } {
+  return { ...share, url: `http://${lanAddress()}:${PORT}/share/${share.token}` };
+}
+
+/**
+ * Handle WebSocket messages for streaming APIs
+ */
+async function handleWebSocketMessage(ws: WebSocket, message: string): Promise<void> {
+  try {
+    const data = JSON.parse(message);
+    const { type, payload } = data;
+
+    switch (type) {
+      case 'xcode:build:start': {
+        const { path, scheme, configuration, platform = 'ios-simulator', packageResolution = 'automatic', arch } = payload;
+        const invalid = !BUILD_PLATFORMS.includes(platform)
+          ? `platform must be one of ${BUILD_PLATFORMS.join(', ')}`
+          : !PACKAGE_RESOLUTIONS.includes(packageResolution)
+          ? `packageResolution must be one of ${PACKAGE_RESOLUTIONS.join(', ')}`
+          : arch !== undefined && !BUILD_ARCHS.includes(arch)
+          ? `arch must be one of ${BUILD_ARCHS.join(', ')}`
+          : null;
+        if (invalid) {
+          sendToClient(ws, 'xcode:build:event', {
+            type: 'error',
+            message: invalid,
+            code: 'invalid_request',
+          });
+          sendToClient(ws, 'xcode:build:end', {});
+          break;
+        }
+        // Boot the simulators the app will run on while it builds
+        if (platform === 'ios-simulator' && Array.isArray(payload.udids)) {
+          for (const udid of payload.udids.filter((udid: unknown) => typeof udid === 'string')) {
+            bootSimulator(udid).catch((err) => {
+              console.warn(`[server] Could not boot ${udid} during the build:`, err);
+            });
+          }
+        }
+        const { derivedDataPath } = getSettings();
+        const emitter = buildSchemeStream(
+          path,
+          scheme,
+          configuration,
+          derivedDataPath || undefined,
+          platform,
+          packageResolution,
+          arch
+        );
+
+        emitter.on('event', (buildEvent) => {
+          sendToClient(ws, 'xcode:build:event', buildEvent);
+        });
+
+        emitter.on('end', () => {
+          sendToClient(ws, 'xcode:build:end', {});
+        });
+        break;
+      }
+
+      case 'simulator:stream:start': {
+        // Fall back to the stream settings when the client doesn't specify them
+        const settings = getSettings();
+        const {
+          udid,
+          platform = 'ios',
+          fps = parseInt(settings.streamFps || '60'),
+          quality = parseFloat(settings.streamQuality || '0.7'),
+        } = payload;
+        // Only consumers holding the session's token may watch it
+        verifyStreamToken(udid, payload.token);
+        const client = clients.get(ws);
+        if (client) {
+          client.subscriptions.add(`stream:${udid}`);
+        }
+
+        // A client coming back to a paused session picks up where it left off
+        if (platform === 'ios' && resumeSession(udid)) {
+          pausedStreams.delete(udid);
+          publish([`stream:${udid}`], 'simulator:stream:resumed', { udid });
         }
+        await startStreamForClient(ws, udid, platform, fps, quality);
+        break;
+      }
+
+      case 'simulator:stream:stop': {
+        const { udid } = payload;
+        stopStreamForClient(udid, ws);
+        break;
+      }
+
+      // Follow one simulator's logs, or every simulator's when udid is omitted
+      case 'simulator:logs:subscribe': {
+        clients.get(ws)?.subscriptions.add(`logs:${payload?.udid || '*'}`);
+        break;
+      }
+
+      case 'simulator:logs:unsubscribe': {
+        clients.get(ws)?.subscriptions.delete(`logs:${payload?.udid || '*'}`);
+        break;
+      }
+    }
+  } catch (error) {
+    console.error('[server] WebSocket message error:', error);
+    const { error: message, code } = toErrorBody(error);
+    sendToClient(ws, 'error', { message, code });
+  }
+}
+
+/**
+ * Start streaming a simulator's frames to a client, replacing the stream
+ * already running for it
+ */
+async function startStreamForClient(
+  ws: WebSocket,
+  udid: string,
+  platform: Platform,
+  fps?: number,
+  quality?: number
+): Promise<void> {
+  // Stop existing stream if any
+  const existing = activeStreams.get(udid);
+  if (existing) {
+    existing.abort.abort();
+  }
+
+  // Start new stream; Android devices are addressed by their adb serial
+  const session = platform === 'android'
+    ? await getOrCreateAndroidSession(udid, fps, quality)
+    : { ...(await getOrCreateSession(udid, fps, quality)), backend: 'mjpeg' };
+  const abortController = new AbortController();
+  activeStreams.set(udid, { abort: abortController, platform, ws });
+  // Keep the session from being evicted as idle while this stream reads it
+  if (platform === 'ios') {
+    retainSession(udid);
+    abortController.signal.addEventListener('abort', () => releaseSession(udid), { once: true });
+  }
+
+  streamFramesToWebSocket(session.streamUrl, udid, session.backend, ws, abortController.signal);
+}
+
+/**
+ * Pause a simulator's session, stopping the WebSocket stream reading its
+ * frames. Share viewers and /api/simulator/stream readers keep their own
+ * connections, since someone else may be watching those; once they're gone
+ * too, simulator-server stops capturing. Returns false when it was already paused.
+ */
+function pauseStreaming(udid: string): boolean {
+  if (!pauseSession(udid)) {
+    return false;
+  }
+  const stream = activeStreams.get(udid);
+  if (stream) {
+    stream.abort.abort();
+    activeStreams.delete(udid);
+    pausedStreams.set(udid, stream.ws);
+  }
+  publish([`stream:${udid}`], 'simulator:stream:paused', { udid });
+  return true;
+}
+
+/**
+ * Resume a paused session, streaming to the client it was streaming to
+ * before when that client is still watching
+ */
+async function resumeStreaming(udid: string): Promise<boolean> {
+  if (!resumeSession(udid)) {
+    return false;
+  }
+  const ws = pausedStreams.get(udid);
+  pausedStreams.delete(udid);
+  publish([`stream:${udid}`], 'simulator:stream:resumed', { udid });
+  if (ws && ws.readyState === WebSocket.OPEN && clients.get(ws)?.subscriptions.has(`stream:${udid}`)) {
+    await startStreamForClient(ws, udid, 'ios');
+  }
+  return true;
+}
+
+/**
+ * Stream MJPEG frames to WebSocket client
+ */
+async function streamFramesToWebSocket(
+  streamUrl: string,
+  udid: string,
+  backend: string,
+  ws: WebSocket,
+  signal: AbortSignal
+): Promise<void> {
+  try {
+    const response = await fetch(streamUrl, { signal: signal as any });
+
+    if (!response.ok) {
+      throw new Error(`Failed to connect to simulator stream: ${response.statusText}`);
+    }
+
+    const body = response.body;
+    if (!body) {
+      throw new Error('No response body from simulator stream');
+    }
+
+    const parser = new MjpegParser();
+
+    const nodeStream = body as unknown as NodeJS.ReadableStream & { destroy?: () => void };
+
+    // Frame metrics accumulated between stats reports
+    let framesSent = 0;
+    let framesDropped = 0;
+    let bytesSent = 0;
+    let lastReport = Date.now();
+
+    const statsTimer = setInterval(() => {
+      const now = Date.now();
+      const seconds = (now - lastReport) / 1000;
+      sendToClient(ws, 'simulator:stream:stats', {
+        udid,
+        backend,
+        fps: Math.round(framesSent / seconds),
+        droppedFrames: framesDropped,
+        bytesPerSecond: Math.round(bytesSent / seconds),
+        captureReport: getFpsReport(udid),
+      });
+      framesSent = 0;
+      framesDropped = 0;
+      bytesSent = 0;
+      lastReport = now;
+    }, STATS_INTERVAL_MS);
+    signal.addEventListener('abort', () => clearInterval(statsTimer));
 
-        buffer = buffer.slice(nextBoundary);
+    nodeStream.on('data', (chunk: Buffer) => {
+      if (signal.aborted || ws.readyState !== WebSocket.OPEN) {
+        clearInterval(statsTimer);
+        nodeStream.destroy?.();
+        return;
       }
 
-      if (buffer.length > 10 * 1024 * 1024) {
-        buffer = buffer.slice(-1024 * 1024);
+      const frames = parser.push(chunk);
+      // Frames the parser had to discard count as dropped for this client
+      framesDropped += parser.droppedFrames;
+      parser.droppedFrames = 0;
+
+      for (const [index, { data, contentType }] of frames.entries()) {
+        // A client that is behind only gets the newest frame of a batch,
+        // and none at all once its socket buffer is past the limit
+        const isStale = index < frames.length - 1 && ws.bufferedAmount > 0;
+        if (isStale || ws.bufferedAmount > MAX_BUFFERED_BYTES) {
+          framesDropped++;
+          continue;
+        }
+
+        const frame = data.toString('base64');
+        // Frames are JPEG unless the part says otherwise (e.g. PNG from Android screencap)
+        sendToClient(ws, 'simulator:stream:frame', {
+          udid,
+          frame,
+          timestamp: Date.now(),
+          ...(contentType && contentType !== 'image/jpeg' ? { mimeType: contentType } : {}),
+        });
+        framesSent++;
+        bytesSent += frame.length;
       }
     });
 
@@ -427,6 +2409,7 @@ async function streamFramesToWebSocket(
     });
 
     nodeStream.on('end', () => {
+      clearInterval(statsTimer);
       activeStreams.delete(udid);
     });
   } catch (error) {
@@ -456,10 +2439,15 @@ function stopStreamForClient(udid: string, ws: WebSocket): void {
 
   // Only stop the stream if no other clients are subscribed
   if (!hasOtherSubscribers) {
+    pausedStreams.delete(udid);
     const stream = activeStreams.get(udid);
     if (stream) {
       stream.abort.abort();
       activeStreams.delete(udid);
+      // Android capture keeps the device busy, so release it once nobody is watching
+      if (stream.platform === 'android') {
+        stopAndroidSession(udid);
+      }
     }
   }
 }
@@ -485,20 +2473,104 @@ function broadcast(type: string, payload: unknown): void {
   }
 }
 
+/**
+ * Log a triggered build's progress, since nobody may be watching the UI,
+ * and send it to connected clients
+ */
+function broadcastTriggerEvent(event: TriggerBuildEvent): void {
+  console.log(`[triggers] ${event.scheme} (${event.reason}): ${event.state}${event.message ? ` - ${event.message}` : ''}`);
+  broadcast('trigger:build', event);
+}
+
+/**
+ * Send a message to clients holding any of the given subscriptions
+ */
+function publish(subscriptions: string[], type: string, payload: unknown): void {
+  const message = JSON.stringify({ type, payload });
+  for (const client of clients.values()) {
+    if (
+      client.ws.readyState === WebSocket.OPEN &&
+      subscriptions.some((subscription) => client.subscriptions.has(subscription))
+    ) {
+      client.ws.send(message);
+    }
+  }
+}
+
+/**
+ * Start sampling an app right after launch; failures only cost the metrics
+ */
+function sampleLaunchedApp(udid: string, bundleId: string): void {
+  startAppMetrics(udid, bundleId).catch((err) => {
+    console.error(`[server] Could not sample ${bundleId}:`, err);
+  });
+}
+
 /**
  * Read request body as JSON
  */
 function readBody(req: http.IncomingMessage): Promise<any> {
+  return readRawBody(req).then((raw) => {
+    const data = raw.toString('utf-8');
+    try {
+      return data ? JSON.parse(data) : {};
+    } catch (e) {
+      throw new Error('Invalid JSON body');
+    }
+  });
+}
+
+/**
+ * Settings as clients see them, leaving out secrets
+ */
+function withoutSecrets(settings: Record<string, string>): Record<string, string> {
+  const visible = { ...settings };
+  delete visible[TRIGGER_SECRET_SETTING];
+  return visible;
+}
+
+/**
+ * Settings a request saves: strings, or null to clear one. Anything else is
+ * refused rather than stored as its string form, like "[object Object]".
+ */
+function settingUpdates(body: Record<string, unknown>): Record<string, string | null> {
+  const updates: Record<string, string | null> = {};
+  for (const [key, value] of Object.entries(body)) {
+    if (value === null || value === undefined) {
+      updates[key] = null;
+    } else if (typeof value === 'string') {
+      updates[key] = value;
+    } else {
+      throw new PlasmaError('invalid_request', `${key} must be a string or null`);
+    }
+  }
+  return updates;
+}
+
+/**
+ * Check the stream token of a request sending input to a simulator, from
+ * the body or, like stream requests, the Authorization header or query
+ */
+function verifyInputToken(req: http.IncomingMessage, url: URL, body: any): void {
+  verifyStreamToken(body.udid, body.token ?? requestStreamToken(req.headers.authorization, url.searchParams));
+}
+
+/**
+ * Refuse a request that doesn't come from Plasma's own pages or tools
+ */
+function requireApp(req: http.IncomingMessage): void {
+  requireAppRequest(req, { port: PORT, devUrl: frontendOptions.devUrl });
+}
+
+/**
+ * Read a request body as is, for webhooks signed over the exact bytes
+ */
+function readRawBody(req: http.IncomingMessage): Promise<Buffer> {
   return new Promise((resolve, reject) => {
-    let data = '';
-    req.on('data', (chunk) => (data += chunk));
-    req.on('end', () => {
-      try {
-        resolve(data ? JSON.parse(data) : {});
-      } catch (e) {
-        reject(new Error('Invalid JSON body'));
-      }
-    });
+    // Decoded only once complete, so characters split between chunks stay whole
+    const chunks: Buffer[] = [];
+    req.on('data', (chunk: Buffer) => chunks.push(chunk));
+    req.on('end', () => resolve(Buffer.concat(chunks)));
     req.on('error', reject);
   });
 }
@@ -510,3 +2582,19 @@ function sendJson(res: http.ServerResponse, data: unknown, statusCode: number =
   res.writeHead(statusCode, { 'Content-Type': 'application/json' });
   res.end(JSON.stringify(data));
 }
+
+/**
+ * Send an exported report (JUnit XML, annotations) as is
+ */
+function sendReport(res: http.ServerResponse, report: ExportedReport): void {
+  res.writeHead(200, { 'Content-Type': `${report.contentType}; charset=utf-8` });
+  res.end(report.body);
+}
+
+/**
+ * Send an error as { error, code } with the status its code maps to
+ */
+function sendError(res: http.ServerResponse, error: unknown): void {
+  const plasmaError = toPlasmaError(error);
+  sendJson(res, toErrorBody(plasmaError), plasmaError.status);
+}
@@ -0,0 +1,309 @@
+import * as path from 'path';
+import { PlasmaError } from './errors';
+import { execCommand } from './exec';
+import { findAxeBinary } from './simulator';
+
+/**
+ * Accessibility hierarchy of the app in the foreground, read through AXe's
+ * describe-ui. Frames are in points, the same space AXe taps use, so tools
+ * can locate an element and act on it without screenshots.
+ */
+
+export interface AccessibilityFrame {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+export interface AccessibilityElement {
+  /** Element type, e.g. Button, StaticText, TextField */
+  type: string;
+  /** Raw accessibility role, e.g. AXButton */
+  role?: string;
+  label?: string;
+  identifier?: string;
+  value?: string;
+  title?: string;
+  hint?: string;
+  frame: AccessibilityFrame;
+  enabled: boolean;
+  /** Trait-like flags derived from the role and state, e.g. button, header, notEnabled */
+  traits: string[];
+  customActions: string[];
+  children: AccessibilityElement[];
+}
+
+// Roles that map onto a UIAccessibilityTraits name
+const TRAITS_BY_ROLE: Record<string, string> = {
+  AXButton: 'button',
+  AXLink: 'link',
+  AXImage: 'image',
+  AXStaticText: 'staticText',
+  AXHeading: 'header',
+  AXSearchField: 'searchField',
+  AXSlider: 'adjustable',
+  AXTabButton: 'button',
+  AXToggle: 'button',
+  AXKeyboardKey: 'keyboardKey',
+};
+
+// How often waitForElement looks at the screen again
+const WAIT_POLL_INTERVAL_MS = 500;
+
+/**
+ * Run an AXe subcommand against a simulator and return its stdout
+ */
+export async function runAxe(udid: string, args: string[]): Promise<string> {
+  const axePath = findAxeBinary();
+  if (!axePath) {
+    throw new PlasmaError('tool_missing', 'AXe binary not found');
+  }
+
+  const result = await execCommand(axePath, [...args, '--udid', udid], {
+    timeout: 'simctl',
+    env: { ...process.env, DYLD_FRAMEWORK_PATH: path.join(path.dirname(axePath), 'Frameworks') },
+  });
+  if (result.code !== 0) {
+    const detail = result.stderr.trim() || `exit code ${result.code}`;
+    throw new PlasmaError('input_failed', `AXe ${args[0]} failed: ${detail}`);
+  }
+  return result.stdout;
+}
+
+function text(value: unknown): string | undefined {
+  return typeof value === 'string' && value !== '' ? value : undefined;
+}
+
+function toFrame(value: unknown): AccessibilityFrame {
+  const frame = (value ?? {}) as Partial<AccessibilityFrame>;
+  return {
+    x: Number(frame.x) || 0,
+    y: Number(frame.y) || 0,
+    width: Number(frame.width) || 0,
+    height: Number(frame.height) || 0,
+  };
+}
+
+function toElement(raw: Record<string, unknown>): AccessibilityElement {
+  const role = text(raw.role);
+  const enabled = raw.enabled !== false;
+
+  const traits: string[] = [];
+  if (role && TRAITS_BY_ROLE[role]) {
+    traits.push(TRAITS_BY_ROLE[role]);
+  }
+  if (!enabled) {
+    traits.push('notEnabled');
+  }
+
+  const customActions = Array.isArray(raw.custom_actions)
+    ? raw.custom_actions.filter((action): action is string => typeof action === 'string')
+    : [];
+  const children = Array.isArray(raw.children)
+    ? raw.children.map((child) => toElement(child as Record<string, unknown>))
+    : [];
+
+  return {
+    type: text(raw.type) ?? role?.replace(/^AX/, '') ?? 'Unknown',
+    role,
+    label: text(raw.AXLabel),
+    identifier: text(raw.AXUniqueId),
+    value: text(raw.AXValue),
+    title: text(raw.title),
+    hint: text(raw.help),
+    frame: toFrame(raw.frame),
+    enabled,
+    traits,
+    customActions,
+    children,
+  };
+}
+
+/**
+ * Parse describe-ui output, which is a single element or a list of roots
+ */
+export function parseAccessibilityTree(output: string): AccessibilityElement[] {
+  let parsed: unknown;
+  try {
+    parsed = JSON.parse(output);
+  } catch {
+    throw new PlasmaError('input_failed', 'AXe returned an unreadable accessibility hierarchy');
+  }
+  const roots = Array.isArray(parsed) ? parsed : [parsed];
+  return roots
+    .filter((root): root is Record<string, unknown> => typeof root === 'object' && root !== null)
+    .map(toElement);
+}
+
+/**
+ * Accessibility hierarchy of the foreground app
+ */
+export async function describeScreen(udid: string): Promise<AccessibilityElement[]> {
+  return parseAccessibilityTree(await runAxe(udid, ['describe-ui']));
+}
+
+/**
+ * Accessibility element at a point, in points from the top-left of the screen
+ */
+export async function describePoint(udid: string, x: number, y: number): Promise<AccessibilityElement[]> {
+  return parseAccessibilityTree(await runAxe(udid, ['describe-ui', '--point', `${Math.round(x)},${Math.round(y)}`]));
+}
+
+/** How an element is identified for an action; identifier wins over label */
+export interface ElementQuery {
+  identifier?: string;
+  label?: string;
+  /** Which match to use when several elements match, in tree order */
+  index?: number;
+}
+
+export interface TapElementResult {
+  element: AccessibilityElement;
+  /** Point that was tapped, in points */
+  x: number;
+  y: number;
+  /** The same point relative to the screen size (0-1) */
+  normalized: { x: number; y: number };
+}
+
+/**
+ * Elements matching a query, depth-first in tree order, skipping ones with no area
+ */
+export function findElements(elements: AccessibilityElement[], query: ElementQuery): AccessibilityElement[] {
+  const matches: AccessibilityElement[] = [];
+  const visit = (element: AccessibilityElement) => {
+    const isMatch = query.identifier
+      ? element.identifier === query.identifier
+      : element.label === query.label;
+    if (isMatch && element.frame.width > 0 && element.frame.height > 0) {
+      matches.push(element);
+    }
+    element.children.forEach(visit);
+  };
+  elements.forEach(visit);
+  return matches;
+}
+
+/** An element query, or text matched against identifiers and then labels */
+export type ElementMatcher = ElementQuery | string;
+
+function describeQuery(query: ElementQuery): string {
+  return query.identifier ? `identifier "${query.identifier}"` : `label "${query.label}"`;
+}
+
+function describeMatcher(matcher: ElementMatcher): string {
+  return typeof matcher === 'string' ? `identifier or label "${matcher}"` : describeQuery(matcher);
+}
+
+/**
+ * Elements a matcher selects; text matches identifiers first and labels
+ * only when no identifier does
+ */
+export function matchElements(elements: AccessibilityElement[], matcher: ElementMatcher): AccessibilityElement[] {
+  if (typeof matcher !== 'string') {
+    return findElements(elements, matcher);
+  }
+  const byIdentifier = findElements(elements, { identifier: matcher });
+  return byIdentifier.length > 0 ? byIdentifier : findElements(elements, { label: matcher });
+}
+
+/**
+ * Tap the center of the element matching a query
+ */
+export async function tapElement(udid: string, query: ElementQuery): Promise<TapElementResult> {
+  if (!query.identifier && !query.label) {
+    throw new PlasmaError('invalid_request', 'identifier or label is required');
+  }
+
+  const tree = await describeScreen(udid);
+  const matches = findElements(tree, query);
+  const index = query.index ?? 0;
+  const element = matches[index];
+  if (!element) {
+    throw new PlasmaError(
+      'not_found',
+      matches.length === 0
+        ? `No element with ${describeQuery(query)} is on screen`
+        : `Only ${matches.length} element(s) with ${describeQuery(query)} are on screen`
+    );
+  }
+  if (!element.enabled) {
+    throw new PlasmaError('input_failed', `Element with ${describeQuery(query)} is not enabled`);
+  }
+
+  const x = Math.round(element.frame.x + element.frame.width / 2);
+  const y = Math.round(element.frame.y + element.frame.height / 2);
+  await runAxe(udid, ['tap', '-x', x.toString(), '-y', y.toString()]);
+
+  const screen = tree[0].frame;
+  const normalized = {
+    x: screen.width ? (x - screen.x) / screen.width : 0,
+    y: screen.height ? (y - screen.y) / screen.height : 0,
+  };
+  return { element, x, y, normalized };
+}
+
+/**
+ * The element a matcher selects, failing with not_found when it isn't on screen
+ */
+export async function assertVisible(udid: string, matcher: ElementMatcher): Promise<AccessibilityElement> {
+  const matches = matchElements(await describeScreen(udid), matcher);
+  const element = matches[typeof matcher === 'string' ? 0 : (matcher.index ?? 0)];
+  if (!element) {
+    throw new PlasmaError('not_found', `No element with ${describeMatcher(matcher)} is on screen`);
+  }
+  return element;
+}
+
+/**
+ * Poll the screen until the element a matcher selects appears, failing with
+ * a timeout error after timeoutSeconds. Flows wait on this instead of fixed sleeps.
+ */
+export async function waitForElement(
+  udid: string,
+  matcher: ElementMatcher,
+  timeoutSeconds: number,
+  signal?: AbortSignal
+): Promise<AccessibilityElement> {
+  const deadline = Date.now() + timeoutSeconds * 1000;
+  for (;;) {
+    try {
+      return await assertVisible(udid, matcher);
+    } catch (err) {
+      if (!(err instanceof PlasmaError) || err.code !== 'not_found') throw err;
+    }
+    if (Date.now() >= deadline) {
+      throw new PlasmaError('timeout', `No element with ${describeMatcher(matcher)} appeared within ${timeoutSeconds}s`);
+    }
+    const cancelled = new PlasmaError('cancelled', `Stopped waiting for ${describeMatcher(matcher)}`);
+    if (signal?.aborted) throw cancelled;
+    await new Promise<void>((resolve, reject) => {
+      const timer = setTimeout(resolve, WAIT_POLL_INTERVAL_MS);
+      signal?.addEventListener('abort', () => {
+        clearTimeout(timer);
+        reject(cancelled);
+      }, { once: true });
+    });
+  }
+}
+
+/**
+ * Deepest element whose frame contains a point that has an identifier or label,
+ * ignoring the application element itself
+ */
+export function elementAt(elements: AccessibilityElement[], x: number, y: number): AccessibilityElement | null {
+  let found: AccessibilityElement | null = null;
+  const visit = (element: AccessibilityElement) => {
+    const { frame } = element;
+    if (x < frame.x || y < frame.y || x > frame.x + frame.width || y > frame.y + frame.height) {
+      return;
+    }
+    if ((element.identifier || element.label) && element.type !== 'Application') {
+      found = element;
+    }
+    element.children.forEach(visit);
+  };
+  elements.forEach(visit);
+  return found;
+}
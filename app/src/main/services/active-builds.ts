@@ -0,0 +1,104 @@
+import { EventEmitter } from 'events';
+import { PlasmaError } from './errors';
+import type { BuildEvent, BuildPlatform } from './xcode';
+
+/**
+ * Builds running right now, whoever started them, so another client (like
+ * `plasma builds tail` in a terminal) can follow a build started from the
+ * app. Each build keeps its last events to replay to late followers.
+ */
+
+export interface ActiveBuild {
+  buildId: string;
+  projectPath: string;
+  scheme: string;
+  configuration: string;
+  platform: BuildPlatform;
+  startedAt: string;
+}
+
+interface TrackedBuild {
+  build: ActiveBuild;
+  /** The started event, then the latest events after it */
+  recentEvents: BuildEvent[];
+  followers: Set<BuildFollower>;
+}
+
+interface BuildFollower {
+  onEvent: (event: BuildEvent) => void;
+  onEnd: () => void;
+}
+
+// Events replayed to a follower joining a running build, besides started
+const REPLAYED_EVENTS = 200;
+
+const activeBuilds = new Map<string, TrackedBuild>();
+
+/**
+ * Follow a build's emitter from when it starts until it ends
+ */
+export function trackBuild(emitter: EventEmitter): void {
+  let tracked: TrackedBuild | null = null;
+
+  emitter.on('event', (event: BuildEvent) => {
+    if (event.type === 'started' && event.buildId) {
+      tracked = {
+        build: {
+          buildId: event.buildId,
+          projectPath: event.projectPath ?? '',
+          scheme: event.scheme ?? '',
+          configuration: event.configuration ?? '',
+          platform: event.platform ?? 'ios-simulator',
+          startedAt: new Date().toISOString(),
+        },
+        recentEvents: [event],
+        followers: new Set(),
+      };
+      activeBuilds.set(event.buildId, tracked);
+      return;
+    }
+    if (!tracked) {
+      return;
+    }
+    tracked.recentEvents.push(event);
+    if (tracked.recentEvents.length > REPLAYED_EVENTS + 1) {
+      tracked.recentEvents.splice(1, 1);
+    }
+    tracked.followers.forEach((follower) => follower.onEvent(event));
+  });
+
+  emitter.once('end', () => {
+    if (!tracked) {
+      return;
+    }
+    activeBuilds.delete(tracked.build.buildId);
+    tracked.followers.forEach((follower) => follower.onEnd());
+  });
+}
+
+/**
+ * Running builds, the most recently started first
+ */
+export function listActiveBuilds(): ActiveBuild[] {
+  return [...activeBuilds.values()].map(({ build }) => build).reverse();
+}
+
+/**
+ * Follow a running build, or the most recently started one without an id.
+ * Its started event and latest events are replayed first. Returns a
+ * function that stops following.
+ */
+export function followBuild(
+  buildId: string | undefined,
+  onEvent: (event: BuildEvent) => void,
+  onEnd: () => void
+): () => void {
+  const tracked = buildId ? activeBuilds.get(buildId) : [...activeBuilds.values()].pop();
+  if (!tracked) {
+    throw new PlasmaError('not_found', buildId ? `Build ${buildId} isn't running` : 'No build is running');
+  }
+  tracked.recentEvents.forEach(onEvent);
+  const follower: BuildFollower = { onEvent, onEnd };
+  tracked.followers.add(follower);
+  return () => tracked.followers.delete(follower);
+}
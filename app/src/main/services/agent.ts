@@ -0,0 +1,277 @@
+import {
+  AccessibilityElement,
+  assertVisible,
+  describeScreen,
+  ElementQuery,
+  runAxe,
+  tapElement,
+  waitForElement,
+} from './accessibility';
+import { PlasmaError, toPlasmaError } from './errors';
+import { DeviceLogEntry, readDeviceLogs, takeScreenshot } from './simulator';
+
+/**
+ * Batches of high-level UI actions for AI agents: one request runs several
+ * actions against a simulator in order and answers with a result per
+ * action, so a tool call doesn't cost a round trip per tap. Elements are
+ * addressed by accessibility identifier or label rather than coordinates,
+ * and screenshots come back inline as base64 PNGs.
+ */
+
+export type AgentAction =
+  | { action: 'screenshot' }
+  | { action: 'describe' }
+  | ({ action: 'tap' } & ElementQuery)
+  | { action: 'tap'; x: number; y: number }
+  | { action: 'type'; text: string }
+  | ({ action: 'wait_for'; timeout?: number } & ElementQuery)
+  | ({ action: 'assert_visible' } & ElementQuery)
+  | { action: 'read_logs'; process?: string; seconds?: number; limit?: number };
+
+/** When screenshots are attached to results besides the screenshot action's own */
+export type AgentScreenshots = 'none' | 'final' | 'each';
+
+export interface AgentScreenshot {
+  mimeType: 'image/png';
+  /** Base64 PNG data */
+  data: string;
+}
+
+export interface AgentActionResult {
+  index: number;
+  action: AgentAction['action'];
+  status: 'passed' | 'failed' | 'skipped';
+  durationMs: number;
+  /** Element tapped, waited for, or asserted */
+  element?: AccessibilityElement;
+  /** Accessibility hierarchy, for describe */
+  elements?: AccessibilityElement[];
+  logs?: DeviceLogEntry[];
+  screenshot?: AgentScreenshot;
+  error?: string;
+  code?: string;
+}
+
+export interface AgentRunOptions {
+  /** Skip the remaining actions after one fails (default true) */
+  stopOnError?: boolean;
+  /** Default final */
+  screenshots?: AgentScreenshots;
+}
+
+export interface AgentRunResult {
+  success: boolean;
+  durationMs: number;
+  results: AgentActionResult[];
+}
+
+const ACTIONS: AgentAction['action'][] = [
+  'screenshot',
+  'describe',
+  'tap',
+  'type',
+  'wait_for',
+  'assert_visible',
+  'read_logs',
+];
+const SCREENSHOT_MODES: AgentScreenshots[] = ['none', 'final', 'each'];
+
+const DEFAULT_WAIT_FOR_SECONDS = 10;
+// Longest a single wait_for may poll, so one action can't hold the simulator for hours
+const MAX_WAIT_FOR_SECONDS = 300;
+// Furthest back read_logs may look; longer windows make log show read for minutes
+const MAX_LOG_SECONDS = 3600;
+// Entries read_logs returns unless it asks for a limit
+const DEFAULT_LOG_LIMIT = 100;
+// Keeps a batch from tying up a simulator indefinitely
+const MAX_ACTIONS = 50;
+
+function invalid(index: number, message: string): never {
+  throw new PlasmaError('invalid_request', `Action ${index + 1}: ${message}`);
+}
+
+function isNormalized(value: unknown): boolean {
+  return typeof value === 'number' && value >= 0 && value <= 1;
+}
+
+function validateAction(value: unknown, index: number): AgentAction {
+  if (typeof value !== 'object' || value === null || Array.isArray(value)) {
+    invalid(index, 'must be an object');
+  }
+  const action = value as Record<string, any>;
+  if (!ACTIONS.includes(action.action)) {
+    invalid(index, `action must be one of ${ACTIONS.join(', ')}`);
+  }
+
+  const hasQuery = typeof action.identifier === 'string' || typeof action.label === 'string';
+  if (action.index !== undefined && (!Number.isInteger(action.index) || action.index < 0)) {
+    invalid(index, 'index must be a non-negative integer');
+  }
+  switch (action.action) {
+    case 'tap':
+      if (hasQuery === (action.x !== undefined || action.y !== undefined)) {
+        invalid(index, 'tap needs either an identifier or label, or x and y');
+      }
+      if (!hasQuery && !(isNormalized(action.x) && isNormalized(action.y))) {
+        invalid(index, 'x and y must be between 0 and 1');
+      }
+      break;
+    case 'type':
+      if (typeof action.text !== 'string' || !action.text) invalid(index, 'type needs text');
+      break;
+    case 'wait_for':
+      if (!hasQuery) invalid(index, 'wait_for needs an identifier or label');
+      if (action.timeout !== undefined && (typeof action.timeout !== 'number' || action.timeout < 0)) {
+        invalid(index, 'timeout must be a number of seconds');
+      }
+      if (action.timeout > MAX_WAIT_FOR_SECONDS) {
+        invalid(index, `timeout can be at most ${MAX_WAIT_FOR_SECONDS} seconds`);
+      }
+      break;
+    case 'assert_visible':
+      if (!hasQuery) invalid(index, 'assert_visible needs an identifier or label');
+      break;
+    case 'read_logs':
+      if (action.process !== undefined && typeof action.process !== 'string') invalid(index, 'process must be a string');
+      for (const option of ['seconds', 'limit']) {
+        if (action[option] !== undefined && (!Number.isInteger(action[option]) || action[option] < 1)) {
+          invalid(index, `${option} must be a positive integer`);
+        }
+      }
+      if (action.seconds > MAX_LOG_SECONDS) {
+        invalid(index, `seconds can be at most ${MAX_LOG_SECONDS}`);
+      }
+      break;
+  }
+  return action as AgentAction;
+}
+
+/**
+ * Validate a batch sent by a client
+ */
+export function parseAgentActions(input: unknown): AgentAction[] {
+  if (!Array.isArray(input) || input.length === 0) {
+    throw new PlasmaError('invalid_request', 'actions must be a non-empty list');
+  }
+  if (input.length > MAX_ACTIONS) {
+    throw new PlasmaError('invalid_request', `A batch can have at most ${MAX_ACTIONS} actions`);
+  }
+  return input.map(validateAction);
+}
+
+export function parseScreenshotMode(value: unknown): AgentScreenshots {
+  if (value === undefined) return 'final';
+  if (!SCREENSHOT_MODES.includes(value as AgentScreenshots)) {
+    throw new PlasmaError('invalid_request', `screenshots must be ${SCREENSHOT_MODES.join(', ')}`);
+  }
+  return value as AgentScreenshots;
+}
+
+function queryOf(action: ElementQuery): ElementQuery {
+  return { identifier: action.identifier, label: action.label, index: action.index };
+}
+
+async function screenshot(udid: string): Promise<AgentScreenshot> {
+  return { mimeType: 'image/png', data: (await takeScreenshot(udid)).toString('base64') };
+}
+
+async function runAction(
+  udid: string,
+  action: AgentAction,
+  signal?: AbortSignal
+): Promise<Partial<AgentActionResult>> {
+  switch (action.action) {
+    case 'screenshot':
+      return { screenshot: await screenshot(udid) };
+    case 'describe':
+      return { elements: await describeScreen(udid) };
+    case 'tap': {
+      if ('x' in action) {
+        const frame = (await describeScreen(udid))[0]?.frame;
+        if (!frame || frame.width === 0 || frame.height === 0) {
+          throw new PlasmaError('input_failed', 'Could not determine the screen size');
+        }
+        const x = Math.round(frame.x + action.x * frame.width).toString();
+        const y = Math.round(frame.y + action.y * frame.height).toString();
+        await runAxe(udid, ['tap', '-x', x, '-y', y]);
+        return {};
+      }
+      return { element: (await tapElement(udid, queryOf(action))).element };
+    }
+    case 'type':
+      await runAxe(udid, ['type', action.text]);
+      return {};
+    case 'wait_for':
+      return {
+        element: await waitForElement(udid, queryOf(action), action.timeout ?? DEFAULT_WAIT_FOR_SECONDS, signal),
+      };
+    case 'assert_visible':
+      return { element: await assertVisible(udid, queryOf(action)) };
+    case 'read_logs':
+      return {
+        logs: await readDeviceLogs(udid, {
+          process: action.process,
+          seconds: action.seconds,
+          limit: action.limit ?? DEFAULT_LOG_LIMIT,
+        }),
+      };
+  }
+}
+
+/**
+ * Run a batch of actions against a simulator in order. Actions after a
+ * failure are skipped unless stopOnError is false.
+ */
+export async function runAgentActions(
+  udid: string,
+  actions: AgentAction[],
+  options: AgentRunOptions = {},
+  signal?: AbortSignal
+): Promise<AgentRunResult> {
+  const startedAt = Date.now();
+  const stopOnError = options.stopOnError ?? true;
+  const screenshots = options.screenshots ?? 'final';
+  const results: AgentActionResult[] = [];
+  let failed = false;
+
+  for (const [index, action] of actions.entries()) {
+    if (failed && stopOnError) {
+      results.push({ index, action: action.action, status: 'skipped', durationMs: 0 });
+      continue;
+    }
+    const actionStartedAt = Date.now();
+    try {
+      if (signal?.aborted) {
+        throw new PlasmaError('cancelled', 'Agent actions were cancelled');
+      }
+      const data = await runAction(udid, action, signal);
+      if (!data.screenshot && screenshots === 'each') {
+        data.screenshot = await screenshot(udid);
+      }
+      results.push({ index, action: action.action, status: 'passed', durationMs: Date.now() - actionStartedAt, ...data });
+    } catch (err) {
+      const error = toPlasmaError(err);
+      failed = true;
+      results.push({
+        index,
+        action: action.action,
+        status: 'failed',
+        durationMs: Date.now() - actionStartedAt,
+        error: error.message,
+        code: error.code,
+      });
+    }
+  }
+
+  // The screen as the batch left it, so the agent can plan its next step
+  const last = results.filter((result) => result.status !== 'skipped').pop();
+  if (screenshots !== 'none' && last && !last.screenshot && !signal?.aborted) {
+    try {
+      last.screenshot = await screenshot(udid);
+    } catch {
+      // The results matter more than the picture
+    }
+  }
+
+  return { success: !failed, durationMs: Date.now() - startedAt, results };
+}
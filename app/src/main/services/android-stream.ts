@@ -0,0 +1,295 @@
+import { spawn, ChildProcess } from 'child_process';
+import * as fs from 'fs';
+import * as http from 'http';
+import { AddressInfo } from 'net';
+import { registerProcess } from './process-manager';
+import { findAdbBinary } from './android';
+import { PlasmaError } from './errors';
+import { encodeMjpegPart, MJPEG_BOUNDARY } from './mjpeg';
+import { revokeStreamToken } from './stream-auth';
+
+export type AndroidCaptureBackend = 'screenrecord' | 'screencap';
+
+interface AndroidSession {
+  serial: string;
+  streamUrl: string;
+  backend: AndroidCaptureBackend;
+  stop: () => void;
+}
+
+// Unsent bytes a stream reader may fall behind by before frames are skipped
+const MAX_PENDING_BYTES = 2 * 1024 * 1024;
+
+const JPEG_SOI = Buffer.from([0xff, 0xd8]);
+const JPEG_EOI = Buffer.from([0xff, 0xd9]);
+
+const sessionCache = new Map<string, AndroidSession>();
+
+/**
+ * Find ffmpeg, which is needed to turn screenrecord's H.264 output into JPEG frames
+ */
+export function findFfmpegBinary(): string | null {
+  // Apps launched from Finder don't inherit the Homebrew PATH
+  for (const candidate of ['/opt/homebrew/bin/ffmpeg', '/usr/local/bin/ffmpeg']) {
+    if (fs.existsSync(candidate)) {
+      return candidate;
+    }
+  }
+
+  const { execSync } = require('child_process');
+  try {
+    const result = execSync('which ffmpeg', { encoding: 'utf-8' }).trim();
+    if (result && fs.existsSync(result)) {
+      return result;
+    }
+  } catch {
+    // Not in PATH
+  }
+
+  return null;
+}
+
+/**
+ * Split a concatenated JPEG stream into individual frames
+ */
+export function extractJpegFrames(buffer: Buffer): { frames: Buffer[]; rest: Buffer } {
+  const frames: Buffer[] = [];
+  let offset = 0;
+
+  for (;;) {
+    const start = buffer.indexOf(JPEG_SOI, offset);
+    if (start === -1) {
+      return { frames, rest: Buffer.alloc(0) };
+    }
+    const end = buffer.indexOf(JPEG_EOI, start + 2);
+    if (end === -1) {
+      return { frames, rest: buffer.subarray(start) };
+    }
+    frames.push(buffer.subarray(start, end + 2));
+    offset = end + 2;
+  }
+}
+
+/**
+ * Serve frames as multipart MJPEG, the same format simulator-server produces
+ */
+function startMjpegServer(): Promise<{
+  url: string;
+  push: (frame: Buffer, contentType: string) => void;
+  close: () => void;
+}> {
+  const clients = new Set<http.ServerResponse>();
+
+  const server = http.createServer((req, res) => {
+    res.writeHead(200, {
+      'Content-Type': `multipart/x-mixed-replace; boundary=${MJPEG_BOUNDARY.slice(2)}`,
+      'Cache-Control': 'no-cache',
+      Connection: 'close',
+    });
+    clients.add(res);
+    req.on('close', () => clients.delete(res));
+  });
+
+  const push = (frame: Buffer, contentType: string) => {
+    const part = encodeMjpegPart(frame, contentType);
+    for (const res of clients) {
+      // Skip frames for a reader that hasn't drained the previous ones
+      if (res.writableLength > MAX_PENDING_BYTES) continue;
+      res.write(part);
+    }
+  };
+
+  const close = () => {
+    for (const res of clients) {
+      res.end();
+    }
+    clients.clear();
+    server.close();
+  };
+
+  return new Promise((resolve) => {
+    server.listen(0, '127.0.0.1', () => {
+      const { port } = server.address() as AddressInfo;
+      resolve({ url: `http://127.0.0.1:${port}/stream.mjpeg`, push, close });
+    });
+  });
+}
+
+/**
+ * Capture with screenrecord (H.264) and transcode to MJPEG with ffmpeg
+ */
+function captureWithScreenrecord(
+  adb: string,
+  ffmpeg: string,
+  serial: string,
+  fps: number,
+  quality: number,
+  onFrame: (frame: Buffer) => void
+): () => void {
+  let stopped = false;
+  let recorder: ChildProcess | null = null;
+  let transcoder: ChildProcess | null = null;
+  // ffmpeg's -q:v goes from 2 (best) to 31 (worst)
+  const jpegQuality = Math.round(31 - Math.max(0, Math.min(1, quality)) * 29);
+
+  const start = () => {
+    recorder = spawn(adb, [
+      '-s', serial, 'exec-out', 'screenrecord',
+      '--output-format=h264',
+      '--bit-rate=8000000',
+      '-',
+    ]);
+    registerProcess(recorder);
+
+    transcoder = spawn(ffmpeg, [
+      '-loglevel', 'error',
+      '-fflags', 'nobuffer',
+      '-f', 'h264',
+      '-i', 'pipe:0',
+      '-r', fps.toString(),
+      '-q:v', jpegQuality.toString(),
+      '-f', 'mjpeg',
+      'pipe:1',
+    ]);
+    registerProcess(transcoder);
+
+    recorder.stdout?.pipe(transcoder.stdin!);
+    transcoder.stdin?.on('error', () => {
+      // The recorder restarting closes the pipe; nothing to report
+    });
+
+    let buffer = Buffer.alloc(0);
+    transcoder.stdout?.on('data', (chunk: Buffer) => {
+      buffer = Buffer.concat([buffer, chunk]);
+      const { frames, rest } = extractJpegFrames(buffer);
+      buffer = rest;
+      for (const frame of frames) {
+        onFrame(frame);
+      }
+    });
+
+    // screenrecord stops after its time limit (3 minutes); start a new recording
+    recorder.on('close', () => {
+      transcoder?.stdin?.end();
+      if (!stopped) {
+        setTimeout(start, 100);
+      }
+    });
+  };
+
+  start();
+
+  return () => {
+    stopped = true;
+    recorder?.kill();
+    transcoder?.kill();
+  };
+}
+
+/**
+ * Capture by polling screencap for PNG screenshots; slower but needs no extra tools
+ */
+function captureWithScreencap(
+  adb: string,
+  serial: string,
+  fps: number,
+  onFrame: (frame: Buffer) => void
+): () => void {
+  let stopped = false;
+  const interval = 1000 / fps;
+
+  const captureNext = () => {
+    if (stopped) return;
+    const startedAt = Date.now();
+    const proc = spawn(adb, ['-s', serial, 'exec-out', 'screencap', '-p']);
+    registerProcess(proc);
+
+    const chunks: Buffer[] = [];
+    proc.stdout.on('data', (chunk: Buffer) => chunks.push(chunk));
+    proc.on('close', (code) => {
+      if (code === 0 && chunks.length > 0) {
+        onFrame(Buffer.concat(chunks));
+      }
+      setTimeout(captureNext, Math.max(0, interval - (Date.now() - startedAt)));
+    });
+  };
+
+  captureNext();
+
+  return () => {
+    stopped = true;
+  };
+}
+
+/**
+ * Get or create a streaming session for an Android device or emulator
+ */
+export async function getOrCreateAndroidSession(
+  serial: string,
+  fps: number = 60,
+  quality: number = 0.7
+): Promise<AndroidSession> {
+  const existing = sessionCache.get(serial);
+  if (existing) {
+    return existing;
+  }
+
+  const adb = findAdbBinary();
+  if (!adb) {
+    throw new PlasmaError('tool_missing', 'adb not found. Install the Android SDK platform tools or set ANDROID_HOME.');
+  }
+
+  const mjpeg = await startMjpegServer();
+  const ffmpeg = findFfmpegBinary();
+
+  let backend: AndroidCaptureBackend;
+  let stopCapture: () => void;
+  if (ffmpeg) {
+    backend = 'screenrecord';
+    stopCapture = captureWithScreenrecord(adb, ffmpeg, serial, fps, quality, (frame) =>
+      mjpeg.push(frame, 'image/jpeg')
+    );
+  } else {
+    console.log('[android-stream] ffmpeg not found, falling back to screencap polling');
+    backend = 'screencap';
+    stopCapture = captureWithScreencap(adb, serial, fps, (frame) => mjpeg.push(frame, 'image/png'));
+  }
+
+  const session: AndroidSession = {
+    serial,
+    streamUrl: mjpeg.url,
+    backend,
+    stop: () => {
+      stopCapture();
+      mjpeg.close();
+      sessionCache.delete(serial);
+      revokeStreamToken(serial);
+    },
+  };
+
+  sessionCache.set(serial, session);
+  return session;
+}
+
+/**
+ * Whether a device has a session streaming it
+ */
+export function hasAndroidSession(serial: string): boolean {
+  return sessionCache.has(serial);
+}
+
+/**
+ * Stop the streaming session for a device, if any
+ */
+export function stopAndroidSession(serial: string): void {
+  sessionCache.get(serial)?.stop();
+}
+
+/**
+ * Stop the streaming sessions of all devices
+ */
+export function stopAllAndroidSessions(): void {
+  for (const session of [...sessionCache.values()]) {
+    session.stop();
+  }
+}
@@ -0,0 +1,213 @@
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { PlasmaError } from './errors';
+import { runCommand, RunCommandOptions } from './exec';
+
+export interface AndroidDevice {
+  serial: string;
+  state: string;
+  model: string | null;
+  isEmulator: boolean;
+}
+
+export interface ApkInfo {
+  applicationId: string;
+  launchableActivity: string | null;
+}
+
+/**
+ * Android SDK root from the environment or the default Android Studio location
+ */
+function getAndroidSdkRoot(): string | null {
+  const candidates = [
+    process.env.ANDROID_HOME,
+    process.env.ANDROID_SDK_ROOT,
+    path.join(os.homedir(), 'Library', 'Android', 'sdk'),
+  ];
+
+  for (const candidate of candidates) {
+    if (candidate && fs.existsSync(candidate)) {
+      return candidate;
+    }
+  }
+  return null;
+}
+
+/**
+ * Find the adb binary
+ */
+export function findAdbBinary(): string | null {
+  const sdkRoot = getAndroidSdkRoot();
+  if (sdkRoot) {
+    const adb = path.join(sdkRoot, 'platform-tools', 'adb');
+    if (fs.existsSync(adb)) {
+      return adb;
+    }
+  }
+
+  const { execSync } = require('child_process');
+  try {
+    const result = execSync('which adb', { encoding: 'utf-8' }).trim();
+    if (result && fs.existsSync(result)) {
+      return result;
+    }
+  } catch {
+    // Not in PATH
+  }
+
+  return null;
+}
+
+/**
+ * Find aapt from the newest installed build-tools
+ */
+function findAaptBinary(): string | null {
+  const sdkRoot = getAndroidSdkRoot();
+  if (!sdkRoot) {
+    return null;
+  }
+
+  const buildToolsDir = path.join(sdkRoot, 'build-tools');
+  let versions: string[];
+  try {
+    versions = fs.readdirSync(buildToolsDir);
+  } catch {
+    return null;
+  }
+
+  versions.sort((a, b) => b.localeCompare(a, undefined, { numeric: true }));
+  for (const version of versions) {
+    const aapt = path.join(buildToolsDir, version, 'aapt');
+    if (fs.existsSync(aapt)) {
+      return aapt;
+    }
+  }
+  return null;
+}
+
+function requireAdb(): string {
+  const adb = findAdbBinary();
+  if (!adb) {
+    throw new PlasmaError('tool_missing', 'adb not found. Install the Android SDK platform tools or set ANDROID_HOME.');
+  }
+  return adb;
+}
+
+const ADB_COMMAND: RunCommandOptions = { timeout: 'tool', errorCode: 'android_failed' };
+
+// adb shell joins its arguments into a command for the device's shell, so
+// names passed through it can't carry anything the shell would interpret
+const APPLICATION_ID_PATTERN = /^[A-Za-z][A-Za-z0-9_]*(\.[A-Za-z][A-Za-z0-9_]*)+$/;
+const ACTIVITY_PATTERN = /^[A-Za-z0-9_.]+$/;
+
+function validateApplicationId(applicationId: string): void {
+  if (!APPLICATION_ID_PATTERN.test(applicationId)) {
+    throw new PlasmaError('invalid_request', `Invalid application id: ${applicationId}`);
+  }
+}
+
+/**
+ * List connected Android devices and running emulators
+ */
+export async function listAndroidDevices(): Promise<AndroidDevice[]> {
+  const output = await runCommand(requireAdb(), ['devices', '-l'], ADB_COMMAND);
+  const devices: AndroidDevice[] = [];
+
+  // Format: "<serial>  <state> product:x model:Pixel_7 device:y transport_id:1"
+  for (const line of output.split('\n').slice(1)) {
+    const trimmed = line.trim();
+    if (!trimmed || trimmed.startsWith('*')) continue;
+
+    const [serial, state, ...details] = trimmed.split(/\s+/);
+    const model = details.find((d) => d.startsWith('model:'))?.slice('model:'.length) ?? null;
+    devices.push({
+      serial,
+      state,
+      model: model ? model.replace(/_/g, ' ') : null,
+      isEmulator: serial.startsWith('emulator-'),
+    });
+  }
+
+  return devices;
+}
+
+/**
+ * Parse `aapt dump badging` output
+ */
+export function parseBadging(output: string): ApkInfo | null {
+  const packageMatch = output.match(/^package: name='([^']+)'/m);
+  if (!packageMatch) {
+    return null;
+  }
+  const activityMatch = output.match(/^launchable-activity: name='([^']+)'/m);
+  return {
+    applicationId: packageMatch[1],
+    launchableActivity: activityMatch ? activityMatch[1] : null,
+  };
+}
+
+/**
+ * Read the application id and launcher activity from an APK's manifest
+ */
+export async function extractApkInfo(apkPath: string): Promise<ApkInfo> {
+  const aapt = findAaptBinary();
+  if (!aapt) {
+    throw new PlasmaError('tool_missing', 'aapt not found. Install the Android SDK build tools.');
+  }
+
+  const output = await runCommand(aapt, ['dump', 'badging', apkPath], ADB_COMMAND);
+  const info = parseBadging(output);
+  if (!info) {
+    throw new PlasmaError('android_failed', `Could not read the application id from ${apkPath}`);
+  }
+  return info;
+}
+
+/**
+ * Install an APK on a device and launch it
+ */
+export async function installAndLaunchApk(
+  serial: string,
+  apkPath: string,
+  applicationId?: string
+): Promise<string> {
+  const adb = requireAdb();
+
+  console.log(`Installing APK at ${apkPath} on ${serial}...`);
+  await runCommand(adb, ['-s', serial, 'install', '-r', apkPath], ADB_COMMAND);
+
+  let activity: string | null = null;
+  if (!applicationId) {
+    const info = await extractApkInfo(apkPath);
+    applicationId = info.applicationId;
+    activity = info.launchableActivity;
+  }
+  validateApplicationId(applicationId);
+  if (activity && !ACTIVITY_PATTERN.test(activity)) {
+    throw new PlasmaError('android_failed', `Invalid launchable activity in ${apkPath}: ${activity}`);
+  }
+
+  console.log(`Launching ${applicationId} on ${serial}...`);
+  if (activity) {
+    await runCommand(adb, ['-s', serial, 'shell', 'am', 'start', '-n', `${applicationId}/${activity}`], ADB_COMMAND);
+  } else {
+    // Resolve the launcher activity on the device when the manifest wasn't read
+    await runCommand(adb, [
+      '-s', serial, 'shell', 'monkey',
+      '-p', applicationId,
+      '-c', 'android.intent.category.LAUNCHER',
+      '1',
+    ], ADB_COMMAND);
+  }
+
+  return `App ${applicationId} launched successfully`;
+}
+
+/**
+ * Stop a running app
+ */
+export async function terminateApp(serial: string, applicationId: string): Promise<void> {
+  validateApplicationId(applicationId);
+  await runCommand(requireAdb(), ['-s', serial, 'shell', 'am', 'force-stop', applicationId], ADB_COMMAND);
+}
@@ -0,0 +1,286 @@
+import * as crypto from 'crypto';
+import { EventEmitter } from 'events';
+import { AppMetricsSessionRecord, saveAppMetricsSession } from './database';
+import { PlasmaError } from './errors';
+import { runCommand, SIMCTL_COMMAND, TOOL_COMMAND } from './exec';
+
+/**
+ * CPU, memory, and thread sampling for apps launched on a simulator.
+ * Simulator apps are ordinary host processes, so once the pid is resolved
+ * through the simulator's launchd they can be sampled with ps. Samples are
+ * streamed to subscribers and each session's summary is stored so runs can
+ * be compared to spot performance regressions.
+ */
+
+export interface AppMetricsSample {
+  timestamp: string;
+  cpuPercent: number;
+  rssKb: number;
+  threads: number;
+}
+
+export interface AppMetricsSummary {
+  id: string;
+  udid: string;
+  bundleId: string;
+  pid: number;
+  startedAt: string;
+  /** Null while the session is still sampling */
+  endedAt: string | null;
+  samples: number;
+  avgCpuPercent: number;
+  peakCpuPercent: number;
+  avgRssKb: number;
+  peakRssKb: number;
+  peakThreads: number;
+}
+
+export type AppMetricsEvent =
+  | { type: 'sample'; sessionId: string; udid: string; bundleId: string; sample: AppMetricsSample }
+  | { type: 'ended'; sessionId: string; udid: string; bundleId: string; summary: AppMetricsSummary };
+
+interface MetricsSession {
+  summary: AppMetricsSummary;
+  cpuTotal: number;
+  rssTotal: number;
+  timer: NodeJS.Timeout;
+}
+
+const DEFAULT_INTERVAL_MS = 1000;
+const MIN_INTERVAL_MS = 250;
+// The app can take a moment to register with launchd after simctl launch returns
+const PID_LOOKUP_ATTEMPTS = 10;
+const PID_LOOKUP_DELAY_MS = 500;
+
+// Samples are published per simulator ("metrics:<udid>")
+const metricsEmitter = new EventEmitter();
+metricsEmitter.setMaxListeners(0);
+
+const sessions = new Map<string, MetricsSession>();
+
+function sessionKey(udid: string, bundleId: string): string {
+  return `${udid}:${bundleId}`;
+}
+
+/**
+ * Pid of a running app from the simulator's launchctl list, where apps are
+ * labeled UIKitApplication:<bundle id>[<suffix>]
+ */
+export function parseLaunchctlPid(output: string, bundleId: string): number | null {
+  const label = `UIKitApplication:${bundleId}[`;
+  for (const line of output.split('\n')) {
+    const [pid, , name] = line.trim().split(/\s+/);
+    if (name?.startsWith(label) && /^\d+$/.test(pid)) {
+      return Number(pid);
+    }
+  }
+  return null;
+}
+
+/**
+ * Resolve the host pid of an app running on a simulator, waiting briefly for a fresh launch
+ */
+export async function resolveAppPid(udid: string, bundleId: string): Promise<number> {
+  for (let attempt = 0; attempt < PID_LOOKUP_ATTEMPTS; attempt++) {
+    const launchctl = await runCommand('xcrun', ['simctl', 'spawn', udid, 'launchctl', 'list'], SIMCTL_COMMAND);
+    const pid = parseLaunchctlPid(launchctl, bundleId);
+    if (pid !== null) {
+      return pid;
+    }
+    await new Promise((resolve) => setTimeout(resolve, PID_LOOKUP_DELAY_MS));
+  }
+  throw new PlasmaError('not_found', `${bundleId} is not running on simulator ${udid}`);
+}
+
+/**
+ * Sample a process, or return null once it has exited
+ */
+async function sampleProcess(pid: number): Promise<AppMetricsSample | null> {
+  let usage: string;
+  let threads: string;
+  try {
+    usage = await runCommand('ps', ['-o', '%cpu=,rss=', '-p', String(pid)], TOOL_COMMAND);
+    // -M prints a header and one line per thread
+    threads = await runCommand('ps', ['-M', '-p', String(pid)], TOOL_COMMAND);
+  } catch {
+    return null;
+  }
+
+  const [cpu, rss] = usage.trim().split(/\s+/).map(Number);
+  if (Number.isNaN(cpu) || Number.isNaN(rss)) {
+    return null;
+  }
+  return {
+    timestamp: new Date().toISOString(),
+    cpuPercent: cpu,
+    rssKb: rss,
+    threads: Math.max(0, threads.trim().split('\n').length - 1),
+  };
+}
+
+function toRecord(summary: AppMetricsSummary): AppMetricsSessionRecord {
+  return {
+    id: summary.id,
+    udid: summary.udid,
+    bundle_id: summary.bundleId,
+    pid: summary.pid,
+    started_at: summary.startedAt,
+    ended_at: summary.endedAt ?? new Date().toISOString(),
+    samples: summary.samples,
+    avg_cpu_percent: summary.avgCpuPercent,
+    peak_cpu_percent: summary.peakCpuPercent,
+    avg_rss_kb: summary.avgRssKb,
+    peak_rss_kb: summary.peakRssKb,
+    peak_threads: summary.peakThreads,
+  };
+}
+
+export function toAppMetricsSummary(record: AppMetricsSessionRecord): AppMetricsSummary {
+  return {
+    id: record.id,
+    udid: record.udid,
+    bundleId: record.bundle_id,
+    pid: record.pid,
+    startedAt: record.started_at,
+    endedAt: record.ended_at,
+    samples: record.samples,
+    avgCpuPercent: record.avg_cpu_percent,
+    peakCpuPercent: record.peak_cpu_percent,
+    avgRssKb: record.avg_rss_kb,
+    peakRssKb: record.peak_rss_kb,
+    peakThreads: record.peak_threads,
+  };
+}
+
+function endSession(session: MetricsSession): AppMetricsSummary {
+  const { summary } = session;
+  clearInterval(session.timer);
+  sessions.delete(sessionKey(summary.udid, summary.bundleId));
+  summary.endedAt = new Date().toISOString();
+
+  // Sessions that never got a sample have nothing worth comparing
+  if (summary.samples > 0) {
+    saveAppMetricsSession(toRecord(summary));
+  }
+  metricsEmitter.emit(`metrics:${summary.udid}`, {
+    type: 'ended',
+    sessionId: summary.id,
+    udid: summary.udid,
+    bundleId: summary.bundleId,
+    summary,
+  } satisfies AppMetricsEvent);
+  return summary;
+}
+
+/**
+ * Start sampling an app that is running on a simulator. A session already
+ * sampling the same app (e.g. before a relaunch) is ended first.
+ */
+export async function startAppMetrics(
+  udid: string,
+  bundleId: string,
+  intervalMs: number = DEFAULT_INTERVAL_MS
+): Promise<AppMetricsSummary> {
+  if (!Number.isFinite(intervalMs) || intervalMs < MIN_INTERVAL_MS) {
+    throw new PlasmaError('invalid_request', `intervalMs must be at least ${MIN_INTERVAL_MS}`);
+  }
+
+  const existing = sessions.get(sessionKey(udid, bundleId));
+  if (existing) {
+    endSession(existing);
+  }
+
+  const pid = await resolveAppPid(udid, bundleId);
+  const summary: AppMetricsSummary = {
+    id: crypto.randomUUID(),
+    udid,
+    bundleId,
+    pid,
+    startedAt: new Date().toISOString(),
+    endedAt: null,
+    samples: 0,
+    avgCpuPercent: 0,
+    peakCpuPercent: 0,
+    avgRssKb: 0,
+    peakRssKb: 0,
+    peakThreads: 0,
+  };
+
+  let sampling = false;
+  const session: MetricsSession = {
+    summary,
+    cpuTotal: 0,
+    rssTotal: 0,
+    timer: setInterval(async () => {
+      // Skip a tick rather than overlap when ps is slow
+      if (sampling) return;
+      sampling = true;
+      try {
+        const sample = await sampleProcess(pid);
+        if (sessions.get(sessionKey(udid, bundleId)) !== session) return;
+        if (!sample) {
+          console.log(`[app-metrics] ${bundleId} (pid ${pid}) exited`);
+          endSession(session);
+          return;
+        }
+
+        session.cpuTotal += sample.cpuPercent;
+        session.rssTotal += sample.rssKb;
+        summary.samples++;
+        summary.avgCpuPercent = Math.round((session.cpuTotal / summary.samples) * 10) / 10;
+        summary.avgRssKb = Math.round(session.rssTotal / summary.samples);
+        summary.peakCpuPercent = Math.max(summary.peakCpuPercent, sample.cpuPercent);
+        summary.peakRssKb = Math.max(summary.peakRssKb, sample.rssKb);
+        summary.peakThreads = Math.max(summary.peakThreads, sample.threads);
+
+        metricsEmitter.emit(`metrics:${udid}`, {
+          type: 'sample',
+          sessionId: summary.id,
+          udid,
+          bundleId,
+          sample,
+        } satisfies AppMetricsEvent);
+      } finally {
+        sampling = false;
+      }
+    }, intervalMs),
+  };
+  sessions.set(sessionKey(udid, bundleId), session);
+  console.log(`[app-metrics] Sampling ${bundleId} (pid ${pid}) on ${udid} every ${intervalMs}ms`);
+  return summary;
+}
+
+/**
+ * Stop sampling an app and return the stored summary
+ */
+export function stopAppMetrics(udid: string, bundleId: string): AppMetricsSummary {
+  const session = sessions.get(sessionKey(udid, bundleId));
+  if (!session) {
+    throw new PlasmaError('session_not_found', `${bundleId} is not being sampled on simulator ${udid}`);
+  }
+  return endSession(session);
+}
+
+/**
+ * End every sampling session, storing what was sampled so far
+ */
+export function stopAllAppMetrics(): void {
+  for (const session of [...sessions.values()]) {
+    endSession(session);
+  }
+}
+
+/**
+ * Summaries of the sessions currently sampling apps on a simulator
+ */
+export function getActiveAppMetrics(udid: string): AppMetricsSummary[] {
+  return [...sessions.values()].map((session) => session.summary).filter((summary) => summary.udid === udid);
+}
+
+/**
+ * Receive samples and session ends for apps on a simulator
+ */
+export function subscribeAppMetrics(udid: string, listener: (event: AppMetricsEvent) => void): () => void {
+  metricsEmitter.on(`metrics:${udid}`, listener);
+  return () => metricsEmitter.off(`metrics:${udid}`, listener);
+}
@@ -0,0 +1,52 @@
+import type * as http from 'http';
+import { describe, it, expect } from 'vitest';
+import { isAppRequest, requireAppRequest } from './app-requests';
+
+const ORIGINS = { port: 4000, devUrl: 'http://localhost:5180' };
+
+function request(remoteAddress: string, headers: http.IncomingHttpHeaders): http.IncomingMessage {
+  return { socket: { remoteAddress }, headers } as unknown as http.IncomingMessage;
+}
+
+describe('isAppRequest', () => {
+  it('accepts loopback requests to localhost without an Origin', () => {
+    expect(isAppRequest(request('127.0.0.1', { host: 'localhost:4000' }), ORIGINS)).toBe(true);
+    expect(isAppRequest(request('::1', { host: '[::1]:4000' }), ORIGINS)).toBe(true);
+    expect(isAppRequest(request('::ffff:127.0.0.1', { host: '127.0.0.1:4000' }), ORIGINS)).toBe(true);
+  });
+
+  it('rejects requests from other machines', () => {
+    expect(isAppRequest(request('192.168.1.20', { host: 'localhost:4000' }), ORIGINS)).toBe(false);
+    expect(isAppRequest(request('', { host: 'localhost:4000' }), ORIGINS)).toBe(false);
+  });
+
+  it('rejects names rebound to 127.0.0.1 and missing hosts', () => {
+    expect(isAppRequest(request('127.0.0.1', { host: 'attacker.example:4000' }), ORIGINS)).toBe(false);
+    expect(isAppRequest(request('127.0.0.1', {}), ORIGINS)).toBe(false);
+  });
+
+  it("accepts Plasma's own origins", () => {
+    for (const origin of [
+      'file://',
+      'http://localhost:4000',
+      'http://127.0.0.1:4000',
+      'http://localhost:5173',
+      'http://localhost:5180',
+    ]) {
+      expect(isAppRequest(request('127.0.0.1', { host: 'localhost:4000', origin }), ORIGINS)).toBe(true);
+    }
+  });
+
+  it('rejects other origins', () => {
+    for (const origin of ['https://attacker.example', 'http://localhost:8080', 'null']) {
+      expect(isAppRequest(request('127.0.0.1', { host: 'localhost:4000', origin }), ORIGINS)).toBe(false);
+    }
+  });
+});
+
+describe('requireAppRequest', () => {
+  it('throws forbidden for other requests', () => {
+    const req = request('127.0.0.1', { host: 'localhost:4000', origin: 'https://attacker.example' });
+    expect(() => requireAppRequest(req, ORIGINS)).toThrow(expect.objectContaining({ code: 'forbidden' }));
+  });
+});
@@ -0,0 +1,72 @@
+import type * as http from 'http';
+import { PlasmaError } from './errors';
+
+/**
+ * Requests from Plasma itself: its window, the frontend served on the
+ * server's port or by Vite, and tools like the CLI running on this Mac.
+ * The server answers anything that can reach its port, and browsers let
+ * any web page post to localhost, so endpoints that change which commands
+ * Plasma runs or hand out access to a simulator only serve these. A page
+ * can't fake its Origin header, and a page whose name was rebound to
+ * 127.0.0.1 still sends its own name as the Host.
+ */
+
+export interface AppOrigins {
+  /** Port the server listens on */
+  port: number;
+  /** Vite dev server the frontend is proxied to, when there is one */
+  devUrl?: string;
+}
+
+const LOOPBACK_ADDRESSES = new Set(['127.0.0.1', '::1', '::ffff:127.0.0.1']);
+const LOCAL_HOSTNAMES = new Set(['localhost', '127.0.0.1', '[::1]']);
+// Vite's default port, where the Electron window loads the frontend while developing
+const VITE_DEV_PORT = 5173;
+
+function allowedOrigins({ port, devUrl }: AppOrigins): Set<string> {
+  const origins = new Set<string>(['file://']);
+  for (const hostname of ['localhost', '127.0.0.1']) {
+    origins.add(`http://${hostname}:${port}`);
+    origins.add(`http://${hostname}:${VITE_DEV_PORT}`);
+  }
+  if (devUrl) {
+    origins.add(new URL(devUrl).origin);
+  }
+  return origins;
+}
+
+function hostnameOf(host: string | undefined): string | null {
+  if (!host) {
+    return null;
+  }
+  try {
+    return new URL(`http://${host}`).hostname;
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * Whether a request comes from this Mac, addressed to localhost, and from
+ * one of Plasma's own pages when it was sent by a browser
+ */
+export function isAppRequest(req: http.IncomingMessage, origins: AppOrigins): boolean {
+  if (!LOOPBACK_ADDRESSES.has(req.socket.remoteAddress ?? '')) {
+    return false;
+  }
+  const hostname = hostnameOf(req.headers.host);
+  if (!hostname || !LOCAL_HOSTNAMES.has(hostname)) {
+    return false;
+  }
+  const origin = req.headers.origin;
+  return origin === undefined || allowedOrigins(origins).has(origin);
+}
+
+/**
+ * Refuse a request that doesn't come from Plasma itself
+ */
+export function requireAppRequest(req: http.IncomingMessage, origins: AppOrigins): void {
+  if (!isAppRequest(req, origins)) {
+    throw new PlasmaError('forbidden', 'Only Plasma on this Mac can make this request');
+  }
+}
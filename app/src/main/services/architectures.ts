@@ -0,0 +1,143 @@
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { PlasmaError } from './errors';
+import { readAppBundleInfo } from './plist';
+
+/**
+ * CPU architectures of simulator builds. On Apple silicon, simulators run
+ * arm64 apps natively and x86_64 apps through Rosetta. Projects linking
+ * libraries that only ship an x86_64 simulator slice (or exclude arm64 with
+ * EXCLUDED_ARCHS) have to be built for x86_64 and need Rosetta to run.
+ */
+
+export type BuildArch = 'arm64' | 'x86_64';
+
+export const BUILD_ARCHS: BuildArch[] = ['arm64', 'x86_64'];
+
+const CPU_TYPE_X86_64 = 0x01000007;
+const CPU_TYPE_ARM64 = 0x0100000c;
+
+const MH_MAGIC_64 = 0xfeedfacf;
+const FAT_MAGIC = 0xcafebabe;
+const FAT_MAGIC_64 = 0xcafebabf;
+
+// Installed by softwareupdate --install-rosetta
+const ROSETTA_RUNTIME = '/Library/Apple/usr/libexec/oah/libRosettaRuntime';
+
+function archOfCpuType(cpuType: number): BuildArch | null {
+  switch (cpuType) {
+    case CPU_TYPE_ARM64:
+      return 'arm64';
+    case CPU_TYPE_X86_64:
+      return 'x86_64';
+    default:
+      return null;
+  }
+}
+
+/**
+ * The architectures a Mach-O header says its file holds, for both single
+ * architecture and universal binaries
+ */
+export function parseMachOArchs(header: Buffer): BuildArch[] {
+  if (header.length < 8) {
+    return [];
+  }
+  if (header.readUInt32LE(0) === MH_MAGIC_64) {
+    const arch = archOfCpuType(header.readUInt32LE(4));
+    return arch ? [arch] : [];
+  }
+  const magic = header.readUInt32BE(0);
+  if (magic !== FAT_MAGIC && magic !== FAT_MAGIC_64) {
+    return [];
+  }
+  // Each slice starts with its CPU type, big-endian like the rest of the fat header
+  const entrySize = magic === FAT_MAGIC_64 ? 32 : 20;
+  const archs: BuildArch[] = [];
+  const count = header.readUInt32BE(4);
+  for (let index = 0; index < count && 8 + (index + 1) * entrySize <= header.length; index++) {
+    const arch = archOfCpuType(header.readUInt32BE(8 + index * entrySize));
+    if (arch && !archs.includes(arch)) {
+      archs.push(arch);
+    }
+  }
+  return archs;
+}
+
+/**
+ * Architectures of an app bundle's executable, empty when it can't be read
+ */
+export function readAppArchs(appPath: string): BuildArch[] {
+  try {
+    const executable = readAppBundleInfo(appPath).executable ?? path.basename(appPath, '.app');
+    const fd = fs.openSync(path.join(appPath, executable), 'r');
+    try {
+      const header = Buffer.alloc(4096);
+      const bytes = fs.readSync(fd, header, 0, header.length, 0);
+      return parseMachOArchs(header.subarray(0, bytes));
+    } finally {
+      fs.closeSync(fd);
+    }
+  } catch {
+    return [];
+  }
+}
+
+export function isAppleSilicon(): boolean {
+  return process.platform === 'darwin' && (os.arch() === 'arm64' || /^Apple/.test(os.cpus()[0]?.model ?? ''));
+}
+
+export function isRosettaInstalled(): boolean {
+  return fs.existsSync(ROSETTA_RUNTIME);
+}
+
+/**
+ * Make sure this Mac can run an app on a simulator: apps without an arm64
+ * slice run through Rosetta on Apple silicon, which has to be installed
+ */
+export function ensureSimulatorCanRun(appPath: string): void {
+  const archs = readAppArchs(appPath);
+  if (!isAppleSilicon() || archs.length === 0 || archs.includes('arm64') || isRosettaInstalled()) {
+    return;
+  }
+  throw new PlasmaError(
+    'tool_missing',
+    `${path.basename(appPath)} is only built for ${archs.join(', ')} and needs Rosetta to run on this Mac. ` +
+      'Install it with softwareupdate --install-rosetta --agree-to-license, or build for arm64.'
+  );
+}
+
+/**
+ * Recognize linker and compiler errors caused by a library built for another
+ * architecture, returning the architecture that would link, like
+ *   ld: building for iOS Simulator-arm64 but attempting to link with file built for iOS Simulator-x86_64
+ *   ld: building for iOS Simulator, but linking in object file built for iOS, file 'Foo.framework/Foo' for architecture arm64
+ *   could not find module 'Foo' for target 'arm64-apple-ios-simulator'; found: x86_64-apple-ios-simulator
+ *   found architecture 'x86_64', required architecture 'arm64'
+ */
+export function detectArchMismatch(line: string): BuildArch | null {
+  const linked = line.match(/building for [^,]*?-(arm64|x86_64),? but (?:attempting to )?link(?:ing)? (?:in|with) .*?built for [^,]*?-(arm64|x86_64)/);
+  if (linked) {
+    return linked[2] as BuildArch;
+  }
+  // A device-only arm64 slice; the x86_64 simulator build leaves it out
+  if (/building for '?iOS[- ]Simulator'?,? but linking in (?:object file|dylib).*built for '?iOS'?(?:[,\s]|$)/i.test(line)) {
+    return 'x86_64';
+  }
+  const module = line.match(/could not find module '[^']+' for target '(?:arm64|x86_64)-apple-ios[\w.]*-simulator'; found: (arm64|x86_64)-/i);
+  if (module) {
+    return module[1] as BuildArch;
+  }
+  const found = line.match(/found architecture '(arm64|x86_64)', required architecture '(?:arm64|x86_64)'/);
+  return found ? (found[1] as BuildArch) : null;
+}
+
+/**
+ * Build output line suggesting an architecture that would link
+ */
+export function archMismatchHint(arch: BuildArch): string {
+  return arch === 'x86_64'
+    ? 'Some libraries are only built for x86_64 simulators. Build for x86_64 to run the app through Rosetta.'
+    : 'Some libraries are only built for arm64 simulators. Build for arm64 instead.';
+}
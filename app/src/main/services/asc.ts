@@ -0,0 +1,233 @@
+import * as crypto from 'crypto';
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { ErrorCode, PlasmaError, toPlasmaError } from './errors';
+import { runCommand, TOOL_COMMAND } from './exec';
+import { readManifest } from './manifest';
+import { isDictionary, parsePlist } from './plist';
+
+/**
+ * TestFlight uploads through App Store Connect with an API key. The IPA is
+ * uploaded with `altool`, then the App Store Connect REST API is polled
+ * until Apple has processed the build. Key ID and issuer ID come from
+ * APP_STORE_CONNECT_KEY_ID and APP_STORE_CONNECT_ISSUER_ID, or from
+ * [app_store_connect] in plasma.toml, and the .p8 private key from
+ * APP_STORE_CONNECT_PRIVATE_KEY (its contents) or a key_path.
+ */
+
+export interface AscConfig {
+  keyId?: string;
+  issuerId?: string;
+  /** Absolute path of the .p8 private key */
+  keyPath?: string;
+}
+
+export interface AscCredentials {
+  keyId: string;
+  issuerId: string;
+  privateKey: string;
+}
+
+export type BuildProcessingState = 'PROCESSING' | 'FAILED' | 'INVALID' | 'VALID';
+
+export type TestFlightEvent =
+  | { type: 'uploading'; bundleId: string; version: string; buildNumber: string }
+  | { type: 'output'; line: string }
+  | { type: 'uploaded' }
+  | { type: 'processing'; state: BuildProcessingState | 'WAITING' }
+  | { type: 'completed'; bundleId: string; version: string; buildNumber: string; state: BuildProcessingState | null }
+  | { type: 'error'; message: string; code: ErrorCode };
+
+export interface TestFlightUploadOptions {
+  projectPath?: string;
+  /** Wait for App Store Connect to process the build, true by default */
+  waitForProcessing?: boolean;
+}
+
+const API_BASE = 'https://api.appstoreconnect.apple.com/v1';
+/** Tokens can be valid for at most 20 minutes */
+const TOKEN_LIFETIME_S = 15 * 60;
+const PROCESSING_POLL_MS = 30 * 1000;
+const PROCESSING_TIMEOUT_MS = 60 * 60 * 1000;
+
+/**
+ * API key from the environment, falling back to the project's plasma.toml
+ */
+export function getAscCredentials(projectPath?: string): AscCredentials {
+  const config = projectPath ? readManifest(projectPath)?.appStoreConnect : undefined;
+  const keyId = process.env.APP_STORE_CONNECT_KEY_ID ?? config?.keyId;
+  const issuerId = process.env.APP_STORE_CONNECT_ISSUER_ID ?? config?.issuerId;
+  const keyPath = process.env.APP_STORE_CONNECT_PRIVATE_KEY_PATH ?? config?.keyPath;
+  let privateKey = process.env.APP_STORE_CONNECT_PRIVATE_KEY;
+  if (!privateKey && keyPath) {
+    if (!fs.existsSync(keyPath)) {
+      throw new PlasmaError('not_found', `App Store Connect key not found at ${keyPath}`);
+    }
+    privateKey = fs.readFileSync(keyPath, 'utf-8');
+  }
+  if (!keyId || !issuerId || !privateKey) {
+    throw new PlasmaError(
+      'invalid_request',
+      'An App Store Connect API key is required: set APP_STORE_CONNECT_KEY_ID, APP_STORE_CONNECT_ISSUER_ID, and APP_STORE_CONNECT_PRIVATE_KEY'
+    );
+  }
+  return { keyId, issuerId, privateKey };
+}
+
+function base64Url(data: string | Buffer): string {
+  return Buffer.from(data).toString('base64url');
+}
+
+/**
+ * ES256 JSON Web Token for the App Store Connect API
+ */
+export function createAscToken(credentials: AscCredentials, now = Date.now()): string {
+  const issuedAt = Math.floor(now / 1000);
+  const header = base64Url(JSON.stringify({ alg: 'ES256', kid: credentials.keyId, typ: 'JWT' }));
+  const payload = base64Url(
+    JSON.stringify({ iss: credentials.issuerId, iat: issuedAt, exp: issuedAt + TOKEN_LIFETIME_S, aud: 'appstoreconnect-v1' })
+  );
+  // JWTs take the raw r||s signature rather than DER
+  const signature = crypto.sign('sha256', Buffer.from(`${header}.${payload}`), {
+    key: credentials.privateKey,
+    dsaEncoding: 'ieee-p1363',
+  });
+  return `${header}.${payload}.${base64Url(signature)}`;
+}
+
+async function ascGet(credentials: AscCredentials, resource: string): Promise<any> {
+  const response = await fetch(`${API_BASE}${resource}`, {
+    headers: { Authorization: `Bearer ${createAscToken(credentials)}` },
+  });
+  if (!response.ok) {
+    const body = await response.json().catch(() => null);
+    const detail = body?.errors?.[0]?.detail ?? `HTTP ${response.status}`;
+    throw new PlasmaError(response.status === 401 ? 'invalid_request' : 'internal', `App Store Connect: ${detail}`);
+  }
+  return response.json();
+}
+
+/**
+ * Bundle ID, version, and build number from the app inside an IPA
+ */
+async function readIpaInfo(ipaPath: string): Promise<{ bundleId: string; version: string; buildNumber: string }> {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-ipa-'));
+  try {
+    await runCommand('unzip', ['-q', ipaPath, 'Payload/*.app/Info.plist', '-d', dir], TOOL_COMMAND);
+    const payload = path.join(dir, 'Payload');
+    const app = fs.readdirSync(payload).find((name) => name.endsWith('.app'));
+    const plist = app ? parsePlist(fs.readFileSync(path.join(payload, app, 'Info.plist'))) : null;
+    const text = (key: string) => (plist && isDictionary(plist) && typeof plist[key] === 'string' ? (plist[key] as string) : '');
+    const info = {
+      bundleId: text('CFBundleIdentifier'),
+      version: text('CFBundleShortVersionString'),
+      buildNumber: text('CFBundleVersion'),
+    };
+    if (!info.bundleId || !info.version || !info.buildNumber) {
+      throw new PlasmaError('invalid_request', `${path.basename(ipaPath)} has no bundle ID, version, or build number`);
+    }
+    return info;
+  } catch (err) {
+    throw err instanceof PlasmaError && err.code === 'invalid_request'
+      ? err
+      : new PlasmaError('invalid_request', `${path.basename(ipaPath)} is not an IPA`);
+  } finally {
+    fs.rmSync(dir, { recursive: true, force: true });
+  }
+}
+
+/**
+ * Processing state of an uploaded build, or null when App Store Connect
+ * doesn't list it yet
+ */
+export async function getBuildProcessingState(
+  credentials: AscCredentials,
+  bundleId: string,
+  version: string,
+  buildNumber: string
+): Promise<BuildProcessingState | null> {
+  const apps = await ascGet(credentials, `/apps?filter[bundleId]=${encodeURIComponent(bundleId)}&fields[apps]=bundleId`);
+  const app = apps.data?.find((candidate: any) => candidate.attributes?.bundleId === bundleId);
+  if (!app) {
+    throw new PlasmaError('not_found', `No app with bundle ID ${bundleId} in App Store Connect`);
+  }
+  const builds = await ascGet(
+    credentials,
+    `/builds?filter[app]=${app.id}&filter[version]=${encodeURIComponent(buildNumber)}` +
+      `&filter[preReleaseVersion.version]=${encodeURIComponent(version)}&fields[builds]=processingState`
+  );
+  return builds.data?.[0]?.attributes?.processingState ?? null;
+}
+
+/**
+ * Upload an exported IPA to App Store Connect for TestFlight and follow its
+ * processing. Problems with the input throw; failures after that are
+ * reported as an error event.
+ */
+export async function uploadToTestFlight(
+  ipaPath: string,
+  options: TestFlightUploadOptions,
+  onEvent: (event: TestFlightEvent) => void
+): Promise<void> {
+  if (process.platform !== 'darwin') {
+    throw new PlasmaError('invalid_request', 'Uploading to TestFlight needs Xcode on macOS');
+  }
+  if (!ipaPath.endsWith('.ipa') || !fs.existsSync(ipaPath)) {
+    throw new PlasmaError('not_found', `No IPA at ${ipaPath}`);
+  }
+  const credentials = getAscCredentials(options.projectPath);
+  const info = await readIpaInfo(ipaPath);
+
+  // altool only reads keys named AuthKey_<key id>.p8 from API_PRIVATE_KEYS_DIR
+  const keysDir = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-asc-'));
+  try {
+    fs.writeFileSync(path.join(keysDir, `AuthKey_${credentials.keyId}.p8`), credentials.privateKey, { mode: 0o600 });
+    onEvent({ type: 'uploading', ...info });
+    await runCommand(
+      'xcrun',
+      [
+        'altool',
+        '--upload-app',
+        '--type',
+        'ios',
+        '--file',
+        ipaPath,
+        '--apiKey',
+        credentials.keyId,
+        '--apiIssuer',
+        credentials.issuerId,
+      ],
+      {
+        timeout: 'upload',
+        errorCode: 'upload_failed',
+        env: { ...process.env, API_PRIVATE_KEYS_DIR: keysDir },
+        // altool reports progress on stderr
+        onLine: (line) => onEvent({ type: 'output', line }),
+      }
+    );
+    onEvent({ type: 'uploaded' });
+
+    let state: BuildProcessingState | null = null;
+    if (options.waitForProcessing !== false) {
+      const deadline = Date.now() + PROCESSING_TIMEOUT_MS;
+      for (;;) {
+        state = await getBuildProcessingState(credentials, info.bundleId, info.version, info.buildNumber);
+        onEvent({ type: 'processing', state: state ?? 'WAITING' });
+        if (state && state !== 'PROCESSING') {
+          break;
+        }
+        if (Date.now() > deadline) {
+          throw new PlasmaError('timeout', `App Store Connect is still processing ${info.version} (${info.buildNumber})`);
+        }
+        await new Promise((resolve) => setTimeout(resolve, PROCESSING_POLL_MS));
+      }
+    }
+    onEvent({ type: 'completed', ...info, state });
+  } catch (err) {
+    const error = toPlasmaError(err);
+    onEvent({ type: 'error', message: error.message, code: error.code });
+  } finally {
+    fs.rmSync(keysDir, { recursive: true, force: true });
+  }
+}
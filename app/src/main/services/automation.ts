@@ -0,0 +1,348 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import { AccessibilityFrame, assertVisible, describeScreen, runAxe, tapElement, waitForElement } from './accessibility';
+import { PlasmaError, toPlasmaError } from './errors';
+import { parseToml, TomlTable } from './toml';
+import { getDataSubdir } from './paths';
+import { DEVICE_ACTIONS, DeviceAction, launchInstalledApp, performDeviceAction, takeScreenshot } from './simulator';
+import { checkScreenshot, MaskRegion, parseMasks } from './visual-diff';
+
+/**
+ * Replayable automation scripts: a list of steps (launch, wait, tap, type,
+ * assert, screenshot) run against a simulator, one after another, stopping
+ * at the first failure. Scripts are JSON or TOML, e.g.
+ *
+ *   name = "Sign in"
+ *   steps = [
+ *     { launch = "com.example.app" },
+ *     { wait_for = "email-field", timeout = 10 },
+ *     { tap = "email-field" },
+ *     { type = "me@example.com" },
+ *     { tap = { x = 0.5, y = 0.9 } },
+ *     { assert = "Welcome" },
+ *     { screenshot = "home" },
+ *     { assert_screenshot = "home", tolerance = 0.01 },
+ *   ]
+ *
+ * Elements are matched by accessibility identifier, then by label. Points
+ * are normalized (0-1) screen coordinates.
+ */
+
+export interface AutomationPoint {
+  x: number;
+  y: number;
+}
+
+export type AutomationStep =
+  | { launch: string; arguments?: string[] }
+  | { wait: number }
+  | { wait_for: string; timeout?: number }
+  | { tap: string | AutomationPoint }
+  | { swipe: { from: AutomationPoint; to: AutomationPoint; duration?: number } }
+  | { type: string }
+  | { button: 'home' | 'lock' | 'side-button' }
+  | { assert: string }
+  | { screenshot: string }
+  | { assert_screenshot: string; threshold?: number; tolerance?: number; masks?: MaskRegion[] };
+
+export interface AutomationScript {
+  name?: string;
+  /** Project baselines for assert_screenshot are stored under */
+  project?: string;
+  steps: AutomationStep[];
+}
+
+export type AutomationEvent =
+  | { type: 'started'; name?: string; steps: number; outputDir: string }
+  | { type: 'step'; index: number; step: AutomationStep; status: 'running' }
+  | {
+      type: 'step';
+      index: number;
+      step: AutomationStep;
+      status: 'passed' | 'failed';
+      durationMs: number;
+      message?: string;
+      screenshotPath?: string;
+    }
+  | { type: 'completed'; success: boolean; durationMs: number; failedStep?: number; error?: string; code?: string };
+
+const ACTIONS = [
+  'launch',
+  'wait',
+  'wait_for',
+  'tap',
+  'swipe',
+  'type',
+  'button',
+  'simulate',
+  'assert',
+  'screenshot',
+  'assert_screenshot',
+] as const;
+
+const DEFAULT_WAIT_FOR_SECONDS = 10;
+
+function invalid(index: number, message: string): never {
+  throw new PlasmaError('invalid_request', `Step ${index + 1}: ${message}`);
+}
+
+function isPoint(value: unknown): value is AutomationPoint {
+  const point = value as AutomationPoint;
+  return (
+    typeof value === 'object' && value !== null &&
+    typeof point.x === 'number' && typeof point.y === 'number' &&
+    point.x >= 0 && point.x <= 1 && point.y >= 0 && point.y <= 1
+  );
+}
+
+function validateStep(value: unknown, index: number): AutomationStep {
+  if (typeof value !== 'object' || value === null || Array.isArray(value)) {
+    invalid(index, 'must be a table');
+  }
+  const step = value as Record<string, unknown>;
+  const actions = ACTIONS.filter((action) => action in step);
+  if (actions.length !== 1) {
+    invalid(index, `must have exactly one of ${ACTIONS.join(', ')}`);
+  }
+
+  const action = actions[0];
+  const argument = step[action];
+  switch (action) {
+    case 'wait':
+      if (typeof argument !== 'number' || argument < 0) invalid(index, 'wait must be a number of seconds');
+      break;
+    case 'wait_for':
+      if (typeof argument !== 'string') invalid(index, 'wait_for must be an identifier or label');
+      if (step.timeout !== undefined && typeof step.timeout !== 'number') invalid(index, 'timeout must be a number');
+      break;
+    case 'tap':
+      if (typeof argument !== 'string' && !isPoint(argument)) {
+        invalid(index, 'tap must be an identifier, a label, or a point with x and y between 0 and 1');
+      }
+      break;
+    case 'swipe': {
+      const swipe = argument as Record<string, unknown>;
+      if (!swipe || !isPoint(swipe.from) || !isPoint(swipe.to)) invalid(index, 'swipe needs from and to points');
+      break;
+    }
+    case 'button':
+      if (!['home', 'lock', 'side-button'].includes(argument as string)) {
+        invalid(index, 'button must be home, lock, or side-button');
+      }
+      break;
+    case 'simulate':
+      if (!DEVICE_ACTIONS.includes(argument as DeviceAction)) {
+        invalid(index, `simulate must be ${DEVICE_ACTIONS.join(' or ')}`);
+      }
+      break;
+    case 'assert_screenshot':
+      if (typeof argument !== 'string' || !argument) invalid(index, 'assert_screenshot needs a screenshot name');
+      for (const option of ['threshold', 'tolerance']) {
+        if (step[option] !== undefined && typeof step[option] !== 'number') invalid(index, `${option} must be a number`);
+      }
+      try {
+        parseMasks(step.masks);
+      } catch (err) {
+        invalid(index, (err as Error).message);
+      }
+      break;
+    case 'launch':
+      if (typeof argument !== 'string' || !argument) invalid(index, 'launch needs a bundle identifier');
+      if (step.arguments !== undefined && !Array.isArray(step.arguments)) invalid(index, 'arguments must be a list');
+      break;
+    default:
+      if (typeof argument !== 'string' || !argument) invalid(index, `${action} needs a value`);
+  }
+  return step as AutomationStep;
+}
+
+/**
+ * Parse and validate a script given as JSON or TOML text, or as an already parsed object
+ */
+export function parseAutomationScript(input: unknown): AutomationScript {
+  let source = input;
+  if (typeof input === 'string') {
+    if (input.trimStart().startsWith('{')) {
+      try {
+        source = JSON.parse(input);
+      } catch (err) {
+        throw new PlasmaError('invalid_request', `Script is not valid JSON: ${(err as Error).message}`);
+      }
+    } else {
+      source = parseToml(input);
+    }
+  }
+
+  const script = source as TomlTable;
+  if (typeof script !== 'object' || script === null || !Array.isArray(script.steps)) {
+    throw new PlasmaError('invalid_request', 'Script must have a steps list');
+  }
+  if (script.steps.length === 0) {
+    throw new PlasmaError('invalid_request', 'Script has no steps');
+  }
+
+  return {
+    name: typeof script.name === 'string' ? script.name : undefined,
+    project: typeof script.project === 'string' ? script.project : undefined,
+    steps: script.steps.map(validateStep),
+  };
+}
+
+function actionOf(step: AutomationStep): (typeof ACTIONS)[number] {
+  return ACTIONS.find((action) => action in step)!;
+}
+
+function sleep(ms: number, signal?: AbortSignal): Promise<void> {
+  return new Promise((resolve, reject) => {
+    const timer = setTimeout(resolve, ms);
+    signal?.addEventListener('abort', () => {
+      clearTimeout(timer);
+      reject(new PlasmaError('invalid_request', 'Script run was cancelled'));
+    }, { once: true });
+  });
+}
+
+async function screenFrame(udid: string): Promise<AccessibilityFrame> {
+  const frame = (await describeScreen(udid))[0]?.frame;
+  if (!frame || frame.width === 0 || frame.height === 0) {
+    throw new PlasmaError('input_failed', 'Could not determine the screen size');
+  }
+  return frame;
+}
+
+function toPoints(frame: AccessibilityFrame, point: AutomationPoint): [string, string] {
+  return [
+    Math.round(frame.x + point.x * frame.width).toString(),
+    Math.round(frame.y + point.y * frame.height).toString(),
+  ];
+}
+
+async function tapByText(udid: string, text: string): Promise<void> {
+  try {
+    await tapElement(udid, { identifier: text });
+  } catch (err) {
+    if (toPlasmaError(err).code !== 'not_found') throw err;
+    await tapElement(udid, { label: text });
+  }
+}
+
+interface StepContext {
+  udid: string;
+  outputDir: string;
+  project?: string;
+  signal?: AbortSignal;
+}
+
+/**
+ * Run one step, returning a screenshot path when it captured one
+ */
+async function runStep(step: AutomationStep, context: StepContext): Promise<string | undefined> {
+  const { udid, signal } = context;
+
+  if ('launch' in step) {
+    await launchInstalledApp(udid, step.launch, { arguments: step.arguments ?? [], environment: {} });
+  } else if ('wait' in step) {
+    await sleep(step.wait * 1000, signal);
+  } else if ('wait_for' in step) {
+    await waitForElement(udid, step.wait_for, step.timeout ?? DEFAULT_WAIT_FOR_SECONDS, signal);
+  } else if ('tap' in step) {
+    if (typeof step.tap === 'string') {
+      await tapByText(udid, step.tap);
+    } else {
+      const [x, y] = toPoints(await screenFrame(udid), step.tap);
+      await runAxe(udid, ['tap', '-x', x, '-y', y]);
+    }
+  } else if ('swipe' in step) {
+    const frame = await screenFrame(udid);
+    const [startX, startY] = toPoints(frame, step.swipe.from);
+    const [endX, endY] = toPoints(frame, step.swipe.to);
+    await runAxe(udid, [
+      'swipe',
+      '--start-x', startX, '--start-y', startY,
+      '--end-x', endX, '--end-y', endY,
+      '--duration', String(step.swipe.duration ?? 0.3),
+    ]);
+  } else if ('type' in step) {
+    await runAxe(udid, ['type', step.type]);
+  } else if ('button' in step) {
+    await runAxe(udid, ['button', step.button]);
+  } else if ('simulate' in step) {
+    await performDeviceAction(udid, step.simulate);
+  } else if ('assert' in step) {
+    await assertVisible(udid, step.assert);
+  } else if ('screenshot' in step) {
+    const screenshotPath = path.join(context.outputDir, `${step.screenshot.replace(/[^\w.-]+/g, '_')}.png`);
+    fs.writeFileSync(screenshotPath, await takeScreenshot(udid));
+    return screenshotPath;
+  } else if ('assert_screenshot' in step) {
+    const result = await checkScreenshot({
+      udid,
+      name: step.assert_screenshot,
+      project: context.project,
+      threshold: step.threshold,
+      tolerance: step.tolerance,
+      masks: step.masks,
+    });
+    if (!result.passed) {
+      throw new PlasmaError(
+        'not_found',
+        result.message ?? `Screen differs from the baseline in ${(result.diffRatio * 100).toFixed(2)}% of pixels (${result.diffPath})`
+      );
+    }
+    return result.actualPath ?? result.baselinePath;
+  }
+  return undefined;
+}
+
+/**
+ * Run a script against a simulator, reporting each step as it starts and finishes.
+ * Screenshots go to outputDir, or a timestamped directory under the data directory.
+ */
+export async function runAutomation(
+  udid: string,
+  script: AutomationScript,
+  onEvent: (event: AutomationEvent) => void,
+  signal?: AbortSignal,
+  outputDir?: string
+): Promise<boolean> {
+  const startedAt = Date.now();
+  const timestamp = new Date(startedAt).toISOString().replace(/[:.]/g, '-');
+  outputDir ??= path.join(getDataSubdir('automation'), `${udid}-${timestamp}`);
+  fs.mkdirSync(outputDir, { recursive: true });
+
+  onEvent({ type: 'started', name: script.name, steps: script.steps.length, outputDir });
+
+  for (const [index, step] of script.steps.entries()) {
+    const stepStartedAt = Date.now();
+    onEvent({ type: 'step', index, step, status: 'running' });
+    try {
+      if (signal?.aborted) {
+        throw new PlasmaError('invalid_request', 'Script run was cancelled');
+      }
+      const screenshotPath = await runStep(step, { udid, outputDir, project: script.project, signal });
+      onEvent({ type: 'step', index, step, status: 'passed', durationMs: Date.now() - stepStartedAt, screenshotPath });
+    } catch (err) {
+      const error = toPlasmaError(err);
+      onEvent({
+        type: 'step',
+        index,
+        step,
+        status: 'failed',
+        durationMs: Date.now() - stepStartedAt,
+        message: `${actionOf(step)} failed: ${error.message}`,
+      });
+      onEvent({
+        type: 'completed',
+        success: false,
+        durationMs: Date.now() - startedAt,
+        failedStep: index,
+        error: error.message,
+        code: error.code,
+      });
+      return false;
+    }
+  }
+
+  onEvent({ type: 'completed', success: true, durationMs: Date.now() - startedAt });
+  return true;
+}
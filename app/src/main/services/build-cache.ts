@@ -0,0 +1,319 @@
+import * as crypto from 'crypto';
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { PlasmaError } from './errors';
+import { runCommand, RunCommandOptions } from './exec';
+import { readGitInfo } from './git';
+import { readManifest } from './manifest';
+import { getDataSubdir } from './paths';
+
+/**
+ * Opt-in cache of build products, keyed by the project's git revision, the
+ * scheme, configuration, platform, and Xcode version. Products are stored
+ * as a tarball in a local directory and, when configured, in an
+ * S3-compatible bucket so CI machines and teammates share them. A build of
+ * a revision that's already cached restores the products instead of running
+ * xcodebuild.
+ */
+
+export interface BuildCacheConfig {
+  /** Local cache directory, the data directory's build-cache by default */
+  directory?: string;
+  /** s3://bucket/prefix of the remote cache */
+  url?: string;
+  /** Endpoint of an S3-compatible service, AWS S3 when unset */
+  endpoint?: string;
+  region?: string;
+}
+
+export interface BuildCacheKeyInput {
+  projectPath: string;
+  scheme: string;
+  configuration: string;
+  platform: string;
+  arch?: string;
+}
+
+interface CacheBackend {
+  readonly name: string;
+  /** Download the archive for a key to a file, returning false on a miss */
+  get(key: string, destination: string): Promise<boolean>;
+  put(key: string, archive: string): Promise<void>;
+}
+
+/** Local entries kept before the least recently used ones are removed */
+const MAX_LOCAL_ENTRIES = 20;
+
+const XCODEBUILD_COMMAND: RunCommandOptions = { timeout: 'xcodebuild', errorCode: 'xcodebuild_failed' };
+
+// Archives can hold a large app's products, so they get as long as a build
+const TAR_COMMAND: RunCommandOptions = { timeout: 'build', errorCode: 'tool_failed' };
+
+function archiveName(key: string): string {
+  return `${key}.tar.gz`;
+}
+
+class DirectoryBackend implements CacheBackend {
+  readonly name: string;
+
+  constructor(private directory: string) {
+    this.name = directory;
+  }
+
+  async get(key: string, destination: string): Promise<boolean> {
+    const archive = path.join(this.directory, archiveName(key));
+    if (!fs.existsSync(archive)) {
+      return false;
+    }
+    fs.copyFileSync(archive, destination);
+    // Hits count as use so pruning keeps them
+    const now = new Date();
+    fs.utimesSync(archive, now, now);
+    return true;
+  }
+
+  async put(key: string, archive: string): Promise<void> {
+    fs.mkdirSync(this.directory, { recursive: true });
+    // Copy then rename so concurrent readers never see a partial archive
+    const target = path.join(this.directory, archiveName(key));
+    const partial = `${target}.${process.pid}.partial`;
+    fs.copyFileSync(archive, partial);
+    fs.renameSync(partial, target);
+    this.prune();
+  }
+
+  private prune(): void {
+    const entries = fs
+      .readdirSync(this.directory)
+      .filter((name) => name.endsWith('.tar.gz'))
+      .map((name) => {
+        const file = path.join(this.directory, name);
+        return { file, mtime: fs.statSync(file).mtimeMs };
+      })
+      .sort((a, b) => b.mtime - a.mtime);
+    for (const entry of entries.slice(MAX_LOCAL_ENTRIES)) {
+      fs.rmSync(entry.file, { force: true });
+    }
+  }
+}
+
+function sha256(data: string | Buffer): string {
+  return crypto.createHash('sha256').update(data).digest('hex');
+}
+
+function hmac(key: string | Buffer, data: string): Buffer {
+  return crypto.createHmac('sha256', key).update(data).digest();
+}
+
+/**
+ * S3 or an S3-compatible service (R2, MinIO), using path-style requests
+ * signed with AWS Signature Version 4. Credentials come from the usual
+ * AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY, and AWS_SESSION_TOKEN variables.
+ */
+class S3Backend implements CacheBackend {
+  readonly name: string;
+  private bucket: string;
+  private prefix: string;
+  private endpoint: URL;
+  private region: string;
+
+  constructor(url: string, endpoint?: string, region?: string) {
+    const match = url.match(/^s3:\/\/([^/]+)\/?(.*)$/);
+    if (!match) {
+      throw new PlasmaError('invalid_request', `build_cache.url must look like s3://bucket/prefix, got ${url}`);
+    }
+    this.name = url;
+    this.bucket = match[1];
+    this.prefix = match[2].replace(/\/+$/, '');
+    this.region = region ?? process.env.AWS_REGION ?? process.env.AWS_DEFAULT_REGION ?? 'us-east-1';
+    this.endpoint = new URL(endpoint ?? `https://s3.${this.region}.amazonaws.com`);
+  }
+
+  async get(key: string, destination: string): Promise<boolean> {
+    const response = await this.request('GET', key);
+    if (response.status === 404) {
+      return false;
+    }
+    if (!response.ok) {
+      throw new PlasmaError('internal', `Downloading from ${this.name} failed with HTTP ${response.status}`);
+    }
+    fs.writeFileSync(destination, Buffer.from(await response.arrayBuffer()));
+    return true;
+  }
+
+  async put(key: string, archive: string): Promise<void> {
+    const response = await this.request('PUT', key, fs.readFileSync(archive));
+    if (!response.ok) {
+      throw new PlasmaError('internal', `Uploading to ${this.name} failed with HTTP ${response.status}`);
+    }
+  }
+
+  private request(method: 'GET' | 'PUT', key: string, body?: Buffer): Promise<Response> {
+    const accessKey = process.env.AWS_ACCESS_KEY_ID;
+    const secretKey = process.env.AWS_SECRET_ACCESS_KEY;
+    if (!accessKey || !secretKey) {
+      throw new PlasmaError('invalid_request', 'AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY are required for the remote build cache');
+    }
+
+    const segments = [this.bucket, ...this.prefix.split('/').filter(Boolean), archiveName(key)];
+    const basePath = this.endpoint.pathname.replace(/\/+$/, '');
+    const canonicalUri = `${basePath}/${segments
+      .map((segment) => encodeURIComponent(segment).replace(/[!'()*]/g, (c) => `%${c.charCodeAt(0).toString(16).toUpperCase()}`))
+      .join('/')}`;
+
+    const amzDate = new Date().toISOString().replace(/[-:]/g, '').replace(/\.\d{3}/, '');
+    const date = amzDate.slice(0, 8);
+    const payloadHash = body ? sha256(body) : sha256('');
+    const headers: Record<string, string> = {
+      'x-amz-content-sha256': payloadHash,
+      'x-amz-date': amzDate,
+    };
+    if (process.env.AWS_SESSION_TOKEN) {
+      headers['x-amz-security-token'] = process.env.AWS_SESSION_TOKEN;
+    }
+
+    // fetch sets the host header itself, but it's still signed
+    const signed: Record<string, string> = { host: this.endpoint.host, ...headers };
+    const signedHeaders = Object.keys(signed).sort();
+    const canonicalRequest = [
+      method,
+      canonicalUri,
+      '',
+      ...signedHeaders.map((name) => `${name}:${signed[name].trim()}`),
+      '',
+      signedHeaders.join(';'),
+      payloadHash,
+    ].join('\n');
+    const scope = `${date}/${this.region}/s3/aws4_request`;
+    const stringToSign = ['AWS4-HMAC-SHA256', amzDate, scope, sha256(canonicalRequest)].join('\n');
+    const signingKey = hmac(hmac(hmac(hmac(`AWS4${secretKey}`, date), this.region), 's3'), 'aws4_request');
+    const signature = crypto.createHmac('sha256', signingKey).update(stringToSign).digest('hex');
+
+    return fetch(`${this.endpoint.origin}${canonicalUri}`, {
+      method,
+      body,
+      headers: {
+        ...headers,
+        Authorization: `AWS4-HMAC-SHA256 Credential=${accessKey}/${scope}, SignedHeaders=${signedHeaders.join(';')}, Signature=${signature}`,
+      },
+    });
+  }
+}
+
+let xcodeVersion: Promise<string> | null = null;
+
+/**
+ * `xcodebuild -version` on one line, looked up once per process
+ */
+function getXcodeVersion(): Promise<string> {
+  if (!xcodeVersion) {
+    xcodeVersion = runCommand('xcodebuild', ['-version'], XCODEBUILD_COMMAND).then((output) =>
+      output.trim().replace(/\s+/g, ' ')
+    );
+    // Look it up again next time if xcodebuild failed
+    xcodeVersion.catch(() => {
+      xcodeVersion = null;
+    });
+  }
+  return xcodeVersion;
+}
+
+/**
+ * Commit checked out in the project's repository, or null when there are
+ * uncommitted changes, since the commit then doesn't describe what's built
+ */
+async function getProjectRevision(projectPath: string): Promise<string | null> {
+  const git = await readGitInfo(projectPath);
+  return git && !git.dirty ? git.commit : null;
+}
+
+/**
+ * Cache key for a build, or null when the project can't be cached
+ */
+export async function getBuildCacheKey(input: BuildCacheKeyInput): Promise<string | null> {
+  const revision = await getProjectRevision(input.projectPath);
+  if (!revision) {
+    return null;
+  }
+  const xcode = await getXcodeVersion();
+  // Only builds for a chosen architecture are keyed by it
+  const arch = input.arch ? [input.arch] : [];
+  return sha256(JSON.stringify([revision, input.scheme, input.configuration, input.platform, xcode, ...arch]));
+}
+
+export class BuildCache {
+  private constructor(private backends: CacheBackend[]) {}
+
+  /**
+   * The cache configured in the project's plasma.toml, or null when it's off
+   */
+  static forProject(projectPath: string): BuildCache | null {
+    const config = readManifest(projectPath)?.buildCache;
+    if (!config) {
+      return null;
+    }
+    const backends: CacheBackend[] = [new DirectoryBackend(config.directory ?? getDataSubdir('build-cache'))];
+    if (config.url) {
+      backends.push(new S3Backend(config.url, config.endpoint, config.region));
+    }
+    return new BuildCache(backends);
+  }
+
+  /**
+   * Extract the cached products for a key into the build directory,
+   * returning where they came from, or null on a miss
+   */
+  async restore(key: string, buildDir: string): Promise<string | null> {
+    const workDir = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-cache-'));
+    try {
+      const archive = path.join(workDir, archiveName(key));
+      for (const [index, backend] of this.backends.entries()) {
+        if (!(await backend.get(key, archive))) {
+          continue;
+        }
+        // Remote hits are kept locally for the next build
+        if (index > 0) {
+          await this.backends[0].put(key, archive);
+        }
+
+        const extracted = path.join(workDir, 'products');
+        fs.mkdirSync(extracted);
+        await runCommand('tar', ['-xzf', archive, '-C', extracted], TAR_COMMAND);
+        fs.mkdirSync(buildDir, { recursive: true });
+        for (const name of fs.readdirSync(extracted)) {
+          const target = path.join(buildDir, name);
+          fs.rmSync(target, { recursive: true, force: true });
+          fs.renameSync(path.join(extracted, name), target);
+        }
+        return backend.name;
+      }
+      return null;
+    } finally {
+      fs.rmSync(workDir, { recursive: true, force: true });
+    }
+  }
+
+  /**
+   * Archive products of the build directory (with their dSYMs) under a key
+   */
+  async store(key: string, buildDir: string, productNames: string[]): Promise<void> {
+    const names = productNames.flatMap((name) =>
+      fs.existsSync(path.join(buildDir, `${name}.dSYM`)) ? [name, `${name}.dSYM`] : [name]
+    );
+    if (names.length === 0) {
+      return;
+    }
+
+    const workDir = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-cache-'));
+    try {
+      const archive = path.join(workDir, archiveName(key));
+      await runCommand('tar', ['-czf', archive, '-C', buildDir, ...names], TAR_COMMAND);
+      for (const backend of this.backends) {
+        await backend.put(key, archive);
+      }
+    } finally {
+      fs.rmSync(workDir, { recursive: true, force: true });
+    }
+  }
+}
@@ -0,0 +1,211 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import { execCommand } from './exec';
+
+/**
+ * The machine a build ran on: Xcode and macOS versions, SDKs, the tools
+ * xcodebuild resolves, environment variables that change how it builds,
+ * and the Swift package revisions the project resolved. Stored with each
+ * build so two builds can be compared when one works and the other doesn't.
+ */
+
+export interface ResolvedPackage {
+  identity: string;
+  location: string;
+  version?: string;
+  branch?: string;
+  revision: string;
+}
+
+export interface BuildEnvironment {
+  xcodeVersion: string | null;
+  xcodeBuildVersion: string | null;
+  developerDir: string | null;
+  macosVersion: string | null;
+  macosBuildVersion: string | null;
+  /** SDK versions by canonical name, like iphonesimulator18.2 */
+  sdks: Record<string, string>;
+  /** Resolved paths of the toolchain's tools */
+  tools: Record<string, string>;
+  /** Environment variables that affect builds, as set for xcodebuild */
+  variables: Record<string, string>;
+  /** Build settings passed on the command line */
+  buildSettings: Record<string, string>;
+  packages: ResolvedPackage[];
+}
+
+export interface EnvironmentChange {
+  /** Like xcodeVersion, sdks.iphonesimulator18.2, or packages.alamofire */
+  key: string;
+  before: string | null;
+  after: string | null;
+}
+
+export interface BuildEnvironmentDiff {
+  from: string;
+  to: string;
+  /** Empty when both builds ran in the same environment */
+  changes: EnvironmentChange[];
+}
+
+const TOOLS = ['xcodebuild', 'swift', 'clang', 'ld', 'actool'];
+const BUILD_VARIABLES = /^(DEVELOPER_DIR|SDKROOT|TOOLCHAINS|CC|CXX|LD|LIBRARY_PATH|CPATH|SWIFT_\w+|XCODE_\w+|OTHER_\w+FLAGS)$/;
+
+/**
+ * Run a command, resolving with its output or null when it fails
+ */
+function readCommand(cmd: string, args: string[]): Promise<string | null> {
+  return execCommand(cmd, args, { timeout: 'tool' }).then(
+    (result) => (result.code === 0 ? result.stdout : null),
+    () => null
+  );
+}
+
+/**
+ * Parse `xcodebuild -showsdks -json` into versions by SDK name
+ */
+export function parseSdks(output: string): Record<string, string> {
+  const sdks: Record<string, string> = {};
+  try {
+    for (const sdk of JSON.parse(output) as Array<{ canonicalName?: string; sdkVersion?: string }>) {
+      if (sdk.canonicalName) {
+        sdks[sdk.canonicalName] = sdk.sdkVersion ?? '';
+      }
+    }
+  } catch {
+    // Older Xcode without -json
+  }
+  return sdks;
+}
+
+/**
+ * Parse a Package.resolved file, in the version 1 format or the later ones
+ */
+export function parsePackageResolved(text: string): ResolvedPackage[] {
+  const resolved = JSON.parse(text);
+  const pins: any[] = resolved.pins ?? resolved.object?.pins ?? [];
+  return pins
+    .map((pin) => ({
+      identity: (pin.identity ?? pin.package ?? '').toLowerCase(),
+      location: pin.location ?? pin.repositoryURL ?? '',
+      version: pin.state?.version ?? undefined,
+      branch: pin.state?.branch ?? undefined,
+      revision: pin.state?.revision ?? '',
+    }))
+    .filter((pin) => pin.identity)
+    .sort((a, b) => a.identity.localeCompare(b.identity));
+}
+
+/**
+ * Package.resolved of a project, workspace, or package
+ */
+function findPackageResolved(projectPath: string): string | null {
+  const candidates = projectPath.endsWith('.xcworkspace')
+    ? [path.join(projectPath, 'xcshareddata', 'swiftpm', 'Package.resolved')]
+    : projectPath.endsWith('.xcodeproj')
+    ? [path.join(projectPath, 'project.xcworkspace', 'xcshareddata', 'swiftpm', 'Package.resolved')]
+    : [];
+  candidates.push(path.join(path.dirname(projectPath), 'Package.resolved'), path.join(projectPath, 'Package.resolved'));
+  return candidates.find((candidate) => fs.existsSync(candidate)) ?? null;
+}
+
+function readPackages(projectPath: string): ResolvedPackage[] {
+  const file = findPackageResolved(projectPath);
+  if (!file) {
+    return [];
+  }
+  try {
+    return parsePackageResolved(fs.readFileSync(file, 'utf-8'));
+  } catch (err) {
+    console.warn(`[build-environment] Could not read ${file}:`, err);
+    return [];
+  }
+}
+
+/**
+ * Capture the environment of a build about to run. Anything that can't be
+ * read is left out rather than failing the build.
+ */
+export async function captureBuildEnvironment(projectPath: string, xcodebuildArgs: string[]): Promise<BuildEnvironment> {
+  const [xcodeVersion, developerDir, macosVersion, macosBuild, sdks, ...toolPaths] = await Promise.all([
+    readCommand('xcodebuild', ['-version']),
+    readCommand('xcode-select', ['-p']),
+    readCommand('sw_vers', ['-productVersion']),
+    readCommand('sw_vers', ['-buildVersion']),
+    readCommand('xcodebuild', ['-showsdks', '-json']),
+    ...TOOLS.map((tool) => readCommand('xcrun', ['--find', tool])),
+  ]);
+
+  const tools: Record<string, string> = {};
+  TOOLS.forEach((tool, index) => {
+    const toolPath = toolPaths[index]?.trim();
+    if (toolPath) {
+      tools[tool] = toolPath;
+    }
+  });
+
+  const variables: Record<string, string> = {};
+  for (const [key, value] of Object.entries(process.env)) {
+    if (value !== undefined && BUILD_VARIABLES.test(key)) {
+      variables[key] = value;
+    }
+  }
+
+  const buildSettings: Record<string, string> = {};
+  for (const arg of xcodebuildArgs) {
+    const match = arg.match(/^([A-Z][A-Z0-9_]*)=(.*)$/);
+    if (match) {
+      buildSettings[match[1]] = match[2];
+    }
+  }
+
+  return {
+    xcodeVersion: xcodeVersion?.match(/^Xcode (.+)$/m)?.[1] ?? null,
+    xcodeBuildVersion: xcodeVersion?.match(/^Build version (.+)$/m)?.[1] ?? null,
+    developerDir: developerDir?.trim() || null,
+    macosVersion: macosVersion?.trim() || null,
+    macosBuildVersion: macosBuild?.trim() || null,
+    sdks: sdks ? parseSdks(sdks) : {},
+    tools,
+    variables,
+    buildSettings,
+    packages: readPackages(projectPath),
+  };
+}
+
+function describePackage(pkg: ResolvedPackage): string {
+  const pinned = pkg.version ?? pkg.branch;
+  return pinned ? `${pinned} (${pkg.revision.slice(0, 7)})` : pkg.revision;
+}
+
+/**
+ * What changed between the environments of two builds
+ */
+export function diffBuildEnvironments(before: BuildEnvironment, after: BuildEnvironment): EnvironmentChange[] {
+  const changes: EnvironmentChange[] = [];
+  const compare = (key: string, a: string | null | undefined, b: string | null | undefined) => {
+    if ((a ?? null) !== (b ?? null)) {
+      changes.push({ key, before: a ?? null, after: b ?? null });
+    }
+  };
+  const compareRecords = (prefix: string, a: Record<string, string>, b: Record<string, string>) => {
+    for (const key of [...new Set([...Object.keys(a), ...Object.keys(b)])].sort()) {
+      compare(`${prefix}.${key}`, a[key], b[key]);
+    }
+  };
+
+  compare('xcodeVersion', before.xcodeVersion, after.xcodeVersion);
+  compare('xcodeBuildVersion', before.xcodeBuildVersion, after.xcodeBuildVersion);
+  compare('developerDir', before.developerDir, after.developerDir);
+  compare('macosVersion', before.macosVersion, after.macosVersion);
+  compare('macosBuildVersion', before.macosBuildVersion, after.macosBuildVersion);
+  compareRecords('sdks', before.sdks, after.sdks);
+  compareRecords('tools', before.tools, after.tools);
+  compareRecords('variables', before.variables, after.variables);
+  compareRecords('buildSettings', before.buildSettings, after.buildSettings);
+
+  const packages = (environment: BuildEnvironment) =>
+    Object.fromEntries(environment.packages.map((pkg) => [pkg.identity, describePackage(pkg)]));
+  compareRecords('packages', packages(before), packages(after));
+  return changes;
+}
@@ -0,0 +1,182 @@
+import * as path from 'path';
+import type { SourceLocation } from './editor';
+
+/**
+ * Condenses raw xcodebuild output into one short line per step, like
+ * xcbeautify: "Compiling ContentView.swift", "Linking MyApp", failures with
+ * the source line and caret under them. Everything else (the long clang and
+ * swift-frontend invocations, environment dumps) is left out. The raw lines
+ * are still what's logged and stored; this is only for display.
+ */
+
+export type BuildLineKind =
+  | 'compile'
+  | 'link'
+  | 'sign'
+  | 'step'
+  | 'info'
+  | 'error'
+  | 'warning'
+  | 'note'
+  | 'context'
+  | 'test-passed'
+  | 'test-failed'
+  | 'succeeded'
+  | 'failed';
+
+export interface FormattedBuildLine {
+  kind: BuildLineKind;
+  text: string;
+  /** Target the step belongs to, from "(in target 'App' from project 'App')" */
+  target?: string;
+  /** Source of a compiler diagnostic */
+  location?: SourceLocation;
+}
+
+// Trailing "(in target 'App' from project 'App')" of xcodebuild step headers
+const TARGET_SUFFIX = /\s*\(in target '([^']+)' from project '[^']+'\)\s*$/;
+// /path/File.swift:12:5: error: message, with the column left out by some tools
+const DIAGNOSTIC = /^(\/[^:]+):(\d+):(?:(\d+):)?\s*(fatal error|error|warning|note):\s*(.*)$/;
+const TEST_CASE = /^Test Case '-\[\S+ (\S+)\]' (passed|failed) \((\d+(?:\.\d+)?) seconds\)/;
+// Lines under a diagnostic that quote the source and point at the column
+const CARET_LINE = /^\s*[\^~]+[\^~\s]*$/;
+
+// An absolute path as xcodebuild prints it, with spaces escaped
+const PATH = String.raw`\/(?:\\ |\S)+`;
+
+function header(pattern: string): RegExp {
+  return new RegExp(`^${pattern}`);
+}
+
+/** Step headers and what they become, given the step's arguments */
+const STEPS: Array<{ pattern: RegExp; kind: BuildLineKind; format: (match: RegExpMatchArray) => string }> = [
+  { pattern: header(String.raw`(?:CompileSwift|SwiftCompile) \S+ \S+ (${PATH}?\.swift)\b`), kind: 'compile', format: (m) => `Compiling ${base(m[1])}` },
+  { pattern: header(String.raw`CompileC ${PATH} (${PATH}?\.(?:m|mm|c|cc|cpp)) `), kind: 'compile', format: (m) => `Compiling ${base(m[1])}` },
+  { pattern: header(String.raw`CompileAssetCatalog(?:Variant \S+)? ${PATH} (${PATH}?\.xcassets)`), kind: 'compile', format: (m) => `Compiling ${base(m[1])}` },
+  { pattern: header(String.raw`Compile(?:Storyboard|XIB) (${PATH}?\.(?:storyboard|xib))`), kind: 'compile', format: (m) => `Compiling ${base(m[1])}` },
+  { pattern: header(String.raw`SwiftEmitModule \S+ \S+ Emitting\\ module\\ for\\ (\S+)`), kind: 'compile', format: (m) => `Emitting module for ${m[1]}` },
+  { pattern: header(String.raw`Ld (${PATH}) `), kind: 'link', format: (m) => `Linking ${base(m[1])}` },
+  { pattern: header(String.raw`CodeSign (${PATH})`), kind: 'sign', format: (m) => `Signing ${base(m[1])}` },
+  { pattern: header(String.raw`ProcessInfoPlistFile ${PATH} (${PATH})`), kind: 'step', format: (m) => `Processing ${base(m[1])}` },
+  { pattern: header(String.raw`PhaseScriptExecution (.+?) ${PATH}\.sh`), kind: 'step', format: (m) => `Running script '${unescape(m[1])}'` },
+  { pattern: header(String.raw`CopySwiftLibs (${PATH})`), kind: 'step', format: (m) => `Copying Swift libraries into ${base(m[1])}` },
+  { pattern: header(String.raw`Touch (${PATH}?\.app)\b`), kind: 'step', format: (m) => `Touching ${base(m[1])}` },
+];
+
+function unescape(value: string): string {
+  return value.replace(/\\(.)/g, '$1');
+}
+
+function base(file: string): string {
+  return path.basename(unescape(file));
+}
+
+/**
+ * Formats the lines of one build. It keeps state between lines since a
+ * diagnostic's context and the list of failed commands span several.
+ */
+export class BuildOutputFormatter {
+  // Lines after a diagnostic that may still be its source excerpt and caret
+  private contextLines = 0;
+  private inFailedCommands = false;
+
+  /**
+   * The condensed form of a line, or null when it's left out
+   */
+  format(raw: string): FormattedBuildLine | null {
+    const line = raw.replace(/\s+$/, '');
+    if (!line.trim()) {
+      this.contextLines = 0;
+      this.inFailedCommands = false;
+      return null;
+    }
+
+    const diagnostic = line.trim().match(DIAGNOSTIC);
+    if (diagnostic) {
+      const [, file, lineNumber, column, severity, message] = diagnostic;
+      this.contextLines = 2;
+      const kind: BuildLineKind = severity === 'note' ? 'note' : severity === 'warning' ? 'warning' : 'error';
+      return {
+        kind,
+        text: `${path.basename(file)}:${lineNumber}${column ? `:${column}` : ''}: ${message}`,
+        location: { file, line: parseInt(lineNumber, 10), ...(column ? { column: parseInt(column, 10) } : {}) },
+      };
+    }
+    if (line === '** BUILD SUCCEEDED **') return { kind: 'succeeded', text: 'Build succeeded' };
+    if (line === '** BUILD FAILED **') return { kind: 'failed', text: 'Build failed' };
+    if (line === 'The following build commands failed:') {
+      this.inFailedCommands = true;
+      return { kind: 'error', text: 'Failed commands:' };
+    }
+    if (this.inFailedCommands) {
+      if (/^\(\d+ failures?\)$/.test(line.trim())) {
+        this.inFailedCommands = false;
+        return null;
+      }
+      return { kind: 'error', text: `  ${line.trim().replace(TARGET_SUFFIX, '')}` };
+    }
+
+    const test = line.match(TEST_CASE);
+    if (test) {
+      const [, name, status, seconds] = test;
+      return { kind: status === 'passed' ? 'test-passed' : 'test-failed', text: `${name} (${seconds}s)` };
+    }
+
+    const target = line.match(TARGET_SUFFIX)?.[1];
+    for (const step of STEPS) {
+      const match = line.match(step.pattern);
+      if (match) {
+        this.contextLines = 0;
+        return { kind: step.kind, text: step.format(match), ...(target ? { target } : {}) };
+      }
+    }
+
+    if (this.contextLines > 0) {
+      this.contextLines = CARET_LINE.test(line) ? 0 : this.contextLines - 1;
+      return { kind: 'context', text: line };
+    }
+
+    // Errors and warnings that don't point at a file, from ld, clang, and xcodebuild itself
+    const trimmed = line.trim();
+    if (/^(?:(?:ld|clang|xcodebuild): )?warning: /.test(trimmed)) {
+      return { kind: 'warning', text: trimmed };
+    }
+    if (/^(?:(?:clang|xcodebuild): )?(?:fatal )?error: /.test(trimmed) || /^ld: /.test(trimmed)) {
+      return { kind: 'error', text: trimmed };
+    }
+    return null;
+  }
+}
+
+const ANSI: Partial<Record<BuildLineKind, string>> = {
+  compile: '\x1b[36m',
+  link: '\x1b[35m',
+  sign: '\x1b[35m',
+  error: '\x1b[31m',
+  failed: '\x1b[1;31m',
+  warning: '\x1b[33m',
+  note: '\x1b[2m',
+  context: '\x1b[2m',
+  'test-passed': '\x1b[32m',
+  'test-failed': '\x1b[31m',
+  succeeded: '\x1b[1;32m',
+};
+
+const SYMBOLS: Partial<Record<BuildLineKind, string>> = {
+  error: '✖ ',
+  failed: '✖ ',
+  warning: '⚠ ',
+  'test-passed': '✔ ',
+  'test-failed': '✖ ',
+  succeeded: '✔ ',
+};
+
+/**
+ * A formatted line as terminal text, colored when color is true
+ */
+export function renderBuildLine(formatted: FormattedBuildLine, color: boolean): string {
+  const target = formatted.target ? `[${formatted.target}] ` : '';
+  const text = `${SYMBOLS[formatted.kind] ?? ''}${target}${formatted.text}`;
+  const code = ANSI[formatted.kind];
+  return color && code ? `${code}${text}\x1b[0m` : text;
+}
@@ -0,0 +1,123 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import { PlasmaError } from './errors';
+import { getDataSubdir } from './paths';
+
+/**
+ * Full xcodebuild output of each build, written to
+ * <data dir>/build-logs/<build id>.log as it arrives. Only the last lines
+ * are kept in memory, for builds still running; everything else is read
+ * back from the file in ranges.
+ */
+
+export interface BuildLogTail {
+  lines: string[];
+  /** Whether the build finished writing its log */
+  complete: boolean;
+}
+
+/** Lines of a running build kept in memory */
+const TAIL_LINES = 1000;
+/** Bytes read from the end of a finished log to find its last lines */
+const TAIL_READ_BYTES = 256 * 1024;
+
+const activeLogs = new Map<string, BuildLog>();
+
+export function getBuildLogPath(buildId: string): string {
+  return path.join(getDataSubdir('build-logs'), `${path.basename(buildId)}.log`);
+}
+
+/**
+ * Log of a running build
+ */
+export class BuildLog {
+  private readonly stream: fs.WriteStream;
+  private readonly tail: string[] = [];
+
+  constructor(readonly buildId: string) {
+    this.stream = fs.createWriteStream(getBuildLogPath(buildId), { flags: 'a' });
+    this.stream.on('error', (err) => {
+      console.warn(`[build-logs] Could not write the log of ${buildId}:`, err);
+    });
+    activeLogs.set(buildId, this);
+  }
+
+  append(line: string): void {
+    this.stream.write(`${line}\n`);
+    this.tail.push(line);
+    if (this.tail.length > TAIL_LINES) {
+      this.tail.splice(0, this.tail.length - TAIL_LINES);
+    }
+  }
+
+  lastLines(count: number): string[] {
+    return this.tail.slice(-count);
+  }
+
+  close(): void {
+    activeLogs.delete(this.buildId);
+    this.stream.end();
+  }
+}
+
+/**
+ * Size of a build's log, throwing when the build has none
+ */
+export function getBuildLogSize(buildId: string): number {
+  const logPath = getBuildLogPath(buildId);
+  if (!fs.existsSync(logPath)) {
+    throw new PlasmaError('not_found', `No log for build ${buildId}`);
+  }
+  return fs.statSync(logPath).size;
+}
+
+/**
+ * Read bytes start to end (inclusive) of a build's log
+ */
+export function readBuildLogRange(buildId: string, start: number, end: number): Buffer {
+  const fd = fs.openSync(getBuildLogPath(buildId), 'r');
+  try {
+    const buffer = Buffer.alloc(Math.max(0, end - start + 1));
+    const read = fs.readSync(fd, buffer, 0, buffer.length, start);
+    return buffer.subarray(0, read);
+  } finally {
+    fs.closeSync(fd);
+  }
+}
+
+/**
+ * The last lines of a build's log, from memory while it runs
+ */
+export function tailBuildLog(buildId: string, count: number = 200): BuildLogTail {
+  const active = activeLogs.get(buildId);
+  if (active) {
+    return { lines: active.lastLines(count), complete: false };
+  }
+  const size = getBuildLogSize(buildId);
+  const text = readBuildLogRange(buildId, Math.max(0, size - TAIL_READ_BYTES), size - 1).toString('utf-8');
+  const lines = text.split('\n');
+  // The last entry is empty after the final newline, and the first may be cut
+  lines.pop();
+  if (size > TAIL_READ_BYTES) {
+    lines.shift();
+  }
+  return { lines: lines.slice(-count), complete: true };
+}
+
+/**
+ * Parse a `Range: bytes=start-end` header against a file size. Returns null
+ * when there's no usable range, so the whole file is sent.
+ */
+export function parseByteRange(header: string | undefined, size: number): { start: number; end: number } | null {
+  const match = header?.match(/^bytes=(\d*)-(\d*)$/);
+  if (!match || (!match[1] && !match[2])) {
+    return null;
+  }
+  // bytes=-500 is the last 500 bytes
+  const start = match[1] ? Number(match[1]) : Math.max(0, size - Number(match[2]));
+  const end = match[1] && match[2] ? Math.min(Number(match[2]), size - 1) : size - 1;
+  if (start > end || start >= size) {
+    throw new PlasmaError('invalid_request', `Range ${header} is outside the ${size} byte log`);
+  }
+  return { start, end };
+}
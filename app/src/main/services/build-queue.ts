@@ -0,0 +1,107 @@
+import * as os from 'os';
+import { getSettings } from './database';
+import { PlasmaError } from './errors';
+
+/**
+ * Limits how many xcodebuild builds run at once, so several projects (an
+ * app and its frameworks, say) can build side by side without thrashing
+ * the machine. Builds past the limit wait in line and start in the order
+ * they were queued. While more than one build may run, each gets a share
+ * of the cores through xcodebuild's -jobs.
+ */
+
+export interface BuildQueueStatus {
+  concurrency: number;
+  running: number;
+  queued: number;
+  /** The -jobs each build is limited to, or null when builds use every core */
+  jobsPerBuild: number | null;
+}
+
+export interface BuildSlot {
+  /** Value for xcodebuild -jobs, or null to leave it to xcodebuild */
+  jobs: number | null;
+  release: () => void;
+}
+
+const DEFAULT_CONCURRENCY = 1;
+export const MAX_BUILD_CONCURRENCY = 8;
+
+let running = 0;
+const waiting: Array<() => void> = [];
+
+/**
+ * How many builds may run at once, from the buildConcurrency setting
+ */
+export function getBuildConcurrency(): number {
+  const value = parseInt(getSettings().buildConcurrency ?? '');
+  return Number.isInteger(value) && value >= 1 ? Math.min(value, MAX_BUILD_CONCURRENCY) : DEFAULT_CONCURRENCY;
+}
+
+/**
+ * Parallel compile tasks per build: the buildJobs setting, or the cores
+ * split evenly between the builds that may run at once
+ */
+export function getJobsPerBuild(concurrency = getBuildConcurrency()): number | null {
+  const configured = parseInt(getSettings().buildJobs ?? '');
+  if (Number.isInteger(configured) && configured >= 1) {
+    return configured;
+  }
+  return concurrency > 1 ? Math.max(1, Math.floor(os.cpus().length / concurrency)) : null;
+}
+
+export function getBuildQueueStatus(): BuildQueueStatus {
+  const concurrency = getBuildConcurrency();
+  return { concurrency, running, queued: waiting.length, jobsPerBuild: getJobsPerBuild(concurrency) };
+}
+
+function startWaiting(): void {
+  while (waiting.length > 0 && running < getBuildConcurrency()) {
+    running++;
+    waiting.shift()!();
+  }
+}
+
+/**
+ * Wait for a free build slot. onQueued is told the build's place in line
+ * (1 for next) when it has to wait. Aborting signal takes a waiting build
+ * out of line and rejects with cancelled.
+ */
+export function acquireBuildSlot(onQueued?: (position: number) => void, signal?: AbortSignal): Promise<BuildSlot> {
+  return new Promise((resolve, reject) => {
+    if (signal?.aborted) {
+      reject(new PlasmaError('cancelled', 'The build was cancelled before it started'));
+      return;
+    }
+
+    let released = false;
+    const cancel = () => {
+      const index = waiting.indexOf(grant);
+      if (index !== -1) {
+        waiting.splice(index, 1);
+        reject(new PlasmaError('cancelled', 'The build was cancelled before it started'));
+      }
+    };
+    const grant = () => {
+      signal?.removeEventListener('abort', cancel);
+      resolve({
+        jobs: getJobsPerBuild(),
+        release: () => {
+          if (released) return;
+          released = true;
+          running--;
+          startWaiting();
+        },
+      });
+    };
+
+    if (running < getBuildConcurrency() && waiting.length === 0) {
+      running++;
+      grant();
+      return;
+    }
+    waiting.push(grant);
+    signal?.addEventListener('abort', cancel, { once: true });
+    onQueued?.(waiting.length);
+  });
+}
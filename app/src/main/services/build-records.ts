@@ -0,0 +1,265 @@
+import * as crypto from 'crypto';
+import { BuildEnvironment, BuildEnvironmentDiff, diffBuildEnvironments } from './build-environment';
+import { BuildRecord, getBuild, getBuilds, saveBuild } from './database';
+import { PlasmaError } from './errors';
+import type { GitInfo } from './git';
+
+/**
+ * History of builds with the errors and warnings xcodebuild reported, so a
+ * finished build's diagnostics can be looked up and exported for CI.
+ */
+
+export type DiagnosticSeverity = 'error' | 'warning';
+
+export interface BuildDiagnostic {
+  severity: DiagnosticSeverity;
+  message: string;
+  file?: string;
+  line?: number;
+  column?: number;
+}
+
+export interface BuildSummary {
+  id: string;
+  projectPath: string;
+  scheme: string;
+  configuration: string;
+  success: boolean;
+  startedAt: string;
+  finishedAt: string;
+  errors: number;
+  warnings: number;
+  diagnostics: BuildDiagnostic[];
+  /** Why the build failed when it wasn't a compiler error (hook, timeout) */
+  error: string | null;
+  /** null for builds stored before environments were recorded */
+  environment: BuildEnvironment | null;
+  /** null outside a git repository and for builds stored before it was recorded */
+  git: GitInfo | null;
+}
+
+export interface WarningTrendPoint {
+  buildId: string;
+  scheme: string;
+  configuration: string;
+  startedAt: string;
+  warnings: number;
+}
+
+/** Warnings of one file or one rule across stored builds */
+export interface WarningGroup {
+  /** File path, or the rule */
+  name: string;
+  /** Warnings in all builds together */
+  occurrences: number;
+  /** Builds that reported at least one */
+  builds: number;
+  lastSeenAt: string;
+  /** Line of the latest warning, for files */
+  line?: number;
+  /** Latest message, for rules */
+  message?: string;
+}
+
+export interface WarningsReport {
+  builds: number;
+  /** Warnings per build, oldest first */
+  trend: WarningTrendPoint[];
+  byFile: WarningGroup[];
+  byRule: WarningGroup[];
+}
+
+// /path/File.swift:12:5: error: cannot find 'x' in scope
+const LOCATED_DIAGNOSTIC = /^(\/.+?):(\d+)(?::(\d+))?: (error|warning): (.+)$/;
+// error: Signing requires a development team / ld: warning: ...
+const UNLOCATED_DIAGNOSTIC = /^(?:[\w-]+: )?(error|warning): (.+)$/;
+
+/**
+ * Parse a compiler, linker, or xcodebuild diagnostic from a line of build output
+ */
+export function parseBuildDiagnostic(line: string): BuildDiagnostic | null {
+  const trimmed = line.trim();
+  const located = trimmed.match(LOCATED_DIAGNOSTIC);
+  if (located) {
+    const [, file, lineNumber, column, severity, message] = located;
+    return {
+      severity: severity as DiagnosticSeverity,
+      message,
+      file,
+      line: Number(lineNumber),
+      column: column ? Number(column) : undefined,
+    };
+  }
+  const unlocated = trimmed.match(UNLOCATED_DIAGNOSTIC);
+  if (unlocated) {
+    return { severity: unlocated[1] as DiagnosticSeverity, message: unlocated[2] };
+  }
+  return null;
+}
+
+/**
+ * Collects the diagnostics of a build, dropping the repeats xcodebuild
+ * prints when it summarizes failures at the end
+ */
+export class DiagnosticCollector {
+  readonly diagnostics: BuildDiagnostic[] = [];
+  private seen = new Set<string>();
+
+  add(line: string): void {
+    const diagnostic = parseBuildDiagnostic(line);
+    if (!diagnostic) return;
+    const key = `${diagnostic.severity}|${diagnostic.file}|${diagnostic.line}|${diagnostic.column}|${diagnostic.message}`;
+    if (this.seen.has(key)) return;
+    this.seen.add(key);
+    this.diagnostics.push(diagnostic);
+  }
+}
+
+function toSummary(record: BuildRecord): BuildSummary {
+  const diagnostics: BuildDiagnostic[] = JSON.parse(record.diagnostics);
+  return {
+    id: record.id,
+    projectPath: record.project_path,
+    scheme: record.scheme,
+    configuration: record.configuration,
+    success: record.success === 1,
+    startedAt: record.started_at,
+    finishedAt: record.finished_at,
+    errors: diagnostics.filter((diagnostic) => diagnostic.severity === 'error').length,
+    warnings: diagnostics.filter((diagnostic) => diagnostic.severity === 'warning').length,
+    diagnostics,
+    error: record.error,
+    environment: record.environment ? JSON.parse(record.environment) : null,
+    git: record.git ? JSON.parse(record.git) : null,
+  };
+}
+
+/**
+ * Store a finished build, returning its id
+ */
+export function recordBuild(build: {
+  /** Generated when omitted */
+  id?: string;
+  projectPath: string;
+  scheme: string;
+  configuration: string;
+  success: boolean;
+  startedAt: string;
+  diagnostics: BuildDiagnostic[];
+  error?: string;
+  environment?: BuildEnvironment | null;
+  git?: GitInfo | null;
+}): string {
+  const id = build.id ?? crypto.randomUUID();
+  saveBuild({
+    id,
+    project_path: build.projectPath,
+    scheme: build.scheme,
+    configuration: build.configuration,
+    success: build.success ? 1 : 0,
+    started_at: build.startedAt,
+    finished_at: new Date().toISOString(),
+    diagnostics: JSON.stringify(build.diagnostics),
+    error: build.error ?? null,
+    environment: build.environment ? JSON.stringify(build.environment) : null,
+    git: build.git ? JSON.stringify(build.git) : null,
+  });
+  return id;
+}
+
+export function getBuildById(id: string): BuildSummary {
+  const record = getBuild(id);
+  if (!record) {
+    throw new PlasmaError('not_found', `Build ${id} not found`);
+  }
+  return toSummary(record);
+}
+
+/**
+ * What changed in the environment from one build to another, to explain why
+ * the same project builds on one machine or day and not on another
+ */
+export function getBuildEnvironmentDiff(fromId: string, toId: string): BuildEnvironmentDiff {
+  const from = getBuildById(fromId);
+  const to = getBuildById(toId);
+  for (const build of [from, to]) {
+    if (!build.environment) {
+      throw new PlasmaError('not_found', `Build ${build.id} has no recorded environment`);
+    }
+  }
+  return { from: from.id, to: to.id, changes: diffBuildEnvironments(from.environment!, to.environment!) };
+}
+
+/**
+ * Stored builds, newest first
+ */
+export function listBuilds(projectPath?: string, limit?: number): BuildSummary[] {
+  return getBuilds(projectPath, limit).map(toSummary);
+}
+
+/**
+ * What kind of warning a message is: the clang flag or Swift diagnostic
+ * group when the compiler prints one, otherwise the message with quoted
+ * names left out so the same warning about different symbols groups together
+ */
+export function warningRule(message: string): string {
+  const flag = message.match(/\[(-W[\w-]+)\]$/);
+  if (flag) return flag[1];
+  const group = message.match(/\[#(\w+)\]$/);
+  if (group) return group[1];
+  return message.replace(/'[^']*'/g, "'…'").replace(/"[^"]*"/g, '"…"');
+}
+
+/**
+ * Warnings of the latest stored builds grouped by file and by rule. Only
+ * files that were compiled report warnings, so incremental builds show
+ * fewer than clean ones.
+ */
+export function getWarningsReport(projectPath?: string, limit: number = 50): WarningsReport {
+  const builds = listBuilds(projectPath, limit);
+  const byFile = new Map<string, WarningGroup>();
+  const byRule = new Map<string, WarningGroup>();
+
+  const count = (groups: Map<string, WarningGroup>, name: string, build: BuildSummary, seen: Set<string>) => {
+    const group = groups.get(name) ?? { name, occurrences: 0, builds: 0, lastSeenAt: build.startedAt };
+    group.occurrences++;
+    if (!seen.has(name)) {
+      seen.add(name);
+      group.builds++;
+    }
+    groups.set(name, group);
+    return group;
+  };
+
+  // Builds are newest first, so the first warning seen of a group is the latest
+  for (const build of builds) {
+    const filesSeen = new Set<string>();
+    const rulesSeen = new Set<string>();
+    for (const diagnostic of build.diagnostics) {
+      if (diagnostic.severity !== 'warning') continue;
+      if (diagnostic.file) {
+        const file = count(byFile, diagnostic.file, build, filesSeen);
+        file.line ??= diagnostic.line;
+      }
+      const rule = count(byRule, warningRule(diagnostic.message), build, rulesSeen);
+      rule.message ??= diagnostic.message;
+    }
+  }
+
+  const sorted = (groups: Map<string, WarningGroup>) =>
+    [...groups.values()].sort((a, b) => b.builds - a.builds || b.occurrences - a.occurrences);
+  return {
+    builds: builds.length,
+    trend: builds
+      .map((build) => ({
+        buildId: build.id,
+        scheme: build.scheme,
+        configuration: build.configuration,
+        startedAt: build.startedAt,
+        warnings: build.warnings,
+      }))
+      .reverse(),
+    byFile: sorted(byFile),
+    byRule: sorted(byRule),
+  };
+}
@@ -0,0 +1,297 @@
+import * as crypto from 'crypto';
+import * as fs from 'fs';
+import {
+  BuildTriggerRecord,
+  deleteBuildTrigger,
+  getBuildTrigger,
+  getBuildTriggers,
+  getSettings,
+  saveBuildTrigger,
+  updateSettings,
+} from './database';
+import { cronMatches, parseCron } from './cron';
+import { PlasmaError } from './errors';
+import { buildScheme } from './xcode';
+import type { BuildTrigger, CreateBuildTriggerRequest, TriggerBuildEvent } from '../../shared/ipc-types';
+
+/**
+ * Builds started without anyone at the UI: on a cron schedule, or when a
+ * git host posts a push to /api/triggers/git. Together they turn a
+ * headless Plasma into a small CI for personal projects. Triggers build the
+ * checkout on disk as it is, so pushes only help when something (a hook,
+ * a preBuild `git pull`) keeps that checkout up to date. Webhooks are only
+ * accepted once a trigger secret is set, and the secret never leaves the
+ * settings it's stored in.
+ */
+
+export type { BuildTrigger, CreateBuildTriggerRequest, TriggerBuildEvent };
+
+export type TriggerReason = TriggerBuildEvent['reason'];
+
+export type TriggerBuildState = TriggerBuildEvent['state'];
+
+/** A push reported by GitHub, GitLab, or a plain { repository, branch } body */
+export interface GitPush {
+  repositories: string[];
+  branch: string | null;
+}
+
+/** Setting holding the secret webhooks are signed with */
+export const TRIGGER_SECRET_SETTING = 'triggerSecret';
+
+// Triggers building right now, so a slow build isn't started again on top of itself
+const runningTriggers = new Set<string>();
+let schedulerTimer: ReturnType<typeof setTimeout> | null = null;
+
+function toBuildTrigger(record: BuildTriggerRecord): BuildTrigger {
+  return {
+    id: record.id,
+    projectPath: record.project_path,
+    scheme: record.scheme,
+    configuration: record.configuration,
+    schedule: record.schedule,
+    push: record.on_push ? { repository: record.push_repository, branch: record.push_branch } : null,
+    lastRunAt: record.last_run_at,
+    lastBuildId: record.last_build_id,
+    lastSuccess: record.last_success === null ? null : record.last_success === 1,
+    createdAt: record.created_at,
+  };
+}
+
+export function listBuildTriggers(): BuildTrigger[] {
+  return getBuildTriggers().map(toBuildTrigger);
+}
+
+/**
+ * Store a trigger after checking its project and schedule
+ */
+export function createBuildTrigger(request: CreateBuildTriggerRequest): BuildTrigger {
+  if (typeof request?.projectPath !== 'string' || typeof request.scheme !== 'string' || !request.scheme) {
+    throw new PlasmaError('invalid_request', 'projectPath and scheme are required');
+  }
+  if (!fs.existsSync(request.projectPath)) {
+    throw new PlasmaError('not_found', `${request.projectPath} does not exist`);
+  }
+  if (!request.schedule && !request.push) {
+    throw new PlasmaError('invalid_request', 'A trigger needs a schedule, push, or both');
+  }
+  if (request.schedule) {
+    parseCron(request.schedule);
+  }
+  const record: BuildTriggerRecord = {
+    id: crypto.randomUUID(),
+    project_path: request.projectPath,
+    scheme: request.scheme,
+    configuration: request.configuration || 'Debug',
+    schedule: request.schedule?.trim() || null,
+    on_push: request.push ? 1 : 0,
+    push_repository: request.push?.repository || null,
+    push_branch: request.push?.branch || null,
+    last_run_at: null,
+    last_build_id: null,
+    last_success: null,
+    created_at: new Date().toISOString(),
+  };
+  saveBuildTrigger(record);
+  return toBuildTrigger(record);
+}
+
+export function removeBuildTrigger(id: string): void {
+  if (!deleteBuildTrigger(id)) {
+    throw new PlasmaError('not_found', `Build trigger ${id} not found`);
+  }
+}
+
+/**
+ * Build a trigger's project, reporting progress through onEvent. Resolves
+ * once the build finishes; a trigger already building is skipped.
+ */
+export async function runBuildTrigger(
+  id: string,
+  reason: TriggerReason,
+  onEvent: (event: TriggerBuildEvent) => void
+): Promise<void> {
+  const record = getBuildTrigger(id);
+  if (!record) {
+    throw new PlasmaError('not_found', `Build trigger ${id} not found`);
+  }
+  const report = (state: TriggerBuildState, extra: Partial<TriggerBuildEvent> = {}) =>
+    onEvent({
+      triggerId: id,
+      projectPath: record.project_path,
+      scheme: record.scheme,
+      reason,
+      state,
+      timestamp: new Date().toISOString(),
+      ...extra,
+    });
+
+  if (runningTriggers.has(id)) {
+    report('skipped', { message: 'The previous build of this trigger is still running' });
+    return;
+  }
+  runningTriggers.add(id);
+  report('building', { message: `Building ${record.scheme} (${reason})` });
+  try {
+    const result = await buildScheme(
+      record.project_path,
+      record.scheme,
+      record.configuration,
+      getSettings().derivedDataPath || undefined
+    );
+    saveBuildTrigger({
+      ...(getBuildTrigger(id) ?? record),
+      last_run_at: new Date().toISOString(),
+      last_build_id: result.buildId ?? null,
+      last_success: result.success ? 1 : 0,
+    });
+    report(result.success ? 'succeeded' : 'failed', { buildId: result.buildId, message: result.error });
+  } catch (err) {
+    report('failed', { message: err instanceof Error ? err.message : String(err) });
+  } finally {
+    runningTriggers.delete(id);
+  }
+}
+
+/**
+ * Start checking schedules at the top of every minute
+ */
+export function startBuildScheduler(onEvent: (event: TriggerBuildEvent) => void): void {
+  stopBuildScheduler();
+  const tick = () => {
+    const now = new Date();
+    for (const trigger of getBuildTriggers()) {
+      try {
+        if (trigger.schedule && cronMatches(parseCron(trigger.schedule), now)) {
+          runBuildTrigger(trigger.id, 'schedule', onEvent).catch((err) => {
+            console.error(`[triggers] Scheduled build of ${trigger.scheme} failed:`, err);
+          });
+        }
+      } catch (err) {
+        console.warn(`[triggers] Skipping trigger ${trigger.id}:`, err);
+      }
+    }
+    schedule();
+  };
+  const schedule = () => {
+    // A second past the minute, so timer drift never lands in the previous one
+    schedulerTimer = setTimeout(tick, 61_000 - (Date.now() % 60_000));
+  };
+  schedule();
+}
+
+export function stopBuildScheduler(): void {
+  if (schedulerTimer) {
+    clearTimeout(schedulerTimer);
+    schedulerTimer = null;
+  }
+}
+
+/**
+ * Read the repository and branch of a push webhook from GitHub, GitLab, or
+ * a plain { "repository": "owner/app", "branch": "main" } body
+ */
+export function parseGitPush(body: any): GitPush {
+  const repository = body?.repository;
+  const project = body?.project;
+  const repositories = [
+    typeof repository === 'string' ? repository : null,
+    repository?.full_name,
+    repository?.clone_url,
+    repository?.ssh_url,
+    repository?.html_url,
+    project?.path_with_namespace,
+    project?.git_http_url,
+    project?.git_ssh_url,
+    project?.web_url,
+  ].filter((value): value is string => typeof value === 'string' && value.length > 0);
+  const ref = typeof body?.ref === 'string' ? body.ref : null;
+  const branch = typeof body?.branch === 'string' ? body.branch : ref?.startsWith('refs/heads/') ? ref.slice(11) : null;
+  return { repositories, branch };
+}
+
+function normalizeRepository(repository: string): string {
+  return repository
+    .toLowerCase()
+    .replace(/\.git$/, '')
+    .replace(/^(https?:\/\/|ssh:\/\/|git@)/, '')
+    .replace(/:/g, '/');
+}
+
+/**
+ * Whether a push-triggered build should run for a push
+ */
+export function pushMatches(trigger: BuildTrigger, push: GitPush): boolean {
+  if (!trigger.push) {
+    return false;
+  }
+  if (trigger.push.branch && trigger.push.branch !== push.branch) {
+    return false;
+  }
+  const wanted = trigger.push.repository ? normalizeRepository(trigger.push.repository) : null;
+  // owner/app matches the end of a URL like github.com/owner/app
+  return (
+    !wanted ||
+    push.repositories.map(normalizeRepository).some((name) => name === wanted || name.endsWith(`/${wanted}`))
+  );
+}
+
+/**
+ * Store the secret webhooks have to be signed with, generating one when
+ * none is given
+ */
+export function setTriggerSecret(secret?: unknown): string {
+  if (secret !== undefined && (typeof secret !== 'string' || secret.length < 16)) {
+    throw new PlasmaError('invalid_request', 'secret must be a string of at least 16 characters');
+  }
+  const value = secret ?? crypto.randomBytes(24).toString('base64url');
+  updateSettings({ [TRIGGER_SECRET_SETTING]: value });
+  return value;
+}
+
+/**
+ * Check a webhook against the trigger secret: GitHub's X-Hub-Signature-256
+ * HMAC of the body, or the secret itself in X-Gitlab-Token or
+ * X-Plasma-Token. Without a secret, no push is accepted.
+ */
+export function verifyWebhook(headers: Record<string, string | string[] | undefined>, rawBody: Buffer): void {
+  const secret = getSettings()[TRIGGER_SECRET_SETTING];
+  if (!secret) {
+    throw new PlasmaError('unauthorized', 'Set a trigger secret with POST /api/triggers/secret to accept webhooks');
+  }
+  const header = (name: string) => {
+    const value = headers[name];
+    return Array.isArray(value) ? value[0] : value;
+  };
+  // Compared as bytes, since timingSafeEqual throws on buffers of different lengths
+  const equal = (a: string, b: string) => {
+    const [bytesA, bytesB] = [Buffer.from(a), Buffer.from(b)];
+    return bytesA.length === bytesB.length && crypto.timingSafeEqual(bytesA, bytesB);
+  };
+
+  const signature = header('x-hub-signature-256');
+  if (signature) {
+    const expected = `sha256=${crypto.createHmac('sha256', secret).update(rawBody).digest('hex')}`;
+    if (equal(signature, expected)) {
+      return;
+    }
+  }
+  const token = header('x-gitlab-token') ?? header('x-plasma-token');
+  if (token && equal(token, secret)) {
+    return;
+  }
+  throw new PlasmaError('unauthorized', 'The webhook signature or token does not match the trigger secret');
+}
+
+/**
+ * Start the builds of every trigger matching a push, returning their ids
+ */
+export function handleGitPush(push: GitPush, onEvent: (event: TriggerBuildEvent) => void): string[] {
+  const matching = listBuildTriggers().filter((trigger) => pushMatches(trigger, push));
+  for (const trigger of matching) {
+    runBuildTrigger(trigger.id, 'push', onEvent).catch((err) => {
+      console.error(`[triggers] Push build of ${trigger.scheme} failed:`, err);
+    });
+  }
+  return matching.map((trigger) => trigger.id);
+}
@@ -0,0 +1,241 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import { PlasmaError } from './errors';
+import { runCommand, TOOL_COMMAND } from './exec';
+import { readAppBundleInfo } from './plist';
+
+/**
+ * Size breakdown of a built .app: every file is put in one category, with
+ * frameworks and localizations also totalled one by one. The main binary's
+ * segments come from `size -m`, and when a link map is given (written with
+ * LD_GENERATE_MAP_FILE=YES) its symbols are totalled per object file and
+ * library to show what the code size comes from.
+ */
+
+export type SizeCategory = 'binaries' | 'frameworks' | 'plugins' | 'assets' | 'localizations' | 'other';
+
+export interface SizeEntry {
+  name: string;
+  bytes: number;
+}
+
+export interface SizedFile {
+  /** Path inside the bundle */
+  path: string;
+  bytes: number;
+  category: SizeCategory;
+}
+
+export interface BinarySize {
+  path: string;
+  bytes: number;
+  /** Segments of the first architecture, in memory */
+  segments: SizeEntry[];
+  /** Code and data per object file or library, from the link map */
+  contributors?: SizeEntry[];
+}
+
+export interface BundleSizeReport {
+  path: string;
+  bundleId: string;
+  totalBytes: number;
+  categories: Array<SizeEntry & { name: SizeCategory; files: number }>;
+  frameworks: SizeEntry[];
+  /** Per language, from the .lproj folders */
+  localizations: SizeEntry[];
+  largestFiles: SizedFile[];
+  binary: BinarySize | null;
+}
+
+const LARGEST_FILES = 20;
+const TOP_CONTRIBUTORS = 30;
+
+const ASSET_EXTENSIONS = new Set([
+  '.car',
+  '.png',
+  '.jpg',
+  '.jpeg',
+  '.heic',
+  '.gif',
+  '.pdf',
+  '.svg',
+  '.ttf',
+  '.otf',
+  '.mp3',
+  '.m4a',
+  '.wav',
+  '.caf',
+  '.mp4',
+  '.mov',
+  '.json',
+  '.storyboardc',
+  '.nib',
+  '.mlmodelc',
+]);
+
+// MH_MAGIC_64, MH_CIGAM_64, and FAT_MAGIC, as the first four bytes read big-endian
+const MACH_O_MAGICS = new Set([0xfeedfacf, 0xcffaedfe, 0xcafebabe]);
+
+function isMachO(file: string): boolean {
+  const fd = fs.openSync(file, 'r');
+  try {
+    const header = Buffer.alloc(4);
+    return fs.readSync(fd, header, 0, 4, 0) === 4 && MACH_O_MAGICS.has(header.readUInt32BE(0));
+  } finally {
+    fs.closeSync(fd);
+  }
+}
+
+function walk(dir: string, onFile: (file: string, bytes: number) => void): void {
+  for (const entry of fs.readdirSync(dir, { withFileTypes: true })) {
+    const entryPath = path.join(dir, entry.name);
+    if (entry.isDirectory()) {
+      walk(entryPath, onFile);
+    } else if (entry.isFile()) {
+      onFile(entryPath, fs.statSync(entryPath).size);
+    }
+  }
+}
+
+/**
+ * Category of a file from its path inside the bundle, with the
+ * Contents/ prefix of Mac apps already removed
+ */
+export function categorize(relativePath: string, machO: boolean): SizeCategory {
+  const parts = relativePath.split(/[\\/]/);
+  if (parts[0] === 'Frameworks') return 'frameworks';
+  if (parts[0] === 'PlugIns' || parts[0] === 'Extensions') return 'plugins';
+  if (parts.some((part) => part.endsWith('.lproj'))) return 'localizations';
+  if (machO) return 'binaries';
+  if (parts.some((part) => ASSET_EXTENSIONS.has(path.extname(part).toLowerCase()))) return 'assets';
+  return 'other';
+}
+
+function increment(totals: Map<string, number>, name: string, bytes: number): void {
+  totals.set(name, (totals.get(name) ?? 0) + bytes);
+}
+
+function sortedEntries(totals: Map<string, number>, limit?: number): SizeEntry[] {
+  return [...totals.entries()]
+    .map(([name, bytes]) => ({ name, bytes }))
+    .sort((a, b) => b.bytes - a.bytes)
+    .slice(0, limit);
+}
+
+/**
+ * Parse `size -m`, keeping the first architecture of a universal binary.
+ * __PAGEZERO is left out since it takes no space in the file.
+ */
+export function parseSegmentSizes(output: string): SizeEntry[] {
+  const segments: SizeEntry[] = [];
+  let architectures = 0;
+  for (const line of output.split('\n')) {
+    if (/\(for architecture [^)]+\):$/.test(line) && ++architectures > 1) {
+      break;
+    }
+    const match = line.match(/^Segment (\S+): (\d+)/);
+    if (match && match[1] !== '__PAGEZERO') {
+      segments.push({ name: match[1], bytes: Number(match[2]) });
+    }
+  }
+  return segments;
+}
+
+/**
+ * Total symbol sizes per object file in an ld link map. Objects from a
+ * static library (libFoo.a(Bar.o)) count toward the library.
+ */
+export function parseLinkMap(text: string): SizeEntry[] {
+  const objects = new Map<number, string>();
+  const totals = new Map<string, number>();
+  let section: 'objects' | 'symbols' | null = null;
+
+  for (const line of text.split('\n')) {
+    if (line.startsWith('#')) {
+      section = line.startsWith('# Object files:') ? 'objects' : line.startsWith('# Symbols:') ? 'symbols' : section;
+      if (line.startsWith('# Sections:') || line.startsWith('# Dead Stripped Symbols:')) {
+        section = null;
+      }
+      continue;
+    }
+    if (section === 'objects') {
+      const match = line.match(/^\[\s*(\d+)\] (.+)$/);
+      if (match) {
+        const file = match[2].trim();
+        const library = file.match(/^(.+\.a)\(.+\)$/)?.[1];
+        objects.set(Number(match[1]), path.basename(library ?? file));
+      }
+    } else if (section === 'symbols') {
+      const match = line.match(/^0x[0-9A-Fa-f]+\s+0x([0-9A-Fa-f]+)\s+\[\s*(\d+)\]/);
+      const object = match && objects.get(Number(match[2]));
+      if (object) {
+        increment(totals, object, parseInt(match[1], 16));
+      }
+    }
+  }
+  return sortedEntries(totals, TOP_CONTRIBUTORS);
+}
+
+/**
+ * Analyze where the size of a built .app goes
+ */
+export async function analyzeBundleSize(appPath: string, linkMapPath?: string): Promise<BundleSizeReport> {
+  if (!appPath.endsWith('.app') || !fs.existsSync(appPath)) {
+    throw new PlasmaError('not_found', `No app bundle at ${appPath}`);
+  }
+  if (linkMapPath && !fs.existsSync(linkMapPath)) {
+    throw new PlasmaError('not_found', `No link map at ${linkMapPath}`);
+  }
+
+  const info = readAppBundleInfo(appPath);
+  const contents = fs.existsSync(path.join(appPath, 'Contents', 'Info.plist')) ? path.join(appPath, 'Contents') : appPath;
+  const categories = new Map<SizeCategory, { bytes: number; files: number }>();
+  const frameworks = new Map<string, number>();
+  const localizations = new Map<string, number>();
+  const files: SizedFile[] = [];
+
+  walk(appPath, (file, bytes) => {
+    const relativePath = path.relative(contents, file);
+    const category = categorize(relativePath, isMachO(file));
+    const total = categories.get(category) ?? { bytes: 0, files: 0 };
+    categories.set(category, { bytes: total.bytes + bytes, files: total.files + 1 });
+    files.push({ path: path.relative(appPath, file), bytes, category });
+
+    const parts = relativePath.split(path.sep);
+    if (category === 'frameworks' && parts.length > 1) {
+      increment(frameworks, parts[1], bytes);
+    }
+    const lproj = parts.find((part) => part.endsWith('.lproj'));
+    if (lproj) {
+      increment(localizations, path.basename(lproj, '.lproj'), bytes);
+    }
+  });
+
+  const executablePath = path.join(
+    contents,
+    contents === appPath ? '' : 'MacOS',
+    info.executable ?? path.basename(appPath, '.app')
+  );
+  let binary: BinarySize | null = null;
+  if (fs.existsSync(executablePath)) {
+    binary = {
+      path: path.relative(appPath, executablePath),
+      bytes: fs.statSync(executablePath).size,
+      segments: parseSegmentSizes(await runCommand('size', ['-m', executablePath], TOOL_COMMAND)),
+      contributors: linkMapPath ? parseLinkMap(fs.readFileSync(linkMapPath, 'utf-8')) : undefined,
+    };
+  }
+
+  return {
+    path: appPath,
+    bundleId: info.bundleId,
+    totalBytes: files.reduce((sum, file) => sum + file.bytes, 0),
+    categories: [...categories.entries()]
+      .map(([name, total]) => ({ name, ...total }))
+      .sort((a, b) => b.bytes - a.bytes),
+    frameworks: sortedEntries(frameworks),
+    localizations: sortedEntries(localizations),
+    largestFiles: files.sort((a, b) => b.bytes - a.bytes).slice(0, LARGEST_FILES),
+    binary,
+  };
+}
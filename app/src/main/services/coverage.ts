@@ -0,0 +1,193 @@
+import {
+  CoverageFileRecord,
+  CoverageTargetRecord,
+  getCoverageFiles,
+  getCoverageTargets,
+  getTestRun,
+  saveCoverage,
+} from './database';
+import { PlasmaError } from './errors';
+import { runCommand, TOOL_COMMAND } from './exec';
+
+/**
+ * Code coverage of test runs. xcodebuild records coverage in the run's
+ * .xcresult bundle; the per-target and per-file summary from
+ * `xccov view --report` is stored so it outlives the bundle, while
+ * line-level data is read from the bundle on demand.
+ */
+
+export interface CoverageStats {
+  coveredLines: number;
+  executableLines: number;
+  /** Fraction (0-1) of executable lines that ran */
+  lineCoverage: number;
+}
+
+export interface FileCoverage extends CoverageStats {
+  name: string;
+  path: string;
+}
+
+export interface TargetCoverage extends CoverageStats {
+  name: string;
+  files: FileCoverage[];
+}
+
+export interface CoverageReport extends CoverageStats {
+  testRunId: string;
+  targets: TargetCoverage[];
+}
+
+export interface LineCoverage {
+  line: number;
+  /** Null for lines that aren't executable (comments, declarations) */
+  executionCount: number | null;
+}
+
+export interface FileLineCoverage {
+  testRunId: string;
+  path: string;
+  lines: LineCoverage[];
+}
+
+function toStats(stats: CoverageStats): CoverageStats {
+  return {
+    coveredLines: stats.coveredLines,
+    executableLines: stats.executableLines,
+    lineCoverage: stats.lineCoverage,
+  };
+}
+
+/**
+ * Sum of stats, with coverage recomputed from the line counts
+ */
+function totalStats(items: CoverageStats[]): CoverageStats {
+  const coveredLines = items.reduce((sum, item) => sum + item.coveredLines, 0);
+  const executableLines = items.reduce((sum, item) => sum + item.executableLines, 0);
+  return { coveredLines, executableLines, lineCoverage: executableLines > 0 ? coveredLines / executableLines : 0 };
+}
+
+/**
+ * Parse the JSON of `xccov view --report --json`
+ */
+export function parseCoverageReport(testRunId: string, json: string): CoverageReport {
+  const report = JSON.parse(json);
+  const targets: TargetCoverage[] = (Array.isArray(report.targets) ? report.targets : []).map(
+    (target: Record<string, any>) => ({
+      name: String(target.name),
+      ...toStats(target as CoverageStats),
+      files: (Array.isArray(target.files) ? target.files : []).map((file: Record<string, any>) => ({
+        name: String(file.name),
+        path: String(file.path),
+        ...toStats(file as CoverageStats),
+      })),
+    })
+  );
+  return { testRunId, ...toStats(report), targets };
+}
+
+/**
+ * Parse the JSON of `xccov view --archive --file <path> --json`, which maps
+ * the file's path to one entry per line
+ */
+export function parseLineCoverage(testRunId: string, filePath: string, json: string): FileLineCoverage {
+  const archive = JSON.parse(json) as Record<string, Array<Record<string, any>>>;
+  const entries = archive[filePath] ?? Object.values(archive)[0] ?? [];
+  return {
+    testRunId,
+    path: filePath,
+    lines: entries.map((entry) => ({
+      line: Number(entry.line),
+      executionCount: entry.isExecutable ? Number(entry.executionCount ?? 0) : null,
+    })),
+  };
+}
+
+/**
+ * Read the coverage of a result bundle and store it for a test run
+ */
+export async function recordCoverage(testRunId: string, resultBundlePath: string): Promise<CoverageReport> {
+  const report = parseCoverageReport(
+    testRunId,
+    await runCommand('xcrun', ['xccov', 'view', '--report', '--json', resultBundlePath], TOOL_COMMAND)
+  );
+
+  const targets: CoverageTargetRecord[] = report.targets.map((target) => ({
+    test_run_id: testRunId,
+    name: target.name,
+    covered_lines: target.coveredLines,
+    executable_lines: target.executableLines,
+    line_coverage: target.lineCoverage,
+  }));
+  const files: CoverageFileRecord[] = report.targets.flatMap((target) =>
+    target.files.map((file) => ({
+      test_run_id: testRunId,
+      target: target.name,
+      path: file.path,
+      name: file.name,
+      covered_lines: file.coveredLines,
+      executable_lines: file.executableLines,
+      line_coverage: file.lineCoverage,
+    }))
+  );
+  saveCoverage(targets, files);
+  return report;
+}
+
+/**
+ * Stored coverage of a test run, per target and file
+ */
+export function getCoverageReport(testRunId: string): CoverageReport {
+  const run = getTestRun(testRunId);
+  if (!run) {
+    throw new PlasmaError('not_found', `Test run ${testRunId} not found`);
+  }
+
+  const files = getCoverageFiles(testRunId);
+  const targets: TargetCoverage[] = getCoverageTargets(testRunId).map((target) => ({
+    name: target.name,
+    coveredLines: target.covered_lines,
+    executableLines: target.executable_lines,
+    lineCoverage: target.line_coverage,
+    files: files
+      .filter((file) => file.target === target.name)
+      .map((file) => ({
+        name: file.name,
+        path: file.path,
+        coveredLines: file.covered_lines,
+        executableLines: file.executable_lines,
+        lineCoverage: file.line_coverage,
+      })),
+  }));
+  if (run.coverage_enabled !== 1 || targets.length === 0) {
+    throw new PlasmaError('not_found', `Test run ${testRunId} has no coverage data`);
+  }
+  return { testRunId, ...totalStats(targets), targets };
+}
+
+/**
+ * Per-line execution counts of a file, read from the run's result bundle
+ */
+export async function getFileLineCoverage(testRunId: string, filePath: string): Promise<FileLineCoverage> {
+  const run = getTestRun(testRunId);
+  if (!run) {
+    throw new PlasmaError('not_found', `Test run ${testRunId} not found`);
+  }
+  if (run.coverage_enabled !== 1 || !run.result_bundle_path) {
+    throw new PlasmaError('not_found', `Test run ${testRunId} has no coverage data`);
+  }
+  if (!getCoverageFiles(testRunId).some((file) => file.path === filePath)) {
+    throw new PlasmaError('not_found', `No coverage for ${filePath} in test run ${testRunId}`);
+  }
+
+  const json = await runCommand('xcrun', [
+    'xccov',
+    'view',
+    '--archive',
+    '--file',
+    filePath,
+    '--json',
+    run.result_bundle_path,
+  ], TOOL_COMMAND);
+  return parseLineCoverage(testRunId, filePath, json);
+}
@@ -0,0 +1,284 @@
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { CrashReportRecord, getCrashReport, getCrashReports, hasCrashReportAt, saveCrashReport } from './database';
+import { PlasmaError } from './errors';
+import { findBuildSymbols, symbolicateAddresses } from './symbols';
+
+/**
+ * Crash reports of apps running on simulators. Simulator apps are host
+ * processes, so their crashes land in ~/Library/Logs/DiagnosticReports as
+ * .ips files like any Mac app. Reports are collected from there,
+ * symbolicated against the symbols recorded for the build whose binary UUID
+ * they reference, and stored so they stay readable after the build is gone.
+ */
+
+export interface CrashFrame {
+  /** Image (binary) the frame is in */
+  image: string;
+  /** Absolute address, hex */
+  address: string;
+  symbol?: string;
+  file?: string;
+  line?: number;
+}
+
+export interface CrashThread {
+  index: number;
+  name?: string;
+  queue?: string;
+  crashed: boolean;
+  frames: CrashFrame[];
+}
+
+export interface CrashReportSummary {
+  id: string;
+  udid: string | null;
+  bundleId: string | null;
+  processName: string;
+  appVersion: string | null;
+  crashedAt: string;
+  exception: string | null;
+  /** Build the crashed binary came from, when Plasma built it */
+  build: { uuid: string; projectPath: string | null; builtAt: string } | null;
+  symbolicated: boolean;
+}
+
+export interface CrashReport extends CrashReportSummary {
+  reportPath: string;
+  threads: CrashThread[];
+}
+
+interface IpsImage {
+  base: number;
+  uuid?: string;
+  name?: string;
+  path?: string;
+  arch?: string;
+}
+
+interface IpsFrame {
+  imageIndex: number;
+  imageOffset: number;
+  symbol?: string;
+  symbolLocation?: number;
+  sourceFile?: string;
+  sourceLine?: number;
+}
+
+interface ParsedCrash {
+  id: string;
+  udid: string | null;
+  bundleId: string | null;
+  processName: string;
+  processPath: string | null;
+  appVersion: string | null;
+  crashedAt: string;
+  exception: string | null;
+  images: IpsImage[];
+  threads: Array<{ name?: string; queue?: string; crashed: boolean; frames: IpsFrame[] }>;
+}
+
+// Crash reports use bug type 309
+const CRASH_BUG_TYPE = '309';
+
+export function getDiagnosticReportsDir(): string {
+  return path.join(os.homedir(), 'Library', 'Logs', 'DiagnosticReports');
+}
+
+/**
+ * Parse an .ips crash report: a JSON header line followed by a JSON body.
+ * Returns null for other report types.
+ */
+export function parseIpsReport(text: string): ParsedCrash | null {
+  const newline = text.indexOf('\n');
+  if (newline === -1) {
+    return null;
+  }
+  let header: Record<string, any>;
+  let body: Record<string, any>;
+  try {
+    header = JSON.parse(text.slice(0, newline));
+    body = JSON.parse(text.slice(newline + 1));
+  } catch {
+    return null;
+  }
+  if (String(header.bug_type) !== CRASH_BUG_TYPE) {
+    return null;
+  }
+
+  const processPath: string | null = body.procPath ?? null;
+  const udid = processPath?.match(/\/CoreSimulator\/Devices\/([0-9A-Fa-f-]{36})\//)?.[1] ?? null;
+  const exception = body.exception
+    ? [body.exception.type, body.exception.signal && `(${body.exception.signal})`].filter(Boolean).join(' ')
+    : null;
+
+  return {
+    id: String(header.incident_id ?? body.incident),
+    udid,
+    bundleId: body.bundleInfo?.CFBundleIdentifier ?? header.bundleID ?? null,
+    processName: body.procName ?? header.app_name ?? header.name ?? 'Unknown',
+    processPath,
+    appVersion: body.bundleInfo?.CFBundleShortVersionString ?? header.app_version ?? null,
+    crashedAt: body.captureTime ?? header.timestamp ?? new Date().toISOString(),
+    exception,
+    images: Array.isArray(body.usedImages) ? body.usedImages : [],
+    threads: (Array.isArray(body.threads) ? body.threads : []).map((thread: Record<string, any>) => ({
+      name: thread.name,
+      queue: thread.queue,
+      crashed: thread.triggered === true,
+      frames: Array.isArray(thread.frames) ? thread.frames : [],
+    })),
+  };
+}
+
+/**
+ * Turn parsed frames into readable ones, resolving frames in binaries Plasma
+ * built with atos and keeping the OS's symbols for everything else
+ */
+async function symbolicate(crash: ParsedCrash): Promise<{ threads: CrashThread[]; buildUuid: string | null }> {
+  const threads: CrashThread[] = crash.threads.map((thread, index) => ({
+    index,
+    name: thread.name,
+    queue: thread.queue,
+    crashed: thread.crashed,
+    frames: thread.frames.map((frame) => {
+      const image = crash.images[frame.imageIndex];
+      return {
+        image: image?.name ?? '???',
+        address: `0x${((image?.base ?? 0) + frame.imageOffset).toString(16)}`,
+        symbol: frame.symbol,
+        file: frame.sourceFile,
+        line: frame.sourceLine,
+      };
+    }),
+  }));
+
+  // The build is identified by the UUID of the app's main binary
+  const mainImage = crash.images.find((image) => image.path && image.path === crash.processPath);
+  const buildUuid = mainImage?.uuid ? mainImage.uuid.toUpperCase() : null;
+
+  for (const [imageIndex, image] of crash.images.entries()) {
+    const symbols = image.uuid ? findBuildSymbols(image.uuid) : null;
+    if (!symbols) {
+      continue;
+    }
+
+    const targets: CrashFrame[] = [];
+    const addresses: number[] = [];
+    crash.threads.forEach((thread, threadIndex) => {
+      thread.frames.forEach((frame, frameIndex) => {
+        if (frame.imageIndex === imageIndex) {
+          targets.push(threads[threadIndex].frames[frameIndex]);
+          addresses.push(image.base + frame.imageOffset);
+        }
+      });
+    });
+    if (addresses.length === 0) {
+      continue;
+    }
+
+    try {
+      const resolved = await symbolicateAddresses(symbols, image.base, addresses);
+      resolved.forEach((frame, index) => {
+        if (frame) {
+          Object.assign(targets[index], frame);
+        }
+      });
+    } catch (err) {
+      console.warn(`[crash-reports] Could not symbolicate ${image.name ?? image.uuid}:`, err);
+    }
+  }
+
+  return { threads, buildUuid };
+}
+
+function toSummary(record: CrashReportRecord): CrashReportSummary {
+  const symbols = record.build_uuid ? findBuildSymbols(record.build_uuid) : null;
+  return {
+    id: record.id,
+    udid: record.udid,
+    bundleId: record.bundle_id,
+    processName: record.process_name,
+    appVersion: record.app_version,
+    crashedAt: record.crashed_at,
+    exception: record.exception,
+    build:
+      record.build_uuid && symbols
+        ? { uuid: record.build_uuid, projectPath: symbols.project_path, builtAt: symbols.built_at }
+        : null,
+    symbolicated: record.symbolicated === 1,
+  };
+}
+
+/**
+ * Import simulator crash reports that haven't been collected yet, returning how many were added
+ */
+export async function collectCrashReports(): Promise<number> {
+  const dir = getDiagnosticReportsDir();
+  if (!fs.existsSync(dir)) {
+    return 0;
+  }
+
+  let collected = 0;
+  for (const entry of fs.readdirSync(dir)) {
+    const reportPath = path.join(dir, entry);
+    if (!entry.endsWith('.ips') || hasCrashReportAt(reportPath)) {
+      continue;
+    }
+
+    let crash: ParsedCrash | null;
+    try {
+      crash = parseIpsReport(fs.readFileSync(reportPath, 'utf-8'));
+    } catch (err) {
+      console.warn(`[crash-reports] Could not read ${entry}:`, err);
+      continue;
+    }
+    // Only apps that ran on a simulator
+    if (!crash?.udid) {
+      continue;
+    }
+
+    const { threads, buildUuid } = await symbolicate(crash);
+    const symbolicated = buildUuid !== null && findBuildSymbols(buildUuid) !== null;
+    saveCrashReport({
+      id: crash.id,
+      udid: crash.udid,
+      bundle_id: crash.bundleId,
+      process_name: crash.processName,
+      app_version: crash.appVersion,
+      crashed_at: crash.crashedAt,
+      exception: crash.exception,
+      report_path: reportPath,
+      build_uuid: buildUuid,
+      symbolicated: symbolicated ? 1 : 0,
+      threads: JSON.stringify(threads),
+      collected_at: new Date().toISOString(),
+    });
+    collected++;
+  }
+
+  if (collected > 0) {
+    console.log(`[crash-reports] Collected ${collected} crash report(s)`);
+  }
+  return collected;
+}
+
+/**
+ * Collected crash reports, newest first, after picking up new ones
+ */
+export async function listCrashReports(filter: { bundleId?: string; udid?: string } = {}): Promise<CrashReportSummary[]> {
+  await collectCrashReports();
+  return getCrashReports(filter).map(toSummary);
+}
+
+/**
+ * A crash report with its symbolicated threads
+ */
+export function getCrashReportById(id: string): CrashReport {
+  const record = getCrashReport(id);
+  if (!record) {
+    throw new PlasmaError('not_found', `Crash report ${id} not found`);
+  }
+  return { ...toSummary(record), reportPath: record.report_path, threads: JSON.parse(record.threads) };
+}
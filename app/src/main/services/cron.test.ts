@@ -0,0 +1,78 @@
+import { describe, it, expect } from 'vitest';
+import { cronMatches, nextCronRun, parseCron } from './cron';
+
+describe('cron', () => {
+  describe('parseCron', () => {
+    it('expands steps, ranges, and lists', () => {
+      const schedule = parseCron('*/15 0-10/5 1,15 1-3 *');
+      expect([...schedule.minutes.values]).toEqual([0, 15, 30, 45]);
+      expect([...schedule.hours.values]).toEqual([0, 5, 10]);
+      expect([...schedule.daysOfMonth.values]).toEqual([1, 15]);
+      expect([...schedule.months.values]).toEqual([1, 2, 3]);
+    });
+
+    it('runs a step from a start value to the end of the range', () => {
+      expect([...parseCron('5/15 * * * *').minutes.values]).toEqual([5, 20, 35, 50]);
+    });
+
+    it('treats 7 as Sunday', () => {
+      expect([...parseCron('0 0 * * 5-7').daysOfWeek.values].sort()).toEqual([0, 5, 6]);
+    });
+
+    it('expands shortcuts', () => {
+      expect(parseCron('@weekly')).toEqual(parseCron('0 0 * * 0'));
+    });
+
+    it("rejects values outside a field's range", () => {
+      expect(() => parseCron('60 * * * *')).toThrow(/minute "60" is outside 0-59/);
+      expect(() => parseCron('* * 0 * *')).toThrow(/day of month/);
+    });
+
+    it('rejects the wrong number of fields', () => {
+      expect(() => parseCron('* * *')).toThrow(/must have 5 fields/);
+    });
+  });
+
+  describe('cronMatches', () => {
+    it('runs on either day when both day fields are restricted', () => {
+      const schedule = parseCron('0 9 13 * 5');
+      // Friday the 13th, a Friday, and a Tuesday the 13th
+      expect(cronMatches(schedule, new Date(2026, 1, 13, 9, 0))).toBe(true);
+      expect(cronMatches(schedule, new Date(2026, 1, 6, 9, 0))).toBe(true);
+      expect(cronMatches(schedule, new Date(2026, 0, 13, 9, 0))).toBe(true);
+      expect(cronMatches(schedule, new Date(2026, 1, 12, 9, 0))).toBe(false);
+    });
+
+    it('requires both days when one of them is unrestricted', () => {
+      const schedule = parseCron('0 9 1 * */2');
+      // Sunday the 1st, Wednesday the 1st, and a Tuesday
+      expect(cronMatches(schedule, new Date(2026, 1, 1, 9, 0))).toBe(true);
+      expect(cronMatches(schedule, new Date(2026, 3, 1, 9, 0))).toBe(false);
+      expect(cronMatches(schedule, new Date(2026, 1, 3, 9, 0))).toBe(false);
+    });
+
+    it('checks the minute, hour, and month', () => {
+      const schedule = parseCron('30 9 * 2 *');
+      expect(cronMatches(schedule, new Date(2026, 1, 3, 9, 30))).toBe(true);
+      expect(cronMatches(schedule, new Date(2026, 1, 3, 9, 31))).toBe(false);
+      expect(cronMatches(schedule, new Date(2026, 1, 3, 10, 30))).toBe(false);
+      expect(cronMatches(schedule, new Date(2026, 2, 3, 9, 30))).toBe(false);
+    });
+  });
+
+  describe('nextCronRun', () => {
+    it('finds the next matching minute', () => {
+      expect(nextCronRun(parseCron('5/15 * * * *'), new Date(2026, 0, 5, 9, 7, 30))).toEqual(
+        new Date(2026, 0, 5, 9, 20)
+      );
+    });
+
+    it('never returns the minute it starts in', () => {
+      expect(nextCronRun(parseCron('@daily'), new Date(2026, 0, 5, 0, 0))).toEqual(new Date(2026, 0, 6, 0, 0));
+    });
+
+    it('returns null for a schedule that never runs', () => {
+      expect(nextCronRun(parseCron('0 0 31 2 *'), new Date(2026, 0, 1))).toBeNull();
+    });
+  });
+});
@@ -0,0 +1,113 @@
+import { PlasmaError } from './errors';
+
+/**
+ * Cron schedules for build triggers: five fields (minute hour day-of-month
+ * month day-of-week) with lists, ranges, and steps, plus the @hourly style
+ * shortcuts. Times are matched in local time, like cron.
+ */
+
+interface CronField {
+  values: Set<number>;
+  /** Whether the field starts with `*`, which matters for day-of-month/day-of-week */
+  any: boolean;
+}
+
+export interface CronSchedule {
+  minutes: CronField;
+  hours: CronField;
+  daysOfMonth: CronField;
+  months: CronField;
+  daysOfWeek: CronField;
+}
+
+const CRON_FIELDS: Array<{ name: string; min: number; max: number }> = [
+  { name: 'minute', min: 0, max: 59 },
+  { name: 'hour', min: 0, max: 23 },
+  { name: 'day of month', min: 1, max: 31 },
+  { name: 'month', min: 1, max: 12 },
+  { name: 'day of week', min: 0, max: 7 },
+];
+
+const CRON_SHORTCUTS: Record<string, string> = {
+  '@hourly': '0 * * * *',
+  '@daily': '0 0 * * *',
+  '@nightly': '0 0 * * *',
+  '@weekly': '0 0 * * 0',
+  '@monthly': '0 0 1 * *',
+};
+
+function parseCronField(text: string, field: (typeof CRON_FIELDS)[number]): CronField {
+  const values = new Set<number>();
+  for (const part of text.split(',')) {
+    const match = part.match(/^(\*|(\d+)(?:-(\d+))?)(?:\/(\d+))?$/);
+    if (!match) {
+      throw new PlasmaError('invalid_request', `Invalid ${field.name} "${part}" in schedule`);
+    }
+    const step = match[4] ? Number(match[4]) : 1;
+    const start = match[1] === '*' ? field.min : Number(match[2]);
+    // 5/15 runs from 5 to the end of the range, like cron
+    const end = match[1] === '*' || (match[4] && !match[3]) ? field.max : Number(match[3] ?? match[2]);
+    if (step < 1 || start < field.min || end > field.max || start > end) {
+      throw new PlasmaError('invalid_request', `${field.name} "${part}" is outside ${field.min}-${field.max}`);
+    }
+    for (let value = start; value <= end; value += step) {
+      values.add(value);
+    }
+  }
+  // */2 still counts as unrestricted for the day fields, as in cron
+  return { values, any: text.startsWith('*') };
+}
+
+/**
+ * Parse a five-field cron expression, or one of @hourly, @daily, @weekly,
+ * and @monthly
+ */
+export function parseCron(expression: string): CronSchedule {
+  const fields = (CRON_SHORTCUTS[expression.trim()] ?? expression).trim().split(/\s+/);
+  if (fields.length !== 5) {
+    throw new PlasmaError('invalid_request', `Schedule "${expression}" must have 5 fields: minute hour day month weekday`);
+  }
+  const [minutes, hours, daysOfMonth, months, daysOfWeek] = fields.map((text, index) =>
+    parseCronField(text, CRON_FIELDS[index])
+  );
+  // Sunday is both 0 and 7
+  if (daysOfWeek.values.delete(7)) {
+    daysOfWeek.values.add(0);
+  }
+  return { minutes, hours, daysOfMonth, months, daysOfWeek };
+}
+
+/**
+ * Whether a schedule fires in the minute of a date, in local time
+ */
+export function cronMatches(schedule: CronSchedule, date: Date): boolean {
+  if (
+    !schedule.minutes.values.has(date.getMinutes()) ||
+    !schedule.hours.values.has(date.getHours()) ||
+    !schedule.months.values.has(date.getMonth() + 1)
+  ) {
+    return false;
+  }
+  const dayOfMonth = schedule.daysOfMonth.values.has(date.getDate());
+  const dayOfWeek = schedule.daysOfWeek.values.has(date.getDay());
+  // When both day fields are restricted, cron runs on either
+  if (!schedule.daysOfMonth.any && !schedule.daysOfWeek.any) {
+    return dayOfMonth || dayOfWeek;
+  }
+  return dayOfMonth && dayOfWeek;
+}
+
+/**
+ * The next time a schedule fires after a date, looking up to a year ahead
+ */
+export function nextCronRun(schedule: CronSchedule, after: Date): Date | null {
+  const date = new Date(after);
+  date.setSeconds(0, 0);
+  for (let minutes = 0; minutes < 366 * 24 * 60; minutes++) {
+    date.setMinutes(date.getMinutes() + 1);
+    if (cronMatches(schedule, date)) {
+      return date;
+    }
+  }
+  return null;
+}
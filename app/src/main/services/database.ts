@@ -1,16 +1,21 @@
 import Database from 'better-sqlite3';
 import * as path from 'path';
-import * as os from 'os';
-import * as fs from 'fs';
+import { getDataDir } from './paths';
 
 let db: Database.Database | null = null;
 
 function getDbPath(): string {
-  const dataDir = path.join(os.homedir(), '.local', 'share', 'plasma');
-  if (!fs.existsSync(dataDir)) {
-    fs.mkdirSync(dataDir, { recursive: true });
+  return path.join(getDataDir(), 'plasma.db');
+}
+
+/**
+ * Add a column to a table created by an earlier version
+ */
+function addColumnIfMissing(database: Database.Database, table: string, column: string, definition: string): void {
+  const columns = database.pragma(`table_info(${table})`) as Array<{ name: string }>;
+  if (!columns.some((existing) => existing.name === column)) {
+    database.exec(`ALTER TABLE ${table} ADD COLUMN ${column} ${definition}`);
   }
-  return path.join(dataDir, 'plasma.db');
 }
 
 export function getDatabase(): Database.Database {
@@ -42,10 +47,196 @@ export function getDatabase(): Database.Database {
         created_at TEXT
       )
     `);
+
+    // Per-project preferences (selected simulator, etc.) stored as key/value pairs
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS project_settings (
+        project_id INTEGER NOT NULL,
+        key TEXT NOT NULL,
+        value TEXT,
+        updated_at TEXT,
+        PRIMARY KEY (project_id, key)
+      )
+    `);
+
+    // App-wide preferences (stream quality, theme, etc.) stored as key/value pairs
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS settings (
+        key TEXT PRIMARY KEY,
+        value TEXT,
+        updated_at TEXT
+      )
+    `);
+
+    // Debug symbols of built binaries, keyed by Mach-O UUID
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS build_symbols (
+        uuid TEXT PRIMARY KEY,
+        arch TEXT NOT NULL,
+        symbols_path TEXT NOT NULL,
+        bundle_id TEXT,
+        project_path TEXT,
+        built_at TEXT NOT NULL
+      )
+    `);
+
+    // Crash reports of simulator apps, symbolicated when they were collected
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS crash_reports (
+        id TEXT PRIMARY KEY,
+        udid TEXT,
+        bundle_id TEXT,
+        process_name TEXT NOT NULL,
+        app_version TEXT,
+        crashed_at TEXT NOT NULL,
+        exception TEXT,
+        report_path TEXT NOT NULL UNIQUE,
+        build_uuid TEXT,
+        symbolicated INTEGER NOT NULL DEFAULT 0,
+        threads TEXT NOT NULL,
+        collected_at TEXT NOT NULL
+      )
+    `);
+
+    // Finished builds with their diagnostics stored as JSON
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS builds (
+        id TEXT PRIMARY KEY,
+        project_path TEXT NOT NULL,
+        scheme TEXT NOT NULL,
+        configuration TEXT NOT NULL,
+        success INTEGER NOT NULL,
+        started_at TEXT NOT NULL,
+        finished_at TEXT NOT NULL,
+        diagnostics TEXT NOT NULL DEFAULT '[]',
+        error TEXT
+      )
+    `);
+    addColumnIfMissing(db, 'builds', 'environment', 'TEXT');
+    addColumnIfMissing(db, 'builds', 'git', 'TEXT');
+
+    // xcodebuild test runs; test case results are stored as JSON
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS test_runs (
+        id TEXT PRIMARY KEY,
+        project_path TEXT NOT NULL,
+        scheme TEXT NOT NULL,
+        configuration TEXT NOT NULL,
+        udid TEXT NOT NULL,
+        status TEXT NOT NULL,
+        coverage_enabled INTEGER NOT NULL DEFAULT 0,
+        result_bundle_path TEXT,
+        started_at TEXT NOT NULL,
+        finished_at TEXT,
+        passed INTEGER NOT NULL DEFAULT 0,
+        failed INTEGER NOT NULL DEFAULT 0,
+        skipped INTEGER NOT NULL DEFAULT 0,
+        line_coverage REAL,
+        tests TEXT NOT NULL DEFAULT '[]',
+        error TEXT
+      )
+    `);
+    addColumnIfMissing(db, 'test_runs', 'shards', 'INTEGER NOT NULL DEFAULT 1');
+    addColumnIfMissing(db, 'test_runs', 'flaky', 'INTEGER NOT NULL DEFAULT 0');
+
+    // Code coverage of a test run per target and per file
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS coverage_files (
+        test_run_id TEXT NOT NULL,
+        target TEXT NOT NULL,
+        path TEXT NOT NULL,
+        name TEXT NOT NULL,
+        covered_lines INTEGER NOT NULL,
+        executable_lines INTEGER NOT NULL,
+        line_coverage REAL NOT NULL,
+        PRIMARY KEY (test_run_id, target, path)
+      )
+    `);
+
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS coverage_targets (
+        test_run_id TEXT NOT NULL,
+        name TEXT NOT NULL,
+        covered_lines INTEGER NOT NULL,
+        executable_lines INTEGER NOT NULL,
+        line_coverage REAL NOT NULL,
+        PRIMARY KEY (test_run_id, name)
+      )
+    `);
+
+    // Summaries of CPU/memory sampling sessions for launched apps
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS app_metrics_sessions (
+        id TEXT PRIMARY KEY,
+        udid TEXT NOT NULL,
+        bundle_id TEXT NOT NULL,
+        pid INTEGER NOT NULL,
+        started_at TEXT NOT NULL,
+        ended_at TEXT NOT NULL,
+        samples INTEGER NOT NULL,
+        avg_cpu_percent REAL NOT NULL,
+        peak_cpu_percent REAL NOT NULL,
+        avg_rss_kb INTEGER NOT NULL,
+        peak_rss_kb INTEGER NOT NULL,
+        peak_threads INTEGER NOT NULL
+      )
+    `);
+
+    // Builds started on a cron schedule or by git push webhooks
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS build_triggers (
+        id TEXT PRIMARY KEY,
+        project_path TEXT NOT NULL,
+        scheme TEXT NOT NULL,
+        configuration TEXT NOT NULL,
+        schedule TEXT,
+        on_push INTEGER NOT NULL DEFAULT 0,
+        push_repository TEXT,
+        push_branch TEXT,
+        last_run_at TEXT,
+        last_build_id TEXT,
+        last_success INTEGER,
+        created_at TEXT NOT NULL
+      )
+    `);
+
+    // Sets of projects open side by side, each with its own scheme and device
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS workspaces (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        active_project_id INTEGER,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+      )
+    `);
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS workspace_projects (
+        workspace_id TEXT NOT NULL,
+        project_id INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        platform TEXT NOT NULL,
+        scheme TEXT,
+        configuration TEXT,
+        device_id TEXT,
+        PRIMARY KEY (workspace_id, project_id)
+      )
+    `);
   }
   return db;
 }
 
+/**
+ * Write the WAL back into the database file and close it, for shutdown
+ */
+export function closeDatabase(): void {
+  if (db) {
+    db.pragma('wal_checkpoint(TRUNCATE)');
+    db.close();
+    db = null;
+  }
+}
+
 export type Platform = 'ios' | 'android';
 
 // New unified project record
@@ -127,8 +318,365 @@ export function updateProjectLastOpened(id: number): void {
   db.prepare('UPDATE unified_projects SET last_opened_at = ? WHERE id = ?').run(now, id);
 }
 
+// Per-project settings
+export type ProjectSettings = Record<string, string>;
+
+export function getProjectSettings(projectId: number): ProjectSettings {
+  const db = getDatabase();
+  const rows = db.prepare(
+    'SELECT key, value FROM project_settings WHERE project_id = ? AND value IS NOT NULL'
+  ).all(projectId) as { key: string; value: string }[];
+
+  const settings: ProjectSettings = {};
+  for (const row of rows) {
+    settings[row.key] = row.value;
+  }
+  return settings;
+}
+
+/**
+ * Settings of the most recently opened project using this Xcode or Android path
+ */
+export function getProjectSettingsForPath(projectPath: string): ProjectSettings {
+  const db = getDatabase();
+  const record = db.prepare(`
+    SELECT id FROM unified_projects
+    WHERE xcode_path = ? OR android_path = ?
+    ORDER BY last_opened_at DESC
+    LIMIT 1
+  `).get(projectPath, projectPath) as { id: number } | undefined;
+  return record ? getProjectSettings(record.id) : {};
+}
+
+export function updateProjectSettings(
+  projectId: number,
+  updates: Record<string, string | null>
+): ProjectSettings {
+  const db = getDatabase();
+  const now = new Date().toISOString();
+  const upsert = db.prepare(`
+    INSERT INTO project_settings (project_id, key, value, updated_at) VALUES (?, ?, ?, ?)
+    ON CONFLICT(project_id, key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at
+  `);
+
+  db.transaction(() => {
+    for (const [key, value] of Object.entries(updates)) {
+      upsert.run(projectId, key, value, now);
+    }
+  })();
+
+  return getProjectSettings(projectId);
+}
+
+// App-wide settings
+export type AppSettings = Record<string, string>;
+
+export function getSettings(): AppSettings {
+  const db = getDatabase();
+  const rows = db.prepare(
+    'SELECT key, value FROM settings WHERE value IS NOT NULL'
+  ).all() as { key: string; value: string }[];
+
+  const settings: AppSettings = {};
+  for (const row of rows) {
+    settings[row.key] = row.value;
+  }
+  return settings;
+}
+
+export function updateSettings(updates: Record<string, string | null>): AppSettings {
+  const db = getDatabase();
+  const now = new Date().toISOString();
+  const upsert = db.prepare(`
+    INSERT INTO settings (key, value, updated_at) VALUES (?, ?, ?)
+    ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at
+  `);
+
+  db.transaction(() => {
+    for (const [key, value] of Object.entries(updates)) {
+      upsert.run(key, value, now);
+    }
+  })();
+
+  return getSettings();
+}
+
+// App metrics session summaries
+export interface AppMetricsSessionRecord {
+  id: string;
+  udid: string;
+  bundle_id: string;
+  pid: number;
+  started_at: string;
+  ended_at: string;
+  samples: number;
+  avg_cpu_percent: number;
+  peak_cpu_percent: number;
+  avg_rss_kb: number;
+  peak_rss_kb: number;
+  peak_threads: number;
+}
+
+export function saveAppMetricsSession(record: AppMetricsSessionRecord): void {
+  const db = getDatabase();
+  db.prepare(`
+    INSERT OR REPLACE INTO app_metrics_sessions (
+      id, udid, bundle_id, pid, started_at, ended_at, samples,
+      avg_cpu_percent, peak_cpu_percent, avg_rss_kb, peak_rss_kb, peak_threads
+    ) VALUES (
+      @id, @udid, @bundle_id, @pid, @started_at, @ended_at, @samples,
+      @avg_cpu_percent, @peak_cpu_percent, @avg_rss_kb, @peak_rss_kb, @peak_threads
+    )
+  `).run(record);
+}
+
+export function getAppMetricsSessions(bundleId?: string, limit: number = 20): AppMetricsSessionRecord[] {
+  const db = getDatabase();
+  if (bundleId) {
+    return db.prepare(`
+      SELECT * FROM app_metrics_sessions
+      WHERE bundle_id = ?
+      ORDER BY started_at DESC
+      LIMIT ?
+    `).all(bundleId, limit) as AppMetricsSessionRecord[];
+  }
+  return db.prepare(`
+    SELECT * FROM app_metrics_sessions
+    ORDER BY started_at DESC
+    LIMIT ?
+  `).all(limit) as AppMetricsSessionRecord[];
+}
+
+// Debug symbols of built binaries
+export interface BuildSymbolsRecord {
+  uuid: string;
+  arch: string;
+  symbols_path: string;
+  bundle_id: string | null;
+  project_path: string | null;
+  built_at: string;
+}
+
+export function saveBuildSymbols(records: BuildSymbolsRecord[]): void {
+  const db = getDatabase();
+  const upsert = db.prepare(`
+    INSERT OR REPLACE INTO build_symbols (uuid, arch, symbols_path, bundle_id, project_path, built_at)
+    VALUES (@uuid, @arch, @symbols_path, @bundle_id, @project_path, @built_at)
+  `);
+  db.transaction(() => {
+    for (const record of records) {
+      upsert.run(record);
+    }
+  })();
+}
+
+export function getBuildSymbols(uuid: string): BuildSymbolsRecord | null {
+  const db = getDatabase();
+  const record = db.prepare('SELECT * FROM build_symbols WHERE uuid = ?').get(uuid) as BuildSymbolsRecord | undefined;
+  return record || null;
+}
+
+// Crash reports
+export interface CrashReportRecord {
+  id: string;
+  udid: string | null;
+  bundle_id: string | null;
+  process_name: string;
+  app_version: string | null;
+  crashed_at: string;
+  exception: string | null;
+  report_path: string;
+  build_uuid: string | null;
+  symbolicated: number;
+  /** JSON-encoded threads with frames */
+  threads: string;
+  collected_at: string;
+}
+
+export function saveCrashReport(record: CrashReportRecord): void {
+  const db = getDatabase();
+  db.prepare(`
+    INSERT OR REPLACE INTO crash_reports (
+      id, udid, bundle_id, process_name, app_version, crashed_at, exception,
+      report_path, build_uuid, symbolicated, threads, collected_at
+    ) VALUES (
+      @id, @udid, @bundle_id, @process_name, @app_version, @crashed_at, @exception,
+      @report_path, @build_uuid, @symbolicated, @threads, @collected_at
+    )
+  `).run(record);
+}
+
+export function hasCrashReportAt(reportPath: string): boolean {
+  const db = getDatabase();
+  return db.prepare('SELECT 1 FROM crash_reports WHERE report_path = ?').get(reportPath) !== undefined;
+}
+
+export function getCrashReports(
+  filter: { bundleId?: string; udid?: string } = {},
+  limit: number = 50
+): CrashReportRecord[] {
+  const db = getDatabase();
+  return db.prepare(`
+    SELECT * FROM crash_reports
+    WHERE (@bundleId IS NULL OR bundle_id = @bundleId) AND (@udid IS NULL OR udid = @udid)
+    ORDER BY crashed_at DESC
+    LIMIT @limit
+  `).all({ bundleId: filter.bundleId ?? null, udid: filter.udid ?? null, limit }) as CrashReportRecord[];
+}
+
+export function getCrashReport(id: string): CrashReportRecord | null {
+  const db = getDatabase();
+  const record = db.prepare('SELECT * FROM crash_reports WHERE id = ?').get(id) as CrashReportRecord | undefined;
+  return record || null;
+}
+
+// Builds
+export interface BuildRecord {
+  id: string;
+  project_path: string;
+  scheme: string;
+  configuration: string;
+  success: number;
+  started_at: string;
+  finished_at: string;
+  /** JSON-encoded errors and warnings */
+  diagnostics: string;
+  error: string | null;
+  /** JSON-encoded machine and toolchain the build ran with */
+  environment: string | null;
+  /** JSON-encoded commit, branch, and dirty state of the project */
+  git: string | null;
+}
+
+export function saveBuild(record: BuildRecord): void {
+  const db = getDatabase();
+  db.prepare(`
+    INSERT OR REPLACE INTO builds (id, project_path, scheme, configuration, success, started_at, finished_at, diagnostics, error, environment, git)
+    VALUES (@id, @project_path, @scheme, @configuration, @success, @started_at, @finished_at, @diagnostics, @error, @environment, @git)
+  `).run(record);
+}
+
+export function getBuild(id: string): BuildRecord | null {
+  const db = getDatabase();
+  const record = db.prepare('SELECT * FROM builds WHERE id = ?').get(id) as BuildRecord | undefined;
+  return record || null;
+}
+
+export function getBuilds(projectPath?: string, limit: number = 20): BuildRecord[] {
+  const db = getDatabase();
+  return db.prepare(`
+    SELECT * FROM builds
+    WHERE @projectPath IS NULL OR project_path = @projectPath
+    ORDER BY started_at DESC
+    LIMIT @limit
+  `).all({ projectPath: projectPath ?? null, limit }) as BuildRecord[];
+}
+
+// Test runs
+export interface TestRunRecord {
+  id: string;
+  project_path: string;
+  scheme: string;
+  configuration: string;
+  udid: string;
+  status: string;
+  coverage_enabled: number;
+  result_bundle_path: string | null;
+  started_at: string;
+  finished_at: string | null;
+  passed: number;
+  failed: number;
+  skipped: number;
+  flaky: number;
+  line_coverage: number | null;
+  /** Number of simulators the tests were split across */
+  shards: number;
+  /** JSON-encoded test case results */
+  tests: string;
+  error: string | null;
+}
+
+export function saveTestRun(record: TestRunRecord): void {
+  const db = getDatabase();
+  db.prepare(`
+    INSERT OR REPLACE INTO test_runs (
+      id, project_path, scheme, configuration, udid, status, coverage_enabled, result_bundle_path,
+      started_at, finished_at, passed, failed, skipped, flaky, line_coverage, shards, tests, error
+    ) VALUES (
+      @id, @project_path, @scheme, @configuration, @udid, @status, @coverage_enabled, @result_bundle_path,
+      @started_at, @finished_at, @passed, @failed, @skipped, @flaky, @line_coverage, @shards, @tests, @error
+    )
+  `).run(record);
+}
+
+export function getTestRun(id: string): TestRunRecord | null {
+  const db = getDatabase();
+  const record = db.prepare('SELECT * FROM test_runs WHERE id = ?').get(id) as TestRunRecord | undefined;
+  return record || null;
+}
+
+export function getTestRuns(projectPath?: string, limit: number = 20): TestRunRecord[] {
+  const db = getDatabase();
+  return db.prepare(`
+    SELECT * FROM test_runs
+    WHERE @projectPath IS NULL OR project_path = @projectPath
+    ORDER BY started_at DESC
+    LIMIT @limit
+  `).all({ projectPath: projectPath ?? null, limit }) as TestRunRecord[];
+}
+
+// Code coverage
+export interface CoverageTargetRecord {
+  test_run_id: string;
+  name: string;
+  covered_lines: number;
+  executable_lines: number;
+  line_coverage: number;
+}
+
+export interface CoverageFileRecord {
+  test_run_id: string;
+  target: string;
+  path: string;
+  name: string;
+  covered_lines: number;
+  executable_lines: number;
+  line_coverage: number;
+}
+
+export function saveCoverage(targets: CoverageTargetRecord[], files: CoverageFileRecord[]): void {
+  const db = getDatabase();
+  const insertTarget = db.prepare(`
+    INSERT OR REPLACE INTO coverage_targets (test_run_id, name, covered_lines, executable_lines, line_coverage)
+    VALUES (@test_run_id, @name, @covered_lines, @executable_lines, @line_coverage)
+  `);
+  const insertFile = db.prepare(`
+    INSERT OR REPLACE INTO coverage_files (test_run_id, target, path, name, covered_lines, executable_lines, line_coverage)
+    VALUES (@test_run_id, @target, @path, @name, @covered_lines, @executable_lines, @line_coverage)
+  `);
+
+  db.transaction(() => {
+    targets.forEach((target) => insertTarget.run(target));
+    files.forEach((file) => insertFile.run(file));
+  })();
+}
+
+export function getCoverageTargets(testRunId: string): CoverageTargetRecord[] {
+  const db = getDatabase();
+  return db.prepare(
+    'SELECT * FROM coverage_targets WHERE test_run_id = ? ORDER BY name'
+  ).all(testRunId) as CoverageTargetRecord[];
+}
+
+export function getCoverageFiles(testRunId: string): CoverageFileRecord[] {
+  const db = getDatabase();
+  return db.prepare(
+    'SELECT * FROM coverage_files WHERE test_run_id = ? ORDER BY target, path'
+  ).all(testRunId) as CoverageFileRecord[];
+}
+
 // Legacy functions for backward compatibility
-export function saveProject(projectPath: string, name: string, platforms: Platform[]): void {
+export function saveProject(projectPath: string, name: string, platforms: Platform[]): ProjectRecord {
   const db = getDatabase();
   const now = new Date().toISOString();
   const platformsJson = JSON.stringify(platforms);
@@ -140,6 +688,8 @@ export function saveProject(projectPath: string, name: string, platforms: Platfo
   } else {
     db.prepare('INSERT INTO projects (path, name, platforms, last_opened_at, created_at) VALUES (?, ?, ?, ?, ?)').run(projectPath, name, platformsJson, now, now);
   }
+
+  return parseProjectRecord(db.prepare('SELECT * FROM projects WHERE path = ?').get(projectPath) as DbProjectRecord);
 }
 
 interface DbProjectRecord {
@@ -180,3 +730,120 @@ export function getRecentProjects(query?: string, limit: number = 10): ProjectRe
 
   return records.map(parseProjectRecord);
 }
+
+// Build triggers
+export interface BuildTriggerRecord {
+  id: string;
+  project_path: string;
+  scheme: string;
+  configuration: string;
+  /** Cron expression, or null when only pushes start builds */
+  schedule: string | null;
+  on_push: number;
+  push_repository: string | null;
+  push_branch: string | null;
+  last_run_at: string | null;
+  last_build_id: string | null;
+  last_success: number | null;
+  created_at: string;
+}
+
+export function saveBuildTrigger(record: BuildTriggerRecord): void {
+  const db = getDatabase();
+  db.prepare(`
+    INSERT OR REPLACE INTO build_triggers (
+      id, project_path, scheme, configuration, schedule, on_push, push_repository, push_branch,
+      last_run_at, last_build_id, last_success, created_at
+    ) VALUES (
+      @id, @project_path, @scheme, @configuration, @schedule, @on_push, @push_repository, @push_branch,
+      @last_run_at, @last_build_id, @last_success, @created_at
+    )
+  `).run(record);
+}
+
+export function getBuildTrigger(id: string): BuildTriggerRecord | null {
+  const db = getDatabase();
+  const record = db.prepare('SELECT * FROM build_triggers WHERE id = ?').get(id) as BuildTriggerRecord | undefined;
+  return record || null;
+}
+
+export function getBuildTriggers(): BuildTriggerRecord[] {
+  const db = getDatabase();
+  return db.prepare('SELECT * FROM build_triggers ORDER BY created_at').all() as BuildTriggerRecord[];
+}
+
+export function deleteBuildTrigger(id: string): boolean {
+  const db = getDatabase();
+  return db.prepare('DELETE FROM build_triggers WHERE id = ?').run(id).changes > 0;
+}
+
+// Workspaces
+export interface WorkspaceRecord {
+  id: string;
+  name: string;
+  active_project_id: number | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface WorkspaceProjectRecord {
+  workspace_id: string;
+  project_id: number;
+  position: number;
+  platform: Platform;
+  scheme: string | null;
+  configuration: string | null;
+  device_id: string | null;
+}
+
+export function saveWorkspace(record: WorkspaceRecord): void {
+  const db = getDatabase();
+  db.prepare(`
+    INSERT OR REPLACE INTO workspaces (id, name, active_project_id, created_at, updated_at)
+    VALUES (@id, @name, @active_project_id, @created_at, @updated_at)
+  `).run(record);
+}
+
+export function getWorkspace(id: string): WorkspaceRecord | null {
+  const db = getDatabase();
+  const record = db.prepare('SELECT * FROM workspaces WHERE id = ?').get(id) as WorkspaceRecord | undefined;
+  return record || null;
+}
+
+export function getWorkspaces(): WorkspaceRecord[] {
+  const db = getDatabase();
+  return db.prepare('SELECT * FROM workspaces ORDER BY updated_at DESC').all() as WorkspaceRecord[];
+}
+
+export function deleteWorkspace(id: string): boolean {
+  const db = getDatabase();
+  return db.transaction(() => {
+    db.prepare('DELETE FROM workspace_projects WHERE workspace_id = ?').run(id);
+    return db.prepare('DELETE FROM workspaces WHERE id = ?').run(id).changes > 0;
+  })();
+}
+
+export function getWorkspaceProjects(workspaceId: string): WorkspaceProjectRecord[] {
+  const db = getDatabase();
+  return db.prepare(
+    'SELECT * FROM workspace_projects WHERE workspace_id = ? ORDER BY position'
+  ).all(workspaceId) as WorkspaceProjectRecord[];
+}
+
+export function saveWorkspaceProject(record: WorkspaceProjectRecord): void {
+  const db = getDatabase();
+  db.prepare(`
+    INSERT OR REPLACE INTO workspace_projects (
+      workspace_id, project_id, position, platform, scheme, configuration, device_id
+    ) VALUES (
+      @workspace_id, @project_id, @position, @platform, @scheme, @configuration, @device_id
+    )
+  `).run(record);
+}
+
+export function deleteWorkspaceProject(workspaceId: string, projectId: number): boolean {
+  const db = getDatabase();
+  return db.prepare(
+    'DELETE FROM workspace_projects WHERE workspace_id = ? AND project_id = ?'
+  ).run(workspaceId, projectId).changes > 0;
+}
@@ -0,0 +1,113 @@
+import { PlasmaError } from './errors';
+import type { LaunchOptions } from './manifest';
+
+/**
+ * Runtime debug options per simulator: slow animations and the rendering
+ * and runtime-issue overlays Simulator's Debug menu offers. They take effect
+ * through launch arguments and environment variables, so apps pick them up
+ * the next time they're launched from Plasma.
+ */
+
+export type DebugOption =
+  | 'slow-animations'
+  | 'color-blended-layers'
+  | 'color-copied-images'
+  | 'color-offscreen-rendered'
+  | 'runtime-issues';
+
+export interface DebugOptionInfo {
+  id: DebugOption;
+  title: string;
+  description: string;
+}
+
+export interface DebugOptionsState {
+  udid: string;
+  enabled: DebugOption[];
+  available: DebugOptionInfo[];
+}
+
+interface DebugOptionDefinition extends DebugOptionInfo {
+  arguments?: string[];
+  environment?: Record<string, string>;
+}
+
+const DEBUG_OPTIONS: DebugOptionDefinition[] = [
+  {
+    id: 'slow-animations',
+    title: 'Slow animations',
+    description: 'Run UIKit and SwiftUI animations 10 times slower',
+    arguments: ['-UIAnimationDragCoefficient', '10'],
+  },
+  {
+    id: 'color-blended-layers',
+    title: 'Color blended layers',
+    description: 'Tint layers drawn with blending red and opaque ones green',
+    environment: { CA_COLOR_OPAQUE: '1' },
+  },
+  {
+    id: 'color-copied-images',
+    title: 'Color copied images',
+    description: 'Tint images Core Animation had to copy',
+    environment: { CA_COLOR_COPY: '1' },
+  },
+  {
+    id: 'color-offscreen-rendered',
+    title: 'Color offscreen-rendered',
+    description: 'Tint content rendered offscreen yellow',
+    environment: { CA_COLOR_OFFSCREEN: '1' },
+  },
+  {
+    id: 'runtime-issues',
+    title: 'Log runtime issues',
+    description: 'Print SwiftUI runtime issues and unsatisfiable constraints to the app logs',
+    arguments: ['-_UIConstraintBasedLayoutLogUnsatisfiable', 'YES'],
+    environment: { OS_ACTIVITY_DT_MODE: 'YES' },
+  },
+];
+
+const enabledOptions = new Map<string, Set<DebugOption>>();
+
+export function getDebugOptions(udid: string): DebugOptionsState {
+  const enabled = enabledOptions.get(udid) ?? new Set();
+  return {
+    udid,
+    enabled: DEBUG_OPTIONS.map((option) => option.id).filter((id) => enabled.has(id)),
+    available: DEBUG_OPTIONS.map(({ id, title, description }) => ({ id, title, description })),
+  };
+}
+
+/**
+ * Replace the debug options enabled for a simulator's next launches
+ */
+export function setDebugOptions(udid: string, options: unknown): DebugOptionsState {
+  if (!Array.isArray(options)) {
+    throw new PlasmaError('invalid_request', 'options must be a list');
+  }
+  const known = DEBUG_OPTIONS.map((option) => option.id);
+  const unknown = options.filter((option) => !known.includes(option));
+  if (unknown.length > 0) {
+    throw new PlasmaError('invalid_request', `Unknown debug options ${unknown.join(', ')}; use ${known.join(', ')}`);
+  }
+  if (options.length === 0) {
+    enabledOptions.delete(udid);
+  } else {
+    enabledOptions.set(udid, new Set(options as DebugOption[]));
+  }
+  return getDebugOptions(udid);
+}
+
+/**
+ * Launch arguments and environment for the debug options enabled on a simulator
+ */
+export function debugLaunchOptions(udid: string): LaunchOptions {
+  const enabled = enabledOptions.get(udid);
+  const launchOptions: LaunchOptions = { arguments: [], environment: {} };
+  for (const option of DEBUG_OPTIONS) {
+    if (enabled?.has(option.id)) {
+      launchOptions.arguments.push(...(option.arguments ?? []));
+      Object.assign(launchOptions.environment, option.environment);
+    }
+  }
+  return launchOptions;
+}
@@ -0,0 +1,222 @@
+import { spawn } from 'child_process';
+import { getSettings, updateSettings } from './database';
+import { PlasmaError } from './errors';
+import { registerProcess, signalProcess } from './process-manager';
+import { DeviceLogEntry, parseDeviceLogLine } from './simulator';
+
+/**
+ * Live streaming of a simulator's unified log, filtered on the server so
+ * clients only receive the entries they'd show. The log of a booted
+ * simulator prints hundreds of entries a second, nearly all from system
+ * daemons. Filters are kept as named profiles in the logFilterProfiles
+ * setting, next to two built-in ones.
+ */
+
+export interface LogFilterProfile {
+  name: string;
+  /** Only entries from the app: its subsystem (the bundle ID and below) or its process */
+  appOnly: boolean;
+  /** Leave out entries of processes that don't run from an installed app */
+  hideSystem: boolean;
+  /** Keep only entries whose message matches one of these regular expressions */
+  include: string[];
+  /** Drop entries whose message matches one of these regular expressions */
+  exclude: string[];
+  /** Comes with Plasma and can't be changed or removed */
+  builtIn: boolean;
+}
+
+/** The app whose entries appOnly keeps */
+export interface LogFilterApp {
+  bundleId?: string;
+  /** Executable name */
+  process?: string;
+}
+
+export type DeviceLogFilter = (entry: DeviceLogEntry, processPath: string) => boolean;
+
+const SETTING_KEY = 'logFilterProfiles';
+
+const BUILT_IN_PROFILES: LogFilterProfile[] = [
+  { name: 'App only', appOnly: true, hideSystem: false, include: [], exclude: [], builtIn: true },
+  { name: 'Hide system', appOnly: false, hideSystem: true, include: [], exclude: [], builtIn: true },
+];
+
+// Apps installed on a simulator run from its data container; system apps and daemons from the runtime
+const INSTALLED_APP_PATH = /\/Containers\/Bundle\/Application\//;
+
+function savedProfiles(): LogFilterProfile[] {
+  const value = getSettings()[SETTING_KEY];
+  if (!value) return [];
+  try {
+    const profiles = JSON.parse(value);
+    return Array.isArray(profiles)
+      ? profiles.map((profile) => ({
+          name: String(profile.name),
+          appOnly: profile.appOnly === true,
+          hideSystem: profile.hideSystem === true,
+          include: Array.isArray(profile.include) ? profile.include : [],
+          exclude: Array.isArray(profile.exclude) ? profile.exclude : [],
+          builtIn: false,
+        }))
+      : [];
+  } catch {
+    console.warn('[device-logs] Ignoring unreadable log filter profiles');
+    return [];
+  }
+}
+
+function storeProfiles(profiles: LogFilterProfile[]): void {
+  updateSettings({
+    [SETTING_KEY]: JSON.stringify(
+      profiles.map(({ name, appOnly, hideSystem, include, exclude }) => ({ name, appOnly, hideSystem, include, exclude }))
+    ),
+  });
+}
+
+function validatePatterns(key: string, value: unknown): string[] {
+  if (value === undefined) return [];
+  if (!Array.isArray(value) || value.some((pattern) => typeof pattern !== 'string')) {
+    throw new PlasmaError('invalid_request', `${key} must be a list of regular expressions`);
+  }
+  for (const pattern of value) {
+    try {
+      new RegExp(pattern);
+    } catch {
+      throw new PlasmaError('invalid_request', `Invalid ${key} pattern: ${pattern}`);
+    }
+  }
+  return value;
+}
+
+/**
+ * Built-in profiles, then the saved ones
+ */
+export function listLogFilterProfiles(): LogFilterProfile[] {
+  return [...BUILT_IN_PROFILES, ...savedProfiles()];
+}
+
+export function getLogFilterProfile(name: string): LogFilterProfile {
+  const profile = listLogFilterProfiles().find((candidate) => candidate.name === name);
+  if (!profile) {
+    throw new PlasmaError('not_found', `No log filter profile named ${name}`);
+  }
+  return profile;
+}
+
+/**
+ * Save a profile, replacing the saved one with the same name
+ */
+export function saveLogFilterProfile(request: Partial<LogFilterProfile>): LogFilterProfile {
+  if (typeof request.name !== 'string' || !request.name.trim()) {
+    throw new PlasmaError('invalid_request', 'name is required');
+  }
+  const name = request.name.trim();
+  if (BUILT_IN_PROFILES.some((profile) => profile.name === name)) {
+    throw new PlasmaError('invalid_request', `${name} is a built-in profile and can't be changed`);
+  }
+  const profile: LogFilterProfile = {
+    name,
+    appOnly: request.appOnly === true,
+    hideSystem: request.hideSystem === true,
+    include: validatePatterns('include', request.include),
+    exclude: validatePatterns('exclude', request.exclude),
+    builtIn: false,
+  };
+  storeProfiles([...savedProfiles().filter((saved) => saved.name !== name), profile]);
+  return profile;
+}
+
+export function deleteLogFilterProfile(name: string): void {
+  const profiles = savedProfiles();
+  if (!profiles.some((profile) => profile.name === name)) {
+    throw new PlasmaError('not_found', `No saved log filter profile named ${name}`);
+  }
+  storeProfiles(profiles.filter((profile) => profile.name !== name));
+}
+
+/**
+ * Whether an entry passes a profile's filters
+ */
+export function createLogFilter(profile: LogFilterProfile, app: LogFilterApp = {}): DeviceLogFilter {
+  if (profile.appOnly && !app.bundleId && !app.process) {
+    throw new PlasmaError('invalid_request', `${profile.name} needs the app's bundleId or process`);
+  }
+  const include = profile.include.map((pattern) => new RegExp(pattern));
+  const exclude = profile.exclude.map((pattern) => new RegExp(pattern));
+  return (entry, processPath) => {
+    if (profile.appOnly) {
+      const fromSubsystem =
+        !!app.bundleId &&
+        !!entry.subsystem &&
+        (entry.subsystem === app.bundleId || entry.subsystem.startsWith(`${app.bundleId}.`));
+      if (!fromSubsystem && entry.process !== app.process) return false;
+    }
+    if (profile.hideSystem && !INSTALLED_APP_PATH.test(processPath)) return false;
+    if (include.length > 0 && !include.some((pattern) => pattern.test(entry.message))) return false;
+    return !exclude.some((pattern) => pattern.test(entry.message));
+  };
+}
+
+/**
+ * A `log stream --predicate` for the entries appOnly keeps, so log itself
+ * skips the rest instead of printing them for Plasma to drop
+ */
+export function appPredicate(app: LogFilterApp): string | null {
+  const quote = (value: string) => `"${value.replace(/["\\]/g, '')}"`;
+  const clauses = [
+    ...(app.bundleId ? [`subsystem == ${quote(app.bundleId)}`, `subsystem BEGINSWITH ${quote(`${app.bundleId}.`)}`] : []),
+    ...(app.process ? [`process == ${quote(app.process)}`] : []),
+  ];
+  return clauses.length > 0 ? clauses.join(' OR ') : null;
+}
+
+/**
+ * Stream a simulator's unified log as it's written, calling onEntry with
+ * each entry the filter keeps, until the signal aborts
+ */
+export function streamDeviceLogs(
+  udid: string,
+  onEntry: (entry: DeviceLogEntry) => void,
+  options: { filter?: DeviceLogFilter; predicate?: string | null; signal?: AbortSignal } = {}
+): Promise<void> {
+  const { filter, predicate, signal } = options;
+  return new Promise((resolve, reject) => {
+    // Debug entries only when log narrows the stream down to the app itself
+    const args = ['simctl', 'spawn', udid, 'log', 'stream', '--style', 'ndjson', '--level', predicate ? 'debug' : 'info'];
+    if (predicate) {
+      args.push('--predicate', predicate);
+    }
+    const proc = spawn('xcrun', args);
+    registerProcess(proc);
+
+    const onAbort = () => signalProcess(proc, 'SIGTERM');
+    signal?.addEventListener('abort', onAbort);
+
+    let buffered = '';
+    let stderr = '';
+    proc.stdout.on('data', (data: Buffer) => {
+      const lines = (buffered + data.toString()).split('\n');
+      buffered = lines.pop() ?? '';
+      for (const line of lines) {
+        const parsed = parseDeviceLogLine(line);
+        if (parsed && (!filter || filter(parsed.entry, parsed.processPath))) {
+          onEntry(parsed.entry);
+        }
+      }
+    });
+    proc.stderr.on('data', (data: Buffer) => {
+      stderr += data.toString();
+    });
+
+    proc.on('close', (code) => {
+      signal?.removeEventListener('abort', onAbort);
+      if (code !== 0 && !signal?.aborted) {
+        reject(new PlasmaError('simctl_failed', `log stream failed: ${stderr.trim() || `exit code ${code}`}`));
+      } else {
+        resolve();
+      }
+    });
+    proc.on('error', reject);
+  });
+}
@@ -0,0 +1,170 @@
+import * as path from 'path';
+import { PlasmaError } from './errors';
+import { runCommand, SIMCTL_COMMAND } from './exec';
+import { isDictionary, PlistDictionary, PlistValue, readPlist } from './plist';
+
+/**
+ * The device types and runtimes simulators can be created from, for
+ * offering accurate choices when creating one. simctl lists them; the
+ * screen and chip of a device type come from the profile in its
+ * .simdevicetype bundle, which simctl doesn't report.
+ */
+
+export interface DeviceScreen {
+  /** Pixels */
+  width: number;
+  height: number;
+  scale: number;
+  /** Points, the pixels divided by the scale */
+  pointWidth: number;
+  pointHeight: number;
+}
+
+export interface SimulatorDeviceType {
+  identifier: string;
+  name: string;
+  /** iPhone, iPad, Apple Watch, Apple TV, or Apple Vision */
+  productFamily: string | null;
+  /** Hardware model, like iPhone16,1 */
+  modelIdentifier: string | null;
+  screen: DeviceScreen | null;
+  /** SoC platform of the hardware, like t8130 */
+  chipset: string | null;
+  architectures: string[];
+  minRuntimeVersion: string | null;
+  maxRuntimeVersion: string | null;
+  /** Identifiers of the installed runtimes that can run it */
+  runtimes: string[];
+}
+
+export interface SimulatorRuntime {
+  identifier: string;
+  name: string;
+  platform: string | null;
+  version: string;
+  buildVersion: string | null;
+  available: boolean;
+  /** Why the runtime can't be used, when it isn't available */
+  availabilityError: string | null;
+}
+
+export interface DeviceTypeCatalog {
+  deviceTypes: SimulatorDeviceType[];
+  runtimes: SimulatorRuntime[];
+}
+
+function numberValue(value: PlistValue | undefined): number | null {
+  if (typeof value === 'number') return value;
+  if (typeof value === 'bigint') return Number(value);
+  return null;
+}
+
+function readDictionary(plistPath: string): PlistDictionary | null {
+  try {
+    const plist = readPlist(plistPath);
+    return isDictionary(plist) ? plist : null;
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * Screen, chip, and architectures from a .simdevicetype bundle's
+ * profile.plist and capabilities.plist
+ */
+export function readDeviceProfile(
+  bundlePath: string
+): Pick<SimulatorDeviceType, 'screen' | 'chipset' | 'architectures'> {
+  const resources = path.join(bundlePath, 'Contents', 'Resources');
+  const profile = readDictionary(path.join(resources, 'profile.plist')) ?? {};
+  const capabilities = readDictionary(path.join(resources, 'capabilities.plist'))?.capabilities;
+
+  const width = numberValue(profile.mainScreenWidth);
+  const height = numberValue(profile.mainScreenHeight);
+  const scale = numberValue(profile.mainScreenScale) ?? 1;
+  const platform = capabilities && isDictionary(capabilities) ? capabilities.HardwarePlatform : undefined;
+  return {
+    screen:
+      width && height
+        ? { width, height, scale, pointWidth: Math.round(width / scale), pointHeight: Math.round(height / scale) }
+        : null,
+    chipset: typeof platform === 'string' && platform ? platform : null,
+    architectures: Array.isArray(profile.supportedArchs)
+      ? profile.supportedArchs.filter((arch): arch is string => typeof arch === 'string')
+      : [],
+  };
+}
+
+/**
+ * Parse the JSON of `simctl list runtimes -j`, with the identifiers of the
+ * device types each runtime supports
+ */
+export function parseRuntimes(json: string): Array<{ runtime: SimulatorRuntime; deviceTypes: string[] }> {
+  const runtimes = JSON.parse(json).runtimes;
+  return (Array.isArray(runtimes) ? runtimes : [])
+    .filter((runtime: Record<string, any>) => typeof runtime.identifier === 'string')
+    .map((runtime: Record<string, any>) => ({
+      runtime: {
+        identifier: runtime.identifier,
+        name: String(runtime.name ?? runtime.identifier),
+        platform: typeof runtime.platform === 'string' ? runtime.platform : null,
+        version: String(runtime.version ?? ''),
+        buildVersion: typeof runtime.buildversion === 'string' ? runtime.buildversion : null,
+        available: runtime.isAvailable !== false,
+        availabilityError: typeof runtime.availabilityError === 'string' ? runtime.availabilityError : null,
+      },
+      deviceTypes: (Array.isArray(runtime.supportedDeviceTypes) ? runtime.supportedDeviceTypes : [])
+        .map((deviceType: Record<string, any>) => deviceType.identifier)
+        .filter((identifier: unknown): identifier is string => typeof identifier === 'string'),
+    }));
+}
+
+/**
+ * Parse the JSON of `simctl list devicetypes -j`, before the profiles are read
+ */
+export function parseDeviceTypes(
+  json: string
+): Array<Omit<SimulatorDeviceType, 'screen' | 'chipset' | 'architectures' | 'runtimes'> & { bundlePath: string | null }> {
+  const deviceTypes = JSON.parse(json).devicetypes;
+  return (Array.isArray(deviceTypes) ? deviceTypes : [])
+    .filter((deviceType: Record<string, any>) => typeof deviceType.identifier === 'string')
+    .map((deviceType: Record<string, any>) => ({
+      identifier: deviceType.identifier,
+      name: String(deviceType.name ?? deviceType.identifier),
+      productFamily: typeof deviceType.productFamily === 'string' ? deviceType.productFamily : null,
+      modelIdentifier: typeof deviceType.modelIdentifier === 'string' ? deviceType.modelIdentifier : null,
+      minRuntimeVersion:
+        typeof deviceType.minRuntimeVersionString === 'string' ? deviceType.minRuntimeVersionString : null,
+      maxRuntimeVersion:
+        typeof deviceType.maxRuntimeVersionString === 'string' ? deviceType.maxRuntimeVersionString : null,
+      bundlePath: typeof deviceType.bundlePath === 'string' ? deviceType.bundlePath : null,
+    }));
+}
+
+/**
+ * Every device type with its screen and chip and the installed runtimes
+ * that support it, and every runtime
+ */
+export async function listDeviceTypes(): Promise<DeviceTypeCatalog> {
+  const [deviceTypesJson, runtimesJson] = await Promise.all([
+    runCommand('xcrun', ['simctl', 'list', 'devicetypes', '-j'], SIMCTL_COMMAND),
+    runCommand('xcrun', ['simctl', 'list', 'runtimes', '-j'], SIMCTL_COMMAND),
+  ]);
+  let parsedRuntimes: ReturnType<typeof parseRuntimes>;
+  let parsedDeviceTypes: ReturnType<typeof parseDeviceTypes>;
+  try {
+    parsedRuntimes = parseRuntimes(runtimesJson);
+    parsedDeviceTypes = parseDeviceTypes(deviceTypesJson);
+  } catch (err) {
+    throw new PlasmaError('simctl_failed', `Failed to parse simctl output: ${err}`);
+  }
+
+  const deviceTypes = parsedDeviceTypes.map(({ bundlePath, ...deviceType }) => ({
+    ...deviceType,
+    ...(bundlePath ? readDeviceProfile(bundlePath) : { screen: null, chipset: null, architectures: [] }),
+    runtimes: parsedRuntimes
+      .filter(({ runtime, deviceTypes }) => runtime.available && deviceTypes.includes(deviceType.identifier))
+      .map(({ runtime }) => runtime.identifier),
+  }));
+  return { deviceTypes, runtimes: parsedRuntimes.map(({ runtime }) => runtime) };
+}
@@ -0,0 +1,235 @@
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { getSettings } from './database';
+import { PlasmaError } from './errors';
+import { execCommand, runCommand, SIMCTL_COMMAND } from './exec';
+import { getLogsDir } from './logger';
+import { getDataDir } from './paths';
+import { isDictionary, readPlist } from './plist';
+import { listSimulators } from './simulator';
+import type {
+  ArtifactCategory,
+  ArtifactUsage,
+  CleanupRequest,
+  DerivedDataUsage,
+  DiskUsageReport,
+  SimulatorUsage,
+} from '../../shared/ipc-types';
+
+/**
+ * Disk used by what builds and Plasma leave behind: DerivedData per
+ * project, simulator devices, and the recordings, screenshots, results,
+ * caches, and logs under Plasma's data directory, with actions to remove
+ * each of them. Sizes come from `du`, which is much faster than walking
+ * DerivedData from Node.
+ */
+
+export type {
+  ArtifactCategory,
+  ArtifactUsage,
+  CleanupRequest,
+  DerivedDataUsage,
+  DiskUsageReport,
+  SimulatorUsage,
+};
+
+export const ARTIFACT_CATEGORIES: ArtifactCategory[] = [
+  'recordings',
+  'screenshots',
+  'automation',
+  'test-results',
+  'build-cache',
+  'baselines',
+  'mac-apps',
+  'build-logs',
+  'logs',
+];
+
+const SIMULATOR_DEVICES_DIR = path.join(os.homedir(), 'Library', 'Developer', 'CoreSimulator', 'Devices');
+
+/**
+ * `du -sk` output for paths
+ */
+async function runDu(paths: string[]): Promise<string> {
+  const result = await execCommand('du', ['-sk', ...paths], { timeout: 'tool' });
+  // du exits with 1 when some files couldn't be read, but still prints the totals
+  if (result.code !== 0 && !result.stdout) {
+    throw new PlasmaError('tool_failed', `du failed: ${result.stderr.trim() || `exit code ${result.code}`}`);
+  }
+  return result.stdout;
+}
+
+/**
+ * Where Xcode puts DerivedData, or the folder set in Plasma's settings
+ */
+export function getDerivedDataDir(): string {
+  return (
+    getSettings().derivedDataPath || path.join(os.homedir(), 'Library', 'Developer', 'Xcode', 'DerivedData')
+  );
+}
+
+/**
+ * Parse `du -sk` output into bytes by path
+ */
+export function parseDiskUsage(output: string): Map<string, number> {
+  const sizes = new Map<string, number>();
+  for (const line of output.split('\n')) {
+    const match = line.match(/^(\d+)\t(.+)$/);
+    if (match) {
+      sizes.set(match[2], Number(match[1]) * 1024);
+    }
+  }
+  return sizes;
+}
+
+async function measure(paths: string[]): Promise<Map<string, number>> {
+  const existing = paths.filter((entry) => fs.existsSync(entry));
+  return existing.length > 0 ? parseDiskUsage(await runDu(existing)) : new Map();
+}
+
+function subdirectories(dir: string): string[] {
+  if (!fs.existsSync(dir)) {
+    return [];
+  }
+  return fs
+    .readdirSync(dir, { withFileTypes: true })
+    .filter((entry) => entry.isDirectory())
+    .map((entry) => path.join(dir, entry.name));
+}
+
+function plistText(plistPath: string, key: string): string | null {
+  try {
+    const plist = readPlist(plistPath);
+    return isDictionary(plist) && typeof plist[key] === 'string' ? (plist[key] as string) : null;
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * A project's folders in DerivedData, leaving out the caches Xcode shares
+ * between projects
+ */
+function derivedDataDirectories(): string[] {
+  return subdirectories(getDerivedDataDir()).filter(
+    (dir) => !['ModuleCache.noindex', 'SymbolCache.noindex'].includes(path.basename(dir))
+  );
+}
+
+function deviceDirectories(): string[] {
+  return subdirectories(SIMULATOR_DEVICES_DIR).filter((dir) => fs.existsSync(path.join(dir, 'device.plist')));
+}
+
+/**
+ * Measure DerivedData, simulator devices, and Plasma's stored artifacts,
+ * largest first
+ */
+export async function getDiskUsage(): Promise<DiskUsageReport> {
+  const derivedDataDirs = derivedDataDirectories();
+  const deviceDirs = deviceDirectories();
+  const artifactDirs = ARTIFACT_CATEGORIES.map((category) =>
+    category === 'logs' ? getLogsDir() : path.join(getDataDir(), category)
+  );
+  const [sizes, available] = await Promise.all([
+    measure([...derivedDataDirs, ...deviceDirs, ...artifactDirs]),
+    listSimulators()
+      .then((simulators) => new Set(simulators.map((simulator) => simulator.udid)))
+      .catch(() => null),
+  ]);
+
+  const bySize = <T extends { bytes: number }>(entries: T[]) => entries.sort((a, b) => b.bytes - a.bytes);
+  const derivedData = bySize(
+    derivedDataDirs.map((dir) => ({
+      name: path.basename(dir),
+      path: dir,
+      bytes: sizes.get(dir) ?? 0,
+      projectPath: plistText(path.join(dir, 'info.plist'), 'WorkspacePath'),
+      lastModifiedAt: fs.statSync(dir).mtime.toISOString(),
+    }))
+  );
+  const simulators = bySize(
+    deviceDirs.map((dir) => {
+      const udid = path.basename(dir);
+      return {
+        udid,
+        name: plistText(path.join(dir, 'device.plist'), 'name') ?? udid,
+        runtime: plistText(path.join(dir, 'device.plist'), 'runtime'),
+        path: dir,
+        bytes: sizes.get(dir) ?? 0,
+        available: available ? available.has(udid) : true,
+      };
+    })
+  );
+  const artifacts = bySize(
+    ARTIFACT_CATEGORIES.map((category, index) => ({
+      category,
+      path: artifactDirs[index],
+      bytes: sizes.get(artifactDirs[index]) ?? 0,
+    }))
+  );
+
+  return {
+    totalBytes: [...derivedData, ...simulators, ...artifacts].reduce((sum, entry) => sum + entry.bytes, 0),
+    derivedData,
+    simulators,
+    artifacts,
+  };
+}
+
+function removeContents(dir: string, keep: (name: string) => boolean = () => false): void {
+  if (!fs.existsSync(dir)) {
+    return;
+  }
+  for (const name of fs.readdirSync(dir)) {
+    if (!keep(name)) {
+      fs.rmSync(path.join(dir, name), { recursive: true, force: true });
+    }
+  }
+}
+
+/**
+ * Remove one DerivedData folder, simulator, or kind of artifact, returning
+ * the bytes freed. Folders and simulators are looked up by the name or UDID
+ * the disk usage report lists them under, never by a path a client sends.
+ */
+export async function cleanUp(request: CleanupRequest): Promise<{ freedBytes: number }> {
+  switch (request.target) {
+    case 'derived-data': {
+      const dir = derivedDataDirectories().find((entry) => path.basename(entry) === request.name);
+      if (!dir) {
+        throw new PlasmaError('not_found', `No DerivedData folder named ${request.name}`);
+      }
+      const freedBytes = (await measure([dir])).get(dir) ?? 0;
+      fs.rmSync(dir, { recursive: true, force: true });
+      return { freedBytes };
+    }
+    case 'simulator': {
+      const dir = deviceDirectories().find((entry) => path.basename(entry) === request.udid);
+      if (!dir) {
+        throw new PlasmaError('simulator_not_found', `Simulator ${request.udid} not found`);
+      }
+      const freedBytes = (await measure([dir])).get(dir) ?? 0;
+      await runCommand('xcrun', ['simctl', 'delete', request.udid], SIMCTL_COMMAND);
+      return { freedBytes };
+    }
+    case 'unavailable-simulators': {
+      const before = (await getDiskUsage()).simulators.filter((simulator) => !simulator.available);
+      await runCommand('xcrun', ['simctl', 'delete', 'unavailable'], SIMCTL_COMMAND);
+      return { freedBytes: before.reduce((sum, simulator) => sum + simulator.bytes, 0) };
+    }
+    case 'artifacts': {
+      if (!ARTIFACT_CATEGORIES.includes(request.category)) {
+        throw new PlasmaError('invalid_request', `category must be one of ${ARTIFACT_CATEGORIES.join(', ')}`);
+      }
+      const dir = request.category === 'logs' ? getLogsDir() : path.join(getDataDir(), request.category);
+      const freedBytes = (await measure([dir])).get(dir) ?? 0;
+      // Today's log is still being written to
+      const today = `plasma-${new Date().toISOString().slice(0, 10)}.log`;
+      removeContents(dir, (name) => request.category === 'logs' && name === today);
+      return { freedBytes };
+    }
+    default:
+      throw new PlasmaError('invalid_request', 'target must be derived-data, simulator, unavailable-simulators, or artifacts');
+  }
+}
@@ -0,0 +1,153 @@
+import { spawn } from 'child_process';
+import * as fs from 'fs';
+import * as path from 'path';
+import { getSettings } from './database';
+import { PlasmaError } from './errors';
+
+/**
+ * Open a file at a line in the user's editor, so build diagnostics can jump
+ * straight to the source. The editor is a preset or a command template from
+ * the editorCommand setting, with {file}, {line}, and {column} filled in.
+ * Templates can only run known editors, so the setting can't be turned
+ * into a way to run any command. Besides the API,
+ * plasma://open?file=...&line=... links open files too.
+ */
+
+export interface SourceLocation {
+  file: string;
+  line: number;
+  column?: number;
+}
+
+export const EDITOR_PRESETS: Record<string, string> = {
+  xcode: 'xed --line {line} {file}',
+  vscode: 'code --goto {file}:{line}:{column}',
+  cursor: 'cursor --goto {file}:{line}:{column}',
+};
+
+const DEFAULT_EDITOR = 'xcode';
+
+// Executables a custom template may start, by name or by a path ending in one
+const EDITOR_EXECUTABLES = new Set([
+  'xed',
+  'code',
+  'code-insiders',
+  'cursor',
+  'windsurf',
+  'zed',
+  'subl',
+  'idea',
+  'studio',
+  'fleet',
+  'nova',
+  'bbedit',
+  'mate',
+]);
+
+/**
+ * Split a command template into arguments, keeping quoted parts together
+ */
+export function splitCommand(template: string): string[] {
+  const args: string[] = [];
+  for (const match of template.matchAll(/"([^"]*)"|'([^']*)'|(\S+)/g)) {
+    args.push(match[1] ?? match[2] ?? match[3]);
+  }
+  return args;
+}
+
+/**
+ * Check an editorCommand value: a preset name, or a template starting with
+ * one of the known editors
+ */
+export function validateEditorCommand(editor: string): void {
+  if (EDITOR_PRESETS[editor]) {
+    return;
+  }
+  const [executable] = splitCommand(editor);
+  if (!executable) {
+    throw new PlasmaError('invalid_request', 'The editor command is empty');
+  }
+  if (!EDITOR_EXECUTABLES.has(path.basename(executable))) {
+    throw new PlasmaError(
+      'invalid_request',
+      `${executable} isn't a supported editor. Use one of ${[...EDITOR_EXECUTABLES].join(', ')}.`
+    );
+  }
+}
+
+/**
+ * The command that opens a location, from a preset name or a template.
+ * Placeholders are filled in per argument so paths with spaces stay whole.
+ */
+export function buildEditorCommand(editor: string, location: SourceLocation): string[] {
+  validateEditorCommand(editor);
+  const template = EDITOR_PRESETS[editor] ?? editor;
+  const args = splitCommand(template).map((arg) =>
+    arg
+      .replace(/\{file\}/g, location.file)
+      .replace(/\{line\}/g, String(location.line))
+      .replace(/\{column\}/g, String(location.column ?? 1))
+  );
+  return args;
+}
+
+/**
+ * Check a location sent by a client
+ */
+export function validateSourceLocation(body: any): SourceLocation {
+  const line = Number(body?.line ?? 1);
+  const column = body?.column !== undefined && body?.column !== null ? Number(body.column) : undefined;
+  if (typeof body?.file !== 'string' || !path.isAbsolute(body.file)) {
+    throw new PlasmaError('invalid_request', 'file must be an absolute path');
+  }
+  if (!Number.isInteger(line) || line < 1 || (column !== undefined && (!Number.isInteger(column) || column < 1))) {
+    throw new PlasmaError('invalid_request', 'line and column must be positive integers');
+  }
+  return { file: body.file, line, column };
+}
+
+/**
+ * Read a plasma://open?file=/path&line=12&column=3 link
+ */
+export function parseEditorUrl(url: string): SourceLocation {
+  let parsed: URL;
+  try {
+    parsed = new URL(url);
+  } catch {
+    throw new PlasmaError('invalid_request', `${url} is not a URL`);
+  }
+  if (parsed.protocol !== 'plasma:' || parsed.hostname !== 'open') {
+    throw new PlasmaError('invalid_request', `Unsupported link ${url}`);
+  }
+  return validateSourceLocation({
+    file: parsed.searchParams.get('file'),
+    line: parsed.searchParams.get('line') ?? undefined,
+    column: parsed.searchParams.get('column') ?? undefined,
+  });
+}
+
+/**
+ * Open a location in the configured editor. The editor is left running
+ * when Plasma quits, so it isn't tracked with Plasma's own processes.
+ */
+export function openInEditor(location: SourceLocation): Promise<void> {
+  if (!fs.existsSync(location.file)) {
+    return Promise.reject(new PlasmaError('not_found', `${location.file} does not exist`));
+  }
+  const [cmd, ...args] = buildEditorCommand(getSettings().editorCommand || DEFAULT_EDITOR, location);
+
+  return new Promise((resolve, reject) => {
+    const proc = spawn(cmd, args, { detached: true, stdio: 'ignore' });
+    proc.on('error', (err: NodeJS.ErrnoException) => {
+      reject(
+        err.code === 'ENOENT'
+          ? new PlasmaError('tool_missing', `${cmd} was not found. Set the editor command in Settings.`)
+          : new PlasmaError('internal', `Could not run ${cmd}: ${err.message}`)
+      );
+    });
+    proc.on('spawn', () => {
+      proc.unref();
+      resolve();
+    });
+  });
+}
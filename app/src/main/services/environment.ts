@@ -0,0 +1,188 @@
+import { findSimulatorServerBinary } from './simulator';
+import { PlasmaError } from './errors';
+import { CommandResult, execCommand } from './exec';
+import { detectHelperTools, findHomebrewBinary, helperToolInstallCommand, HelperTool, HelperToolId } from './tooling';
+
+export type EnvironmentCheckId =
+  | 'command-line-tools'
+  | 'xcode-license'
+  | 'simulator-runtime'
+  | 'simulator-server'
+  | HelperToolId;
+
+export interface EnvironmentCheck {
+  id: EnvironmentCheckId;
+  title: string;
+  ok: boolean;
+  required: boolean;
+  detail?: string;
+  fixLabel?: string;
+  /** Version of the helper tool found */
+  version?: string;
+  /** Command that installs what's missing */
+  installCommand?: string;
+}
+
+/**
+ * Run a command and capture its output without rejecting on failure
+ */
+function runCheckCommand(cmd: string, args: string[]): Promise<CommandResult> {
+  return execCommand(cmd, args, { timeout: 'tool' }).catch((err: Error) => ({
+    code: null,
+    stdout: '',
+    stderr: err.message,
+  }));
+}
+
+async function checkCommandLineTools(): Promise<EnvironmentCheck> {
+  const result = await runCheckCommand('xcode-select', ['-p']);
+  const developerDir = result.stdout.trim();
+  return {
+    id: 'command-line-tools',
+    title: 'Xcode command line tools',
+    ok: result.code === 0 && developerDir.length > 0,
+    required: true,
+    detail: result.code === 0 ? developerDir : 'xcode-select could not find a developer directory',
+    fixLabel: 'Install',
+  };
+}
+
+async function checkXcodeLicense(): Promise<EnvironmentCheck> {
+  // Exits non-zero when the license for the selected Xcode hasn't been accepted
+  const result = await runCheckCommand('xcodebuild', ['-license', 'check']);
+  return {
+    id: 'xcode-license',
+    title: 'Xcode license accepted',
+    ok: result.code === 0,
+    required: true,
+    detail: result.code === 0 ? undefined : (result.stderr.trim() || 'The Xcode license has not been accepted'),
+    fixLabel: 'Review license',
+  };
+}
+
+async function checkSimulatorRuntime(): Promise<EnvironmentCheck> {
+  const result = await runCheckCommand('xcrun', ['simctl', 'list', 'runtimes', '--json']);
+  let runtimes: string[] = [];
+
+  if (result.code === 0) {
+    try {
+      const data = JSON.parse(result.stdout) as {
+        runtimes?: { name: string; isAvailable?: boolean }[];
+      };
+      runtimes = (data.runtimes || [])
+        .filter((runtime) => runtime.isAvailable !== false)
+        .map((runtime) => runtime.name);
+    } catch {
+      // Treated as no runtimes below
+    }
+  }
+
+  return {
+    id: 'simulator-runtime',
+    title: 'Simulator runtime installed',
+    ok: runtimes.length > 0,
+    required: true,
+    detail: runtimes.length > 0 ? runtimes.join(', ') : 'No simulator runtimes are installed',
+    fixLabel: 'Download iOS runtime',
+  };
+}
+
+function checkHelperBinary(id: 'simulator-server', title: string, binaryPath: string | null): EnvironmentCheck {
+  return {
+    id,
+    title,
+    ok: binaryPath !== null,
+    required: false,
+    detail: binaryPath ?? 'Not found; some simulator features will be unavailable',
+  };
+}
+
+function checkHelperTool(tool: HelperTool): EnvironmentCheck {
+  if (!tool.installed) {
+    return {
+      id: tool.id,
+      title: tool.title,
+      ok: false,
+      required: false,
+      detail: 'Not installed; some simulator features will be unavailable',
+      fixLabel: 'Install with Homebrew',
+      installCommand: tool.installCommand,
+    };
+  }
+  return {
+    id: tool.id,
+    title: tool.title,
+    ok: true,
+    required: false,
+    detail: tool.path ?? undefined,
+    version: tool.version ?? undefined,
+  };
+}
+
+/**
+ * Run all first-run environment checks
+ */
+export async function checkEnvironment(): Promise<EnvironmentCheck[]> {
+  const [commandLineTools, license, runtime, tools] = await Promise.all([
+    checkCommandLineTools(),
+    checkXcodeLicense(),
+    checkSimulatorRuntime(),
+    // Checking again is how the user confirms an install finished
+    detectHelperTools({ refresh: true }),
+  ]);
+
+  return [
+    commandLineTools,
+    license,
+    runtime,
+    checkHelperBinary('simulator-server', 'Streaming helper (simulator-server)', findSimulatorServerBinary()),
+    ...tools.map(checkHelperTool),
+  ];
+}
+
+/**
+ * Run a command in a new Terminal window so the user can answer prompts (e.g. sudo)
+ */
+function runInTerminal(command: string): Promise<void> {
+  const script = `tell application "Terminal"
+  activate
+  do script "${command.replace(/\\/g, '\\\\').replace(/"/g, '\\"')}"
+end tell`;
+  return runCheckCommand('osascript', ['-e', script]).then((result) => {
+    if (result.code !== 0) {
+      throw new Error(result.stderr.trim() || 'Failed to open Terminal');
+    }
+  });
+}
+
+/**
+ * Start the fix for a failing check
+ */
+export async function fixEnvironmentCheck(id: EnvironmentCheckId): Promise<void> {
+  switch (id) {
+    case 'command-line-tools': {
+      // Opens the system installer dialog
+      const result = await runCheckCommand('xcode-select', ['--install']);
+      if (result.code !== 0 && !result.stderr.includes('already installed')) {
+        throw new Error(result.stderr.trim() || 'Failed to start the installer');
+      }
+      return;
+    }
+    case 'xcode-license':
+      return runInTerminal('sudo xcodebuild -license');
+    case 'simulator-runtime':
+      return runInTerminal('xcodebuild -downloadPlatform iOS');
+    case 'axe':
+    case 'fbsimctl':
+    case 'idb': {
+      const brew = findHomebrewBinary('brew');
+      if (!brew) {
+        throw new PlasmaError('tool_missing', 'Homebrew is not installed; get it from https://brew.sh');
+      }
+      // Terminal doesn't always have Homebrew on its PATH either
+      return runInTerminal(helperToolInstallCommand(id).replace(/^brew/, brew));
+    }
+    default:
+      throw new PlasmaError('invalid_request', `No automatic fix for ${id}`);
+  }
+}
@@ -0,0 +1,73 @@
+import type { ErrorCode } from '../../shared/ipc-types';
+
+/**
+ * Shared error type with stable, machine-readable codes
+ * Routes, WebSocket handlers, and the CLI all report failures as
+ * { error, code } so clients can branch on the code instead of the message.
+ */
+
+export type { ErrorCode };
+
+const STATUS_BY_CODE: Record<ErrorCode, number> = {
+  invalid_request: 400,
+  unauthorized: 401,
+  forbidden: 403,
+  not_found: 404,
+  project_not_found: 404,
+  not_an_xcode_project: 400,
+  xcodebuild_failed: 502,
+  build_failed: 502,
+  hook_failed: 502,
+  simulator_not_found: 404,
+  simctl_failed: 502,
+  session_not_found: 409,
+  stream_failed: 502,
+  input_failed: 502,
+  tool_missing: 424,
+  tool_failed: 502,
+  android_failed: 502,
+  upload_failed: 502,
+  timeout: 504,
+  // The client went away or asked to stop
+  cancelled: 499,
+  internal: 500,
+};
+
+export class PlasmaError extends Error {
+  readonly code: ErrorCode;
+
+  constructor(code: ErrorCode, message: string, options?: { cause?: unknown }) {
+    super(message, options);
+    this.name = 'PlasmaError';
+    this.code = code;
+  }
+
+  /** HTTP status the code maps to */
+  get status(): number {
+    return STATUS_BY_CODE[this.code];
+  }
+}
+
+export interface ErrorBody {
+  error: string;
+  code: ErrorCode;
+}
+
+/**
+ * Normalize anything thrown into a PlasmaError, defaulting to internal
+ */
+export function toPlasmaError(error: unknown, fallback: ErrorCode = 'internal'): PlasmaError {
+  if (error instanceof PlasmaError) {
+    return error;
+  }
+  const message = error instanceof Error ? error.message : String(error || 'Unknown error');
+  return new PlasmaError(fallback, message, { cause: error });
+}
+
+/**
+ * JSON body sent to clients for an error
+ */
+export function toErrorBody(error: unknown): ErrorBody {
+  const plasmaError = toPlasmaError(error);
+  return { error: plasmaError.message, code: plasmaError.code };
+}
@@ -0,0 +1,97 @@
+import { spawn } from 'child_process';
+import * as path from 'path';
+import { ErrorCode, PlasmaError } from './errors';
+import { LineSplitter } from './lines';
+import { registerProcess } from './process-manager';
+import { enforceTimeout, TimeoutOperation } from './timeouts';
+
+/**
+ * Running external tools and collecting their output. Every command is
+ * registered so it's stopped with Plasma, and bounded by the timeout of
+ * the kind of work it does. Failures carry the error code the caller
+ * reports them with.
+ */
+
+export interface CommandResult {
+  code: number | null;
+  stdout: string;
+  stderr: string;
+}
+
+export interface ExecOptions {
+  /** Timeout the command runs under */
+  timeout: TimeoutOperation;
+  cwd?: string;
+  env?: NodeJS.ProcessEnv;
+  /** Called with each line printed on stdout or stderr, as it arrives */
+  onLine?: (line: string) => void;
+}
+
+export interface RunCommandOptions extends ExecOptions {
+  /** Code a non-zero exit is reported with */
+  errorCode: ErrorCode;
+}
+
+/** simctl and the tools it spawns inside a simulator */
+export const SIMCTL_COMMAND: RunCommandOptions = { timeout: 'simctl', errorCode: 'simctl_failed' };
+
+/** Other command line tools, like git, codesign, or xccov */
+export const TOOL_COMMAND: RunCommandOptions = { timeout: 'tool', errorCode: 'tool_failed' };
+
+/**
+ * Name a command is reported by: the tool xcrun runs, or the executable
+ */
+function toolName(cmd: string, args: string[]): string {
+  return cmd === 'xcrun' && args[0] ? args[0] : path.basename(cmd);
+}
+
+/**
+ * Run a command to completion whatever its exit code. Rejects when it
+ * can't be started or runs over its timeout.
+ */
+export function execCommand(cmd: string, args: string[], options: ExecOptions): Promise<CommandResult> {
+  return new Promise((resolve, reject) => {
+    const proc = spawn(cmd, args, { cwd: options.cwd, env: options.env });
+    registerProcess(proc);
+    enforceTimeout(proc, options.timeout, reject);
+    let stdout = '';
+    let stderr = '';
+
+    // Each stream keeps the end of its last chunk until the rest of the line arrives
+    const collect = (stream: NodeJS.ReadableStream, append: (text: string) => void) => {
+      const lines = new LineSplitter();
+      stream.on('data', (data: Buffer) => {
+        append(data.toString());
+        if (options.onLine) lines.push(data).forEach(options.onLine);
+      });
+      stream.on('end', () => {
+        if (options.onLine) lines.end().forEach(options.onLine);
+      });
+    };
+    collect(proc.stdout, (text) => (stdout += text));
+    collect(proc.stderr, (text) => (stderr += text));
+
+    proc.on('close', (code) => resolve({ code, stdout, stderr }));
+    proc.on('error', (err: NodeJS.ErrnoException) => {
+      const tool = toolName(cmd, args);
+      reject(
+        err.code === 'ENOENT'
+          ? new PlasmaError('tool_missing', `${tool} was not found`)
+          : new PlasmaError('internal', `Could not run ${tool}: ${err.message}`)
+      );
+    });
+  });
+}
+
+/**
+ * Run a command and return its stdout, rejecting with the given error code
+ * when it exits with anything but 0
+ */
+export async function runCommand(cmd: string, args: string[], options: RunCommandOptions): Promise<string> {
+  const result = await execCommand(cmd, args, options);
+  if (result.code !== 0) {
+    const detail = result.stderr.trim() || result.stdout.trim() || `exit code ${result.code}`;
+    throw new PlasmaError(options.errorCode, `${toolName(cmd, args)} failed: ${detail}`);
+  }
+  return result.stdout;
+}
@@ -0,0 +1,194 @@
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { PlasmaError } from './errors';
+import { runCommand, SIMCTL_COMMAND } from './exec';
+import { readManifest } from './manifest';
+import { readAppBundleInfo } from './plist';
+import { addMedia, bootSimulator, shutdownSimulator } from './simulator';
+
+/**
+ * Named sets of fixture data declared in plasma.toml under [fixtures.<name>]
+ * and seeded into a simulator so demos and tests start from a known state.
+ * Photos, videos, and contacts go in with `simctl addmedia`. There's no
+ * simctl command for calendar events, so those are handed to a companion
+ * helper app: a small app of the project's own that reads
+ * Documents/plasma-fixtures.json from its container when launched with
+ * -PlasmaSeedFixtures and saves the events with EventKit.
+ */
+
+export interface FixtureContact {
+  firstName?: string;
+  lastName?: string;
+  phone?: string;
+  email?: string;
+  organization?: string;
+}
+
+export interface FixtureEvent {
+  title: string;
+  /** ISO 8601 date and time */
+  start: string;
+  end: string;
+  location?: string;
+  notes?: string;
+  allDay?: boolean;
+}
+
+export interface FixtureSet {
+  name: string;
+  /** Photos and videos, as absolute paths */
+  photos: string[];
+  contacts: FixtureContact[];
+  /** vCard files with more contacts, as absolute paths */
+  vcards: string[];
+  events: FixtureEvent[];
+  /** Bundle ID of the helper app that saves calendar events */
+  helper?: string;
+  /** Build of the helper app, installed before the events are handed over */
+  helperApp?: string;
+  /** Erase the simulator before seeding */
+  erase: boolean;
+}
+
+export interface FixtureSummary {
+  name: string;
+  photos: number;
+  contacts: number;
+  vcards: number;
+  events: number;
+  erase: boolean;
+}
+
+export interface SeedResult {
+  name: string;
+  udid: string;
+  photos: number;
+  contacts: number;
+  vcards: number;
+  events: number;
+}
+
+/** Launch argument telling the helper app to import the fixture file */
+export const HELPER_SEED_ARGUMENT = '-PlasmaSeedFixtures';
+export const HELPER_FIXTURES_FILE = 'plasma-fixtures.json';
+
+function escapeVCard(value: string): string {
+  return value.replace(/\\/g, '\\\\').replace(/\n/g, '\\n').replace(/([,;])/g, '\\$1');
+}
+
+/**
+ * vCard 3.0 for a fixture contact
+ */
+export function toVCard(contact: FixtureContact): string {
+  const first = escapeVCard(contact.firstName ?? '');
+  const last = escapeVCard(contact.lastName ?? '');
+  const lines = [
+    'BEGIN:VCARD',
+    'VERSION:3.0',
+    `N:${last};${first};;;`,
+    `FN:${[first, last].filter(Boolean).join(' ') || escapeVCard(contact.organization ?? contact.email ?? '')}`,
+  ];
+  if (contact.organization) lines.push(`ORG:${escapeVCard(contact.organization)}`);
+  if (contact.phone) lines.push(`TEL;TYPE=CELL:${escapeVCard(contact.phone)}`);
+  if (contact.email) lines.push(`EMAIL;TYPE=INTERNET:${escapeVCard(contact.email)}`);
+  lines.push('END:VCARD');
+  return lines.map((line) => `${line}\r\n`).join('');
+}
+
+function getFixtureSets(projectPath: string): FixtureSet[] {
+  return readManifest(projectPath)?.fixtures ?? [];
+}
+
+export function listFixtureSets(projectPath: string): FixtureSummary[] {
+  return getFixtureSets(projectPath).map((set) => ({
+    name: set.name,
+    photos: set.photos.length,
+    contacts: set.contacts.length,
+    vcards: set.vcards.length,
+    events: set.events.length,
+    erase: set.erase,
+  }));
+}
+
+/**
+ * Seed a simulator with one of the project's fixture sets
+ */
+export async function seedFixtures(
+  udid: string,
+  projectPath: string,
+  name: string,
+  onProgress?: (message: string) => void
+): Promise<SeedResult> {
+  const set = getFixtureSets(projectPath).find((candidate) => candidate.name === name);
+  if (!set) {
+    throw new PlasmaError('not_found', `No fixture set named ${name} in plasma.toml`);
+  }
+  if (set.events.length > 0 && !set.helper && !set.helperApp) {
+    throw new PlasmaError('invalid_request', `Fixture set ${name} has events but no helper app to save them`);
+  }
+  const files = [...set.photos, ...set.vcards, ...(set.helperApp ? [set.helperApp] : [])];
+  const missing = files.filter((file) => !fs.existsSync(file));
+  if (missing.length > 0) {
+    throw new PlasmaError('not_found', `Fixture files not found: ${missing.join(', ')}`);
+  }
+
+  if (set.erase) {
+    onProgress?.('Erasing the simulator');
+    await shutdownSimulator(udid);
+    await runCommand('xcrun', ['simctl', 'erase', udid], SIMCTL_COMMAND);
+  }
+  onProgress?.('Booting the simulator');
+  await bootSimulator(udid);
+
+  if (set.photos.length > 0) {
+    onProgress?.(`Adding ${set.photos.length} photos and videos`);
+    await addMedia(udid, set.photos);
+  }
+
+  if (set.contacts.length > 0 || set.vcards.length > 0) {
+    const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-fixtures-'));
+    try {
+      const vcards = [...set.vcards];
+      if (set.contacts.length > 0) {
+        const file = path.join(dir, `${name}.vcf`);
+        fs.writeFileSync(file, set.contacts.map(toVCard).join(''));
+        vcards.push(file);
+      }
+      onProgress?.(`Adding contacts from ${vcards.length} vCard files`);
+      await addMedia(udid, vcards);
+    } finally {
+      fs.rmSync(dir, { recursive: true, force: true });
+    }
+  }
+
+  if (set.events.length > 0) {
+    const helper = set.helper ?? readAppBundleInfo(set.helperApp!).bundleId;
+    if (set.helperApp) {
+      onProgress?.(`Installing ${helper}`);
+      await runCommand('xcrun', ['simctl', 'install', udid, set.helperApp], SIMCTL_COMMAND);
+    }
+    onProgress?.(`Saving ${set.events.length} calendar events with ${helper}`);
+    let container: string;
+    try {
+      container = (
+        await runCommand('xcrun', ['simctl', 'get_app_container', udid, helper, 'data'], SIMCTL_COMMAND)
+      ).trim();
+    } catch {
+      throw new PlasmaError('invalid_request', `Install the fixture helper app ${helper} on the simulator first`);
+    }
+    const documents = path.join(container, 'Documents');
+    fs.mkdirSync(documents, { recursive: true });
+    fs.writeFileSync(path.join(documents, HELPER_FIXTURES_FILE), JSON.stringify({ events: set.events }, null, 2));
+    await runCommand('xcrun', ['simctl', 'launch', udid, helper, HELPER_SEED_ARGUMENT], SIMCTL_COMMAND);
+  }
+
+  return {
+    name,
+    udid,
+    photos: set.photos.length,
+    contacts: set.contacts.length,
+    vcards: set.vcards.length,
+    events: set.events.length,
+  };
+}
@@ -0,0 +1,248 @@
+import * as fs from 'fs';
+import * as http from 'http';
+import type { Duplex } from 'stream';
+import * as path from 'path';
+import * as zlib from 'zlib';
+import { PlasmaError } from './errors';
+
+/**
+ * The web frontend, served for requests outside /api so a browser can open
+ * Plasma from the server's own port. While developing the frontend, requests
+ * (and Vite's HMR WebSocket) are proxied to the Vite dev server instead of
+ * reading the built frontend/dist. Built files are compressed with brotli or
+ * gzip, and Vite's content-hashed assets are cached for good.
+ */
+
+export interface FrontendOptions {
+  /** Vite dev server to proxy to, like http://localhost:5173 */
+  devUrl?: string;
+}
+
+const CONTENT_TYPES: Record<string, string> = {
+  '.html': 'text/html; charset=utf-8',
+  '.js': 'text/javascript; charset=utf-8',
+  '.mjs': 'text/javascript; charset=utf-8',
+  '.css': 'text/css; charset=utf-8',
+  '.json': 'application/json; charset=utf-8',
+  '.svg': 'image/svg+xml',
+  '.png': 'image/png',
+  '.jpg': 'image/jpeg',
+  '.ico': 'image/x-icon',
+  '.woff': 'font/woff',
+  '.woff2': 'font/woff2',
+  '.wasm': 'application/wasm',
+  '.map': 'application/json; charset=utf-8',
+};
+
+// Protocol Vite's client asks for when it opens the HMR socket
+const VITE_HMR_PROTOCOL = 'vite-hmr';
+
+export type ContentEncoding = 'br' | 'gzip';
+
+// Files smaller than this aren't worth compressing
+const MIN_COMPRESS_BYTES = 1024;
+const COMPRESSIBLE_EXTENSIONS = new Set(['.html', '.js', '.mjs', '.css', '.json', '.svg', '.wasm', '.map']);
+// Vite names built assets like assets/index-B2x9QzAf.js
+const HASHED_ASSET = /^assets\/.+-[\w-]{8,}\.\w+$/;
+
+// Compressed files by path and encoding, with the modification time they were compressed at
+const compressedCache = new Map<string, { mtimeMs: number; body: Buffer }>();
+
+export function getFrontendDistDir(): string {
+  return path.join(__dirname, '../../../frontend/dist');
+}
+
+/**
+ * Check a --frontend-dev-url value, returning it without a trailing slash
+ */
+export function parseFrontendDevUrl(value: string): string {
+  let url: URL;
+  try {
+    url = new URL(value);
+  } catch {
+    throw new PlasmaError('invalid_request', `--frontend-dev-url must be a URL, got ${value}`);
+  }
+  if (url.protocol !== 'http:') {
+    throw new PlasmaError('invalid_request', `--frontend-dev-url must be an http:// URL, got ${value}`);
+  }
+  return url.origin;
+}
+
+/**
+ * Whether an upgrade request is Vite's HMR socket rather than Plasma's own
+ */
+export function isHmrUpgrade(req: http.IncomingMessage): boolean {
+  return (req.headers['sec-websocket-protocol'] ?? '')
+    .split(',')
+    .some((protocol) => protocol.trim() === VITE_HMR_PROTOCOL);
+}
+
+function devServerRequestOptions(req: http.IncomingMessage, devUrl: string): http.RequestOptions {
+  const target = new URL(devUrl);
+  return {
+    hostname: target.hostname,
+    port: target.port,
+    path: req.url,
+    method: req.method,
+    // Vite only answers to the hosts it knows
+    headers: { ...req.headers, host: target.host },
+  };
+}
+
+/**
+ * Forward a request to the Vite dev server and stream back its response
+ */
+export function proxyToDevServer(req: http.IncomingMessage, res: http.ServerResponse, devUrl: string): void {
+  const upstream = http.request(devServerRequestOptions(req, devUrl), (upstreamRes) => {
+    res.writeHead(upstreamRes.statusCode ?? 502, upstreamRes.headers);
+    upstreamRes.pipe(res);
+  });
+  upstream.on('error', (err) => {
+    console.error(`[frontend] Dev server at ${devUrl} is not reachable:`, err.message);
+    if (!res.headersSent) {
+      res.writeHead(502, { 'Content-Type': 'text/plain; charset=utf-8' });
+    }
+    res.end(`The frontend dev server at ${devUrl} is not reachable. Is \`pnpm dev:frontend\` running?`);
+  });
+  req.pipe(upstream);
+}
+
+/**
+ * Pass an HMR WebSocket upgrade through to the Vite dev server
+ */
+export function proxyUpgradeToDevServer(req: http.IncomingMessage, socket: Duplex, head: Buffer, devUrl: string): void {
+  const upstream = http.request(devServerRequestOptions(req, devUrl));
+  upstream.on('upgrade', (upstreamRes, upstreamSocket, upstreamHead) => {
+    const headers = Object.entries(upstreamRes.headers).flatMap(([name, value]) =>
+      (Array.isArray(value) ? value : [value]).map((entry) => `${name}: ${entry}`)
+    );
+    socket.write(
+      [`HTTP/1.1 ${upstreamRes.statusCode} ${upstreamRes.statusMessage}`, ...headers, '', ''].join('\r\n')
+    );
+    if (upstreamHead.length > 0) {
+      socket.write(upstreamHead);
+    }
+    if (head.length > 0) {
+      upstreamSocket.write(head);
+    }
+    upstreamSocket.pipe(socket).pipe(upstreamSocket);
+    upstreamSocket.on('error', () => socket.destroy());
+    socket.on('error', () => upstreamSocket.destroy());
+  });
+  upstream.on('response', (upstreamRes) => {
+    // The dev server refused to upgrade
+    socket.end(`HTTP/1.1 ${upstreamRes.statusCode} ${upstreamRes.statusMessage}\r\n\r\n`);
+  });
+  upstream.on('error', (err) => {
+    console.error(`[frontend] Could not proxy HMR to ${devUrl}:`, err.message);
+    socket.destroy();
+  });
+  upstream.end();
+}
+
+/**
+ * Resolve a request path to a file in the built frontend. Paths that aren't
+ * files are routes of the single-page app, which index.html handles.
+ */
+export function resolveFrontendFile(distDir: string, requestPath: string): string {
+  let decoded: string;
+  try {
+    decoded = decodeURIComponent(requestPath);
+  } catch {
+    decoded = requestPath;
+  }
+  const filePath = path.join(distDir, path.normalize(decoded));
+  if (filePath.startsWith(distDir + path.sep) && fs.existsSync(filePath) && fs.statSync(filePath).isFile()) {
+    return filePath;
+  }
+  return path.join(distDir, 'index.html');
+}
+
+/**
+ * The encoding to compress a response with, from an Accept-Encoding header,
+ * preferring brotli
+ */
+export function pickEncoding(acceptEncoding: string | undefined): ContentEncoding | null {
+  const accepted = new Map<string, number>();
+  for (const part of (acceptEncoding ?? '').split(',')) {
+    const [name, ...params] = part.trim().toLowerCase().split(';');
+    const quality = params.map((param) => param.trim().match(/^q=([\d.]+)$/)?.[1]).find(Boolean);
+    if (name) {
+      accepted.set(name, quality !== undefined ? Number(quality) : 1);
+    }
+  }
+  const allows = (encoding: ContentEncoding) => (accepted.get(encoding) ?? accepted.get('*') ?? 0) > 0;
+  return allows('br') ? 'br' : allows('gzip') ? 'gzip' : null;
+}
+
+/**
+ * Cache-Control for a file of the built frontend, relative to its dist folder.
+ * Hashed assets never change under the same name; everything else, index.html
+ * above all, has to be revalidated so a new build is picked up.
+ */
+export function cacheControlFor(relativePath: string): string {
+  return HASHED_ASSET.test(relativePath.split(path.sep).join('/'))
+    ? 'public, max-age=31536000, immutable'
+    : 'no-cache';
+}
+
+function compress(filePath: string, stat: fs.Stats, encoding: ContentEncoding): Buffer {
+  const key = `${filePath}:${encoding}`;
+  const cached = compressedCache.get(key);
+  if (cached?.mtimeMs === stat.mtimeMs) {
+    return cached.body;
+  }
+  const contents = fs.readFileSync(filePath);
+  const body =
+    encoding === 'br'
+      ? zlib.brotliCompressSync(contents, { params: { [zlib.constants.BROTLI_PARAM_QUALITY]: 9 } })
+      : zlib.gzipSync(contents, { level: 9 });
+  compressedCache.set(key, { mtimeMs: stat.mtimeMs, body });
+  return body;
+}
+
+/**
+ * Serve a file of the built frontend
+ */
+export function serveFrontendFile(req: http.IncomingMessage, res: http.ServerResponse, requestPath: string): void {
+  const distDir = getFrontendDistDir();
+  if (!fs.existsSync(path.join(distDir, 'index.html'))) {
+    throw new PlasmaError(
+      'not_found',
+      'The frontend is not built. Run `pnpm build`, or start the server with --frontend-dev-url to use the Vite dev server.'
+    );
+  }
+  const filePath = resolveFrontendFile(distDir, requestPath);
+  const stat = fs.statSync(filePath);
+  const etag = `W/"${stat.size.toString(16)}-${Math.floor(stat.mtimeMs).toString(16)}"`;
+  const headers: http.OutgoingHttpHeaders = {
+    'Content-Type': CONTENT_TYPES[path.extname(filePath)] ?? 'application/octet-stream',
+    'Cache-Control': cacheControlFor(path.relative(distDir, filePath)),
+    ETag: etag,
+    Vary: 'Accept-Encoding',
+  };
+
+  if (req.headers['if-none-match']?.split(',').some((tag) => tag.trim() === etag)) {
+    res.writeHead(304, headers);
+    res.end();
+    return;
+  }
+
+  const encoding =
+    COMPRESSIBLE_EXTENSIONS.has(path.extname(filePath)) && stat.size >= MIN_COMPRESS_BYTES
+      ? pickEncoding(req.headers['accept-encoding'])
+      : null;
+  if (encoding) {
+    const body = compress(filePath, stat, encoding);
+    res.writeHead(200, { ...headers, 'Content-Encoding': encoding, 'Content-Length': body.length });
+    res.end(req.method === 'HEAD' ? undefined : body);
+    return;
+  }
+
+  res.writeHead(200, { ...headers, 'Content-Length': stat.size });
+  if (req.method === 'HEAD') {
+    res.end();
+    return;
+  }
+  fs.createReadStream(filePath).pipe(res);
+}
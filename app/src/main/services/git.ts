@@ -0,0 +1,55 @@
+import * as path from 'path';
+import { runCommand, TOOL_COMMAND } from './exec';
+
+/**
+ * The git state of a project when it's built: the commit checked out, the
+ * branch, and whether there were uncommitted changes. Recorded with builds
+ * so an app can be traced back to the code it was built from.
+ */
+
+export interface GitInfo {
+  commit: string;
+  /** null on a detached HEAD */
+  branch: string | null;
+  /** Whether tracked or untracked files had changes not yet committed */
+  dirty: boolean;
+}
+
+/**
+ * Parse `git status --porcelain=v2 --branch`, returning null before the
+ * first commit
+ */
+export function parseGitStatus(output: string): GitInfo | null {
+  let commit: string | null = null;
+  let branch: string | null = null;
+  let dirty = false;
+  for (const line of output.split('\n')) {
+    if (line.startsWith('# branch.oid ')) {
+      const oid = line.slice('# branch.oid '.length).trim();
+      commit = oid === '(initial)' ? null : oid;
+    } else if (line.startsWith('# branch.head ')) {
+      const head = line.slice('# branch.head '.length).trim();
+      branch = head === '(detached)' ? null : head;
+    } else if (line && !line.startsWith('#')) {
+      dirty = true;
+    }
+  }
+  return commit ? { commit, branch, dirty } : null;
+}
+
+/**
+ * Git state of the repository a project lives in, or null when it isn't
+ * in one or git isn't installed
+ */
+export async function readGitInfo(projectPath: string): Promise<GitInfo | null> {
+  // Sources live next to the .xcodeproj/.xcworkspace bundle
+  const dir = /\.(xcodeproj|xcworkspace)$/.test(projectPath) ? path.dirname(projectPath) : projectPath;
+  try {
+    return parseGitStatus(
+      await runCommand('git', ['status', '--porcelain=v2', '--branch'], { ...TOOL_COMMAND, cwd: dir })
+    );
+  } catch {
+    return null;
+  }
+}
+
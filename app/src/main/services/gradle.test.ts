@@ -0,0 +1,64 @@
+import { describe, it, expect } from 'vitest';
+import { parseModuleBuildScript, parseSettingsModules } from './gradle';
+
+describe('gradle', () => {
+  describe('parseSettingsModules', () => {
+    it('reads Groovy includes', () => {
+      expect(parseSettingsModules("include ':app', ':lib'\ninclude ':feature:login'")).toEqual([
+        { name: ':app', dir: 'app' },
+        { name: ':lib', dir: 'lib' },
+        { name: ':feature:login', dir: 'feature/login' },
+      ]);
+    });
+
+    it('reads Kotlin includes spanning several lines', () => {
+      const source = `rootProject.name = "Sample"
+include(
+    ":app",
+    ":lib"
+)
+include(":wear")`;
+      expect(parseSettingsModules(source).map((module) => module.name)).toEqual([':app', ':lib', ':wear']);
+    });
+
+    it('reads Groovy includes continued with a trailing comma', () => {
+      const source = "include ':app',\n        ':lib'\nrootProject.name = 'Sample'";
+      expect(parseSettingsModules(source).map((module) => module.name)).toEqual([':app', ':lib']);
+    });
+
+    it('ignores commented out modules and includeBuild', () => {
+      const source = `include(":app") // include(":old")
+/* include(":legacy") */
+includeBuild("build-logic")`;
+      expect(parseSettingsModules(source).map((module) => module.name)).toEqual([':app']);
+    });
+
+    it('applies projectDir overrides', () => {
+      const source = `include ':app'
+project(':app').projectDir = new File(rootDir, 'android/app')`;
+      expect(parseSettingsModules(source)).toEqual([{ name: ':app', dir: 'android/app' }]);
+    });
+  });
+
+  describe('parseModuleBuildScript', () => {
+    it('combines flavors and build types into variants', () => {
+      const source = `plugins { id("com.android.application") }
+android {
+    namespace = "com.example.app"
+    defaultConfig { applicationId = "com.example.sample" }
+    flavorDimensions += listOf("tier")
+    productFlavors {
+        create("free") { dimension = "tier" }
+        create("paid") { dimension = "tier" }
+    }
+}`;
+      expect(parseModuleBuildScript(source)).toEqual({
+        isApplication: true,
+        applicationId: 'com.example.sample',
+        buildTypes: ['debug', 'release'],
+        productFlavors: ['free', 'paid'],
+        variants: ['freeDebug', 'freeRelease', 'paidDebug', 'paidRelease'],
+      });
+    });
+  });
+});
@@ -0,0 +1,290 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import { PlasmaError } from './errors';
+
+export interface AndroidModule {
+  name: string; // Gradle path, e.g. ":app"
+  path: string;
+  isApplication: boolean;
+  applicationId: string | null;
+  buildTypes: string[];
+  productFlavors: string[];
+  variants: string[];
+}
+
+export interface AndroidProject {
+  path: string;
+  name: string;
+  settingsFile: string | null;
+  modules: AndroidModule[];
+}
+
+const SETTINGS_FILES = ['settings.gradle.kts', 'settings.gradle'];
+const BUILD_FILES = ['build.gradle.kts', 'build.gradle'];
+
+/**
+ * Whether a file name is a Gradle settings or build script
+ */
+export function isGradleFile(fileName: string): boolean {
+  return SETTINGS_FILES.includes(fileName) || BUILD_FILES.includes(fileName);
+}
+
+function findFile(dir: string, names: string[]): string | null {
+  for (const name of names) {
+    const candidate = path.join(dir, name);
+    if (fs.existsSync(candidate)) {
+      return candidate;
+    }
+  }
+  return null;
+}
+
+/**
+ * Remove // and block comments so they don't produce false matches
+ */
+function stripComments(source: string): string {
+  return source.replace(/\/\*[\s\S]*?\*\//g, '').replace(/(^|[^:])\/\/.*$/gm, '$1');
+}
+
+/**
+ * Return the contents of the first `name { ... }` block, or null if missing
+ */
+function extractBlock(source: string, name: string): string | null {
+  const match = new RegExp(`\\b${name}\\s*\\{`).exec(source);
+  if (!match) {
+    return null;
+  }
+
+  const start = match.index + match[0].length;
+  let depth = 1;
+  for (let i = start; i < source.length; i++) {
+    if (source[i] === '{') depth++;
+    if (source[i] === '}') depth--;
+    if (depth === 0) {
+      return source.slice(start, i);
+    }
+  }
+  return null;
+}
+
+/**
+ * Names of the blocks declared directly inside a container block, e.g.
+ * `release {` (Groovy) or `create("staging") {` / `getByName("release") {` (Kotlin DSL)
+ */
+function childBlocks(block: string): { name: string; body: string }[] {
+  const children: { name: string; body: string }[] = [];
+  let depth = 0;
+  let bodyStart = -1;
+  let currentName: string | null = null;
+
+  for (let i = 0; i < block.length; i++) {
+    const char = block[i];
+    if (char === '{') {
+      if (depth === 0) {
+        const header = block.slice(0, i).split(/[\n;}]/).pop()!.trim();
+        const kotlin = header.match(/^(?:create|getByName|named|register|maybeCreate)\s*\(\s*["']([^"']+)["']\s*\)$/);
+        const groovy = header.match(/^["']?([A-Za-z_][\w]*)["']?$/);
+        currentName = kotlin ? kotlin[1] : groovy ? groovy[1] : null;
+        bodyStart = i + 1;
+      }
+      depth++;
+    } else if (char === '}') {
+      depth--;
+      if (depth === 0 && currentName) {
+        children.push({ name: currentName, body: block.slice(bodyStart, i) });
+        currentName = null;
+      }
+    }
+  }
+
+  return children;
+}
+
+function capitalize(value: string): string {
+  return value.charAt(0).toUpperCase() + value.slice(1);
+}
+
+/**
+ * Arguments of each `include` call: everything up to the matching `)`, or
+ * for Groovy's call without parentheses, the line and any lines it
+ * continues with a trailing comma
+ */
+function includeArguments(content: string): string[] {
+  const calls: string[] = [];
+  for (const match of content.matchAll(/\binclude\b\s*/g)) {
+    const start = match.index! + match[0].length;
+    if (content[start] === '(') {
+      let depth = 0;
+      for (let i = start; i < content.length; i++) {
+        if (content[i] === '(') depth++;
+        if (content[i] === ')') depth--;
+        if (depth === 0) {
+          calls.push(content.slice(start + 1, i));
+          break;
+        }
+      }
+    } else {
+      let end = start;
+      do {
+        const newline = content.indexOf('\n', end);
+        end = newline === -1 ? content.length : newline + 1;
+      } while (end < content.length && content.slice(start, end).trimEnd().endsWith(','));
+      calls.push(content.slice(start, end));
+    }
+  }
+  return calls;
+}
+
+/**
+ * Parse the modules included by a settings script, mapped to their directories
+ */
+export function parseSettingsModules(source: string): { name: string; dir: string }[] {
+  const content = stripComments(source);
+  const names: string[] = [];
+
+  // include ':app', ':lib'  |  include(":app", ":lib")  |  include ":app", both across lines
+  for (const args of includeArguments(content)) {
+    for (const quoted of args.matchAll(/["']([^"']+)["']/g)) {
+      const name = quoted[1].startsWith(':') ? quoted[1] : `:${quoted[1]}`;
+      if (!names.includes(name)) {
+        names.push(name);
+      }
+    }
+  }
+
+  // project(':app').projectDir = file('android/app')  |  project(":app").projectDir = file("android/app")
+  const overrides = new Map<string, string>();
+  for (const match of content.matchAll(
+    /project\(\s*["']([^"']+)["']\s*\)\.projectDir\s*=\s*(?:new\s+)?(?:file|File)\(\s*(?:[^,)]+,\s*)?["']([^"']+)["']\s*\)/g
+  )) {
+    overrides.set(match[1], match[2]);
+  }
+
+  return names.map((name) => ({
+    name,
+    dir: overrides.get(name) ?? name.slice(1).replace(/:/g, '/'),
+  }));
+}
+
+/**
+ * Parse a module build script for its application id, build types, and flavors
+ */
+export function parseModuleBuildScript(source: string): Omit<AndroidModule, 'name' | 'path'> {
+  const content = stripComments(source);
+
+  const isApplication =
+    /com\.android\.application|android\.application|\bapplicationId\b/.test(content);
+  const defaultConfig = extractBlock(content, 'defaultConfig') ?? content;
+  const applicationIdMatch = defaultConfig.match(/\bapplicationId\s*(?:=\s*)?["']([^"']+)["']/);
+  const namespaceMatch = content.match(/\bnamespace\s*(?:=\s*)?["']([^"']+)["']/);
+
+  // debug and release always exist, even if not configured
+  const buildTypes = ['debug', 'release'];
+  const buildTypesBlock = extractBlock(content, 'buildTypes');
+  if (buildTypesBlock) {
+    for (const { name } of childBlocks(buildTypesBlock)) {
+      if (!buildTypes.includes(name)) {
+        buildTypes.push(name);
+      }
+    }
+  }
+
+  // Flavors are grouped by dimension; variants combine one flavor per dimension
+  const dimensionOrder: string[] = [];
+  const dimensionsMatch = content.match(/flavorDimensions\s*(?:\+?=\s*(?:listOf|setOf)?)?\s*\(?([^\n)]*)/);
+  if (dimensionsMatch) {
+    for (const quoted of dimensionsMatch[1].matchAll(/["']([^"']+)["']/g)) {
+      dimensionOrder.push(quoted[1]);
+    }
+  }
+
+  const flavorsByDimension = new Map<string, string[]>();
+  const productFlavors: string[] = [];
+  const flavorsBlock = extractBlock(content, 'productFlavors');
+  if (flavorsBlock) {
+    for (const { name, body } of childBlocks(flavorsBlock)) {
+      productFlavors.push(name);
+      const dimension = body.match(/\bdimension\s*(?:=\s*)?["']([^"']+)["']/)?.[1] ?? '';
+      if (!dimensionOrder.includes(dimension)) {
+        dimensionOrder.push(dimension);
+      }
+      flavorsByDimension.set(dimension, [...(flavorsByDimension.get(dimension) ?? []), name]);
+    }
+  }
+
+  let flavorCombinations: string[][] = [[]];
+  for (const dimension of dimensionOrder) {
+    const flavors = flavorsByDimension.get(dimension);
+    if (!flavors) continue;
+    flavorCombinations = flavorCombinations.flatMap((combo) =>
+      flavors.map((flavor) => [...combo, flavor])
+    );
+  }
+
+  const variants = flavorCombinations.flatMap((combo) =>
+    buildTypes.map((buildType) =>
+      [...combo, buildType].map((part, i) => (i === 0 ? part : capitalize(part))).join('')
+    )
+  );
+
+  return {
+    isApplication,
+    applicationId: applicationIdMatch?.[1] ?? (isApplication ? namespaceMatch?.[1] ?? null : null),
+    buildTypes,
+    productFlavors,
+    variants,
+  };
+}
+
+function readModule(name: string, moduleDir: string): AndroidModule | null {
+  const buildFile = findFile(moduleDir, BUILD_FILES);
+  if (!buildFile) {
+    return null;
+  }
+  return {
+    name,
+    path: moduleDir,
+    ...parseModuleBuildScript(fs.readFileSync(buildFile, 'utf-8')),
+  };
+}
+
+/**
+ * Discover an Android Gradle project's modules, application ids, and variants
+ */
+export function discoverAndroidProject(projectPath: string): AndroidProject {
+  if (!fs.existsSync(projectPath)) {
+    throw new PlasmaError('not_found', 'Path does not exist');
+  }
+
+  const rootDir = fs.statSync(projectPath).isDirectory() ? projectPath : path.dirname(projectPath);
+  const settingsFile = findFile(rootDir, SETTINGS_FILES);
+  const modules: AndroidModule[] = [];
+
+  if (settingsFile) {
+    for (const { name, dir } of parseSettingsModules(fs.readFileSync(settingsFile, 'utf-8'))) {
+      const module = readModule(name, path.resolve(rootDir, dir));
+      if (module) {
+        modules.push(module);
+      }
+    }
+  }
+
+  // Single-module projects keep everything in the root build script
+  if (modules.length === 0) {
+    const rootModule = readModule(':', rootDir);
+    if (rootModule) {
+      modules.push(rootModule);
+    }
+  }
+
+  if (!settingsFile && modules.length === 0) {
+    throw new PlasmaError('project_not_found', 'No Gradle settings or build script found');
+  }
+
+  return {
+    path: rootDir,
+    name: path.basename(rootDir),
+    settingsFile,
+    modules,
+  };
+}
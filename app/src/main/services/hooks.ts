@@ -0,0 +1,95 @@
+import * as path from 'path';
+import { getProjectSettingsForPath } from './database';
+import { PlasmaError } from './errors';
+import { execCommand } from './exec';
+import { readManifest, HookStage, ProjectHooks } from './manifest';
+import { TimeoutError } from './timeouts';
+
+/**
+ * Shell commands run around a build: before it starts, after it succeeds,
+ * and after the app is installed. Hooks come from the project's settings
+ * (one command per line) or from the [hooks] table in plasma.toml; settings
+ * win per stage so a developer can override what the team committed. Hooks
+ * run as shell commands, so only Plasma itself can change the settings, and
+ * the server only starts builds, installs, and watch mode, which run them,
+ * for requests from Plasma itself or tools on the same Mac.
+ */
+
+/** Project settings key holding each stage's commands */
+export const HOOK_SETTING_KEYS: Record<HookStage, string> = {
+  preBuild: 'preBuildHook',
+  postBuild: 'postBuildHook',
+  postInstall: 'postInstallHook',
+};
+
+export interface HookContext {
+  projectPath: string;
+  scheme?: string;
+  configuration?: string;
+  udid?: string;
+  appPath?: string;
+}
+
+/**
+ * Hooks configured for a project, merging settings over plasma.toml
+ */
+export function loadHooks(projectPath: string): ProjectHooks {
+  const hooks: ProjectHooks = { ...(readManifest(projectPath)?.hooks ?? {}) };
+
+  const settings = getProjectSettingsForPath(projectPath);
+  for (const [stage, key] of Object.entries(HOOK_SETTING_KEYS) as [HookStage, string][]) {
+    const value = settings[key];
+    if (value !== undefined) {
+      hooks[stage] = value.split('\n').map((line) => line.trim()).filter(Boolean);
+    }
+  }
+
+  return hooks;
+}
+
+async function runHookCommand(
+  command: string,
+  stage: HookStage,
+  context: HookContext,
+  onLine: (line: string) => void
+): Promise<void> {
+  const cwd = /\.(xcodeproj|xcworkspace)$/.test(context.projectPath)
+    ? path.dirname(context.projectPath)
+    : context.projectPath;
+
+  const env = {
+    ...process.env,
+    PLASMA_HOOK: stage,
+    PLASMA_PROJECT_PATH: context.projectPath,
+    PLASMA_SCHEME: context.scheme ?? '',
+    PLASMA_CONFIGURATION: context.configuration ?? '',
+    PLASMA_SIMULATOR_UDID: context.udid ?? '',
+    PLASMA_APP_PATH: context.appPath ?? '',
+  };
+  const result = await execCommand('/bin/sh', ['-c', command], { timeout: 'build', cwd, env, onLine }).catch(
+    (err: Error) => {
+      // Timeouts are reported as such; anything else means the shell couldn't be started
+      throw err instanceof TimeoutError
+        ? err
+        : new PlasmaError('hook_failed', `${stage} hook "${command}" failed: ${err.message}`);
+    }
+  );
+  if (result.code !== 0) {
+    throw new PlasmaError('hook_failed', `${stage} hook "${command}" exited with code ${result.code}`);
+  }
+}
+
+/**
+ * Run a stage's commands in order, stopping at the first failure
+ */
+export async function runHooks(
+  stage: HookStage,
+  context: HookContext,
+  onLine: (line: string) => void,
+  hooks: ProjectHooks = loadHooks(context.projectPath)
+): Promise<void> {
+  for (const command of hooks[stage] ?? []) {
+    onLine(`[${stage}] $ ${command}`);
+    await runHookCommand(command, stage, context, (line) => onLine(`[${stage}] ${line}`));
+  }
+}
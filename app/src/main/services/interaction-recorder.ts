@@ -0,0 +1,269 @@
+import { AccessibilityElement, describeScreen, elementAt } from './accessibility';
+import type { AutomationScript, AutomationStep } from './automation';
+import { PlasmaError } from './errors';
+import type { HardwareButton } from './simulator';
+
+/**
+ * Records the input forwarded to a simulator so a manual session can be
+ * turned into a regression test. Taps are resolved to the accessibility
+ * element under them, looked up as the touch begins and before the app
+ * reacts, so generated tests query elements instead of coordinates.
+ */
+
+/** Element a step acted on, as XCUITest would query it */
+export interface RecordedElement {
+  type: string;
+  identifier?: string;
+  label?: string;
+}
+
+/** Normalized (0-1) screen coordinates */
+export interface RecordedPoint {
+  x: number;
+  y: number;
+}
+
+export type RecordedAction =
+  | { action: 'tap'; point: RecordedPoint; element?: RecordedElement }
+  | { action: 'swipe'; from: RecordedPoint; to: RecordedPoint; durationMs: number }
+  | { action: 'button'; button: HardwareButton };
+
+/** An action plus the time since the previous one */
+export type RecordedStep = RecordedAction & { delayMs: number };
+
+interface PendingTouch {
+  start: RecordedPoint;
+  startedAt: number;
+  element: Promise<RecordedElement | undefined>;
+}
+
+interface InteractionRecording {
+  lastStepAt: number;
+  // Steps resolve asynchronously (taps look up their element), kept in input order
+  steps: Promise<RecordedStep>[];
+  touch: PendingTouch | null;
+}
+
+// Movement below this fraction of the screen still counts as a tap
+const TAP_THRESHOLD = 0.02;
+
+const recordings = new Map<string, InteractionRecording>();
+
+export function startInteractionRecording(udid: string): void {
+  if (recordings.has(udid)) {
+    throw new PlasmaError('invalid_request', `Interactions on simulator ${udid} are already being recorded`);
+  }
+  recordings.set(udid, { lastStepAt: Date.now(), steps: [], touch: null });
+}
+
+export function isRecordingInteractions(udid: string): boolean {
+  return recordings.has(udid);
+}
+
+/**
+ * Stop recording and return the steps in the order they happened
+ */
+export async function stopInteractionRecording(udid: string): Promise<RecordedStep[]> {
+  const recording = recordings.get(udid);
+  if (!recording) {
+    throw new PlasmaError('session_not_found', `No interaction recording for simulator ${udid}`);
+  }
+  recordings.delete(udid);
+  return Promise.all(recording.steps);
+}
+
+/**
+ * Look up the element under a normalized point, or undefined when it can't be resolved
+ */
+async function resolveElement(udid: string, point: RecordedPoint): Promise<RecordedElement | undefined> {
+  try {
+    const tree = await describeScreen(udid);
+    const screen = tree[0]?.frame;
+    if (!screen || screen.width === 0 || screen.height === 0) {
+      return undefined;
+    }
+    const element = elementAt(tree, screen.x + point.x * screen.width, screen.y + point.y * screen.height);
+    return element ? toRecordedElement(element) : undefined;
+  } catch (err) {
+    // Without AXe the tap is still recorded, just by coordinate
+    console.error('[interaction-recorder] Failed to resolve element:', err);
+    return undefined;
+  }
+}
+
+function toRecordedElement(element: AccessibilityElement): RecordedElement {
+  return { type: element.type, identifier: element.identifier, label: element.label };
+}
+
+function addStep(udid: string, action: RecordedAction | Promise<RecordedAction>): void {
+  const recording = recordings.get(udid);
+  if (!recording) {
+    return;
+  }
+  const now = Date.now();
+  const delayMs = now - recording.lastStepAt;
+  recording.lastStepAt = now;
+  recording.steps.push(Promise.resolve(action).then((resolved) => ({ ...resolved, delayMs })));
+}
+
+/**
+ * Record a forwarded touch; a press and release become a tap or a swipe
+ */
+export function recordTouch(udid: string, type: 'began' | 'moved' | 'ended', point: RecordedPoint): void {
+  const recording = recordings.get(udid);
+  if (!recording) {
+    return;
+  }
+
+  if (type === 'began') {
+    recording.touch = { start: point, startedAt: Date.now(), element: resolveElement(udid, point) };
+    return;
+  }
+  if (!recording.touch || type === 'moved') {
+    return;
+  }
+
+  const { start, startedAt, element } = recording.touch;
+  recording.touch = null;
+  if (Math.abs(point.x - start.x) <= TAP_THRESHOLD && Math.abs(point.y - start.y) <= TAP_THRESHOLD) {
+    addStep(udid, element.then((resolved): RecordedAction => ({ action: 'tap', point: start, element: resolved })));
+  } else {
+    addStep(udid, { action: 'swipe', from: start, to: point, durationMs: Date.now() - startedAt });
+  }
+}
+
+/**
+ * Record a tap at a normalized point, resolving the element under it unless
+ * the caller already knows it. Call before forwarding the tap.
+ */
+export function recordTap(udid: string, point: RecordedPoint, element?: AccessibilityElement): void {
+  if (!recordings.has(udid)) {
+    return;
+  }
+  addStep(
+    udid,
+    element
+      ? { action: 'tap', point, element: toRecordedElement(element) }
+      : resolveElement(udid, point).then((resolved): RecordedAction => ({ action: 'tap', point, element: resolved }))
+  );
+}
+
+export function recordSwipe(udid: string, from: RecordedPoint, to: RecordedPoint, durationMs: number): void {
+  addStep(udid, { action: 'swipe', from, to, durationMs });
+}
+
+export function recordButton(udid: string, button: HardwareButton): void {
+  addStep(udid, { action: 'button', button });
+}
+
+// XCUIElement.ElementType query for common accessibility element types
+const XCUI_QUERIES: Record<string, string> = {
+  Button: 'buttons',
+  StaticText: 'staticTexts',
+  TextField: 'textFields',
+  SecureTextField: 'secureTextFields',
+  TextView: 'textViews',
+  SearchField: 'searchFields',
+  Cell: 'cells',
+  Switch: 'switches',
+  Toggle: 'switches',
+  Slider: 'sliders',
+  Image: 'images',
+  Link: 'links',
+  Tab: 'tabs',
+  Heading: 'staticTexts',
+};
+
+function swiftString(value: string): string {
+  return JSON.stringify(value);
+}
+
+function swiftNumber(value: number): string {
+  return value.toFixed(3);
+}
+
+function coordinate(point: RecordedPoint): string {
+  return `app.coordinate(withNormalizedOffset: CGVector(dx: ${swiftNumber(point.x)}, dy: ${swiftNumber(point.y)}))`;
+}
+
+function elementQuery(element: RecordedElement): string {
+  const query = XCUI_QUERIES[element.type] ?? 'descendants(matching: .any)';
+  return `app.${query}[${swiftString(element.identifier ?? element.label ?? '')}]`;
+}
+
+const XCUI_BUTTONS: Record<HardwareButton, string | null> = {
+  home: '.home',
+  // XCUIDevice can't press lock or the side button
+  lock: null,
+  sideButton: null,
+};
+
+/**
+ * Render recorded steps as an XCUITest method
+ */
+export function toXCUITest(steps: RecordedStep[], testName = 'testRecordedInteraction'): string {
+  if (!/^[A-Za-z_][A-Za-z0-9_]*$/.test(testName)) {
+    throw new PlasmaError('invalid_request', `${testName} is not a valid Swift method name`);
+  }
+  const lines = ['let app = XCUIApplication()', 'app.launch()'];
+
+  for (const step of steps) {
+    if (step.delayMs >= 2000) {
+      lines.push('', `Thread.sleep(forTimeInterval: ${(step.delayMs / 1000).toFixed(1)})`);
+    }
+    switch (step.action) {
+      case 'tap':
+        if (step.element && (step.element.identifier || step.element.label)) {
+          lines.push(`${elementQuery(step.element)}.tap()`);
+        } else {
+          lines.push(`${coordinate(step.point)}.tap()`);
+        }
+        break;
+      case 'swipe':
+        lines.push(`${coordinate(step.from)}.press(forDuration: 0.05, thenDragTo: ${coordinate(step.to)})`);
+        break;
+      case 'button': {
+        const button = XCUI_BUTTONS[step.button];
+        lines.push(button ? `XCUIDevice.shared.press(${button})` : `// ${step.button} button press isn't supported by XCUIDevice`);
+        break;
+      }
+    }
+  }
+
+  const body = lines.map((line) => (line ? `    ${line}` : '')).join('\n');
+  return `func ${testName}() throws {\n${body}\n}\n`;
+}
+
+const SCRIPT_BUTTONS: Record<HardwareButton, 'home' | 'lock' | 'side-button'> = {
+  home: 'home',
+  lock: 'lock',
+  sideButton: 'side-button',
+};
+
+/**
+ * Render recorded steps as a replayable automation script
+ */
+export function toAutomationScript(steps: RecordedStep[], name?: string): AutomationScript {
+  const scriptSteps: AutomationStep[] = [];
+
+  for (const step of steps) {
+    if (step.delayMs >= 1000) {
+      scriptSteps.push({ wait: Math.round(step.delayMs / 100) / 10 });
+    }
+    switch (step.action) {
+      case 'tap': {
+        const text = step.element?.identifier ?? step.element?.label;
+        scriptSteps.push({ tap: text ?? step.point });
+        break;
+      }
+      case 'swipe':
+        scriptSteps.push({ swipe: { from: step.from, to: step.to, duration: step.durationMs / 1000 } });
+        break;
+      case 'button':
+        scriptSteps.push({ button: SCRIPT_BUTTONS[step.button] });
+        break;
+    }
+  }
+
+  return { name, steps: scriptSteps };
+}
@@ -0,0 +1,45 @@
+import { describe, it, expect } from 'vitest';
+import { detectArchMismatch } from './architectures';
+import { BuildOutputFormatter } from './build-format';
+import { LineSplitter } from './lines';
+
+describe('LineSplitter', () => {
+  it('holds a line split across chunks until it completes', () => {
+    const lines = new LineSplitter();
+    expect(lines.push(Buffer.from('first\nsec'))).toEqual(['first']);
+    expect(lines.push(Buffer.from('ond\n\nthird'))).toEqual(['second']);
+    expect(lines.end()).toEqual(['third']);
+  });
+
+  it('holds a UTF-8 character split across chunks', () => {
+    const bytes = Buffer.from('Compiling Café.swift\n');
+    const split = bytes.indexOf(0xc3) + 1;
+    const lines = new LineSplitter();
+    expect(lines.push(bytes.subarray(0, split))).toEqual([]);
+    expect(lines.push(bytes.subarray(split))).toEqual(['Compiling Café.swift']);
+    expect(lines.end()).toEqual([]);
+  });
+
+  it('formats and checks the architecture of lines split across chunks', () => {
+    const compile =
+      'SwiftCompile normal arm64 /Users/me/App/Sources/ContentView.swift ' +
+      "(in target 'App' from project 'App')\n";
+    const mismatch =
+      "ld: building for 'iOS-simulator', but linking in object file (/Users/me/Lib.a[2](Lib.o)) " +
+      "built for 'iOS'\n";
+    const lines = new LineSplitter();
+    const formatter = new BuildOutputFormatter();
+    const received: string[] = [];
+    for (const chunk of [compile.slice(0, 30), compile.slice(30) + mismatch.slice(0, 20), mismatch.slice(20)]) {
+      received.push(...lines.push(Buffer.from(chunk)));
+    }
+
+    expect(received).toEqual([compile.trimEnd(), mismatch.trimEnd()]);
+    expect(formatter.format(received[0])).toEqual({
+      kind: 'compile',
+      text: 'Compiling ContentView.swift',
+      target: 'App',
+    });
+    expect(detectArchMismatch(received[1])).toBe('x86_64');
+  });
+});
@@ -0,0 +1,30 @@
+import { StringDecoder } from 'string_decoder';
+
+/**
+ * Splits output that arrives in chunks into whole lines. A chunk can end in
+ * the middle of a line, or of a UTF-8 character, so what's left is kept
+ * until the next chunk completes it.
+ */
+export class LineSplitter {
+  private decoder = new StringDecoder('utf8');
+  private buffered = '';
+
+  /**
+   * Append a chunk and return the non-blank lines it completed
+   */
+  push(chunk: Buffer | string): string[] {
+    const text = typeof chunk === 'string' ? chunk : this.decoder.write(chunk);
+    const lines = (this.buffered + text).split('\n');
+    this.buffered = lines.pop() ?? '';
+    return lines.filter((line) => line.trim());
+  }
+
+  /**
+   * The last line, when the output didn't end with a newline
+   */
+  end(): string[] {
+    const rest = this.buffered + this.decoder.end();
+    this.buffered = '';
+    return rest.trim() ? [rest] : [];
+  }
+}
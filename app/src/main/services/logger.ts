@@ -0,0 +1,156 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import { format } from 'util';
+import { getDataSubdir } from './paths';
+
+/**
+ * File logging for bug reports
+ * Console output is mirrored to JSON lines under <data dir>/logs, one file
+ * per day, so logs from headless or crashed sessions can be attached later.
+ */
+
+export type LogLevel = 'debug' | 'info' | 'warn' | 'error';
+
+export interface LogEntry {
+  timestamp: string;
+  level: LogLevel;
+  scope?: string;
+  message: string;
+}
+
+/** Daily log files kept before the oldest are removed */
+const RETENTION_DAYS = 7;
+
+const LOG_FILE_PATTERN = /^plasma-(\d{4}-\d{2}-\d{2})\.log$/;
+
+let currentDate: string | null = null;
+let currentStream: fs.WriteStream | null = null;
+let initialized = false;
+
+/**
+ * Directory holding the daily log files
+ */
+export function getLogsDir(): string {
+  return getDataSubdir('logs');
+}
+
+function logFileForDate(date: string): string {
+  return path.join(getLogsDir(), `plasma-${date}.log`);
+}
+
+function toEntry(level: LogLevel, args: unknown[]): LogEntry {
+  const text = format(...args);
+  // Most call sites prefix their output with "[scope]"
+  const match = text.match(/^\[([\w:-]+)\]\s*/);
+  return {
+    timestamp: new Date().toISOString(),
+    level,
+    scope: match?.[1],
+    message: match ? text.slice(match[0].length) : text,
+  };
+}
+
+function pruneOldLogs(): void {
+  const cutoff = new Date(Date.now() - RETENTION_DAYS * 24 * 60 * 60 * 1000).toISOString().slice(0, 10);
+  for (const file of fs.readdirSync(getLogsDir())) {
+    const match = file.match(LOG_FILE_PATTERN);
+    if (match && match[1] < cutoff) {
+      fs.rmSync(path.join(getLogsDir(), file), { force: true });
+    }
+  }
+}
+
+function streamForToday(): fs.WriteStream {
+  const today = new Date().toISOString().slice(0, 10);
+  if (!currentStream || currentDate !== today) {
+    currentStream?.end();
+    currentDate = today;
+    currentStream = fs.createWriteStream(logFileForDate(today), { flags: 'a' });
+    currentStream.on('error', () => {
+      // Never let logging take the app down
+    });
+    try {
+      pruneOldLogs();
+    } catch {
+      // Ignore cleanup failures
+    }
+  }
+  return currentStream;
+}
+
+function writeEntry(entry: LogEntry): void {
+  try {
+    streamForToday().write(`${JSON.stringify(entry)}\n`);
+  } catch {
+    // Never let logging take the app down
+  }
+}
+
+/**
+ * Mirror console output to the daily log file
+ * Safe to call more than once.
+ */
+export function initLogging(): void {
+  if (initialized) {
+    return;
+  }
+  initialized = true;
+
+  const methods: ['log' | 'info' | 'warn' | 'error' | 'debug', LogLevel][] = [
+    ['log', 'info'],
+    ['info', 'info'],
+    ['warn', 'warn'],
+    ['error', 'error'],
+    ['debug', 'debug'],
+  ];
+
+  for (const [method, level] of methods) {
+    const original = console[method].bind(console);
+    console[method] = (...args: unknown[]) => {
+      original(...args);
+      writeEntry(toEntry(level, args));
+    };
+  }
+
+  // Written synchronously because the process is about to die
+  process.on('uncaughtExceptionMonitor', (err) => {
+    const entry = toEntry('error', ['[crash] Uncaught exception:', err]);
+    try {
+      fs.appendFileSync(logFileForDate(entry.timestamp.slice(0, 10)), `${JSON.stringify(entry)}\n`);
+    } catch {
+      // Nothing left to report to
+    }
+  });
+}
+
+/**
+ * Read log entries, oldest first, optionally only those after since
+ */
+export function readLogs(since?: Date, limit: number = 1000): LogEntry[] {
+  const sinceDate = since ? since.toISOString().slice(0, 10) : null;
+  const files = fs
+    .readdirSync(getLogsDir())
+    .filter((file) => {
+      const match = file.match(LOG_FILE_PATTERN);
+      return match && (!sinceDate || match[1] >= sinceDate);
+    })
+    .sort();
+
+  const entries: LogEntry[] = [];
+  for (const file of files) {
+    const content = fs.readFileSync(path.join(getLogsDir(), file), 'utf-8');
+    for (const line of content.split('\n')) {
+      if (!line) continue;
+      try {
+        const entry = JSON.parse(line) as LogEntry;
+        if (!since || new Date(entry.timestamp) > since) {
+          entries.push(entry);
+        }
+      } catch {
+        // Skip lines truncated by a crash
+      }
+    }
+  }
+
+  return entries.slice(-limit);
+}
@@ -0,0 +1,150 @@
+import { spawn, ChildProcess } from 'child_process';
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { PlasmaError } from './errors';
+import { runCommand, TOOL_COMMAND } from './exec';
+import type { LaunchOptions } from './manifest';
+import { getDataSubdir } from './paths';
+import { readAppBundleInfo } from './plist';
+
+/**
+ * Running macOS and Mac Catalyst builds on this Mac. Apps are launched from
+ * their executable so launch arguments and environment apply the same way
+ * they do on a simulator, with output going to a log file per app. Windows
+ * are captured with screencapture, which needs the Screen Recording
+ * permission.
+ */
+
+export interface MacLaunchResult {
+  message: string;
+  bundleId: string;
+  pid: number;
+  /** File the app's stdout and stderr are written to */
+  logPath: string;
+}
+
+/** How long to wait for a launched app to put a window on screen */
+const WINDOW_TIMEOUT_MS = 10_000;
+const WINDOW_POLL_MS = 250;
+
+// Apps launched by Plasma, by bundle ID, so a relaunch replaces them
+const runningApps = new Map<string, ChildProcess>();
+
+/**
+ * Launch a Mac app bundle, terminating the instance Plasma launched before
+ */
+export async function launchMacApp(appPath: string, launchOptions?: LaunchOptions): Promise<MacLaunchResult> {
+  if (process.platform !== 'darwin') {
+    throw new PlasmaError('invalid_request', 'Mac apps can only be launched on macOS');
+  }
+  const info = readAppBundleInfo(appPath);
+  const executable = path.join(appPath, 'Contents', 'MacOS', info.executable ?? path.basename(appPath, '.app'));
+  if (!fs.existsSync(executable)) {
+    throw new PlasmaError('invalid_request', `${appPath} is not a Mac app bundle`);
+  }
+
+  await terminateMacApp(info.bundleId);
+
+  const logPath = path.join(getDataSubdir('mac-apps'), `${info.bundleId}.log`);
+  const log = fs.openSync(logPath, 'w');
+  // Detached so the app keeps running like it would on a simulator
+  const proc = spawn(executable, launchOptions?.arguments ?? [], {
+    detached: true,
+    stdio: ['ignore', log, log],
+    env: { ...process.env, ...launchOptions?.environment },
+  });
+  fs.closeSync(log);
+
+  await new Promise<void>((resolve, reject) => {
+    proc.once('spawn', resolve);
+    proc.once('error', (err) => reject(new PlasmaError('internal', `Could not launch ${info.bundleId}: ${err.message}`)));
+  });
+  proc.unref();
+  runningApps.set(info.bundleId, proc);
+  proc.once('exit', () => {
+    if (runningApps.get(info.bundleId) === proc) {
+      runningApps.delete(info.bundleId);
+    }
+  });
+
+  return {
+    message: `App ${info.bundleId} launched successfully`,
+    bundleId: info.bundleId,
+    pid: proc.pid!,
+    logPath,
+  };
+}
+
+/**
+ * Terminate a Mac app Plasma launched, waiting for it to exit
+ */
+export async function terminateMacApp(bundleId: string): Promise<boolean> {
+  const proc = runningApps.get(bundleId);
+  if (!proc || proc.exitCode !== null || proc.signalCode !== null) {
+    return false;
+  }
+  runningApps.delete(bundleId);
+  await new Promise<void>((resolve) => {
+    const timer = setTimeout(() => {
+      proc.kill('SIGKILL');
+      resolve();
+    }, 5000);
+    proc.once('exit', () => {
+      clearTimeout(timer);
+      resolve();
+    });
+    proc.kill('SIGTERM');
+  });
+  return true;
+}
+
+// Prints the number of the process's frontmost normal window, or nothing.
+// 17 is kCGWindowListOptionOnScreenOnly | kCGWindowListExcludeDesktopElements.
+const FIND_WINDOW_SCRIPT = `
+ObjC.import('CoreGraphics');
+function run(argv) {
+  const pid = Number(argv[0]);
+  const windows = ObjC.deepUnwrap(ObjC.castRefToObject($.CGWindowListCopyWindowInfo(17, 0))) || [];
+  const window = windows.find((w) => w.kCGWindowOwnerPID === pid && w.kCGWindowLayer === 0);
+  return window ? String(window.kCGWindowNumber) : '';
+}
+`;
+
+async function findWindow(pid: number): Promise<number | null> {
+  const output = await runCommand(
+    'osascript',
+    ['-l', 'JavaScript', '-e', FIND_WINDOW_SCRIPT, String(pid)],
+    TOOL_COMMAND
+  );
+  const windowId = Number(output.trim());
+  return output.trim() && Number.isInteger(windowId) ? windowId : null;
+}
+
+/**
+ * Capture the main window of an app Plasma launched as PNG, waiting for it
+ * to appear. Other processes' windows are off limits.
+ */
+export async function captureMacAppWindow(pid: number): Promise<Buffer> {
+  if (![...runningApps.values()].some((proc) => proc.pid === pid)) {
+    throw new PlasmaError('not_found', `No app launched by Plasma is running as process ${pid}`);
+  }
+  const deadline = Date.now() + WINDOW_TIMEOUT_MS;
+  let windowId = await findWindow(pid);
+  while (windowId === null && Date.now() < deadline) {
+    await new Promise((resolve) => setTimeout(resolve, WINDOW_POLL_MS));
+    windowId = await findWindow(pid);
+  }
+  if (windowId === null) {
+    throw new PlasmaError('not_found', `Process ${pid} has no window on screen`);
+  }
+
+  const screenshotPath = path.join(fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-screenshot-')), 'window.png');
+  try {
+    // -o leaves out the window shadow, -x the capture sound
+    await runCommand('screencapture', ['-x', '-o', '-t', 'png', '-l', String(windowId), screenshotPath], TOOL_COMMAND);
+    return fs.readFileSync(screenshotPath);
+  } finally {
+    fs.rmSync(path.dirname(screenshotPath), { recursive: true, force: true });
+  }
+}
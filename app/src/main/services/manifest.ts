@@ -0,0 +1,221 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import type { AscConfig } from './asc';
+import type { BuildCacheConfig } from './build-cache';
+import type { FixtureContact, FixtureEvent, FixtureSet } from './fixtures';
+import { PlasmaError } from './errors';
+import { parseToml, TomlTable, TomlValue } from './toml';
+
+/**
+ * Optional plasma.toml checked into a repository so teams can share
+ * Plasma configuration.
+ */
+
+export const MANIFEST_FILE_NAME = 'plasma.toml';
+
+export type HookStage = 'preBuild' | 'postBuild' | 'postInstall';
+
+export type ProjectHooks = Partial<Record<HookStage, string[]>>;
+
+export interface ProjectManifest {
+  /** Path of the plasma.toml the values were read from */
+  path: string;
+  scheme?: string;
+  configuration?: string;
+  /** Simulator name or UDID to run on by default */
+  device?: string;
+  launchArguments: string[];
+  environment: Record<string, string>;
+  hooks: ProjectHooks;
+  /** Retries of failed tests, from [tests.retry] */
+  testRetry: { maxAttempts?: number; retryOn?: string[] };
+  /** Build product cache from [build_cache], null when it's off */
+  buildCache: BuildCacheConfig | null;
+  /** Named fixture sets from [fixtures.<name>] */
+  fixtures: FixtureSet[];
+  /** App Store Connect API key from [app_store_connect] */
+  appStoreConnect: AscConfig;
+}
+
+/** Launch arguments and environment passed to the app on launch */
+export interface LaunchOptions {
+  arguments: string[];
+  environment: Record<string, string>;
+}
+
+function toStringValue(value: TomlValue | undefined): string | undefined {
+  return typeof value === 'string' && value.trim() ? value : undefined;
+}
+
+function toTable(value: TomlValue | undefined): TomlTable {
+  return typeof value === 'object' && !Array.isArray(value) ? value : {};
+}
+
+function toStringList(value: TomlValue | undefined): string[] | undefined {
+  if (typeof value === 'string') {
+    return value.trim() ? [value] : [];
+  }
+  if (Array.isArray(value)) {
+    return value.filter((item): item is string => typeof item === 'string' && item.trim() !== '');
+  }
+  return undefined;
+}
+
+function toTableList(value: TomlValue | undefined): TomlTable[] {
+  return Array.isArray(value) ? value.map(toTable) : [];
+}
+
+/**
+ * Fixture sets, with file paths resolved against the manifest's directory
+ */
+function readFixtureSets(fixtures: TomlTable, baseDir: string): FixtureSet[] {
+  const resolvePaths = (value: TomlValue | undefined) =>
+    (toStringList(value) ?? []).map((file) => path.resolve(baseDir, file));
+
+  return Object.entries(fixtures).map(([name, value]) => {
+    const set = toTable(value);
+    const contacts = toTableList(set.contacts).map(
+      (contact): FixtureContact => ({
+        firstName: toStringValue(contact.first_name),
+        lastName: toStringValue(contact.last_name),
+        phone: toStringValue(contact.phone),
+        email: toStringValue(contact.email),
+        organization: toStringValue(contact.organization),
+      })
+    );
+    const events = toTableList(set.events).map((event): FixtureEvent => {
+      const title = toStringValue(event.title);
+      const start = toStringValue(event.start);
+      const end = toStringValue(event.end);
+      if (!title || !start || !end || Number.isNaN(Date.parse(start)) || Number.isNaN(Date.parse(end))) {
+        throw new PlasmaError(
+          'invalid_request',
+          `${MANIFEST_FILE_NAME}: events in fixtures.${name} need a title and ISO 8601 start and end strings`
+        );
+      }
+      return {
+        title,
+        start,
+        end,
+        location: toStringValue(event.location),
+        notes: toStringValue(event.notes),
+        allDay: event.all_day === true,
+      };
+    });
+    const helperApp = toStringValue(set.helper_app);
+
+    return {
+      name,
+      photos: resolvePaths(set.photos),
+      contacts,
+      vcards: resolvePaths(set.vcards),
+      events,
+      helper: toStringValue(set.helper),
+      helperApp: helperApp ? path.resolve(baseDir, helperApp) : undefined,
+      erase: set.erase === true,
+    };
+  });
+}
+
+/**
+ * Find plasma.toml next to the project or in a parent directory up to the repository root
+ */
+export function findManifest(projectPath: string): string | null {
+  let dir = /\.(xcodeproj|xcworkspace)$/.test(projectPath) ? path.dirname(projectPath) : projectPath;
+
+  for (;;) {
+    const candidate = path.join(dir, MANIFEST_FILE_NAME);
+    if (fs.existsSync(candidate)) {
+      return candidate;
+    }
+    const parent = path.dirname(dir);
+    if (parent === dir || fs.existsSync(path.join(dir, '.git'))) {
+      return null;
+    }
+    dir = parent;
+  }
+}
+
+/**
+ * Read and parse the project's plasma.toml, or null when there is none
+ */
+export function readManifest(projectPath: string): ProjectManifest | null {
+  const manifestPath = findManifest(projectPath);
+  if (!manifestPath) {
+    return null;
+  }
+
+  const toml = parseToml(fs.readFileSync(manifestPath, 'utf-8'), MANIFEST_FILE_NAME);
+  const hooks = toTable(toml.hooks);
+  const testRetry = toTable(toTable(toml.tests).retry);
+  const buildCache = toml.build_cache === undefined ? null : toTable(toml.build_cache);
+  const buildCacheDir = toStringValue(buildCache?.path);
+  const appStoreConnect = toTable(toml.app_store_connect);
+  const ascKeyPath = toStringValue(appStoreConnect.key_path);
+
+  const environment: Record<string, string> = {};
+  for (const [key, value] of Object.entries(toTable(toml.environment))) {
+    if (typeof value === 'string' || typeof value === 'number' || typeof value === 'boolean') {
+      environment[key] = String(value);
+    }
+  }
+
+  return {
+    path: manifestPath,
+    scheme: toStringValue(toml.scheme),
+    configuration: toStringValue(toml.configuration),
+    device: toStringValue(toml.device),
+    launchArguments: toStringList(toml.launch_arguments) ?? [],
+    environment,
+    hooks: {
+      preBuild: toStringList(hooks.pre_build),
+      postBuild: toStringList(hooks.post_build),
+      postInstall: toStringList(hooks.post_install),
+    },
+    testRetry: {
+      maxAttempts: typeof testRetry.max_attempts === 'number' ? testRetry.max_attempts : undefined,
+      retryOn: toStringList(testRetry.retry_on),
+    },
+    buildCache:
+      buildCache && buildCache.enabled !== false
+        ? {
+            directory: buildCacheDir ? path.resolve(path.dirname(manifestPath), buildCacheDir) : undefined,
+            url: toStringValue(buildCache.url),
+            endpoint: toStringValue(buildCache.endpoint),
+            region: toStringValue(buildCache.region),
+          }
+        : null,
+    fixtures: readFixtureSets(toTable(toml.fixtures), path.dirname(manifestPath)),
+    appStoreConnect: {
+      keyId: toStringValue(appStoreConnect.key_id),
+      issuerId: toStringValue(appStoreConnect.issuer_id),
+      keyPath: ascKeyPath ? path.resolve(path.dirname(manifestPath), ascKeyPath) : undefined,
+    },
+  };
+}
+
+/**
+ * Launch arguments and environment declared in the project's plasma.toml
+ */
+export function getLaunchOptions(projectPath: string): LaunchOptions {
+  const manifest = readManifest(projectPath);
+  return {
+    arguments: manifest?.launchArguments ?? [],
+    environment: manifest?.environment ?? {},
+  };
+}
+
+/**
+ * Project settings defaults declared by a manifest, keyed like the stored settings
+ */
+export function manifestSettings(manifest: ProjectManifest | null): Record<string, string> {
+  if (!manifest) {
+    return {};
+  }
+
+  const settings: Record<string, string> = { manifestPath: manifest.path };
+  if (manifest.scheme) settings.scheme = manifest.scheme;
+  if (manifest.configuration) settings.configuration = manifest.configuration;
+  if (manifest.device) settings.preferredDevice = manifest.device;
+  return settings;
+}
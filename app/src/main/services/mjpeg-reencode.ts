@@ -0,0 +1,113 @@
+import { spawn } from 'child_process';
+import { extractJpegFrames, findFfmpegBinary } from './android-stream';
+import { PlasmaError } from './errors';
+import { MjpegParser } from './mjpeg';
+import { registerProcess } from './process-manager';
+
+/**
+ * Re-encoding of proxied MJPEG streams for clients on slow links (remote
+ * browsers, phones), which can ask for narrower frames or a lower JPEG
+ * quality without changing what simulator-server produces for everyone
+ * else. Frames go through a long-running ffmpeg rather than one process
+ * per frame.
+ */
+
+export interface ReencodeOptions {
+  /** Frames wider than this are scaled down, keeping their aspect ratio */
+  maxWidth?: number;
+  /** JPEG quality from 0 (smallest) to 1 (best) */
+  quality?: number;
+}
+
+// Unsent bytes ffmpeg may fall behind by before incoming frames are skipped
+const MAX_PENDING_BYTES = 2 * 1024 * 1024;
+
+/**
+ * Read maxWidth and quality query parameters, returning null when neither is set
+ */
+export function parseReencodeOptions(params: URLSearchParams): ReencodeOptions | null {
+  const maxWidth = params.get('maxWidth');
+  const quality = params.get('quality');
+  if (maxWidth === null && quality === null) {
+    return null;
+  }
+  const options: ReencodeOptions = {};
+  if (maxWidth !== null) {
+    options.maxWidth = Number(maxWidth);
+    if (!Number.isInteger(options.maxWidth) || options.maxWidth < 16) {
+      throw new PlasmaError('invalid_request', 'maxWidth must be a whole number of pixels, at least 16');
+    }
+  }
+  if (quality !== null) {
+    options.quality = Number(quality);
+    if (quality === '' || isNaN(options.quality) || options.quality < 0 || options.quality > 1) {
+      throw new PlasmaError('invalid_request', 'quality must be between 0 and 1');
+    }
+  }
+  return options;
+}
+
+/**
+ * ffmpeg arguments that read JPEG frames on stdin and write re-encoded ones to stdout
+ */
+export function reencodeArguments(options: ReencodeOptions): string[] {
+  const args = ['-loglevel', 'error', '-fflags', 'nobuffer', '-f', 'image2pipe', '-c:v', 'mjpeg', '-i', 'pipe:0'];
+  if (options.maxWidth) {
+    // Never upscale; keep the height even, as the encoder needs
+    args.push('-vf', `scale='min(${options.maxWidth},iw)':-2`);
+  }
+  // ffmpeg's -q:v goes from 2 (best) to 31 (worst)
+  const quality = Math.max(0, Math.min(1, options.quality ?? 0.7));
+  args.push('-q:v', Math.round(31 - quality * 29).toString(), '-fps_mode', 'passthrough', '-f', 'mjpeg', 'pipe:1');
+  return args;
+}
+
+/**
+ * Re-encode a multipart MJPEG stream, calling onFrame with each new JPEG.
+ * Returns a function that stops re-encoding.
+ */
+export function reencodeMjpeg(
+  source: NodeJS.ReadableStream,
+  options: ReencodeOptions,
+  onFrame: (frame: Buffer) => void
+): () => void {
+  const ffmpeg = findFfmpegBinary();
+  if (!ffmpeg) {
+    throw new PlasmaError('tool_missing', 'ffmpeg is needed to resize streams. Install it with `brew install ffmpeg`.');
+  }
+
+  const transcoder = spawn(ffmpeg, reencodeArguments(options));
+  registerProcess(transcoder);
+  transcoder.stdin.on('error', () => {
+    // Stopping closes the pipe mid-frame; nothing to report
+  });
+
+  const parser = new MjpegParser();
+  const onData = (chunk: Buffer) => {
+    for (const { data, contentType } of parser.push(chunk)) {
+      // Only JPEG frames can be fed to the decoder
+      if ((contentType ?? 'image/jpeg') !== 'image/jpeg') continue;
+      // Keep latency down when ffmpeg can't keep up by skipping frames
+      if (transcoder.stdin.writableLength > MAX_PENDING_BYTES) continue;
+      transcoder.stdin.write(data);
+    }
+  };
+  source.on('data', onData);
+  source.on('end', () => transcoder.stdin.end());
+
+  let buffer = Buffer.alloc(0);
+  transcoder.stdout.on('data', (chunk: Buffer) => {
+    buffer = Buffer.concat([buffer, chunk]);
+    const { frames, rest } = extractJpegFrames(buffer);
+    buffer = rest;
+    for (const frame of frames) {
+      onFrame(frame);
+    }
+  });
+
+  return () => {
+    source.off('data', onData);
+    transcoder.stdin.end();
+    transcoder.kill();
+  };
+}
@@ -0,0 +1,88 @@
+/**
+ * Incremental parser for multipart MJPEG streams with a bounded buffer.
+ * When the upstream produces data faster than it's consumed, whole frames
+ * are discarded at part boundaries instead of letting the buffer grow.
+ */
+
+export const MJPEG_BOUNDARY = '--mjpegstream';
+
+// Upper bound on unparsed bytes held for a single stream
+const MAX_PENDING_BYTES = 8 * 1024 * 1024;
+
+/**
+ * A frame as one part of a multipart stream delimited by MJPEG_BOUNDARY
+ */
+export function encodeMjpegPart(frame: Buffer, contentType = 'image/jpeg'): Buffer {
+  const header = `${MJPEG_BOUNDARY}\r\nContent-Type: ${contentType}\r\nContent-Length: ${frame.length}\r\n\r\n`;
+  return Buffer.concat([Buffer.from(header), frame, Buffer.from('\r\n')]);
+}
+
+export interface MjpegFrame {
+  data: Buffer;
+  /** Content-Type of the part, when the stream declares one */
+  contentType?: string;
+}
+
+export class MjpegParser {
+  private buffer = Buffer.alloc(0);
+
+  /** Frames discarded because the pending buffer hit its limit */
+  droppedFrames = 0;
+
+  constructor(
+    private readonly boundary = MJPEG_BOUNDARY,
+    private readonly maxPendingBytes = MAX_PENDING_BYTES
+  ) {}
+
+  /**
+   * Append a chunk and return the complete frames it finished
+   */
+  push(chunk: Buffer): MjpegFrame[] {
+    this.buffer = this.buffer.length ? Buffer.concat([this.buffer, chunk]) : chunk;
+
+    const frames: MjpegFrame[] = [];
+    let boundaryIndex: number;
+    while ((boundaryIndex = this.buffer.indexOf(this.boundary)) !== -1) {
+      const headerEnd = this.buffer.indexOf('\r\n\r\n', boundaryIndex);
+      if (headerEnd === -1) break;
+
+      const dataStart = headerEnd + 4;
+      const nextBoundary = this.buffer.indexOf(this.boundary, dataStart);
+      if (nextBoundary === -1) break;
+
+      const data = this.buffer.subarray(dataStart, nextBoundary - 2);
+      const contentType = this.buffer
+        .subarray(boundaryIndex, headerEnd)
+        .toString()
+        .match(/Content-Type:\s*([^\r\n]+)/i)?.[1]
+        ?.trim();
+
+      if (data.length > 0) {
+        // Copy so the frame doesn't pin the whole buffer in memory
+        frames.push({ data: Buffer.from(data), contentType });
+      }
+      this.buffer = this.buffer.subarray(nextBoundary);
+    }
+
+    if (this.buffer.length > this.maxPendingBytes) {
+      this.trim();
+    }
+
+    return frames;
+  }
+
+  /**
+   * Drop buffered data up to the last part boundary so parsing resumes
+   * at the start of a frame rather than in the middle of one
+   */
+  private trim(): void {
+    const lastBoundary = this.buffer.lastIndexOf(this.boundary);
+    if (lastBoundary > 0) {
+      this.buffer = Buffer.from(this.buffer.subarray(lastBoundary));
+    } else {
+      // A single part larger than the limit; wait for the next boundary
+      this.buffer = Buffer.alloc(0);
+    }
+    this.droppedFrames++;
+  }
+}
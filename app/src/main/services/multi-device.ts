@@ -0,0 +1,119 @@
+import { PlasmaError, toPlasmaError } from './errors';
+import type { LaunchOptions } from './manifest';
+import { bootSimulator, getOrCreateSession, installAndLaunch, listSimulators } from './simulator';
+
+/**
+ * Running one build on several simulators at once. Boots are limited to a
+ * few at a time, in the order the devices were picked, since booting many
+ * simulators together slows every one of them down. Installs, launches,
+ * and stream sessions then proceed per device as soon as it has booted.
+ */
+
+export type DeviceRunState = 'queued' | 'booting' | 'installing' | 'streaming' | 'failed';
+
+export interface DeviceRunStatus {
+  udid: string;
+  name: string;
+  state: DeviceRunState;
+  bundleId?: string;
+  /** simulator-server stream of the device, once streaming */
+  streamUrl?: string;
+  error?: string;
+  code?: string;
+}
+
+export type MultiDeviceEvent =
+  | { type: 'status'; device: DeviceRunStatus }
+  | { type: 'completed'; devices: DeviceRunStatus[]; succeeded: number; failed: number };
+
+export interface MultiDeviceRunOptions {
+  launchOptions?: LaunchOptions;
+  fps?: number;
+  quality?: number;
+}
+
+/** Simulators booting at the same time */
+export const MAX_CONCURRENT_BOOTS = 2;
+/** Most simulators one run can target */
+export const MAX_RUN_DEVICES = 8;
+
+/**
+ * Limits how many boots run at once, handing out slots in request order
+ */
+class BootQueue {
+  private active = 0;
+  private waiting: Array<() => void> = [];
+
+  constructor(private limit: number) {}
+
+  async run<T>(fn: () => Promise<T>): Promise<T> {
+    if (this.active >= this.limit) {
+      await new Promise<void>((resolve) => this.waiting.push(resolve));
+    }
+    this.active++;
+    try {
+      return await fn();
+    } finally {
+      this.active--;
+      this.waiting.shift()?.();
+    }
+  }
+}
+
+/**
+ * Boot the simulators, install and launch the app on each, and start a
+ * stream session per device. One device failing doesn't stop the others.
+ */
+export async function runOnDevices(
+  appPath: string,
+  udids: string[],
+  options: MultiDeviceRunOptions,
+  onEvent: (event: MultiDeviceEvent) => void
+): Promise<DeviceRunStatus[]> {
+  const unique = [...new Set(udids)];
+  if (unique.length === 0 || unique.length > MAX_RUN_DEVICES) {
+    throw new PlasmaError('invalid_request', `Pick between 1 and ${MAX_RUN_DEVICES} simulators`);
+  }
+  const simulators = await listSimulators();
+  const missing = unique.filter((udid) => !simulators.some((simulator) => simulator.udid === udid));
+  if (missing.length > 0) {
+    throw new PlasmaError('simulator_not_found', `No simulator ${missing.join(', ')}`);
+  }
+
+  const statuses = unique.map(
+    (udid): DeviceRunStatus => ({
+      udid,
+      name: simulators.find((simulator) => simulator.udid === udid)!.name,
+      state: 'queued',
+    })
+  );
+  const update = (status: DeviceRunStatus, changes: Partial<DeviceRunStatus>) => {
+    Object.assign(status, changes);
+    onEvent({ type: 'status', device: { ...status } });
+  };
+  statuses.forEach((status) => onEvent({ type: 'status', device: { ...status } }));
+
+  const queue = new BootQueue(MAX_CONCURRENT_BOOTS);
+  await Promise.all(
+    statuses.map(async (status) => {
+      try {
+        await queue.run(async () => {
+          update(status, { state: 'booting' });
+          await bootSimulator(status.udid);
+        });
+        update(status, { state: 'installing' });
+        const launched = await installAndLaunch(status.udid, appPath, undefined, options.launchOptions);
+        status.bundleId = launched.bundleId;
+        const session = await getOrCreateSession(status.udid, options.fps, options.quality);
+        update(status, { state: 'streaming', streamUrl: session.streamUrl });
+      } catch (err) {
+        const error = toPlasmaError(err);
+        update(status, { state: 'failed', error: error.message, code: error.code });
+      }
+    })
+  );
+
+  const failed = statuses.filter((status) => status.state === 'failed').length;
+  onEvent({ type: 'completed', devices: statuses, succeeded: statuses.length - failed, failed });
+  return statuses;
+}
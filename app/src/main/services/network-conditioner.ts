@@ -0,0 +1,293 @@
+import * as http from 'http';
+import * as net from 'net';
+import { Transform, TransformCallback } from 'stream';
+import { PlasmaError } from './errors';
+
+/**
+ * Network link conditioning per simulator. Simulators share the host's
+ * network stack, so conditions can't be applied at the interface without
+ * affecting the whole Mac; instead each conditioned simulator gets a local
+ * HTTP proxy (CONNECT tunnels for HTTPS) that delays, throttles, and stalls
+ * traffic. Apps launched afterwards get the proxy in HTTP_PROXY/HTTPS_PROXY
+ * and PLASMA_NETWORK_PROXY and route through it from their URLSession
+ * configuration.
+ */
+
+export interface NetworkConditions {
+  /** One-way delay added to every chunk, in milliseconds */
+  latencyMs: number;
+  /** Bandwidth limits in kilobits per second, 0 for unlimited */
+  downloadKbps: number;
+  uploadKbps: number;
+  /** Fraction (0-1) of chunks that are "lost" and delivered after a retransmission delay */
+  packetLoss: number;
+  /** Refuse every connection */
+  offline: boolean;
+}
+
+export type NetworkPreset = 'none' | '3g' | 'lossy-wifi' | 'offline';
+
+export const NETWORK_PRESETS: Record<NetworkPreset, NetworkConditions> = {
+  none: { latencyMs: 0, downloadKbps: 0, uploadKbps: 0, packetLoss: 0, offline: false },
+  '3g': { latencyMs: 100, downloadKbps: 780, uploadKbps: 330, packetLoss: 0, offline: false },
+  'lossy-wifi': { latencyMs: 40, downloadKbps: 10000, uploadKbps: 5000, packetLoss: 0.1, offline: false },
+  offline: { latencyMs: 0, downloadKbps: 0, uploadKbps: 0, packetLoss: 0, offline: true },
+};
+
+export interface NetworkConditionState {
+  udid: string;
+  /** Null when custom conditions were set */
+  preset: NetworkPreset | null;
+  conditions: NetworkConditions;
+  /** http://127.0.0.1:<port>, or null when the simulator isn't conditioned */
+  proxyUrl: string | null;
+}
+
+interface ConditionedSimulator {
+  server: http.Server;
+  port: number;
+  preset: NetworkPreset | null;
+  conditions: NetworkConditions;
+  sockets: Set<net.Socket>;
+}
+
+// Delay before a "lost" chunk is delivered, roughly a TCP retransmission timeout
+const RETRANSMIT_DELAY_MS = 200;
+
+const conditioned = new Map<string, ConditionedSimulator>();
+
+/**
+ * Delays and rate-limits data flowing through it. Conditions are read per
+ * chunk so changes apply to connections that are already open.
+ */
+class ThrottleStream extends Transform {
+  private linkFreeAt = 0;
+  private lastDeliveryAt = 0;
+  private pending = 0;
+  private onDrained: TransformCallback | null = null;
+
+  constructor(private readonly getConditions: () => NetworkConditions, private readonly direction: 'up' | 'down') {
+    super();
+  }
+
+  _transform(chunk: Buffer, _encoding: BufferEncoding, callback: TransformCallback): void {
+    const conditions = this.getConditions();
+    const kbps = this.direction === 'down' ? conditions.downloadKbps : conditions.uploadKbps;
+    const now = Date.now();
+
+    // Time on the wire, then propagation delay; a lost chunk occupies the
+    // link again for its retransmission
+    let transmittedAt = Math.max(now, this.linkFreeAt) + (kbps > 0 ? (chunk.length * 8) / kbps : 0);
+    if (conditions.packetLoss > 0 && Math.random() < conditions.packetLoss) {
+      transmittedAt += RETRANSMIT_DELAY_MS;
+    }
+    this.linkFreeAt = transmittedAt;
+    const deliverAt = Math.max(transmittedAt + conditions.latencyMs, this.lastDeliveryAt);
+    this.lastDeliveryAt = deliverAt;
+
+    // Unconditioned traffic passes straight through once earlier chunks are out
+    if (deliverAt <= now && this.pending === 0) {
+      callback(null, chunk);
+      return;
+    }
+
+    this.pending++;
+    setTimeout(() => {
+      this.push(chunk);
+      this.pending--;
+      if (this.pending === 0 && this.onDrained) {
+        this.onDrained();
+        this.onDrained = null;
+      }
+    }, Math.max(0, deliverAt - now));
+    // Accept the next chunk once this one is off the wire so upstream
+    // backpressure follows the bandwidth limit
+    setTimeout(callback, Math.max(0, transmittedAt - now));
+  }
+
+  _flush(callback: TransformCallback): void {
+    // End only after every delayed chunk was delivered
+    if (this.pending === 0) {
+      callback();
+    } else {
+      this.onDrained = callback;
+    }
+  }
+}
+
+/**
+ * Pipe two sockets together through throttles in both directions
+ */
+function relay(client: net.Socket, upstream: net.Socket, getConditions: () => NetworkConditions): void {
+  client.pipe(new ThrottleStream(getConditions, 'up')).pipe(upstream);
+  upstream.pipe(new ThrottleStream(getConditions, 'down')).pipe(client);
+  // A side that closes normally ends the other once delayed data is
+  // delivered; errors tear both down right away
+  client.on('error', () => upstream.destroy());
+  upstream.on('error', () => client.destroy());
+}
+
+function createProxy(udid: string): http.Server {
+  const getConditions = () => conditioned.get(udid)?.conditions ?? NETWORK_PRESETS.none;
+
+  // Plain HTTP requests arrive with an absolute URL
+  const server = http.createServer((req, res) => {
+    if (getConditions().offline) {
+      res.writeHead(502);
+      res.end('Network is offline');
+      return;
+    }
+
+    let target: URL;
+    try {
+      target = new URL(req.url ?? '');
+    } catch {
+      res.writeHead(400);
+      res.end('Expected an absolute URL');
+      return;
+    }
+
+    const upstream = http.request(
+      {
+        host: target.hostname,
+        port: target.port || 80,
+        method: req.method,
+        path: `${target.pathname}${target.search}`,
+        headers: req.headers,
+      },
+      (upstreamRes) => {
+        res.writeHead(upstreamRes.statusCode ?? 502, upstreamRes.headers);
+        upstreamRes.pipe(new ThrottleStream(getConditions, 'down')).pipe(res);
+      }
+    );
+    upstream.on('error', (err) => {
+      if (!res.headersSent) {
+        res.writeHead(502);
+      }
+      res.end(err.message);
+    });
+    req.pipe(new ThrottleStream(getConditions, 'up')).pipe(upstream);
+  });
+
+  // HTTPS and other TCP traffic is tunneled with CONNECT
+  server.on('connect', (req: http.IncomingMessage, client: net.Socket, head: Buffer) => {
+    if (getConditions().offline) {
+      client.end('HTTP/1.1 502 Bad Gateway\r\n\r\n');
+      return;
+    }
+
+    const [host, port] = (req.url ?? '').split(':');
+    const upstream = net.connect(Number(port) || 443, host, () => {
+      client.write('HTTP/1.1 200 Connection Established\r\n\r\n');
+      if (head.length > 0) {
+        upstream.write(head);
+      }
+      relay(client, upstream, getConditions);
+    });
+    upstream.on('error', () => client.end('HTTP/1.1 502 Bad Gateway\r\n\r\n'));
+  });
+
+  return server;
+}
+
+function validateConditions(conditions: Partial<NetworkConditions>): NetworkConditions {
+  const merged = { ...NETWORK_PRESETS.none, ...conditions };
+  for (const key of ['latencyMs', 'downloadKbps', 'uploadKbps'] as const) {
+    if (typeof merged[key] !== 'number' || merged[key] < 0) {
+      throw new PlasmaError('invalid_request', `${key} must be a non-negative number`);
+    }
+  }
+  if (typeof merged.packetLoss !== 'number' || merged.packetLoss < 0 || merged.packetLoss > 1) {
+    throw new PlasmaError('invalid_request', 'packetLoss must be between 0 and 1');
+  }
+  return { ...merged, offline: merged.offline === true };
+}
+
+function toState(udid: string, simulator: ConditionedSimulator | undefined): NetworkConditionState {
+  return {
+    udid,
+    preset: simulator ? simulator.preset : 'none',
+    conditions: simulator?.conditions ?? NETWORK_PRESETS.none,
+    proxyUrl: simulator ? `http://127.0.0.1:${simulator.port}` : null,
+  };
+}
+
+export function getNetworkConditions(udid: string): NetworkConditionState {
+  return toState(udid, conditioned.get(udid));
+}
+
+/**
+ * Apply a preset or custom conditions, starting the simulator's proxy on first use
+ */
+export async function setNetworkConditions(
+  udid: string,
+  setting: { preset: NetworkPreset } | { conditions: Partial<NetworkConditions> }
+): Promise<NetworkConditionState> {
+  let preset: NetworkPreset | null = null;
+  let conditions: NetworkConditions;
+  if ('preset' in setting) {
+    if (!(setting.preset in NETWORK_PRESETS)) {
+      throw new PlasmaError(
+        'invalid_request',
+        `Unknown network preset ${setting.preset}; expected one of ${Object.keys(NETWORK_PRESETS).join(', ')}`
+      );
+    }
+    preset = setting.preset;
+    conditions = NETWORK_PRESETS[preset];
+  } else {
+    conditions = validateConditions(setting.conditions);
+  }
+
+  const existing = conditioned.get(udid);
+  if (existing) {
+    existing.preset = preset;
+    existing.conditions = conditions;
+    if (conditions.offline) {
+      // Going offline drops connections that are already open
+      existing.sockets.forEach((socket) => socket.destroy());
+    }
+    return toState(udid, existing);
+  }
+
+  const server = createProxy(udid);
+  const sockets = new Set<net.Socket>();
+  server.on('connection', (socket: net.Socket) => {
+    sockets.add(socket);
+    socket.on('close', () => sockets.delete(socket));
+  });
+  const port = await new Promise<number>((resolve, reject) => {
+    server.once('error', (err) => reject(new PlasmaError('internal', `Failed to start network proxy: ${err.message}`)));
+    server.listen(0, '127.0.0.1', () => resolve((server.address() as net.AddressInfo).port));
+  });
+
+  const simulator: ConditionedSimulator = { server, port, preset, conditions, sockets };
+  conditioned.set(udid, simulator);
+  console.log(`[network-conditioner] Proxy for ${udid} listening on port ${port}`);
+  return toState(udid, simulator);
+}
+
+/**
+ * Stop conditioning a simulator and close its proxy. Returns false when it wasn't conditioned.
+ */
+export function clearNetworkConditions(udid: string): boolean {
+  const simulator = conditioned.get(udid);
+  if (!simulator) {
+    return false;
+  }
+  conditioned.delete(udid);
+  simulator.sockets.forEach((socket) => socket.destroy());
+  simulator.server.close();
+  return true;
+}
+
+/**
+ * Environment pointing an app launched on the simulator at its proxy, if any
+ */
+export function networkProxyEnvironment(udid: string): Record<string, string> {
+  const simulator = conditioned.get(udid);
+  if (!simulator) {
+    return {};
+  }
+  const proxy = `http://127.0.0.1:${simulator.port}`;
+  return { HTTP_PROXY: proxy, HTTPS_PROXY: proxy, PLASMA_NETWORK_PROXY: `127.0.0.1:${simulator.port}` };
+}
@@ -0,0 +1,25 @@
+import * as path from 'path';
+import * as os from 'os';
+import * as fs from 'fs';
+
+/**
+ * Directory where Plasma keeps its database and other managed data
+ */
+export function getDataDir(): string {
+  const dataDir = path.join(os.homedir(), '.local', 'share', 'plasma');
+  if (!fs.existsSync(dataDir)) {
+    fs.mkdirSync(dataDir, { recursive: true });
+  }
+  return dataDir;
+}
+
+/**
+ * Subdirectory of the data directory, created on demand
+ */
+export function getDataSubdir(...segments: string[]): string {
+  const dir = path.join(getDataDir(), ...segments);
+  if (!fs.existsSync(dir)) {
+    fs.mkdirSync(dir, { recursive: true });
+  }
+  return dir;
+}
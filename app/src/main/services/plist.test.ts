@@ -0,0 +1,89 @@
+import { describe, it, expect } from 'vitest';
+import { parseBinaryPlist, parseOpenStepPlist } from './plist';
+
+/** A bplist00 with one-byte offsets and references, the first object at the top */
+function bplist(objects: number[][]): Buffer {
+  const offsets: number[] = [];
+  let offset = 8;
+  for (const object of objects) {
+    offsets.push(offset);
+    offset += object.length;
+  }
+  const trailer = Buffer.alloc(32);
+  trailer.writeUInt8(1, 6);
+  trailer.writeUInt8(1, 7);
+  trailer.writeBigUInt64BE(BigInt(objects.length), 8);
+  trailer.writeBigUInt64BE(0n, 16);
+  trailer.writeBigUInt64BE(BigInt(offset), 24);
+  const body = objects.map((object) => Buffer.from(object));
+  return Buffer.concat([Buffer.from('bplist00'), ...body, Buffer.from(offsets), trailer]);
+}
+
+describe('plist', () => {
+  describe('parseBinaryPlist', () => {
+    it('reads unsigned integers of one, two, and four bytes', () => {
+      expect(parseBinaryPlist(bplist([[0x10, 0x2a]]))).toBe(42);
+      expect(parseBinaryPlist(bplist([[0x11, 0x01, 0x00]]))).toBe(256);
+      expect(parseBinaryPlist(bplist([[0x12, 0xff, 0xff, 0xff, 0xff]]))).toBe(4294967295);
+    });
+
+    it('reads eight-byte integers as signed, and as bigints past the safe range', () => {
+      expect(parseBinaryPlist(bplist([[0x13, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe]]))).toBe(-2);
+      expect(parseBinaryPlist(bplist([[0x13, 0x10, 0, 0, 0, 0, 0, 0, 0]]))).toBe(2n ** 60n);
+    });
+
+    it('reads dictionaries of ASCII and UTF-16 strings', () => {
+      const plist = bplist([
+        [0xd2, 1, 3, 2, 4],
+        [0x58, ...Buffer.from('bundleId')],
+        // "cé" as UTF-16BE
+        [0x62, 0x00, 0x63, 0x00, 0xe9],
+        [0x57, ...Buffer.from('numbers')],
+        [0xa2, 5, 6],
+        [0x10, 1],
+        [0x10, 2],
+      ]);
+      expect(parseBinaryPlist(plist)).toEqual({ bundleId: 'cé', numbers: [1, 2] });
+    });
+
+    it('rejects data without the bplist00 header', () => {
+      expect(() => parseBinaryPlist(Buffer.alloc(40))).toThrow(/Invalid binary plist header/);
+    });
+
+    it('rejects references past the object table', () => {
+      expect(() => parseBinaryPlist(bplist([[0xa1, 7]]))).toThrow(/Invalid binary plist object reference/);
+    });
+  });
+
+  describe('parseOpenStepPlist', () => {
+    it('reads the dictionaries simctl prints', () => {
+      const source = `{
+    "com.example.app" =     {
+        ApplicationType = User;
+        CFBundleDisplayName = "Example App";
+        UIDeviceFamily =         (
+            1,
+            2
+        );
+        Token = <0102 ff>;
+    };
+}`;
+      expect(parseOpenStepPlist(source)).toEqual({
+        'com.example.app': {
+          ApplicationType: 'User',
+          CFBundleDisplayName: 'Example App',
+          UIDeviceFamily: ['1', '2'],
+          Token: Buffer.from([1, 2, 255]),
+        },
+      });
+    });
+
+    it('unescapes quoted strings and skips comments', () => {
+      expect(parseOpenStepPlist('{ /* note */ Path = "a\\"b\\nc"; // trailing\n}')).toEqual({ Path: 'a"b\nc' });
+    });
+
+    it('reports where it stopped', () => {
+      expect(() => parseOpenStepPlist('{ Key = Value }')).toThrow('Invalid plist: expected ";" at offset 14');
+    });
+  });
+});
@@ -0,0 +1,440 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import { PlasmaError } from './errors';
+
+/**
+ * Property list reader for XML and binary (bplist00) plists
+ * Reads Info.plist in-process instead of shelling out to PlistBuddy.
+ */
+
+export type PlistValue =
+  | string
+  | number
+  | bigint
+  | boolean
+  | Date
+  | Buffer
+  | null
+  | PlistValue[]
+  | PlistDictionary;
+
+export interface PlistDictionary {
+  [key: string]: PlistValue;
+}
+
+/** Metadata read from an app bundle's Info.plist */
+export interface AppBundleInfo {
+  bundleId: string;
+  /** Name of the main executable inside the bundle */
+  executable?: string;
+  displayName?: string;
+  version?: string;
+  buildNumber?: string;
+  minimumOSVersion?: string;
+}
+
+// Seconds between the Unix epoch and Apple's 2001-01-01 reference date
+const APPLE_EPOCH_OFFSET = 978307200;
+
+const XML_ENTITIES: Record<string, string> = {
+  amp: '&',
+  lt: '<',
+  gt: '>',
+  quot: '"',
+  apos: "'",
+};
+
+function decodeXmlText(text: string): string {
+  return text.replace(/&(#x[0-9a-f]+|#\d+|\w+);/gi, (entity, name: string) => {
+    if (name[0] === '#') {
+      const code = name[1].toLowerCase() === 'x' ? parseInt(name.slice(2), 16) : parseInt(name.slice(1), 10);
+      return String.fromCodePoint(code);
+    }
+    return XML_ENTITIES[name] ?? entity;
+  });
+}
+
+/**
+ * Parse an XML property list
+ */
+export function parseXmlPlist(xml: string): PlistValue {
+  // Tokens: <tag>, </tag>, <tag/>, and text between them
+  const tokens = xml
+    .replace(/<\?xml[\s\S]*?\?>|<!DOCTYPE[\s\S]*?>|<!--[\s\S]*?-->/g, '')
+    .match(/<\/?[A-Za-z]+[^>]*?\/?>|[^<]+/g) ?? [];
+  let pos = 0;
+
+  const invalid = (message: string): never => {
+    throw new PlasmaError('invalid_request', `Invalid plist: ${message}`);
+  };
+
+  const nextTag = (): string => {
+    while (pos < tokens.length && !tokens[pos].startsWith('<')) {
+      if (tokens[pos].trim()) invalid(`unexpected text "${tokens[pos].trim()}"`);
+      pos++;
+    }
+    if (pos >= tokens.length) invalid('unexpected end of document');
+    return tokens[pos++];
+  };
+
+  const readText = (tag: string): string => {
+    let text = '';
+    while (pos < tokens.length && !tokens[pos].startsWith('<')) {
+      text += tokens[pos++];
+    }
+    if (tokens[pos] !== `</${tag}>`) invalid(`expected </${tag}>`);
+    pos++;
+    return decodeXmlText(text);
+  };
+
+  const parseValue = (token: string): PlistValue => {
+    const name = token.replace(/^<\/?|\/?>$/g, '').split(/\s/)[0];
+    const selfClosing = token.endsWith('/>');
+
+    switch (name) {
+      case 'dict': {
+        const dict: PlistDictionary = {};
+        if (selfClosing) return dict;
+        for (;;) {
+          const keyToken = nextTag();
+          if (keyToken === '</dict>') return dict;
+          if (keyToken !== '<key>') invalid('expected <key>');
+          const key = readText('key');
+          dict[key] = parseValue(nextTag());
+        }
+      }
+      case 'array': {
+        const items: PlistValue[] = [];
+        if (selfClosing) return items;
+        for (;;) {
+          const itemToken = nextTag();
+          if (itemToken === '</array>') return items;
+          items.push(parseValue(itemToken));
+        }
+      }
+      case 'string':
+        return selfClosing ? '' : readText('string');
+      case 'integer': {
+        const text = readText('integer').trim();
+        const value = BigInt(text);
+        return value >= BigInt(Number.MIN_SAFE_INTEGER) && value <= BigInt(Number.MAX_SAFE_INTEGER)
+          ? Number(value)
+          : value;
+      }
+      case 'real':
+        return parseFloat(readText('real'));
+      case 'true':
+        return true;
+      case 'false':
+        return false;
+      case 'date':
+        return new Date(readText('date').trim());
+      case 'data':
+        return Buffer.from(readText('data').replace(/\s+/g, ''), 'base64');
+      case 'plist':
+        return parseValue(nextTag());
+      default:
+        return invalid(`unsupported element <${name}>`);
+    }
+  };
+
+  return parseValue(nextTag());
+}
+
+/**
+ * Parse a binary property list (bplist00)
+ */
+export function parseBinaryPlist(buffer: Buffer): PlistValue {
+  if (buffer.length < 40 || buffer.toString('ascii', 0, 8) !== 'bplist00') {
+    throw new PlasmaError('invalid_request', 'Invalid binary plist header');
+  }
+
+  const trailer = buffer.subarray(buffer.length - 32);
+  const offsetSize = trailer.readUInt8(6);
+  const refSize = trailer.readUInt8(7);
+  const objectCount = Number(trailer.readBigUInt64BE(8));
+  const topObject = Number(trailer.readBigUInt64BE(16));
+  const offsetTableOffset = Number(trailer.readBigUInt64BE(24));
+
+  const readUInt = (offset: number, size: number): number => {
+    let value = 0;
+    for (let i = 0; i < size; i++) {
+      value = value * 256 + buffer[offset + i];
+    }
+    return value;
+  };
+
+  const offsets: number[] = [];
+  for (let i = 0; i < objectCount; i++) {
+    offsets.push(readUInt(offsetTableOffset + i * offsetSize, offsetSize));
+  }
+
+  const parseObject = (ref: number, depth: number): PlistValue => {
+    if (depth > 512 || ref >= objectCount) {
+      throw new PlasmaError('invalid_request', 'Invalid binary plist object reference');
+    }
+
+    const offset = offsets[ref];
+    const marker = buffer[offset];
+    const type = marker >> 4;
+    const info = marker & 0x0f;
+
+    // Collections and strings store their length inline or as a following int
+    const readLength = (): { length: number; start: number } => {
+      if (info !== 0x0f) {
+        return { length: info, start: offset + 1 };
+      }
+      const intSize = 1 << (buffer[offset + 1] & 0x0f);
+      return { length: readUInt(offset + 2, intSize), start: offset + 2 + intSize };
+    };
+
+    switch (type) {
+      case 0x0:
+        if (info === 0x8) return false;
+        if (info === 0x9) return true;
+        return null;
+      case 0x1: {
+        const size = 1 << info;
+        if (size === 8) {
+          const value = buffer.readBigInt64BE(offset + 1);
+          return value >= BigInt(Number.MIN_SAFE_INTEGER) && value <= BigInt(Number.MAX_SAFE_INTEGER)
+            ? Number(value)
+            : value;
+        }
+        if (size === 16) {
+          return buffer.readBigInt64BE(offset + 9);
+        }
+        return readUInt(offset + 1, size);
+      }
+      case 0x2:
+        return info === 2 ? buffer.readFloatBE(offset + 1) : buffer.readDoubleBE(offset + 1);
+      case 0x3:
+        return new Date((buffer.readDoubleBE(offset + 1) + APPLE_EPOCH_OFFSET) * 1000);
+      case 0x4: {
+        const { length, start } = readLength();
+        return Buffer.from(buffer.subarray(start, start + length));
+      }
+      case 0x5: {
+        const { length, start } = readLength();
+        return buffer.toString('ascii', start, start + length);
+      }
+      case 0x6: {
+        const { length, start } = readLength();
+        const utf16 = Buffer.from(buffer.subarray(start, start + length * 2));
+        utf16.swap16();
+        return utf16.toString('utf16le');
+      }
+      case 0x8:
+        return readUInt(offset + 1, info + 1);
+      case 0xa: {
+        const { length, start } = readLength();
+        const items: PlistValue[] = [];
+        for (let i = 0; i < length; i++) {
+          items.push(parseObject(readUInt(start + i * refSize, refSize), depth + 1));
+        }
+        return items;
+      }
+      case 0xd: {
+        const { length, start } = readLength();
+        const dict: PlistDictionary = {};
+        for (let i = 0; i < length; i++) {
+          const key = parseObject(readUInt(start + i * refSize, refSize), depth + 1);
+          const value = parseObject(readUInt(start + (length + i) * refSize, refSize), depth + 1);
+          dict[String(key)] = value;
+        }
+        return dict;
+      }
+      default:
+        throw new PlasmaError('invalid_request', `Unsupported binary plist object type 0x${type.toString(16)}`);
+    }
+  };
+
+  return parseObject(topObject, 0);
+}
+
+/**
+ * Parse an old-style (OpenStep) property list, the format simctl prints
+ * for listapps and appinfo
+ */
+export function parseOpenStepPlist(text: string): PlistValue {
+  let pos = 0;
+
+  const invalid = (message: string): never => {
+    throw new PlasmaError('invalid_request', `Invalid plist: ${message} at offset ${pos}`);
+  };
+
+  const skipWhitespace = () => {
+    for (;;) {
+      while (pos < text.length && /\s/.test(text[pos])) pos++;
+      if (text.startsWith('//', pos)) {
+        while (pos < text.length && text[pos] !== '\n') pos++;
+      } else if (text.startsWith('/*', pos)) {
+        const end = text.indexOf('*/', pos + 2);
+        pos = end === -1 ? text.length : end + 2;
+      } else {
+        return;
+      }
+    }
+  };
+
+  const expect = (char: string) => {
+    skipWhitespace();
+    if (text[pos] !== char) invalid(`expected "${char}"`);
+    pos++;
+  };
+
+  const parseString = (): string => {
+    skipWhitespace();
+    if (text[pos] === '"') {
+      pos++;
+      let value = '';
+      while (text[pos] !== '"') {
+        if (pos >= text.length) invalid('unterminated string');
+        if (text[pos] === '\\') {
+          const next = text[pos + 1];
+          value += next === 'n' ? '\n' : next === 't' ? '\t' : next;
+          pos += 2;
+        } else {
+          value += text[pos++];
+        }
+      }
+      pos++;
+      return value;
+    }
+    const match = /^[A-Za-z0-9_$+\/:.\-]+/.exec(text.slice(pos));
+    if (!match) invalid('expected a string');
+    pos += match![0].length;
+    return match![0];
+  };
+
+  const parseValue = (): PlistValue => {
+    skipWhitespace();
+    const char = text[pos];
+
+    if (char === '{') {
+      pos++;
+      const dict: PlistDictionary = {};
+      for (;;) {
+        skipWhitespace();
+        if (text[pos] === '}') {
+          pos++;
+          return dict;
+        }
+        const key = parseString();
+        expect('=');
+        dict[key] = parseValue();
+        expect(';');
+      }
+    }
+
+    if (char === '(') {
+      pos++;
+      const items: PlistValue[] = [];
+      for (;;) {
+        skipWhitespace();
+        if (text[pos] === ')') {
+          pos++;
+          return items;
+        }
+        items.push(parseValue());
+        skipWhitespace();
+        if (text[pos] === ',') {
+          pos++;
+        } else if (text[pos] !== ')') {
+          invalid('expected "," or ")"');
+        }
+      }
+    }
+
+    if (char === '<') {
+      const end = text.indexOf('>', pos);
+      if (end === -1) invalid('unterminated data');
+      const hex = text.slice(pos + 1, end).replace(/\s+/g, '');
+      pos = end + 1;
+      return Buffer.from(hex, 'hex');
+    }
+
+    return parseString();
+  };
+
+  return parseValue();
+}
+
+/**
+ * Parse a plist in any of the XML, binary, or OpenStep formats
+ */
+export function parsePlist(data: Buffer): PlistValue {
+  if (data.toString('ascii', 0, 8) === 'bplist00') {
+    return parseBinaryPlist(data);
+  }
+  const text = data.toString('utf-8');
+  return /^\s*</.test(text) ? parseXmlPlist(text) : parseOpenStepPlist(text);
+}
+
+/**
+ * Read a plist file from disk
+ */
+export function readPlist(plistPath: string): PlistValue {
+  return parsePlist(fs.readFileSync(plistPath));
+}
+
+export function isDictionary(value: PlistValue): value is PlistDictionary {
+  return (
+    typeof value === 'object' &&
+    value !== null &&
+    !Array.isArray(value) &&
+    !(value instanceof Date) &&
+    !Buffer.isBuffer(value)
+  );
+}
+
+/** A plist value as it can be sent as JSON */
+export type PlistJson = string | number | boolean | PlistJson[] | { [key: string]: PlistJson };
+
+/**
+ * JSON for a plist value. Dates become ISO strings and data base64.
+ */
+export function plistToJson(value: PlistValue): PlistJson {
+  if (value instanceof Date) return value.toISOString();
+  if (Buffer.isBuffer(value)) return value.toString('base64');
+  if (typeof value === 'bigint') {
+    return Number.isSafeInteger(Number(value)) ? Number(value) : value.toString();
+  }
+  if (value === null) return '';
+  if (Array.isArray(value)) return value.map(plistToJson);
+  if (isDictionary(value)) {
+    return Object.fromEntries(Object.entries(value).map(([key, item]) => [key, plistToJson(item)]));
+  }
+  return value;
+}
+
+function stringValue(dict: PlistDictionary, key: string): string | undefined {
+  const value = dict[key];
+  return typeof value === 'string' && value ? value : undefined;
+}
+
+/**
+ * Read identifying metadata from an .app bundle's Info.plist
+ */
+export function readAppBundleInfo(appPath: string): AppBundleInfo {
+  // Mac apps keep theirs in Contents
+  const macInfoPlist = path.join(appPath, 'Contents', 'Info.plist');
+  const plist = readPlist(fs.existsSync(macInfoPlist) ? macInfoPlist : path.join(appPath, 'Info.plist'));
+  if (!isDictionary(plist)) {
+    throw new PlasmaError('invalid_request', `Info.plist in ${appPath} is not a dictionary`);
+  }
+
+  const bundleId = stringValue(plist, 'CFBundleIdentifier');
+  if (!bundleId) {
+    throw new PlasmaError('invalid_request', `Info.plist in ${appPath} has no CFBundleIdentifier`);
+  }
+
+  return {
+    bundleId,
+    executable: stringValue(plist, 'CFBundleExecutable'),
+    displayName: stringValue(plist, 'CFBundleDisplayName') ?? stringValue(plist, 'CFBundleName'),
+    version: stringValue(plist, 'CFBundleShortVersionString'),
+    buildNumber: stringValue(plist, 'CFBundleVersion'),
+    minimumOSVersion: stringValue(plist, 'MinimumOSVersion') ?? stringValue(plist, 'LSMinimumSystemVersion'),
+  };
+}
@@ -0,0 +1,203 @@
+import * as zlib from 'zlib';
+import { PlasmaError } from './errors';
+
+/**
+ * Minimal PNG codec for screenshots, built on zlib so no native image
+ * library is needed. Decodes non-interlaced 8- and 16-bit grayscale, RGB,
+ * palette, and alpha images into 8-bit RGBA; encodes RGBA.
+ */
+
+export interface RgbaImage {
+  width: number;
+  height: number;
+  /** Row-major RGBA, 4 bytes per pixel */
+  data: Buffer;
+}
+
+const SIGNATURE = Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a]);
+
+// Samples per pixel for each PNG color type
+const CHANNELS: Record<number, number> = { 0: 1, 2: 3, 3: 1, 4: 2, 6: 4 };
+
+const CRC_TABLE = (() => {
+  const table = new Uint32Array(256);
+  for (let n = 0; n < 256; n++) {
+    let c = n;
+    for (let k = 0; k < 8; k++) {
+      c = c & 1 ? 0xedb88320 ^ (c >>> 1) : c >>> 1;
+    }
+    table[n] = c >>> 0;
+  }
+  return table;
+})();
+
+function crc32(buffer: Buffer): number {
+  let crc = 0xffffffff;
+  for (const byte of buffer) {
+    crc = CRC_TABLE[(crc ^ byte) & 0xff] ^ (crc >>> 8);
+  }
+  return (crc ^ 0xffffffff) >>> 0;
+}
+
+function invalid(message: string): never {
+  throw new PlasmaError('invalid_request', `Invalid PNG: ${message}`);
+}
+
+function paeth(left: number, up: number, upLeft: number): number {
+  const p = left + up - upLeft;
+  const pLeft = Math.abs(p - left);
+  const pUp = Math.abs(p - up);
+  const pUpLeft = Math.abs(p - upLeft);
+  if (pLeft <= pUp && pLeft <= pUpLeft) return left;
+  return pUp <= pUpLeft ? up : upLeft;
+}
+
+/**
+ * Decode a PNG into 8-bit RGBA
+ */
+export function decodePng(buffer: Buffer): RgbaImage {
+  if (buffer.length < SIGNATURE.length || !buffer.subarray(0, SIGNATURE.length).equals(SIGNATURE)) {
+    invalid('missing signature');
+  }
+
+  let width = 0;
+  let height = 0;
+  let bitDepth = 0;
+  let colorType = 0;
+  let palette: Buffer | null = null;
+  let transparency: Buffer | null = null;
+  const idat: Buffer[] = [];
+
+  let offset = SIGNATURE.length;
+  while (offset + 8 <= buffer.length) {
+    const length = buffer.readUInt32BE(offset);
+    const type = buffer.toString('ascii', offset + 4, offset + 8);
+    const data = buffer.subarray(offset + 8, offset + 8 + length);
+    offset += 12 + length;
+
+    if (type === 'IHDR') {
+      width = data.readUInt32BE(0);
+      height = data.readUInt32BE(4);
+      bitDepth = data[8];
+      colorType = data[9];
+      if (data[12] !== 0) invalid('interlaced images are not supported');
+    } else if (type === 'PLTE') {
+      palette = data;
+    } else if (type === 'tRNS') {
+      transparency = data;
+    } else if (type === 'IDAT') {
+      idat.push(data);
+    } else if (type === 'IEND') {
+      break;
+    }
+  }
+
+  const channels = CHANNELS[colorType];
+  if (!width || !height || !channels) invalid('unsupported header');
+  if (bitDepth !== 8 && bitDepth !== 16) invalid(`unsupported bit depth ${bitDepth}`);
+  if (colorType === 3 && !palette) invalid('missing palette');
+
+  const bytesPerPixel = (channels * bitDepth) / 8;
+  const stride = width * bytesPerPixel;
+  const raw = zlib.inflateSync(Buffer.concat(idat));
+  if (raw.length < (stride + 1) * height) invalid('truncated image data');
+
+  // Undo the per-row filters in place
+  const pixels = Buffer.alloc(stride * height);
+  for (let y = 0; y < height; y++) {
+    const filter = raw[y * (stride + 1)];
+    const source = y * (stride + 1) + 1;
+    const row = y * stride;
+    for (let x = 0; x < stride; x++) {
+      const left = x >= bytesPerPixel ? pixels[row + x - bytesPerPixel] : 0;
+      const up = y > 0 ? pixels[row - stride + x] : 0;
+      const upLeft = y > 0 && x >= bytesPerPixel ? pixels[row - stride + x - bytesPerPixel] : 0;
+      let value = raw[source + x];
+      switch (filter) {
+        case 1: value += left; break;
+        case 2: value += up; break;
+        case 3: value += (left + up) >> 1; break;
+        case 4: value += paeth(left, up, upLeft); break;
+      }
+      pixels[row + x] = value & 0xff;
+    }
+  }
+
+  // Expand to RGBA, keeping the high byte of 16-bit samples
+  const data = Buffer.alloc(width * height * 4);
+  const sample = (index: number) => pixels[index * (bitDepth / 8)];
+  for (let i = 0; i < width * height; i++) {
+    const base = i * channels;
+    let r: number;
+    let g: number;
+    let b: number;
+    let a = 255;
+    switch (colorType) {
+      case 0:
+        r = g = b = sample(base);
+        break;
+      case 2:
+        r = sample(base);
+        g = sample(base + 1);
+        b = sample(base + 2);
+        break;
+      case 3: {
+        const entry = pixels[i];
+        r = palette![entry * 3];
+        g = palette![entry * 3 + 1];
+        b = palette![entry * 3 + 2];
+        a = transparency && entry < transparency.length ? transparency[entry] : 255;
+        break;
+      }
+      case 4:
+        r = g = b = sample(base);
+        a = sample(base + 1);
+        break;
+      default:
+        r = sample(base);
+        g = sample(base + 1);
+        b = sample(base + 2);
+        a = sample(base + 3);
+    }
+    data[i * 4] = r;
+    data[i * 4 + 1] = g;
+    data[i * 4 + 2] = b;
+    data[i * 4 + 3] = a;
+  }
+
+  return { width, height, data };
+}
+
+function chunk(type: string, data: Buffer): Buffer {
+  const header = Buffer.alloc(8);
+  header.writeUInt32BE(data.length, 0);
+  header.write(type, 4, 'ascii');
+  const crc = Buffer.alloc(4);
+  crc.writeUInt32BE(crc32(Buffer.concat([header.subarray(4), data])), 0);
+  return Buffer.concat([header, data, crc]);
+}
+
+/**
+ * Encode an RGBA image as PNG
+ */
+export function encodePng(image: RgbaImage): Buffer {
+  const header = Buffer.alloc(13);
+  header.writeUInt32BE(image.width, 0);
+  header.writeUInt32BE(image.height, 4);
+  header[8] = 8;
+  header[9] = 6;
+
+  const stride = image.width * 4;
+  const raw = Buffer.alloc((stride + 1) * image.height);
+  for (let y = 0; y < image.height; y++) {
+    // Filter type 0 (none) keeps encoding simple; deflate does the rest
+    image.data.copy(raw, y * (stride + 1) + 1, y * stride, (y + 1) * stride);
+  }
+
+  return Buffer.concat([
+    SIGNATURE,
+    chunk('IHDR', header),
+    chunk('IDAT', zlib.deflateSync(raw)),
+    chunk('IEND', Buffer.alloc(0)),
+  ]);
+}
@@ -0,0 +1,156 @@
+import { getProjectSettings, getSettings, getUnifiedProjectById } from './database';
+import { toPlasmaError } from './errors';
+import { readManifest } from './manifest';
+import { bootSimulator, findSimulator, listSimulators, waitForBoot } from './simulator';
+
+/**
+ * Booting a simulator adds 20-60s to the first run of a project. With the
+ * prewarmSimulator setting on, opening a project boots its preferred
+ * simulator in the background while the user reads code or starts a build,
+ * and keeps it booted until another project is opened, so installing and
+ * launching after the build doesn't wait on it.
+ */
+
+export type PrewarmState = 'booting' | 'ready' | 'failed';
+
+export interface PrewarmStatus {
+  enabled: boolean;
+  projectId: number | null;
+  udid: string | null;
+  state: PrewarmState | null;
+  startedAt: string | null;
+  /** When the simulator finished booting */
+  readyAt: string | null;
+  error: string | null;
+}
+
+interface PrewarmTarget {
+  projectId: number;
+  udid: string;
+  state: PrewarmState;
+  startedAt: number;
+  readyAt: number | null;
+  error: string | null;
+  /** Requested directly rather than by the setting */
+  requested: boolean;
+}
+
+// How often the pre-warmed simulator is checked and booted again if it was shut down
+const KEEP_ALIVE_INTERVAL_MS = 30_000;
+
+let target: PrewarmTarget | null = null;
+let keepAliveTimer: ReturnType<typeof setInterval> | null = null;
+
+export function isPrewarmEnabled(): boolean {
+  return getSettings().prewarmSimulator === 'true';
+}
+
+export function getPrewarmStatus(): PrewarmStatus {
+  return {
+    enabled: isPrewarmEnabled(),
+    projectId: target?.projectId ?? null,
+    udid: target?.udid ?? null,
+    state: target?.state ?? null,
+    startedAt: target ? new Date(target.startedAt).toISOString() : null,
+    readyAt: target?.readyAt ? new Date(target.readyAt).toISOString() : null,
+    error: target?.error ?? null,
+  };
+}
+
+/**
+ * The simulator a project runs on, chosen like the editor does: the one
+ * saved for the project, then the app-wide default, then plasma.toml's
+ * device. Android-only projects and projects without one get null.
+ */
+async function preferredSimulator(projectId: number): Promise<string | null> {
+  const project = getUnifiedProjectById(projectId);
+  if (!project?.xcode_path) return null;
+  const query =
+    getProjectSettings(projectId).simulatorUdid ||
+    getSettings().defaultSimulatorUdid ||
+    readManifest(project.xcode_path)?.device;
+  if (!query) return null;
+  return findSimulator(await listSimulators(), query)?.udid ?? null;
+}
+
+async function boot(warming: PrewarmTarget): Promise<void> {
+  warming.state = 'booting';
+  warming.error = null;
+  try {
+    await bootSimulator(warming.udid);
+    await waitForBoot(warming.udid);
+    if (target === warming) {
+      warming.state = 'ready';
+      warming.readyAt = Date.now();
+    }
+  } catch (err) {
+    if (target === warming) {
+      warming.state = 'failed';
+      warming.error = toPlasmaError(err).message;
+      console.warn(`[prewarm] Could not boot ${warming.udid}:`, err);
+    }
+  }
+}
+
+async function keepAlive(): Promise<void> {
+  const warming = target;
+  if (!warming || warming.state === 'booting') return;
+  if (!warming.requested && !isPrewarmEnabled()) {
+    stopPrewarm();
+    return;
+  }
+  try {
+    const simulator = (await listSimulators()).find((s) => s.udid === warming.udid);
+    if (!simulator || target !== warming) return;
+    if (simulator.state !== 'Booted') {
+      await boot(warming);
+    } else if (warming.state === 'failed') {
+      // Waiting on the boot timed out, but it finished after all
+      warming.state = 'ready';
+      warming.readyAt = Date.now();
+      warming.error = null;
+    }
+  } catch (err) {
+    console.warn('[prewarm] Keep-alive check failed:', err);
+  }
+}
+
+/**
+ * Boot the preferred simulator of a project that was just opened, when
+ * pre-warming is on. Resolves with the status once booting started; the
+ * boot itself continues in the background.
+ */
+export async function prewarmForProject(projectId: number, force = false): Promise<PrewarmStatus> {
+  if (!force && !isPrewarmEnabled()) {
+    return getPrewarmStatus();
+  }
+  const udid = await preferredSimulator(projectId);
+  if (!udid) {
+    return getPrewarmStatus();
+  }
+  if (target?.udid === udid && target.state !== 'failed') {
+    target.projectId = projectId;
+    target.requested ||= force;
+    return getPrewarmStatus();
+  }
+
+  target = { projectId, udid, state: 'booting', startedAt: Date.now(), readyAt: null, error: null, requested: force };
+  void boot(target);
+  if (!keepAliveTimer) {
+    keepAliveTimer = setInterval(() => void keepAlive(), KEEP_ALIVE_INTERVAL_MS);
+    keepAliveTimer.unref();
+  }
+  return getPrewarmStatus();
+}
+
+/**
+ * Stop keeping the pre-warmed simulator booted. The simulator itself is
+ * left running.
+ */
+export function stopPrewarm(): void {
+  target = null;
+  if (keepAliveTimer) {
+    clearInterval(keepAliveTimer);
+    keepAliveTimer = null;
+  }
+}
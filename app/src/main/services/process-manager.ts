@@ -1,52 +1,329 @@
-import { ChildProcess } from 'child_process';
+import { ChildProcess, execFile, execFileSync } from 'child_process';
+import * as fs from 'fs';
+import * as path from 'path';
+import { getDataDir } from './paths';
 
 /**
  * Process manager to track all spawned child processes
  * and ensure they are properly cleaned up when the app exits
+ *
+ * Tracked pids are also persisted to disk so children left behind by a
+ * crashed or SIGKILLed instance can be reaped on the next startup. The app
+ * and the CLI share that registry, so it's rewritten behind a lock file, off
+ * the event loop, and a child stays in it until its 'close' event.
  */
 
-const activeProcesses = new Set<ChildProcess>();
+export interface RegisterOptions {
+  /** The child was spawned detached and leads its own process group */
+  group?: boolean;
+}
+
+interface ProcessRecord {
+  pid: number;
+  command: string;
+  group: boolean;
+  ownerPid: number;
+  startedAt: string;
+  /** Start time ps reports for the pid, set shortly after it's registered */
+  processStartedAt?: string;
+}
+
+const activeProcesses = new Map<ChildProcess, ProcessRecord>();
+
+const KILL_GRACE_MS = 1000;
+
+// How often a held registry lock is checked again, and how long it's waited for
+// before it's taken to be left behind by an instance that hung
+const LOCK_RETRY_MS = 20;
+const LOCK_TIMEOUT_MS = 2000;
+
+// The write in progress, and the one queued behind it, which picks up every change made until it starts
+let registryWrite: Promise<void> = Promise.resolve();
+let queuedWrite: Promise<void> | null = null;
+
+function getRegistryPath(): string {
+  return path.join(getDataDir(), 'processes.json');
+}
+
+function parseRegistry(contents: string): ProcessRecord[] {
+  try {
+    const parsed = JSON.parse(contents);
+    return Array.isArray(parsed) ? parsed : [];
+  } catch {
+    return [];
+  }
+}
+
+function readRegistry(): ProcessRecord[] {
+  try {
+    return parseRegistry(fs.readFileSync(getRegistryPath(), 'utf-8'));
+  } catch {
+    return [];
+  }
+}
+
+/**
+ * Whether a lock file was left by an instance that's no longer running
+ */
+async function isStaleLock(lockPath: string): Promise<boolean> {
+  try {
+    const owner = parseInt(await fs.promises.readFile(lockPath, 'utf-8'), 10);
+    // An empty file is a lock still being taken
+    return !Number.isNaN(owner) && !isAlive(owner);
+  } catch {
+    return false;
+  }
+}
+
+/**
+ * Run body holding the registry's lock file, so instances don't overwrite
+ * each other's entries
+ */
+async function withRegistryLock<T>(body: () => Promise<T>): Promise<T> {
+  const lockPath = `${getRegistryPath()}.lock`;
+  const deadline = Date.now() + LOCK_TIMEOUT_MS;
+  for (;;) {
+    try {
+      await fs.promises.writeFile(lockPath, process.pid.toString(), { flag: 'wx' });
+      break;
+    } catch (err) {
+      if ((err as NodeJS.ErrnoException).code !== 'EEXIST') {
+        throw err;
+      }
+      if (Date.now() > deadline || (await isStaleLock(lockPath))) {
+        await fs.promises.rm(lockPath, { force: true });
+        continue;
+      }
+      await new Promise((resolve) => setTimeout(resolve, LOCK_RETRY_MS));
+    }
+  }
+  try {
+    return await body();
+  } finally {
+    await fs.promises.rm(lockPath, { force: true });
+  }
+}
+
+/**
+ * Rewrite the registry with this instance's live children, keeping
+ * entries owned by other running instances (e.g. the CLI next to the app)
+ */
+async function writeRegistry(): Promise<void> {
+  try {
+    await withRegistryLock(async () => {
+      const registryPath = getRegistryPath();
+      const contents = await fs.promises.readFile(registryPath, 'utf-8').catch(() => '[]');
+      const others = parseRegistry(contents).filter(
+        (record) => record.ownerPid !== process.pid && isAlive(record.ownerPid)
+      );
+      const records = [...others, ...activeProcesses.values()];
+      // Written aside and renamed so a reader never sees half a file
+      const partialPath = `${registryPath}.${process.pid}.tmp`;
+      await fs.promises.writeFile(partialPath, JSON.stringify(records, null, 2));
+      await fs.promises.rename(partialPath, registryPath);
+    });
+  } catch (err) {
+    console.error('[process-manager] Failed to persist process registry:', err);
+  }
+}
+
+/**
+ * Queue a rewrite of the registry, resolving once it's on disk
+ */
+function persistRegistry(): Promise<void> {
+  if (!queuedWrite) {
+    const write: Promise<void> = registryWrite.then(() => {
+      queuedWrite = null;
+      return writeRegistry();
+    });
+    queuedWrite = write;
+    registryWrite = write;
+  }
+  return queuedWrite;
+}
+
+function isAlive(pid: number): boolean {
+  try {
+    process.kill(pid, 0);
+    return true;
+  } catch (err) {
+    // EPERM means the pid exists but belongs to someone else
+    return (err as NodeJS.ErrnoException).code === 'EPERM';
+  }
+}
+
+/**
+ * Name of the executable currently running as pid and when it started.
+ * A recorded pid is only signalled when both still match, so an unrelated
+ * process that reused it, even one running the same tool, is left alone.
+ */
+function describePid(pid: number): { command: string; startedAt: string } | null {
+  try {
+    const command = execFileSync('ps', ['-o', 'comm=', '-p', pid.toString()], { encoding: 'utf-8' });
+    const startedAt = execFileSync('ps', ['-o', 'lstart=', '-p', pid.toString()], { encoding: 'utf-8' });
+    return { command: path.basename(command.trim()), startedAt: startedAt.trim() };
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * Record when a registered child started, as ps reports it
+ */
+function recordProcessStart(proc: ChildProcess, record: ProcessRecord): void {
+  execFile('ps', ['-o', 'lstart=', '-p', record.pid.toString()], { encoding: 'utf-8' }, (err, stdout) => {
+    const startedAt = stdout?.trim();
+    // Skip children that exited while ps ran
+    if (err || !startedAt || activeProcesses.get(proc) !== record) {
+      return;
+    }
+    record.processStartedAt = startedAt;
+    persistRegistry();
+  });
+}
+
+function signal(pid: number, group: boolean, sig: NodeJS.Signals): void {
+  if (group) {
+    try {
+      process.kill(-pid, sig);
+      return;
+    } catch {
+      // Fall back to the leader alone if the group is already gone
+    }
+  }
+  process.kill(pid, sig);
+}
 
 /**
  * Register a child process for tracking
  * The process will be automatically removed when it exits
  */
-export function registerProcess(proc: ChildProcess): void {
-  activeProcesses.add(proc);
+export function registerProcess(proc: ChildProcess, options: RegisterOptions = {}): void {
+  if (proc.pid === undefined) {
+    return;
+  }
 
-  const cleanup = () => {
-    activeProcesses.delete(proc);
+  const record: ProcessRecord = {
+    pid: proc.pid,
+    command: path.basename(proc.spawnfile),
+    group: options.group ?? false,
+    ownerPid: process.pid,
+    startedAt: new Date().toISOString(),
   };
+  activeProcesses.set(proc, record);
+  persistRegistry();
+  recordProcessStart(proc, record);
+
+  // Kept until its stdio is closed too, since the process group may outlive the leader until then
+  proc.once('close', () => {
+    if (activeProcesses.delete(proc)) {
+      persistRegistry();
+    }
+  });
+  // Errors are reported by whoever spawned it; listening keeps one from being thrown here
+  proc.on('error', () => {});
+}
+
+/**
+ * Send a signal to a tracked process, including its process group when it leads one
+ */
+export function signalProcess(proc: ChildProcess, sig: NodeJS.Signals): void {
+  const record = activeProcesses.get(proc);
+  if (!record) {
+    proc.kill(sig);
+    return;
+  }
+  try {
+    signal(record.pid, record.group, sig);
+  } catch {
+    // Already exited
+  }
+}
 
-  proc.on('close', cleanup);
-  proc.on('exit', cleanup);
-  proc.on('error', cleanup);
+/**
+ * Resolve with true once a tracked child has closed, or false after ms
+ */
+function waitForClose(proc: ChildProcess, ms: number): Promise<boolean> {
+  if (!activeProcesses.has(proc)) {
+    return Promise.resolve(true);
+  }
+  return new Promise((resolve) => {
+    const onClose = () => {
+      clearTimeout(timer);
+      resolve(true);
+    };
+    const timer = setTimeout(() => {
+      proc.off('close', onClose);
+      resolve(false);
+    }, ms);
+    proc.once('close', onClose);
+  });
+}
+
+/**
+ * SIGTERM a child, and SIGKILL it when it's still running after the grace period
+ */
+async function terminate(proc: ChildProcess, record: ProcessRecord): Promise<void> {
+  try {
+    signal(record.pid, record.group, 'SIGTERM');
+  } catch {
+    // Already exited, and closes on its own
+  }
+  if (await waitForClose(proc, KILL_GRACE_MS)) {
+    return;
+  }
+  try {
+    signal(record.pid, record.group, 'SIGKILL');
+  } catch {
+    // Exited in the meantime
+  }
+  if (!(await waitForClose(proc, KILL_GRACE_MS))) {
+    console.error(`[process-manager] ${record.command} (pid ${record.pid}) did not exit`);
+  }
 }
 
 /**
- * Kill all tracked processes
- * Called during app shutdown to prevent orphaned processes
+ * Kill all tracked processes and wait for them to exit
+ * Called during app shutdown to prevent orphaned processes. Children that
+ * don't exit stay in the registry, to be reaped on the next startup.
  */
-export function killAllProcesses(): void {
+export async function killAllProcesses(): Promise<void> {
   console.log(`[process-manager] Killing ${activeProcesses.size} active processes...`);
+  await Promise.all([...activeProcesses].map(([proc, record]) => terminate(proc, record)));
+  await persistRegistry();
+}
+
+/**
+ * Terminate children recorded by instances that are no longer running
+ * Called on startup so a crash doesn't leave xcodebuild or simulator-server behind
+ */
+export function reapOrphanedProcesses(): number {
+  let reaped = 0;
+
+  for (const record of readRegistry()) {
+    if (record.ownerPid === process.pid || isAlive(record.ownerPid)) {
+      continue;
+    }
+    // Without a recorded start time there's no telling the child from a process that reused its pid
+    if (!record.processStartedAt || !isAlive(record.pid)) {
+      continue;
+    }
+    const current = describePid(record.pid);
+    if (!current || current.command !== record.command || current.startedAt !== record.processStartedAt) {
+      continue;
+    }
 
-  for (const proc of activeProcesses) {
     try {
-      if (!proc.killed) {
-        proc.kill('SIGTERM');
-        // Give it a moment, then force kill if still running
-        setTimeout(() => {
-          if (!proc.killed) {
-            proc.kill('SIGKILL');
-          }
-        }, 1000);
-      }
+      signal(record.pid, record.group, 'SIGKILL');
+      reaped++;
+      console.log(`[process-manager] Reaped orphaned ${record.command} (pid ${record.pid})`);
     } catch (err) {
-      console.error(`[process-manager] Error killing process:`, err);
+      console.error(`[process-manager] Failed to reap pid ${record.pid}:`, err);
     }
   }
 
-  activeProcesses.clear();
+  persistRegistry();
+  return reaped;
 }
 
 /**
@@ -0,0 +1,130 @@
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { PlasmaError } from './errors';
+import { runCommand, TOOL_COMMAND } from './exec';
+import { isDictionary, parsePlist, PlistJson, plistToJson, readAppBundleInfo, readPlist } from './plist';
+
+/**
+ * What a built .app actually contains: its full Info.plist, the
+ * entitlements it was signed with, and the libraries its executable links.
+ * Simulator builds aren't signed, so their entitlements are read from the
+ * __TEXT,__entitlements section Xcode links into the executable instead.
+ */
+
+export interface LinkedLibrary {
+  /** Install name, like @rpath/Foo.framework/Foo */
+  installName: string;
+  name: string;
+  /** Shipped in the app's Frameworks folder */
+  embedded: boolean;
+  /** Part of the OS */
+  system: boolean;
+}
+
+export interface ProductDetails {
+  path: string;
+  bundleId: string;
+  infoPlist: Record<string, PlistJson>;
+  /** null when the product has no entitlements */
+  entitlements: Record<string, PlistJson> | null;
+  entitlementsSource: 'signature' | 'simulator' | null;
+  linkedLibraries: LinkedLibrary[];
+}
+
+function isMacApp(appPath: string): boolean {
+  return fs.existsSync(path.join(appPath, 'Contents', 'Info.plist'));
+}
+
+function toJsonDictionary(data: Buffer): Record<string, PlistJson> | null {
+  const plist = parsePlist(data);
+  if (!isDictionary(plist) || Object.keys(plist).length === 0) {
+    return null;
+  }
+  return plistToJson(plist) as Record<string, PlistJson>;
+}
+
+/**
+ * Parse `otool -L`, which repeats the list per architecture for universal binaries
+ */
+export function parseLinkedLibraries(output: string, frameworksDir: string): LinkedLibrary[] {
+  const seen = new Set<string>();
+  const libraries: LinkedLibrary[] = [];
+  for (const line of output.split('\n')) {
+    const match = line.match(/^\s+(\S.*?) \(compatibility version/);
+    if (!match || seen.has(match[1])) {
+      continue;
+    }
+    const installName = match[1];
+    seen.add(installName);
+    // Foo.framework/Foo or libFoo.dylib
+    const bundle = installName.match(/([^/]+\.framework)\//)?.[1];
+    const name = bundle ?? path.basename(installName);
+    libraries.push({
+      installName,
+      name,
+      embedded: fs.existsSync(path.join(frameworksDir, name)),
+      system: installName.startsWith('/System/') || installName.startsWith('/usr/lib/'),
+    });
+  }
+  return libraries;
+}
+
+async function readEntitlements(
+  appPath: string,
+  executablePath: string
+): Promise<Pick<ProductDetails, 'entitlements' | 'entitlementsSource'>> {
+  try {
+    const signed = toJsonDictionary(
+      Buffer.from(await runCommand('codesign', ['-d', '--entitlements', '-', '--xml', appPath], TOOL_COMMAND))
+    );
+    if (signed) {
+      return { entitlements: signed, entitlementsSource: 'signature' };
+    }
+  } catch {
+    // Not signed
+  }
+
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-entitlements-'));
+  try {
+    const section = path.join(dir, 'entitlements.plist');
+    await runCommand('segedit', [executablePath, '-extract', '__TEXT', '__entitlements', section], TOOL_COMMAND);
+    const simulated = toJsonDictionary(fs.readFileSync(section));
+    return { entitlements: simulated, entitlementsSource: simulated ? 'simulator' : null };
+  } catch {
+    // No entitlements section
+    return { entitlements: null, entitlementsSource: null };
+  } finally {
+    fs.rmSync(dir, { recursive: true, force: true });
+  }
+}
+
+/**
+ * Inspect a built .app bundle
+ */
+export async function getProductDetails(appPath: string): Promise<ProductDetails> {
+  if (!appPath.endsWith('.app') || !fs.existsSync(appPath)) {
+    throw new PlasmaError('not_found', `No app bundle at ${appPath}`);
+  }
+  const info = readAppBundleInfo(appPath);
+  const contents = isMacApp(appPath) ? path.join(appPath, 'Contents') : appPath;
+  const infoPlist = plistToJson(readPlist(path.join(contents, 'Info.plist'))) as Record<string, PlistJson>;
+  const executablePath = path.join(
+    contents,
+    isMacApp(appPath) ? 'MacOS' : '',
+    info.executable ?? path.basename(appPath, '.app')
+  );
+
+  const linkedLibraries = parseLinkedLibraries(
+    await runCommand('otool', ['-L', executablePath], TOOL_COMMAND),
+    path.join(contents, 'Frameworks')
+  );
+
+  return {
+    path: appPath,
+    bundleId: info.bundleId,
+    infoPlist,
+    ...(await readEntitlements(appPath, executablePath)),
+    linkedLibraries,
+  };
+}
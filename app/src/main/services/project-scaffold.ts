@@ -1,6 +1,163 @@
 import * as fs from 'fs';
 import * as path from 'path';
 import { execSync } from 'child_process';
+import { PlasmaError } from './errors';
+
+/** Starting points for a new SwiftUI app, which differ in their ContentView */
+export type AppTemplate = 'hello-world' | 'list' | 'tabs';
+
+export interface AppTemplateInfo {
+  id: AppTemplate;
+  title: string;
+  description: string;
+}
+
+export interface NewAppResult {
+  /** Folder the app was created in */
+  projectPath: string;
+  xcodePath: string;
+  /** Scheme Xcode derives from the app target */
+  scheme: string;
+  template: AppTemplate;
+}
+
+const HELLO_WORLD_CONTENT_VIEW = `import SwiftUI
+
+struct ContentView: View {
+    var body: some View {
+        VStack {
+            Image(systemName: "globe")
+                .imageScale(.large)
+                .foregroundStyle(.tint)
+            Text("Hello, world!")
+        }
+        .padding()
+    }
+}
+
+#Preview {
+    ContentView()
+}
+`;
+
+const LIST_CONTENT_VIEW = `import SwiftUI
+
+struct Item: Identifiable, Hashable {
+    let id = UUID()
+    var title: String
+}
+
+struct ContentView: View {
+    @State private var items = (1...3).map { Item(title: "Item \\($0)") }
+
+    var body: some View {
+        NavigationStack {
+            List(items) { item in
+                NavigationLink(item.title, value: item)
+            }
+            .navigationTitle("Items")
+            .navigationDestination(for: Item.self) { item in
+                Text(item.title)
+                    .font(.largeTitle)
+                    .navigationTitle(item.title)
+            }
+            .toolbar {
+                Button("Add", systemImage: "plus") {
+                    items.append(Item(title: "Item \\(items.count + 1)"))
+                }
+            }
+        }
+    }
+}
+
+#Preview {
+    ContentView()
+}
+`;
+
+const TABS_CONTENT_VIEW = `import SwiftUI
+
+struct ContentView: View {
+    var body: some View {
+        TabView {
+            Text("Home")
+                .tabItem { Label("Home", systemImage: "house") }
+            Text("Search")
+                .tabItem { Label("Search", systemImage: "magnifyingglass") }
+            Text("Settings")
+                .tabItem { Label("Settings", systemImage: "gear") }
+        }
+    }
+}
+
+#Preview {
+    ContentView()
+}
+`;
+
+const APP_TEMPLATES: Array<AppTemplateInfo & { contentView: string }> = [
+  {
+    id: 'hello-world',
+    title: 'Hello world',
+    description: 'A single view with a greeting',
+    contentView: HELLO_WORLD_CONTENT_VIEW,
+  },
+  {
+    id: 'list',
+    title: 'List and detail',
+    description: 'A navigation stack with a list of items and a detail view',
+    contentView: LIST_CONTENT_VIEW,
+  },
+  {
+    id: 'tabs',
+    title: 'Tabs',
+    description: 'A tab bar with three tabs',
+    contentView: TABS_CONTENT_VIEW,
+  },
+];
+
+export function listAppTemplates(): AppTemplateInfo[] {
+  return APP_TEMPLATES.map(({ id, title, description }) => ({ id, title, description }));
+}
+
+/**
+ * Scaffold a minimal SwiftUI app in <directory>/<name> from one of the
+ * bundled templates, ready to build for the simulator
+ */
+export async function scaffoldSwiftUIApp(
+  directory: string,
+  name: string,
+  template: AppTemplate = 'hello-world'
+): Promise<NewAppResult> {
+  const templateInfo = APP_TEMPLATES.find((candidate) => candidate.id === template);
+  if (!templateInfo) {
+    throw new PlasmaError(
+      'invalid_request',
+      `template must be one of ${APP_TEMPLATES.map((candidate) => candidate.id).join(', ')}`
+    );
+  }
+  // The name is also the target, scheme, and Swift type prefix
+  const sanitizedName = name.replace(/[^a-zA-Z0-9]/g, '');
+  if (!/^[a-zA-Z]/.test(sanitizedName)) {
+    throw new PlasmaError('invalid_request', 'The app name must start with a letter');
+  }
+  if (!fs.existsSync(directory)) {
+    throw new PlasmaError('not_found', `${directory} does not exist`);
+  }
+  const projectPath = path.join(directory, name);
+  if (fs.existsSync(projectPath)) {
+    throw new PlasmaError('invalid_request', `Directory already exists: ${projectPath}`);
+  }
+
+  fs.mkdirSync(projectPath);
+  try {
+    const xcodePath = await scaffoldXcodeProject(projectPath, sanitizedName, templateInfo.contentView);
+    return { projectPath, xcodePath, scheme: sanitizedName, template };
+  } catch (error) {
+    fs.rmSync(projectPath, { recursive: true, force: true });
+    throw error;
+  }
+}
 
 export interface ScaffoldResult {
   success: boolean;
@@ -70,7 +227,11 @@ export async function scaffoldNewProject(
 /**
  * Scaffold a minimal SwiftUI Xcode project
  */
-async function scaffoldXcodeProject(applePath: string, projectName: string): Promise<string> {
+async function scaffoldXcodeProject(
+  applePath: string,
+  projectName: string,
+  contentView: string = HELLO_WORLD_CONTENT_VIEW
+): Promise<string> {
   const projPath = path.join(applePath, `${projectName}.xcodeproj`);
   const srcPath = path.join(applePath, projectName);
 
@@ -92,25 +253,7 @@ struct ${projectName}App: App {
   fs.writeFileSync(path.join(srcPath, `${projectName}App.swift`), appContent);
 
   // Create ContentView
-  const contentViewContent = `import SwiftUI
-
-struct ContentView: View {
-    var body: some View {
-        VStack {
-            Image(systemName: "globe")
-                .imageScale(.large)
-                .foregroundStyle(.tint)
-            Text("Hello, world!")
-        }
-        .padding()
-    }
-}
-
-#Preview {
-    ContentView()
-}
-`;
-  fs.writeFileSync(path.join(srcPath, 'ContentView.swift'), contentViewContent);
+  fs.writeFileSync(path.join(srcPath, 'ContentView.swift'), contentView);
 
   // Create Assets.xcassets
   const assetsPath = path.join(srcPath, 'Assets.xcassets');
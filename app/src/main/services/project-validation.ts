@@ -1,5 +1,6 @@
 import * as fs from 'fs';
 import * as path from 'path';
+import { AndroidProject, discoverAndroidProject, isGradleFile } from './gradle';
 
 export interface XcodeValidationResult {
   valid: boolean;
@@ -11,6 +12,8 @@ export interface XcodeValidationResult {
 export interface AndroidValidationResult {
   valid: boolean;
   path: string | null;
+  /** Modules, application ids, and variants of a valid project */
+  project?: AndroidProject;
   error?: string;
 }
 
@@ -84,8 +87,9 @@ export function validateXcodePath(inputPath: string): XcodeValidationResult {
 
 /**
  * Validate an Android project path
- * - If directory: validates it contains build.gradle or build.gradle.kts
+ * - If directory: validates it contains a Gradle settings or build script
  * - If gradle file path: validate it exists
+ * A valid project comes back with its Gradle modules and variants.
  */
 export function validateAndroidPath(inputPath: string): AndroidValidationResult {
   if (!inputPath.trim()) {
@@ -103,28 +107,22 @@ export function validateAndroidPath(inputPath: string): AndroidValidationResult
   const fileName = path.basename(normalizedPath);
 
   // Direct gradle file path
-  if (fileName === 'build.gradle' || fileName === 'build.gradle.kts') {
+  if (isGradleFile(fileName)) {
     if (!stats.isFile()) {
       return { valid: false, path: null, error: 'Invalid gradle file' };
     }
     // Return the directory containing the gradle file
-    return { valid: true, path: path.dirname(normalizedPath) };
+    const projectDir = path.dirname(normalizedPath);
+    return { valid: true, path: projectDir, project: discoverAndroidProject(projectDir) };
   }
 
   // Directory - search for gradle file
   if (stats.isDirectory()) {
-    const gradlePath = path.join(normalizedPath, 'build.gradle');
-    const gradleKtsPath = path.join(normalizedPath, 'build.gradle.kts');
-
-    if (fs.existsSync(gradlePath)) {
-      return { valid: true, path: normalizedPath };
-    }
-
-    if (fs.existsSync(gradleKtsPath)) {
-      return { valid: true, path: normalizedPath };
+    if (fs.readdirSync(normalizedPath).some(isGradleFile)) {
+      return { valid: true, path: normalizedPath, project: discoverAndroidProject(normalizedPath) };
     }
 
-    return { valid: false, path: null, error: 'No build.gradle or build.gradle.kts found in directory' };
+    return { valid: false, path: null, error: 'No settings.gradle or build.gradle found in directory' };
   }
 
   return { valid: false, path: null, error: 'Invalid path - must be a directory or gradle file' };
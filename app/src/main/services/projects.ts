@@ -1,6 +1,7 @@
 import * as fs from 'fs';
 import * as path from 'path';
 import type { Platform, ProjectType } from '../../shared/ipc-types';
+import { isGradleFile } from './gradle';
 
 export interface Project {
   path: string;
@@ -18,8 +19,7 @@ function isProjectPath(filePath: string): boolean {
   return (
     name.endsWith('.xcworkspace') ||
     name.endsWith('.xcodeproj') ||
-    name === 'build.gradle' ||
-    name === 'build.gradle.kts'
+    isGradleFile(name)
   );
 }
 
@@ -53,8 +53,8 @@ function detectFromProjectPath(projectPath: string): Project | null {
     };
   }
 
-  // Android Gradle build file
-  if (fileName === 'build.gradle' || fileName === 'build.gradle.kts') {
+  // Android Gradle settings or build file
+  if (isGradleFile(fileName)) {
     const parentDir = path.dirname(projectPath);
     const name = path.basename(parentDir) || 'Unknown';
     return {
@@ -109,7 +109,7 @@ function detectFromDirectory(dirPath: string): Project | null {
       };
     }
 
-    if (entry.name === 'build.gradle' || entry.name === 'build.gradle.kts') {
+    if (isGradleFile(entry.name)) {
       const name = path.basename(dirPath) || 'Unknown';
       const projectPath = path.join(dirPath, entry.name);
       return {
@@ -0,0 +1,261 @@
+import * as path from 'path';
+import type { BuildDiagnostic, BuildSummary } from './build-records';
+import { PlasmaError } from './errors';
+import type { TestRun } from './test-runner';
+
+/**
+ * Exports of stored builds and test runs for CI: JUnit XML, which most CI
+ * systems render as a test report, and GitHub Actions workflow commands,
+ * which show up as annotations on the pull request diff.
+ */
+
+export type ExportFormat = 'junit' | 'github';
+
+export const EXPORT_FORMATS: ExportFormat[] = ['junit', 'github'];
+
+export interface ExportOptions {
+  /** Directory file paths in annotations are made relative to, normally the repository checkout */
+  root?: string;
+}
+
+export interface ExportedReport {
+  contentType: string;
+  body: string;
+}
+
+export function parseExportFormat(value: string | null | undefined): ExportFormat {
+  if (!value || !EXPORT_FORMATS.includes(value as ExportFormat)) {
+    throw new PlasmaError('invalid_request', `format must be one of ${EXPORT_FORMATS.join(', ')}`);
+  }
+  return value as ExportFormat;
+}
+
+function escapeXml(value: string): string {
+  return value
+    .replace(/&/g, '&amp;')
+    .replace(/</g, '&lt;')
+    .replace(/>/g, '&gt;')
+    .replace(/"/g, '&quot;')
+    .replace(/'/g, '&apos;');
+}
+
+function seconds(ms: number): string {
+  return (ms / 1000).toFixed(3);
+}
+
+function durationMs(start: string, end: string | null): number {
+  return end ? Math.max(0, Date.parse(end) - Date.parse(start)) : 0;
+}
+
+function location(file?: string, line?: number): string {
+  return file ? `${file}${line ? `:${line}` : ''}` : '';
+}
+
+/**
+ * JUnit XML for a build: one test case per compiler error, or a single
+ * passing case when the build succeeded. Warnings go to system-out.
+ */
+export function buildToJUnit(build: BuildSummary): string {
+  const errors = build.diagnostics.filter((diagnostic) => diagnostic.severity === 'error');
+  const warnings = build.diagnostics.filter((diagnostic) => diagnostic.severity === 'warning');
+  const time = seconds(durationMs(build.startedAt, build.finishedAt));
+
+  const cases: string[] = errors.map((diagnostic) => {
+    const where = location(diagnostic.file, diagnostic.line);
+    const classname = diagnostic.file ? path.basename(diagnostic.file) : build.scheme;
+    return [
+      `    <testcase classname="${escapeXml(classname)}" name="${escapeXml(where || diagnostic.message)}" time="0">`,
+      `      <failure message="${escapeXml(diagnostic.message)}" type="error">${escapeXml(
+        where ? `${where}: ${diagnostic.message}` : diagnostic.message
+      )}</failure>`,
+      '    </testcase>',
+    ].join('\n');
+  });
+  if (cases.length === 0) {
+    cases.push(
+      build.success
+        ? `    <testcase classname="${escapeXml(build.scheme)}" name="build" time="${time}"/>`
+        : [
+            `    <testcase classname="${escapeXml(build.scheme)}" name="build" time="${time}">`,
+            `      <failure message="${escapeXml(build.error ?? 'Build failed')}" type="error"/>`,
+            '    </testcase>',
+          ].join('\n')
+    );
+  }
+
+  const systemOut = warnings.length
+    ? `    <system-out>${escapeXml(
+        warnings.map((warning) => `${location(warning.file, warning.line) || 'warning'}: ${warning.message}`).join('\n')
+      )}</system-out>\n`
+    : '';
+
+  return [
+    '<?xml version="1.0" encoding="UTF-8"?>',
+    `<testsuites name="${escapeXml(`Build ${build.scheme}`)}" tests="${cases.length}" failures="${build.success ? 0 : cases.length}" time="${time}">`,
+    `  <testsuite name="${escapeXml(`${build.scheme} (${build.configuration})`)}" tests="${cases.length}" failures="${
+      build.success ? 0 : cases.length
+    }" errors="0" skipped="0" time="${time}" timestamp="${build.startedAt}">`,
+    ...cases,
+    `${systemOut}  </testsuite>`,
+    '</testsuites>',
+    '',
+  ].join('\n');
+}
+
+/**
+ * JUnit XML for a test run, with a test suite per test class
+ */
+export function testRunToJUnit(run: TestRun): string {
+  const suites = new Map<string, TestRun['tests']>();
+  for (const test of run.tests) {
+    const suite = test.identifier.includes('/')
+      ? test.identifier.split('/').slice(0, -1).join('.')
+      : test.suite ?? run.scheme;
+    suites.set(suite, [...(suites.get(suite) ?? []), test]);
+  }
+
+  const suiteElements = [...suites.entries()].map(([suite, tests]) => {
+    const failures = tests.filter((test) => test.status === 'failed').length;
+    const skipped = tests.filter((test) => test.status === 'skipped').length;
+    const time = seconds(tests.reduce((sum, test) => sum + test.durationMs, 0));
+    const cases = tests.map((test) => {
+      const open = `    <testcase classname="${escapeXml(suite)}" name="${escapeXml(test.name)}" time="${seconds(test.durationMs)}"`;
+      const children: string[] = [];
+      if (test.status === 'failed') {
+        const failure = test.failures[0];
+        const details = test.failures
+          .map((item) => `${location(item.file, item.line)}${item.file ? ': ' : ''}${item.message}`)
+          .join('\n');
+        children.push(
+          `      <failure message="${escapeXml(failure?.message ?? 'Test failed')}" type="failure">${escapeXml(details)}</failure>`
+        );
+      } else if (test.status === 'skipped') {
+        children.push('      <skipped/>');
+      }
+      if (test.flaky) {
+        children.push(`      <system-out>Flaky: passed on attempt ${test.attempts}</system-out>`);
+      }
+      return children.length ? [`${open}>`, ...children, '    </testcase>'].join('\n') : `${open}/>`;
+    });
+    return [
+      `  <testsuite name="${escapeXml(suite)}" tests="${tests.length}" failures="${failures}" errors="0" skipped="${skipped}" time="${time}">`,
+      ...cases,
+      '  </testsuite>',
+    ].join('\n');
+  });
+
+  // A run that errored before any test finished still reports why
+  if (run.status === 'error' && run.tests.length === 0) {
+    suiteElements.push(
+      [
+        `  <testsuite name="${escapeXml(run.scheme)}" tests="1" failures="0" errors="1" skipped="0" time="0">`,
+        `    <testcase classname="${escapeXml(run.scheme)}" name="test run" time="0">`,
+        `      <error message="${escapeXml(run.error ?? 'Test run failed')}"/>`,
+        '    </testcase>',
+        '  </testsuite>',
+      ].join('\n')
+    );
+  }
+
+  const total = run.tests.length + (run.status === 'error' && run.tests.length === 0 ? 1 : 0);
+  return [
+    '<?xml version="1.0" encoding="UTF-8"?>',
+    `<testsuites name="${escapeXml(run.scheme)}" tests="${total}" failures="${run.failed}" skipped="${run.skipped}" time="${seconds(
+      durationMs(run.startedAt, run.finishedAt)
+    )}">`,
+    ...suiteElements,
+    '</testsuites>',
+    '',
+  ].join('\n');
+}
+
+/**
+ * Escape workflow command data; properties also escape their separators
+ */
+function escapeData(value: string): string {
+  return value.replace(/%/g, '%25').replace(/\r/g, '%0D').replace(/\n/g, '%0A');
+}
+
+function escapeProperty(value: string): string {
+  return escapeData(value).replace(/:/g, '%3A').replace(/,/g, '%2C');
+}
+
+function annotation(
+  level: 'error' | 'warning' | 'notice',
+  message: string,
+  properties: { file?: string; line?: number; col?: number; title?: string },
+  options: ExportOptions
+): string {
+  const props: string[] = [];
+  if (properties.file) {
+    const root = options.root ? path.resolve(options.root) : null;
+    const file = root && properties.file.startsWith(`${root}${path.sep}`) ? path.relative(root, properties.file) : properties.file;
+    props.push(`file=${escapeProperty(file)}`);
+  }
+  if (properties.line) props.push(`line=${properties.line}`);
+  if (properties.col) props.push(`col=${properties.col}`);
+  if (properties.title) props.push(`title=${escapeProperty(properties.title)}`);
+  return `::${level}${props.length ? ` ${props.join(',')}` : ''}::${escapeData(message)}`;
+}
+
+function diagnosticAnnotation(diagnostic: BuildDiagnostic, options: ExportOptions): string {
+  return annotation(
+    diagnostic.severity,
+    diagnostic.message,
+    { file: diagnostic.file, line: diagnostic.line, col: diagnostic.column },
+    options
+  );
+}
+
+/**
+ * GitHub Actions annotations for a build's errors and warnings
+ */
+export function buildToGitHubAnnotations(build: BuildSummary, options: ExportOptions = {}): string {
+  const lines = build.diagnostics.map((diagnostic) => diagnosticAnnotation(diagnostic, options));
+  if (!build.success && !build.diagnostics.some((diagnostic) => diagnostic.severity === 'error')) {
+    lines.push(annotation('error', build.error ?? 'Build failed', { title: `Build ${build.scheme} failed` }, options));
+  }
+  return lines.map((line) => `${line}\n`).join('');
+}
+
+/**
+ * GitHub Actions annotations for a test run's failures and flaky tests
+ */
+export function testRunToGitHubAnnotations(run: TestRun, options: ExportOptions = {}): string {
+  const lines: string[] = [];
+  for (const test of run.tests) {
+    if (test.status === 'failed') {
+      const failures = test.failures.length ? test.failures : [{ message: 'Test failed', file: undefined, line: undefined }];
+      for (const failure of failures) {
+        lines.push(
+          annotation('error', failure.message, { file: failure.file, line: failure.line, title: `${test.identifier} failed` }, options)
+        );
+      }
+    } else if (test.flaky) {
+      lines.push(
+        annotation(
+          'warning',
+          `Passed on attempt ${test.attempts} after failing`,
+          { title: `${test.identifier} is flaky` },
+          options
+        )
+      );
+    }
+  }
+  if (run.status === 'error') {
+    lines.push(annotation('error', run.error ?? 'Test run failed', { title: `Testing ${run.scheme} failed` }, options));
+  }
+  return lines.map((line) => `${line}\n`).join('');
+}
+
+export function exportBuild(build: BuildSummary, format: ExportFormat, options: ExportOptions = {}): ExportedReport {
+  return format === 'junit'
+    ? { contentType: 'application/xml', body: buildToJUnit(build) }
+    : { contentType: 'text/plain', body: buildToGitHubAnnotations(build, options) };
+}
+
+export function exportTestRun(run: TestRun, format: ExportFormat, options: ExportOptions = {}): ExportedReport {
+  return format === 'junit'
+    ? { contentType: 'application/xml', body: testRunToJUnit(run) }
+    : { contentType: 'text/plain', body: testRunToGitHubAnnotations(run, options) };
+}
@@ -0,0 +1,105 @@
+import { TimeoutError } from './timeouts';
+import { PlasmaError } from './errors';
+
+/**
+ * Retry with exponential backoff for flaky simctl operations
+ * CoreSimulator intermittently fails boot, install, and launch while the
+ * device or its services are still settling; those errors usually succeed
+ * on a second try, while invalid devices or bundles never will.
+ */
+
+export interface RetryOptions {
+  /** Total attempts including the first one */
+  attempts?: number;
+  /** Delay before the first retry, doubled after each failure */
+  baseDelayMs?: number;
+  /** Decides whether an error is worth another attempt */
+  isRetryable?: (error: unknown) => boolean;
+}
+
+export interface RetryResult<T> {
+  value: T;
+  attempts: number;
+}
+
+/**
+ * Error thrown once retries are exhausted or a fatal error is hit
+ * Carries the number of attempts that were made.
+ */
+export class RetryError extends PlasmaError {
+  readonly attempts: number;
+
+  constructor(label: string, attempts: number, cause: unknown) {
+    const message = cause instanceof Error ? cause.message : String(cause);
+    super(
+      cause instanceof PlasmaError ? cause.code : 'simctl_failed',
+      attempts > 1 ? `${label} failed after ${attempts} attempts: ${message}` : `${label} failed: ${message}`,
+      { cause }
+    );
+    this.name = 'RetryError';
+    this.attempts = attempts;
+  }
+}
+
+// Transient CoreSimulator failures seen while a device boots or its services restart
+const RETRYABLE_PATTERNS = [
+  /CoreSimulatorService/i,
+  /connection (?:was )?(?:invalid|interrupted)/i,
+  /in current state: (?:Creating|Booting|Shutting Down)/i,
+  /FBSOpenApplicationServiceErrorDomain/,
+  /Mach error -308/,
+  /NSPOSIXErrorDomain, code=(?:4|35|60)\b/,
+  /Operation timed out/i,
+  /temporarily unavailable/i,
+  /busy/i,
+];
+
+// Failures that a retry can't fix
+const FATAL_PATTERNS = [
+  /Invalid device/i,
+  /No such file or directory/i,
+  /not installed/i,
+  /Unable to find (?:a )?bundle/i,
+  /does not exist/i,
+];
+
+/**
+ * Classify a simctl error as transient (worth retrying) or fatal
+ */
+export function isRetryableSimctlError(error: unknown): boolean {
+  const message = error instanceof Error ? error.message : String(error);
+  if (FATAL_PATTERNS.some((pattern) => pattern.test(message))) {
+    return false;
+  }
+  return RETRYABLE_PATTERNS.some((pattern) => pattern.test(message));
+}
+
+/**
+ * Run fn until it succeeds, the error is fatal, or attempts run out
+ */
+export async function withRetry<T>(
+  label: string,
+  fn: (attempt: number) => Promise<T>,
+  options: RetryOptions = {}
+): Promise<RetryResult<T>> {
+  const attempts = options.attempts ?? 3;
+  const baseDelayMs = options.baseDelayMs ?? 1000;
+  const isRetryable = options.isRetryable ?? isRetryableSimctlError;
+
+  for (let attempt = 1; ; attempt++) {
+    try {
+      return { value: await fn(attempt), attempts: attempt };
+    } catch (err) {
+      // Timeouts already waited long enough; surface them unchanged
+      if (err instanceof TimeoutError) {
+        throw err;
+      }
+      if (attempt >= attempts || !isRetryable(err)) {
+        throw new RetryError(label, attempt, err);
+      }
+      const delay = baseDelayMs * 2 ** (attempt - 1);
+      console.log(`[retry] ${label} failed (attempt ${attempt}/${attempts}), retrying in ${delay}ms`);
+      await new Promise((resolve) => setTimeout(resolve, delay));
+    }
+  }
+}
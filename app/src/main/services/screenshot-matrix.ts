@@ -0,0 +1,201 @@
+import * as path from 'path';
+import { AutomationEvent, AutomationStep, parseAutomationScript, runAutomation } from './automation';
+import { PlasmaError, toPlasmaError } from './errors';
+import { parseToml, TomlTable } from './toml';
+import { getDataSubdir } from './paths';
+import { bootSimulator, findSimulator, listSimulators, Simulator } from './simulator';
+
+/**
+ * Localized screenshot generation for App Store listings: the same
+ * navigation script runs on every device × locale pair, with the app
+ * launched under -AppleLanguages/-AppleLocale overrides, and named
+ * screenshots land in <output>/<locale>/<device>/<name>.png. Configs are
+ * JSON or TOML, e.g.
+ *
+ *   name = "App Store"
+ *   bundle_id = "com.example.app"
+ *   devices = ["iPhone 16 Pro Max", "iPad Pro 13-inch (M4)"]
+ *   locales = ["en-US", "de-DE", "ja"]
+ *   steps = [
+ *     { wait_for = "home" },
+ *     { screenshot = "01-home" },
+ *     { tap = "Settings" },
+ *     { screenshot = "02-settings" },
+ *   ]
+ */
+
+export interface ScreenshotMatrix {
+  name?: string;
+  bundleId: string;
+  /** Simulator names or UDIDs */
+  devices: string[];
+  /** BCP 47 language tags, e.g. en-US or ja */
+  locales: string[];
+  /** Defaults to a timestamped directory under the data directory */
+  outputDir?: string;
+  steps: AutomationStep[];
+}
+
+export type ScreenshotMatrixEvent =
+  | { type: 'started'; name?: string; runs: number; outputDir: string }
+  | { type: 'run'; device: string; udid: string; locale: string; status: 'running'; outputDir: string }
+  | { type: 'run'; device: string; udid: string; locale: string; status: 'passed' | 'failed'; error?: string }
+  | { type: 'step'; device: string; locale: string; event: AutomationEvent }
+  | { type: 'completed'; success: boolean; durationMs: number; passed: number; failed: number };
+
+function invalid(message: string): never {
+  throw new PlasmaError('invalid_request', `Invalid screenshot config: ${message}`);
+}
+
+function toList(value: unknown, key: string): string[] {
+  if (!Array.isArray(value) || value.length === 0 || !value.every((item) => typeof item === 'string' && item.trim())) {
+    invalid(`${key} must be a non-empty list of strings`);
+  }
+  return value as string[];
+}
+
+/**
+ * Parse and validate a config given as JSON or TOML text, or as an already parsed object
+ */
+export function parseScreenshotMatrix(input: unknown): ScreenshotMatrix {
+  let source = input;
+  if (typeof input === 'string') {
+    if (input.trimStart().startsWith('{')) {
+      try {
+        source = JSON.parse(input);
+      } catch (err) {
+        invalid(`not valid JSON: ${(err as Error).message}`);
+      }
+    } else {
+      source = parseToml(input);
+    }
+  }
+
+  const config = source as TomlTable;
+  if (typeof config !== 'object' || config === null) {
+    invalid('must be a table');
+  }
+  if (typeof config.bundle_id !== 'string' || !config.bundle_id) {
+    invalid('bundle_id is required');
+  }
+  for (const locale of toList(config.locales, 'locales')) {
+    if (!/^[A-Za-z]{2,3}(-[A-Za-z0-9]+)*$/.test(locale)) {
+      invalid(`${locale} is not a language tag like en-US`);
+    }
+  }
+
+  // Steps are validated as an automation script
+  const script = parseAutomationScript({ steps: config.steps });
+  return {
+    name: typeof config.name === 'string' ? config.name : undefined,
+    bundleId: config.bundle_id,
+    devices: toList(config.devices, 'devices'),
+    locales: config.locales as string[],
+    outputDir: typeof config.output_dir === 'string' ? config.output_dir : undefined,
+    steps: script.steps,
+  };
+}
+
+/**
+ * Launch arguments that make the app run in a locale, e.g. de-DE becomes
+ * -AppleLanguages (de-DE) -AppleLocale de_DE
+ */
+export function localeLaunchArguments(locale: string): string[] {
+  return ['-AppleLanguages', `(${locale})`, '-AppleLocale', locale.replace(/-/g, '_')];
+}
+
+function slug(value: string): string {
+  return value.replace(/[^\w.-]+/g, '_').replace(/^_+|_+$/g, '');
+}
+
+/**
+ * Resolve the configured devices, failing on the first one that doesn't
+ * exist so a typo is caught before anything boots
+ */
+export async function resolveMatrixDevices(matrix: ScreenshotMatrix): Promise<Simulator[]> {
+  const simulators = await listSimulators();
+  return matrix.devices.map((query) => {
+    const simulator = findSimulator(simulators, query);
+    if (!simulator) {
+      throw new PlasmaError('simulator_not_found', `No simulator matches "${query}"`);
+    }
+    return simulator;
+  });
+}
+
+/**
+ * Run the script for every device and locale, continuing past failed runs
+ * so one broken locale doesn't cost the rest of the screenshots
+ */
+export async function runScreenshotMatrix(
+  matrix: ScreenshotMatrix,
+  devices: Simulator[],
+  onEvent: (event: ScreenshotMatrixEvent) => void,
+  signal?: AbortSignal
+): Promise<boolean> {
+  const startedAt = Date.now();
+  const timestamp = new Date(startedAt).toISOString().replace(/[:.]/g, '-');
+  const outputDir = matrix.outputDir
+    ? path.resolve(matrix.outputDir)
+    : path.join(getDataSubdir('screenshots'), `${slug(matrix.name ?? 'screenshots') || 'screenshots'}-${timestamp}`);
+
+  onEvent({ type: 'started', name: matrix.name, runs: devices.length * matrix.locales.length, outputDir });
+
+  let passed = 0;
+  let failed = 0;
+  for (const device of devices) {
+    if (signal?.aborted) {
+      break;
+    }
+    let bootError: string | undefined;
+    try {
+      await bootSimulator(device.udid);
+    } catch (err) {
+      bootError = toPlasmaError(err).message;
+    }
+
+    for (const locale of matrix.locales) {
+      if (signal?.aborted) {
+        break;
+      }
+      const base = { device: device.name, udid: device.udid, locale };
+      if (bootError) {
+        failed++;
+        onEvent({ type: 'run', ...base, status: 'failed', error: bootError });
+        continue;
+      }
+
+      const runDir = path.join(outputDir, locale, slug(device.name));
+      onEvent({ type: 'run', ...base, status: 'running', outputDir: runDir });
+
+      let error: string | undefined;
+      const steps: AutomationStep[] = [
+        { launch: matrix.bundleId, arguments: localeLaunchArguments(locale) },
+        ...matrix.steps,
+      ];
+      const success = await runAutomation(
+        device.udid,
+        { name: matrix.name, steps },
+        (event) => {
+          if (event.type === 'completed' && !event.success) {
+            error = event.error;
+          }
+          onEvent({ type: 'step', device: device.name, locale, event });
+        },
+        signal,
+        runDir
+      );
+
+      if (success) {
+        passed++;
+      } else {
+        failed++;
+      }
+      onEvent({ type: 'run', ...base, status: success ? 'passed' : 'failed', error });
+    }
+  }
+
+  const success = failed === 0 && !signal?.aborted;
+  onEvent({ type: 'completed', success, durationMs: Date.now() - startedAt, passed, failed });
+  return success;
+}
@@ -0,0 +1,205 @@
+import * as crypto from 'crypto';
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { PlasmaError } from './errors';
+import { runCommand, TOOL_COMMAND } from './exec';
+import { isDictionary, parseXmlPlist, PlistDictionary, PlistJson, plistToJson } from './plist';
+
+/**
+ * Code signing identities in the keychain and installed provisioning
+ * profiles, the pieces device deploys and archive exports are signed with.
+ * Profiles are CMS-signed plists; the plist is read straight out of the
+ * signed data without verifying the signature, like Xcode shows them.
+ */
+
+export interface SigningIdentity {
+  /** SHA-1 of the certificate, what codesign and export options take */
+  sha1: string;
+  /** Like "Apple Development: Jane Appleseed (ABCDE12345)" */
+  name: string;
+  /** Like "Apple Development" or "Apple Distribution" */
+  kind: string;
+  teamId?: string;
+  /** ISO 8601, when the certificate could be read */
+  expiresAt?: string;
+}
+
+export type ProfileType = 'development' | 'ad-hoc' | 'app-store' | 'enterprise';
+
+export interface ProvisioningProfile {
+  uuid: string;
+  name: string;
+  path: string;
+  type: ProfileType;
+  teamId?: string;
+  teamName?: string;
+  /** App ID the profile is for, with the team prefix removed; may end in * */
+  bundleId?: string;
+  platforms: string[];
+  createdAt?: string;
+  expiresAt?: string;
+  expired: boolean;
+  /** UDIDs of the devices it can install on, empty for App Store and enterprise */
+  devices: string[];
+  /** SHA-1 of the certificates that can sign with it */
+  certificates: string[];
+  entitlements: Record<string, PlistJson>;
+}
+
+/** Where Xcode installs profiles, before and since Xcode 16 */
+const PROFILE_DIRECTORIES = [
+  path.join(os.homedir(), 'Library', 'MobileDevice', 'Provisioning Profiles'),
+  path.join(os.homedir(), 'Library', 'Developer', 'Xcode', 'UserData', 'Provisioning Profiles'),
+];
+
+/**
+ * Parse `security find-identity -v -p codesigning`, whose identities read like
+ *   1) 0123ABCD... "Apple Development: Jane Appleseed (ABCDE12345)"
+ */
+export function parseIdentities(output: string): SigningIdentity[] {
+  const identities: SigningIdentity[] = [];
+  for (const line of output.split('\n')) {
+    const match = line.match(/^\s*\d+\)\s+([0-9A-F]{40})\s+"(.+)"/);
+    if (match && !identities.some((identity) => identity.sha1 === match[1])) {
+      const name = match[2];
+      identities.push({ sha1: match[1], name, kind: name.includes(':') ? name.split(':')[0] : name });
+    }
+  }
+  return identities;
+}
+
+/**
+ * Parse the PEM certificates of `security find-certificate -a -Z -p`, each
+ * preceded by its SHA-1 hash, into expiry and team by hash
+ */
+export function parseCertificates(output: string): Map<string, { expiresAt: string; teamId?: string }> {
+  const certificates = new Map<string, { expiresAt: string; teamId?: string }>();
+  const pattern = /SHA-1 hash: ([0-9A-F]{40})[\s\S]*?(-----BEGIN CERTIFICATE-----[\s\S]+?-----END CERTIFICATE-----)/g;
+  for (const match of output.matchAll(pattern)) {
+    try {
+      const certificate = new crypto.X509Certificate(match[2]);
+      certificates.set(match[1], {
+        expiresAt: new Date(certificate.validTo).toISOString(),
+        // The organizational unit of Apple's signing certificates is the team ID
+        teamId: certificate.subject.match(/^OU=(.+)$/m)?.[1],
+      });
+    } catch {
+      // Not a certificate Node can read
+    }
+  }
+  return certificates;
+}
+
+export async function listSigningIdentities(): Promise<SigningIdentity[]> {
+  if (process.platform !== 'darwin') {
+    return [];
+  }
+  const identities = parseIdentities(
+    await runCommand('security', ['find-identity', '-v', '-p', 'codesigning'], TOOL_COMMAND)
+  );
+  if (identities.length === 0) {
+    return identities;
+  }
+  let certificates = new Map<string, { expiresAt: string; teamId?: string }>();
+  try {
+    certificates = parseCertificates(
+      await runCommand('security', ['find-certificate', '-a', '-Z', '-p'], TOOL_COMMAND)
+    );
+  } catch (err) {
+    console.warn('[signing] Could not read certificates:', err);
+  }
+  return identities.map((identity) => ({ ...identity, ...certificates.get(identity.sha1) }));
+}
+
+function text(dict: PlistDictionary, key: string): string | undefined {
+  const value = dict[key];
+  return typeof value === 'string' ? value : undefined;
+}
+
+function isoDate(value: unknown): string | undefined {
+  return value instanceof Date ? value.toISOString() : undefined;
+}
+
+/**
+ * Read a .mobileprovision or .provisionprofile file
+ */
+export function parseProvisioningProfile(data: Buffer, file: string, now = new Date()): ProvisioningProfile {
+  const start = data.indexOf('<?xml');
+  const end = data.indexOf('</plist>');
+  if (start === -1 || end === -1) {
+    throw new PlasmaError('invalid_request', `${path.basename(file)} has no property list`);
+  }
+  const plist = parseXmlPlist(data.subarray(start, end + '</plist>'.length).toString('utf-8'));
+  if (!isDictionary(plist)) {
+    throw new PlasmaError('invalid_request', `${path.basename(file)} is not a provisioning profile`);
+  }
+
+  const entitlements = isDictionary(plist.Entitlements ?? null) ? (plist.Entitlements as PlistDictionary) : {};
+  const teamIds = Array.isArray(plist.TeamIdentifier) ? plist.TeamIdentifier.filter((id) => typeof id === 'string') : [];
+  const teamId = teamIds[0] as string | undefined;
+  const appId = text(entitlements, 'application-identifier') ?? text(entitlements, 'com.apple.application-identifier');
+  const devices = Array.isArray(plist.ProvisionedDevices)
+    ? plist.ProvisionedDevices.filter((udid): udid is string => typeof udid === 'string')
+    : [];
+  const certificates = Array.isArray(plist.DeveloperCertificates)
+    ? plist.DeveloperCertificates.filter(Buffer.isBuffer).map((der) =>
+        crypto.createHash('sha1').update(der).digest('hex').toUpperCase()
+      )
+    : [];
+
+  let type: ProfileType;
+  if (plist.ProvisionsAllDevices === true) {
+    type = 'enterprise';
+  } else if (devices.length > 0) {
+    type = entitlements['get-task-allow'] === true ? 'development' : 'ad-hoc';
+  } else {
+    type = 'app-store';
+  }
+
+  const expiration = plist.ExpirationDate instanceof Date ? plist.ExpirationDate : null;
+  return {
+    uuid: text(plist, 'UUID') ?? path.basename(file, path.extname(file)),
+    name: text(plist, 'Name') ?? path.basename(file),
+    path: file,
+    type,
+    teamId,
+    teamName: text(plist, 'TeamName'),
+    bundleId: appId && teamId && appId.startsWith(`${teamId}.`) ? appId.slice(teamId.length + 1) : appId,
+    platforms: Array.isArray(plist.Platform) ? plist.Platform.filter((p): p is string => typeof p === 'string') : [],
+    createdAt: isoDate(plist.CreationDate),
+    expiresAt: isoDate(expiration),
+    expired: expiration !== null && expiration.getTime() <= now.getTime(),
+    devices,
+    certificates,
+    entitlements: plistToJson(entitlements) as Record<string, PlistJson>,
+  };
+}
+
+/**
+ * Installed provisioning profiles, the ones expiring last first
+ */
+export function listProvisioningProfiles(): ProvisioningProfile[] {
+  const profiles = new Map<string, ProvisioningProfile>();
+  for (const dir of PROFILE_DIRECTORIES) {
+    if (!fs.existsSync(dir)) {
+      continue;
+    }
+    for (const name of fs.readdirSync(dir)) {
+      if (!/\.(mobileprovision|provisionprofile)$/.test(name)) {
+        continue;
+      }
+      const file = path.join(dir, name);
+      try {
+        const profile = parseProvisioningProfile(fs.readFileSync(file), file);
+        // The same profile can be installed in both directories
+        if (!profiles.has(profile.uuid)) {
+          profiles.set(profile.uuid, profile);
+        }
+      } catch (err) {
+        console.warn(`[signing] Could not read ${name}:`, err);
+      }
+    }
+  }
+  return [...profiles.values()].sort((a, b) => (b.expiresAt ?? '').localeCompare(a.expiresAt ?? ''));
+}
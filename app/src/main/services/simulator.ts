@@ -1,9 +1,23 @@
 import { spawn, ChildProcess } from 'child_process';
 import { EventEmitter } from 'events';
 import * as fs from 'fs';
+import * as os from 'os';
 import * as path from 'path';
+import { Readable, Transform } from 'stream';
+import { pipeline } from 'stream/promises';
 import fetch from 'node-fetch';
-import { registerProcess } from './process-manager';
+import { registerProcess, signalProcess } from './process-manager';
+import { getDataSubdir } from './paths';
+import { enforceTimeout, getTimeoutMs } from './timeouts';
+import { withRetry } from './retry';
+import type { LaunchOptions } from './manifest';
+import { isDictionary, parseOpenStepPlist, PlistDictionary, PlistValue, readAppBundleInfo } from './plist';
+import { PlasmaError } from './errors';
+import { runCommand, SIMCTL_COMMAND } from './exec';
+import { networkProxyEnvironment } from './network-conditioner';
+import { debugLaunchOptions } from './debug-options';
+import { revokeStreamToken } from './stream-auth';
+import { ensureSimulatorCanRun } from './architectures';
 
 // Types
 export interface Simulator {
@@ -14,13 +28,24 @@ export interface Simulator {
 }
 
 export interface StreamLogEvent {
+  /** Simulator the event belongs to */
+  udid: string;
   type: 'info' | 'error' | 'debug' | 'frame';
   message?: string;
   frameNumber?: number;
 }
 
-// Global log emitter for SSE
-export const logEmitter = new EventEmitter();
+// Log events are published on a per-UDID channel ("log:<udid>") and on an
+// aggregate "log" channel for consumers that want every simulator
+const logEmitter = new EventEmitter();
+logEmitter.setMaxListeners(0);
+
+// Recent events per UDID, replayed to new subscribers
+const MAX_RECENT_LOGS = 200;
+const recentLogs = new Map<string, StreamLogEvent[]>();
+
+// Latest periodic report printed by simulator-server ("fps_report <json>")
+export type FpsReport = Record<string, unknown>;
 
 // Session cache - one per UDID
 interface SimulatorSession {
@@ -28,14 +53,30 @@ interface SimulatorSession {
   process: ChildProcess;
   streamUrl: string;
   stdin: NodeJS.WritableStream;
+  fpsReport: FpsReport | null;
+  /** Streams currently reading from the session */
+  clients: number;
+  lastUsedAt: number;
+  /** The app's viewer is hidden, so its WebSocket stream stops reading frames until it's resumed */
+  paused: boolean;
+  /** simulator-server is stopped with SIGSTOP, capturing and encoding nothing, while paused and unwatched */
+  suspended: boolean;
 }
 
 const sessionCache = new Map<string, SimulatorSession>();
 
+// Sessions still waiting for stream_ready, so concurrent requests for the
+// same simulator join one startup instead of spawning a second server
+const pendingSessions = new Map<string, Promise<SimulatorSession>>();
+
+// How often idle sessions are looked for
+const EVICTION_INTERVAL_MS = 30 * 1000;
+let evictionTimer: NodeJS.Timeout | null = null;
+
 /**
  * Find the simulator-server binary
  */
-function findSimulatorServerBinary(): string | null {
+export function findSimulatorServerBinary(): string | null {
   // 1. Environment variable override
   if (process.env.SIMULATOR_SERVER) {
     if (fs.existsSync(process.env.SIMULATOR_SERVER)) {
@@ -72,7 +113,7 @@ function findSimulatorServerBinary(): string | null {
 /**
  * Find the AXe binary
  */
-function findAxeBinary(): string | null {
+export function findAxeBinary(): string | null {
   // 1. Environment variable override
   if (process.env.AXE_BINARY) {
     if (fs.existsSync(process.env.AXE_BINARY)) {
@@ -101,7 +142,14 @@ function findAxeBinary(): string | null {
     }
   }
 
-  // 4. Check PATH
+  // 4. Homebrew, which apps launched from Finder don't have on their PATH
+  for (const candidate of ['/opt/homebrew/bin/axe', '/usr/local/bin/axe']) {
+    if (fs.existsSync(candidate)) {
+      return candidate;
+    }
+  }
+
+  // 5. Check PATH
   const { execSync } = require('child_process');
   try {
     const result = execSync('which axe', { encoding: 'utf-8' }).trim();
@@ -125,10 +173,10 @@ async function startSession(
 ): Promise<SimulatorSession> {
   const serverPath = findSimulatorServerBinary();
   if (!serverPath) {
-    throw new Error('simulator-server binary not found');
+    throw new PlasmaError('tool_missing', 'simulator-server binary not found');
   }
 
-  emitLog('info', `Spawning simulator-server for ${udid}`);
+  emitLog(udid, 'info', `Spawning simulator-server for ${udid}`);
 
   const proc = spawn(serverPath, [
     '--udid', udid,
@@ -136,10 +184,29 @@ async function startSession(
     '--quality', quality.toString(),
   ], {
     stdio: ['pipe', 'pipe', 'pipe'],
+    detached: true,
   });
-  registerProcess(proc);
+  registerProcess(proc, { group: true });
 
   const stdin = proc.stdin!;
+  let fpsReport: FpsReport | null = null;
+
+  // Parse an "fps_report <json>" line, returning false for any other output
+  const handleFpsReport = (line: string): boolean => {
+    if (!line.startsWith('fps_report ')) {
+      return false;
+    }
+    try {
+      fpsReport = JSON.parse(line.slice('fps_report '.length));
+      const session = sessionCache.get(udid);
+      if (session) {
+        session.fpsReport = fpsReport;
+      }
+    } catch {
+      emitLog(udid, 'debug', `Ignoring malformed fps_report: ${line}`);
+    }
+    return true;
+  };
 
   // Read stream_ready URL from stdout
   const streamUrl = await new Promise<string>((resolve, reject) => {
@@ -157,17 +224,19 @@ async function startSession(
           // Continue reading stdout in background
           proc.stdout?.off('data', onData);
           proc.stdout?.on('data', (d: Buffer) => {
-            const msg = d.toString().trim();
-            if (msg) {
-              console.log(`[simulator-server stdout] ${msg}`);
-              emitLog('debug', `simulator-server stdout: ${msg}`);
+            for (const rawLine of d.toString().split('\n')) {
+              const msg = rawLine.trim();
+              if (msg && !handleFpsReport(msg)) {
+                console.log(`[simulator-server stdout] ${msg}`);
+                emitLog(udid, 'debug', `simulator-server stdout: ${msg}`);
+              }
             }
           });
           return;
         }
         if (trimmed) {
           console.log(`[simulator-server stdout] ${trimmed}`);
-          emitLog('debug', `simulator-server stdout: ${trimmed}`);
+          emitLog(udid, 'debug', `simulator-server stdout: ${trimmed}`);
         }
       }
     };
@@ -178,19 +247,19 @@ async function startSession(
       const msg = data.toString().trim();
       if (msg) {
         console.log(`[simulator-server stderr] ${msg}`);
-        emitLog('debug', `simulator-server stderr: ${msg}`);
+        emitLog(udid, 'debug', `simulator-server stderr: ${msg}`);
       }
     });
 
     proc.on('error', (err) => {
       if (!resolved) {
-        reject(new Error(`simulator-server error: ${err.message}`));
+        reject(new PlasmaError('stream_failed', `simulator-server error: ${err.message}`));
       }
     });
 
     proc.on('close', (code) => {
       if (!resolved) {
-        reject(new Error(`simulator-server exited with code ${code}`));
+        reject(new PlasmaError('stream_failed', `simulator-server exited with code ${code}`));
       }
     });
 
@@ -198,19 +267,64 @@ async function startSession(
     setTimeout(() => {
       if (!resolved) {
         proc.kill();
-        reject(new Error('Timeout waiting for stream_ready'));
+        reject(new PlasmaError('timeout', 'Timeout waiting for stream_ready'));
       }
     }, 10000);
   });
 
-  emitLog('info', `simulator-server ready at ${streamUrl}`);
+  emitLog(udid, 'info', `simulator-server ready at ${streamUrl}`);
 
-  return {
+  const session: SimulatorSession = {
     udid,
     process: proc,
     streamUrl,
     stdin,
+    fpsReport,
+    clients: 0,
+    lastUsedAt: Date.now(),
+    paused: false,
+    suspended: false,
   };
+
+  // Drop the session as soon as simulator-server goes away so it isn't reused
+  proc.on('close', (code) => {
+    if (sessionCache.get(udid) === session) {
+      sessionCache.delete(udid);
+      revokeStreamToken(udid);
+      emitLog(udid, 'info', `simulator-server exited with code ${code}`);
+    }
+  });
+
+  return session;
+}
+
+function isSessionAlive(session: SimulatorSession): boolean {
+  return session.process.exitCode === null && session.process.signalCode === null && !session.process.killed;
+}
+
+/**
+ * Stop sessions no stream has used for longer than the idle timeout.
+ * Paused sessions are kept until they're resumed or stopped.
+ */
+function evictIdleSessions(): void {
+  const idleMs = getTimeoutMs('session');
+  if (idleMs === 0) {
+    return;
+  }
+  const now = Date.now();
+  for (const session of [...sessionCache.values()]) {
+    if (session.clients === 0 && !session.paused && now - session.lastUsedAt > idleMs) {
+      emitLog(session.udid, 'info', `Stopping idle session for ${session.udid}`);
+      stopSession(session.udid);
+    }
+  }
+}
+
+function scheduleEviction(): void {
+  if (!evictionTimer) {
+    evictionTimer = setInterval(evictIdleSessions, EVICTION_INTERVAL_MS);
+    evictionTimer.unref();
+  }
 }
 
 /**
@@ -222,14 +336,133 @@ export async function getOrCreateSession(
   quality: number = 0.7
 ): Promise<SimulatorSession> {
   const existing = sessionCache.get(udid);
-  if (existing) {
-    emitLog('info', `Reusing cached session for ${udid}`);
+  if (existing && isSessionAlive(existing)) {
+    emitLog(udid, 'info', `Reusing cached session for ${udid}`);
+    existing.lastUsedAt = Date.now();
     return existing;
   }
+  if (existing) {
+    emitLog(udid, 'info', `Cached session for ${udid} is no longer running, starting a new one`);
+    sessionCache.delete(udid);
+  }
 
-  const session = await startSession(udid, fps, quality);
-  sessionCache.set(udid, session);
-  return session;
+  const pending = pendingSessions.get(udid);
+  if (pending) {
+    emitLog(udid, 'info', `Waiting for session startup already in progress for ${udid}`);
+    return pending;
+  }
+
+  const startup = startSession(udid, fps, quality)
+    .then((session) => {
+      sessionCache.set(udid, session);
+      scheduleEviction();
+      return session;
+    })
+    .finally(() => {
+      pendingSessions.delete(udid);
+    });
+  pendingSessions.set(udid, startup);
+  return startup;
+}
+
+/**
+ * Stop simulator-server while its session is paused and no other stream
+ * reads it, and continue it as soon as either changes
+ */
+function updateSuspension(session: SimulatorSession): void {
+  const suspend = session.paused && session.clients === 0;
+  if (suspend === session.suspended) {
+    return;
+  }
+  session.suspended = suspend;
+  signalProcess(session.process, suspend ? 'SIGSTOP' : 'SIGCONT');
+  emitLog(session.udid, 'info', `${suspend ? 'Suspended' : 'Continued'} simulator-server for ${session.udid}`);
+}
+
+/**
+ * Mark a session as in use by a stream so it isn't evicted while watched
+ */
+export function retainSession(udid: string): void {
+  const session = sessionCache.get(udid);
+  if (session) {
+    session.clients++;
+    session.lastUsedAt = Date.now();
+    updateSuspension(session);
+  }
+}
+
+/**
+ * Release a stream's hold on a session; it becomes idle once none remain
+ */
+export function releaseSession(udid: string): void {
+  const session = sessionCache.get(udid);
+  if (session) {
+    session.clients = Math.max(0, session.clients - 1);
+    session.lastUsedAt = Date.now();
+    updateSuspension(session);
+  }
+}
+
+/**
+ * Whether a simulator has a session streaming it
+ */
+export function hasSession(udid: string): boolean {
+  return sessionCache.has(udid);
+}
+
+/**
+ * Stop a session's simulator-server and forget it, returning false when there is none
+ */
+export function stopSession(udid: string): boolean {
+  const session = sessionCache.get(udid);
+  if (!session) {
+    return false;
+  }
+  sessionCache.delete(udid);
+  revokeStreamToken(udid);
+  signalProcess(session.process, 'SIGTERM');
+  // A stopped process only handles the SIGTERM once it's continued
+  if (session.suspended) {
+    signalProcess(session.process, 'SIGCONT');
+  }
+  return true;
+}
+
+/**
+ * Pause a session while the app's viewer is hidden: its WebSocket stream stops
+ * reading frames, and once no share or proxied stream reads it either,
+ * simulator-server is stopped so it captures and encodes nothing. It's
+ * continued rather than restarted on resume, keeping its stream URL and token.
+ * Returns false when it was already paused.
+ */
+export function pauseSession(udid: string): boolean {
+  const session = sessionCache.get(udid);
+  if (!session) {
+    throw new PlasmaError('session_not_found', `No active session for simulator ${udid}`);
+  }
+  if (session.paused) {
+    return false;
+  }
+  session.paused = true;
+  session.lastUsedAt = Date.now();
+  emitLog(udid, 'info', `Paused the session for ${udid}`);
+  updateSuspension(session);
+  return true;
+}
+
+/**
+ * Resume a paused session, returning false when it wasn't paused
+ */
+export function resumeSession(udid: string): boolean {
+  const session = sessionCache.get(udid);
+  if (!session?.paused) {
+    return false;
+  }
+  session.paused = false;
+  session.lastUsedAt = Date.now();
+  emitLog(udid, 'info', `Resumed the session for ${udid}`);
+  updateSuspension(session);
+  return true;
 }
 
 /**
@@ -238,13 +471,14 @@ export async function getOrCreateSession(
 export async function sendSessionCommand(udid: string, command: string): Promise<void> {
   const session = sessionCache.get(udid);
   if (!session) {
-    throw new Error(`No active session for simulator ${udid}`);
+    throw new PlasmaError('session_not_found', `No active session for simulator ${udid}`);
   }
+  session.lastUsedAt = Date.now();
 
   return new Promise((resolve, reject) => {
     session.stdin.write(`${command}\n`, (err) => {
       if (err) {
-        reject(new Error(`Failed to write command: ${err.message}`));
+        reject(new PlasmaError('stream_failed', `Failed to write command: ${err.message}`));
       } else {
         resolve();
       }
@@ -253,10 +487,48 @@ export async function sendSessionCommand(udid: string, command: string): Promise
 }
 
 /**
- * Emit a log event
+ * Get the most recent fps_report emitted by a session, if any
+ */
+export function getFpsReport(udid: string): FpsReport | null {
+  return sessionCache.get(udid)?.fpsReport ?? null;
+}
+
+/**
+ * Emit a log event on the simulator's channel and the aggregate channel
+ */
+function emitLog(udid: string, type: StreamLogEvent['type'], message: string) {
+  const event: StreamLogEvent = { udid, type, message };
+
+  const recent = recentLogs.get(udid) ?? [];
+  recent.push(event);
+  if (recent.length > MAX_RECENT_LOGS) {
+    recent.splice(0, recent.length - MAX_RECENT_LOGS);
+  }
+  recentLogs.set(udid, recent);
+
+  logEmitter.emit(`log:${udid}`, event);
+  logEmitter.emit('log', event);
+}
+
+/**
+ * Subscribe to one simulator's log events, or to all of them when udid is null
+ */
+export function subscribeLogs(
+  udid: string | null,
+  listener: (event: StreamLogEvent) => void
+): () => void {
+  const channel = udid ? `log:${udid}` : 'log';
+  logEmitter.on(channel, listener);
+  return () => {
+    logEmitter.off(channel, listener);
+  };
+}
+
+/**
+ * Most recent log events for a simulator, oldest first
  */
-function emitLog(type: StreamLogEvent['type'], message: string) {
-  logEmitter.emit('log', { type, message });
+export function getRecentLogs(udid: string): StreamLogEvent[] {
+  return [...(recentLogs.get(udid) ?? [])];
 }
 
 /**
@@ -266,6 +538,7 @@ export async function listSimulators(): Promise<Simulator[]> {
   return new Promise((resolve, reject) => {
     const proc = spawn('xcrun', ['simctl', 'list', 'devices', '-j']);
     registerProcess(proc);
+    enforceTimeout(proc, 'simctl', reject);
     let stdout = '';
     let stderr = '';
 
@@ -279,7 +552,7 @@ export async function listSimulators(): Promise<Simulator[]> {
 
     proc.on('close', (code) => {
       if (code !== 0) {
-        reject(new Error(`simctl failed: ${stderr}`));
+        reject(new PlasmaError('simctl_failed', `simctl failed: ${stderr}`));
         return;
       }
 
@@ -315,86 +588,167 @@ export async function listSimulators(): Promise<Simulator[]> {
 
         resolve(simulators);
       } catch (err) {
-        reject(new Error(`Failed to parse simctl output: ${err}`));
+        reject(new PlasmaError('simctl_failed', `Failed to parse simctl output: ${err}`));
       }
     });
   });
 }
 
+/**
+ * Find a simulator by UDID or name, preferring a booted one when several share a name
+ */
+export function findSimulator(simulators: Simulator[], query: string): Simulator | null {
+  const byUdid = simulators.find((s) => s.udid.toLowerCase() === query.toLowerCase());
+  if (byUdid) {
+    return byUdid;
+  }
+
+  const byName = simulators.filter((s) => s.name.toLowerCase() === query.toLowerCase());
+  return byName.find((s) => s.state === 'Booted') ?? byName[0] ?? null;
+}
+
+export interface LaunchAttempts {
+  boot: number;
+  install: number;
+  launch: number;
+}
+
+export interface LaunchResult {
+  message: string;
+  bundleId: string;
+  attempts: LaunchAttempts;
+}
+
+// Boots in progress, so a launch waits on the boot a build started instead of racing it
+const pendingBoots = new Map<string, Promise<{ attempts: number }>>();
+
+/**
+ * Boot a simulator, succeeding if it is already booted
+ */
+export function bootSimulator(udid: string): Promise<{ attempts: number }> {
+  const pending = pendingBoots.get(udid);
+  if (pending) {
+    return pending;
+  }
+  const boot = withRetry('Boot', async () => {
+    try {
+      await runCommand('xcrun', ['simctl', 'boot', udid], SIMCTL_COMMAND);
+    } catch (err) {
+      // Ignore if already booted
+      if (err instanceof Error && err.message.includes('current state: Booted')) {
+        return;
+      }
+      throw err;
+    }
+  }).finally(() => pendingBoots.delete(udid));
+  pendingBoots.set(udid, boot);
+  return boot;
+}
+
+/**
+ * Wait until a booting simulator has finished starting its system
+ * services, so installs and launches don't have to
+ */
+export async function waitForBoot(udid: string): Promise<void> {
+  await runCommand('xcrun', ['simctl', 'bootstatus', udid], SIMCTL_COMMAND);
+}
+
+/**
+ * Shut a simulator down, succeeding if it is already shut down
+ */
+export async function shutdownSimulator(udid: string): Promise<void> {
+  try {
+    await runCommand('xcrun', ['simctl', 'shutdown', udid], SIMCTL_COMMAND);
+  } catch (err) {
+    if (err instanceof Error && err.message.includes('current state: Shutdown')) {
+      return;
+    }
+    throw err;
+  }
+}
+
+/**
+ * Clone a shut down simulator, returning the clone's UDID
+ */
+export async function cloneSimulator(udid: string, name: string): Promise<string> {
+  const output = await runCommand('xcrun', ['simctl', 'clone', udid, name], SIMCTL_COMMAND);
+  return output.trim();
+}
+
+/**
+ * Shut down and delete a simulator
+ */
+export async function deleteSimulator(udid: string): Promise<void> {
+  await shutdownSimulator(udid);
+  await runCommand('xcrun', ['simctl', 'delete', udid], SIMCTL_COMMAND);
+}
+
 /**
  * Boot, install, and launch an app on a simulator
+ * Each step is retried on transient CoreSimulator errors.
  */
 export async function installAndLaunch(
   udid: string,
   appPath: string,
-  bundleId?: string
-): Promise<string> {
+  bundleId?: string,
+  launchOptions?: LaunchOptions
+): Promise<LaunchResult> {
+  // x86_64-only builds run through Rosetta on Apple silicon
+  ensureSimulatorCanRun(appPath);
+
   // Boot simulator
   console.log(`Booting simulator ${udid}...`);
-  await runCommand('xcrun', ['simctl', 'boot', udid]).catch((err) => {
-    // Ignore if already booted
-    if (!err.message.includes('current state: Booted')) {
-      console.log(`Boot warning: ${err.message}`);
-    }
-  });
+  const boot = await bootSimulator(udid);
 
   // Install app
   console.log(`Installing app at ${appPath}...`);
-  await runCommand('xcrun', ['simctl', 'install', udid, appPath]);
+  const install = await withRetry('Install', () =>
+    runCommand('xcrun', ['simctl', 'install', udid, appPath], SIMCTL_COMMAND)
+  );
 
   // Get bundle ID if not provided
   if (!bundleId) {
-    bundleId = await extractBundleId(appPath);
+    bundleId = readAppBundleInfo(appPath).bundleId;
   }
 
   // Launch app
   console.log(`Launching app with bundle ID ${bundleId}...`);
-  await runCommand('xcrun', ['simctl', 'launch', udid, bundleId]);
+  const { args, env } = appLaunch(udid, launchOptions);
+  const launch = await withRetry('Launch', () =>
+    runCommand('xcrun', ['simctl', 'launch', udid, bundleId!, ...args], { ...SIMCTL_COMMAND, env })
+  );
 
-  return `App ${bundleId} launched successfully`;
+  return {
+    message: `App ${bundleId} launched successfully`,
+    bundleId,
+    attempts: { boot: boot.attempts, install: install.attempts, launch: launch.attempts },
+  };
 }
 
 /**
- * Run a command and return stdout
+ * Launch arguments and environment for an app, with the simulator's network
+ * proxy and debug options added to the project's own
  */
-function runCommand(cmd: string, args: string[]): Promise<string> {
-  return new Promise((resolve, reject) => {
-    const proc = spawn(cmd, args);
-    registerProcess(proc);
-    let stdout = '';
-    let stderr = '';
-
-    proc.stdout.on('data', (data) => {
-      stdout += data.toString();
-    });
-
-    proc.stderr.on('data', (data) => {
-      stderr += data.toString();
-    });
-
-    proc.on('close', (code) => {
-      if (code !== 0) {
-        reject(new Error(stderr || `Command failed with code ${code}`));
-      } else {
-        resolve(stdout);
-      }
-    });
-
-    proc.on('error', reject);
-  });
+function appLaunch(udid: string, launchOptions?: LaunchOptions): { args: string[]; env: NodeJS.ProcessEnv | undefined } {
+  const debug = debugLaunchOptions(udid);
+  return {
+    args: [...debug.arguments, ...(launchOptions?.arguments ?? [])],
+    env: launchEnvironment({ ...networkProxyEnvironment(udid), ...debug.environment, ...launchOptions?.environment }),
+  };
 }
 
 /**
- * Extract bundle ID from an app's Info.plist
+ * simctl forwards variables prefixed with SIMCTL_CHILD_ to the launched app
  */
-async function extractBundleId(appPath: string): Promise<string> {
-  const plistPath = path.join(appPath, 'Info.plist');
-  const stdout = await runCommand('/usr/libexec/PlistBuddy', [
-    '-c',
-    'Print :CFBundleIdentifier',
-    plistPath,
-  ]);
-  return stdout.trim();
+function launchEnvironment(environment?: Record<string, string>): NodeJS.ProcessEnv | undefined {
+  if (!environment || Object.keys(environment).length === 0) {
+    return undefined;
+  }
+  const env: NodeJS.ProcessEnv = { ...process.env };
+  for (const [key, value] of Object.entries(environment)) {
+    env[`SIMCTL_CHILD_${key}`] = value;
+  }
+  return env;
 }
 
 /**
@@ -409,7 +763,7 @@ export async function sendTap(
 ): Promise<void> {
   const axePath = findAxeBinary();
   if (!axePath) {
-    throw new Error('AXe binary not found');
+    throw new PlasmaError('tool_missing', 'AXe binary not found');
   }
 
   // Calculate scale factor and point coordinates
@@ -437,7 +791,7 @@ export async function sendTap(
 
     proc.on('close', (code) => {
       if (code !== 0) {
-        reject(new Error(`AXe tap failed: ${stderr}`));
+        reject(new PlasmaError('input_failed', `AXe tap failed: ${stderr}`));
       } else {
         resolve();
       }
@@ -462,7 +816,7 @@ export async function sendSwipe(
 ): Promise<void> {
   const axePath = findAxeBinary();
   if (!axePath) {
-    throw new Error('AXe binary not found');
+    throw new PlasmaError('tool_missing', 'AXe binary not found');
   }
 
   const scaleFactor = screenWidth > 1000 ? 3.0 : screenWidth > 700 ? 2.0 : 1.0;
@@ -497,7 +851,7 @@ export async function sendSwipe(
 
     proc.on('close', (code) => {
       if (code !== 0) {
-        reject(new Error(`AXe swipe failed: ${stderr}`));
+        reject(new PlasmaError('input_failed', `AXe swipe failed: ${stderr}`));
       } else {
         resolve();
       }
@@ -507,13 +861,418 @@ export async function sendSwipe(
   });
 }
 
+export type HardwareButton = 'home' | 'lock' | 'sideButton';
+
+/**
+ * Press and release a hardware button through the simulator-server session
+ */
+export async function pressButton(udid: string, button: HardwareButton): Promise<void> {
+  await sendSessionCommand(udid, `button ${button} down`);
+  await sendSessionCommand(udid, `button ${button} up`);
+}
+
+/**
+ * Rotate the simulator to the given orientation in degrees (0, 90, 180, 270)
+ */
+export async function rotate(udid: string, rotation: number): Promise<void> {
+  const normalized = ((Math.round(rotation / 90) * 90) % 360 + 360) % 360;
+  await sendSessionCommand(udid, `rotate ${normalized}`);
+}
+
+export interface InstalledApp {
+  bundleId: string;
+  name: string;
+  applicationType: 'User' | 'System' | string;
+  version?: string;
+  buildNumber?: string;
+  path?: string;
+  dataContainer?: string;
+}
+
+function fileUrlToPath(value: PlistValue | undefined): string | undefined {
+  if (typeof value !== 'string' || !value) return undefined;
+  return value.startsWith('file://') ? decodeURIComponent(new URL(value).pathname) : value;
+}
+
+function toInstalledApp(bundleId: string, info: PlistDictionary): InstalledApp {
+  const text = (key: string) => (typeof info[key] === 'string' ? (info[key] as string) : undefined);
+  return {
+    bundleId: text('CFBundleIdentifier') ?? bundleId,
+    name: text('CFBundleDisplayName') ?? text('CFBundleName') ?? bundleId,
+    applicationType: text('ApplicationType') ?? 'User',
+    version: text('CFBundleShortVersionString'),
+    buildNumber: text('CFBundleVersion'),
+    path: fileUrlToPath(info.Path ?? info.Bundle),
+    dataContainer: fileUrlToPath(info.DataContainer),
+  };
+}
+
+/**
+ * List apps installed on a booted simulator, user apps first
+ */
+export async function listInstalledApps(udid: string): Promise<InstalledApp[]> {
+  const stdout = await runCommand('xcrun', ['simctl', 'listapps', udid], SIMCTL_COMMAND);
+  const plist = parseOpenStepPlist(stdout);
+  if (!isDictionary(plist)) {
+    throw new PlasmaError('simctl_failed', 'Unexpected simctl listapps output');
+  }
+
+  const apps = Object.entries(plist)
+    .filter((entry): entry is [string, PlistDictionary] => isDictionary(entry[1]))
+    .map(([bundleId, info]) => toInstalledApp(bundleId, info));
+
+  return apps.sort((a, b) => {
+    if (a.applicationType !== b.applicationType) {
+      return a.applicationType === 'User' ? -1 : b.applicationType === 'User' ? 1 : 0;
+    }
+    return a.name.localeCompare(b.name);
+  });
+}
+
+/**
+ * Metadata for a single installed app
+ */
+export async function getAppInfo(udid: string, bundleId: string): Promise<InstalledApp> {
+  const stdout = await runCommand('xcrun', ['simctl', 'appinfo', udid, bundleId], SIMCTL_COMMAND);
+  const plist = parseOpenStepPlist(stdout);
+  if (!isDictionary(plist) || Object.keys(plist).length === 0) {
+    throw new PlasmaError('not_found', `${bundleId} is not installed on ${udid}`);
+  }
+  return toInstalledApp(bundleId, plist);
+}
+
+/**
+ * Launch an app that is already installed, without rebuilding it
+ */
+export async function launchInstalledApp(
+  udid: string,
+  bundleId: string,
+  launchOptions?: LaunchOptions
+): Promise<LaunchResult> {
+  const { args, env } = appLaunch(udid, launchOptions);
+  const launch = await withRetry('Launch', () =>
+    runCommand('xcrun', ['simctl', 'launch', '--terminate-running-process', udid, bundleId, ...args], {
+      ...SIMCTL_COMMAND,
+      env,
+    })
+  );
+  return {
+    message: `App ${bundleId} launched successfully`,
+    bundleId,
+    attempts: { boot: 0, install: 0, launch: launch.attempts },
+  };
+}
+
+async function terminateApp(udid: string, bundleId: string): Promise<void> {
+  try {
+    await runCommand('xcrun', ['simctl', 'terminate', udid, bundleId], SIMCTL_COMMAND);
+  } catch {
+    // Not running
+  }
+}
+
+/**
+ * Erase everything in an app's data container so its next launch is a
+ * first launch. The app stays installed and is terminated if running.
+ */
+export async function resetAppData(udid: string, bundleId: string): Promise<void> {
+  const app = await getAppInfo(udid, bundleId);
+  if (!app.dataContainer || !fs.existsSync(app.dataContainer)) {
+    throw new PlasmaError('not_found', `${bundleId} has no data container on ${udid}`);
+  }
+  await terminateApp(udid, bundleId);
+  // The container itself belongs to the simulator; only its contents go
+  for (const name of fs.readdirSync(app.dataContainer)) {
+    fs.rmSync(path.join(app.dataContainer, name), { recursive: true, force: true });
+  }
+  // Keep the standard layout apps expect to exist
+  for (const dir of ['Documents', 'Library/Caches', 'Library/Preferences', 'tmp']) {
+    fs.mkdirSync(path.join(app.dataContainer, dir), { recursive: true });
+  }
+}
+
+/**
+ * Remove an app's NSUserDefaults. Goes through the simulator's `defaults`
+ * so cfprefsd drops its cached copy instead of writing it back.
+ */
+export async function resetUserDefaults(udid: string, bundleId: string): Promise<void> {
+  await getAppInfo(udid, bundleId);
+  await terminateApp(udid, bundleId);
+  try {
+    await runCommand('xcrun', ['simctl', 'spawn', udid, 'defaults', 'delete', bundleId], SIMCTL_COMMAND);
+  } catch (err) {
+    // An app that never wrote defaults has no domain to delete
+    if (!(err instanceof PlasmaError && /does not exist/.test(err.message))) {
+      throw err;
+    }
+  }
+}
+
+/**
+ * Remove every keychain item and certificate from a simulator
+ */
+export async function resetKeychain(udid: string): Promise<void> {
+  await runCommand('xcrun', ['simctl', 'keychain', udid, 'reset'], SIMCTL_COMMAND);
+}
+
+export type DeviceAction = 'shake' | 'memory-warning';
+
+export const DEVICE_ACTIONS: DeviceAction[] = ['shake', 'memory-warning'];
+
+// Darwin notifications UIKit handles in the simulator, the same ones Simulator's menus post
+const DEVICE_ACTION_NOTIFICATIONS: Record<DeviceAction, string> = {
+  shake: 'com.apple.UIKit.SimulatorShake',
+  'memory-warning': 'com.apple.UIKit.SimulatorMemoryWarning',
+};
+
+/**
+ * Send the foreground app a shake gesture or a memory warning
+ */
+export async function performDeviceAction(udid: string, action: DeviceAction): Promise<void> {
+  if (!DEVICE_ACTIONS.includes(action)) {
+    throw new PlasmaError('invalid_request', `action must be one of ${DEVICE_ACTIONS.join(', ')}`);
+  }
+  await runCommand('xcrun', ['simctl', 'notify_post', udid, DEVICE_ACTION_NOTIFICATIONS[action]], SIMCTL_COMMAND);
+}
+
+/**
+ * Trigger the shake gesture in the foreground app
+ */
+export async function shake(udid: string): Promise<void> {
+  await performDeviceAction(udid, 'shake');
+}
+
+/** Files `simctl addmedia` accepts: photos and videos, plus contacts as vCards */
+export const MEDIA_EXTENSIONS = ['.jpg', '.jpeg', '.png', '.heic', '.gif', '.tiff', '.mov', '.mp4', '.m4v', '.vcf'];
+
+function assertMediaFile(file: string): void {
+  if (!MEDIA_EXTENSIONS.includes(path.extname(file).toLowerCase())) {
+    throw new PlasmaError(
+      'invalid_request',
+      `${path.basename(file)} is not a supported media file (${MEDIA_EXTENSIONS.join(', ')})`
+    );
+  }
+}
+
+/**
+ * Add photos and videos to the simulator's photo library (and vCards to its contacts)
+ */
+export async function addMedia(udid: string, files: string[]): Promise<void> {
+  if (files.length === 0) {
+    throw new PlasmaError('invalid_request', 'No media files given');
+  }
+  for (const file of files) {
+    assertMediaFile(file);
+    if (!fs.existsSync(file)) {
+      throw new PlasmaError('not_found', `${file} does not exist`);
+    }
+  }
+  // Adding media needs a booted simulator
+  await bootSimulator(udid);
+  await runCommand('xcrun', ['simctl', 'addmedia', udid, ...files], SIMCTL_COMMAND);
+}
+
+/** Largest file accepted by addMediaUpload */
+export const MAX_MEDIA_UPLOAD_BYTES = 1024 * 1024 * 1024;
+
+/**
+ * Add an uploaded file to the simulator's library. The upload is written
+ * to a temporary file under its original name, which Photos shows.
+ */
+export async function addMediaUpload(udid: string, filename: string, source: Readable): Promise<void> {
+  assertMediaFile(filename);
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-media-'));
+  try {
+    const file = path.join(dir, path.basename(filename));
+    let size = 0;
+    await pipeline(
+      source,
+      new Transform({
+        transform(chunk: Buffer, _encoding, callback) {
+          size += chunk.length;
+          callback(
+            size > MAX_MEDIA_UPLOAD_BYTES
+              ? new PlasmaError('invalid_request', `Uploads are limited to ${MAX_MEDIA_UPLOAD_BYTES / 1024 / 1024} MB`)
+              : null,
+            chunk
+          );
+        },
+      }),
+      fs.createWriteStream(file)
+    );
+    await addMedia(udid, [file]);
+  } finally {
+    fs.rmSync(dir, { recursive: true, force: true });
+  }
+}
+
+/**
+ * Capture a PNG screenshot of the simulator screen
+ */
+export async function takeScreenshot(udid: string): Promise<Buffer> {
+  const screenshotPath = path.join(
+    fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-screenshot-')),
+    'screenshot.png'
+  );
+
+  try {
+    await runCommand('xcrun', ['simctl', 'io', udid, 'screenshot', '--type=png', screenshotPath], SIMCTL_COMMAND);
+    return fs.readFileSync(screenshotPath);
+  } finally {
+    fs.rmSync(path.dirname(screenshotPath), { recursive: true, force: true });
+  }
+}
+
+export interface DeviceLogEntry {
+  timestamp: string;
+  /** Name of the process that logged the entry */
+  process: string;
+  /** Default, Info, Debug, Error, or Fault */
+  level: string;
+  subsystem?: string;
+  category?: string;
+  message: string;
+}
+
+export interface DeviceLogOptions {
+  /** Only entries from this process, e.g. the app's executable name */
+  process?: string;
+  /** How far back to read, in seconds */
+  seconds?: number;
+  /** Keep only the newest entries */
+  limit?: number;
+}
+
+/**
+ * An entry of `log show` or `log stream` with --style ndjson, and the path
+ * of the process that logged it. Other lines, like the header and summary
+ * log prints besides the entries, give null.
+ */
+export function parseDeviceLogLine(line: string): { entry: DeviceLogEntry; processPath: string } | null {
+  if (!line.startsWith('{')) return null;
+  try {
+    const raw = JSON.parse(line);
+    if (typeof raw.eventMessage !== 'string') return null;
+    const processPath = typeof raw.processImagePath === 'string' ? raw.processImagePath : '';
+    return {
+      entry: {
+        timestamp: raw.timestamp,
+        process: path.basename(processPath),
+        level: raw.messageType ?? 'Default',
+        subsystem: raw.subsystem || undefined,
+        category: raw.category || undefined,
+        message: raw.eventMessage,
+      },
+      processPath,
+    };
+  } catch {
+    // Partial line
+    return null;
+  }
+}
+
+/**
+ * Recent entries of a simulator's unified log, oldest first
+ */
+export async function readDeviceLogs(udid: string, options: DeviceLogOptions = {}): Promise<DeviceLogEntry[]> {
+  const args = ['simctl', 'spawn', udid, 'log', 'show', '--style', 'ndjson', '--last', `${options.seconds ?? 30}s`];
+  if (options.process) {
+    args.push('--predicate', `process == "${options.process.replace(/["\\]/g, '')}"`);
+  }
+  const entries: DeviceLogEntry[] = [];
+  for (const line of (await runCommand('xcrun', args, SIMCTL_COMMAND)).split('\n')) {
+    const parsed = parseDeviceLogLine(line);
+    if (parsed) entries.push(parsed.entry);
+  }
+  return options.limit !== undefined ? entries.slice(-options.limit) : entries;
+}
+
+// Active screen recordings - one per UDID
+interface ScreenRecording {
+  process: ChildProcess;
+  path: string;
+}
+
+const activeRecordings = new Map<string, ScreenRecording>();
+
+/**
+ * Start recording the simulator screen to a video file in the data directory
+ */
+export function startRecording(udid: string): string {
+  if (activeRecordings.has(udid)) {
+    throw new PlasmaError('invalid_request', `Simulator ${udid} is already being recorded`);
+  }
+
+  const timestamp = new Date().toISOString().replace(/[:.]/g, '-');
+  const recordingPath = path.join(getDataSubdir('recordings'), `${udid}-${timestamp}.mp4`);
+
+  const proc = spawn('xcrun', ['simctl', 'io', udid, 'recordVideo', '--codec=h264', '--force', recordingPath]);
+  registerProcess(proc);
+
+  proc.stderr?.on('data', (data: Buffer) => {
+    const msg = data.toString().trim();
+    if (msg) {
+      emitLog(udid, 'debug', `recordVideo: ${msg}`);
+    }
+  });
+
+  proc.on('close', () => {
+    activeRecordings.delete(udid);
+  });
+
+  activeRecordings.set(udid, { process: proc, path: recordingPath });
+  emitLog(udid, 'info', `Recording ${udid} to ${recordingPath}`);
+  return recordingPath;
+}
+
+/**
+ * Stop an active recording; simctl finalizes the file on SIGINT
+ */
+export async function stopRecording(udid: string): Promise<string> {
+  const recording = activeRecordings.get(udid);
+  if (!recording) {
+    throw new PlasmaError('session_not_found', `No active recording for simulator ${udid}`);
+  }
+
+  await new Promise<void>((resolve) => {
+    const timeout = setTimeout(() => {
+      recording.process.kill('SIGKILL');
+      resolve();
+    }, 10000);
+
+    recording.process.once('close', () => {
+      clearTimeout(timeout);
+      resolve();
+    });
+    recording.process.kill('SIGINT');
+  });
+
+  activeRecordings.delete(udid);
+  emitLog(udid, 'info', `Recording saved to ${recording.path}`);
+  return recording.path;
+}
+
+export function isRecording(udid: string): boolean {
+  return activeRecordings.has(udid);
+}
+
+/**
+ * Finish every recording and stop every session's simulator-server, for shutdown
+ */
+export async function stopAllSessions(): Promise<void> {
+  await Promise.all([...activeRecordings.keys()].map((udid) => stopRecording(udid).catch(() => undefined)));
+  for (const udid of [...sessionCache.keys()]) {
+    stopSession(udid);
+  }
+}
+
 /**
  * Proxy the MJPEG stream from simulator-server
  */
 export async function proxyStream(streamUrl: string): Promise<NodeJS.ReadableStream> {
   const response = await fetch(streamUrl);
   if (!response.ok) {
-    throw new Error(`Failed to connect to simulator-server: ${response.statusText}`);
+    throw new PlasmaError('stream_failed', `Failed to connect to simulator-server: ${response.statusText}`);
   }
   return response.body as NodeJS.ReadableStream;
 }
@@ -0,0 +1,40 @@
+import { describe, it, expect } from 'vitest';
+import { issueStreamToken, requestStreamToken, revokeStreamToken, verifyStreamToken } from './stream-auth';
+
+describe('stream-auth', () => {
+  it('issues one token per session and verifies it', () => {
+    const token = issueStreamToken('device-a');
+    expect(issueStreamToken('device-a')).toBe(token);
+    expect(() => verifyStreamToken('device-a', token)).not.toThrow();
+  });
+
+  it("rejects missing tokens and another device's token", () => {
+    const token = issueStreamToken('device-b');
+    issueStreamToken('device-c');
+    expect(() => verifyStreamToken('device-c', token)).toThrow(/not valid for device-c/);
+    expect(() => verifyStreamToken('device-b', undefined)).toThrow(/A stream token is required/);
+    expect(() => verifyStreamToken('device-b', '')).toThrow(expect.objectContaining({ code: 'unauthorized' }));
+  });
+
+  it('rejects tokens of the same length in characters but not in bytes', () => {
+    const token = issueStreamToken('device-d');
+    const wide = `é${token.slice(1)}`;
+    expect(wide.length).toBe(token.length);
+    expect(() => verifyStreamToken('device-d', wide)).toThrow(/not valid for device-d/);
+  });
+
+  it('stops accepting a token once its session is torn down', () => {
+    const token = issueStreamToken('device-e');
+    revokeStreamToken('device-e');
+    expect(() => verifyStreamToken('device-e', token)).toThrow(/not valid for device-e/);
+    const next = issueStreamToken('device-e');
+    expect(next).not.toBe(token);
+    expect(() => verifyStreamToken('device-e', next)).not.toThrow();
+  });
+
+  it('reads the token from a bearer header or the query', () => {
+    expect(requestStreamToken('Bearer abc', new URLSearchParams('token=def'))).toBe('abc');
+    expect(requestStreamToken(undefined, new URLSearchParams('token=def'))).toBe('def');
+    expect(requestStreamToken(undefined, new URLSearchParams())).toBeNull();
+  });
+});
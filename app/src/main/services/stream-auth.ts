@@ -0,0 +1,59 @@
+import * as crypto from 'crypto';
+import { PlasmaError } from './errors';
+
+/**
+ * Tokens for stream sessions. Anyone who can reach the port and knows a
+ * UDID could otherwise watch a simulator or send it input, so a token is
+ * issued to Plasma itself when a session is created and required to stream
+ * it or send it taps, swipes, buttons, and device actions. A session gets a
+ * new token each time it's started, and its token stops working when it's
+ * torn down.
+ */
+
+const tokens = new Map<string, string>();
+
+/**
+ * The token of a device's current session, issuing one when it has none
+ */
+export function issueStreamToken(udid: string): string {
+  let token = tokens.get(udid);
+  if (!token) {
+    token = crypto.randomBytes(24).toString('base64url');
+    tokens.set(udid, token);
+  }
+  return token;
+}
+
+/**
+ * Check that a consumer presented the token of the device's session
+ */
+export function verifyStreamToken(udid: string, token: unknown): void {
+  const expected = tokens.get(udid);
+  if (typeof token !== 'string' || !token) {
+    throw new PlasmaError('unauthorized', 'A stream token is required; create a session to get one');
+  }
+  const presented = Buffer.from(token);
+  const matches =
+    expected !== undefined &&
+    Buffer.byteLength(expected) === presented.length &&
+    crypto.timingSafeEqual(Buffer.from(expected), presented);
+  if (!matches) {
+    throw new PlasmaError('unauthorized', `The stream token is not valid for ${udid}`);
+  }
+}
+
+/**
+ * Invalidate a device's token as its session ends
+ */
+export function revokeStreamToken(udid: string): void {
+  tokens.delete(udid);
+}
+
+/**
+ * The token a request carries, from an Authorization: Bearer header or a
+ * token query parameter (for <img> and <video> elements, which can't set headers)
+ */
+export function requestStreamToken(authorization: string | undefined, searchParams: URLSearchParams): string | null {
+  const bearer = authorization?.match(/^Bearer\s+(\S+)$/i)?.[1];
+  return bearer ?? searchParams.get('token');
+}
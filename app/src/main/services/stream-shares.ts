@@ -0,0 +1,256 @@
+import * as crypto from 'crypto';
+import * as http from 'http';
+import * as os from 'os';
+import fetch from 'node-fetch';
+import { getSettings } from './database';
+import { PlasmaError } from './errors';
+import { encodeMjpegPart, MJPEG_BOUNDARY, MjpegParser } from './mjpeg';
+import { getOrCreateSession, releaseSession, retainSession } from './simulator';
+import type { StreamShare as SharedStreamShare } from '../../shared/ipc-types';
+
+/**
+ * Time-limited, view-only links to a simulator's stream, for teammates on
+ * the LAN. A link opens a page showing the screen as MJPEG, which browsers
+ * render without any script, and has no way to send input. Every viewer of
+ * a simulator shares one connection to simulator-server, whose encoded
+ * frames are written to each of them as they arrive.
+ */
+
+/** A link as tracked here; the server adds its url, which depends on the address it's reached at */
+export type StreamShare = Omit<SharedStreamShare, 'url'>;
+
+interface ShareState {
+  token: string;
+  udid: string;
+  createdAt: number;
+  expiresAt: number;
+  viewers: Set<http.ServerResponse>;
+  timer: NodeJS.Timeout;
+}
+
+interface Broadcast {
+  abort: AbortController;
+  viewers: Set<http.ServerResponse>;
+}
+
+const DEFAULT_SHARE_MINUTES = 60;
+const MAX_SHARE_MINUTES = 24 * 60;
+
+// Unsent bytes a viewer may fall behind by before frames are skipped for it
+const MAX_PENDING_BYTES = 2 * 1024 * 1024;
+
+const shares = new Map<string, ShareState>();
+// Upstream connections per simulator, shared by the viewers of all its links
+const broadcasts = new Map<string, Broadcast>();
+
+function toShare(state: ShareState): StreamShare {
+  return {
+    token: state.token,
+    udid: state.udid,
+    createdAt: new Date(state.createdAt).toISOString(),
+    expiresAt: new Date(state.expiresAt).toISOString(),
+    viewers: state.viewers.size,
+  };
+}
+
+/**
+ * The machine's first non-internal IPv4 address, which teammates on the
+ * LAN can reach, or localhost when it isn't on a network
+ */
+export function lanAddress(): string {
+  for (const addresses of Object.values(os.networkInterfaces())) {
+    const address = addresses?.find((entry) => entry.family === 'IPv4' && !entry.internal);
+    if (address) {
+      return address.address;
+    }
+  }
+  return 'localhost';
+}
+
+/**
+ * Create a link to watch a simulator's stream for the given number of minutes
+ */
+export function createStreamShare(udid: string, minutes: unknown = DEFAULT_SHARE_MINUTES): StreamShare {
+  if (typeof minutes !== 'number' || !Number.isFinite(minutes) || minutes <= 0 || minutes > MAX_SHARE_MINUTES) {
+    throw new PlasmaError('invalid_request', `expiresInMinutes must be between 0 and ${MAX_SHARE_MINUTES}`);
+  }
+  const token = crypto.randomBytes(18).toString('base64url');
+  const createdAt = Date.now();
+  const state: ShareState = {
+    token,
+    udid,
+    createdAt,
+    expiresAt: createdAt + minutes * 60 * 1000,
+    viewers: new Set(),
+    timer: setTimeout(() => endShare(token), minutes * 60 * 1000),
+  };
+  state.timer.unref();
+  shares.set(token, state);
+  return toShare(state);
+}
+
+/**
+ * Links that haven't expired, for one simulator or all of them
+ */
+export function listStreamShares(udid?: string): StreamShare[] {
+  return [...shares.values()].filter((state) => !udid || state.udid === udid).map(toShare);
+}
+
+/**
+ * A link that is still valid
+ */
+export function getStreamShare(token: string): StreamShare {
+  const state = shares.get(token);
+  if (!state || state.expiresAt <= Date.now()) {
+    throw new PlasmaError('not_found', 'This share link has expired or was revoked');
+  }
+  return toShare(state);
+}
+
+/**
+ * Revoke a link before it expires, disconnecting its viewers
+ */
+export function revokeStreamShare(token: string): void {
+  if (!shares.has(token)) {
+    throw new PlasmaError('not_found', `No share link ${token}`);
+  }
+  endShare(token);
+}
+
+/**
+ * Revoke every link, as the server stops
+ */
+export function revokeAllStreamShares(): void {
+  for (const token of [...shares.keys()]) {
+    endShare(token);
+  }
+}
+
+function endShare(token: string): void {
+  const state = shares.get(token);
+  if (!state) return;
+  shares.delete(token);
+  clearTimeout(state.timer);
+  for (const res of state.viewers) {
+    res.end();
+  }
+}
+
+function removeViewer(udid: string, broadcast: Broadcast, res: http.ServerResponse): void {
+  broadcast.viewers.delete(res);
+  if (broadcast.viewers.size === 0 && !broadcast.abort.signal.aborted) {
+    broadcast.abort.abort();
+    if (broadcasts.get(udid) === broadcast) {
+      broadcasts.delete(udid);
+    }
+  }
+}
+
+function writeFrame(res: http.ServerResponse, frame: Buffer, contentType: string): void {
+  // Skip frames for a viewer that hasn't drained the previous ones
+  if (res.writableLength > MAX_PENDING_BYTES) return;
+  res.write(encodeMjpegPart(frame, contentType));
+}
+
+/**
+ * Read a simulator's stream once and write its frames to every viewer
+ */
+async function runBroadcast(udid: string, broadcast: Broadcast): Promise<void> {
+  const { signal } = broadcast.abort;
+  const settings = getSettings();
+  try {
+    const session = await getOrCreateSession(
+      udid,
+      parseInt(settings.streamFps || '60'),
+      parseFloat(settings.streamQuality || '0.7')
+    );
+    if (signal.aborted) return;
+    // Keep the session from being evicted as idle while it's watched
+    retainSession(udid);
+    signal.addEventListener('abort', () => releaseSession(udid), { once: true });
+
+    const response = await fetch(session.streamUrl, { signal: signal as any });
+    if (!response.ok || !response.body) {
+      throw new PlasmaError('stream_failed', `Failed to connect to simulator-server: ${response.statusText}`);
+    }
+    const parser = new MjpegParser();
+    for await (const chunk of response.body as unknown as AsyncIterable<Buffer>) {
+      for (const { data, contentType } of parser.push(chunk)) {
+        for (const res of broadcast.viewers) {
+          writeFrame(res, data, contentType ?? 'image/jpeg');
+        }
+      }
+    }
+  } catch (err) {
+    if (!signal.aborted) {
+      console.error(`[shares] Stream for ${udid} failed:`, err);
+    }
+  } finally {
+    // The stream ended on its own; viewers can reload to reconnect
+    if (!signal.aborted) {
+      if (broadcasts.get(udid) === broadcast) {
+        broadcasts.delete(udid);
+      }
+      for (const res of broadcast.viewers) {
+        res.end();
+      }
+    }
+  }
+}
+
+/**
+ * Answer a viewer's request with the simulator's stream as multipart MJPEG
+ * until they leave or the link ends
+ */
+export function addShareViewer(token: string, res: http.ServerResponse): void {
+  const { udid } = getStreamShare(token);
+  const state = shares.get(token)!;
+
+  res.writeHead(200, {
+    'Content-Type': `multipart/x-mixed-replace; boundary=${MJPEG_BOUNDARY.slice(2)}`,
+    'Cache-Control': 'no-cache',
+    Connection: 'close',
+  });
+  state.viewers.add(res);
+
+  let broadcast = broadcasts.get(udid);
+  if (!broadcast) {
+    broadcast = { abort: new AbortController(), viewers: new Set() };
+    broadcasts.set(udid, broadcast);
+    void runBroadcast(udid, broadcast);
+  }
+  const viewing = broadcast;
+  viewing.viewers.add(res);
+
+  res.on('close', () => {
+    state.viewers.delete(res);
+    removeViewer(udid, viewing, res);
+  });
+}
+
+/**
+ * Page a link opens: the stream and when the link expires, nothing else
+ */
+export function renderSharePage(share: StreamShare): string {
+  return `<!doctype html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Plasma · Shared simulator</title>
+<style>
+  body { margin: 0; min-height: 100vh; display: flex; flex-direction: column; align-items: center; justify-content: center; gap: 12px; background: #111; color: #aaa; font: 13px system-ui, sans-serif; }
+  img { max-width: 100vw; max-height: calc(100vh - 48px); border-radius: 24px; }
+</style>
+</head>
+<body>
+<img src="/share/${share.token}/stream" alt="Simulator screen">
+<div>View only · expires <time datetime="${share.expiresAt}">${share.expiresAt}</time></div>
+<script>
+  const time = document.querySelector('time');
+  time.textContent = new Date(time.dateTime).toLocaleString();
+</script>
+</body>
+</html>
+`;
+}
@@ -0,0 +1,122 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import { BuildSymbolsRecord, getBuildSymbols, saveBuildSymbols } from './database';
+import { runCommand, TOOL_COMMAND } from './exec';
+import type { BuildProduct } from './xcode';
+
+/**
+ * Debug symbols of built apps, keyed by the Mach-O UUID of each binary so a
+ * crash report can be matched to the exact build that produced it. Builds
+ * with a dSYM use its DWARF file; Debug builds without one use the
+ * executable, whose debug map still points at the object files.
+ */
+
+export interface SymbolicatedFrame {
+  symbol: string;
+  file?: string;
+  line?: number;
+}
+
+/**
+ * Parse `dwarfdump --uuid` output, one "UUID: <uuid> (<arch>) <path>" line per slice
+ */
+export function parseDwarfdumpUuids(output: string): Array<{ uuid: string; arch: string }> {
+  const slices: Array<{ uuid: string; arch: string }> = [];
+  for (const line of output.split('\n')) {
+    const match = line.match(/^UUID: ([0-9A-Fa-f-]{36}) \(([^)]+)\)/);
+    if (match) {
+      slices.push({ uuid: match[1].toUpperCase(), arch: match[2] });
+    }
+  }
+  return slices;
+}
+
+/**
+ * Binary to read symbols from for a product: the dSYM's DWARF file, or the executable
+ */
+function symbolsBinary(product: BuildProduct): string | null {
+  const executable = product.executable ?? path.basename(product.name, '.app');
+  if (product.dsymPath) {
+    const dwarf = path.join(product.dsymPath, 'Contents', 'Resources', 'DWARF', executable);
+    if (fs.existsSync(dwarf)) {
+      return dwarf;
+    }
+  }
+  const binary = product.executablePath ?? path.join(product.path, executable);
+  return fs.existsSync(binary) ? binary : null;
+}
+
+/**
+ * Record the symbols of a build's products by UUID
+ */
+export async function recordBuildSymbols(projectPath: string, products: BuildProduct[]): Promise<void> {
+  const builtAt = new Date().toISOString();
+  const records: BuildSymbolsRecord[] = [];
+
+  for (const product of products) {
+    const binary = symbolsBinary(product);
+    if (!binary) {
+      continue;
+    }
+    for (const { uuid, arch } of parseDwarfdumpUuids(await runCommand('dwarfdump', ['--uuid', binary], TOOL_COMMAND))) {
+      records.push({
+        uuid,
+        arch,
+        symbols_path: binary,
+        bundle_id: product.bundleId ?? null,
+        project_path: projectPath,
+        built_at: builtAt,
+      });
+    }
+  }
+
+  if (records.length > 0) {
+    saveBuildSymbols(records);
+  }
+}
+
+/**
+ * Symbols recorded for a binary UUID, if it was built by Plasma and still exists
+ */
+export function findBuildSymbols(uuid: string): BuildSymbolsRecord | null {
+  const record = getBuildSymbols(uuid.toUpperCase());
+  return record && fs.existsSync(record.symbols_path) ? record : null;
+}
+
+/**
+ * Parse one line of atos output, e.g.
+ * "closure #1 in ContentView.body.getter (in MyApp) (ContentView.swift:12)"
+ */
+export function parseAtosLine(line: string): SymbolicatedFrame | null {
+  const trimmed = line.trim();
+  // atos echoes addresses it couldn't resolve
+  if (!trimmed || /^0x[0-9a-f]+$/i.test(trimmed)) {
+    return null;
+  }
+  const match = trimmed.match(/^(.*) \(in [^)]+\) \((.+):(\d+)\)$/);
+  if (match) {
+    return { symbol: match[1], file: match[2], line: Number(match[3]) };
+  }
+  return { symbol: trimmed.replace(/ \(in [^)]+\)( \+ \d+)?$/, '') };
+}
+
+/**
+ * Resolve addresses in a binary loaded at loadAddress, in the same order
+ */
+export async function symbolicateAddresses(
+  symbols: BuildSymbolsRecord,
+  loadAddress: number,
+  addresses: number[]
+): Promise<Array<SymbolicatedFrame | null>> {
+  const output = await runCommand('atos', [
+    '-o',
+    symbols.symbols_path,
+    '-arch',
+    symbols.arch,
+    '-l',
+    `0x${loadAddress.toString(16)}`,
+    ...addresses.map((address) => `0x${address.toString(16)}`),
+  ], TOOL_COMMAND);
+  const lines = output.split('\n');
+  return addresses.map((_, index) => parseAtosLine(lines[index] ?? ''));
+}
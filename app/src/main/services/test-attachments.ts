@@ -0,0 +1,204 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import { getTestRun } from './database';
+import { PlasmaError } from './errors';
+import { runCommand, TOOL_COMMAND } from './exec';
+import { getDataSubdir } from './paths';
+
+/**
+ * Attachments of failed tests: the screenshot XCTest takes when a UI test
+ * fails, and the logs and files tests attach themselves. They're exported
+ * from the run's .xcresult bundle with `xcresulttool export attachments`
+ * and kept next to it, so why a test failed can be seen without opening
+ * the bundle in Xcode. Exporting needs Xcode 16 or later.
+ */
+
+export interface TestAttachment {
+  /** Name of the exported file, which the attachment is fetched by */
+  fileName: string;
+  /** Name the test gave it, like "Screenshot_1_0D2E….png" */
+  name: string;
+  contentType: string;
+  bytes: number;
+  timestamp: string | null;
+  deviceName: string | null;
+}
+
+export interface FailedTestAttachments {
+  /** Identifier of the test in the run */
+  identifier: string;
+  attachments: TestAttachment[];
+}
+
+export interface TestRunAttachments {
+  testRunId: string;
+  tests: FailedTestAttachments[];
+}
+
+interface ExportedTest {
+  testIdentifier: string;
+  attachments: Array<Omit<TestAttachment, 'contentType' | 'bytes'>>;
+}
+
+const MANIFEST = 'manifest.json';
+
+const CONTENT_TYPES: Record<string, string> = {
+  '.png': 'image/png',
+  '.jpg': 'image/jpeg',
+  '.jpeg': 'image/jpeg',
+  '.heic': 'image/heic',
+  '.gif': 'image/gif',
+  '.mp4': 'video/mp4',
+  '.mov': 'video/quicktime',
+  '.txt': 'text/plain; charset=utf-8',
+  '.log': 'text/plain; charset=utf-8',
+  '.json': 'application/json',
+  '.html': 'text/html; charset=utf-8',
+  '.plist': 'application/x-plist',
+};
+
+// Exports in progress, so concurrent requests for a run wait on the same one
+const pendingExports = new Map<string, Promise<void>>();
+
+export function contentTypeOf(fileName: string): string {
+  return CONTENT_TYPES[path.extname(fileName).toLowerCase()] ?? 'application/octet-stream';
+}
+
+/**
+ * Parse the manifest.json `xcresulttool export attachments` writes next to
+ * the files, which lists each test's attachments
+ */
+export function parseAttachmentManifest(json: string): ExportedTest[] {
+  const manifest = JSON.parse(json);
+  return (Array.isArray(manifest) ? manifest : [])
+    .filter((entry: Record<string, any>) => typeof entry.testIdentifier === 'string')
+    .map((entry: Record<string, any>) => ({
+      testIdentifier: entry.testIdentifier,
+      attachments: (Array.isArray(entry.attachments) ? entry.attachments : [])
+        .filter((attachment: Record<string, any>) => typeof attachment.exportedFileName === 'string')
+        .map((attachment: Record<string, any>) => ({
+          fileName: path.basename(attachment.exportedFileName),
+          name: String(attachment.suggestedHumanReadableName ?? attachment.exportedFileName),
+          // Seconds since 1970
+          timestamp:
+            typeof attachment.timestamp === 'number' ? new Date(attachment.timestamp * 1000).toISOString() : null,
+          deviceName: typeof attachment.deviceName === 'string' ? attachment.deviceName : null,
+        })),
+    }));
+}
+
+/**
+ * Whether a test identifier of the result bundle, like "LoginTests/testSignIn()",
+ * names a test of the run, like "MyAppTests/LoginTests/testSignIn"
+ */
+export function matchesTestIdentifier(identifier: string, bundleIdentifier: string): boolean {
+  const parts = (value: string) => value.split('/').map((part) => part.replace(/\(\)$/, ''));
+  const run = parts(identifier);
+  const bundle = parts(bundleIdentifier);
+  if (bundle.length > run.length) return false;
+  return bundle.every((part, index) => part === run[run.length - bundle.length + index]);
+}
+
+function attachmentsDir(testRunId: string): string {
+  return path.join(getDataSubdir('test-results'), `${path.basename(testRunId)}-attachments`);
+}
+
+/**
+ * Export the attachments of a result bundle's failed tests, once. The
+ * files go to a temporary folder first so a failed export isn't mistaken
+ * for one without attachments.
+ */
+export async function exportTestAttachments(testRunId: string, resultBundlePath: string): Promise<void> {
+  const dir = attachmentsDir(testRunId);
+  if (fs.existsSync(path.join(dir, MANIFEST))) return;
+
+  let pending = pendingExports.get(testRunId);
+  if (!pending) {
+    pending = (async () => {
+      const partial = `${dir}.partial`;
+      fs.rmSync(partial, { recursive: true, force: true });
+      fs.mkdirSync(partial, { recursive: true });
+      try {
+        await runCommand('xcrun', [
+          'xcresulttool',
+          'export',
+          'attachments',
+          '--path',
+          resultBundlePath,
+          '--output-path',
+          partial,
+          '--only-failures',
+        ], TOOL_COMMAND);
+        if (!fs.existsSync(path.join(partial, MANIFEST))) {
+          fs.writeFileSync(path.join(partial, MANIFEST), '[]');
+        }
+        fs.rmSync(dir, { recursive: true, force: true });
+        fs.renameSync(partial, dir);
+      } catch (err) {
+        fs.rmSync(partial, { recursive: true, force: true });
+        throw err;
+      }
+    })().finally(() => pendingExports.delete(testRunId));
+    pendingExports.set(testRunId, pending);
+  }
+  return pending;
+}
+
+function requireResultBundle(testRunId: string): { bundlePath: string; failedTests: string[] } {
+  const record = getTestRun(testRunId);
+  if (!record) {
+    throw new PlasmaError('not_found', `Test run ${testRunId} not found`);
+  }
+  if (!record.result_bundle_path || !fs.existsSync(record.result_bundle_path)) {
+    throw new PlasmaError('not_found', `Test run ${testRunId} has no result bundle`);
+  }
+  // Flaky tests failed before passing on a retry, and the bundle has that first attempt
+  const tests: Array<{ identifier: string; status: string; flaky: boolean }> = JSON.parse(record.tests);
+  return {
+    bundlePath: record.result_bundle_path,
+    failedTests: tests.filter((test) => test.status === 'failed' || test.flaky).map((test) => test.identifier),
+  };
+}
+
+/**
+ * Attachments of each failed test of a run, exported from its result
+ * bundle the first time they're asked for
+ */
+export async function getTestRunAttachments(testRunId: string): Promise<TestRunAttachments> {
+  const { bundlePath, failedTests } = requireResultBundle(testRunId);
+  if (failedTests.length === 0) {
+    return { testRunId, tests: [] };
+  }
+  await exportTestAttachments(testRunId, bundlePath);
+
+  const dir = attachmentsDir(testRunId);
+  const exported = parseAttachmentManifest(fs.readFileSync(path.join(dir, MANIFEST), 'utf-8'));
+  const tests: FailedTestAttachments[] = failedTests
+    .map((identifier) => ({
+      identifier,
+      attachments: exported
+        .filter((entry) => matchesTestIdentifier(identifier, entry.testIdentifier))
+        .flatMap((entry) => entry.attachments)
+        .filter((attachment) => fs.existsSync(path.join(dir, attachment.fileName)))
+        .map((attachment) => ({
+          ...attachment,
+          contentType: contentTypeOf(attachment.fileName),
+          bytes: fs.statSync(path.join(dir, attachment.fileName)).size,
+        })),
+    }))
+    .filter((test) => test.attachments.length > 0);
+  return { testRunId, tests };
+}
+
+/**
+ * Path of an exported attachment, for serving it
+ */
+export async function getTestAttachmentPath(testRunId: string, fileName: string): Promise<string> {
+  const { bundlePath } = requireResultBundle(testRunId);
+  await exportTestAttachments(testRunId, bundlePath);
+  const file = path.join(attachmentsDir(testRunId), path.basename(fileName));
+  if (path.basename(fileName) === MANIFEST || !fs.existsSync(file)) {
+    throw new PlasmaError('not_found', `No attachment ${fileName} in test run ${testRunId}`);
+  }
+  return file;
+}
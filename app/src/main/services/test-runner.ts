@@ -0,0 +1,735 @@
+import { spawn } from 'child_process';
+import * as crypto from 'crypto';
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { getTestRun, getTestRuns, saveTestRun, TestRunRecord } from './database';
+import { PlasmaError } from './errors';
+import { runCommand, TOOL_COMMAND } from './exec';
+import { readManifest } from './manifest';
+import { getDataSubdir } from './paths';
+import { detectProject } from './projects';
+import { registerProcess, signalProcess } from './process-manager';
+import { enforceTimeout } from './timeouts';
+import { recordCoverage } from './coverage';
+import { exportTestAttachments } from './test-attachments';
+import { cloneSimulator, deleteSimulator, findSimulator, listSimulators, shutdownSimulator } from './simulator';
+import type { TestCaseResult, TestFailure, TestRun, TestRunStatus, TestStatus } from '../../shared/ipc-types';
+
+/**
+ * Test runs with `xcodebuild test` on a simulator. Results are parsed from
+ * the console output as tests finish (XCTest and Swift Testing), each run
+ * keeps its .xcresult bundle, and the outcome is stored so runs can be
+ * listed and their coverage looked up later.
+ *
+ * Large suites can be sharded: the tests are built once, split across
+ * clones of the simulator, and run concurrently with test-without-building,
+ * after which the shards' results and result bundles are merged.
+ *
+ * Failed tests can be retried per a retry policy; a test that passes on a
+ * later attempt counts as passed but is marked flaky, so instability can be
+ * tracked across runs.
+ *
+ * The attachments of failed tests, such as failure screenshots, are
+ * exported from the result bundle when the run finishes.
+ */
+
+export type { TestCaseResult, TestFailure, TestRun, TestRunStatus, TestStatus };
+
+export interface TestRunOptions {
+  projectPath: string;
+  scheme: string;
+  udid: string;
+  configuration?: string;
+  coverage?: boolean;
+  /** Limit the run to these tests (Target, Target/Class, or Target/Class/method) */
+  onlyTesting?: string[];
+  /** Split the tests across this many clones of the simulator */
+  shards?: number;
+  /** Defaults to the [tests.retry] table of the project's plasma.toml */
+  retry?: Partial<TestRetryPolicy>;
+}
+
+export interface TestRetryPolicy {
+  /** Times a failing test is run in total, 1 for no retries */
+  maxAttempts: number;
+  /** Only retry failures whose message matches one of these regular expressions; empty retries any failure */
+  retryOn: string[];
+}
+
+export type TestShardStatus = 'running' | 'passed' | 'failed' | 'error';
+
+export type TestRunEvent =
+  | { type: 'started'; run: TestRun }
+  | { type: 'output'; line: string; shard?: number }
+  | { type: 'shard'; index: number; udid: string; tests: number; status: TestShardStatus }
+  | { type: 'test'; test: TestCaseResult; shard?: number }
+  | { type: 'retry'; attempt: number; tests: string[] }
+  | { type: 'completed'; run: TestRun };
+
+export interface FlakyTestSummary {
+  identifier: string;
+  /** Stored runs the test was flaky in */
+  flakyRuns: number;
+  /** Stored runs the test ran in */
+  totalRuns: number;
+  lastFlakyAt: string;
+}
+
+export const MAX_TEST_SHARDS = 8;
+export const MAX_TEST_ATTEMPTS = 10;
+
+// Test Case '-[MyAppTests.LoginTests testSignIn]' passed (0.012 seconds).
+// Test case 'LoginTests.testSignIn()' passed on 'Clone 1 of iPhone 15' (0.012 seconds)
+const XCTEST_RESULT = /^Test [Cc]ase '(.+?)' (passed|failed|skipped)(?: on '.+?')? \((\d+(?:\.\d+)?) seconds\)/;
+// /path/LoginTests.swift:42: error: -[MyAppTests.LoginTests testSignIn] : XCTAssertTrue failed
+const XCTEST_FAILURE = /^(.+?):(\d+): error: (.+?) : (.*)$/;
+// ✔ Test signIn() passed after 0.012 seconds.
+const SWIFT_TESTING_RESULT = /^\S*\s*Test (.+?) (passed|failed) after (\d+(?:\.\d+)?) seconds/;
+// ↪ Test signIn() skipped: "Needs a network"
+const SWIFT_TESTING_SKIPPED = /^\S*\s*Test (.+?) skipped/;
+// ✘ Test signIn() recorded an issue at LoginTests.swift:42:5: Expectation failed: ...
+const SWIFT_TESTING_ISSUE = /^\S*\s*Test (.+?) recorded an issue at (.+?):(\d+):\d+: (.*)$/;
+
+/**
+ * Split an XCTest case name into its parts: "-[Target.Class method]" or
+ * "Class.method()" as printed for parallel runs
+ */
+function parseXCTestName(raw: string): Pick<TestCaseResult, 'identifier' | 'suite' | 'name'> {
+  const objc = raw.match(/^-\[(?:(.+)\.)?(\S+) (\S+)\]$/);
+  if (objc) {
+    const [, target, suite, name] = objc;
+    return { identifier: [target, suite, name].filter(Boolean).join('/'), suite, name };
+  }
+  const swift = raw.match(/^(?:(.+)\.)?([^.]+?)(?:\(\))?$/);
+  if (swift && swift[1]) {
+    return { identifier: `${swift[1].replace(/\./g, '/')}/${swift[2]}`, suite: swift[1], name: swift[2] };
+  }
+  return { identifier: raw, suite: null, name: raw };
+}
+
+/**
+ * Turns xcodebuild test output into test results, one line at a time.
+ * Failures are printed before the line reporting the test's result, so
+ * they are held until the test finishes.
+ */
+export class TestOutputParser {
+  private pendingFailures = new Map<string, TestFailure[]>();
+
+  /**
+   * Feed a line of output, returning the result of a test that finished on it
+   */
+  parseLine(line: string): TestCaseResult | null {
+    const trimmed = line.trim();
+
+    const xctestFailure = trimmed.match(XCTEST_FAILURE);
+    if (xctestFailure) {
+      const [, file, lineNumber, test, message] = xctestFailure;
+      this.addFailure(test, { message, file, line: Number(lineNumber) });
+      return null;
+    }
+
+    const issue = trimmed.match(SWIFT_TESTING_ISSUE);
+    if (issue) {
+      const [, test, file, lineNumber, message] = issue;
+      this.addFailure(test, { message, file, line: Number(lineNumber) });
+      return null;
+    }
+
+    const xctestResult = trimmed.match(XCTEST_RESULT);
+    if (xctestResult) {
+      const [, raw, status, seconds] = xctestResult;
+      return this.finish(raw, parseXCTestName(raw), status as TestStatus, Number(seconds));
+    }
+
+    const swiftResult = trimmed.match(SWIFT_TESTING_RESULT);
+    if (swiftResult) {
+      const [, name, status, seconds] = swiftResult;
+      return this.finish(name, { identifier: name, suite: null, name }, status as TestStatus, Number(seconds));
+    }
+
+    const skipped = trimmed.match(SWIFT_TESTING_SKIPPED);
+    if (skipped) {
+      const name = skipped[1];
+      return this.finish(name, { identifier: name, suite: null, name }, 'skipped', 0);
+    }
+
+    return null;
+  }
+
+  private addFailure(test: string, failure: TestFailure): void {
+    const failures = this.pendingFailures.get(test) ?? [];
+    failures.push(failure);
+    this.pendingFailures.set(test, failures);
+  }
+
+  private finish(
+    raw: string,
+    name: Pick<TestCaseResult, 'identifier' | 'suite' | 'name'>,
+    status: TestStatus,
+    seconds: number
+  ): TestCaseResult {
+    const failures = this.pendingFailures.get(raw) ?? [];
+    this.pendingFailures.delete(raw);
+    return { ...name, status, durationMs: Math.round(seconds * 1000), failures, attempts: 1, flaky: false };
+  }
+}
+
+function toRecord(run: TestRun): TestRunRecord {
+  return {
+    id: run.id,
+    project_path: run.projectPath,
+    scheme: run.scheme,
+    configuration: run.configuration,
+    udid: run.udid,
+    status: run.status,
+    coverage_enabled: run.coverageEnabled ? 1 : 0,
+    result_bundle_path: run.resultBundlePath,
+    started_at: run.startedAt,
+    finished_at: run.finishedAt,
+    passed: run.passed,
+    failed: run.failed,
+    skipped: run.skipped,
+    flaky: run.flaky,
+    line_coverage: run.lineCoverage,
+    shards: run.shards,
+    tests: JSON.stringify(run.tests),
+    error: run.error,
+  };
+}
+
+function fromRecord(record: TestRunRecord): TestRun {
+  return {
+    id: record.id,
+    projectPath: record.project_path,
+    scheme: record.scheme,
+    configuration: record.configuration,
+    udid: record.udid,
+    status: record.status as TestRunStatus,
+    coverageEnabled: record.coverage_enabled === 1,
+    resultBundlePath: record.result_bundle_path,
+    startedAt: record.started_at,
+    finishedAt: record.finished_at,
+    passed: record.passed,
+    failed: record.failed,
+    skipped: record.skipped,
+    flaky: record.flaky,
+    lineCoverage: record.line_coverage,
+    shards: record.shards,
+    tests: JSON.parse(record.tests),
+    error: record.error,
+  };
+}
+
+/**
+ * Retry policy of a run: the request's, falling back to plasma.toml
+ */
+function resolveRetryPolicy(options: TestRunOptions): TestRetryPolicy {
+  const manifest = readManifest(options.projectPath)?.testRetry;
+  const maxAttempts = options.retry?.maxAttempts ?? manifest?.maxAttempts ?? 1;
+  const retryOn = options.retry?.retryOn ?? manifest?.retryOn ?? [];
+  if (!Number.isInteger(maxAttempts) || maxAttempts < 1 || maxAttempts > MAX_TEST_ATTEMPTS) {
+    throw new PlasmaError('invalid_request', `maxAttempts must be a whole number from 1 to ${MAX_TEST_ATTEMPTS}`);
+  }
+  for (const pattern of retryOn) {
+    try {
+      new RegExp(pattern);
+    } catch {
+      throw new PlasmaError('invalid_request', `Invalid retry pattern: ${pattern}`);
+    }
+  }
+  return { maxAttempts, retryOn };
+}
+
+/**
+ * Failed tests a retry policy allows another attempt of. Only tests with
+ * a Target/Class/method identifier can be selected with -only-testing.
+ */
+export function retryableTests(tests: TestCaseResult[], policy: TestRetryPolicy): TestCaseResult[] {
+  const patterns = policy.retryOn.map((pattern) => new RegExp(pattern));
+  return tests.filter(
+    (test) =>
+      test.status === 'failed' &&
+      test.attempts < policy.maxAttempts &&
+      test.identifier.includes('/') &&
+      (patterns.length === 0 ||
+        test.failures.some((failure) => patterns.some((pattern) => pattern.test(failure.message))))
+  );
+}
+
+/**
+ * Check a run's options, returning the .xcworkspace or .xcodeproj to test,
+ * so callers can reject a bad request before starting to stream a run
+ */
+export function validateTestRun(options: TestRunOptions): string {
+  const { shards = 1 } = options;
+  if (!Number.isInteger(shards) || shards < 1 || shards > MAX_TEST_SHARDS) {
+    throw new PlasmaError('invalid_request', `shards must be a whole number from 1 to ${MAX_TEST_SHARDS}`);
+  }
+  resolveRetryPolicy(options);
+  const project = detectProject(options.projectPath);
+  if (!project) {
+    throw new PlasmaError('project_not_found', 'No Xcode project found at path');
+  }
+  if (project.type !== 'xcode') {
+    throw new PlasmaError('not_an_xcode_project', `Not an Xcode project: ${project.type}`);
+  }
+  return project.path;
+}
+
+interface XcodebuildExit {
+  code: number | null;
+  error?: PlasmaError;
+}
+
+/**
+ * Run an xcodebuild action, feeding each line of output to onLine
+ */
+function runXcodebuild(args: string[], onLine: (line: string) => void, signal?: AbortSignal): Promise<XcodebuildExit> {
+  return new Promise((resolve) => {
+    // Own process group so the test runner and simulator helpers go down with it
+    const proc = spawn('xcodebuild', args, { detached: true });
+    registerProcess(proc, { group: true });
+    enforceTimeout(proc, 'build', (error) => resolve({ code: null, error }));
+
+    const onAbort = () => signalProcess(proc, 'SIGTERM');
+    signal?.addEventListener('abort', onAbort);
+
+    let buffered = '';
+    const onData = (data: Buffer) => {
+      const lines = (buffered + data.toString()).split('\n');
+      buffered = lines.pop() ?? '';
+      lines.forEach(onLine);
+    };
+    proc.stdout.on('data', onData);
+    proc.stderr.on('data', onData);
+
+    proc.on('close', (code) => {
+      signal?.removeEventListener('abort', onAbort);
+      onLine(buffered);
+      resolve({ code });
+    });
+    proc.on('error', (err) => {
+      signal?.removeEventListener('abort', onAbort);
+      resolve({ code: null, error: new PlasmaError('xcodebuild_failed', err.message) });
+    });
+  });
+}
+
+/**
+ * Parse the output of `-enumerate-tests -test-enumeration-style flat`
+ * into test identifiers
+ */
+export function parseTestEnumeration(json: string): string[] {
+  const enumeration = JSON.parse(json);
+  const identifiers = new Set<string>();
+  for (const plan of Array.isArray(enumeration.values) ? enumeration.values : []) {
+    for (const test of Array.isArray(plan.enabledTests) ? plan.enabledTests : []) {
+      if (typeof test.identifier === 'string') {
+        identifiers.add(test.identifier);
+      }
+    }
+  }
+  return [...identifiers];
+}
+
+/**
+ * Split tests into at most `count` shards of similar size. Tests of the same
+ * class stay together so its setup runs once; the largest classes are
+ * placed first, each on the shard with the fewest tests so far.
+ */
+export function splitTests(identifiers: string[], count: number): string[][] {
+  const classes = new Map<string, string[]>();
+  for (const identifier of identifiers) {
+    const key = identifier.split('/').slice(0, 2).join('/');
+    classes.set(key, [...(classes.get(key) ?? []), identifier]);
+  }
+
+  const shards: string[][] = Array.from({ length: Math.min(count, classes.size) }, () => []);
+  const bySize = [...classes.values()].sort((a, b) => b.length - a.length);
+  for (const tests of bySize) {
+    const smallest = shards.reduce((min, shard) => (shard.length < min.length ? shard : min));
+    smallest.push(...tests);
+  }
+  return shards;
+}
+
+/**
+ * Whether an enumerated test is selected by -only-testing style filters
+ */
+function isSelected(identifier: string, onlyTesting: string[]): boolean {
+  return (
+    onlyTesting.length === 0 ||
+    onlyTesting.some((filter) => identifier === filter || identifier.startsWith(`${filter}/`))
+  );
+}
+
+interface RunContext {
+  run: TestRun;
+  projectArgs: string[];
+  onlyTesting: string[];
+  /** Handles a line of output, returning the test that finished on it */
+  handleLine: (line: string, shard?: number) => TestCaseResult | null;
+  onEvent: (event: TestRunEvent) => void;
+  signal?: AbortSignal;
+}
+
+/**
+ * Run the tests in one `xcodebuild test` on the run's simulator
+ */
+async function runUnsharded(context: RunContext): Promise<XcodebuildExit[]> {
+  const { run, projectArgs, onlyTesting, handleLine, signal } = context;
+  const args = [
+    'test',
+    ...projectArgs,
+    '-destination',
+    `id=${run.udid}`,
+    '-resultBundlePath',
+    run.resultBundlePath!,
+    ...onlyTesting.map((test) => `-only-testing:${test}`),
+  ];
+  return [await runXcodebuild(args, handleLine, signal)];
+}
+
+/**
+ * Build for testing once, then run shards of the tests concurrently on
+ * clones of the run's simulator and merge their result bundles
+ */
+async function runSharded(context: RunContext): Promise<XcodebuildExit[]> {
+  const { run, projectArgs, onlyTesting, handleLine, onEvent, signal } = context;
+  const workDir = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-tests-'));
+  const sharedArgs = [...projectArgs, '-derivedDataPath', path.join(workDir, 'DerivedData')];
+  const clones: string[] = [];
+
+  try {
+    const build = await runXcodebuild(
+      ['build-for-testing', ...sharedArgs, '-destination', `id=${run.udid}`],
+      handleLine,
+      signal
+    );
+    if (build.error || build.code !== 0 || signal?.aborted) {
+      return [build];
+    }
+
+    const enumerationPath = path.join(workDir, 'tests.json');
+    const enumerate = await runXcodebuild(
+      [
+        'test-without-building',
+        ...sharedArgs,
+        '-destination',
+        `id=${run.udid}`,
+        '-enumerate-tests',
+        '-test-enumeration-format',
+        'json',
+        '-test-enumeration-style',
+        'flat',
+        '-test-enumeration-output-path',
+        enumerationPath,
+      ],
+      handleLine,
+      signal
+    );
+    if (enumerate.error || enumerate.code !== 0 || signal?.aborted) {
+      return [enumerate];
+    }
+
+    const tests = parseTestEnumeration(fs.readFileSync(enumerationPath, 'utf-8')).filter((test) =>
+      isSelected(test, onlyTesting)
+    );
+    const shards = splitTests(tests, run.shards);
+    if (shards.length === 0) {
+      return [{ code: null, error: new PlasmaError('invalid_request', 'No tests found to run') }];
+    }
+    // Fewer test classes than requested shards leaves some simulators out
+    run.shards = shards.length;
+
+    // Clones need a shut down source; the source itself runs the first shard
+    const source = findSimulator(await listSimulators(), run.udid);
+    if (!source) {
+      return [{ code: null, error: new PlasmaError('simulator_not_found', `Simulator ${run.udid} not found`) }];
+    }
+    await shutdownSimulator(run.udid);
+    for (let index = 1; index < shards.length; index++) {
+      clones.push(await cloneSimulator(run.udid, `${source.name} (Plasma shard ${index + 1})`));
+    }
+    const udids = [run.udid, ...clones];
+
+    const bundles = shards.map((_, index) => path.join(workDir, `shard-${index + 1}.xcresult`));
+    const exits = await Promise.all(
+      shards.map(async (shard, index) => {
+        onEvent({ type: 'shard', index, udid: udids[index], tests: shard.length, status: 'running' });
+        let failed = 0;
+        const exit = await runXcodebuild(
+          [
+            'test-without-building',
+            ...sharedArgs,
+            '-destination',
+            `id=${udids[index]}`,
+            '-resultBundlePath',
+            bundles[index],
+            ...shard.map((test) => `-only-testing:${test}`),
+          ],
+          (line) => {
+            if (handleLine(line, index)?.status === 'failed') failed++;
+          },
+          signal
+        );
+        const status: TestShardStatus =
+          exit.code === 0 ? 'passed' : !exit.error && failed > 0 ? 'failed' : 'error';
+        onEvent({ type: 'shard', index, udid: udids[index], tests: shard.length, status });
+        return exit;
+      })
+    );
+
+    const existing = bundles.filter((bundle) => fs.existsSync(bundle));
+    if (existing.length > 0 && !signal?.aborted) {
+      try {
+        await runCommand(
+          'xcrun',
+          ['xcresulttool', 'merge', ...existing, '--output-path', run.resultBundlePath!],
+          TOOL_COMMAND
+        );
+      } catch (err) {
+        console.warn('[test-runner] Could not merge shard result bundles:', err);
+      }
+    }
+    return exits;
+  } finally {
+    for (const clone of clones) {
+      await deleteSimulator(clone).catch((err) => console.warn(`[test-runner] Could not delete clone ${clone}:`, err));
+    }
+    fs.rmSync(workDir, { recursive: true, force: true });
+  }
+}
+
+/**
+ * Rerun failed tests the policy allows, one attempt at a time, updating
+ * the run's results in place
+ */
+async function retryFailedTests(context: RunContext, policy: TestRetryPolicy): Promise<void> {
+  const { run, projectArgs, onEvent, signal } = context;
+  const workDir = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-retries-'));
+
+  try {
+    for (let attempt = 2; attempt <= policy.maxAttempts && !signal?.aborted; attempt++) {
+      const retrying = retryableTests(run.tests, policy);
+      if (retrying.length === 0) {
+        return;
+      }
+      onEvent({ type: 'retry', attempt, tests: retrying.map((test) => test.identifier) });
+
+      const parser = new TestOutputParser();
+      const exit = await runXcodebuild(
+        [
+          'test',
+          ...projectArgs,
+          '-destination',
+          `id=${run.udid}`,
+          '-resultBundlePath',
+          path.join(workDir, `attempt-${attempt}.xcresult`),
+          ...retrying.map((test) => `-only-testing:${test.identifier}`),
+        ],
+        (line) => {
+          if (!line.trim()) return;
+          onEvent({ type: 'output', line });
+          const result = parser.parseLine(line);
+          if (!result) return;
+          const test = retrying.find((candidate) => candidate.identifier === result.identifier);
+          if (!test) return;
+
+          test.attempts = attempt;
+          test.durationMs = result.durationMs;
+          if (result.status === 'passed') {
+            test.status = 'passed';
+            test.flaky = true;
+            run.failed--;
+            run.passed++;
+            run.flaky++;
+          } else {
+            test.failures = result.failures;
+          }
+          onEvent({ type: 'test', test });
+        },
+        signal
+      );
+      if (exit.error) {
+        console.warn(`[test-runner] Retry attempt ${attempt} failed: ${exit.error.message}`);
+        return;
+      }
+    }
+  } finally {
+    fs.rmSync(workDir, { recursive: true, force: true });
+  }
+}
+
+/**
+ * Run a scheme's tests on a simulator, reporting results as tests finish.
+ * Resolves with the stored run; failing tests are a "failed" run, while a
+ * build failure or crash of xcodebuild itself is an "error" run.
+ */
+export async function runTests(
+  options: TestRunOptions,
+  onEvent: (event: TestRunEvent) => void,
+  signal?: AbortSignal
+): Promise<TestRun> {
+  const { projectPath, scheme, udid, configuration = 'Debug', coverage = false, onlyTesting = [], shards = 1 } = options;
+  const projectFile = validateTestRun(options);
+  const retryPolicy = resolveRetryPolicy(options);
+
+  const id = crypto.randomUUID();
+  const resultBundlePath = path.join(getDataSubdir('test-results'), `${id}.xcresult`);
+  const run: TestRun = {
+    id,
+    projectPath,
+    scheme,
+    configuration,
+    udid,
+    status: 'running',
+    coverageEnabled: coverage,
+    resultBundlePath,
+    startedAt: new Date().toISOString(),
+    finishedAt: null,
+    passed: 0,
+    failed: 0,
+    skipped: 0,
+    flaky: 0,
+    lineCoverage: null,
+    shards,
+    tests: [],
+    error: null,
+  };
+  saveTestRun(toRecord(run));
+  onEvent({ type: 'started', run });
+
+  const projectArgs = [
+    projectFile.endsWith('.xcworkspace') ? '-workspace' : '-project',
+    projectFile,
+    '-scheme',
+    scheme,
+    '-configuration',
+    configuration,
+    '-enableCodeCoverage',
+    coverage ? 'YES' : 'NO',
+    'CODE_SIGN_IDENTITY=',
+    'CODE_SIGNING_REQUIRED=NO',
+    'CODE_SIGNING_ALLOWED=NO',
+  ];
+
+  // Shards print concurrently, so each gets its own parser
+  const parsers = new Map<number, TestOutputParser>();
+  const errors: string[] = [];
+  const handleLine = (line: string, shard?: number): TestCaseResult | null => {
+    if (!line.trim()) return null;
+    onEvent({ type: 'output', line, shard });
+    if (/^(?:.+: )?error: /.test(line.trim()) && errors.length < 20) {
+      errors.push(line.trim());
+    }
+    const key = shard ?? 0;
+    const parser = parsers.get(key) ?? new TestOutputParser();
+    parsers.set(key, parser);
+    const test = parser.parseLine(line);
+    if (test) {
+      run.tests.push(test);
+      run[test.status]++;
+      onEvent({ type: 'test', test, shard });
+    }
+    return test;
+  };
+
+  const context: RunContext = { run, projectArgs, onlyTesting, handleLine, onEvent, signal };
+  let exits: XcodebuildExit[];
+  try {
+    exits = await (shards > 1 ? runSharded(context) : runUnsharded(context));
+  } catch (err) {
+    exits = [{ code: null, error: err instanceof PlasmaError ? err : new PlasmaError('internal', String(err)) }];
+  }
+
+  const failedExit = exits.find((exit) => exit.error);
+  if (!failedExit && run.failed > 0 && !signal?.aborted) {
+    await retryFailedTests(context, retryPolicy);
+  }
+
+  if (signal?.aborted) {
+    run.status = 'cancelled';
+  } else if (failedExit) {
+    run.status = 'error';
+    run.error = failedExit.error!.message;
+  } else if (exits.every((exit) => exit.code === 0) || (run.flaky > 0 && run.failed === 0)) {
+    // Tests that failed and then passed on a retry don't fail the run
+    run.status = 'passed';
+  } else if (run.failed > 0) {
+    run.status = 'failed';
+  } else {
+    // Nonzero exit without failing tests: the build or the test runner itself failed
+    const code = exits.find((exit) => exit.code !== 0)?.code;
+    run.status = 'error';
+    run.error = errors.length > 0 ? errors.join('\n') : `xcodebuild exited with code ${code}`;
+  }
+
+  if (!fs.existsSync(resultBundlePath)) {
+    run.resultBundlePath = null;
+  } else if (coverage && run.status !== 'cancelled') {
+    try {
+      run.lineCoverage = (await recordCoverage(id, resultBundlePath)).lineCoverage;
+    } catch (err) {
+      console.warn('[test-runner] Could not read coverage:', err);
+    }
+  }
+  if (run.resultBundlePath && (run.failed > 0 || run.flaky > 0) && run.status !== 'cancelled') {
+    try {
+      await exportTestAttachments(id, run.resultBundlePath);
+    } catch (err) {
+      console.warn('[test-runner] Could not export attachments:', err);
+    }
+  }
+
+  run.finishedAt = new Date().toISOString();
+  saveTestRun(toRecord(run));
+  console.log(
+    `[test-runner] ${scheme} on ${udid}: ${run.status} (${run.passed} passed, ${run.failed} failed, ${run.skipped} skipped, ${run.flaky} flaky)`
+  );
+  onEvent({ type: 'completed', run });
+  return run;
+}
+
+/**
+ * Stored test runs, newest first
+ */
+export function listTestRuns(projectPath?: string, limit?: number): TestRun[] {
+  return getTestRuns(projectPath, limit).map(fromRecord);
+}
+
+export function getTestRunById(id: string): TestRun {
+  const record = getTestRun(id);
+  if (!record) {
+    throw new PlasmaError('not_found', `Test run ${id} not found`);
+  }
+  return fromRecord(record);
+}
+
+/**
+ * Tests that were flaky in the stored runs of a project, most often flaky first
+ */
+export function listFlakyTests(projectPath?: string, limit: number = 100): FlakyTestSummary[] {
+  const summaries = new Map<string, FlakyTestSummary>();
+  // Runs are newest first, so the first flaky run seen is the latest
+  for (const run of listTestRuns(projectPath, limit)) {
+    for (const test of run.tests) {
+      const summary = summaries.get(test.identifier) ?? {
+        identifier: test.identifier,
+        flakyRuns: 0,
+        totalRuns: 0,
+        lastFlakyAt: '',
+      };
+      summary.totalRuns++;
+      if (test.flaky) {
+        summary.flakyRuns++;
+        summary.lastFlakyAt ||= run.startedAt;
+      }
+      summaries.set(test.identifier, summary);
+    }
+  }
+  return [...summaries.values()]
+    .filter((summary) => summary.flakyRuns > 0)
+    .sort((a, b) => b.flakyRuns / b.totalRuns - a.flakyRuns / a.totalRuns || b.flakyRuns - a.flakyRuns);
+}
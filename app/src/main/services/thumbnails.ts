@@ -0,0 +1,138 @@
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { PlasmaError } from './errors';
+import { runCommand, SIMCTL_COMMAND, TOOL_COMMAND } from './exec';
+
+/**
+ * Small previews of simulator screens for lists of devices, where a full
+ * MJPEG stream per device would be too heavy. A simulator's screen is
+ * captured as a JPEG at most once every two seconds however many clients
+ * ask, and scaled down with sips, so no image is decoded on the event loop.
+ */
+
+export interface Thumbnail {
+  jpeg: Buffer;
+  width: number;
+  height: number;
+  capturedAt: number;
+}
+
+interface Capture {
+  /** The screen at full size */
+  screen: Thumbnail;
+  /** Scaled versions made or being made, by width */
+  thumbnails: Map<number, Promise<Thumbnail>>;
+}
+
+export const THUMBNAIL_INTERVAL_MS = 2000;
+const DEFAULT_THUMBNAIL_WIDTH = 160;
+const MAX_THUMBNAIL_WIDTH = 480;
+// Captures of simulators nobody asked about for this long are dropped
+const STALE_CAPTURE_MS = 60_000;
+
+const captures = new Map<string, Capture>();
+// Captures in progress, so clients polling together share one screenshot
+const pendingCaptures = new Map<string, Promise<Capture>>();
+
+/**
+ * Read the width query parameter, defaulting to 160 pixels
+ */
+export function parseThumbnailWidth(value: string | null): number {
+  if (value === null) {
+    return DEFAULT_THUMBNAIL_WIDTH;
+  }
+  const width = Number(value);
+  if (!Number.isInteger(width) || width < 16 || width > MAX_THUMBNAIL_WIDTH) {
+    throw new PlasmaError('invalid_request', `width must be a whole number of pixels from 16 to ${MAX_THUMBNAIL_WIDTH}`);
+  }
+  return width;
+}
+
+/**
+ * Parse the size `sips -g pixelWidth -g pixelHeight` prints
+ */
+export function parseSipsSize(output: string): { width: number; height: number } {
+  const width = Number(output.match(/pixelWidth:\s*(\d+)/)?.[1]);
+  const height = Number(output.match(/pixelHeight:\s*(\d+)/)?.[1]);
+  if (!width || !height) {
+    throw new PlasmaError('tool_failed', 'sips did not report the size of the screenshot');
+  }
+  return { width, height };
+}
+
+async function inTempDir<T>(body: (dir: string) => Promise<T>): Promise<T> {
+  const dir = await fs.promises.mkdtemp(path.join(os.tmpdir(), 'plasma-thumbnail-'));
+  try {
+    return await body(dir);
+  } finally {
+    await fs.promises.rm(dir, { recursive: true, force: true });
+  }
+}
+
+function dropStaleCaptures(now: number): void {
+  for (const [udid, capture] of captures) {
+    if (now - capture.screen.capturedAt > STALE_CAPTURE_MS) {
+      captures.delete(udid);
+    }
+  }
+}
+
+async function captureScreen(udid: string): Promise<Capture> {
+  const cached = captures.get(udid);
+  if (cached && Date.now() - cached.screen.capturedAt < THUMBNAIL_INTERVAL_MS) {
+    return cached;
+  }
+  let pending = pendingCaptures.get(udid);
+  if (!pending) {
+    pending = inTempDir(async (dir) => {
+      const file = path.join(dir, 'screen.jpg');
+      await runCommand('xcrun', ['simctl', 'io', udid, 'screenshot', '--type=jpeg', file], SIMCTL_COMMAND);
+      const sizeOutput = await runCommand('sips', ['-g', 'pixelWidth', '-g', 'pixelHeight', file], TOOL_COMMAND);
+      const size = parseSipsSize(sizeOutput);
+      const capture: Capture = {
+        screen: { jpeg: await fs.promises.readFile(file), ...size, capturedAt: Date.now() },
+        thumbnails: new Map(),
+      };
+      dropStaleCaptures(capture.screen.capturedAt);
+      captures.set(udid, capture);
+      return capture;
+    }).finally(() => pendingCaptures.delete(udid));
+    pendingCaptures.set(udid, pending);
+  }
+  return pending;
+}
+
+/**
+ * Scale a screen down to a width, keeping its aspect ratio. Screens
+ * already narrower are returned as they are.
+ */
+async function scaleScreen(screen: Thumbnail, width: number): Promise<Thumbnail> {
+  if (width >= screen.width) {
+    return screen;
+  }
+  const height = Math.max(1, Math.round((screen.height * width) / screen.width));
+  return inTempDir(async (dir) => {
+    const source = path.join(dir, 'screen.jpg');
+    const scaled = path.join(dir, 'thumbnail.jpg');
+    await fs.promises.writeFile(source, screen.jpeg);
+    await runCommand('sips', ['-z', String(height), String(width), source, '--out', scaled], TOOL_COMMAND);
+    return { jpeg: await fs.promises.readFile(scaled), width, height, capturedAt: screen.capturedAt };
+  });
+}
+
+/**
+ * A preview of a simulator's screen at most two seconds old, as a JPEG
+ * scaled down to the given width
+ */
+export async function getThumbnail(udid: string, width: number = DEFAULT_THUMBNAIL_WIDTH): Promise<Thumbnail> {
+  const capture = await captureScreen(udid);
+  let thumbnail = capture.thumbnails.get(width);
+  if (!thumbnail) {
+    thumbnail = scaleScreen(capture.screen, width);
+    capture.thumbnails.set(width, thumbnail);
+    // A failed scale is tried again by the next request
+    thumbnail.catch(() => capture.thumbnails.delete(width));
+  }
+  return thumbnail;
+}
@@ -0,0 +1,111 @@
+import { ChildProcess } from 'child_process';
+import { getSettings } from './database';
+import { signalProcess } from './process-manager';
+import { PlasmaError } from './errors';
+
+/**
+ * Per-operation timeouts for external tools
+ * xcodebuild and simctl can hang indefinitely (e.g. waiting on a license
+ * prompt), so every invocation is bounded and terminated when it overruns.
+ */
+
+export type TimeoutOperation = 'build' | 'xcodebuild' | 'simctl' | 'tool' | 'upload' | 'session';
+
+/** Settings key holding the timeout in seconds for each operation */
+export const TIMEOUT_SETTING_KEYS: Record<TimeoutOperation, string> = {
+  build: 'buildTimeout',
+  xcodebuild: 'xcodebuildTimeout',
+  simctl: 'simctlTimeout',
+  // Other command line tools, like git, codesign, adb, or xccov
+  tool: 'toolTimeout',
+  // Uploading an IPA to App Store Connect, which takes a while for large apps
+  upload: 'uploadTimeout',
+  // How long an unwatched simulator-server session is kept around
+  session: 'sessionIdleTimeout',
+};
+
+/** Defaults in seconds when no setting is stored */
+export const DEFAULT_TIMEOUTS: Record<TimeoutOperation, number> = {
+  build: 30 * 60,
+  xcodebuild: 2 * 60,
+  simctl: 60,
+  tool: 2 * 60,
+  upload: 60 * 60,
+  session: 5 * 60,
+};
+
+/** Time between SIGTERM and SIGKILL */
+const KILL_GRACE_MS = 5000;
+
+/**
+ * Raised when an external tool exceeds its configured timeout
+ */
+export class TimeoutError extends PlasmaError {
+  readonly operation: TimeoutOperation;
+  readonly timeoutMs: number;
+
+  constructor(operation: TimeoutOperation, command: string, timeoutMs: number) {
+    super('timeout', `${command} timed out after ${Math.round(timeoutMs / 1000)}s`);
+    this.name = 'TimeoutError';
+    this.operation = operation;
+    this.timeoutMs = timeoutMs;
+  }
+}
+
+/**
+ * Configured timeout for an operation in milliseconds, 0 when disabled
+ */
+export function getTimeoutMs(operation: TimeoutOperation): number {
+  let seconds = DEFAULT_TIMEOUTS[operation];
+  try {
+    const stored = getSettings()[TIMEOUT_SETTING_KEYS[operation]];
+    const parsed = stored === undefined ? NaN : parseFloat(stored);
+    if (Number.isFinite(parsed) && parsed >= 0) {
+      seconds = parsed;
+    }
+  } catch (err) {
+    console.error('[timeouts] Failed to read timeout settings:', err);
+  }
+  return seconds * 1000;
+}
+
+/**
+ * Terminate proc if it runs longer than the operation's timeout
+ * Sends SIGTERM, then SIGKILL after a grace period, and reports a TimeoutError.
+ * Returns a function that clears the timer; it is also cleared when the process exits.
+ */
+export function enforceTimeout(
+  proc: ChildProcess,
+  operation: TimeoutOperation,
+  onTimeout: (error: TimeoutError) => void
+): () => void {
+  const timeoutMs = getTimeoutMs(operation);
+  if (timeoutMs === 0) {
+    return () => {};
+  }
+
+  let killTimer: NodeJS.Timeout | null = null;
+  const timer = setTimeout(() => {
+    const error = new TimeoutError(operation, proc.spawnfile, timeoutMs);
+    console.error(`[timeouts] ${error.message}, terminating pid ${proc.pid}`);
+    signalProcess(proc, 'SIGTERM');
+    killTimer = setTimeout(() => {
+      if (proc.exitCode === null && proc.signalCode === null) {
+        signalProcess(proc, 'SIGKILL');
+      }
+    }, KILL_GRACE_MS);
+    onTimeout(error);
+  }, timeoutMs);
+
+  const clear = () => {
+    clearTimeout(timer);
+    if (killTimer) {
+      clearTimeout(killTimer);
+    }
+  };
+
+  proc.once('exit', () => clearTimeout(timer));
+  proc.once('close', clear);
+  proc.once('error', clear);
+  return clear;
+}
@@ -0,0 +1,58 @@
+import { describe, it, expect } from 'vitest';
+import { parseToml } from './toml';
+
+describe('parseToml', () => {
+  it('reads tables, dotted keys, and values', () => {
+    const source = `# Shared settings
+scheme = "App"
+retries = 1_000
+debug = true
+
+[hooks]
+preBuild = ["swiftlint", 'make generate'] # trailing comment
+env.API_URL = "http://localhost"
+
+[build_cache.s3]
+bucket = "builds"`;
+    expect(parseToml(source)).toEqual({
+      scheme: 'App',
+      retries: 1000,
+      debug: true,
+      hooks: { preBuild: ['swiftlint', 'make generate'], env: { API_URL: 'http://localhost' } },
+      build_cache: { s3: { bucket: 'builds' } },
+    });
+  });
+
+  it('reads inline tables and arrays spanning lines', () => {
+    const source = `steps = [
+  { launch = true },
+  { tap = "login", index = 2 },
+]`;
+    expect(parseToml(source)).toEqual({ steps: [{ launch: true }, { tap: 'login', index: 2 }] });
+  });
+
+  it('decodes escapes in basic strings only', () => {
+    expect(parseToml('a = "caf\\u00e9 \\u263A\\t\\"q\\""\nb = \'C:\\path\\u00e9\'')).toEqual({
+      a: 'café ☺\t"q"',
+      b: 'C:\\path\\u00e9',
+    });
+  });
+
+  it('rejects \\u without 4 hex digits', () => {
+    expect(() => parseToml('a = "\\u00g1"')).toThrow(/4 hex digits/);
+    expect(() => parseToml('a = "\\u12"')).toThrow(/4 hex digits/);
+  });
+
+  it('trims the newline opening a multi-line string', () => {
+    expect(parseToml('text = """\nfirst\nsecond"""')).toEqual({ text: 'first\nsecond' });
+  });
+
+  it('reports the line of an error with the file name', () => {
+    expect(() => parseToml('a = 1\na = 2', 'plasma.toml')).toThrow('plasma.toml:2: Duplicate key a');
+    expect(() => parseToml('a = 1\n\nb = nope')).toThrow('Line 3: Unsupported value nope');
+  });
+
+  it('rejects arrays of tables', () => {
+    expect(() => parseToml('[[steps]]')).toThrow(/Arrays of tables are not supported/);
+  });
+});
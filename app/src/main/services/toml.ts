@@ -0,0 +1,200 @@
+import { PlasmaError } from './errors';
+
+/**
+ * The subset of TOML Plasma's files need: plasma.toml, automation scripts,
+ * and screenshot matrices. Tables, dotted keys, strings, numbers, booleans,
+ * arrays, and inline tables are supported; arrays of tables and dates aren't.
+ */
+
+export type TomlValue = string | number | boolean | TomlValue[] | TomlTable;
+export interface TomlTable {
+  [key: string]: TomlValue;
+}
+
+/**
+ * Parse TOML, naming fileName in errors when given
+ */
+export function parseToml(source: string, fileName?: string): TomlTable {
+  const root: TomlTable = {};
+  let current = root;
+  let pos = 0;
+
+  const error = (message: string): never => {
+    const line = source.slice(0, pos).split('\n').length;
+    throw new PlasmaError('invalid_request', fileName ? `${fileName}:${line}: ${message}` : `Line ${line}: ${message}`);
+  };
+
+  const skipWhitespace = (allowNewlines: boolean) => {
+    while (pos < source.length) {
+      const char = source[pos];
+      if (char === ' ' || char === '\t' || char === '\r' || (allowNewlines && char === '\n')) {
+        pos++;
+      } else if (char === '#') {
+        while (pos < source.length && source[pos] !== '\n') pos++;
+      } else {
+        break;
+      }
+    }
+  };
+
+  const parseKey = (): string[] => {
+    const parts: string[] = [];
+    for (;;) {
+      skipWhitespace(false);
+      if (source[pos] === '"' || source[pos] === "'") {
+        parts.push(parseString());
+      } else {
+        const match = /^[A-Za-z0-9_-]+/.exec(source.slice(pos));
+        if (!match) error('Expected a key');
+        parts.push(match![0]);
+        pos += match![0].length;
+      }
+      skipWhitespace(false);
+      if (source[pos] !== '.') return parts;
+      pos++;
+    }
+  };
+
+  const parseString = (): string => {
+    const quote = source[pos];
+    const multiline = source.startsWith(quote.repeat(3), pos);
+    const delimiter = multiline ? quote.repeat(3) : quote;
+    pos += delimiter.length;
+    // A newline right after the opening delimiter is trimmed
+    if (multiline && source[pos] === '\n') pos++;
+
+    let value = '';
+    while (!source.startsWith(delimiter, pos)) {
+      if (pos >= source.length || (!multiline && source[pos] === '\n')) {
+        error('Unterminated string');
+      }
+      const char = source[pos];
+      if (char === '\\' && quote === '"') {
+        const next = source[pos + 1];
+        const escapes: Record<string, string> = { n: '\n', t: '\t', r: '\r', '"': '"', '\\': '\\' };
+        if (next === 'u') {
+          const hex = source.slice(pos + 2, pos + 6);
+          if (!/^[0-9A-Fa-f]{4}$/.test(hex)) error('\\u must be followed by 4 hex digits');
+          value += String.fromCharCode(parseInt(hex, 16));
+          pos += 6;
+        } else if (next in escapes) {
+          value += escapes[next];
+          pos += 2;
+        } else {
+          error(`Invalid escape \\${next}`);
+        }
+      } else {
+        value += char;
+        pos++;
+      }
+    }
+    pos += delimiter.length;
+    return value;
+  };
+
+  const parseValue = (): TomlValue => {
+    skipWhitespace(false);
+    const char = source[pos];
+
+    if (char === '"' || char === "'") {
+      return parseString();
+    }
+
+    if (char === '[') {
+      pos++;
+      const items: TomlValue[] = [];
+      for (;;) {
+        skipWhitespace(true);
+        if (source[pos] === ']') {
+          pos++;
+          return items;
+        }
+        items.push(parseValue());
+        skipWhitespace(true);
+        if (source[pos] === ',') {
+          pos++;
+        } else if (source[pos] !== ']') {
+          error('Expected , or ] in array');
+        }
+      }
+    }
+
+    if (char === '{') {
+      pos++;
+      const table: TomlTable = {};
+      skipWhitespace(false);
+      if (source[pos] === '}') {
+        pos++;
+        return table;
+      }
+      for (;;) {
+        const key = parseKey();
+        if (source[pos] !== '=') error('Expected =');
+        pos++;
+        assign(table, key, parseValue());
+        skipWhitespace(false);
+        if (source[pos] === '}') {
+          pos++;
+          return table;
+        }
+        if (source[pos] !== ',') error('Expected , or } in inline table');
+        pos++;
+      }
+    }
+
+    const match = /^[^\s,\]}#]+/.exec(source.slice(pos));
+    if (!match) error('Expected a value');
+    const raw = match![0];
+    pos += raw.length;
+    if (raw === 'true') return true;
+    if (raw === 'false') return false;
+    const number = Number(raw.replace(/_/g, ''));
+    if (Number.isNaN(number)) error(`Unsupported value ${raw}`);
+    return number;
+  };
+
+  const tableAt = (table: TomlTable, keys: string[]): TomlTable => {
+    let target = table;
+    for (const key of keys) {
+      const existing = target[key];
+      if (existing === undefined) {
+        target[key] = {};
+      } else if (typeof existing !== 'object' || Array.isArray(existing)) {
+        error(`${key} is not a table`);
+      }
+      target = target[key] as TomlTable;
+    }
+    return target;
+  };
+
+  const assign = (table: TomlTable, keys: string[], value: TomlValue) => {
+    const target = tableAt(table, keys.slice(0, -1));
+    const last = keys[keys.length - 1];
+    if (last in target) error(`Duplicate key ${keys.join('.')}`);
+    target[last] = value;
+  };
+
+  for (;;) {
+    skipWhitespace(true);
+    if (pos >= source.length) break;
+
+    if (source[pos] === '[') {
+      if (source[pos + 1] === '[') error('Arrays of tables are not supported');
+      pos++;
+      const keys = parseKey();
+      if (source[pos] !== ']') error('Expected ]');
+      pos++;
+      current = tableAt(root, keys);
+    } else {
+      const keys = parseKey();
+      if (source[pos] !== '=') error('Expected =');
+      pos++;
+      assign(current, keys, parseValue());
+    }
+
+    skipWhitespace(false);
+    if (pos < source.length && source[pos] !== '\n') error('Expected a newline');
+  }
+
+  return root;
+}
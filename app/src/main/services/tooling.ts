@@ -0,0 +1,127 @@
+import { execFile } from 'child_process';
+import * as fs from 'fs';
+import { findAxeBinary } from './simulator';
+
+/**
+ * External helper tools Plasma uses when they're installed, all available
+ * from Homebrew: AXe for touch input and accessibility, and Meta's
+ * fbsimctl and idb_companion as alternative simulator drivers.
+ */
+
+export type HelperToolId = 'axe' | 'fbsimctl' | 'idb';
+
+export interface HelperTool {
+  id: HelperToolId;
+  title: string;
+  installed: boolean;
+  path: string | null;
+  /** First line of its --version output, when it prints one */
+  version: string | null;
+  /** Homebrew command that installs it */
+  installCommand: string;
+}
+
+interface HelperToolDefinition {
+  id: HelperToolId;
+  title: string;
+  binary: string;
+  tap: string;
+  formula: string;
+  find?: () => string | null;
+}
+
+const HELPER_TOOLS: HelperToolDefinition[] = [
+  { id: 'axe', title: 'Input helper (AXe)', binary: 'axe', tap: 'cameroncooke/axe', formula: 'axe', find: findAxeBinary },
+  { id: 'fbsimctl', title: 'Simulator driver (fbsimctl)', binary: 'fbsimctl', tap: 'facebook/fb', formula: 'fbsimctl' },
+  { id: 'idb', title: 'Simulator driver (idb)', binary: 'idb_companion', tap: 'facebook/fb', formula: 'idb-companion' },
+];
+
+// Apps launched from Finder don't inherit the Homebrew PATH
+const HOMEBREW_PREFIXES = ['/opt/homebrew/bin', '/usr/local/bin'];
+
+const VERSION_TIMEOUT_MS = 5000;
+// Detection runs several processes, so health checks reuse it for a while
+const DETECTION_TTL_MS = 60_000;
+
+let detected: { tools: HelperTool[]; at: number } | null = null;
+let pendingDetection: Promise<HelperTool[]> | null = null;
+
+/**
+ * Find a binary in Homebrew's prefixes or on the PATH
+ */
+export function findHomebrewBinary(name: string): string | null {
+  for (const prefix of HOMEBREW_PREFIXES) {
+    const candidate = `${prefix}/${name}`;
+    if (fs.existsSync(candidate)) {
+      return candidate;
+    }
+  }
+
+  const { execSync } = require('child_process');
+  try {
+    const result = execSync(`which ${name}`, { encoding: 'utf-8' }).trim();
+    if (result && fs.existsSync(result)) {
+      return result;
+    }
+  } catch {
+    // Not in PATH
+  }
+
+  return null;
+}
+
+function installCommand(tool: HelperToolDefinition): string {
+  return `brew install ${tool.tap}/${tool.formula}`;
+}
+
+/**
+ * First non-empty line a binary prints for --version, or null when it
+ * fails or takes too long
+ */
+function readVersion(binaryPath: string): Promise<string | null> {
+  return new Promise((resolve) => {
+    execFile(binaryPath, ['--version'], { timeout: VERSION_TIMEOUT_MS }, (error, stdout, stderr) => {
+      const line = `${stdout}\n${stderr}`.split('\n').find((candidate) => candidate.trim());
+      resolve(error && !stdout.trim() ? null : line?.trim() ?? null);
+    });
+  });
+}
+
+async function detectTool(tool: HelperToolDefinition): Promise<HelperTool> {
+  const binaryPath = tool.find ? tool.find() : findHomebrewBinary(tool.binary);
+  return {
+    id: tool.id,
+    title: tool.title,
+    installed: binaryPath !== null,
+    path: binaryPath,
+    version: binaryPath ? await readVersion(binaryPath) : null,
+    installCommand: installCommand(tool),
+  };
+}
+
+/**
+ * Which helper tools are installed, where, and at which version
+ */
+export async function detectHelperTools(options: { refresh?: boolean } = {}): Promise<HelperTool[]> {
+  if (!options.refresh && detected && Date.now() - detected.at < DETECTION_TTL_MS) {
+    return detected.tools;
+  }
+  if (!pendingDetection) {
+    pendingDetection = Promise.all(HELPER_TOOLS.map(detectTool))
+      .then((tools) => {
+        detected = { tools, at: Date.now() };
+        return tools;
+      })
+      .finally(() => {
+        pendingDetection = null;
+      });
+  }
+  return pendingDetection;
+}
+
+/**
+ * The Homebrew command that installs a helper tool
+ */
+export function helperToolInstallCommand(id: HelperToolId): string {
+  return installCommand(HELPER_TOOLS.find((tool) => tool.id === id)!);
+}
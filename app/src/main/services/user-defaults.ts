@@ -0,0 +1,106 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import { PlasmaError } from './errors';
+import { runCommand, SIMCTL_COMMAND } from './exec';
+import { isDictionary, PlistJson, plistToJson, readPlist } from './plist';
+import { getAppInfo, launchInstalledApp } from './simulator';
+
+/**
+ * NSUserDefaults of simulator apps as JSON. Reads come from the preferences
+ * plist in the app's data container. Writes go through the simulator's
+ * `defaults` so cfprefsd picks them up, and the app is relaunched so it
+ * reads the new values.
+ */
+
+export type DefaultsValue = PlistJson;
+
+export interface UserDefaults {
+  bundleId: string;
+  /** Preferences plist in the app's data container */
+  path: string;
+  values: Record<string, DefaultsValue>;
+}
+
+function escapeXml(value: string): string {
+  return value.replace(/&/g, '&amp;').replace(/</g, '&lt;').replace(/>/g, '&gt;');
+}
+
+/**
+ * XML plist fragment for a JSON value, the form `defaults write` takes for
+ * values of any type
+ */
+export function toPlistXml(value: unknown): string {
+  if (typeof value === 'string') return `<string>${escapeXml(value)}</string>`;
+  if (typeof value === 'boolean') return value ? '<true/>' : '<false/>';
+  if (typeof value === 'number') {
+    if (!Number.isFinite(value)) {
+      throw new PlasmaError('invalid_request', `${value} can't be stored in defaults`);
+    }
+    return Number.isInteger(value) ? `<integer>${value}</integer>` : `<real>${value}</real>`;
+  }
+  if (Array.isArray(value)) return `<array>${value.map(toPlistXml).join('')}</array>`;
+  if (typeof value === 'object' && value !== null) {
+    return `<dict>${Object.entries(value)
+      .map(([key, item]) => `<key>${escapeXml(key)}</key>${toPlistXml(item)}`)
+      .join('')}</dict>`;
+  }
+  throw new PlasmaError('invalid_request', 'Defaults values must be strings, numbers, booleans, arrays, or objects');
+}
+
+async function getPreferencesPath(udid: string, bundleId: string): Promise<string> {
+  const app = await getAppInfo(udid, bundleId);
+  if (!app.dataContainer) {
+    throw new PlasmaError('not_found', `${bundleId} has no data container on ${udid}`);
+  }
+  return path.join(app.dataContainer, 'Library', 'Preferences', `${bundleId}.plist`);
+}
+
+/**
+ * An app's defaults, empty when it hasn't written any
+ */
+export async function readUserDefaults(udid: string, bundleId: string): Promise<UserDefaults> {
+  const plistPath = await getPreferencesPath(udid, bundleId);
+  if (!fs.existsSync(plistPath)) {
+    return { bundleId, path: plistPath, values: {} };
+  }
+  const plist = readPlist(plistPath);
+  if (!isDictionary(plist)) {
+    throw new PlasmaError('internal', `${plistPath} is not a dictionary`);
+  }
+  return { bundleId, path: plistPath, values: plistToJson(plist) as Record<string, DefaultsValue> };
+}
+
+/**
+ * Set defaults keys, removing the ones set to null, then relaunch the app
+ */
+export async function writeUserDefaults(
+  udid: string,
+  bundleId: string,
+  changes: Record<string, unknown>,
+  relaunch = true
+): Promise<UserDefaults> {
+  const entries = Object.entries(changes);
+  if (entries.length === 0) {
+    throw new PlasmaError('invalid_request', 'No defaults to write');
+  }
+  // Validate every value before changing anything
+  const writes = entries.map(([key, value]) => ({ key, xml: value === null ? null : toPlistXml(value) }));
+  await getPreferencesPath(udid, bundleId);
+
+  for (const { key, xml } of writes) {
+    const args = xml === null ? ['delete', bundleId, key] : ['write', bundleId, key, xml];
+    try {
+      await runCommand('xcrun', ['simctl', 'spawn', udid, 'defaults', ...args], SIMCTL_COMMAND);
+    } catch (err) {
+      // Deleting a key that isn't set
+      if (!(xml === null && err instanceof PlasmaError && /does not exist/.test(err.message))) {
+        throw err;
+      }
+    }
+  }
+
+  if (relaunch) {
+    await launchInstalledApp(udid, bundleId);
+  }
+  return readUserDefaults(udid, bundleId);
+}
@@ -0,0 +1,231 @@
+import * as crypto from 'crypto';
+import * as fs from 'fs';
+import * as path from 'path';
+import { PlasmaError } from './errors';
+import { getDataSubdir } from './paths';
+import { decodePng, encodePng, RgbaImage } from './png';
+import { listSimulators, takeScreenshot } from './simulator';
+
+/**
+ * Visual regression checks: compare a simulator screenshot against a stored
+ * baseline for the same project, device, and screen name. Pixels are
+ * compared with a perceptual (YIQ) color distance so antialiasing noise
+ * stays under the threshold, and mask regions (clocks, carets, ads) are
+ * ignored entirely.
+ */
+
+/** Region to ignore, as fractions (0-1) of the screenshot size */
+export interface MaskRegion {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+export interface CompareOptions {
+  /** Per-pixel color distance (0-1) above which a pixel counts as different */
+  threshold?: number;
+  /** Fraction of differing pixels (0-1) still considered a pass */
+  tolerance?: number;
+  masks?: MaskRegion[];
+}
+
+export interface ImageComparison {
+  passed: boolean;
+  diffPixels: number;
+  /** Differing pixels over compared (unmasked) pixels */
+  diffRatio: number;
+  /** Baseline faded to gray with differing pixels in red and masks in blue */
+  diff: RgbaImage;
+  /** Set when the images can't be compared pixel by pixel */
+  message?: string;
+}
+
+export type VisualCheckStatus = 'passed' | 'failed' | 'baseline_created' | 'baseline_updated';
+
+export interface VisualCheckResult {
+  status: VisualCheckStatus;
+  passed: boolean;
+  diffPixels: number;
+  diffRatio: number;
+  message?: string;
+  baselinePath: string;
+  actualPath?: string;
+  diffPath?: string;
+  /** Base64 PNG of the diff image, when a comparison was made */
+  diffImage?: string;
+}
+
+export interface VisualCheckRequest extends CompareOptions {
+  udid: string;
+  /** Screen name the baseline is stored under */
+  name: string;
+  /** Project the baseline belongs to; baselines without one are shared */
+  project?: string;
+  /** Replace the baseline with the current screen instead of comparing */
+  updateBaseline?: boolean;
+}
+
+const DEFAULT_THRESHOLD = 0.1;
+// Largest possible YIQ distance between two colors
+const MAX_YIQ_DELTA = 35215;
+
+function slug(value: string): string {
+  return value.replace(/[^\w.-]+/g, '_').replace(/^_+|_+$/g, '') || 'default';
+}
+
+/**
+ * Squared YIQ distance between two pixels blended over white, as in pixelmatch
+ */
+function colorDelta(a: Buffer, ai: number, b: Buffer, bi: number): number {
+  const blend = (value: number, alpha: number) => 255 + (value - 255) * alpha;
+  const aAlpha = a[ai + 3] / 255;
+  const bAlpha = b[bi + 3] / 255;
+  const r1 = blend(a[ai], aAlpha);
+  const g1 = blend(a[ai + 1], aAlpha);
+  const b1 = blend(a[ai + 2], aAlpha);
+  const r2 = blend(b[bi], bAlpha);
+  const g2 = blend(b[bi + 1], bAlpha);
+  const b2 = blend(b[bi + 2], bAlpha);
+
+  const y = (r1 - r2) * 0.29889531 + (g1 - g2) * 0.58662247 + (b1 - b2) * 0.11448223;
+  const i = (r1 - r2) * 0.59597799 - (g1 - g2) * 0.2741761 - (b1 - b2) * 0.32180189;
+  const q = (r1 - r2) * 0.21147017 - (g1 - g2) * 0.52261711 + (b1 - b2) * 0.31114694;
+  return 0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q;
+}
+
+function isMasked(masks: MaskRegion[], x: number, y: number, width: number, height: number): boolean {
+  const fx = x / width;
+  const fy = y / height;
+  return masks.some((mask) => fx >= mask.x && fx < mask.x + mask.width && fy >= mask.y && fy < mask.y + mask.height);
+}
+
+/**
+ * Compare two images of the same size pixel by pixel
+ */
+export function compareImages(actual: RgbaImage, baseline: RgbaImage, options: CompareOptions = {}): ImageComparison {
+  const threshold = options.threshold ?? DEFAULT_THRESHOLD;
+  const tolerance = options.tolerance ?? 0;
+  const masks = options.masks ?? [];
+
+  if (actual.width !== baseline.width || actual.height !== baseline.height) {
+    return {
+      passed: false,
+      diffPixels: actual.width * actual.height,
+      diffRatio: 1,
+      diff: actual,
+      message: `Screenshot is ${actual.width}x${actual.height} but the baseline is ${baseline.width}x${baseline.height}`,
+    };
+  }
+
+  const { width, height } = actual;
+  const maxDelta = MAX_YIQ_DELTA * threshold * threshold;
+  const diff = Buffer.alloc(width * height * 4);
+  let diffPixels = 0;
+  let comparedPixels = 0;
+
+  for (let y = 0; y < height; y++) {
+    for (let x = 0; x < width; x++) {
+      const index = (y * width + x) * 4;
+      let color: [number, number, number];
+      if (isMasked(masks, x, y, width, height)) {
+        color = [120, 160, 255];
+      } else {
+        comparedPixels++;
+        if (colorDelta(actual.data, index, baseline.data, index) > maxDelta) {
+          diffPixels++;
+          color = [255, 0, 0];
+        } else {
+          // Faded grayscale of the baseline for context
+          const gray = 0.299 * baseline.data[index] + 0.587 * baseline.data[index + 1] + 0.114 * baseline.data[index + 2];
+          const faded = 255 + (gray - 255) * 0.1;
+          color = [faded, faded, faded];
+        }
+      }
+      diff[index] = color[0];
+      diff[index + 1] = color[1];
+      diff[index + 2] = color[2];
+      diff[index + 3] = 255;
+    }
+  }
+
+  const diffRatio = comparedPixels === 0 ? 0 : diffPixels / comparedPixels;
+  return { passed: diffRatio <= tolerance, diffPixels, diffRatio, diff: { width, height, data: diff } };
+}
+
+/**
+ * Validate mask regions coming from a request or script
+ */
+export function parseMasks(value: unknown): MaskRegion[] {
+  if (value === undefined) {
+    return [];
+  }
+  const isFraction = (n: unknown) => typeof n === 'number' && n >= 0 && n <= 1;
+  if (
+    !Array.isArray(value) ||
+    !value.every((mask) => mask && isFraction(mask.x) && isFraction(mask.y) && isFraction(mask.width) && isFraction(mask.height))
+  ) {
+    throw new PlasmaError('invalid_request', 'masks must be a list of { x, y, width, height } fractions between 0 and 1');
+  }
+  return value as MaskRegion[];
+}
+
+/**
+ * Directory holding baselines for a project and device
+ */
+async function baselineDir(udid: string, project?: string): Promise<string> {
+  const simulator = (await listSimulators()).find((s) => s.udid === udid);
+  if (!simulator) {
+    throw new PlasmaError('simulator_not_found', `Simulator ${udid} not found`);
+  }
+
+  // Baselines are keyed by device model and runtime rather than UDID so they
+  // can be shared across machines
+  const projectKey = project
+    ? `${slug(path.basename(project))}-${crypto.createHash('sha1').update(project).digest('hex').slice(0, 8)}`
+    : 'default';
+  const runtime = simulator.runtime.split('.').pop() ?? simulator.runtime;
+  return getDataSubdir('baselines', projectKey, slug(`${simulator.name}-${runtime}`));
+}
+
+/**
+ * Capture the screen and compare it against the stored baseline, creating
+ * the baseline on first use
+ */
+export async function checkScreenshot(request: VisualCheckRequest): Promise<VisualCheckResult> {
+  if (!request.name) {
+    throw new PlasmaError('invalid_request', 'A screenshot name is required');
+  }
+
+  const dir = await baselineDir(request.udid, request.project);
+  const name = slug(request.name);
+  const baselinePath = path.join(dir, `${name}.png`);
+  const screenshot = await takeScreenshot(request.udid);
+
+  if (request.updateBaseline || !fs.existsSync(baselinePath)) {
+    const status = fs.existsSync(baselinePath) ? 'baseline_updated' : 'baseline_created';
+    fs.writeFileSync(baselinePath, screenshot);
+    return { status, passed: true, diffPixels: 0, diffRatio: 0, baselinePath };
+  }
+
+  const comparison = compareImages(decodePng(screenshot), decodePng(fs.readFileSync(baselinePath)), request);
+  const diffImage = encodePng(comparison.diff);
+
+  // Keep the latest actual and diff next to the baseline for inspection
+  const actualPath = path.join(dir, `${name}.actual.png`);
+  const diffPath = path.join(dir, `${name}.diff.png`);
+  fs.writeFileSync(actualPath, screenshot);
+  fs.writeFileSync(diffPath, diffImage);
+
+  return {
+    status: comparison.passed ? 'passed' : 'failed',
+    passed: comparison.passed,
+    diffPixels: comparison.diffPixels,
+    diffRatio: comparison.diffRatio,
+    message: comparison.message,
+    baselinePath,
+    actualPath,
+    diffPath,
+    diffImage: diffImage.toString('base64'),
+  };
+}
@@ -0,0 +1,158 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import { PlasmaError } from './errors';
+import { runCommand, SIMCTL_COMMAND } from './exec';
+import type { LaunchOptions } from './manifest';
+import { readAppBundleInfo } from './plist';
+import { withRetry } from './retry';
+import { bootSimulator, installAndLaunch, listSimulators, Simulator } from './simulator';
+
+/**
+ * Phone and watch simulator pairs for watchOS apps. A pair is created with
+ * `simctl pair`, booted together, and gets the iOS app installed on the
+ * phone and its embedded watch app on the watch.
+ */
+
+export interface PairedDevice {
+  udid: string;
+  name: string;
+  state: string;
+}
+
+export interface SimulatorPair {
+  id: string;
+  watch: PairedDevice;
+  phone: PairedDevice;
+  /** Only one pair per phone is active, the one the phone talks to */
+  active: boolean;
+  connected: boolean;
+}
+
+export interface WatchInstallResult {
+  message: string;
+  phoneBundleId: string;
+  watchBundleId: string;
+}
+
+function toPairedDevice(value: any): PairedDevice {
+  return { udid: value?.udid ?? '', name: value?.name ?? '', state: value?.state ?? '' };
+}
+
+/**
+ * Parse `simctl list pairs -j`, where each pair's state reads like "(active, connected)"
+ */
+export function parsePairs(output: string): SimulatorPair[] {
+  const json = JSON.parse(output);
+  return Object.entries((json.pairs ?? {}) as Record<string, any>)
+    .filter(([, pair]) => !String(pair.state).includes('unavailable'))
+    .map(([id, pair]) => {
+      const flags = String(pair.state ?? '')
+        .replace(/[()]/g, '')
+        .split(',')
+        .map((flag) => flag.trim());
+      return {
+        id,
+        watch: toPairedDevice(pair.watch),
+        phone: toPairedDevice(pair.phone),
+        active: flags.includes('active'),
+        connected: flags.includes('connected'),
+      };
+    });
+}
+
+export async function listSimulatorPairs(): Promise<SimulatorPair[]> {
+  return parsePairs(await runCommand('xcrun', ['simctl', 'list', 'pairs', '-j'], SIMCTL_COMMAND));
+}
+
+async function getPair(pairId: string): Promise<SimulatorPair> {
+  const pair = (await listSimulatorPairs()).find((candidate) => candidate.id.toLowerCase() === pairId.toLowerCase());
+  if (!pair) {
+    throw new PlasmaError('simulator_not_found', `No simulator pair ${pairId}`);
+  }
+  return pair;
+}
+
+function requireRuntime(simulators: Simulator[], udid: string, runtime: 'iOS' | 'watchOS'): Simulator {
+  const simulator = simulators.find((candidate) => candidate.udid.toLowerCase() === udid.toLowerCase());
+  if (!simulator) {
+    throw new PlasmaError('simulator_not_found', `No simulator ${udid}`);
+  }
+  // Runtime identifiers look like com.apple.CoreSimulator.SimRuntime.watchOS-11-2
+  if (!simulator.runtime.includes(`SimRuntime.${runtime}-`)) {
+    throw new PlasmaError('invalid_request', `${simulator.name} is not a ${runtime} simulator`);
+  }
+  return simulator;
+}
+
+/**
+ * Pair a watch simulator with a phone simulator
+ */
+export async function createSimulatorPair(watchUdid: string, phoneUdid: string): Promise<SimulatorPair> {
+  const simulators = await listSimulators();
+  const watch = requireRuntime(simulators, watchUdid, 'watchOS');
+  const phone = requireRuntime(simulators, phoneUdid, 'iOS');
+  const pairId = (await runCommand('xcrun', ['simctl', 'pair', watch.udid, phone.udid], SIMCTL_COMMAND)).trim();
+  return getPair(pairId);
+}
+
+export async function deleteSimulatorPair(pairId: string): Promise<void> {
+  await runCommand('xcrun', ['simctl', 'unpair', (await getPair(pairId)).id], SIMCTL_COMMAND);
+}
+
+/**
+ * Make a pair the phone's active one and boot the phone, then the watch
+ */
+export async function bootSimulatorPair(pairId: string): Promise<SimulatorPair> {
+  const pair = await getPair(pairId);
+  if (!pair.active) {
+    await runCommand('xcrun', ['simctl', 'pair_activate', pair.id], SIMCTL_COMMAND);
+  }
+  await bootSimulator(pair.phone.udid);
+  await bootSimulator(pair.watch.udid);
+  return getPair(pair.id);
+}
+
+/**
+ * The watch app embedded in an iOS app's Watch folder
+ */
+export function findWatchApp(appPath: string): string | null {
+  const watchDir = path.join(appPath, 'Watch');
+  if (!fs.existsSync(watchDir)) {
+    return null;
+  }
+  const watchApp = fs.readdirSync(watchDir).find((name) => name.endsWith('.app'));
+  return watchApp ? path.join(watchDir, watchApp) : null;
+}
+
+/**
+ * Boot a pair, install and launch the iOS app on the phone, and install
+ * its companion watch app on the watch, launching it when asked to
+ */
+export async function installOnPair(
+  pairId: string,
+  appPath: string,
+  options: { launchWatchApp?: boolean; launchOptions?: LaunchOptions } = {}
+): Promise<WatchInstallResult> {
+  const watchApp = findWatchApp(appPath);
+  if (!watchApp) {
+    throw new PlasmaError('invalid_request', `${path.basename(appPath)} has no embedded watch app`);
+  }
+
+  const pair = await bootSimulatorPair(pairId);
+  const phone = await installAndLaunch(pair.phone.udid, appPath, undefined, options.launchOptions);
+  await withRetry('Install', () =>
+    runCommand('xcrun', ['simctl', 'install', pair.watch.udid, watchApp], SIMCTL_COMMAND)
+  );
+  const watchBundleId = readAppBundleInfo(watchApp).bundleId;
+  if (options.launchWatchApp) {
+    await withRetry('Launch', () =>
+      runCommand('xcrun', ['simctl', 'launch', pair.watch.udid, watchBundleId], SIMCTL_COMMAND)
+    );
+  }
+
+  return {
+    message: `App ${phone.bundleId} installed on ${pair.phone.name} and ${watchBundleId} on ${pair.watch.name}`,
+    phoneBundleId: phone.bundleId,
+    watchBundleId,
+  };
+}
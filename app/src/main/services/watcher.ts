@@ -0,0 +1,168 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import { buildScheme, getLaunchableProducts } from './xcode';
+import { installAndLaunch } from './simulator';
+import { runHooks } from './hooks';
+import { getLaunchOptions } from './manifest';
+
+export type WatchState = 'watching' | 'building' | 'launching' | 'error' | 'stopped';
+
+export interface WatchStatus {
+  projectPath: string;
+  state: WatchState;
+  message?: string;
+  changedFiles?: string[];
+  timestamp: string;
+}
+
+export interface WatchOptions {
+  projectPath: string;
+  scheme: string;
+  configuration?: string;
+  udids: string[];
+  derivedDataPath?: string;
+  debounceMs?: number;
+}
+
+// Directories whose changes never need a rebuild
+const IGNORED_SEGMENTS = new Set([
+  '.git',
+  'build',
+  'DerivedData',
+  '.build',
+  'Pods',
+  'node_modules',
+  'xcuserdata',
+  '.swiftpm',
+]);
+
+/**
+ * Whether a changed file should trigger a rebuild
+ */
+export function isWatchedChange(relativePath: string): boolean {
+  const segments = relativePath.split(path.sep);
+  if (segments.some((segment) => IGNORED_SEGMENTS.has(segment))) {
+    return false;
+  }
+  const fileName = segments[segments.length - 1];
+  // Editor swap files and macOS metadata
+  return !(fileName.startsWith('.') || fileName.endsWith('~') || fileName.endsWith('.swp'));
+}
+
+/**
+ * Watch a project's sources and rebuild, reinstall, and relaunch on change.
+ * Returns a function that stops watching.
+ */
+export function watchProject(
+  options: WatchOptions,
+  onStatus: (status: WatchStatus) => void
+): () => void {
+  // Sources live next to the .xcodeproj/.xcworkspace bundle
+  const rootDir = /\.(xcodeproj|xcworkspace)$/.test(options.projectPath)
+    ? path.dirname(options.projectPath)
+    : options.projectPath;
+  const debounceMs = options.debounceMs ?? 300;
+
+  let pendingFiles = new Set<string>();
+  let debounceTimer: ReturnType<typeof setTimeout> | null = null;
+  let isRunning = false;
+  let stopped = false;
+
+  const report = (state: WatchState, extra: Partial<WatchStatus> = {}) => {
+    onStatus({
+      projectPath: options.projectPath,
+      state,
+      timestamp: new Date().toISOString(),
+      ...extra,
+    });
+  };
+
+  const rebuild = async () => {
+    if (isRunning || stopped || pendingFiles.size === 0) return;
+
+    const changedFiles = [...pendingFiles];
+    pendingFiles = new Set();
+    isRunning = true;
+
+    try {
+      report('building', { changedFiles, message: `Rebuilding ${options.scheme}` });
+      const result = await buildScheme(
+        options.projectPath,
+        options.scheme,
+        options.configuration,
+        options.derivedDataPath
+      );
+
+      if (stopped) return;
+      if (!result.success) {
+        report('error', { changedFiles, message: result.error || 'Build failed' });
+        return;
+      }
+
+      let products = result.products;
+      if (products.length === 0 && result.buildDir) {
+        products = await getLaunchableProducts(result.buildDir);
+      }
+      if (products.length === 0) {
+        report('error', { changedFiles, message: 'No build products found' });
+        return;
+      }
+
+      report('launching', { changedFiles });
+      await Promise.all(
+        options.udids.map(async (udid) => {
+          await installAndLaunch(udid, products[0].path, undefined, getLaunchOptions(options.projectPath));
+          await runHooks(
+            'postInstall',
+            {
+              projectPath: options.projectPath,
+              scheme: options.scheme,
+              configuration: options.configuration,
+              udid,
+              appPath: products[0].path,
+            },
+            (line) => console.log(`[watcher] ${line}`)
+          );
+        })
+      );
+      report('watching', { changedFiles, message: 'Relaunched' });
+    } catch (err) {
+      report('error', { changedFiles, message: err instanceof Error ? err.message : String(err) });
+    } finally {
+      isRunning = false;
+      // Changes made during the build get their own rebuild
+      if (pendingFiles.size > 0 && !stopped) {
+        rebuild();
+      }
+    }
+  };
+
+  // Recursive watching is supported on macOS, which is the only platform that builds iOS apps
+  const watcher = fs.watch(rootDir, { recursive: true }, (_event, fileName) => {
+    if (!fileName || !isWatchedChange(fileName.toString())) return;
+
+    pendingFiles.add(fileName.toString());
+    if (debounceTimer) {
+      clearTimeout(debounceTimer);
+    }
+    debounceTimer = setTimeout(() => {
+      debounceTimer = null;
+      rebuild();
+    }, debounceMs);
+  });
+
+  watcher.on('error', (err) => {
+    report('error', { message: `Watcher failed: ${err.message}` });
+  });
+
+  report('watching', { message: `Watching ${rootDir}` });
+
+  return () => {
+    stopped = true;
+    if (debounceTimer) {
+      clearTimeout(debounceTimer);
+    }
+    watcher.close();
+    report('stopped');
+  };
+}
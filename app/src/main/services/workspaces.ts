@@ -0,0 +1,285 @@
+import * as crypto from 'crypto';
+import { hasAndroidSession } from './android-stream';
+import {
+  deleteWorkspace,
+  deleteWorkspaceProject,
+  getProjectSettings,
+  getUnifiedProjectById,
+  getWorkspace,
+  getWorkspaceProjects,
+  getWorkspaces,
+  Platform,
+  saveWorkspace,
+  saveWorkspaceProject,
+  UnifiedProjectRecord,
+  WorkspaceProjectRecord,
+  WorkspaceRecord,
+} from './database';
+import { PlasmaError } from './errors';
+import { hasSession } from './simulator';
+
+/**
+ * Workspaces are sets of projects open at the same time, like tabs. Each
+ * project in a workspace keeps its own scheme, configuration, and device,
+ * so switching tabs doesn't change what another tab builds or streams. A
+ * project opened in a workspace starts from the selections saved for it.
+ */
+
+export interface WorkspaceProject {
+  projectId: number;
+  name: string;
+  xcodePath: string | null;
+  androidPath: string | null;
+  /** Which side of the project the tab builds and runs */
+  platform: Platform;
+  scheme: string | null;
+  configuration: string | null;
+  /** Simulator UDID or Android serial */
+  deviceId: string | null;
+  /** Whether the device has a stream session running */
+  streaming: boolean;
+}
+
+export interface Workspace {
+  id: string;
+  name: string;
+  projects: WorkspaceProject[];
+  /** The project in front, or null when none is open */
+  activeProjectId: number | null;
+  createdAt: string;
+  updatedAt: string;
+}
+
+export interface CreateWorkspaceRequest {
+  name: string;
+  projectIds?: number[];
+}
+
+export interface UpdateWorkspaceRequest {
+  name?: string;
+  activeProjectId?: number | null;
+}
+
+/** Selections of a project in a workspace; null clears one */
+export interface WorkspaceProjectSelection {
+  platform?: Platform;
+  scheme?: string | null;
+  configuration?: string | null;
+  deviceId?: string | null;
+}
+
+const PLATFORMS: Platform[] = ['ios', 'android'];
+
+function requireWorkspace(id: string): WorkspaceRecord {
+  const record = getWorkspace(id);
+  if (!record) {
+    throw new PlasmaError('not_found', `Workspace ${id} not found`);
+  }
+  return record;
+}
+
+function requireProject(projectId: number): UnifiedProjectRecord {
+  const project = Number.isInteger(projectId) ? getUnifiedProjectById(projectId) : null;
+  if (!project) {
+    throw new PlasmaError('project_not_found', `Project ${projectId} not found`);
+  }
+  return project;
+}
+
+function toWorkspaceProject(record: WorkspaceProjectRecord): WorkspaceProject | null {
+  const project = getUnifiedProjectById(record.project_id);
+  if (!project) return null;
+  const streaming = record.device_id
+    ? record.platform === 'android'
+      ? hasAndroidSession(record.device_id)
+      : hasSession(record.device_id)
+    : false;
+  return {
+    projectId: record.project_id,
+    name: project.name,
+    xcodePath: project.xcode_path,
+    androidPath: project.android_path,
+    platform: record.platform,
+    scheme: record.scheme,
+    configuration: record.configuration,
+    deviceId: record.device_id,
+    streaming,
+  };
+}
+
+function toWorkspace(record: WorkspaceRecord): Workspace {
+  return {
+    id: record.id,
+    name: record.name,
+    projects: getWorkspaceProjects(record.id)
+      .map(toWorkspaceProject)
+      .filter((project): project is WorkspaceProject => project !== null),
+    activeProjectId: record.active_project_id,
+    createdAt: record.created_at,
+    updatedAt: record.updated_at,
+  };
+}
+
+function touch(record: WorkspaceRecord, changes: Partial<WorkspaceRecord> = {}): void {
+  saveWorkspace({ ...record, ...changes, updated_at: new Date().toISOString() });
+}
+
+function validateSelection(selection: WorkspaceProjectSelection): void {
+  if (selection.platform !== undefined && !PLATFORMS.includes(selection.platform)) {
+    throw new PlasmaError('invalid_request', `platform must be one of ${PLATFORMS.join(', ')}`);
+  }
+  for (const key of ['scheme', 'configuration', 'deviceId'] as const) {
+    const value = selection[key];
+    if (value !== undefined && value !== null && typeof value !== 'string') {
+      throw new PlasmaError('invalid_request', `${key} must be a string or null`);
+    }
+  }
+}
+
+function validateName(name: unknown): string {
+  if (typeof name !== 'string' || !name.trim()) {
+    throw new PlasmaError('invalid_request', 'name is required');
+  }
+  return name.trim();
+}
+
+/**
+ * Workspaces, most recently changed first
+ */
+export function listWorkspaces(): Workspace[] {
+  return getWorkspaces().map(toWorkspace);
+}
+
+export function getWorkspaceById(id: string): Workspace {
+  return toWorkspace(requireWorkspace(id));
+}
+
+/**
+ * Create a workspace, opening the given projects in it
+ */
+export function createWorkspace(request: CreateWorkspaceRequest): Workspace {
+  const name = validateName(request.name);
+  const projectIds = request.projectIds ?? [];
+  if (!Array.isArray(projectIds)) {
+    throw new PlasmaError('invalid_request', 'projectIds must be a list');
+  }
+  projectIds.forEach(requireProject);
+
+  const now = new Date().toISOString();
+  const record: WorkspaceRecord = {
+    id: crypto.randomUUID(),
+    name,
+    active_project_id: null,
+    created_at: now,
+    updated_at: now,
+  };
+  saveWorkspace(record);
+  for (const projectId of new Set(projectIds)) {
+    openWorkspaceProject(record.id, projectId);
+  }
+  return getWorkspaceById(record.id);
+}
+
+/**
+ * Rename a workspace or bring one of its projects to the front
+ */
+export function updateWorkspace(id: string, request: UpdateWorkspaceRequest): Workspace {
+  const record = requireWorkspace(id);
+  const changes: Partial<WorkspaceRecord> = {};
+  if (request.name !== undefined) {
+    changes.name = validateName(request.name);
+  }
+  if (request.activeProjectId !== undefined) {
+    if (
+      request.activeProjectId !== null &&
+      !getWorkspaceProjects(id).some((project) => project.project_id === request.activeProjectId)
+    ) {
+      throw new PlasmaError('invalid_request', `Project ${request.activeProjectId} is not open in this workspace`);
+    }
+    changes.active_project_id = request.activeProjectId;
+  }
+  touch(record, changes);
+  return getWorkspaceById(id);
+}
+
+export function removeWorkspace(id: string): void {
+  if (!deleteWorkspace(id)) {
+    throw new PlasmaError('not_found', `Workspace ${id} not found`);
+  }
+}
+
+/**
+ * Open a project in a workspace and bring it to the front. Selections not
+ * given come from the project's saved settings.
+ */
+export function openWorkspaceProject(
+  workspaceId: string,
+  projectId: number,
+  selection: WorkspaceProjectSelection = {}
+): Workspace {
+  const record = requireWorkspace(workspaceId);
+  const project = requireProject(projectId);
+  validateSelection(selection);
+
+  const open = getWorkspaceProjects(workspaceId);
+  if (open.some((entry) => entry.project_id === projectId)) {
+    throw new PlasmaError('invalid_request', `Project ${projectId} is already open in this workspace`);
+  }
+  const saved = getProjectSettings(projectId);
+  saveWorkspaceProject({
+    workspace_id: workspaceId,
+    project_id: projectId,
+    position: open.length === 0 ? 0 : open[open.length - 1].position + 1,
+    platform: selection.platform ?? (project.xcode_path ? 'ios' : 'android'),
+    scheme: selection.scheme !== undefined ? selection.scheme : saved.scheme ?? null,
+    configuration: selection.configuration !== undefined ? selection.configuration : saved.configuration ?? null,
+    device_id: selection.deviceId !== undefined ? selection.deviceId : saved.simulatorUdid ?? null,
+  });
+  touch(record, { active_project_id: projectId });
+  return getWorkspaceById(workspaceId);
+}
+
+/**
+ * Change the scheme, configuration, device, or platform of a project in a workspace
+ */
+export function updateWorkspaceProject(
+  workspaceId: string,
+  projectId: number,
+  selection: WorkspaceProjectSelection
+): Workspace {
+  const record = requireWorkspace(workspaceId);
+  const entry = getWorkspaceProjects(workspaceId).find((project) => project.project_id === projectId);
+  if (!entry) {
+    throw new PlasmaError('not_found', `Project ${projectId} is not open in this workspace`);
+  }
+  validateSelection(selection);
+  saveWorkspaceProject({
+    ...entry,
+    platform: selection.platform ?? entry.platform,
+    scheme: selection.scheme !== undefined ? selection.scheme : entry.scheme,
+    configuration: selection.configuration !== undefined ? selection.configuration : entry.configuration,
+    device_id: selection.deviceId !== undefined ? selection.deviceId : entry.device_id,
+  });
+  touch(record);
+  return getWorkspaceById(workspaceId);
+}
+
+/**
+ * Close a project in a workspace. When it was in front, the project next
+ * to it takes its place.
+ */
+export function closeWorkspaceProject(workspaceId: string, projectId: number): Workspace {
+  const record = requireWorkspace(workspaceId);
+  const open = getWorkspaceProjects(workspaceId);
+  const index = open.findIndex((project) => project.project_id === projectId);
+  if (index === -1 || !deleteWorkspaceProject(workspaceId, projectId)) {
+    throw new PlasmaError('not_found', `Project ${projectId} is not open in this workspace`);
+  }
+  let activeProjectId = record.active_project_id;
+  if (activeProjectId === projectId) {
+    const next = open[index + 1] ?? open[index - 1];
+    activeProjectId = next?.project_id ?? null;
+  }
+  touch(record, { active_project_id: activeProjectId });
+  return getWorkspaceById(workspaceId);
+}
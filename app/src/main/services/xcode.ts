@@ -1,62 +1,276 @@
 import { spawn, ChildProcess } from 'child_process';
+import * as crypto from 'crypto';
 import { EventEmitter } from 'events';
 import * as fs from 'fs';
+import * as os from 'os';
 import * as path from 'path';
 import { detectProject } from './projects';
-import { registerProcess } from './process-manager';
+import { registerProcess, signalProcess } from './process-manager';
+import { enforceTimeout } from './timeouts';
+import { loadHooks, runHooks } from './hooks';
+import { AppBundleInfo, readAppBundleInfo } from './plist';
+import { ErrorCode, PlasmaError, toPlasmaError } from './errors';
+import { recordBuildSymbols } from './symbols';
+import { DiagnosticCollector, recordBuild } from './build-records';
+import { captureBuildEnvironment } from './build-environment';
+import { readGitInfo } from './git';
+import { BuildLog } from './build-logs';
+import { BuildCache, getBuildCacheKey } from './build-cache';
+import { acquireBuildSlot } from './build-queue';
+import { BuildOutputFormatter } from './build-format';
+import { LineSplitter } from './lines';
+import { archMismatchHint, BuildArch, BUILD_ARCHS, detectArchMismatch } from './architectures';
+import { trackBuild } from './active-builds';
+import type { BuildEvent, BuildProduct } from '../../shared/ipc-types';
 
 export type XcodeProjectType = 'project' | 'workspace';
 
+/** Where a scheme is built to run: a simulator, or this Mac natively or through Catalyst */
+export type BuildPlatform = 'ios-simulator' | 'macos' | 'mac-catalyst';
+
+export const BUILD_PLATFORMS: BuildPlatform[] = ['ios-simulator', 'macos', 'mac-catalyst'];
+
+/**
+ * How Swift packages are resolved before building: as Xcode does, pinned to
+ * the versions in Package.resolved, or not at all (the checkouts must exist)
+ */
+export type PackageResolution = 'automatic' | 'resolved-file' | 'disabled';
+
+export const PACKAGE_RESOLUTIONS: PackageResolution[] = ['automatic', 'resolved-file', 'disabled'];
+
+export type PackagePhase = 'resolving' | 'fetching' | 'checking-out' | 'resolved';
+
+/**
+ * Where a scheme is defined: committed in xcshareddata, only in the current
+ * user's xcuserdata, or created on the fly by Xcode (like package schemes)
+ */
+export type SchemeSharing = 'shared' | 'user' | 'automatic';
+
 export interface XcodeProject {
   path: string;
   projectType: XcodeProjectType;
   schemes: string[];
+  schemeSharing: Record<string, SchemeSharing>;
   targets: string[];
   configurations: string[];
+  /** For workspaces, the projects in it */
+  projects?: WorkspaceMember[];
 }
 
-export interface BuildProduct {
+export interface WorkspaceMember {
   name: string;
   path: string;
+  targets: string[];
+  /** The workspace's schemes that build this project */
+  schemes: string[];
 }
 
-export interface BuildEvent {
-  type: 'started' | 'output' | 'completed' | 'error';
-  scheme?: string;
-  projectPath?: string;
-  line?: string;
-  success?: boolean;
-  buildDir?: string;
-  products?: BuildProduct[];
-  message?: string;
+export type { BuildEvent, BuildProduct };
+
+// xcodebuild -list results by project path, reused while the files it reads are unchanged
+const discoveryCache = new Map<string, { fingerprint: string; project: XcodeProject }>();
+// Build directories from -showBuildSettings by project, scheme, configuration, platform,
+// and derived data path, so later builds start xcodebuild without asking again
+const buildSettingsCache = new Map<string, { fingerprint: string; buildDir: string; isWorkspace: boolean }>();
+
+function listFiles(dir: string): string[] {
+  return fs.existsSync(dir) ? fs.readdirSync(dir).map((name) => path.join(dir, name)) : [];
+}
+
+function sharedSchemeFiles(container: string): string[] {
+  return listFiles(path.join(container, 'xcshareddata', 'xcschemes'));
+}
+
+function userSchemeFiles(container: string): string[] {
+  return listFiles(path.join(container, 'xcuserdata')).flatMap((userDir) => listFiles(path.join(userDir, 'xcschemes')));
+}
+
+/**
+ * Projects a workspace references. `group:` locations are relative to the
+ * enclosing group, and `container:` ones to the workspace's folder.
+ */
+function workspaceProjects(workspacePath: string): string[] {
+  const contentsPath = path.join(workspacePath, 'contents.xcworkspacedata');
+  if (!fs.existsSync(contentsPath)) {
+    return [];
+  }
+  const contents = fs.readFileSync(contentsPath, 'utf-8');
+  const root = path.dirname(workspacePath);
+  const groups = [root];
+  const projects: string[] = [];
+  for (const [tag] of contents.matchAll(/<\/?(Group|FileRef)\b[^>]*>/g)) {
+    if (tag.startsWith('</')) {
+      groups.pop();
+      continue;
+    }
+    const base = groups[groups.length - 1] ?? root;
+    const match = tag.match(/location\s*=\s*"(\w+):([^"]*)"/);
+    let resolved = base;
+    if (match?.[1] === 'absolute') {
+      resolved = match[2];
+    } else if (match?.[1] === 'container') {
+      resolved = path.join(root, match[2]);
+    } else if (match?.[1] === 'group') {
+      resolved = path.join(base, match[2]);
+    }
+    if (tag.startsWith('<Group') && !tag.endsWith('/>')) {
+      groups.push(resolved);
+    } else if (tag.startsWith('<FileRef') && resolved.endsWith('.xcodeproj')) {
+      projects.push(resolved);
+    }
+  }
+  return projects;
+}
+
+/**
+ * Names of the targets in a project.pbxproj
+ */
+export function parseProjectTargets(pbxproj: string): string[] {
+  const targets: string[] = [];
+  const sections = pbxproj.matchAll(
+    /\/\* Begin PBX(?:Native|Aggregate|Legacy)Target section \*\/([\s\S]*?)\/\* End PBX\w+Target section \*\//g
+  );
+  for (const [, section] of sections) {
+    for (const [, name] of section.matchAll(/^\s*name = (.+);$/gm)) {
+      targets.push(name.replace(/^"(.*)"$/, '$1'));
+    }
+  }
+  return targets;
+}
+
+/**
+ * Project a workspace-level scheme builds, from its first buildable reference
+ */
+function schemeContainer(schemeFile: string, workspacePath: string): string | null {
+  const location = fs.readFileSync(schemeFile, 'utf-8').match(/ReferencedContainer\s*=\s*"container:([^"]+)"/)?.[1];
+  return location ? path.join(path.dirname(workspacePath), location) : null;
+}
+
+/**
+ * The projects of a workspace with their targets, and the schemes each one
+ * holds. Workspace-level schemes go to the project they build, and schemes
+ * Xcode creates to the project with a target of the same name.
+ */
+function readWorkspaceMembers(workspacePath: string, schemes: string[]): WorkspaceMember[] {
+  const members: WorkspaceMember[] = workspaceProjects(workspacePath)
+    .filter((projectPath) => fs.existsSync(path.join(projectPath, 'project.pbxproj')))
+    .map((projectPath) => ({
+      name: path.basename(projectPath, '.xcodeproj'),
+      path: projectPath,
+      targets: parseProjectTargets(fs.readFileSync(path.join(projectPath, 'project.pbxproj'), 'utf-8')),
+      schemes: [],
+    }));
+
+  const owners = new Map<string, string>();
+  const schemeName = (file: string) => path.basename(file, '.xcscheme');
+  for (const member of members) {
+    for (const file of [...sharedSchemeFiles(member.path), ...userSchemeFiles(member.path)]) {
+      owners.set(schemeName(file), member.path);
+    }
+  }
+  for (const file of [...sharedSchemeFiles(workspacePath), ...userSchemeFiles(workspacePath)]) {
+    const container = file.endsWith('.xcscheme') ? schemeContainer(file, workspacePath) : null;
+    if (container) {
+      owners.set(schemeName(file), container);
+    }
+  }
+
+  for (const scheme of schemes) {
+    const owner =
+      members.find((member) => member.path === owners.get(scheme)) ??
+      members.find((member) => member.targets.includes(scheme));
+    owner?.schemes.push(scheme);
+  }
+  return members;
 }
 
 /**
- * Discover Xcode project details including schemes, targets, and configurations
+ * The project, or a workspace and the projects in it
  */
-export async function discoverProject(projectPath: string): Promise<XcodeProject> {
+function discoveryContainers(projectPath: string): string[] {
+  return projectPath.endsWith('.xcworkspace') ? [projectPath, ...workspaceProjects(projectPath)] : [projectPath];
+}
+
+/**
+ * Whether each scheme has a shared .xcscheme file, only a user one, or none.
+ * Schemes only in xcuserdata aren't in the repository, so CI and other
+ * machines can't build them.
+ */
+function readSchemeSharing(projectPath: string, schemes: string[]): Record<string, SchemeSharing> {
+  const containers = discoveryContainers(projectPath);
+  const names = (files: string[]) =>
+    new Set(files.filter((file) => file.endsWith('.xcscheme')).map((file) => path.basename(file, '.xcscheme')));
+  const shared = names(containers.flatMap(sharedSchemeFiles));
+  const user = names(containers.flatMap(userSchemeFiles));
+  return Object.fromEntries(
+    schemes.map((scheme): [string, SchemeSharing] => [
+      scheme,
+      shared.has(scheme) ? 'shared' : user.has(scheme) ? 'user' : 'automatic',
+    ])
+  );
+}
+
+/**
+ * Modification times of everything `xcodebuild -list` reads: the project
+ * files, the workspace's projects, and their scheme files
+ */
+function discoveryFingerprint(projectPath: string): string {
+  return discoveryContainers(projectPath)
+    .flatMap((container) => [
+      path.join(container, container.endsWith('.xcworkspace') ? 'contents.xcworkspacedata' : 'project.pbxproj'),
+      ...sharedSchemeFiles(container),
+      ...userSchemeFiles(container),
+    ])
+    .map((file) => `${file}:${fs.existsSync(file) ? fs.statSync(file).mtimeMs : 'missing'}`)
+    .join('\n');
+}
+
+/**
+ * Discover Xcode project details including schemes, targets, and configurations.
+ * Results are cached until the project or its schemes change on disk.
+ */
+export async function discoverProject(
+  projectPath: string,
+  options: { forceRefresh?: boolean } = {}
+): Promise<XcodeProject> {
   const project = detectProject(projectPath);
 
   if (!project) {
-    throw new Error('No Xcode project found at path');
+    throw new PlasmaError('project_not_found', 'No Xcode project found at path');
   }
 
   if (project.type !== 'xcode') {
-    throw new Error(`Not an Xcode project: ${project.type}`);
+    throw new PlasmaError('not_an_xcode_project', `Not an Xcode project: ${project.type}`);
   }
 
-  const isWorkspace = project.path.endsWith('.xcworkspace');
+  const fingerprint = discoveryFingerprint(project.path);
+  const cached = discoveryCache.get(project.path);
+  if (cached && cached.fingerprint === fingerprint && !options.forceRefresh) {
+    return cached.project;
+  }
+
+  const discovered = await listProject(project.path);
+  discoveryCache.set(project.path, { fingerprint, project: discovered });
+  return discovered;
+}
+
+/**
+ * Run `xcodebuild -list` for a project or workspace
+ */
+function listProject(projectPath: string): Promise<XcodeProject> {
+  const isWorkspace = projectPath.endsWith('.xcworkspace');
   const projectType: XcodeProjectType = isWorkspace ? 'workspace' : 'project';
 
   const args = isWorkspace
-    ? ['-workspace', project.path, '-list', '-json']
-    : ['-project', project.path, '-list', '-json'];
+    ? ['-workspace', projectPath, '-list', '-json']
+    : ['-project', projectPath, '-list', '-json'];
 
   return new Promise((resolve, reject) => {
     const proc = spawn('xcodebuild', args);
     registerProcess(proc);
     let stdout = '';
     let stderr = '';
+    enforceTimeout(proc, 'xcodebuild', reject);
 
     proc.stdout.on('data', (data) => {
       stdout += data.toString();
@@ -68,7 +282,7 @@ export async function discoverProject(projectPath: string): Promise<XcodeProject
 
     proc.on('close', (code) => {
       if (code !== 0) {
-        reject(new Error(`xcodebuild failed: ${stderr}`));
+        reject(new PlasmaError('xcodebuild_failed', `xcodebuild failed: ${stderr}`));
         return;
       }
 
@@ -77,19 +291,22 @@ export async function discoverProject(projectPath: string): Promise<XcodeProject
         const info = isWorkspace ? buildList.workspace : buildList.project;
 
         if (!info) {
-          reject(new Error('No project/workspace info in xcodebuild output'));
+          reject(new PlasmaError('xcodebuild_failed', 'No project/workspace info in xcodebuild output'));
           return;
         }
 
+        const schemes: string[] = info.schemes || [];
         resolve({
-          path: project.path,
+          path: projectPath,
           projectType,
-          schemes: info.schemes || [],
+          schemes,
+          schemeSharing: readSchemeSharing(projectPath, schemes),
           targets: info.targets || [],
           configurations: info.configurations || [],
+          projects: isWorkspace ? readWorkspaceMembers(projectPath, schemes) : undefined,
         });
       } catch (err) {
-        reject(new Error(`Failed to parse xcodebuild output: ${err}`));
+        reject(new PlasmaError('xcodebuild_failed', `Failed to parse xcodebuild output: ${err}`));
       }
     });
   });
@@ -121,9 +338,22 @@ async function findBuildProducts(buildDir: string): Promise<BuildProduct[]> {
 
   for (const entry of entries) {
     if (entry.name.endsWith('.app')) {
+      const productPath = path.join(buildDir, entry.name);
+      let info: AppBundleInfo | null = null;
+      try {
+        info = readAppBundleInfo(productPath);
+      } catch (err) {
+        console.warn(`[xcode] Could not read Info.plist of ${entry.name}:`, err);
+      }
+      const dsymPath = `${productPath}.dSYM`;
+      const executable = info?.executable ?? path.basename(entry.name, '.app');
+      const macExecutable = path.join(productPath, 'Contents', 'MacOS', executable);
       products.push({
         name: entry.name,
-        path: path.join(buildDir, entry.name),
+        path: productPath,
+        ...info,
+        dsymPath: fs.existsSync(dsymPath) ? dsymPath : undefined,
+        executablePath: fs.existsSync(macExecutable) ? macExecutable : path.join(productPath, executable),
       });
     }
   }
@@ -131,17 +361,84 @@ async function findBuildProducts(buildDir: string): Promise<BuildProduct[]> {
   return products;
 }
 
+const MAC_SIGNING_ARGS = ['CODE_SIGN_IDENTITY=-', 'CODE_SIGN_STYLE=Manual', 'DEVELOPMENT_TEAM=', 'PROVISIONING_PROFILE_SPECIFIER='];
+
+/**
+ * SDK, destination, and signing arguments for a platform. Mac apps are
+ * signed ad hoc since they can't run unsigned on Apple silicon.
+ */
+function platformArgs(platform: BuildPlatform): string[] {
+  switch (platform) {
+    case 'macos':
+      return ['-destination', 'generic/platform=macOS', ...MAC_SIGNING_ARGS];
+    case 'mac-catalyst':
+      return ['-destination', 'generic/platform=macOS,variant=Mac Catalyst', ...MAC_SIGNING_ARGS];
+    default:
+      return [
+        '-sdk',
+        'iphonesimulator',
+        '-destination',
+        'generic/platform=iOS Simulator',
+        'CODE_SIGN_IDENTITY=',
+        'CODE_SIGNING_REQUIRED=NO',
+        'CODE_SIGNING_ALLOWED=NO',
+      ];
+  }
+}
+
+function packageResolutionArgs(resolution: PackageResolution): string[] {
+  switch (resolution) {
+    case 'resolved-file':
+      return ['-onlyUsePackageVersionsFromResolvedFile'];
+    case 'disabled':
+      return ['-disableAutomaticPackageResolution'];
+    default:
+      return [];
+  }
+}
+
+/**
+ * Recognize Swift package resolution progress in xcodebuild output, like
+ *   Fetching from https://github.com/apple/swift-log.git
+ *   Checking out 1.5.3 of package swift-log
+ */
+export function parsePackageProgress(line: string): { phase: PackagePhase; package?: string } | null {
+  const trimmed = line.trim();
+  if (/^Resolve Package Graph$/.test(trimmed) || /^Computing version for /.test(trimmed)) {
+    return { phase: 'resolving', package: trimmed.match(/^Computing version for (.+)$/)?.[1] };
+  }
+  const fetching = trimmed.match(/^(?:Fetching|Updating|Cloning)(?: from)? (\S+)/);
+  if (fetching) {
+    return { phase: 'fetching', package: path.basename(fetching[1], '.git') };
+  }
+  const checkout =
+    trimmed.match(/^Checking out .+ of package (\S+)/) ??
+    trimmed.match(/^Creating working copy of package ['‘]?([^'’\s]+)/);
+  if (checkout) {
+    return { phase: 'checking-out', package: checkout[1] };
+  }
+  if (/^Resolved source packages:/.test(trimmed)) {
+    return { phase: 'resolved' };
+  }
+  return null;
+}
+
 /**
- * Get build settings to determine build directory
+ * Get build settings to determine build directory. Packages are resolved
+ * first, so their output is passed to onLine.
  */
 async function getBuildSettings(
   projectPath: string,
-  scheme: string
+  scheme: string,
+  configuration: string,
+  derivedDataPath?: string,
+  platform: BuildPlatform = 'ios-simulator',
+  options: { packageResolution?: PackageResolution; onLine?: (line: string) => void } = {}
 ): Promise<{ buildDir: string; isWorkspace: boolean }> {
   const project = detectProject(projectPath);
 
   if (!project || project.type !== 'xcode') {
-    throw new Error('Not an Xcode project');
+    throw new PlasmaError('not_an_xcode_project', 'Not an Xcode project');
   }
 
   const isWorkspace = project.path.endsWith('.xcworkspace');
@@ -152,35 +449,48 @@ async function getBuildSettings(
     '-scheme',
     scheme,
     '-configuration',
-    'Debug',
-    '-sdk',
-    'iphonesimulator',
-    '-destination',
-    'generic/platform=iOS Simulator',
-    'CODE_SIGN_IDENTITY=',
-    'CODE_SIGNING_REQUIRED=NO',
-    'CODE_SIGNING_ALLOWED=NO',
+    configuration,
+    ...platformArgs(platform),
+    ...packageResolutionArgs(options.packageResolution ?? 'automatic'),
     '-showBuildSettings',
   ];
 
+  if (derivedDataPath) {
+    args.push('-derivedDataPath', derivedDataPath);
+  }
+
   return new Promise((resolve, reject) => {
     const proc = spawn('xcodebuild', args);
     registerProcess(proc);
+    enforceTimeout(proc, 'xcodebuild', reject);
     let stdout = '';
 
+    // Package resolution progress goes to both streams; chunks can end mid-line
+    const pending = { stdout: '', stderr: '' };
+    const forwardLines = (stream: 'stdout' | 'stderr', data: Buffer) => {
+      const lines = (pending[stream] + data.toString()).split('\n');
+      pending[stream] = lines.pop() ?? '';
+      lines.forEach((line) => options.onLine?.(line));
+    };
+
     proc.stdout.on('data', (data) => {
       stdout += data.toString();
+      forwardLines('stdout', data);
+    });
+
+    proc.stderr.on('data', (data) => {
+      forwardLines('stderr', data);
     });
 
     proc.on('close', (code) => {
       if (code !== 0) {
-        reject(new Error('Failed to get build settings'));
+        reject(new PlasmaError('xcodebuild_failed', 'Failed to get build settings'));
         return;
       }
 
       const buildDir = extractBuildDirFromSettings(stdout);
       if (!buildDir) {
-        reject(new Error('Could not find build directory'));
+        reject(new PlasmaError('xcodebuild_failed', 'Could not find build directory'));
         return;
       }
 
@@ -190,13 +500,28 @@ async function getBuildSettings(
 }
 
 /**
- * Stream build output line by line for live updates
+ * Stream build output line by line for live updates. Aborting signal takes
+ * a queued build out of line, or stops xcodebuild once it runs.
  */
 export function buildSchemeStream(
   projectPath: string,
-  scheme: string
+  scheme: string,
+  configuration: string = 'Debug',
+  derivedDataPath?: string,
+  platform: BuildPlatform = 'ios-simulator',
+  packageResolution: PackageResolution = 'automatic',
+  arch?: BuildArch,
+  signal?: AbortSignal
 ): EventEmitter {
   const emitter = new EventEmitter();
+  // Other clients can follow the build once it starts
+  trackBuild(emitter);
+  const emitPackageProgress = (line: string) => {
+    const progress = parsePackageProgress(line);
+    if (progress) {
+      emitter.emit('event', { type: 'packages', ...progress, message: line.trim() } as BuildEvent);
+    }
+  };
 
   (async () => {
     try {
@@ -206,65 +531,266 @@ export function buildSchemeStream(
         emitter.emit('event', {
           type: 'error',
           message: 'Not an Xcode project',
+          code: 'not_an_xcode_project',
         } as BuildEvent);
         emitter.emit('end');
         return;
       }
 
-      const { buildDir, isWorkspace } = await getBuildSettings(projectPath, scheme);
+      // Wait for a free slot when as many builds as allowed are already running
+      const slot = await acquireBuildSlot((position) => {
+        emitter.emit('event', { type: 'queued', scheme, projectPath, position } as BuildEvent);
+      }, signal);
+      emitter.once('end', slot.release);
+
+      // Read while xcodebuild resolves the build settings
+      const environmentCapture = captureBuildEnvironment(project.path, platformArgs(platform));
+      const gitCapture = readGitInfo(project.path);
+      const settingsKey = JSON.stringify([project.path, scheme, configuration, platform, derivedDataPath ?? null]);
+      const fingerprint = discoveryFingerprint(project.path);
+      const knownSettings = buildSettingsCache.get(settingsKey);
+      const settings =
+        knownSettings && knownSettings.fingerprint === fingerprint
+          ? knownSettings
+          : await getBuildSettings(projectPath, scheme, configuration, derivedDataPath, platform, {
+              packageResolution,
+              onLine: emitPackageProgress,
+            });
+      const reusedSettings = settings === knownSettings;
+      buildSettingsCache.set(settingsKey, { fingerprint, buildDir: settings.buildDir, isWorkspace: settings.isWorkspace });
+      const { isWorkspace } = settings;
+      let { buildDir } = settings;
+      const [environment, git] = await Promise.all([environmentCapture, gitCapture]);
+
+      // Output goes to the build's log file rather than staying in memory
+      const buildId = crypto.randomUUID();
+      const log = new BuildLog(buildId);
+      emitter.on('event', (event: BuildEvent) => {
+        if (event.type === 'output' && event.line) {
+          log.append(event.line);
+        }
+      });
+      emitter.once('end', () => log.close());
 
       emitter.emit('event', {
         type: 'started',
         scheme,
+        configuration,
         projectPath,
+        platform,
+        arch,
+        buildId,
+        git,
+        buildDir,
       } as BuildEvent);
 
+      // Every build that got this far is stored with its diagnostics
+      const startedAt = new Date().toISOString();
+      const diagnostics = new DiagnosticCollector();
+      const saveRecord = (success: boolean, error?: string): string | undefined => {
+        try {
+          return recordBuild({
+            id: buildId,
+            projectPath,
+            scheme,
+            configuration,
+            success,
+            startedAt,
+            diagnostics: diagnostics.diagnostics,
+            error,
+            environment,
+            git,
+          });
+        } catch (err) {
+          console.warn('[xcode] Could not record build:', err);
+          return undefined;
+        }
+      };
+
+      // Plasma's own lines, like hook output, are shown as they are
+      const emitLine = (line: string) =>
+        emitter.emit('event', { type: 'output', line, formatted: { kind: 'info', text: line } } as BuildEvent);
+      const formatter = new BuildOutputFormatter();
+
+      // Products of a clean revision that was built before are restored
+      // instead, skipping xcodebuild and the build hooks
+      let cache: BuildCache | null = null;
+      let cacheKey: string | null = null;
+      try {
+        cache = BuildCache.forProject(projectPath);
+        cacheKey = cache
+          ? await getBuildCacheKey({ projectPath, scheme, configuration, platform, arch })
+          : null;
+        if (cache && !cacheKey) {
+          emitLine('Build cache skipped: the project is not a clean git checkout');
+        }
+        const source = cache && cacheKey ? await cache.restore(cacheKey, buildDir) : null;
+        if (source) {
+          emitLine(`Restored build products from the build cache (${source})`);
+          const products = await findBuildProducts(buildDir);
+          await recordBuildSymbols(projectPath, products).catch((err) => {
+            console.warn('[xcode] Could not record build symbols:', err);
+          });
+          emitter.emit('event', {
+            type: 'completed',
+            success: true,
+            buildDir,
+            products,
+            cached: true,
+            buildId: saveRecord(true),
+          } as BuildEvent);
+          emitter.emit('end');
+          return;
+        }
+      } catch (err) {
+        console.warn('[xcode] Build cache lookup failed:', err);
+        emitLine(`Build cache lookup failed: ${toPlasmaError(err).message}`);
+      }
+
+      const hooks = loadHooks(projectPath);
+      const hookContext = { projectPath, scheme, configuration };
+      await runHooks('preBuild', hookContext, emitLine, hooks);
+
       const args = [
         isWorkspace ? '-workspace' : '-project',
         project.path,
         '-scheme',
         scheme,
         '-configuration',
-        'Debug',
-        '-sdk',
-        'iphonesimulator',
-        '-destination',
-        'generic/platform=iOS Simulator',
-        'CODE_SIGN_IDENTITY=',
-        'CODE_SIGNING_REQUIRED=NO',
-        'CODE_SIGNING_ALLOWED=NO',
+        configuration,
+        ...platformArgs(platform),
+        ...packageResolutionArgs(packageResolution),
       ];
 
-      const proc = spawn('xcodebuild', args);
-      registerProcess(proc);
+      if (derivedDataPath) {
+        args.push('-derivedDataPath', derivedDataPath);
+      }
+      // Overrides the architectures the project builds for
+      if (arch) {
+        args.push('-arch', arch);
+      }
+      // Leave cores for the other builds allowed to run alongside this one
+      if (slot.jobs) {
+        args.push('-jobs', slot.jobs.toString());
+      }
 
-      proc.stdout.on('data', (data) => {
-        const lines = data.toString().split('\n');
-        for (const line of lines) {
-          if (line.trim()) {
-            emitter.emit('event', { type: 'output', line } as BuildEvent);
-          }
-        }
-      });
+      if (signal?.aborted) {
+        throw new PlasmaError('cancelled', 'The build was cancelled');
+      }
 
-      proc.stderr.on('data', (data) => {
-        const lines = data.toString().split('\n');
-        for (const line of lines) {
-          if (line.trim()) {
-            emitter.emit('event', { type: 'output', line } as BuildEvent);
-          }
-        }
+      // Own process group so the compiler and linker children go down with it
+      const proc = spawn('xcodebuild', args, { detached: true });
+      registerProcess(proc, { group: true });
+
+      let cancelled = false;
+      const cancel = () => {
+        cancelled = true;
+        signalProcess(proc, 'SIGTERM');
+      };
+      signal?.addEventListener('abort', cancel, { once: true });
+      emitter.once('end', () => signal?.removeEventListener('abort', cancel));
+
+      // The build ends, freeing its slot, once xcodebuild has exited
+      let timedOut = false;
+      enforceTimeout(proc, 'build', (error) => {
+        timedOut = true;
+        emitter.emit('event', {
+          type: 'error',
+          message: error.message,
+          code: error.code,
+          buildId: saveRecord(false, error.message),
+        } as BuildEvent);
       });
 
+      // Set when a library fails to link because it's built for another architecture
+      let suggestedArch: BuildArch | null = null;
+
+      const handleLine = (line: string) => {
+        diagnostics.add(line);
+        suggestedArch ??= detectArchMismatch(line);
+        emitPackageProgress(line);
+        emitter.emit('event', { type: 'output', line, formatted: formatter.format(line) } as BuildEvent);
+      };
+      // Chunks end mid-line, so lines are only handled once they're complete
+      for (const stream of [proc.stdout, proc.stderr]) {
+        const lines = new LineSplitter();
+        stream.on('data', (data: Buffer) => lines.push(data).forEach(handleLine));
+        stream.on('end', () => lines.end().forEach(handleLine));
+      }
+
       proc.on('close', async (code) => {
+        if (timedOut) {
+          emitter.emit('end');
+          return;
+        }
+        if (cancelled) {
+          emitter.emit('event', {
+            type: 'error',
+            message: 'The build was cancelled',
+            code: 'cancelled',
+            buildId: saveRecord(false, 'The build was cancelled'),
+          } as BuildEvent);
+          emitter.emit('end');
+          return;
+        }
         const success = code === 0;
-        const products = success ? await findBuildProducts(buildDir) : [];
+
+        if (success) {
+          try {
+            await runHooks('postBuild', hookContext, emitLine, hooks);
+          } catch (err) {
+            const message = err instanceof Error ? err.message : String(err);
+            emitter.emit('event', {
+              type: 'error',
+              message,
+              code: toPlasmaError(err).code,
+              buildId: saveRecord(false, message),
+            } as BuildEvent);
+            emitter.emit('end');
+            return;
+          }
+        }
+        let products = success ? await findBuildProducts(buildDir) : [];
+        if (success && products.length === 0 && reusedSettings) {
+          // The project may build somewhere else now, so ask xcodebuild again
+          buildSettingsCache.delete(settingsKey);
+          try {
+            ({ buildDir } = await getBuildSettings(projectPath, scheme, configuration, derivedDataPath, platform, {
+              packageResolution,
+            }));
+            buildSettingsCache.set(settingsKey, { fingerprint, buildDir, isWorkspace });
+            products = await findBuildProducts(buildDir);
+          } catch (err) {
+            console.warn('[xcode] Could not read the build settings again:', err);
+          }
+        }
+        if (success) {
+          // Keep the symbols of this exact build so later crashes can be symbolicated
+          await recordBuildSymbols(projectPath, products).catch((err) => {
+            console.warn('[xcode] Could not record build symbols:', err);
+          });
+          if (cache && cacheKey && products.length > 0) {
+            try {
+              await cache.store(cacheKey, buildDir, products.map((product) => product.name));
+              emitLine('Stored build products in the build cache');
+            } catch (err) {
+              console.warn('[xcode] Could not store build in the cache:', err);
+              emitLine(`Build cache upload failed: ${toPlasmaError(err).message}`);
+            }
+          }
+        }
+        const mismatch = !success && suggestedArch !== arch ? suggestedArch : null;
+        if (mismatch) {
+          emitLine(archMismatchHint(mismatch));
+        }
 
         emitter.emit('event', {
           type: 'completed',
           success,
           buildDir,
           products,
+          buildId: saveRecord(success),
+          suggestedArch: mismatch ?? undefined,
         } as BuildEvent);
 
         emitter.emit('end');
@@ -274,6 +800,8 @@ export function buildSchemeStream(
         emitter.emit('event', {
           type: 'error',
           message: err.message,
+          code: 'xcodebuild_failed',
+          buildId: saveRecord(false, err.message),
         } as BuildEvent);
         emitter.emit('end');
       });
@@ -281,6 +809,7 @@ export function buildSchemeStream(
       emitter.emit('event', {
         type: 'error',
         message: err instanceof Error ? err.message : String(err),
+        code: toPlasmaError(err).code,
       } as BuildEvent);
       emitter.emit('end');
     }
@@ -289,9 +818,185 @@ export function buildSchemeStream(
   return emitter;
 }
 
+/** Where SwiftPM and Xcode keep the package repositories and manifests they fetched */
+const PACKAGE_CACHE_DIRECTORIES = [path.join(os.homedir(), 'Library', 'Caches', 'org.swift.swiftpm')];
+
+function directorySize(dir: string): number {
+  let bytes = 0;
+  for (const entry of fs.readdirSync(dir, { withFileTypes: true })) {
+    const entryPath = path.join(dir, entry.name);
+    if (entry.isDirectory()) {
+      bytes += directorySize(entryPath);
+    } else if (entry.isFile()) {
+      bytes += fs.statSync(entryPath).size;
+    }
+  }
+  return bytes;
+}
+
+/**
+ * Remove the shared Swift package cache so the next resolution fetches every
+ * package again, for when a cached checkout is corrupt or stale
+ */
+export function purgePackageCache(): { removed: string[]; bytes: number } {
+  const removed: string[] = [];
+  let bytes = 0;
+  for (const dir of PACKAGE_CACHE_DIRECTORIES) {
+    if (!fs.existsSync(dir)) {
+      continue;
+    }
+    bytes += directorySize(dir);
+    fs.rmSync(dir, { recursive: true, force: true });
+    removed.push(dir);
+  }
+  return { removed, bytes };
+}
+
 /**
  * Get launchable products from a build directory
  */
 export async function getLaunchableProducts(buildDir: string): Promise<BuildProduct[]> {
   return findBuildProducts(buildDir);
 }
+
+export interface BuildResult {
+  success: boolean;
+  products: BuildProduct[];
+  buildDir?: string;
+  error?: string;
+  code?: ErrorCode;
+  buildId?: string;
+  cached?: boolean;
+  /** Architecture the build would link for, when it failed on a library built for another one */
+  suggestedArch?: BuildArch;
+}
+
+/**
+ * Build a scheme and resolve once it finishes, forwarding events as they happen
+ */
+export function buildScheme(
+  projectPath: string,
+  scheme: string,
+  configuration: string = 'Debug',
+  derivedDataPath?: string,
+  onEvent?: (event: BuildEvent) => void,
+  platform: BuildPlatform = 'ios-simulator',
+  packageResolution: PackageResolution = 'automatic',
+  arch?: BuildArch,
+  signal?: AbortSignal
+): Promise<BuildResult> {
+  return new Promise((resolve) => {
+    const emitter = buildSchemeStream(
+      projectPath,
+      scheme,
+      configuration,
+      derivedDataPath,
+      platform,
+      packageResolution,
+      arch,
+      signal
+    );
+
+    emitter.on('event', (event: BuildEvent) => {
+      onEvent?.(event);
+
+      if (event.type === 'completed') {
+        resolve({
+          success: event.success ?? false,
+          products: event.products || [],
+          buildDir: event.buildDir,
+          buildId: event.buildId,
+          cached: event.cached,
+          suggestedArch: event.suggestedArch,
+        });
+      } else if (event.type === 'error') {
+        resolve({
+          success: false,
+          products: [],
+          error: event.message || 'Build failed',
+          code: event.code ?? 'build_failed',
+          buildId: event.buildId,
+        });
+      }
+    });
+  });
+}
+
+export interface BatchBuild {
+  projectPath: string;
+  scheme: string;
+  configuration?: string;
+  platform?: BuildPlatform;
+  arch?: BuildArch;
+}
+
+export type BatchBuildEvent =
+  | { type: 'build'; index: number; projectPath: string; scheme: string; event: BuildEvent }
+  | {
+      type: 'completed';
+      success: boolean;
+      durationMs: number;
+      results: Array<{ index: number; projectPath: string; scheme: string; success: boolean; buildId?: string; error?: string }>;
+    };
+
+/**
+ * Check the builds of a batch sent by a client
+ */
+export function parseBatchBuilds(input: unknown): BatchBuild[] {
+  if (!Array.isArray(input) || input.length === 0) {
+    throw new PlasmaError('invalid_request', 'builds must be a non-empty list');
+  }
+  return input.map((value, index) => {
+    const build = value as Record<string, unknown>;
+    if (typeof build?.projectPath !== 'string' || typeof build.scheme !== 'string') {
+      throw new PlasmaError('invalid_request', `Build ${index + 1}: projectPath and scheme are required`);
+    }
+    if (build.platform !== undefined && !BUILD_PLATFORMS.includes(build.platform as BuildPlatform)) {
+      throw new PlasmaError('invalid_request', `Build ${index + 1}: platform must be one of ${BUILD_PLATFORMS.join(', ')}`);
+    }
+    if (build.arch !== undefined && !BUILD_ARCHS.includes(build.arch as BuildArch)) {
+      throw new PlasmaError('invalid_request', `Build ${index + 1}: arch must be one of ${BUILD_ARCHS.join(', ')}`);
+    }
+    return {
+      projectPath: build.projectPath,
+      scheme: build.scheme,
+      configuration: typeof build.configuration === 'string' ? build.configuration : undefined,
+      platform: build.platform as BuildPlatform | undefined,
+      arch: build.arch as BuildArch | undefined,
+    };
+  });
+}
+
+/**
+ * Build several projects at once, as many at a time as the build queue
+ * allows. Events of all builds are interleaved, each tagged with its build.
+ * Aborting signal cancels the builds still queued or running.
+ */
+export async function buildProjects(
+  builds: BatchBuild[],
+  onEvent: (event: BatchBuildEvent) => void,
+  derivedDataPath?: string,
+  signal?: AbortSignal
+): Promise<boolean> {
+  const startedAt = Date.now();
+  const results = await Promise.all(
+    builds.map(async ({ projectPath, scheme, configuration, platform, arch }, index) => {
+      const result = await buildScheme(
+        projectPath,
+        scheme,
+        configuration,
+        // Builds sharing a derived data folder would lock each other out
+        derivedDataPath ? path.join(derivedDataPath, `${path.basename(projectPath).replace(/\.\w+$/, '')}-${index}`) : undefined,
+        (event) => onEvent({ type: 'build', index, projectPath, scheme, event }),
+        platform,
+        undefined,
+        arch,
+        signal
+      );
+      return { index, projectPath, scheme, success: result.success, buildId: result.buildId, error: result.error };
+    })
+  );
+  const success = results.every((result) => result.success);
+  onEvent({ type: 'completed', success, durationMs: Date.now() - startedAt, results });
+  return success;
+}
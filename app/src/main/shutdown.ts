@@ -0,0 +1,44 @@
+/**
+ * Coordinated shutdown shared by the tray's Quit, Ctrl+C, and SIGTERM:
+ * stop the server and its streams, kill child processes and wait for them
+ * to exit, then flush the database. Runs once however many times it's
+ * requested.
+ */
+
+import { stopServer } from './server';
+import { closeDatabase } from './services/database';
+import { killAllProcesses } from './services/process-manager';
+
+// Give up on a clean stop after this long so quitting never hangs
+const SHUTDOWN_TIMEOUT_MS = 10000;
+
+let shutdownPromise: Promise<void> | null = null;
+
+export function shutdown(): Promise<void> {
+  if (!shutdownPromise) {
+    shutdownPromise = run();
+  }
+  return shutdownPromise;
+}
+
+async function run(): Promise<void> {
+  try {
+    await Promise.race([
+      stopServer(),
+      new Promise<void>((resolve) => {
+        setTimeout(() => {
+          console.warn('[shutdown] Server did not stop in time, exiting anyway');
+          resolve();
+        }, SHUTDOWN_TIMEOUT_MS).unref();
+      }),
+    ]);
+  } catch (err) {
+    console.error('[shutdown] Failed to stop the server:', err);
+  }
+  await killAllProcesses();
+  try {
+    closeDatabase();
+  } catch (err) {
+    console.error('[shutdown] Failed to close the database:', err);
+  }
+}
@@ -15,6 +15,10 @@ contextBridge.exposeInMainWorld('electron', {
   minimize: () => ipcRenderer.send('app-minimize'),
   maximize: () => ipcRenderer.send('app-maximize'),
 
+  // Appearance
+  setThemeSource: (source: 'system' | 'light' | 'dark') =>
+    ipcRenderer.send('set-theme-source', source),
+
   // Platform info
   platform: process.platform,
 });
@@ -28,6 +32,7 @@ declare global {
       quit: () => void;
       minimize: () => void;
       maximize: () => void;
+      setThemeSource: (source: 'system' | 'light' | 'dark') => void;
       platform: NodeJS.Platform;
     };
   }
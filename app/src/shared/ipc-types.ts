@@ -1,6 +1,34 @@
 // IPC Channel Type Definitions
 // Shared between main process and renderer (via preload)
 
+// ============================================================================
+// Errors
+// ============================================================================
+
+/** Machine-readable code of a failure, sent as { error, code } and on error events */
+export type ErrorCode =
+  | 'invalid_request'
+  | 'unauthorized'
+  | 'forbidden'
+  | 'not_found'
+  | 'project_not_found'
+  | 'not_an_xcode_project'
+  | 'xcodebuild_failed'
+  | 'build_failed'
+  | 'hook_failed'
+  | 'simulator_not_found'
+  | 'simctl_failed'
+  | 'session_not_found'
+  | 'stream_failed'
+  | 'input_failed'
+  | 'tool_missing'
+  | 'tool_failed'
+  | 'android_failed'
+  | 'upload_failed'
+  | 'timeout'
+  | 'cancelled'
+  | 'internal';
+
 // ============================================================================
 // Projects API
 // ============================================================================
@@ -41,45 +69,264 @@ export interface GetRecentProjectsRequest {
 
 export type GetRecentProjectsResponse = ProjectRecord[];
 
+/** A project with an Xcode side, an Android side, or both */
+export interface UnifiedProject {
+  id: number;
+  name: string;
+  xcode_path: string | null;
+  android_path: string | null;
+  last_opened_at: string | null;
+  created_at: string | null;
+}
+
+export interface CreateProjectRequest {
+  name: string;
+  xcodePath?: string;
+  androidPath?: string;
+  directory?: string;
+}
+
+export interface CreateProjectResponse {
+  project?: UnifiedProject;
+  error?: string;
+}
+
+export interface XcodeValidationResult {
+  valid: boolean;
+  path: string | null;
+  type: 'workspace' | 'project' | null;
+  error?: string;
+}
+
+export interface AndroidValidationResult {
+  valid: boolean;
+  path: string | null;
+  /** Modules, application ids, and variants of a valid project */
+  project?: AndroidProject;
+  error?: string;
+}
+
+/** Starting points for a new SwiftUI app */
+export type AppTemplate = 'hello-world' | 'list' | 'tabs';
+
+export interface AppTemplateInfo {
+  id: AppTemplate;
+  title: string;
+  description: string;
+}
+
+export interface NewAppRequest {
+  /** Existing folder the app is created in, as <directory>/<name> */
+  directory: string;
+  name: string;
+  /** hello-world by default */
+  template?: AppTemplate;
+}
+
+export interface NewAppResponse {
+  project: ProjectRecord;
+  projectPath: string;
+  xcodePath: string;
+  scheme: string;
+  template: AppTemplate;
+}
+
+export interface ProjectSettings {
+  simulatorUdid?: string;
+  scheme?: string;
+  configuration?: string;
+  packageResolution?: PackageResolution;
+  arch?: BuildArch;
+  preBuildHook?: string;
+  postBuildHook?: string;
+  postInstallHook?: string;
+  // Read-only defaults from the repository's plasma.toml
+  preferredDevice?: string;
+  manifestPath?: string;
+}
+
+export interface AppSettings {
+  streamFps?: string;
+  streamQuality?: string;
+  derivedDataPath?: string;
+  theme?: string;
+  defaultSimulatorUdid?: string;
+  buildTimeout?: string;
+  xcodebuildTimeout?: string;
+  simctlTimeout?: string;
+  toolTimeout?: string;
+  uploadTimeout?: string;
+  sessionIdleTimeout?: string;
+  /** Editor preset (xcode, vscode, cursor) or a command template with {file}, {line}, and {column} */
+  editorCommand?: string;
+  /** Builds that may run at once, 1 by default */
+  buildConcurrency?: string;
+  /** xcodebuild -jobs per build; the cores split between concurrent builds when unset */
+  buildJobs?: string;
+  /** 'true' to boot a project's simulator in the background when it's opened */
+  prewarmSimulator?: string;
+}
+
+/** A project open in a workspace, with the selections of its tab */
+export interface WorkspaceProject {
+  projectId: number;
+  name: string;
+  xcodePath: string | null;
+  androidPath: string | null;
+  platform: Platform;
+  scheme: string | null;
+  configuration: string | null;
+  /** Simulator UDID or Android serial */
+  deviceId: string | null;
+  /** Whether the device has a stream session running */
+  streaming: boolean;
+}
+
+/** Projects open side by side, like tabs */
+export interface Workspace {
+  id: string;
+  name: string;
+  projects: WorkspaceProject[];
+  activeProjectId: number | null;
+  createdAt: string;
+  updatedAt: string;
+}
+
+/** Selections of a project in a workspace; null clears one */
+export interface WorkspaceProjectSelection {
+  platform?: Platform;
+  scheme?: string | null;
+  configuration?: string | null;
+  deviceId?: string | null;
+}
+
 // ============================================================================
 // Xcode API
 // ============================================================================
 
 export type XcodeProjectType = 'project' | 'workspace';
 
+/** Committed in xcshareddata, only in the user's xcuserdata, or created by Xcode */
+export type SchemeSharing = 'shared' | 'user' | 'automatic';
+
 export interface XcodeProject {
   path: string;
   projectType: XcodeProjectType;
   schemes: string[];
+  schemeSharing: Record<string, SchemeSharing>;
   targets: string[];
   configurations: string[];
+  /** For workspaces, the projects in it */
+  projects?: WorkspaceMember[];
+}
+
+export interface WorkspaceMember {
+  name: string;
+  path: string;
+  targets: string[];
+  /** The workspace's schemes that build this project */
+  schemes: string[];
 }
 
 export interface BuildProduct {
   name: string;
   path: string;
+  bundleId?: string;
+  displayName?: string;
+  version?: string;
+  buildNumber?: string;
+  minimumOSVersion?: string;
+  executable?: string;
+  /** <name>.app.dSYM next to the product, when the build produced one */
+  dsymPath?: string;
+  /** Path of the executable inside the bundle, Contents/MacOS for Mac apps */
+  executablePath?: string;
 }
 
 export interface DiscoverProjectRequest {
   path: string;
+  /** Run xcodebuild -list even when the cached schemes are still current */
+  forceRefresh?: boolean;
 }
 
 export type DiscoverProjectResponse = XcodeProject;
 
+/** Where a scheme is built to run: a simulator, or this Mac natively or through Catalyst */
+export type BuildPlatform = 'ios-simulator' | 'macos' | 'mac-catalyst';
+
+/** Resolve packages as Xcode does, only from Package.resolved, or not at all */
+export type PackageResolution = 'automatic' | 'resolved-file' | 'disabled';
+
+/** CPU architecture of a build; x86_64 simulator apps run through Rosetta on Apple silicon */
+export type BuildArch = 'arm64' | 'x86_64';
+
 export interface BuildStreamRequest {
   path: string;
   scheme: string;
+  configuration?: string;
+  /** ios-simulator by default */
+  platform?: BuildPlatform;
+  /** automatic by default */
+  packageResolution?: PackageResolution;
+  /** The architectures the project builds for by default */
+  arch?: BuildArch;
+  /** Simulators to boot while the build runs, so the app can be installed as soon as it's built */
+  udids?: string[];
 }
 
 export interface BuildEvent {
-  type: 'started' | 'output' | 'completed' | 'error';
+  type: 'queued' | 'started' | 'packages' | 'output' | 'completed' | 'error';
   scheme?: string;
+  configuration?: string;
   projectPath?: string;
+  platform?: BuildPlatform;
+  /** Architecture passed to xcodebuild, on started events; unset builds the project's own */
+  arch?: BuildArch;
   line?: string;
   success?: boolean;
+  /** Where the products go, from started on */
   buildDir?: string;
   products?: BuildProduct[];
   message?: string;
+  code?: ErrorCode;
+  /** Id of the stored build and its log, from started on */
+  buildId?: string;
+  /** Whether the products were restored from the build cache instead of built */
+  cached?: boolean;
+  /** Architecture that would link, on completed events of builds that failed on a library built for another one */
+  suggestedArch?: BuildArch;
+  /** Package resolution progress, on packages events */
+  phase?: 'resolving' | 'fetching' | 'checking-out' | 'resolved';
+  package?: string;
+  /** Commit, branch, and dirty state of the project, on started events */
+  git?: GitInfo | null;
+  /** Place in line on queued events, 1 being next */
+  position?: number;
+  /** Condensed form of an output line, or null when the formatted log leaves it out */
+  formatted?: FormattedBuildLine | null;
+}
+
+export type BuildLineKind =
+  | 'compile'
+  | 'link'
+  | 'sign'
+  | 'step'
+  | 'info'
+  | 'error'
+  | 'warning'
+  | 'note'
+  | 'context'
+  | 'test-passed'
+  | 'test-failed'
+  | 'succeeded'
+  | 'failed';
+
+/** An xcodebuild output line condensed to one short line per step */
+export interface FormattedBuildLine {
+  kind: BuildLineKind;
+  text: string;
+  target?: string;
+  location?: SourceLocation;
 }
 
 export interface GetLaunchableProductsRequest {
@@ -101,20 +348,445 @@ export interface Simulator {
 
 export type ListSimulatorsResponse = Simulator[];
 
+export interface DeviceScreen {
+  /** Pixels */
+  width: number;
+  height: number;
+  scale: number;
+  /** Points, the pixels divided by the scale */
+  pointWidth: number;
+  pointHeight: number;
+}
+
+export interface SimulatorDeviceType {
+  identifier: string;
+  name: string;
+  /** iPhone, iPad, Apple Watch, Apple TV, or Apple Vision */
+  productFamily: string | null;
+  /** Hardware model, like iPhone16,1 */
+  modelIdentifier: string | null;
+  screen: DeviceScreen | null;
+  /** SoC platform of the hardware, like t8130 */
+  chipset: string | null;
+  architectures: string[];
+  minRuntimeVersion: string | null;
+  maxRuntimeVersion: string | null;
+  /** Identifiers of the installed runtimes that can run it */
+  runtimes: string[];
+}
+
+export interface SimulatorRuntime {
+  identifier: string;
+  name: string;
+  platform: string | null;
+  version: string;
+  buildVersion: string | null;
+  available: boolean;
+  /** Why the runtime can't be used, when it isn't available */
+  availabilityError: string | null;
+}
+
+export interface DeviceTypeCatalog {
+  deviceTypes: SimulatorDeviceType[];
+  runtimes: SimulatorRuntime[];
+}
+
+export type PrewarmState = 'booting' | 'ready' | 'failed';
+
+/** The simulator booted in the background for the open project */
+export interface PrewarmStatus {
+  enabled: boolean;
+  projectId: number | null;
+  udid: string | null;
+  state: PrewarmState | null;
+  startedAt: string | null;
+  readyAt: string | null;
+  error: string | null;
+}
+
 export interface LaunchAppRequest {
   udid: string;
   appPath: string;
   bundleId?: string;
+  // Runs the project's post-install hooks when set
+  projectPath?: string;
+}
+
+export interface LaunchAttempts {
+  boot: number;
+  install: number;
+  launch: number;
 }
 
 export interface LaunchAppResponse {
   message: string;
+  bundleId: string;
+  attempts: LaunchAttempts;
+  hookOutput: string[];
+}
+
+export type DeviceRunState = 'queued' | 'booting' | 'installing' | 'streaming' | 'failed';
+
+export interface DeviceRunStatus {
+  udid: string;
+  name: string;
+  state: DeviceRunState;
+  bundleId?: string;
+  /** simulator-server stream of the device, once streaming */
+  streamUrl?: string;
+  error?: string;
+  code?: string;
+}
+
+export type MultiDeviceEvent =
+  | { type: 'status'; device: DeviceRunStatus }
+  | { type: 'completed'; devices: DeviceRunStatus[]; succeeded: number; failed: number };
+
+export interface LaunchMultipleRequest {
+  appPath: string;
+  /** Up to 8 simulators, booted two at a time in this order */
+  udids: string[];
+  // Applies the project's launch options when set
+  projectPath?: string;
+  fps?: number;
+  quality?: number;
+}
+
+export type BuildProcessingState = 'PROCESSING' | 'FAILED' | 'INVALID' | 'VALID';
+
+export type TestFlightEvent =
+  | { type: 'uploading'; bundleId: string; version: string; buildNumber: string }
+  | { type: 'output'; line: string }
+  | { type: 'uploaded' }
+  | { type: 'processing'; state: BuildProcessingState | 'WAITING' }
+  | { type: 'completed'; bundleId: string; version: string; buildNumber: string; state: BuildProcessingState | null }
+  | { type: 'error'; message: string; code: string };
+
+export interface TestFlightUploadRequest {
+  /** Exported .ipa */
+  ipaPath: string;
+  /** Project whose plasma.toml has the [app_store_connect] key */
+  projectPath?: string;
+  waitForProcessing?: boolean;
+}
+
+export interface SigningIdentity {
+  /** SHA-1 of the certificate */
+  sha1: string;
+  name: string;
+  /** Like "Apple Development" or "Apple Distribution" */
+  kind: string;
+  teamId?: string;
+  expiresAt?: string;
+}
+
+export type ProfileType = 'development' | 'ad-hoc' | 'app-store' | 'enterprise';
+
+export interface ProvisioningProfile {
+  uuid: string;
+  name: string;
+  path: string;
+  type: ProfileType;
+  teamId?: string;
+  teamName?: string;
+  /** May end in * for wildcard App IDs */
+  bundleId?: string;
+  platforms: string[];
+  createdAt?: string;
+  expiresAt?: string;
+  expired: boolean;
+  devices: string[];
+  /** SHA-1 of the certificates that can sign with it, matching SigningIdentity.sha1 */
+  certificates: string[];
+  entitlements: Record<string, PlistJson>;
+}
+
+export type SizeCategory = 'binaries' | 'frameworks' | 'plugins' | 'assets' | 'localizations' | 'other';
+
+export interface SizeEntry {
+  name: string;
+  bytes: number;
+}
+
+export interface BundleSizeReport {
+  path: string;
+  bundleId: string;
+  totalBytes: number;
+  categories: Array<SizeEntry & { name: SizeCategory; files: number }>;
+  frameworks: SizeEntry[];
+  localizations: SizeEntry[];
+  largestFiles: Array<{ path: string; bytes: number; category: SizeCategory }>;
+  binary: {
+    path: string;
+    bytes: number;
+    segments: SizeEntry[];
+    /** Code and data per object file or library, when a link map was given */
+    contributors?: SizeEntry[];
+  } | null;
+}
+
+export type PlistJson = string | number | boolean | PlistJson[] | { [key: string]: PlistJson };
+
+export interface LinkedLibrary {
+  /** Install name, like @rpath/Foo.framework/Foo */
+  installName: string;
+  name: string;
+  /** Shipped in the app's Frameworks folder */
+  embedded: boolean;
+  /** Part of the OS */
+  system: boolean;
+}
+
+export interface ProductDetails {
+  path: string;
+  bundleId: string;
+  infoPlist: Record<string, PlistJson>;
+  entitlements: Record<string, PlistJson> | null;
+  /** Signed products carry entitlements in their signature, simulator builds in the executable */
+  entitlementsSource: 'signature' | 'simulator' | null;
+  linkedLibraries: LinkedLibrary[];
+}
+
+export type DefaultsValue = PlistJson;
+
+export interface UserDefaults {
+  bundleId: string;
+  /** Preferences plist in the app's data container */
+  path: string;
+  values: Record<string, DefaultsValue>;
+}
+
+export interface FixtureSummary {
+  name: string;
+  photos: number;
+  contacts: number;
+  vcards: number;
+  events: number;
+  /** Whether seeding erases the simulator first */
+  erase: boolean;
+}
+
+export interface SeedFixturesRequest {
+  udid: string;
+  projectPath: string;
+  name: string;
+}
+
+export interface SeedFixturesResponse {
+  name: string;
+  udid: string;
+  photos: number;
+  contacts: number;
+  vcards: number;
+  events: number;
+}
+
+export interface PairedDevice {
+  udid: string;
+  name: string;
+  state: string;
+}
+
+export interface SimulatorPair {
+  id: string;
+  watch: PairedDevice;
+  phone: PairedDevice;
+  /** Only one pair per phone is active, the one the phone talks to */
+  active: boolean;
+  connected: boolean;
+}
+
+export interface CreateSimulatorPairRequest {
+  watchUdid: string;
+  phoneUdid: string;
+}
+
+export interface InstallOnPairRequest {
+  pairId: string;
+  /** iOS app with the watch app embedded in its Watch folder */
+  appPath: string;
+  launchWatchApp?: boolean;
+  // Applies the project's launch options when set
+  projectPath?: string;
+}
+
+export interface InstallOnPairResponse {
+  message: string;
+  phoneBundleId: string;
+  watchBundleId: string;
+}
+
+export interface MacLaunchRequest {
+  appPath: string;
+  // Applies the project's launch options and runs its post-install hooks when set
+  projectPath?: string;
+}
+
+export interface MacLaunchResponse {
+  message: string;
+  bundleId: string;
+  pid: number;
+  /** File the app's stdout and stderr are written to */
+  logPath: string;
+  hookOutput: string[];
+}
+
+export interface AppMetricsSample {
+  timestamp: string;
+  cpuPercent: number;
+  rssKb: number;
+  threads: number;
+}
+
+export interface AppMetricsSummary {
+  id: string;
+  udid: string;
+  bundleId: string;
+  pid: number;
+  startedAt: string;
+  /** Null while the session is still sampling */
+  endedAt: string | null;
+  samples: number;
+  avgCpuPercent: number;
+  peakCpuPercent: number;
+  avgRssKb: number;
+  peakRssKb: number;
+  peakThreads: number;
+}
+
+export type AppMetricsEvent =
+  | { type: 'sample'; sessionId: string; udid: string; bundleId: string; sample: AppMetricsSample }
+  | { type: 'ended'; sessionId: string; udid: string; bundleId: string; summary: AppMetricsSummary };
+
+export interface CrashFrame {
+  /** Image (binary) the frame is in */
+  image: string;
+  address: string;
+  symbol?: string;
+  file?: string;
+  line?: number;
+}
+
+export interface CrashThread {
+  index: number;
+  name?: string;
+  queue?: string;
+  crashed: boolean;
+  frames: CrashFrame[];
+}
+
+export interface CrashReportSummary {
+  id: string;
+  udid: string | null;
+  bundleId: string | null;
+  processName: string;
+  appVersion: string | null;
+  crashedAt: string;
+  exception: string | null;
+  /** Build the crashed binary came from, when Plasma built it */
+  build: { uuid: string; projectPath: string | null; builtAt: string } | null;
+  symbolicated: boolean;
+}
+
+export interface CrashReport extends CrashReportSummary {
+  reportPath: string;
+  threads: CrashThread[];
+}
+
+export interface InstalledApp {
+  bundleId: string;
+  name: string;
+  applicationType: string;
+  version?: string;
+  buildNumber?: string;
+  path?: string;
+  dataContainer?: string;
+}
+
+export interface LaunchInstalledAppRequest {
+  udid: string;
+  bundleId: string;
+}
+
+export type NetworkPreset = 'none' | '3g' | 'lossy-wifi' | 'offline';
+
+export interface NetworkConditions {
+  /** One-way delay added to every chunk, in milliseconds */
+  latencyMs: number;
+  /** Bandwidth limits in kilobits per second, 0 for unlimited */
+  downloadKbps: number;
+  uploadKbps: number;
+  /** Fraction (0-1) of chunks delivered after a retransmission delay */
+  packetLoss: number;
+  offline: boolean;
+}
+
+export interface NetworkConditionState {
+  udid: string;
+  /** Null when custom conditions were set */
+  preset: NetworkPreset | null;
+  conditions: NetworkConditions;
+  /** Proxy apps must route through, or null when the simulator isn't conditioned */
+  proxyUrl: string | null;
+}
+
+export interface SetNetworkConditionsRequest {
+  udid: string;
+  preset?: NetworkPreset;
+  conditions?: Partial<NetworkConditions>;
+}
+
+/** Slow animations and debug overlays, applied through launch arguments and environment */
+export type DebugOption =
+  | 'slow-animations'
+  | 'color-blended-layers'
+  | 'color-copied-images'
+  | 'color-offscreen-rendered'
+  | 'runtime-issues';
+
+export interface DebugOptionInfo {
+  id: DebugOption;
+  title: string;
+  description: string;
+}
+
+export interface DebugOptionsState {
+  udid: string;
+  enabled: DebugOption[];
+  available: DebugOptionInfo[];
+}
+
+export interface SetDebugOptionsRequest {
+  udid: string;
+  options: DebugOption[];
+  /** Relaunch this app so the options take effect right away */
+  bundleId?: string;
+  projectPath?: string;
+}
+
+export interface StreamShare {
+  token: string;
+  udid: string;
+  createdAt: string;
+  expiresAt: string;
+  /** Viewers watching through the link right now */
+  viewers: number;
+  /** Page teammates on the LAN open to watch */
+  url: string;
+}
+
+export interface CreateStreamShareRequest {
+  udid: string;
+  /** Default 60, at most a day */
+  expiresInMinutes?: number;
 }
 
 export interface TouchEvent {
   udid: string;
   type: 'began' | 'moved' | 'ended';
   touches: Array<{ x: number; y: number }>;
+  /** The stream session's token */
+  token?: string;
 }
 
 export interface TapRequest {
@@ -136,23 +808,743 @@ export interface SwipeRequest {
   duration?: number;
 }
 
-export interface StartStreamRequest {
+export type HardwareButton = 'home' | 'lock' | 'sideButton';
+
+export interface RecordingResponse {
+  path: string;
+}
+
+export interface AccessibilityFrame {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+export interface AccessibilityElement {
+  type: string;
+  role?: string;
+  label?: string;
+  identifier?: string;
+  value?: string;
+  title?: string;
+  hint?: string;
+  frame: AccessibilityFrame;
+  enabled: boolean;
+  traits: string[];
+  customActions: string[];
+  children: AccessibilityElement[];
+}
+
+export interface AccessibilityTreeResponse {
+  elements: AccessibilityElement[];
+}
+
+export interface TapElementRequest {
   udid: string;
+  identifier?: string;
+  label?: string;
+  index?: number;
+}
+
+export interface TapElementResponse {
+  element: AccessibilityElement;
+  x: number;
+  y: number;
+  normalized: { x: number; y: number };
+}
+
+export interface RecordedElement {
+  type: string;
+  identifier?: string;
+  label?: string;
+}
+
+export type RecordedStep = (
+  | { action: 'tap'; point: { x: number; y: number }; element?: RecordedElement }
+  | { action: 'swipe'; from: { x: number; y: number }; to: { x: number; y: number }; durationMs: number }
+  | { action: 'button'; button: HardwareButton }
+) & { delayMs: number };
+
+export interface StopInteractionRecordingResponse {
+  steps: RecordedStep[];
+  /** Swift source for an XCUITest method replaying the steps */
+  xcuitest: string;
+  script: AutomationScript;
+}
+
+export interface AndroidModule {
+  name: string; // Gradle path, e.g. ":app"
+  path: string;
+  isApplication: boolean;
+  applicationId: string | null;
+  buildTypes: string[];
+  productFlavors: string[];
+  variants: string[];
+}
+
+export interface AndroidProject {
+  path: string;
+  name: string;
+  settingsFile: string | null;
+  modules: AndroidModule[];
+}
+
+export interface AndroidDevice {
+  serial: string;
+  state: string;
+  model: string | null;
+  isEmulator: boolean;
+}
+
+export interface AndroidLaunchRequest {
+  serial: string;
+  apkPath: string;
+  applicationId?: string;
+}
+
+export interface StartStreamRequest {
+  udid: string; // simulator UDID, or adb serial when platform is 'android'
+  platform?: Platform;
   fps?: number;
   quality?: number;
+  /** Token from POST /api/simulator/session; the stream is refused without it */
+  token?: string;
+}
+
+export interface StreamSession {
+  udid: string;
+  platform: Platform;
+  /** Required to stream the session or send it touches */
+  token: string;
 }
 
 export interface StreamFrame {
   udid: string;
   frame: string; // base64 encoded JPEG
+  timestamp?: number; // ms since epoch when the server received the frame
+  mimeType?: string; // set when the frame isn't a JPEG (e.g. PNG from Android screencap)
+}
+
+export interface StreamStats {
+  udid: string;
+  backend: string;
+  fps: number;
+  droppedFrames: number;
+  bytesPerSecond: number;
+  captureReport: Record<string, unknown> | null;
 }
 
 export interface StreamLogEvent {
+  udid: string;
   type: 'info' | 'error' | 'debug' | 'frame';
   message?: string;
   frameNumber?: number;
 }
 
+// ============================================================================
+// Automation API
+// ============================================================================
+
+export interface AutomationPoint {
+  x: number;
+  y: number;
+}
+
+export type AutomationStep =
+  | { launch: string; arguments?: string[] }
+  | { wait: number }
+  | { wait_for: string; timeout?: number }
+  | { tap: string | AutomationPoint }
+  | { swipe: { from: AutomationPoint; to: AutomationPoint; duration?: number } }
+  | { type: string }
+  | { button: 'home' | 'lock' | 'side-button' }
+  | { simulate: 'shake' | 'memory-warning' }
+  | { assert: string }
+  | { screenshot: string }
+  | { assert_screenshot: string; threshold?: number; tolerance?: number; masks?: MaskRegion[] };
+
+export interface AutomationScript {
+  name?: string;
+  /** Project baselines for assert_screenshot are stored under */
+  project?: string;
+  steps: AutomationStep[];
+}
+
+export type AutomationEvent =
+  | { type: 'started'; name?: string; steps: number; outputDir: string }
+  | { type: 'step'; index: number; step: AutomationStep; status: 'running' }
+  | {
+      type: 'step';
+      index: number;
+      step: AutomationStep;
+      status: 'passed' | 'failed';
+      durationMs: number;
+      message?: string;
+      screenshotPath?: string;
+    }
+  | { type: 'completed'; success: boolean; durationMs: number; failedStep?: number; error?: string; code?: string };
+
+export interface ScreenshotMatrix {
+  name?: string;
+  bundleId: string;
+  /** Simulator names or UDIDs */
+  devices: string[];
+  /** BCP 47 language tags, e.g. en-US or ja */
+  locales: string[];
+  /** Defaults to a timestamped directory under the data directory */
+  outputDir?: string;
+  steps: AutomationStep[];
+}
+
+export type ScreenshotMatrixEvent =
+  | { type: 'started'; name?: string; runs: number; outputDir: string }
+  | { type: 'run'; device: string; udid: string; locale: string; status: 'running'; outputDir: string }
+  | { type: 'run'; device: string; udid: string; locale: string; status: 'passed' | 'failed'; error?: string }
+  | { type: 'step'; device: string; locale: string; event: AutomationEvent }
+  | { type: 'completed'; success: boolean; durationMs: number; passed: number; failed: number };
+
+// ============================================================================
+// Agent API
+// ============================================================================
+
+export type AgentAction =
+  | { action: 'screenshot' }
+  | { action: 'describe' }
+  | { action: 'tap'; identifier?: string; label?: string; index?: number }
+  | { action: 'tap'; x: number; y: number }
+  | { action: 'type'; text: string }
+  | { action: 'wait_for'; identifier?: string; label?: string; index?: number; timeout?: number }
+  | { action: 'assert_visible'; identifier?: string; label?: string; index?: number }
+  | { action: 'read_logs'; process?: string; seconds?: number; limit?: number };
+
+export type AgentScreenshots = 'none' | 'final' | 'each';
+
+export interface AgentActRequest {
+  udid: string;
+  actions: AgentAction[];
+  /** Default true */
+  stopOnError?: boolean;
+  /** Default final */
+  screenshots?: AgentScreenshots;
+}
+
+export interface DeviceLogEntry {
+  timestamp: string;
+  process: string;
+  level: string;
+  subsystem?: string;
+  category?: string;
+  message: string;
+}
+
+export interface LogFilterProfile {
+  name: string;
+  /** Only entries from the app: its subsystem (the bundle ID and below) or its process */
+  appOnly: boolean;
+  /** Leave out entries of processes that don't run from an installed app */
+  hideSystem: boolean;
+  /** Keep only entries whose message matches one of these regular expressions */
+  include: string[];
+  /** Drop entries whose message matches one of these regular expressions */
+  exclude: string[];
+  /** Comes with Plasma and can't be changed or removed */
+  builtIn: boolean;
+}
+
+export interface AgentActionResult {
+  index: number;
+  action: AgentAction['action'];
+  status: 'passed' | 'failed' | 'skipped';
+  durationMs: number;
+  element?: AccessibilityElement;
+  elements?: AccessibilityElement[];
+  logs?: DeviceLogEntry[];
+  /** Base64 PNG */
+  screenshot?: { mimeType: 'image/png'; data: string };
+  error?: string;
+  code?: string;
+}
+
+export interface AgentActResponse {
+  success: boolean;
+  durationMs: number;
+  results: AgentActionResult[];
+}
+
+// ============================================================================
+// Visual Regression API
+// ============================================================================
+
+/** Region to ignore, as fractions (0-1) of the screenshot size */
+export interface MaskRegion {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+export interface VisualCheckRequest {
+  udid: string;
+  /** Screen name the baseline is stored under */
+  name: string;
+  projectPath?: string;
+  /** Per-pixel color distance (0-1) above which a pixel counts as different */
+  threshold?: number;
+  /** Fraction of differing pixels (0-1) still considered a pass */
+  tolerance?: number;
+  masks?: MaskRegion[];
+  /** Replace the baseline with the current screen instead of comparing */
+  updateBaseline?: boolean;
+}
+
+export interface VisualCheckResult {
+  status: 'passed' | 'failed' | 'baseline_created' | 'baseline_updated';
+  passed: boolean;
+  diffPixels: number;
+  diffRatio: number;
+  message?: string;
+  baselinePath: string;
+  actualPath?: string;
+  diffPath?: string;
+  /** Base64 PNG of the diff image, when a comparison was made */
+  diffImage?: string;
+}
+
+// ============================================================================
+// Testing API
+// ============================================================================
+
+export interface BuildDiagnostic {
+  severity: 'error' | 'warning';
+  message: string;
+  file?: string;
+  line?: number;
+  column?: number;
+}
+
+export interface WarningTrendPoint {
+  buildId: string;
+  scheme: string;
+  configuration: string;
+  startedAt: string;
+  warnings: number;
+}
+
+/** Warnings of one file or one rule across stored builds */
+export interface WarningGroup {
+  /** File path, or the rule */
+  name: string;
+  occurrences: number;
+  /** Builds that reported at least one */
+  builds: number;
+  lastSeenAt: string;
+  /** Line of the latest warning, for files */
+  line?: number;
+  /** Latest message, for rules */
+  message?: string;
+}
+
+export interface WarningsReport {
+  builds: number;
+  /** Oldest first */
+  trend: WarningTrendPoint[];
+  byFile: WarningGroup[];
+  byRule: WarningGroup[];
+}
+
+export interface ResolvedPackage {
+  identity: string;
+  location: string;
+  version?: string;
+  branch?: string;
+  revision: string;
+}
+
+/** Git state of a project when it was built */
+export interface GitInfo {
+  commit: string;
+  /** null on a detached HEAD */
+  branch: string | null;
+  dirty: boolean;
+}
+
+/** Machine and toolchain a build ran with */
+export interface BuildEnvironment {
+  xcodeVersion: string | null;
+  xcodeBuildVersion: string | null;
+  developerDir: string | null;
+  macosVersion: string | null;
+  macosBuildVersion: string | null;
+  sdks: Record<string, string>;
+  tools: Record<string, string>;
+  /** Environment variables that affect builds */
+  variables: Record<string, string>;
+  /** Build settings passed on the command line */
+  buildSettings: Record<string, string>;
+  packages: ResolvedPackage[];
+}
+
+export interface EnvironmentChange {
+  /** Like xcodeVersion, sdks.iphonesimulator18.2, or packages.alamofire */
+  key: string;
+  before: string | null;
+  after: string | null;
+}
+
+export interface BuildEnvironmentDiff {
+  from: string;
+  to: string;
+  changes: EnvironmentChange[];
+}
+
+export interface BuildLogTail {
+  lines: string[];
+  /** Whether the build finished writing its log */
+  complete: boolean;
+}
+
+export interface BuildSummary {
+  id: string;
+  projectPath: string;
+  scheme: string;
+  configuration: string;
+  success: boolean;
+  startedAt: string;
+  finishedAt: string;
+  errors: number;
+  warnings: number;
+  diagnostics: BuildDiagnostic[];
+  /** Why the build failed when it wasn't a compiler error (hook, timeout) */
+  error: string | null;
+  /** null for builds stored before environments were recorded */
+  environment: BuildEnvironment | null;
+  /** null outside a git repository and for builds stored before it was recorded */
+  git: GitInfo | null;
+}
+
+export interface BuildQueueStatus {
+  concurrency: number;
+  running: number;
+  queued: number;
+  /** The -jobs each build is limited to, or null when builds use every core */
+  jobsPerBuild: number | null;
+}
+
+export interface BatchBuild {
+  projectPath: string;
+  scheme: string;
+  configuration?: string;
+  platform?: BuildPlatform;
+}
+
+export interface BatchBuildResult {
+  index: number;
+  projectPath: string;
+  scheme: string;
+  success: boolean;
+  buildId?: string;
+  error?: string;
+}
+
+/** Events of a batch's builds interleaved, each tagged with the index of its build */
+export type BatchBuildEvent =
+  | { type: 'build'; index: number; projectPath: string; scheme: string; event: BuildEvent }
+  | { type: 'completed'; success: boolean; durationMs: number; results: BatchBuildResult[] };
+
+/** junit: JUnit XML; github: GitHub Actions annotations */
+export type ExportFormat = 'junit' | 'github';
+
+export type TestStatus = 'passed' | 'failed' | 'skipped';
+
+export type TestRunStatus = 'running' | 'passed' | 'failed' | 'error' | 'cancelled';
+
+export interface TestFailure {
+  message: string;
+  file?: string;
+  line?: number;
+}
+
+export interface TestCaseResult {
+  /** Target/Class/method as accepted by -only-testing, or the bare test name when unknown */
+  identifier: string;
+  suite: string | null;
+  name: string;
+  status: TestStatus;
+  durationMs: number;
+  failures: TestFailure[];
+  /** Times the test ran, more than 1 when it was retried */
+  attempts: number;
+  /** Failed at first and passed on a retry */
+  flaky: boolean;
+}
+
+export interface TestRun {
+  id: string;
+  projectPath: string;
+  scheme: string;
+  configuration: string;
+  udid: string;
+  status: TestRunStatus;
+  coverageEnabled: boolean;
+  resultBundlePath: string | null;
+  startedAt: string;
+  finishedAt: string | null;
+  passed: number;
+  failed: number;
+  skipped: number;
+  /** Passed tests that needed a retry */
+  flaky: number;
+  /** Overall line coverage (0-1) when coverage was collected */
+  lineCoverage: number | null;
+  /** Number of simulators the tests were split across */
+  shards: number;
+  tests: TestCaseResult[];
+  error: string | null;
+}
+
+export interface TestRunRequest {
+  projectPath: string;
+  scheme: string;
+  udid: string;
+  configuration?: string;
+  coverage?: boolean;
+  onlyTesting?: string[];
+  /** Split the tests across this many clones of the simulator (up to 8) */
+  shards?: number;
+  /** Defaults to the [tests.retry] table of the project's plasma.toml */
+  retry?: Partial<TestRetryPolicy>;
+}
+
+export interface TestRetryPolicy {
+  /** Times a failing test is run in total, 1 for no retries */
+  maxAttempts: number;
+  /** Only retry failures whose message matches one of these regular expressions */
+  retryOn: string[];
+}
+
+export type TestShardStatus = 'running' | 'passed' | 'failed' | 'error';
+
+export type TestRunEvent =
+  | { type: 'started'; run: TestRun }
+  | { type: 'output'; line: string; shard?: number }
+  | { type: 'shard'; index: number; udid: string; tests: number; status: TestShardStatus }
+  | { type: 'test'; test: TestCaseResult; shard?: number }
+  | { type: 'retry'; attempt: number; tests: string[] }
+  | { type: 'completed'; run: TestRun };
+
+export interface FlakyTestSummary {
+  identifier: string;
+  /** Stored runs the test was flaky in */
+  flakyRuns: number;
+  /** Stored runs the test ran in */
+  totalRuns: number;
+  lastFlakyAt: string;
+}
+
+export interface TestAttachment {
+  /** Name of the exported file, which the attachment is fetched by */
+  fileName: string;
+  /** Name the test gave it, like "Screenshot_1_0D2E….png" */
+  name: string;
+  contentType: string;
+  bytes: number;
+  timestamp: string | null;
+  deviceName: string | null;
+}
+
+export interface FailedTestAttachments {
+  identifier: string;
+  attachments: TestAttachment[];
+}
+
+export interface TestRunAttachments {
+  testRunId: string;
+  tests: FailedTestAttachments[];
+}
+
+export interface CoverageStats {
+  coveredLines: number;
+  executableLines: number;
+  /** Fraction (0-1) of executable lines that ran */
+  lineCoverage: number;
+}
+
+export interface FileCoverage extends CoverageStats {
+  name: string;
+  path: string;
+}
+
+export interface TargetCoverage extends CoverageStats {
+  name: string;
+  files: FileCoverage[];
+}
+
+export interface CoverageReport extends CoverageStats {
+  testRunId: string;
+  targets: TargetCoverage[];
+}
+
+export interface FileLineCoverage {
+  testRunId: string;
+  path: string;
+  /** executionCount is null for lines that aren't executable */
+  lines: Array<{ line: number; executionCount: number | null }>;
+}
+
+// ============================================================================
+// Watch and Build Triggers API
+// ============================================================================
+
+export type WatchState = 'watching' | 'building' | 'launching' | 'error' | 'stopped';
+
+export interface WatchStatus {
+  projectPath: string;
+  state: WatchState;
+  message?: string;
+  changedFiles?: string[];
+  timestamp: string;
+}
+
+export interface StartWatchRequest {
+  path: string;
+  scheme: string;
+  configuration?: string;
+  udids: string[];
+}
+
+export interface BuildTrigger {
+  id: string;
+  projectPath: string;
+  scheme: string;
+  configuration: string;
+  /** Cron expression (minute hour day-of-month month day-of-week) */
+  schedule: string | null;
+  /** Build on pushes matching repository and branch; null matches any */
+  push: { repository: string | null; branch: string | null } | null;
+  lastRunAt: string | null;
+  lastBuildId: string | null;
+  lastSuccess: boolean | null;
+  createdAt: string;
+}
+
+export interface CreateBuildTriggerRequest {
+  projectPath: string;
+  scheme: string;
+  configuration?: string;
+  schedule?: string;
+  push?: { repository?: string; branch?: string };
+}
+
+export interface TriggerBuildEvent {
+  triggerId: string;
+  projectPath: string;
+  scheme: string;
+  reason: 'schedule' | 'push' | 'manual';
+  state: 'building' | 'succeeded' | 'failed' | 'skipped';
+  buildId?: string;
+  message?: string;
+  timestamp: string;
+}
+
+// ============================================================================
+// Logs API
+// ============================================================================
+
+export interface AppLogEntry {
+  timestamp: string;
+  level: 'debug' | 'info' | 'warn' | 'error';
+  scope?: string;
+  message: string;
+}
+
+// ============================================================================
+// Maintenance API
+// ============================================================================
+
+export type ArtifactCategory =
+  | 'recordings'
+  | 'screenshots'
+  | 'automation'
+  | 'test-results'
+  | 'build-cache'
+  | 'baselines'
+  | 'mac-apps'
+  | 'build-logs'
+  | 'logs';
+
+export interface DerivedDataUsage {
+  /** Folder name, like MyApp-abcdefghijklmnop */
+  name: string;
+  path: string;
+  bytes: number;
+  /** Project or workspace it was built from, when Xcode recorded it */
+  projectPath: string | null;
+  lastModifiedAt: string;
+}
+
+export interface SimulatorUsage {
+  udid: string;
+  name: string;
+  runtime: string | null;
+  path: string;
+  bytes: number;
+  /** false when its runtime is no longer installed */
+  available: boolean;
+}
+
+export interface ArtifactUsage {
+  category: ArtifactCategory;
+  path: string;
+  bytes: number;
+}
+
+export interface DiskUsageReport {
+  totalBytes: number;
+  derivedData: DerivedDataUsage[];
+  simulators: SimulatorUsage[];
+  artifacts: ArtifactUsage[];
+}
+
+export type CleanupRequest =
+  | { target: 'derived-data'; name: string }
+  | { target: 'simulator'; udid: string }
+  | { target: 'unavailable-simulators' }
+  | { target: 'artifacts'; category: ArtifactCategory };
+
+export type EnvironmentCheckId =
+  | 'command-line-tools'
+  | 'xcode-license'
+  | 'simulator-runtime'
+  | 'simulator-server'
+  | 'axe'
+  | 'fbsimctl'
+  | 'idb';
+
+export interface EnvironmentCheck {
+  id: EnvironmentCheckId;
+  title: string;
+  ok: boolean;
+  required: boolean;
+  detail?: string;
+  fixLabel?: string;
+  /** Version of the helper tool found */
+  version?: string;
+  /** Command that installs what's missing */
+  installCommand?: string;
+}
+
+// ============================================================================
+// Editor API
+// ============================================================================
+
+/** A place in a source file, opened in the user's editor */
+export interface SourceLocation {
+  file: string;
+  line: number;
+  column?: number;
+}
+
 // ============================================================================
 // IPC Channel Names
 // ============================================================================
@@ -178,6 +1570,8 @@ export const IpcChannels = {
   SIMULATOR_STREAM_STOP: 'simulator:stream:stop',
   SIMULATOR_STREAM_FRAME: 'simulator:stream:frame', // Main -> Renderer event
   SIMULATOR_LOG: 'simulator:log', // Main -> Renderer event
+  SIMULATOR_LOGS_SUBSCRIBE: 'simulator:logs:subscribe',
+  SIMULATOR_LOGS_UNSUBSCRIBE: 'simulator:logs:unsubscribe',
 } as const;
 
 export type IpcChannel = (typeof IpcChannels)[keyof typeof IpcChannels];
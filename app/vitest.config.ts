@@ -0,0 +1,10 @@
+import { defineConfig } from 'vitest/config';
+
+// Tests for the main process and server, next to the modules they cover.
+// They run in Node; the frontend has its own config with jsdom.
+export default defineConfig({
+  test: {
+    environment: 'node',
+    include: ['src/main/**/*.test.ts'],
+  },
+});